@@ -0,0 +1,334 @@
+// src/accessibility.rs
+use crate::input::actions::{ActionMap, Binding};
+use crate::renderer::Renderer;
+use crate::resource_registry::ResourceRegistry;
+use crate::screen_effect::{EffectId, ScreenEffectChain};
+use glam::{Mat3, Vec4};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+pub const COLORBLIND_FILTER_WGSL: &str = include_str!("colorblind.wgsl");
+pub const COLORBLIND_EFFECT: &str = "colorblind";
+// Runs after the usual effects so it sees the finished image
+pub const COLORBLIND_EFFECT_ORDER: i32 = 1000;
+
+const MIN_UI_SCALE: f32 = 0.5;
+const MAX_UI_SCALE: f32 = 3.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ColorVision {
+    #[default]
+    Normal,
+    // Red-blind
+    Protanopia,
+    // Green-blind, the most common
+    Deuteranopia,
+    // Blue-blind
+    Tritanopia,
+    // No colour at all
+    Achromatopsia,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ColorFilterMode {
+    // Shifts the colours a viewer can't tell apart into ones they can (daltonization)
+    #[default]
+    Correct,
+    // Shows what the viewer sees, for checking that a palette still reads
+    Simulate,
+}
+
+fn from_rows(rows: [[f32; 3]; 3]) -> Mat3 {
+    Mat3::from_cols_array_2d(&rows).transpose()
+}
+
+impl ColorVision {
+    // Linear RGB as this viewer sees it (Machado et al. 2009, full severity)
+    pub fn simulation(&self) -> Mat3 {
+        match self {
+            ColorVision::Normal => Mat3::IDENTITY,
+            ColorVision::Protanopia => from_rows([
+                [0.152286, 1.052583, -0.204868],
+                [0.114503, 0.786281, 0.099216],
+                [-0.003882, -0.048116, 1.051998],
+            ]),
+            ColorVision::Deuteranopia => from_rows([
+                [0.367322, 0.860646, -0.227968],
+                [0.280085, 0.672501, 0.047413],
+                [-0.011820, 0.042940, 0.968881],
+            ]),
+            ColorVision::Tritanopia => from_rows([
+                [1.255528, -0.076749, -0.178779],
+                [-0.078411, 0.930809, 0.147602],
+                [0.004733, 0.691367, 0.303900],
+            ]),
+            ColorVision::Achromatopsia => from_rows([[0.2126, 0.7152, 0.0722]; 3]),
+        }
+    }
+
+    // Adds what the viewer misses back into channels they can see: red-green loss goes to
+    // green and blue, blue loss to red and green. There is nothing to shift into without
+    // any colour vision, so achromatopsia is left as is.
+    pub fn correction(&self) -> Mat3 {
+        let shift = match self {
+            ColorVision::Normal | ColorVision::Achromatopsia => return Mat3::IDENTITY,
+            ColorVision::Protanopia | ColorVision::Deuteranopia => from_rows([[0.0, 0.0, 0.0], [0.7, 1.0, 0.0], [0.7, 0.0, 1.0]]),
+            ColorVision::Tritanopia => from_rows([[1.0, 0.0, 0.7], [0.0, 1.0, 0.7], [0.0, 0.0, 0.0]]),
+        };
+        // color + shift * (color - simulated), as one matrix
+        Mat3::IDENTITY + shift * (Mat3::IDENTITY - self.simulation())
+    }
+
+    pub fn filter(&self, mode: ColorFilterMode) -> Mat3 {
+        match mode {
+            ColorFilterMode::Correct => self.correction(),
+            ColorFilterMode::Simulate => self.simulation(),
+        }
+    }
+}
+
+// A player's accessibility preferences, saved with their other settings as RON. Every
+// field is optional in the file:
+//
+//     (ui_scale: 1.5, color_vision: Deuteranopia, toggle_actions: ["sprint"])
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AccessibilitySettings {
+    // On top of the OS scale factor, see `Renderer::ui_scale`
+    pub ui_scale: f32,
+    pub color_vision: ColorVision,
+    pub color_filter_mode: ColorFilterMode,
+    // 0..1, how much of the filter to apply
+    pub color_filter_strength: f32,
+    // The player's bindings, replacing the game's for these actions
+    pub remapped_controls: BTreeMap<String, Vec<Binding>>,
+    // Actions the player turned from hold into toggle
+    pub toggle_actions: BTreeSet<String>,
+    // Queue `AccessibilityEvent`s for a screen reader or text-to-speech
+    pub screen_reader: bool,
+}
+
+impl Default for AccessibilitySettings {
+    fn default() -> Self {
+        Self {
+            ui_scale: 1.0,
+            color_vision: ColorVision::Normal,
+            color_filter_mode: ColorFilterMode::Correct,
+            color_filter_strength: 1.0,
+            remapped_controls: BTreeMap::new(),
+            toggle_actions: BTreeSet::new(),
+            screen_reader: false,
+        }
+    }
+}
+
+impl AccessibilitySettings {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn from_ron(source: &str) -> Result<Self, String> {
+        ron::from_str(source).map_err(|e| format!("Failed to parse accessibility settings: {}", e))
+    }
+
+    pub fn load(path: &str) -> Result<Self, String> {
+        let source = std::fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+        Self::from_ron(&source)
+    }
+
+    pub fn to_ron(&self) -> Result<String, String> {
+        ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|e| format!("Failed to write accessibility settings: {}", e))
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), String> {
+        std::fs::write(path, self.to_ron()?).map_err(|e| format!("Failed to write {}: {}", path, e))
+    }
+
+    // Records a remap from a controls menu; apply it with `apply_controls`
+    pub fn remap(&mut self, action: &str, bindings: Vec<Binding>) {
+        self.remapped_controls.insert(action.to_string(), bindings);
+    }
+
+    // Back to the game's own bindings for `action`
+    pub fn reset_controls(&mut self, action: &str) {
+        self.remapped_controls.remove(action);
+    }
+
+    pub fn set_toggle(&mut self, action: &str, toggle: bool) {
+        if toggle {
+            self.toggle_actions.insert(action.to_string());
+        } else {
+            self.toggle_actions.remove(action);
+        }
+    }
+
+    // The game's `defaults` with the player's remaps and toggles on top. Starts from the
+    // defaults each time so undoing a preference restores the game's choice.
+    pub fn apply_controls(&self, defaults: &ActionMap) -> ActionMap {
+        let mut map = defaults.clone();
+        for (action, bindings) in &self.remapped_controls {
+            map.rebind(action, bindings.clone());
+        }
+        map.toggles.extend(self.toggle_actions.iter().cloned());
+        map
+    }
+
+    pub fn clamped_ui_scale(&self) -> f32 {
+        if self.ui_scale.is_finite() { self.ui_scale.clamp(MIN_UI_SCALE, MAX_UI_SCALE) } else { 1.0 }
+    }
+
+    pub fn apply_display(&self, renderer: &mut Renderer) {
+        renderer.ui_scale = self.clamped_ui_scale();
+    }
+
+    // Adds the colour filter to `chain` the first time, then keeps its matrix and strength
+    // in step with the settings. Disabled for normal vision.
+    pub fn apply_filter(
+        &self,
+        chain: &mut ScreenEffectChain,
+        device: &wgpu::Device,
+        resources: &ResourceRegistry,
+    ) -> Result<EffectId, String> {
+        let id = match chain.find(COLORBLIND_EFFECT) {
+            Some(id) => id,
+            None => chain.add(device, resources, COLORBLIND_EFFECT, COLORBLIND_EFFECT_ORDER, COLORBLIND_FILTER_WGSL)?,
+        };
+        if let Some(effect) = chain.get_mut(id) {
+            let filter = self.color_vision.filter(self.color_filter_mode);
+            let strength = self.color_filter_strength.clamp(0.0, 1.0);
+            effect.enabled = self.color_vision != ColorVision::Normal && strength > 0.0;
+            effect.params = [
+                filter.row(0).extend(0.0),
+                filter.row(1).extend(0.0),
+                filter.row(2).extend(0.0),
+                Vec4::new(strength, 0.0, 0.0, 0.0),
+            ];
+        }
+        Ok(id)
+    }
+}
+
+// What a focused element is, so a screen reader can say "Volume, slider, 80 percent"
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UiRole {
+    Button,
+    Checkbox { checked: bool },
+    Slider,
+    TextField,
+    Label,
+    Menu,
+    MenuItem,
+    ListItem { index: usize, count: usize },
+    Tab,
+    Dialog,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FocusTarget {
+    // Stable within its screen, e.g. "settings.volume"
+    pub id: String,
+    pub label: String,
+    pub role: UiRole,
+    pub value: Option<String>,
+    // Extra help read after a pause, e.g. "Press A to change"
+    pub hint: Option<String>,
+}
+
+impl FocusTarget {
+    pub fn new(id: &str, label: &str, role: UiRole) -> Self {
+        Self { id: id.to_string(), label: label.to_string(), role, value: None, hint: None }
+    }
+
+    pub fn with_value(mut self, value: &str) -> Self {
+        self.value = Some(value.to_string());
+        self
+    }
+
+    pub fn with_hint(mut self, hint: &str) -> Self {
+        self.hint = Some(hint.to_string());
+        self
+    }
+
+    // One line for text-to-speech
+    pub fn describe(&self) -> String {
+        let role = match self.role {
+            UiRole::Button => "button".to_string(),
+            UiRole::Checkbox { checked: true } => "checkbox, checked".to_string(),
+            UiRole::Checkbox { checked: false } => "checkbox, not checked".to_string(),
+            UiRole::Slider => "slider".to_string(),
+            UiRole::TextField => "text field".to_string(),
+            UiRole::Label => String::new(),
+            UiRole::Menu => "menu".to_string(),
+            UiRole::MenuItem => "menu item".to_string(),
+            UiRole::ListItem { index, count } => format!("{} of {}", index + 1, count),
+            UiRole::Tab => "tab".to_string(),
+            UiRole::Dialog => "dialog".to_string(),
+        };
+        let mut parts = vec![self.label.as_str()];
+        parts.extend([role.as_str(), self.value.as_deref().unwrap_or_default(), self.hint.as_deref().unwrap_or_default()]);
+        parts.retain(|part| !part.is_empty());
+        parts.join(", ")
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum AccessibilityEvent {
+    Focused(FocusTarget),
+    // The focused element's value changed in place, e.g. a slider moved
+    ValueChanged(FocusTarget),
+    FocusCleared,
+    // Free text, e.g. "Checkpoint reached". `interrupt` cuts off whatever is being read.
+    Announce { text: String, interrupt: bool },
+}
+
+// Engine-level accessibility service, kept as a world resource. UI code reports focus
+// changes here and a screen reader bridge or text-to-speech drains the events; nothing
+// is queued unless `settings.screen_reader` is on.
+#[derive(Debug, Clone, Default)]
+pub struct Accessibility {
+    pub settings: AccessibilitySettings,
+    focused: Option<FocusTarget>,
+    events: Vec<AccessibilityEvent>,
+}
+
+impl Accessibility {
+    pub fn new(settings: AccessibilitySettings) -> Self {
+        Self { settings, focused: None, events: Vec::new() }
+    }
+
+    pub fn focused(&self) -> Option<&FocusTarget> {
+        self.focused.as_ref()
+    }
+
+    // Safe to call every frame: only a change of element or value is reported
+    pub fn focus(&mut self, target: FocusTarget) {
+        let event = match &self.focused {
+            Some(current) if *current == target => return,
+            Some(current) if current.id == target.id => AccessibilityEvent::ValueChanged(target.clone()),
+            _ => AccessibilityEvent::Focused(target.clone()),
+        };
+        self.focused = Some(target);
+        self.push(event);
+    }
+
+    pub fn clear_focus(&mut self) {
+        if self.focused.take().is_some() {
+            self.push(AccessibilityEvent::FocusCleared);
+        }
+    }
+
+    pub fn announce(&mut self, text: &str, interrupt: bool) {
+        self.push(AccessibilityEvent::Announce { text: text.to_string(), interrupt });
+    }
+
+    fn push(&mut self, event: AccessibilityEvent) {
+        if self.settings.screen_reader {
+            self.events.push(event);
+        }
+    }
+
+    pub fn drain_events(&mut self) -> impl Iterator<Item = AccessibilityEvent> + '_ {
+        self.events.drain(..)
+    }
+}
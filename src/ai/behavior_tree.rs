@@ -0,0 +1,236 @@
+// src/ai/behavior_tree.rs
+use std::fmt::Write;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    Success,
+    Failure,
+    Running,
+}
+
+type ActionFn<C> = Box<dyn FnMut(&mut C, f64) -> Status>;
+type ConditionFn<C> = Box<dyn Fn(&C) -> bool>;
+
+enum NodeKind<C> {
+    // Runs children in order until one fails
+    Sequence { children: Vec<Node<C>>, current: usize },
+    // Runs children in order until one succeeds
+    Selector { children: Vec<Node<C>>, current: usize },
+    Inverter(Box<Node<C>>),
+    AlwaysSucceed(Box<Node<C>>),
+    // Runs the child to completion `count` times, failing early if it fails
+    Repeat { child: Box<Node<C>>, count: u32, completed: u32 },
+    // Fails without ticking the child until `duration` seconds have passed since it last finished
+    Cooldown { child: Box<Node<C>>, duration: f64, remaining: f64 },
+    Action(ActionFn<C>),
+    Condition(ConditionFn<C>),
+}
+
+pub struct Node<C> {
+    name: String,
+    kind: NodeKind<C>,
+    // Result of the most recent tick, None if the node wasn't reached
+    last_status: Option<Status>,
+}
+
+impl<C> Node<C> {
+    fn new(name: &str, kind: NodeKind<C>) -> Self {
+        Self { name: name.to_string(), kind, last_status: None }
+    }
+
+    pub fn sequence(name: &str, children: Vec<Node<C>>) -> Self {
+        Self::new(name, NodeKind::Sequence { children, current: 0 })
+    }
+
+    pub fn selector(name: &str, children: Vec<Node<C>>) -> Self {
+        Self::new(name, NodeKind::Selector { children, current: 0 })
+    }
+
+    pub fn inverter(name: &str, child: Node<C>) -> Self {
+        Self::new(name, NodeKind::Inverter(Box::new(child)))
+    }
+
+    pub fn always_succeed(name: &str, child: Node<C>) -> Self {
+        Self::new(name, NodeKind::AlwaysSucceed(Box::new(child)))
+    }
+
+    pub fn repeat(name: &str, count: u32, child: Node<C>) -> Self {
+        Self::new(name, NodeKind::Repeat { child: Box::new(child), count, completed: 0 })
+    }
+
+    pub fn cooldown(name: &str, duration: f64, child: Node<C>) -> Self {
+        Self::new(name, NodeKind::Cooldown { child: Box::new(child), duration, remaining: 0.0 })
+    }
+
+    pub fn action(name: &str, action: impl FnMut(&mut C, f64) -> Status + 'static) -> Self {
+        Self::new(name, NodeKind::Action(Box::new(action)))
+    }
+
+    pub fn condition(name: &str, condition: impl Fn(&C) -> bool + 'static) -> Self {
+        Self::new(name, NodeKind::Condition(Box::new(condition)))
+    }
+
+    fn tick(&mut self, ctx: &mut C, dt: f64) -> Status {
+        let status = match &mut self.kind {
+            NodeKind::Sequence { children, current } => tick_composite(children, current, ctx, dt, Status::Success),
+            NodeKind::Selector { children, current } => tick_composite(children, current, ctx, dt, Status::Failure),
+            NodeKind::Inverter(child) => match child.tick(ctx, dt) {
+                Status::Success => Status::Failure,
+                Status::Failure => Status::Success,
+                Status::Running => Status::Running,
+            },
+            NodeKind::AlwaysSucceed(child) => match child.tick(ctx, dt) {
+                Status::Running => Status::Running,
+                _ => Status::Success,
+            },
+            NodeKind::Repeat { child, count, completed } => {
+                let mut status = Status::Success;
+                while *completed < *count {
+                    match child.tick(ctx, dt) {
+                        Status::Success => *completed += 1,
+                        other => {
+                            status = other;
+                            break;
+                        }
+                    }
+                }
+                if status != Status::Running {
+                    *completed = 0;
+                }
+                status
+            }
+            NodeKind::Cooldown { child, duration, remaining } => {
+                *remaining = (*remaining - dt).max(0.0);
+                if *remaining > 0.0 {
+                    Status::Failure
+                } else {
+                    let status = child.tick(ctx, dt);
+                    if status != Status::Running {
+                        *remaining = *duration;
+                    }
+                    status
+                }
+            }
+            NodeKind::Action(action) => action(ctx, dt),
+            NodeKind::Condition(condition) => {
+                if condition(ctx) { Status::Success } else { Status::Failure }
+            }
+        };
+        self.last_status = Some(status);
+        status
+    }
+
+    fn children(&self) -> Vec<&Node<C>> {
+        match &self.kind {
+            NodeKind::Sequence { children, .. } | NodeKind::Selector { children, .. } => children.iter().collect(),
+            NodeKind::Inverter(child)
+            | NodeKind::AlwaysSucceed(child)
+            | NodeKind::Repeat { child, .. }
+            | NodeKind::Cooldown { child, .. } => vec![child.as_ref()],
+            NodeKind::Action(_) | NodeKind::Condition(_) => Vec::new(),
+        }
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut Node<C>> {
+        match &mut self.kind {
+            NodeKind::Sequence { children, .. } | NodeKind::Selector { children, .. } => children.iter_mut().collect(),
+            NodeKind::Inverter(child)
+            | NodeKind::AlwaysSucceed(child)
+            | NodeKind::Repeat { child, .. }
+            | NodeKind::Cooldown { child, .. } => vec![child.as_mut()],
+            NodeKind::Action(_) | NodeKind::Condition(_) => Vec::new(),
+        }
+    }
+
+    fn clear_status(&mut self) {
+        self.last_status = None;
+        for child in self.children_mut() {
+            child.clear_status();
+        }
+    }
+
+    fn reset(&mut self) {
+        match &mut self.kind {
+            NodeKind::Sequence { current, .. } | NodeKind::Selector { current, .. } => *current = 0,
+            NodeKind::Repeat { completed, .. } => *completed = 0,
+            NodeKind::Cooldown { remaining, .. } => *remaining = 0.0,
+            _ => {}
+        }
+        self.last_status = None;
+        for child in self.children_mut() {
+            child.reset();
+        }
+    }
+}
+
+// Shared by sequence and selector: `keep_going` is the child result that moves on to the next child.
+// Resumes from the child that was running last tick instead of re-evaluating earlier ones.
+fn tick_composite<C>(children: &mut [Node<C>], current: &mut usize, ctx: &mut C, dt: f64, keep_going: Status) -> Status {
+    while *current < children.len() {
+        let status = children[*current].tick(ctx, dt);
+        if status == Status::Running {
+            return Status::Running;
+        }
+        if status != keep_going {
+            *current = 0;
+            return status;
+        }
+        *current += 1;
+    }
+    *current = 0;
+    keep_going
+}
+
+// Per-entity tree; tick it from the fixed update with the entity's context (blackboard)
+pub struct BehaviorTree<C> {
+    root: Node<C>,
+}
+
+impl<C> BehaviorTree<C> {
+    pub fn new(root: Node<C>) -> Self {
+        Self { root }
+    }
+
+    pub fn tick(&mut self, ctx: &mut C, dt: f64) -> Status {
+        self.root.clear_status();
+        self.root.tick(ctx, dt)
+    }
+
+    pub fn reset(&mut self) {
+        self.root.reset();
+    }
+
+    // Names of the nodes still running after the last tick, from the root down
+    pub fn active_branch(&self) -> Vec<&str> {
+        let mut branch = Vec::new();
+        let mut node = &self.root;
+        while node.last_status == Some(Status::Running) {
+            branch.push(node.name.as_str());
+            match node.children().into_iter().find(|c| c.last_status == Some(Status::Running)) {
+                Some(child) => node = child,
+                None => break,
+            }
+        }
+        branch
+    }
+
+    // Indented dump of the tree with the result of each node reached last tick
+    pub fn debug_string(&self) -> String {
+        let mut out = String::new();
+        write_node(&self.root, 0, &mut out);
+        out
+    }
+}
+
+fn write_node<C>(node: &Node<C>, depth: usize, out: &mut String) {
+    let marker = match node.last_status {
+        Some(Status::Running) => "[>]",
+        Some(Status::Success) => "[+]",
+        Some(Status::Failure) => "[-]",
+        None => "[ ]",
+    };
+    let _ = writeln!(out, "{}{} {}", "  ".repeat(depth), marker, node.name);
+    for child in node.children() {
+        write_node(child, depth + 1, out);
+    }
+}
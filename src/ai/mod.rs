@@ -0,0 +1,4 @@
+// src/ai/mod.rs
+pub mod behavior_tree;
+pub mod pathfinding;
+pub mod steering;
@@ -0,0 +1,372 @@
+// src/ai/pathfinding.rs
+use glam::Vec2;
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap};
+
+#[derive(Clone, Copy, PartialEq)]
+struct OpenNode {
+    node: usize,
+    f_score: f32,
+}
+
+impl Eq for OpenNode {}
+
+impl Ord for OpenNode {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Reversed so the BinaryHeap pops the lowest f score first
+        other.f_score.total_cmp(&self.f_score)
+    }
+}
+
+impl PartialOrd for OpenNode {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+// Generic A* over integer node ids, shared by the grid and navmesh searches
+fn astar(
+    start: usize,
+    goal: usize,
+    mut neighbors: impl FnMut(usize, &mut Vec<(usize, f32)>),
+    heuristic: impl Fn(usize) -> f32,
+) -> Option<Vec<usize>> {
+    let mut open = BinaryHeap::new();
+    let mut came_from: HashMap<usize, usize> = HashMap::new();
+    let mut g_score: HashMap<usize, f32> = HashMap::new();
+    let mut scratch = Vec::new();
+
+    g_score.insert(start, 0.0);
+    open.push(OpenNode { node: start, f_score: heuristic(start) });
+
+    while let Some(OpenNode { node, f_score }) = open.pop() {
+        if node == goal {
+            let mut path = vec![goal];
+            let mut current = goal;
+            while let Some(&previous) = came_from.get(&current) {
+                path.push(previous);
+                current = previous;
+            }
+            path.reverse();
+            return Some(path);
+        }
+        let g = g_score[&node];
+        // Stale heap entry for a node we've since reached more cheaply
+        if f_score > g + heuristic(node) {
+            continue;
+        }
+
+        scratch.clear();
+        neighbors(node, &mut scratch);
+        for &(next, cost) in &scratch {
+            let tentative = g + cost;
+            if g_score.get(&next).is_none_or(|&existing| tentative < existing) {
+                came_from.insert(next, node);
+                g_score.insert(next, tentative);
+                open.push(OpenNode { node: next, f_score: tentative + heuristic(next) });
+            }
+        }
+    }
+    None
+}
+
+pub struct Grid {
+    width: u32,
+    height: u32,
+    walkable: Vec<bool>,
+    cell_size: f32,
+    origin: Vec2,
+    allow_diagonal: bool,
+}
+
+impl Grid {
+    pub fn new(width: u32, height: u32, cell_size: f32) -> Self {
+        Self {
+            width,
+            height,
+            walkable: vec![true; (width * height) as usize],
+            cell_size,
+            origin: Vec2::ZERO,
+            allow_diagonal: true,
+        }
+    }
+
+    // Builds a grid from tilemap collision data, row-major, `true` = solid tile
+    pub fn from_collision(width: u32, height: u32, cell_size: f32, solid: &[bool]) -> Result<Self, String> {
+        if solid.len() != (width * height) as usize {
+            return Err(format!("Collision data has {} cells, expected {}x{}", solid.len(), width, height));
+        }
+        let mut grid = Self::new(width, height, cell_size);
+        grid.walkable = solid.iter().map(|s| !s).collect();
+        Ok(grid)
+    }
+
+    pub fn with_origin(mut self, origin: Vec2) -> Self {
+        self.origin = origin;
+        self
+    }
+
+    pub fn with_diagonal(mut self, allow_diagonal: bool) -> Self {
+        self.allow_diagonal = allow_diagonal;
+        self
+    }
+
+    pub fn set_walkable(&mut self, x: u32, y: u32, walkable: bool) {
+        if x < self.width && y < self.height {
+            self.walkable[(y * self.width + x) as usize] = walkable;
+        }
+    }
+
+    pub fn is_walkable(&self, x: i64, y: i64) -> bool {
+        x >= 0
+            && y >= 0
+            && x < self.width as i64
+            && y < self.height as i64
+            && self.walkable[(y as u32 * self.width + x as u32) as usize]
+    }
+
+    pub fn world_to_cell(&self, point: Vec2) -> Option<(u32, u32)> {
+        let cell = ((point - self.origin) / self.cell_size).floor();
+        if cell.x < 0.0 || cell.y < 0.0 || cell.x >= self.width as f32 || cell.y >= self.height as f32 {
+            return None;
+        }
+        Some((cell.x as u32, cell.y as u32))
+    }
+
+    pub fn cell_to_world(&self, (x, y): (u32, u32)) -> Vec2 {
+        self.origin + (Vec2::new(x as f32, y as f32) + 0.5) * self.cell_size
+    }
+
+    pub fn find_path(&self, start: (u32, u32), goal: (u32, u32)) -> Option<Vec<(u32, u32)>> {
+        if !self.is_walkable(start.0 as i64, start.1 as i64) || !self.is_walkable(goal.0 as i64, goal.1 as i64) {
+            return None;
+        }
+        let width = self.width as usize;
+        let to_id = |(x, y): (u32, u32)| y as usize * width + x as usize;
+        let to_cell = |id: usize| ((id % width) as u32, (id / width) as u32);
+        let goal_cell = goal;
+
+        let path = astar(
+            to_id(start),
+            to_id(goal),
+            |id, out| {
+                let (x, y) = to_cell(id);
+                let (x, y) = (x as i64, y as i64);
+                for (dx, dy) in [(1, 0), (-1, 0), (0, 1), (0, -1)] {
+                    if self.is_walkable(x + dx, y + dy) {
+                        out.push((to_id(((x + dx) as u32, (y + dy) as u32)), 1.0));
+                    }
+                }
+                if self.allow_diagonal {
+                    for (dx, dy) in [(1, 1), (1, -1), (-1, 1), (-1, -1)] {
+                        // No cutting corners past solid tiles
+                        if self.is_walkable(x + dx, y + dy) && self.is_walkable(x + dx, y) && self.is_walkable(x, y + dy) {
+                            out.push((to_id(((x + dx) as u32, (y + dy) as u32)), std::f32::consts::SQRT_2));
+                        }
+                    }
+                }
+            },
+            |id| {
+                let (x, y) = to_cell(id);
+                let dx = (x as f32 - goal_cell.0 as f32).abs();
+                let dy = (y as f32 - goal_cell.1 as f32).abs();
+                if self.allow_diagonal {
+                    // Octile distance
+                    dx.max(dy) + (std::f32::consts::SQRT_2 - 1.0) * dx.min(dy)
+                } else {
+                    dx + dy
+                }
+            },
+        )?;
+        Some(path.into_iter().map(to_cell).collect())
+    }
+
+    // World-space waypoints through cell centres, ending exactly at `goal`
+    pub fn find_world_path(&self, start: Vec2, goal: Vec2) -> Option<Vec<Vec2>> {
+        let cells = self.find_path(self.world_to_cell(start)?, self.world_to_cell(goal)?)?;
+        let mut points: Vec<Vec2> = cells.into_iter().skip(1).map(|c| self.cell_to_world(c)).collect();
+        points.pop();
+        points.push(goal);
+        Some(points)
+    }
+}
+
+struct NavPolygon {
+    indices: Vec<usize>,
+    centroid: Vec2,
+    // (neighbour polygon, shared edge as it appears in this polygon's winding)
+    neighbors: Vec<(usize, (usize, usize))>,
+}
+
+// Convex polygons (counter-clockwise) connected through shared edges
+pub struct NavMesh {
+    vertices: Vec<Vec2>,
+    polygons: Vec<NavPolygon>,
+}
+
+fn cross(a: Vec2, b: Vec2, c: Vec2) -> f32 {
+    (b - a).perp_dot(c - a)
+}
+
+fn signed_area(points: &[Vec2]) -> f32 {
+    (0..points.len())
+        .map(|i| points[i].perp_dot(points[(i + 1) % points.len()]))
+        .sum::<f32>()
+        * 0.5
+}
+
+impl NavMesh {
+    // Bakes a walkable area given as a simple polygon outline (no holes) by ear clipping it
+    pub fn bake(outline: &[Vec2]) -> Result<Self, String> {
+        if outline.len() < 3 {
+            return Err("Navmesh outline needs at least 3 points".to_string());
+        }
+        let mut vertices = outline.to_vec();
+        if signed_area(&vertices) < 0.0 {
+            vertices.reverse();
+        }
+
+        let mut remaining: Vec<usize> = (0..vertices.len()).collect();
+        let mut triangles = Vec::new();
+        while remaining.len() > 3 {
+            let n = remaining.len();
+            let ear = (0..n).find(|&i| {
+                let (a, b, c) = (remaining[(i + n - 1) % n], remaining[i], remaining[(i + 1) % n]);
+                if cross(vertices[a], vertices[b], vertices[c]) <= 0.0 {
+                    return false;
+                }
+                !remaining.iter().any(|&p| {
+                    p != a
+                        && p != b
+                        && p != c
+                        && cross(vertices[a], vertices[b], vertices[p]) >= 0.0
+                        && cross(vertices[b], vertices[c], vertices[p]) >= 0.0
+                        && cross(vertices[c], vertices[a], vertices[p]) >= 0.0
+                })
+            });
+            let Some(i) = ear else {
+                return Err("Navmesh outline is self-intersecting or degenerate".to_string());
+            };
+            triangles.push(vec![remaining[(i + n - 1) % n], remaining[i], remaining[(i + 1) % n]]);
+            remaining.remove(i);
+        }
+        triangles.push(remaining);
+        Self::from_polygons(vertices, triangles)
+    }
+
+    // Builds a navmesh from convex polygons given as indices into `vertices`
+    pub fn from_polygons(vertices: Vec<Vec2>, polygons: Vec<Vec<usize>>) -> Result<Self, String> {
+        let mut polys = Vec::with_capacity(polygons.len());
+        for mut indices in polygons {
+            if indices.len() < 3 || indices.iter().any(|&i| i >= vertices.len()) {
+                return Err("Navmesh polygon has fewer than 3 vertices or an invalid index".to_string());
+            }
+            let points: Vec<Vec2> = indices.iter().map(|&i| vertices[i]).collect();
+            if signed_area(&points) < 0.0 {
+                indices.reverse();
+            }
+            let centroid = points.iter().copied().sum::<Vec2>() / points.len() as f32;
+            polys.push(NavPolygon { indices, centroid, neighbors: Vec::new() });
+        }
+
+        let mut edges: HashMap<(usize, usize), usize> = HashMap::new();
+        for (p, poly) in polys.iter().enumerate() {
+            let n = poly.indices.len();
+            for i in 0..n {
+                let (a, b) = (poly.indices[i], poly.indices[(i + 1) % n]);
+                edges.insert((a, b), p);
+            }
+        }
+        for poly in &mut polys {
+            let n = poly.indices.len();
+            for i in 0..n {
+                let (a, b) = (poly.indices[i], poly.indices[(i + 1) % n]);
+                // The neighbour walks the shared edge in the opposite direction
+                if let Some(&other) = edges.get(&(b, a)) {
+                    poly.neighbors.push((other, (a, b)));
+                }
+            }
+        }
+        Ok(Self { vertices, polygons: polys })
+    }
+
+    pub fn find_polygon(&self, point: Vec2) -> Option<usize> {
+        self.polygons.iter().position(|poly| {
+            let n = poly.indices.len();
+            (0..n).all(|i| cross(self.vertices[poly.indices[i]], self.vertices[poly.indices[(i + 1) % n]], point) >= 0.0)
+        })
+    }
+
+    // Shortest path as world-space waypoints (excluding `start`), straightened through the portals
+    pub fn find_path(&self, start: Vec2, goal: Vec2) -> Option<Vec<Vec2>> {
+        let start_poly = self.find_polygon(start)?;
+        let goal_poly = self.find_polygon(goal)?;
+        let goal_centroid = self.polygons[goal_poly].centroid;
+
+        let corridor = astar(
+            start_poly,
+            goal_poly,
+            |p, out| {
+                for &(next, _) in &self.polygons[p].neighbors {
+                    out.push((next, self.polygons[p].centroid.distance(self.polygons[next].centroid)));
+                }
+            },
+            |p| self.polygons[p].centroid.distance(goal_centroid),
+        )?;
+
+        // Portals as (left, right) seen when walking through them
+        let mut portals = vec![(start, start)];
+        for pair in corridor.windows(2) {
+            let (_, (a, b)) = self.polygons[pair[0]].neighbors.iter().find(|(n, _)| *n == pair[1])?;
+            portals.push((self.vertices[*b], self.vertices[*a]));
+        }
+        portals.push((goal, goal));
+        Some(string_pull(&portals))
+    }
+}
+
+// Simple stupid funnel algorithm
+fn string_pull(portals: &[(Vec2, Vec2)]) -> Vec<Vec2> {
+    let mut path = Vec::new();
+    let (mut apex, mut left, mut right) = (portals[0].0, portals[0].0, portals[0].1);
+    let (mut left_index, mut right_index) = (0, 0);
+    let mut i = 1;
+    while i < portals.len() {
+        let (new_left, new_right) = portals[i];
+
+        if cross(apex, right, new_right) >= 0.0 {
+            if apex == right || cross(apex, left, new_right) < 0.0 {
+                right = new_right;
+                right_index = i;
+            } else {
+                // Right crossed over left: left becomes a corner of the path
+                path.push(left);
+                apex = left;
+                right = apex;
+                right_index = left_index;
+                i = left_index + 1;
+                continue;
+            }
+        }
+
+        if cross(apex, left, new_left) <= 0.0 {
+            if apex == left || cross(apex, right, new_left) > 0.0 {
+                left = new_left;
+                left_index = i;
+            } else {
+                path.push(right);
+                apex = right;
+                left = apex;
+                left_index = right_index;
+                i = right_index + 1;
+                continue;
+            }
+        }
+        i += 1;
+    }
+    let goal = portals[portals.len() - 1].0;
+    if path.last() != Some(&goal) {
+        path.push(goal);
+    }
+    path
+}
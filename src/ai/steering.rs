@@ -0,0 +1,211 @@
+// src/ai/steering.rs
+use glam::Vec2;
+
+#[derive(Debug, Clone, Copy)]
+pub struct SteeringAgent {
+    pub position: Vec2,
+    pub velocity: Vec2,
+    pub max_speed: f32,
+    pub max_force: f32,
+}
+
+impl SteeringAgent {
+    pub fn new(position: Vec2, max_speed: f32, max_force: f32) -> Self {
+        Self { position, velocity: Vec2::ZERO, max_speed, max_force }
+    }
+
+    // Movement step for agents that aren't driven by physics
+    pub fn integrate(&mut self, dt: f32) {
+        self.position += self.velocity * dt;
+    }
+}
+
+// Each behavior returns a steering force: desired velocity minus current velocity
+
+pub fn seek(agent: &SteeringAgent, target: Vec2) -> Vec2 {
+    (target - agent.position).normalize_or_zero() * agent.max_speed - agent.velocity
+}
+
+// Only reacts to threats inside `panic_radius`
+pub fn flee(agent: &SteeringAgent, threat: Vec2, panic_radius: f32) -> Vec2 {
+    let away = agent.position - threat;
+    if away.length_squared() > panic_radius * panic_radius {
+        return Vec2::ZERO;
+    }
+    away.normalize_or_zero() * agent.max_speed - agent.velocity
+}
+
+// Like seek, but slows down linearly inside `slowing_radius` to stop on the target
+pub fn arrive(agent: &SteeringAgent, target: Vec2, slowing_radius: f32) -> Vec2 {
+    let offset = target - agent.position;
+    let distance = offset.length();
+    if distance < f32::EPSILON {
+        return -agent.velocity;
+    }
+    let speed = if distance < slowing_radius { agent.max_speed * distance / slowing_radius } else { agent.max_speed };
+    offset / distance * speed - agent.velocity
+}
+
+pub fn separation(agent: &SteeringAgent, neighbors: &[SteeringAgent], radius: f32) -> Vec2 {
+    let mut push = Vec2::ZERO;
+    for other in neighbors {
+        let away = agent.position - other.position;
+        let distance = away.length();
+        if distance > f32::EPSILON && distance < radius {
+            // Closer neighbours push harder
+            push += away / (distance * distance);
+        }
+    }
+    if push == Vec2::ZERO {
+        return Vec2::ZERO;
+    }
+    push.normalize() * agent.max_speed - agent.velocity
+}
+
+pub fn alignment(agent: &SteeringAgent, neighbors: &[SteeringAgent], radius: f32) -> Vec2 {
+    let (sum, count) = nearby(agent, neighbors, radius).fold((Vec2::ZERO, 0), |(sum, n), o| (sum + o.velocity, n + 1));
+    if count == 0 {
+        return Vec2::ZERO;
+    }
+    (sum / count as f32).normalize_or_zero() * agent.max_speed - agent.velocity
+}
+
+pub fn cohesion(agent: &SteeringAgent, neighbors: &[SteeringAgent], radius: f32) -> Vec2 {
+    let (sum, count) = nearby(agent, neighbors, radius).fold((Vec2::ZERO, 0), |(sum, n), o| (sum + o.position, n + 1));
+    if count == 0 {
+        return Vec2::ZERO;
+    }
+    seek(agent, sum / count as f32)
+}
+
+fn nearby<'a>(agent: &'a SteeringAgent, neighbors: &'a [SteeringAgent], radius: f32) -> impl Iterator<Item = &'a SteeringAgent> {
+    neighbors.iter().filter(move |o| {
+        let distance_sq = o.position.distance_squared(agent.position);
+        distance_sq > 0.0 && distance_sq < radius * radius
+    })
+}
+
+// Random meandering: seeks a point that drifts around a circle projected ahead of the agent
+#[derive(Debug, Clone)]
+pub struct Wander {
+    pub distance: f32,
+    pub radius: f32,
+    // Max change of the wander angle, in radians per second
+    pub jitter: f32,
+    angle: f32,
+    rng_state: u32,
+}
+
+impl Wander {
+    pub fn new(distance: f32, radius: f32, jitter: f32, seed: u32) -> Self {
+        Self { distance, radius, jitter, angle: 0.0, rng_state: seed.max(1) }
+    }
+
+    // Xorshift, so wandering stays deterministic for a given seed
+    fn next_random(&mut self) -> f32 {
+        self.rng_state ^= self.rng_state << 13;
+        self.rng_state ^= self.rng_state >> 17;
+        self.rng_state ^= self.rng_state << 5;
+        self.rng_state as f32 / u32::MAX as f32 * 2.0 - 1.0
+    }
+
+    pub fn steer(&mut self, agent: &SteeringAgent, dt: f32) -> Vec2 {
+        self.angle += self.next_random() * self.jitter * dt;
+        let heading = agent.velocity.try_normalize().unwrap_or(Vec2::X);
+        let circle_center = agent.position + heading * self.distance;
+        let target = circle_center + Vec2::from_angle(self.angle).rotate(heading) * self.radius;
+        seek(agent, target)
+    }
+}
+
+// Walks a list of waypoints (e.g. from `pathfinding`), arriving at the last one
+#[derive(Debug, Clone)]
+pub struct PathFollower {
+    waypoints: Vec<Vec2>,
+    current: usize,
+    pub waypoint_radius: f32,
+    pub slowing_radius: f32,
+}
+
+impl PathFollower {
+    pub fn new(waypoints: Vec<Vec2>, waypoint_radius: f32, slowing_radius: f32) -> Self {
+        Self { waypoints, current: 0, waypoint_radius, slowing_radius }
+    }
+
+    pub fn set_path(&mut self, waypoints: Vec<Vec2>) {
+        self.waypoints = waypoints;
+        self.current = 0;
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.current >= self.waypoints.len()
+    }
+
+    pub fn steer(&mut self, agent: &SteeringAgent) -> Vec2 {
+        while self.current + 1 < self.waypoints.len()
+            && agent.position.distance(self.waypoints[self.current]) < self.waypoint_radius
+        {
+            self.current += 1;
+        }
+        let Some(&target) = self.waypoints.get(self.current) else {
+            return -agent.velocity;
+        };
+        if self.current + 1 == self.waypoints.len() {
+            if agent.position.distance(target) < self.waypoint_radius * 0.5 && agent.velocity.length() < 0.01 {
+                self.current += 1;
+            }
+            arrive(agent, target, self.slowing_radius)
+        } else {
+            seek(agent, target)
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum Behavior {
+    Seek(Vec2),
+    Flee { threat: Vec2, panic_radius: f32 },
+    Arrive { target: Vec2, slowing_radius: f32 },
+    Wander(Wander),
+    Separation { radius: f32 },
+    Alignment { radius: f32 },
+    Cohesion { radius: f32 },
+    FollowPath(PathFollower),
+}
+
+// Per-entity component: weighted blend of behaviors. Updating it writes the agent's
+// velocity; moving the agent is left to the movement/physics step.
+#[derive(Debug, Clone, Default)]
+pub struct Steering {
+    pub behaviors: Vec<(Behavior, f32)>,
+}
+
+impl Steering {
+    pub fn new() -> Self {
+        Self { behaviors: Vec::new() }
+    }
+
+    pub fn with(mut self, behavior: Behavior, weight: f32) -> Self {
+        self.behaviors.push((behavior, weight));
+        self
+    }
+
+    pub fn update(&mut self, agent: &mut SteeringAgent, neighbors: &[SteeringAgent], dt: f32) {
+        let mut force = Vec2::ZERO;
+        for (behavior, weight) in &mut self.behaviors {
+            let f = match behavior {
+                Behavior::Seek(target) => seek(agent, *target),
+                Behavior::Flee { threat, panic_radius } => flee(agent, *threat, *panic_radius),
+                Behavior::Arrive { target, slowing_radius } => arrive(agent, *target, *slowing_radius),
+                Behavior::Wander(wander) => wander.steer(agent, dt),
+                Behavior::Separation { radius } => separation(agent, neighbors, *radius),
+                Behavior::Alignment { radius } => alignment(agent, neighbors, *radius),
+                Behavior::Cohesion { radius } => cohesion(agent, neighbors, *radius),
+                Behavior::FollowPath(follower) => follower.steer(agent),
+            };
+            force += f * *weight;
+        }
+        let force = force.clamp_length_max(agent.max_force);
+        agent.velocity = (agent.velocity + force * dt).clamp_length_max(agent.max_speed);
+    }
+}
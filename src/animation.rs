@@ -0,0 +1,297 @@
+// src/animation.rs
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+#[derive(Debug, Clone, Deserialize)]
+pub enum Clip {
+    // Frame indices into a sprite sheet, switched instantly on transitions
+    Sprite { frames: Vec<u32>, fps: f32 },
+    // Named skeletal clip, cross-faded on transitions from other skeletal clips
+    Skeletal { name: String, length: f32 },
+}
+
+impl Clip {
+    fn length(&self) -> f32 {
+        match self {
+            Clip::Sprite { frames, fps } => frames.len() as f32 / fps.max(f32::EPSILON),
+            Clip::Skeletal { length, .. } => *length,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct State {
+    pub name: String,
+    pub clip: Clip,
+    #[serde(default = "default_true")]
+    pub looping: bool,
+    #[serde(default = "default_speed")]
+    pub speed: f32,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_speed() -> f32 {
+    1.0
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub enum Parameter {
+    Float(f32),
+    Bool(bool),
+    // Set from gameplay, consumed by the first transition that uses it
+    Trigger,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub enum Condition {
+    Greater(String, f32),
+    Less(String, f32),
+    IsTrue(String),
+    IsFalse(String),
+    Triggered(String),
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Transition {
+    // State name, or "*" for any state
+    pub from: String,
+    pub to: String,
+    #[serde(default)]
+    pub conditions: Vec<Condition>,
+    // Cross-fade duration in seconds; only used between two skeletal clips
+    #[serde(default)]
+    pub duration: f32,
+    // Only fire once the current clip has played this far (0..1, normalized)
+    #[serde(default)]
+    pub exit_time: Option<f32>,
+}
+
+// Data-file description of an animation graph, e.g. `player.anim.ron`
+#[derive(Debug, Clone, Deserialize)]
+pub struct AnimationGraph {
+    pub initial: String,
+    #[serde(default)]
+    pub parameters: HashMap<String, Parameter>,
+    pub states: Vec<State>,
+    #[serde(default)]
+    pub transitions: Vec<Transition>,
+}
+
+impl AnimationGraph {
+    pub fn from_ron(source: &str) -> Result<Self, String> {
+        let graph: Self = ron::from_str(source).map_err(|e| format!("Failed to parse animation graph: {}", e))?;
+        graph.validate()?;
+        Ok(graph)
+    }
+
+    pub fn load(path: &str) -> Result<Self, String> {
+        let source = std::fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+        Self::from_ron(&source)
+    }
+
+    fn state_index(&self, name: &str) -> Option<usize> {
+        self.states.iter().position(|s| s.name == name)
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if self.state_index(&self.initial).is_none() {
+            return Err(format!("Initial state '{}' does not exist", self.initial));
+        }
+        for t in &self.transitions {
+            if t.from != "*" && self.state_index(&t.from).is_none() {
+                return Err(format!("Transition from unknown state '{}'", t.from));
+            }
+            if self.state_index(&t.to).is_none() {
+                return Err(format!("Transition to unknown state '{}'", t.to));
+            }
+            for condition in &t.conditions {
+                let (Condition::Greater(name, _)
+                | Condition::Less(name, _)
+                | Condition::IsTrue(name)
+                | Condition::IsFalse(name)
+                | Condition::Triggered(name)) = condition;
+                if !self.parameters.contains_key(name) {
+                    return Err(format!("Transition {} -> {} uses unknown parameter '{}'", t.from, t.to, name));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+struct Blend {
+    from: usize,
+    from_time: f32,
+    elapsed: f32,
+    duration: f32,
+}
+
+// Skeletal clip to sample and how much it contributes to the final pose
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClipWeight<'a> {
+    pub clip: &'a str,
+    pub time: f32,
+    pub weight: f32,
+}
+
+// Per-entity controller; the graph itself is shared between entities
+pub struct AnimationController {
+    graph: Arc<AnimationGraph>,
+    parameters: HashMap<String, Parameter>,
+    triggers: HashSet<String>,
+    current: usize,
+    time: f32,
+    blend: Option<Blend>,
+}
+
+impl AnimationController {
+    pub fn new(graph: Arc<AnimationGraph>) -> Self {
+        let current = graph.state_index(&graph.initial).unwrap_or(0);
+        Self {
+            parameters: graph.parameters.clone(),
+            triggers: HashSet::new(),
+            graph,
+            current,
+            time: 0.0,
+            blend: None,
+        }
+    }
+
+    pub fn set_float(&mut self, name: &str, value: f32) {
+        self.set(name, Parameter::Float(value));
+    }
+
+    pub fn set_bool(&mut self, name: &str, value: bool) {
+        self.set(name, Parameter::Bool(value));
+    }
+
+    pub fn set_trigger(&mut self, name: &str) {
+        match self.parameters.get(name) {
+            Some(Parameter::Trigger) => {
+                self.triggers.insert(name.to_string());
+            }
+            _ => log::warn!("Animation trigger '{}' does not exist", name),
+        }
+    }
+
+    fn set(&mut self, name: &str, value: Parameter) {
+        match self.parameters.get_mut(name) {
+            Some(param) if std::mem::discriminant(param) == std::mem::discriminant(&value) => *param = value,
+            _ => log::warn!("Animation parameter '{}' does not exist or has a different type", name),
+        }
+    }
+
+    pub fn current_state(&self) -> &str {
+        &self.graph.states[self.current].name
+    }
+
+    pub fn is_blending(&self) -> bool {
+        self.blend.is_some()
+    }
+
+    pub fn update(&mut self, dt: f32) {
+        let state = &self.graph.states[self.current];
+        self.time += dt * state.speed;
+        if let Some(blend) = &mut self.blend {
+            blend.elapsed += dt;
+            blend.from_time += dt * self.graph.states[blend.from].speed;
+            if blend.elapsed >= blend.duration {
+                self.blend = None;
+            }
+        }
+
+        if let Some(index) = self.find_transition() {
+            self.start_transition(index);
+        }
+    }
+
+    fn normalized_time(&self, state: usize, time: f32) -> f32 {
+        let length = self.graph.states[state].clip.length();
+        if length <= 0.0 { 1.0 } else { time / length }
+    }
+
+    fn find_transition(&self) -> Option<usize> {
+        let current = &self.graph.states[self.current].name;
+        self.graph.transitions.iter().position(|t| {
+            (t.from == "*" && &t.to != current || &t.from == current)
+                && t.exit_time.is_none_or(|exit| self.normalized_time(self.current, self.time) >= exit)
+                && t.conditions.iter().all(|c| self.check(c))
+        })
+    }
+
+    fn check(&self, condition: &Condition) -> bool {
+        match condition {
+            Condition::Greater(name, v) => matches!(self.parameters.get(name), Some(Parameter::Float(x)) if x > v),
+            Condition::Less(name, v) => matches!(self.parameters.get(name), Some(Parameter::Float(x)) if x < v),
+            Condition::IsTrue(name) => matches!(self.parameters.get(name), Some(Parameter::Bool(true))),
+            Condition::IsFalse(name) => matches!(self.parameters.get(name), Some(Parameter::Bool(false))),
+            Condition::Triggered(name) => self.triggers.contains(name),
+        }
+    }
+
+    fn start_transition(&mut self, index: usize) {
+        let graph = self.graph.clone();
+        let transition = &graph.transitions[index];
+        for condition in &transition.conditions {
+            if let Condition::Triggered(name) = condition {
+                self.triggers.remove(name);
+            }
+        }
+        let Some(to) = graph.state_index(&transition.to) else { return };
+        // Only poses cross-fade; a switch to or from a sprite clip cuts, or the skeletal
+        // weights wouldn't sum to 1 during the blend
+        let skeletal = |state: usize| matches!(graph.states[state].clip, Clip::Skeletal { .. });
+        let cross_fade = skeletal(self.current) && skeletal(to) && transition.duration > 0.0;
+        self.blend = cross_fade.then_some(Blend {
+            from: self.current,
+            from_time: self.time,
+            elapsed: 0.0,
+            duration: transition.duration,
+        });
+        self.current = to;
+        self.time = 0.0;
+    }
+
+    fn clip_time(&self, state: usize, time: f32) -> f32 {
+        let state = &self.graph.states[state];
+        let length = state.clip.length();
+        if length <= 0.0 {
+            0.0
+        } else if state.looping {
+            time.rem_euclid(length)
+        } else {
+            time.min(length)
+        }
+    }
+
+    // Sprite frame to draw, if the current state is a sprite clip
+    pub fn sprite_frame(&self) -> Option<u32> {
+        let Clip::Sprite { frames, fps } = &self.graph.states[self.current].clip else { return None };
+        let time = self.clip_time(self.current, self.time);
+        let index = ((time * fps) as usize).min(frames.len().saturating_sub(1));
+        frames.get(index).copied()
+    }
+
+    // Skeletal clips contributing to the pose, with weights summing to 1
+    pub fn skeletal_weights(&self) -> Vec<ClipWeight<'_>> {
+        let mut weights = Vec::new();
+        let blend_weight = self.blend.as_ref().map_or(1.0, |b| (b.elapsed / b.duration).clamp(0.0, 1.0));
+        if let Clip::Skeletal { name, .. } = &self.graph.states[self.current].clip {
+            weights.push(ClipWeight { clip: name, time: self.clip_time(self.current, self.time), weight: blend_weight });
+        }
+        if let Some(blend) = &self.blend {
+            if let Clip::Skeletal { name, .. } = &self.graph.states[blend.from].clip {
+                weights.push(ClipWeight {
+                    clip: name,
+                    time: self.clip_time(blend.from, blend.from_time),
+                    weight: 1.0 - blend_weight,
+                });
+            }
+        }
+        weights
+    }
+}
@@ -1,8 +1,10 @@
 // src/app.rs
-use crate::{window::WindowManager, renderer::Renderer, game_loop::GameLoop, input::InputManager};
+use crate::{window::WindowManager, renderer::Renderer, game_loop::GameLoop, input::{process_input, InputEvent, InputManager, actions::update_actions, shortcuts::{ShortcutManager, GLOBAL}, gamepad::{poll_gamepads, Gamepads}, haptics::Haptics, playback::step_input_stream}, frame_pacing::FramePacer, power::{PowerManager, PowerMode}, bench::{BenchRun, BenchScript}, scene::Scene, accessibility::Accessibility, steam::run_steam_callbacks, telemetry::update_telemetry, gpu_fallback::GpuTier, loading::{update_loader, Loader}, state::{update_states, StateStack}, ui::{focus::navigate_ui, update_ui, Ui}};
+use crate::ecs::{lifetime::tick_lifetimes, resources::{Time, WindowInfo}, schedule::{Schedule, Stage, System}, world::World};
+use std::time::Instant;
 use winit::{
     application::ApplicationHandler,
-    event_loop::ActiveEventLoop,
+    event_loop::{ActiveEventLoop, ControlFlow},
     window::WindowId,
     event::WindowEvent,
     keyboard::{KeyCode, PhysicalKey}, // FIXED: Changed imports for key handling
@@ -12,7 +14,14 @@ pub struct VellumApp {
     window_manager: WindowManager,
     renderer: Renderer,
     game_loop: GameLoop,
-    input_manager: InputManager,
+    // Input, time, window info and the scene live in `world.resources` for systems to use
+    world: World,
+    schedule: Schedule<World>,
+    started: Instant,
+    frame_pacer: FramePacer,
+    bench: Option<BenchRun>,
+    #[cfg(feature = "hot-reload")]
+    game: Option<crate::hot_reload::HotReloader>,
 }
 
 impl VellumApp {
@@ -21,9 +30,158 @@ impl VellumApp {
             window_manager: WindowManager::new(),
             renderer: Renderer::new(),
             game_loop: GameLoop::new(60.0),
-            input_manager: InputManager::new(),
+            world: Self::create_world(),
+            schedule: Self::create_schedule(),
+            started: Instant::now(),
+            frame_pacer: FramePacer::default(),
+            bench: None,
+            #[cfg(feature = "hot-reload")]
+            game: None,
         }
     }
+
+    // Dev mode: runs gameplay from a cdylib built with `export_game!`, reloading it on rebuild
+    #[cfg(feature = "hot-reload")]
+    pub fn load_game_library(&mut self, path: &str) -> Result<(), String> {
+        self.game = Some(crate::hot_reload::HotReloader::new(path)?);
+        Ok(())
+    }
+
+    fn create_world() -> World {
+        let mut world = World::new();
+        world.resources.insert(InputManager::new());
+        world.resources.insert(Gamepads::new());
+        world.resources.insert(Haptics::new());
+        world.resources.insert(Accessibility::default());
+        world.resources.insert(Time::default());
+        world.resources.insert(Ui::new());
+        world.resources.insert(StateStack::new());
+        world.resources.insert(Loader::new());
+        // Systems keep the loop awake with `request_frames`, e.g. while an animation plays
+        world.resources.insert(PowerManager::default());
+        let mut shortcuts = ShortcutManager::new();
+        if let Err(e) = shortcuts.bind(GLOBAL, "F3", "debug_view.cycle") {
+            log::warn!("{}", e);
+        }
+        world.resources.insert(shortcuts);
+        world
+    }
+
+    fn create_schedule() -> Schedule<World> {
+        let mut schedule = Schedule::new();
+        schedule.set_stage_end(World::apply_commands);
+        schedule.add_system(System::new(Stage::PreUpdate, "gamepads", poll_gamepads));
+        schedule.add_system(System::new(Stage::PreUpdate, "steam", run_steam_callbacks));
+        schedule.add_system(System::new(Stage::PreUpdate, "loader", update_loader));
+        schedule.add_system(System::new(Stage::PreUpdate, "states", update_states).after("loader"));
+        // Gameplay reading input can order itself `.after("input")`
+        schedule.add_system(System::new(Stage::FixedUpdate, "input_stream", step_input_stream).in_set("input"));
+        schedule.add_system(System::new(Stage::FixedUpdate, "actions", update_actions).in_set("input").after("input_stream"));
+        schedule.add_system(System::new(Stage::FixedUpdate, "ui_navigation", navigate_ui).after("input"));
+        schedule.add_system(System::new(Stage::FixedUpdate, "lifetimes", tick_lifetimes));
+        schedule.add_system(System::new(Stage::FixedUpdate, "scene_update", |world: &mut World, delta_time| {
+            if let Some(scene) = world.resources.get_mut::<Scene>() {
+                scene.update(delta_time);
+            }
+        }));
+        // Bound widgets see this frame's gameplay changes
+        schedule.add_system(System::new(Stage::PostUpdate, "ui", update_ui));
+        schedule.add_system(System::new(Stage::PostUpdate, "telemetry", update_telemetry));
+        schedule
+    }
+
+    // Game state and resources shared with systems
+    pub fn world(&mut self) -> &mut World {
+        &mut self.world
+    }
+
+    fn power(&mut self) -> &mut PowerManager {
+        self.world.resources.get_or_insert_with(PowerManager::default)
+    }
+
+    // Gameplay systems; order them against the built-in "scene_update" as needed
+    pub fn add_system(&mut self, system: System<World>) {
+        self.schedule.add_system(system);
+    }
+
+    // Runs every stage for this frame. The renderer owns the scene between frames and
+    // lends it to the systems as a resource while they run.
+    fn run_systems(&mut self, delta_time: f64, update_count: u32) {
+        // Before gameplay drains the rest
+        if let (Some(shortcuts), Some(debug_view)) = (self.world.resources.get_mut::<ShortcutManager>(), &mut self.renderer.debug_view) {
+            shortcuts.retain_commands(|command| {
+                let handled = debug_view.view.apply_command(command);
+                if handled {
+                    log::info!("Debug view: {}", debug_view.view);
+                }
+                !handled
+            });
+        }
+        let fixed_delta = self.game_loop.fixed_delta();
+        let time = self.world.resources.get_or_insert_with(Time::default);
+        *time = Time { delta: delta_time, fixed_delta, alpha: self.game_loop.alpha(), elapsed: self.started.elapsed().as_secs_f64(), frame: time.frame + 1 };
+        let focused = self.world.resources.get::<WindowInfo>().is_none_or(|info| info.focused);
+        self.world.resources.insert(WindowInfo {
+            metrics: self.renderer.display,
+            refresh_rate_hz: self.window_manager.refresh_rate_hz(),
+            focused,
+        });
+        self.world.resources.insert(std::mem::take(&mut self.renderer.scene));
+
+        #[cfg(feature = "hot-reload")]
+        if let Some(game) = &mut self.game {
+            game.poll();
+        }
+        self.schedule.run_stage(Stage::PreUpdate, &mut self.world, delta_time);
+        for _ in 0..update_count {
+            #[cfg(feature = "hot-reload")]
+            if let (Some(game), Some(scene)) = (&mut self.game, self.world.resources.get_mut::<Scene>()) {
+                game.update(scene, fixed_delta);
+            }
+            self.schedule.run_stage(Stage::FixedUpdate, &mut self.world, fixed_delta);
+        }
+        self.schedule.run_stage(Stage::PostUpdate, &mut self.world, delta_time);
+        self.schedule.run_stage(Stage::RenderExtract, &mut self.world, delta_time);
+        if let Some(ui) = self.world.resources.get::<Ui>() {
+            self.renderer.queue_ui(ui);
+        }
+        if let Some(accessibility) = self.world.resources.get::<Accessibility>() {
+            accessibility.settings.apply_display(&mut self.renderer);
+            if let (Some(device), Some(screen_effects)) = (&self.renderer.device, &mut self.renderer.screen_effects) {
+                if let Err(e) = accessibility.settings.apply_filter(screen_effects, device, &self.renderer.resources) {
+                    log::warn!("{}", e);
+                }
+            }
+        }
+        if let (Some(states), Some(device), Some(screen_effects)) =
+            (self.world.resources.get::<StateStack>(), &self.renderer.device, &mut self.renderer.screen_effects)
+        {
+            if let Err(e) = states.apply_transition_effect(screen_effects, device, &self.renderer.resources) {
+                log::warn!("{}", e);
+            }
+        }
+
+        self.renderer.scene = self.world.resources.remove::<Scene>().unwrap_or_default();
+    }
+
+    // Runs `script` in a window as fast as presentation allows, then writes its CSV and exits
+    pub fn with_bench(script: BenchScript) -> Self {
+        let mut app = Self::new();
+        app.power().mode = PowerMode::Performance;
+        app.bench = Some(BenchRun::new(script));
+        app
+    }
+
+    // Takes effect on the next frame; the adapter choice only at startup
+    pub fn set_power_mode(&mut self, mode: PowerMode) {
+        self.power().mode = mode;
+    }
+}
+
+impl Default for VellumApp {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl ApplicationHandler for VellumApp {
@@ -34,42 +192,116 @@ impl ApplicationHandler for VellumApp {
                 event_loop.exit();
                 return;
             }
+            let power = self.power();
+            power.poll(Instant::now());
+            let preference = power.adapter_preference();
+            self.renderer.power_preference = preference;
             if let Some(window) = &self.window_manager.window {
-                if let Err(e) = pollster::block_on(self.renderer.initialize(window.clone())) {
-                    log::error!("Failed to initialize renderer: {}", e);
-                    event_loop.exit();
-                    return;
+                match pollster::block_on(self.renderer.initialize(window.clone())) {
+                    Ok(()) => {
+                        if let Some(gpu) = self.renderer.gpu.as_ref().filter(|gpu| gpu.tier != GpuTier::Full) {
+                            log::warn!("Running on a reduced GPU tier: {}", gpu.tier);
+                        }
+                    }
+                    // Every tier down to the software rasterizer failed
+                    Err(e) => {
+                        log::error!("Failed to initialize renderer: {}", e);
+                        event_loop.exit();
+                    }
                 }
             }
+            if let (Some(bench), Some(window)) = (&mut self.bench, &self.window_manager.window) {
+                let (width, height) = bench.script.resolution;
+                let _ = window.request_inner_size(winit::dpi::PhysicalSize::new(width, height));
+                bench.restart();
+            }
+            if let Some(hz) = self.window_manager.refresh_rate_hz() {
+                log::info!("Pacing frames to {:.2} Hz", hz);
+                self.frame_pacer.set_refresh_rate(hz);
+            }
         }
     }
 
     fn window_event(&mut self, event_loop: &ActiveEventLoop, id: WindowId, event: WindowEvent) {
-        self.input_manager.handle_event(&event);
+        if let Some(input) = InputEvent::from_window_event(&event) {
+            process_input(&mut self.world, input);
+        }
+        self.power().notify_activity(Instant::now());
         match event {
             WindowEvent::Resized(size) => {
                 self.renderer.resize(size.width, size.height);
                 self.window_manager.handle_window_event(event_loop, id, event);
             }
+            WindowEvent::ScaleFactorChanged { scale_factor, .. } => {
+                self.renderer.set_scale_factor(scale_factor);
+                self.window_manager.handle_window_event(event_loop, id, event);
+            }
+            WindowEvent::Focused(focused) => {
+                if let Some(info) = self.world.resources.get_mut::<WindowInfo>() {
+                    info.focused = focused;
+                }
+            }
+            // May have landed on a monitor with a different refresh rate
+            WindowEvent::Moved(_) => {
+                if let Some(hz) = self.window_manager.refresh_rate_hz() {
+                    self.frame_pacer.set_refresh_rate(hz);
+                }
+            }
             _ => self.window_manager.handle_window_event(event_loop, id, event),
         }
 
         // FIXED: Changed from NamedKey::W to KeyCode::KeyW
-        if self.input_manager.is_key_pressed(PhysicalKey::Code(KeyCode::KeyW)) {
+        if self.world.resources.get::<InputManager>().is_some_and(|input| input.is_key_pressed(PhysicalKey::Code(KeyCode::KeyW))) {
             log::info!("W key is pressed!");
         }
     }
 
-    fn about_to_wait(&mut self, _event_loop: &ActiveEventLoop) { // FIXED: Added underscore
-        let (delta_time, update_count) = self.game_loop.tick();
-        for _ in 0..update_count {
-            self.renderer.scene.update(delta_time);
-            if let Some(device) = &self.renderer.device {
-                self.renderer.scene.initialize_buffer(device);
+    fn about_to_wait(&mut self, event_loop: &ActiveEventLoop) {
+        if self.bench.is_some() {
+            // Unpaced and never idle; presentation alone limits the frame rate
+            let (delta_time, update_count) = self.game_loop.tick();
+            self.renderer.resources.begin_frame();
+            self.run_systems(delta_time, update_count);
+            let Some(bench) = &mut self.bench else { return };
+            bench.begin_frame(&mut self.renderer);
+            self.renderer.render(delta_time);
+            bench.end_frame(&self.renderer);
+            if bench.is_finished() {
+                if let Err(e) = bench.finish() {
+                    log::error!("{}", e);
+                }
+                event_loop.exit();
             }
+            event_loop.set_control_flow(ControlFlow::Poll);
+            return;
+        }
+
+        let now = Instant::now();
+        let power = self.power();
+        power.poll(now);
+        if power.is_idle(now) {
+            // Nothing to show until the next event; the simulation clock restarts then
+            // instead of catching up on the whole idle stretch
+            event_loop.set_control_flow(ControlFlow::Wait);
+            self.game_loop.reset();
+            return;
         }
+        let divisor = if self.power().is_low_power() { 2 } else { 1 };
+        self.frame_pacer.set_divisor(divisor);
+
+        // Woken early by input; sleep again until the next frame is due
+        if !self.frame_pacer.is_ready(now) {
+            event_loop.set_control_flow(ControlFlow::WaitUntil(self.frame_pacer.wake_time()));
+            return;
+        }
+        self.frame_pacer.wait();
+        self.frame_pacer.begin_frame(Instant::now());
+
+        let (delta_time, update_count) = self.game_loop.tick();
+        self.renderer.resources.begin_frame();
+        self.run_systems(delta_time, update_count);
         log::info!("Delta time: {:.4}ms, Updates: {}", delta_time * 1000.0, update_count);
-        self.renderer.render();
-        self.window_manager.request_redraw();
+        self.renderer.render(delta_time);
+        event_loop.set_control_flow(ControlFlow::WaitUntil(self.frame_pacer.wake_time()));
     }
 }
\ No newline at end of file
@@ -0,0 +1,257 @@
+// src/atmosphere.rs
+use crate::resource_registry::{ResourceRegistry, Tracked};
+use crate::upload::UploadBelt;
+use glam::{Mat4, Vec3};
+use std::f32::consts::TAU;
+
+// Prepend to a forward fragment shader to get `apply_fog`
+pub const FOG_WGSL: &str = include_str!("fog.wgsl");
+// Bind group index the fog helpers expect, next to the camera at 0
+pub const FOG_GROUP: u32 = 1;
+
+// Mirrors `AtmosphereParams` in fog.wgsl and sky.wgsl
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct AtmosphereParams {
+    inv_view_proj: [f32; 16],
+    camera_position: [f32; 3],
+    fog_density: f32,
+    fog_color: [f32; 3],
+    fog_start: f32,
+    sun_direction: [f32; 3],
+    height_falloff: f32,
+    rayleigh: [f32; 3],
+    fog_base_height: f32,
+    mie: f32,
+    mie_g: f32,
+    sun_intensity: f32,
+    sun_scatter: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FogSettings {
+    pub color: Vec3,
+    // Extinction per world unit at `base_height`; 0.0 disables fog
+    pub density: f32,
+    // Distance from the camera before fog starts
+    pub start: f32,
+    // How quickly fog thins with height; 0.0 gives uniform distance fog
+    pub height_falloff: f32,
+    pub base_height: f32,
+    // Strength of the glow towards the sun
+    pub sun_scatter: f32,
+}
+
+impl Default for FogSettings {
+    fn default() -> Self {
+        Self {
+            color: Vec3::new(0.6, 0.7, 0.8),
+            density: 0.0,
+            start: 0.0,
+            height_falloff: 0.05,
+            base_height: 0.0,
+            sun_scatter: 0.5,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SkySettings {
+    // Direction towards the sun; see `sun_direction` for a day/night cycle
+    pub sun_direction: Vec3,
+    pub sun_intensity: f32,
+    // Scattering coefficients; Rayleigh's blue bias is what makes the sky blue
+    pub rayleigh: Vec3,
+    pub mie: f32,
+    // Mie anisotropy; closer to 1.0 gives a tighter halo around the sun
+    pub mie_g: f32,
+}
+
+impl Default for SkySettings {
+    fn default() -> Self {
+        Self {
+            sun_direction: Vec3::new(0.3, 0.8, 0.2).normalize(),
+            sun_intensity: 20.0,
+            rayleigh: Vec3::new(0.058, 0.135, 0.331),
+            mie: 0.021,
+            mie_g: 0.76,
+        }
+    }
+}
+
+// Per-scene outdoor look. `sky` is optional for indoor scenes, which still get fog.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Atmosphere {
+    pub fog: FogSettings,
+    pub sky: Option<SkySettings>,
+}
+
+impl Atmosphere {
+    // Blends two presets, e.g. noon and dusk; the sky is kept only if both have one
+    pub fn lerp(&self, other: &Atmosphere, t: f32) -> Atmosphere {
+        let (a, b) = (&self.fog, &other.fog);
+        let fog = FogSettings {
+            color: a.color.lerp(b.color, t),
+            density: a.density + (b.density - a.density) * t,
+            start: a.start + (b.start - a.start) * t,
+            height_falloff: a.height_falloff + (b.height_falloff - a.height_falloff) * t,
+            base_height: a.base_height + (b.base_height - a.base_height) * t,
+            sun_scatter: a.sun_scatter + (b.sun_scatter - a.sun_scatter) * t,
+        };
+        let sky = match (self.sky, other.sky) {
+            (Some(a), Some(b)) => Some(SkySettings {
+                sun_direction: a.sun_direction.slerp(b.sun_direction, t),
+                sun_intensity: a.sun_intensity + (b.sun_intensity - a.sun_intensity) * t,
+                rayleigh: a.rayleigh.lerp(b.rayleigh, t),
+                mie: a.mie + (b.mie - a.mie) * t,
+                mie_g: a.mie_g + (b.mie_g - a.mie_g) * t,
+            }),
+            (a, b) => if t < 0.5 { a } else { b },
+        };
+        Atmosphere { fog, sky }
+    }
+}
+
+// Sun direction for `hours` since midnight: rises in +X, sets in -X, and peaks at noon at
+// `max_elevation` radians above the horizon
+pub fn sun_direction(hours: f32, max_elevation: f32) -> Vec3 {
+    let angle = (hours / 24.0 - 0.25) * TAU;
+    let height = angle.sin();
+    Vec3::new(angle.cos(), height * max_elevation.sin(), height * max_elevation.cos()).normalize()
+}
+
+// Uploads an `Atmosphere` for the fog helpers and draws the optional sky
+pub struct AtmosphereRenderer {
+    params_buffer: Tracked<wgpu::Buffer>,
+    layout: wgpu::BindGroupLayout,
+    bind_group: wgpu::BindGroup,
+    sky_pipeline: wgpu::RenderPipeline,
+    sky_enabled: bool,
+}
+
+impl AtmosphereRenderer {
+    // `depth_format` must match the pass the sky is drawn in
+    pub fn new(
+        device: &wgpu::Device,
+        resources: &ResourceRegistry,
+        color_format: wgpu::TextureFormat,
+        depth_format: Option<wgpu::TextureFormat>,
+    ) -> Self {
+        let params_buffer = resources.create_buffer(device, &wgpu::BufferDescriptor {
+            label: Some("atmosphere params"),
+            size: std::mem::size_of::<AtmosphereParams>() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        }, "atmosphere");
+        let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("atmosphere"),
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+        });
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("atmosphere"),
+            layout: &layout,
+            entries: &[wgpu::BindGroupEntry { binding: 0, resource: params_buffer.as_entire_binding() }],
+        });
+
+        let shader = device.create_shader_module(wgpu::include_wgsl!("sky.wgsl"));
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("sky"),
+            bind_group_layouts: &[&layout],
+            push_constant_ranges: &[],
+        });
+        let sky_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("sky"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: Some("vs_sky"),
+                buffers: &[],
+                compilation_options: Default::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: Some("fs_sky"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: color_format,
+                    blend: None,
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: Default::default(),
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            // At the far plane, so it only fills pixels no geometry covered
+            depth_stencil: depth_format.map(|format| wgpu::DepthStencilState {
+                format,
+                depth_write_enabled: false,
+                depth_compare: wgpu::CompareFunction::LessEqual,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        });
+
+        Self { params_buffer, layout, bind_group, sky_pipeline, sky_enabled: false }
+    }
+
+    // For forward pipeline layouts, at `FOG_GROUP`
+    pub fn layout(&self) -> &wgpu::BindGroupLayout {
+        &self.layout
+    }
+
+    pub fn bind_group(&self) -> &wgpu::BindGroup {
+        &self.bind_group
+    }
+
+    // Call every frame the camera or atmosphere changes
+    pub fn update(
+        &mut self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        belt: &mut UploadBelt,
+        atmosphere: &Atmosphere,
+        camera_position: Vec3,
+        view_proj: Mat4,
+    ) {
+        let fog = &atmosphere.fog;
+        let sky = atmosphere.sky.unwrap_or_default();
+        self.sky_enabled = atmosphere.sky.is_some();
+        let params = AtmosphereParams {
+            inv_view_proj: view_proj.inverse().to_cols_array(),
+            camera_position: camera_position.into(),
+            fog_density: fog.density,
+            fog_color: fog.color.into(),
+            fog_start: fog.start,
+            sun_direction: sky.sun_direction.normalize_or(Vec3::Y).into(),
+            height_falloff: fog.height_falloff,
+            rayleigh: sky.rayleigh.into(),
+            fog_base_height: fog.base_height,
+            mie: sky.mie,
+            mie_g: sky.mie_g,
+            sun_intensity: sky.sun_intensity,
+            // Without a sky there's no sun to glow towards
+            sun_scatter: if self.sky_enabled { fog.sun_scatter } else { 0.0 },
+        };
+        belt.write(device, encoder, &self.params_buffer, 0, bytemuck::bytes_of(&params));
+    }
+
+    // Draw after opaque geometry (or first, with no depth attachment); no-op without a sky
+    pub fn draw_sky(&self, render_pass: &mut wgpu::RenderPass<'_>) {
+        if !self.sky_enabled {
+            return;
+        }
+        render_pass.set_pipeline(&self.sky_pipeline);
+        render_pass.set_bind_group(0, &self.bind_group, &[]);
+        render_pass.draw(0..3, 0..1);
+    }
+}
@@ -0,0 +1,273 @@
+// src/bench.rs
+use crate::game_loop::GameLoop;
+use crate::render_texture::ViewCamera;
+use crate::renderer::Renderer;
+use glam::Vec3;
+use serde::Deserialize;
+use std::fmt::Write as _;
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct CameraKey {
+    // Seconds since the start of the run, warmup included
+    pub time: f32,
+    pub position: [f32; 3],
+    pub target: [f32; 3],
+}
+
+// A benchmark run, loaded from RON and started with `--bench path`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct BenchScript {
+    pub name: String,
+    // Seconds recorded after the warmup
+    pub duration: f32,
+    // Seconds run but not recorded, while pipelines and caches settle
+    pub warmup: f32,
+    // Render offscreen without a window
+    pub headless: bool,
+    pub resolution: (u32, u32),
+    pub fov_y_degrees: f32,
+    // Keys sorted by time; the camera holds still outside them
+    pub camera_path: Vec<CameraKey>,
+    // CSV destination; defaults to `bench_<name>.csv`
+    pub output: Option<String>,
+}
+
+impl Default for BenchScript {
+    fn default() -> Self {
+        Self {
+            name: "bench".to_string(),
+            duration: 10.0,
+            warmup: 1.0,
+            headless: false,
+            resolution: (1280, 720),
+            fov_y_degrees: 60.0,
+            camera_path: Vec::new(),
+            output: None,
+        }
+    }
+}
+
+impl BenchScript {
+    pub fn from_ron(source: &str) -> Result<Self, String> {
+        let script: Self = ron::from_str(source).map_err(|e| format!("Failed to parse bench script: {}", e))?;
+        if !script.duration.is_finite() || script.duration <= 0.0 {
+            return Err(format!("Bench script '{}' needs a positive duration", script.name));
+        }
+        if script.camera_path.windows(2).any(|keys| keys[1].time < keys[0].time) {
+            return Err(format!("Bench script '{}' has camera keys out of time order", script.name));
+        }
+        Ok(script)
+    }
+
+    pub fn load(path: &str) -> Result<Self, String> {
+        let source = std::fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+        Self::from_ron(&source)
+    }
+
+    pub fn output_path(&self) -> String {
+        self.output.clone().unwrap_or_else(|| format!("bench_{}.csv", self.name))
+    }
+
+    // Linear interpolation along the path at `time` seconds
+    pub fn camera_at(&self, time: f32, aspect: f32) -> ViewCamera {
+        let (position, target) = match self.camera_path.iter().position(|key| key.time > time) {
+            None => self.camera_path.last().map_or((Vec3::new(0.0, 0.0, 5.0), Vec3::ZERO), |key| {
+                (Vec3::from(key.position), Vec3::from(key.target))
+            }),
+            Some(0) => {
+                let key = self.camera_path[0];
+                (Vec3::from(key.position), Vec3::from(key.target))
+            }
+            Some(next) => {
+                let (a, b) = (self.camera_path[next - 1], self.camera_path[next]);
+                let t = ((time - a.time) / (b.time - a.time).max(f32::EPSILON)).clamp(0.0, 1.0);
+                (
+                    Vec3::from(a.position).lerp(Vec3::from(b.position), t),
+                    Vec3::from(a.target).lerp(Vec3::from(b.target), t),
+                )
+            }
+        };
+        ViewCamera::perspective(position, target, self.fov_y_degrees.to_radians(), aspect, 0.1, 1000.0)
+    }
+}
+
+// One CSV row
+#[derive(Debug, Clone, Copy)]
+pub struct FrameSample {
+    pub frame: u32,
+    // Seconds since recording started
+    pub time: f32,
+    pub frame_ms: f32,
+    pub draw_calls: u32,
+    pub live_bytes: u64,
+    pub live_resources: u32,
+    pub camera: Vec3,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct BenchSummary {
+    pub frames: u32,
+    pub avg_ms: f32,
+    pub min_ms: f32,
+    pub max_ms: f32,
+    pub p99_ms: f32,
+    pub peak_bytes: u64,
+}
+
+#[derive(Default)]
+pub struct BenchRecorder {
+    samples: Vec<FrameSample>,
+    peak_bytes: u64,
+}
+
+impl BenchRecorder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record(&mut self, sample: FrameSample) {
+        self.peak_bytes = self.peak_bytes.max(sample.live_bytes);
+        self.samples.push(sample);
+    }
+
+    pub fn samples(&self) -> &[FrameSample] {
+        &self.samples
+    }
+
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("frame,time_s,frame_ms,draw_calls,live_bytes,live_resources,camera_x,camera_y,camera_z\n");
+        for s in &self.samples {
+            let _ = writeln!(
+                csv,
+                "{},{:.4},{:.3},{},{},{},{:.3},{:.3},{:.3}",
+                s.frame, s.time, s.frame_ms, s.draw_calls, s.live_bytes, s.live_resources, s.camera.x, s.camera.y, s.camera.z
+            );
+        }
+        csv
+    }
+
+    pub fn write_csv(&self, path: &str) -> Result<(), String> {
+        std::fs::write(path, self.to_csv()).map_err(|e| format!("Failed to write {}: {}", path, e))
+    }
+
+    pub fn summary(&self) -> BenchSummary {
+        let mut times: Vec<f32> = self.samples.iter().map(|s| s.frame_ms).collect();
+        times.sort_by(f32::total_cmp);
+        let frames = times.len() as u32;
+        let percentile = |p: f32| times.get(((times.len() as f32 * p).ceil() as usize).saturating_sub(1)).copied().unwrap_or(0.0);
+        BenchSummary {
+            frames,
+            avg_ms: if times.is_empty() { 0.0 } else { times.iter().sum::<f32>() / frames as f32 },
+            min_ms: times.first().copied().unwrap_or(0.0),
+            max_ms: times.last().copied().unwrap_or(0.0),
+            p99_ms: percentile(0.99),
+            peak_bytes: self.peak_bytes,
+        }
+    }
+}
+
+// Drives a script frame by frame, for both the windowed app and `run_headless`
+pub struct BenchRun {
+    pub script: BenchScript,
+    pub recorder: BenchRecorder,
+    // Camera for the current frame; game code renders with it
+    pub camera: ViewCamera,
+    start: Instant,
+    last_frame: Instant,
+    frame: u32,
+}
+
+impl BenchRun {
+    pub fn new(script: BenchScript) -> Self {
+        let (width, height) = script.resolution;
+        let camera = script.camera_at(0.0, width.max(1) as f32 / height.max(1) as f32);
+        let now = Instant::now();
+        Self { script, recorder: BenchRecorder::new(), camera, start: now, last_frame: now, frame: 0 }
+    }
+
+    // Call once the renderer is ready, so device creation isn't timed
+    pub fn restart(&mut self) {
+        self.start = Instant::now();
+        self.last_frame = self.start;
+        self.frame = 0;
+    }
+
+    pub fn elapsed(&self) -> f32 {
+        self.start.elapsed().as_secs_f32()
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.elapsed() >= self.script.warmup + self.script.duration
+    }
+
+    // Call right before `Renderer::render`; moves the camera along the path and renders
+    // the frame from it
+    pub fn begin_frame(&mut self, renderer: &mut Renderer) {
+        let (width, height) = (renderer.display.physical_width, renderer.display.physical_height);
+        self.camera = self.script.camera_at(self.elapsed(), width.max(1) as f32 / height.max(1) as f32);
+        renderer.camera = self.camera;
+    }
+
+    // Call after `Renderer::render`. Frame time is from end of frame to end of frame, so it
+    // includes waiting on the GPU wherever the frame blocked on it.
+    pub fn end_frame(&mut self, renderer: &Renderer) {
+        let now = Instant::now();
+        let frame_time = now.duration_since(self.last_frame);
+        self.last_frame = now;
+        let time = now.duration_since(self.start).as_secs_f32() - self.script.warmup;
+        if time < 0.0 {
+            return;
+        }
+        let stats = renderer.resource_stats();
+        self.recorder.record(FrameSample {
+            frame: self.frame,
+            time,
+            frame_ms: frame_time.as_secs_f32() * 1000.0,
+            draw_calls: renderer.draw_calls,
+            live_bytes: stats.live_bytes,
+            live_resources: stats.live_count,
+            camera: self.camera.position(),
+        });
+        self.frame += 1;
+    }
+
+    // Writes the CSV and logs the summary
+    pub fn finish(&self) -> Result<BenchSummary, String> {
+        let path = self.script.output_path();
+        self.recorder.write_csv(&path)?;
+        let summary = self.recorder.summary();
+        log::info!(
+            "Bench '{}': {} frames, avg {:.2}ms, min {:.2}ms, max {:.2}ms, p99 {:.2}ms, peak {} bytes -> {}",
+            self.script.name, summary.frames, summary.avg_ms, summary.min_ms, summary.max_ms, summary.p99_ms,
+            summary.peak_bytes, path
+        );
+        Ok(summary)
+    }
+}
+
+// Runs `script` without a window or event loop. Each frame waits for the GPU, since there
+// is no swapchain to throttle submissions.
+pub fn run_headless(script: BenchScript) -> Result<BenchSummary, String> {
+    let mut renderer = Renderer::new();
+    let (width, height) = script.resolution;
+    pollster::block_on(renderer.initialize_headless(width, height))?;
+    let mut game_loop = GameLoop::new(60.0);
+    let mut run = BenchRun::new(script);
+    run.restart();
+    while !run.is_finished() {
+        let (delta_time, update_count) = game_loop.tick();
+        renderer.resources.begin_frame();
+        for _ in 0..update_count {
+            renderer.scene.update(game_loop.fixed_delta());
+        }
+        run.begin_frame(&mut renderer);
+        renderer.render(delta_time);
+        if let Some(device) = &renderer.device {
+            let _ = device.poll(wgpu::PollType::Wait { submission_index: None, timeout: Some(Duration::from_secs(5)) });
+        }
+        run.end_frame(&renderer);
+    }
+    run.finish()
+}
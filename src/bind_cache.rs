@@ -0,0 +1,164 @@
+// src/bind_cache.rs
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum BindingKey {
+    Buffer { buffer: wgpu::Buffer, offset: u64, size: Option<u64> },
+    TextureView(wgpu::TextureView),
+    Sampler(wgpu::Sampler),
+}
+
+impl BindingKey {
+    pub fn buffer(buffer: &wgpu::Buffer) -> Self {
+        Self::Buffer { buffer: buffer.clone(), offset: 0, size: None }
+    }
+
+    fn resource(&self) -> wgpu::BindingResource<'_> {
+        match self {
+            Self::Buffer { buffer, offset, size } => wgpu::BindingResource::Buffer(wgpu::BufferBinding {
+                buffer,
+                offset: *offset,
+                size: size.and_then(wgpu::BufferSize::new),
+            }),
+            Self::TextureView(view) => wgpu::BindingResource::TextureView(view),
+            Self::Sampler(sampler) => wgpu::BindingResource::Sampler(sampler),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct BindGroupKey {
+    layout: wgpu::BindGroupLayout,
+    bindings: Vec<(u32, BindingKey)>,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub evictions: u64,
+    pub entries: usize,
+}
+
+// Reuses bind groups with identical layout + resources. Cached entries keep their
+// resources alive, so old ones are evicted least-recently-used once over capacity.
+pub struct BindGroupCache {
+    entries: HashMap<BindGroupKey, (wgpu::BindGroup, u64)>,
+    capacity: usize,
+    clock: u64,
+    stats: CacheStats,
+}
+
+impl BindGroupCache {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            entries: HashMap::new(),
+            capacity: capacity.max(1),
+            clock: 0,
+            stats: CacheStats::default(),
+        }
+    }
+
+    pub fn get_or_create(
+        &mut self,
+        device: &wgpu::Device,
+        label: &str,
+        layout: &wgpu::BindGroupLayout,
+        bindings: &[(u32, BindingKey)],
+    ) -> wgpu::BindGroup {
+        self.clock += 1;
+        let key = BindGroupKey { layout: layout.clone(), bindings: bindings.to_vec() };
+        if let Some((bind_group, last_used)) = self.entries.get_mut(&key) {
+            *last_used = self.clock;
+            self.stats.hits += 1;
+            return bind_group.clone();
+        }
+
+        self.stats.misses += 1;
+        if self.entries.len() >= self.capacity {
+            self.evict_oldest();
+        }
+        let entries: Vec<wgpu::BindGroupEntry> = key
+            .bindings
+            .iter()
+            .map(|(binding, resource)| wgpu::BindGroupEntry { binding: *binding, resource: resource.resource() })
+            .collect();
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some(label),
+            layout,
+            entries: &entries,
+        });
+        self.entries.insert(key, (bind_group.clone(), self.clock));
+        bind_group
+    }
+
+    fn evict_oldest(&mut self) {
+        let oldest = self.entries.iter().min_by_key(|(_, (_, last_used))| *last_used).map(|(key, _)| key.clone());
+        if let Some(key) = oldest {
+            self.entries.remove(&key);
+            self.stats.evictions += 1;
+        }
+    }
+
+    // Drops every cached bind group, e.g. after resources were recreated on resize
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        CacheStats { entries: self.entries.len(), ..self.stats }
+    }
+}
+
+// SamplerDescriptor has float fields, so it's keyed by their bit patterns
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct SamplerKey {
+    address_modes: [wgpu::AddressMode; 3],
+    filters: [wgpu::FilterMode; 3],
+    lod_clamp: [u32; 2],
+    compare: Option<wgpu::CompareFunction>,
+    anisotropy_clamp: u16,
+    border_color: Option<wgpu::SamplerBorderColor>,
+}
+
+impl SamplerKey {
+    fn new(desc: &wgpu::SamplerDescriptor) -> Self {
+        Self {
+            address_modes: [desc.address_mode_u, desc.address_mode_v, desc.address_mode_w],
+            filters: [desc.mag_filter, desc.min_filter, desc.mipmap_filter],
+            lod_clamp: [desc.lod_min_clamp.to_bits(), desc.lod_max_clamp.to_bits()],
+            compare: desc.compare,
+            anisotropy_clamp: desc.anisotropy_clamp,
+            border_color: desc.border_color,
+        }
+    }
+}
+
+// There are only ever a handful of distinct samplers, so these are never evicted
+#[derive(Default)]
+pub struct SamplerCache {
+    samplers: HashMap<SamplerKey, wgpu::Sampler>,
+    stats: CacheStats,
+}
+
+impl SamplerCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get(&mut self, device: &wgpu::Device, desc: &wgpu::SamplerDescriptor) -> wgpu::Sampler {
+        let key = SamplerKey::new(desc);
+        if let Some(sampler) = self.samplers.get(&key) {
+            self.stats.hits += 1;
+            return sampler.clone();
+        }
+        self.stats.misses += 1;
+        let sampler = device.create_sampler(desc);
+        self.samplers.insert(key, sampler.clone());
+        sampler
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        CacheStats { entries: self.samplers.len(), ..self.stats }
+    }
+}
@@ -0,0 +1,193 @@
+// src/camera/blend.rs
+use super::{CameraPose, ClearMode};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct VirtualCameraId(u32);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BlendCurve {
+    // Instant switch
+    Cut,
+    Linear,
+    #[default]
+    EaseInOut,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CameraBlend {
+    pub curve: BlendCurve,
+    pub duration: f32,
+}
+
+impl CameraBlend {
+    pub const CUT: CameraBlend = CameraBlend { curve: BlendCurve::Cut, duration: 0.0 };
+
+    pub fn ease(duration: f32) -> Self {
+        Self { curve: BlendCurve::EaseInOut, duration }
+    }
+
+    fn weight(&self, elapsed: f32) -> f32 {
+        if self.curve == BlendCurve::Cut || self.duration <= 0.0 {
+            return 1.0;
+        }
+        let t = (elapsed / self.duration).clamp(0.0, 1.0);
+        match self.curve {
+            BlendCurve::EaseInOut => t * t * (3.0 - 2.0 * t),
+            _ => t,
+        }
+    }
+}
+
+// A camera the brain may pick from. Whatever drives it (a controller, a cutscene track)
+// writes `pose` each update; the brain decides whether it is shown.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct VirtualCamera {
+    pub pose: CameraPose,
+    // The enabled camera with the highest priority is live; ties go to the newest
+    pub priority: i32,
+    pub enabled: bool,
+    // How to blend when this camera goes live; `None` uses the brain's default
+    pub blend_in: Option<CameraBlend>,
+    pub clear: ClearMode,
+}
+
+impl VirtualCamera {
+    pub fn new(pose: CameraPose, priority: i32) -> Self {
+        Self { pose, priority, enabled: true, blend_in: None, clear: ClearMode::default() }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum BlendSource {
+    // Follows the outgoing camera as it keeps moving
+    Camera(VirtualCameraId),
+    // Frozen pose, when the outgoing camera is gone or a blend was interrupted
+    Pose(CameraPose),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct ActiveBlend {
+    from: BlendSource,
+    // Last pose seen from `from`, in case the camera is removed mid-blend
+    from_pose: CameraPose,
+    blend: CameraBlend,
+    elapsed: f32,
+}
+
+// Picks the live virtual camera by priority and blends between cameras when that
+// changes, so a cutscene camera can raise its priority to take over from gameplay and
+// hand back smoothly when it is disabled. Position, rotation, zoom and FOV all blend.
+#[derive(Debug, Clone)]
+pub struct CameraBrain {
+    pub default_blend: CameraBlend,
+    cameras: Vec<Option<VirtualCamera>>,
+    live: Option<VirtualCameraId>,
+    blend: Option<ActiveBlend>,
+    output: CameraPose,
+}
+
+impl Default for CameraBrain {
+    fn default() -> Self {
+        Self { default_blend: CameraBlend::ease(1.0), cameras: Vec::new(), live: None, blend: None, output: CameraPose::default() }
+    }
+}
+
+impl CameraBrain {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&mut self, camera: VirtualCamera) -> VirtualCameraId {
+        self.cameras.push(Some(camera));
+        VirtualCameraId(self.cameras.len() as u32 - 1)
+    }
+
+    pub fn remove(&mut self, id: VirtualCameraId) -> Option<VirtualCamera> {
+        self.cameras.get_mut(id.0 as usize)?.take()
+    }
+
+    pub fn get(&self, id: VirtualCameraId) -> Option<&VirtualCamera> {
+        self.cameras.get(id.0 as usize)?.as_ref()
+    }
+
+    pub fn get_mut(&mut self, id: VirtualCameraId) -> Option<&mut VirtualCamera> {
+        self.cameras.get_mut(id.0 as usize)?.as_mut()
+    }
+
+    pub fn live(&self) -> Option<VirtualCameraId> {
+        self.live
+    }
+
+    pub fn is_blending(&self) -> bool {
+        self.blend.is_some()
+    }
+
+    // The pose to render with, as of the last `update`
+    pub fn output(&self) -> CameraPose {
+        self.output
+    }
+
+    // The live camera's clear mode; it takes over at the start of a blend, not the end
+    pub fn clear(&self) -> ClearMode {
+        self.live.and_then(|id| self.get(id)).map(|c| c.clear).unwrap_or_default()
+    }
+
+    fn highest(&self) -> Option<VirtualCameraId> {
+        self.cameras
+            .iter()
+            .enumerate()
+            .filter_map(|(i, c)| c.as_ref().filter(|c| c.enabled).map(|c| (c.priority, i)))
+            .max()
+            .map(|(_, i)| VirtualCameraId(i as u32))
+    }
+
+    fn source_pose(&self, source: BlendSource) -> Option<CameraPose> {
+        match source {
+            BlendSource::Camera(id) => self.get(id).map(|c| c.pose),
+            BlendSource::Pose(pose) => Some(pose),
+        }
+    }
+
+    // Call after the virtual cameras' poses are updated
+    pub fn update(&mut self, delta_time: f32) -> CameraPose {
+        let next = self.highest();
+        if next != self.live {
+            let blend = next.and_then(|id| self.get(id)?.blend_in).unwrap_or(self.default_blend);
+            let from = match (self.live, self.blend) {
+                // A blend cut short continues from wherever it had got to
+                (_, Some(_)) => Some(BlendSource::Pose(self.output)),
+                (Some(id), None) if self.get(id).is_some() => Some(BlendSource::Camera(id)),
+                (Some(_), None) => Some(BlendSource::Pose(self.output)),
+                // Nothing was live before: start on the new camera
+                (None, None) => None,
+            };
+            let from_pose = self.output;
+            self.blend = from.map(|from| ActiveBlend { from, from_pose, blend, elapsed: 0.0 });
+            self.live = next;
+        }
+
+        let Some(target) = self.live.and_then(|id| self.get(id)).map(|c| c.pose) else {
+            // Nothing enabled: hold the last pose
+            self.blend = None;
+            return self.output;
+        };
+        let from_pose = self.blend.and_then(|active| self.source_pose(active.from));
+        self.output = match self.blend.as_mut() {
+            Some(active) => {
+                active.elapsed += delta_time;
+                if let Some(pose) = from_pose {
+                    active.from_pose = pose;
+                }
+                let weight = active.blend.weight(active.elapsed);
+                if weight >= 1.0 {
+                    self.blend = None;
+                    target
+                } else {
+                    active.from_pose.lerp(&target, weight)
+                }
+            }
+            None => target,
+        };
+        self.output
+    }
+}
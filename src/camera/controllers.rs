@@ -0,0 +1,260 @@
+// src/camera/controllers.rs
+use super::CameraPose;
+use crate::procgen::noise::Noise;
+use glam::{EulerRot, Quat, Vec2, Vec3};
+
+// Fraction of the remaining distance to close this step when easing with time constant
+// `smoothing` seconds; frame-rate independent, and 0 snaps
+fn approach(smoothing: f32, delta_time: f32) -> f32 {
+    if smoothing <= 0.0 { 1.0 } else { 1.0 - (-delta_time / smoothing).exp() }
+}
+
+// Region the camera may show, e.g. a level's extent. Views larger than the bounds are
+// centred on them.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CameraBounds {
+    pub min: Vec3,
+    pub max: Vec3,
+}
+
+impl CameraBounds {
+    pub fn new_2d(min: Vec2, max: Vec2) -> Self {
+        Self { min: min.extend(f32::MIN), max: max.extend(f32::MAX) }
+    }
+
+    // Keeps a 2D view of `half_view` extents inside the bounds
+    pub fn confine_2d(&self, center: Vec2, half_view: Vec2) -> Vec2 {
+        let (min, max) = (self.min.truncate() + half_view, self.max.truncate() - half_view);
+        let mid = (self.min.truncate() + self.max.truncate()) * 0.5;
+        Vec2::new(
+            if min.x > max.x { mid.x } else { center.x.clamp(min.x, max.x) },
+            if min.y > max.y { mid.y } else { center.y.clamp(min.y, max.y) },
+        )
+    }
+
+    // Keeps a 3D camera's position inside the box
+    pub fn confine(&self, position: Vec3) -> Vec3 {
+        position.clamp(self.min, self.max)
+    }
+}
+
+// 2D camera that ignores small movements of its target: the target can wander inside the
+// dead zone, and the camera eases after it once it leaves
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FollowCamera2D {
+    pub position: Vec2,
+    pub zoom: f32,
+    // Half size of the box around the camera centre the target moves freely in
+    pub dead_zone: Vec2,
+    // Seconds to close most of the gap; 0 follows rigidly
+    pub smoothing: f32,
+    // Shifts the view towards where the target is heading, in seconds of its velocity
+    pub look_ahead: f32,
+    pub bounds: Option<CameraBounds>,
+    // World area shown at zoom 1, used with `bounds`
+    pub view_size: Vec2,
+    last_target: Option<Vec2>,
+}
+
+impl FollowCamera2D {
+    pub fn new(position: Vec2, view_size: Vec2) -> Self {
+        Self {
+            position,
+            zoom: 1.0,
+            dead_zone: Vec2::ZERO,
+            smoothing: 0.15,
+            look_ahead: 0.0,
+            bounds: None,
+            view_size,
+            last_target: None,
+        }
+    }
+
+    pub fn update(&mut self, target: Vec2, delta_time: f32) {
+        let velocity = match self.last_target {
+            Some(last) if delta_time > 0.0 => (target - last) / delta_time,
+            _ => Vec2::ZERO,
+        };
+        self.last_target = Some(target);
+        let focus = target + velocity * self.look_ahead;
+        // Only the part of the offset outside the dead zone pulls the camera
+        let offset = focus - self.position;
+        let outside = offset - offset.clamp(-self.dead_zone, self.dead_zone);
+        self.position += outside * approach(self.smoothing, delta_time);
+        if let Some(bounds) = self.bounds {
+            self.position = bounds.confine_2d(self.position, self.view_size / (2.0 * self.zoom.max(f32::EPSILON)));
+        }
+    }
+
+    // Jumps straight to `target`, e.g. on level load
+    pub fn snap(&mut self, target: Vec2) {
+        self.position = target;
+        self.last_target = Some(target);
+        if let Some(bounds) = self.bounds {
+            self.position = bounds.confine_2d(self.position, self.view_size / (2.0 * self.zoom.max(f32::EPSILON)));
+        }
+    }
+
+    pub fn pose(&self) -> CameraPose {
+        CameraPose { zoom: self.zoom, ..CameraPose::at_2d(self.position) }
+    }
+}
+
+// 3D camera circling a target, e.g. third-person or model viewers. Input sets the desired
+// angles and distance; `update` eases towards them.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OrbitCamera {
+    pub target: Vec3,
+    // Radians; yaw 0 looks down -Z, positive pitch looks down on the target
+    pub yaw: f32,
+    pub pitch: f32,
+    pub distance: f32,
+    pub pitch_limits: (f32, f32),
+    pub distance_limits: (f32, f32),
+    pub fov_y: f32,
+    pub smoothing: f32,
+    pub bounds: Option<CameraBounds>,
+    current: (f32, f32, f32),
+}
+
+impl OrbitCamera {
+    pub fn new(target: Vec3, distance: f32) -> Self {
+        let pitch = 0.3;
+        Self {
+            target,
+            yaw: 0.0,
+            pitch,
+            distance,
+            pitch_limits: (-1.4, 1.4),
+            distance_limits: (0.5, 100.0),
+            fov_y: 60f32.to_radians(),
+            smoothing: 0.08,
+            bounds: None,
+            current: (0.0, pitch, distance),
+        }
+    }
+
+    // Radians, e.g. mouse delta times a sensitivity
+    pub fn rotate(&mut self, yaw: f32, pitch: f32) {
+        self.yaw += yaw;
+        self.pitch = (self.pitch + pitch).clamp(self.pitch_limits.0, self.pitch_limits.1);
+    }
+
+    // Multiplies the distance, e.g. 0.9 per scroll notch in
+    pub fn zoom(&mut self, factor: f32) {
+        self.distance = (self.distance * factor).clamp(self.distance_limits.0, self.distance_limits.1);
+    }
+
+    pub fn update(&mut self, delta_time: f32) {
+        let t = approach(self.smoothing, delta_time);
+        let (yaw, pitch, distance) = self.current;
+        self.current = (yaw + (self.yaw - yaw) * t, pitch + (self.pitch - pitch) * t, distance + (self.distance - distance) * t);
+    }
+
+    pub fn pose(&self) -> CameraPose {
+        let (yaw, pitch, distance) = self.current;
+        let rotation = Quat::from_euler(EulerRot::YXZ, yaw, -pitch, 0.0);
+        let mut position = self.target - rotation * Vec3::NEG_Z * distance;
+        if let Some(bounds) = self.bounds {
+            position = bounds.confine(position);
+        }
+        CameraPose { fov_y: self.fov_y, ..CameraPose::looking_at(position, self.target) }
+    }
+}
+
+// Free-flying debug and editor camera
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FlyCamera {
+    pub position: Vec3,
+    // Radians; yaw 0 looks down -Z
+    pub yaw: f32,
+    pub pitch: f32,
+    // Units per second, multiplied by `boost` while boosting
+    pub speed: f32,
+    pub boost: f32,
+    pub fov_y: f32,
+    pub bounds: Option<CameraBounds>,
+}
+
+impl FlyCamera {
+    pub fn new(position: Vec3) -> Self {
+        Self { position, yaw: 0.0, pitch: 0.0, speed: 5.0, boost: 4.0, fov_y: 60f32.to_radians(), bounds: None }
+    }
+
+    // Radians, e.g. mouse delta times a sensitivity
+    pub fn look(&mut self, yaw: f32, pitch: f32) {
+        self.yaw += yaw;
+        self.pitch = (self.pitch + pitch).clamp(-1.55, 1.55);
+    }
+
+    // `input` is in camera space: x right, y up, z forward, each -1..1
+    pub fn update(&mut self, input: Vec3, boosting: bool, delta_time: f32) {
+        let rotation = self.rotation();
+        let direction = rotation * Vec3::X * input.x + Vec3::Y * input.y + rotation * Vec3::NEG_Z * input.z;
+        let speed = if boosting { self.speed * self.boost } else { self.speed };
+        self.position += direction.clamp_length_max(1.0) * speed * delta_time;
+        if let Some(bounds) = self.bounds {
+            self.position = bounds.confine(self.position);
+        }
+    }
+
+    fn rotation(&self) -> Quat {
+        Quat::from_euler(EulerRot::YXZ, self.yaw, self.pitch, 0.0)
+    }
+
+    pub fn pose(&self) -> CameraPose {
+        CameraPose { position: self.position, rotation: self.rotation(), fov_y: self.fov_y, zoom: 1.0 }
+    }
+}
+
+// Trauma-based shake: hits add trauma, which decays over time, and the shake grows with
+// its square so small knocks stay subtle. Smooth noise rather than random jumps.
+#[derive(Debug, Clone)]
+pub struct ScreenShake {
+    // 0..1
+    pub trauma: f32,
+    // Trauma lost per second
+    pub decay: f32,
+    // Offset at full trauma, in world units
+    pub max_offset: Vec2,
+    // Roll at full trauma, radians
+    pub max_roll: f32,
+    // Noise speed; higher is more jittery
+    pub frequency: f32,
+    noise: Noise,
+    time: f32,
+}
+
+impl ScreenShake {
+    pub fn new(max_offset: Vec2, max_roll: f32) -> Self {
+        Self { trauma: 0.0, decay: 1.5, max_offset, max_roll, frequency: 25.0, noise: Noise::new(0x5eed), time: 0.0 }
+    }
+
+    pub fn add_trauma(&mut self, amount: f32) {
+        self.trauma = (self.trauma + amount).clamp(0.0, 1.0);
+    }
+
+    pub fn update(&mut self, delta_time: f32) {
+        self.time += delta_time;
+        self.trauma = (self.trauma - self.decay * delta_time).max(0.0);
+    }
+
+    // Current (offset, roll)
+    pub fn sample(&self) -> (Vec2, f32) {
+        let shake = self.trauma * self.trauma;
+        if shake == 0.0 {
+            return (Vec2::ZERO, 0.0);
+        }
+        let t = self.time * self.frequency;
+        // Rows sit between lattice lines, where Perlin noise is never pinned to zero
+        let channel = |row: f32| self.noise.perlin(Vec2::new(t, row * 17.0 + 0.5));
+        (Vec2::new(channel(0.0), channel(1.0)) * self.max_offset * shake, channel(2.0) * self.max_roll * shake)
+    }
+
+    // Shakes `pose` in its own screen plane
+    pub fn apply(&self, pose: &mut CameraPose) {
+        let (offset, roll) = self.sample();
+        pose.position += pose.rotation * offset.extend(0.0);
+        pose.rotation *= Quat::from_rotation_z(roll);
+    }
+}
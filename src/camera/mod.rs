@@ -0,0 +1,137 @@
+// src/camera/mod.rs
+pub mod blend;
+pub mod controllers;
+pub mod viewport;
+
+use crate::atmosphere::{Atmosphere, AtmosphereRenderer};
+use crate::color::Color;
+use crate::render_texture::ViewCamera;
+use glam::{Mat4, Quat, Vec2, Vec3};
+
+// Where a camera is and how it sees, shared by 2D and 3D controllers. 2D cameras use
+// `position.xy` and `zoom`; 3D cameras use `rotation` and `fov_y`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CameraPose {
+    pub position: Vec3,
+    // Looks down -Z with +Y up, like `Mat4::look_at_rh`
+    pub rotation: Quat,
+    // Radians
+    pub fov_y: f32,
+    // Magnification for orthographic views; 2 shows half as much
+    pub zoom: f32,
+}
+
+impl Default for CameraPose {
+    fn default() -> Self {
+        Self { position: Vec3::ZERO, rotation: Quat::IDENTITY, fov_y: 60f32.to_radians(), zoom: 1.0 }
+    }
+}
+
+impl CameraPose {
+    pub fn at_2d(position: Vec2) -> Self {
+        Self { position: position.extend(0.0), ..Default::default() }
+    }
+
+    pub fn looking_at(position: Vec3, target: Vec3) -> Self {
+        let view = Mat4::look_at_rh(position, target, Vec3::Y);
+        Self { position, rotation: Quat::from_mat4(&view.inverse()), ..Default::default() }
+    }
+
+    pub fn forward(&self) -> Vec3 {
+        self.rotation * Vec3::NEG_Z
+    }
+
+    pub fn right(&self) -> Vec3 {
+        self.rotation * Vec3::X
+    }
+
+    pub fn lerp(&self, other: &CameraPose, t: f32) -> CameraPose {
+        CameraPose {
+            position: self.position.lerp(other.position, t),
+            rotation: self.rotation.slerp(other.rotation, t),
+            fov_y: self.fov_y + (other.fov_y - self.fov_y) * t,
+            zoom: self.zoom + (other.zoom - self.zoom) * t,
+        }
+    }
+
+    pub fn view(&self) -> Mat4 {
+        Mat4::from_rotation_translation(self.rotation, self.position).inverse()
+    }
+
+    pub fn perspective(&self, aspect: f32, near: f32, far: f32) -> ViewCamera {
+        ViewCamera { view: self.view(), projection: Mat4::perspective_rh(self.fov_y, aspect, near, far) }
+    }
+
+    // `view_size` is the world area shown at zoom 1
+    pub fn orthographic(&self, view_size: Vec2) -> ViewCamera {
+        let half = view_size / (2.0 * self.zoom.max(f32::EPSILON));
+        ViewCamera { view: self.view(), projection: Mat4::orthographic_rh(-half.x, half.x, -half.y, half.y, -1000.0, 1000.0) }
+    }
+}
+
+// What a camera's pass does with the colour target before drawing
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ClearMode {
+    Color(Color),
+    // Keeps what is already there, e.g. an overlay or picture-in-picture camera drawn
+    // after the main one
+    DontClear,
+    // Clears to the fog colour, then `draw_sky` fills the background from the atmosphere
+    Skybox,
+}
+
+impl Default for ClearMode {
+    fn default() -> Self {
+        ClearMode::Color(Color::BLACK)
+    }
+}
+
+impl ClearMode {
+    pub fn load_op(&self, atmosphere: &Atmosphere) -> wgpu::LoadOp<wgpu::Color> {
+        match self {
+            ClearMode::Color(color) => wgpu::LoadOp::Clear((*color).into()),
+            ClearMode::DontClear => wgpu::LoadOp::Load,
+            ClearMode::Skybox => wgpu::LoadOp::Clear(Color::from(atmosphere.fog.color).into()),
+        }
+    }
+
+    // Call where `AtmosphereRenderer::draw_sky` belongs in the pass; no-op unless `Skybox`
+    pub fn draw_sky(&self, render_pass: &mut wgpu::RenderPass<'_>, sky: &AtmosphereRenderer) {
+        if *self == ClearMode::Skybox {
+            sky.draw_sky(render_pass);
+        }
+    }
+}
+
+// Poses from the last two fixed updates. Controllers step with the simulation; the frame
+// draws `at(time.alpha)` so camera motion stays smooth at any frame rate.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct InterpolatedPose {
+    previous: CameraPose,
+    current: CameraPose,
+}
+
+impl InterpolatedPose {
+    pub fn new(pose: CameraPose) -> Self {
+        Self { previous: pose, current: pose }
+    }
+
+    // Call once per fixed update with the controller's new pose
+    pub fn push(&mut self, pose: CameraPose) {
+        self.previous = self.current;
+        self.current = pose;
+    }
+
+    // Jumps without smoothing, e.g. after a teleport or cut
+    pub fn reset(&mut self, pose: CameraPose) {
+        *self = Self::new(pose);
+    }
+
+    pub fn current(&self) -> CameraPose {
+        self.current
+    }
+
+    pub fn at(&self, alpha: f64) -> CameraPose {
+        self.previous.lerp(&self.current, (alpha as f32).clamp(0.0, 1.0))
+    }
+}
@@ -0,0 +1,101 @@
+// src/camera/viewport.rs
+use crate::render_texture::ViewCamera;
+use crate::split_screen::ViewportRect;
+use crate::window::DisplayMetrics;
+use glam::{Vec2, Vec3};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Ray3 {
+    pub origin: Vec3,
+    // Unit length
+    pub direction: Vec3,
+}
+
+impl Ray3 {
+    pub fn at(&self, distance: f32) -> Vec3 {
+        self.origin + self.direction * distance
+    }
+
+    // Where the ray crosses the plane through `point` facing `normal`; `None` if parallel
+    // or the plane is behind the origin
+    pub fn intersect_plane(&self, point: Vec3, normal: Vec3) -> Option<Vec3> {
+        let denom = self.direction.dot(normal);
+        if denom.abs() < 1e-6 {
+            return None;
+        }
+        let distance = (point - self.origin).dot(normal) / denom;
+        (distance >= 0.0).then(|| self.at(distance))
+    }
+}
+
+// Where on the window a camera draws: the window's size and DPI plus the part of it the
+// camera's viewport covers. Screen positions are logical pixels from the window's top
+// left, like UI layout; winit cursor positions are physical, so pass them through
+// `DisplayMetrics::to_logical` first.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ScreenViewport {
+    pub metrics: DisplayMetrics,
+    pub rect: ViewportRect,
+}
+
+impl ScreenViewport {
+    pub fn new(metrics: DisplayMetrics) -> Self {
+        Self { metrics, rect: ViewportRect::FULL }
+    }
+
+    pub fn with_rect(mut self, rect: ViewportRect) -> Self {
+        self.rect = rect;
+        self
+    }
+
+    // (top left, size) of the viewport in logical pixels
+    pub fn logical_bounds(&self) -> (Vec2, Vec2) {
+        let window = self.metrics.logical_size();
+        (Vec2::new(self.rect.x, self.rect.y) * window, Vec2::new(self.rect.width, self.rect.height) * window)
+    }
+
+    pub fn contains(&self, screen: Vec2) -> bool {
+        let (origin, size) = self.logical_bounds();
+        screen.cmpge(origin).all() && screen.cmple(origin + size).all()
+    }
+
+    // Logical pixels to -1..1 across the viewport, +Y up
+    pub fn screen_to_ndc(&self, screen: Vec2) -> Vec2 {
+        let (origin, size) = self.logical_bounds();
+        let uv = (screen - origin) / size.max(Vec2::splat(f32::EPSILON));
+        Vec2::new(uv.x * 2.0 - 1.0, 1.0 - uv.y * 2.0)
+    }
+
+    pub fn ndc_to_screen(&self, ndc: Vec2) -> Vec2 {
+        let (origin, size) = self.logical_bounds();
+        origin + Vec2::new(ndc.x + 1.0, 1.0 - ndc.y) * 0.5 * size
+    }
+}
+
+impl ViewCamera {
+    // Logical screen position of `world`; `None` behind a perspective camera. Points off
+    // screen still map, outside the viewport, so UI can clamp markers to its edge.
+    pub fn world_to_screen(&self, world: Vec3, viewport: &ScreenViewport) -> Option<Vec2> {
+        let clip = self.view_proj() * world.extend(1.0);
+        if clip.w <= 0.0 {
+            return None;
+        }
+        Some(viewport.ndc_to_screen(clip.truncate().truncate() / clip.w))
+    }
+
+    // From the near plane through `screen`. Orthographic rays are parallel and all point
+    // the same way; perspective rays spread out from the camera.
+    pub fn viewport_ray(&self, screen: Vec2, viewport: &ScreenViewport) -> Ray3 {
+        let ndc = viewport.screen_to_ndc(screen);
+        let inv_view_proj = self.view_proj().inverse();
+        let near = inv_view_proj.project_point3(ndc.extend(0.0));
+        let far = inv_view_proj.project_point3(ndc.extend(1.0));
+        Ray3 { origin: near, direction: (far - near).normalize_or(Vec3::NEG_Z) }
+    }
+
+    // The point under `screen` on the z = 0 plane 2D scenes live on. For 3D ground
+    // picking use `viewport_ray` with `Ray3::intersect_plane`.
+    pub fn screen_to_world(&self, screen: Vec2, viewport: &ScreenViewport) -> Option<Vec2> {
+        self.viewport_ray(screen, viewport).intersect_plane(Vec3::ZERO, Vec3::Z).map(|point| point.truncate())
+    }
+}
@@ -0,0 +1,117 @@
+// src/capabilities.rs
+use crate::indirect::IndirectMode;
+
+// What the device the renderer ended up with can actually do, computed once at init from
+// the device's enabled features and limits plus the adapter's downlevel flags. Subsystems
+// check this to pick a path (or return an error) instead of assuming full desktop support;
+// the fallback tiers in `gpu_fallback` can leave compute, storage buffers or optional
+// features out. The default stands in before init: WebGL2 limits and nothing optional.
+#[derive(Debug, Clone)]
+pub struct GpuCapabilities {
+    // Enabled on the device, not just offered by the adapter
+    pub features: wgpu::Features,
+    pub limits: wgpu::Limits,
+    pub downlevel: wgpu::DownlevelFlags,
+    // Largest 2D texture or render target side
+    pub max_texture_size: u32,
+    // Compute pipelines with at least the engine's storage buffer needs
+    pub compute: bool,
+    // Read-only storage buffers in vertex shaders, e.g. GPU particles drawn from their state
+    pub vertex_storage: bool,
+    // Draw args read from GPU buffers
+    pub indirect_execution: bool,
+    // Arrays of textures indexed per draw or per fragment
+    pub bindless_textures: bool,
+}
+
+// GPU particles and the indirect culler bind this many storage buffers in one compute stage
+const COMPUTE_STORAGE_BUFFERS: u32 = 3;
+
+pub const BINDLESS_FEATURES: wgpu::Features = wgpu::Features::TEXTURE_BINDING_ARRAY
+    .union(wgpu::Features::SAMPLED_TEXTURE_AND_STORAGE_BUFFER_ARRAY_NON_UNIFORM_INDEXING)
+    .union(wgpu::Features::PARTIALLY_BOUND_BINDING_ARRAY);
+
+impl Default for GpuCapabilities {
+    fn default() -> Self {
+        let limits = wgpu::Limits::downlevel_webgl2_defaults();
+        Self {
+            features: wgpu::Features::empty(),
+            downlevel: wgpu::DownlevelFlags::empty(),
+            max_texture_size: limits.max_texture_dimension_2d,
+            compute: false,
+            vertex_storage: false,
+            indirect_execution: false,
+            bindless_textures: false,
+            limits,
+        }
+    }
+}
+
+impl GpuCapabilities {
+    pub fn new(adapter: &wgpu::Adapter, device: &wgpu::Device) -> Self {
+        let features = device.features();
+        let limits = device.limits();
+        let downlevel = adapter.get_downlevel_capabilities().flags;
+        Self {
+            compute: downlevel.contains(wgpu::DownlevelFlags::COMPUTE_SHADERS)
+                && limits.max_storage_buffers_per_shader_stage >= COMPUTE_STORAGE_BUFFERS
+                && limits.max_compute_invocations_per_workgroup > 0,
+            vertex_storage: downlevel.contains(wgpu::DownlevelFlags::VERTEX_STORAGE)
+                && limits.max_storage_buffers_per_shader_stage >= 2,
+            indirect_execution: downlevel.contains(wgpu::DownlevelFlags::INDIRECT_EXECUTION),
+            bindless_textures: features.contains(BINDLESS_FEATURES) && limits.max_binding_array_elements_per_shader_stage > 0,
+            max_texture_size: limits.max_texture_dimension_2d,
+            features,
+            limits,
+            downlevel,
+        }
+    }
+
+    pub fn has(&self, features: wgpu::Features) -> bool {
+        self.features.contains(features)
+    }
+
+    // For subsystems that can't run without a feature: `caps.require("HDR histogram", ...)?`
+    pub fn require(&self, what: &str, features: wgpu::Features) -> Result<(), String> {
+        let missing = features - self.features;
+        if missing.is_empty() {
+            Ok(())
+        } else {
+            Err(format!("{} needs {:?}, which this device doesn't support", what, missing))
+        }
+    }
+
+    pub fn require_compute(&self, what: &str) -> Result<(), String> {
+        if self.compute {
+            Ok(())
+        } else {
+            Err(format!("{} needs compute shaders and storage buffers, which this device doesn't support", what))
+        }
+    }
+
+    // Simulated in compute and drawn straight from the storage buffers
+    pub fn compute_particles(&self) -> bool {
+        self.compute && self.vertex_storage && self.indirect_execution
+    }
+
+    // Multi-draw args are written by the compute culler, and per-draw instance offsets need
+    // INDIRECT_FIRST_INSTANCE, which the downlevel tiers don't request
+    pub fn indirect_mode(&self) -> IndirectMode {
+        if self.compute && self.indirect_execution && self.has(wgpu::Features::INDIRECT_FIRST_INSTANCE) {
+            IndirectMode::MultiDraw
+        } else {
+            IndirectMode::Direct
+        }
+    }
+
+    // Largest render target size the device allows for a requested one
+    pub fn clamp_extent(&self, width: u32, height: u32) -> (u32, u32) {
+        let max = self.max_texture_size.max(1);
+        (width.clamp(1, max), height.clamp(1, max))
+    }
+
+    // Best GPU format a Basis transcoder should target on this device
+    pub fn transcode_format(&self, srgb: bool) -> wgpu::TextureFormat {
+        crate::texture::ktx2::preferred_transcode_format(self.features, srgb)
+    }
+}
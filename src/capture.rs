@@ -0,0 +1,356 @@
+// src/capture.rs
+use crate::resource_registry::{ResourceRegistry, Tracked};
+use std::collections::VecDeque;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+
+// Copies in flight at once; capture skips a frame rather than wait when all are busy
+const READBACK_SLOTS: usize = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CaptureSettings {
+    pub fps: f32,
+    // Length of history kept for `save_clip`
+    pub seconds: f32,
+    // Frames taller than this are box-filtered down by a whole factor to fit
+    pub max_height: u32,
+    // Oldest frames go first once the buffer holds this many bytes
+    pub memory_budget: usize,
+}
+
+impl Default for CaptureSettings {
+    fn default() -> Self {
+        Self { fps: 30.0, seconds: 30.0, max_height: 360, memory_budget: 512 * 1024 * 1024 }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClipFormat {
+    Mp4,
+    // Palette-quantized; best for a few seconds
+    Gif,
+}
+
+impl ClipFormat {
+    pub fn from_path(path: &Path) -> Result<Self, String> {
+        match path.extension().and_then(|extension| extension.to_str()).map(str::to_ascii_lowercase).as_deref() {
+            Some("mp4") => Ok(ClipFormat::Mp4),
+            Some("gif") => Ok(ClipFormat::Gif),
+            _ => Err(format!("Can't tell the clip format of {}; use .mp4 or .gif", path.display())),
+        }
+    }
+}
+
+// Tightly packed RGBA8. Pixels are shared, so saving a clip doesn't copy the buffer.
+#[derive(Debug, Clone)]
+pub struct CapturedFrame {
+    // Seconds since capture started
+    pub time: f64,
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Arc<Vec<u8>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ReadbackState {
+    Idle,
+    Copied,
+    Mapping,
+    Ready,
+}
+
+struct ReadbackSlot {
+    buffer: Tracked<wgpu::Buffer>,
+    size: (u32, u32),
+    padded_row: u32,
+    bgra: bool,
+    time: f64,
+    state: Arc<Mutex<ReadbackState>>,
+}
+
+impl ReadbackSlot {
+    fn state(&self) -> ReadbackState {
+        *self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn set_state(&self, state: ReadbackState) {
+        *self.state.lock().unwrap_or_else(|e| e.into_inner()) = state;
+    }
+}
+
+// An encode started by `FrameCapture::save_clip`
+pub struct ClipJob {
+    pub path: PathBuf,
+    thread: JoinHandle<Result<(), String>>,
+}
+
+impl ClipJob {
+    pub fn is_finished(&self) -> bool {
+        self.thread.is_finished()
+    }
+
+    // Blocks until the file is written
+    pub fn wait(self) -> Result<PathBuf, String> {
+        self.thread.join().map_err(|_| format!("Encoding {} panicked", self.path.display()))??;
+        Ok(self.path)
+    }
+}
+
+// "Save the last 30 seconds" gameplay capture. Each frame `capture` copies the rendered
+// texture into a readback buffer, which maps in the background and joins a ring of recent
+// frames a frame or two later, so the render thread never waits on the GPU. `save_clip`
+// encodes from the ring on its own thread through the system `ffmpeg`, which must be on
+// PATH. The captured texture needs `COPY_SRC` usage and an 8-bit RGBA or BGRA format.
+pub struct FrameCapture {
+    pub enabled: bool,
+    pub settings: CaptureSettings,
+    time: f64,
+    next_capture: f64,
+    slots: Vec<ReadbackSlot>,
+    frames: VecDeque<CapturedFrame>,
+    bytes: usize,
+}
+
+impl FrameCapture {
+    pub fn new(settings: CaptureSettings) -> Self {
+        Self {
+            enabled: true,
+            settings,
+            time: 0.0,
+            next_capture: 0.0,
+            slots: Vec::new(),
+            frames: VecDeque::new(),
+            bytes: 0,
+        }
+    }
+
+    pub fn frames(&self) -> &VecDeque<CapturedFrame> {
+        &self.frames
+    }
+
+    // Seconds of history held
+    pub fn buffered_seconds(&self) -> f64 {
+        match (self.frames.front(), self.frames.back()) {
+            (Some(first), Some(last)) => last.time - first.time,
+            _ => 0.0,
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.frames.clear();
+        self.bytes = 0;
+    }
+
+    // Call once per frame after rendering into `texture`, before submitting `encoder`.
+    // Collects readbacks that have landed, then copies `texture` if a frame is due.
+    pub fn capture(
+        &mut self,
+        device: &wgpu::Device,
+        resources: &ResourceRegistry,
+        encoder: &mut wgpu::CommandEncoder,
+        texture: &wgpu::Texture,
+        delta_time: f64,
+    ) -> Result<(), String> {
+        self.time += delta_time;
+        self.collect();
+        if !self.enabled || self.time < self.next_capture {
+            return Ok(());
+        }
+        let bgra = match texture.format() {
+            wgpu::TextureFormat::Rgba8Unorm | wgpu::TextureFormat::Rgba8UnormSrgb => false,
+            wgpu::TextureFormat::Bgra8Unorm | wgpu::TextureFormat::Bgra8UnormSrgb => true,
+            format => return Err(format!("Can't capture {:?} textures", format)),
+        };
+        if !texture.usage().contains(wgpu::TextureUsages::COPY_SRC) {
+            return Err("Captured texture needs COPY_SRC usage".to_string());
+        }
+
+        let size = (texture.width(), texture.height());
+        let padded_row = (size.0 * 4).div_ceil(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT) * wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+        let slot = match self.slots.iter().position(|slot| slot.state() == ReadbackState::Idle) {
+            Some(index) => index,
+            None if self.slots.len() < READBACK_SLOTS => {
+                self.slots.push(Self::create_slot(device, resources, size, padded_row));
+                self.slots.len() - 1
+            }
+            // Every copy is still in flight; try again next frame
+            None => return Ok(()),
+        };
+        if self.slots[slot].size != size {
+            self.slots[slot] = Self::create_slot(device, resources, size, padded_row);
+        }
+        let slot = &mut self.slots[slot];
+        slot.bgra = bgra;
+        slot.time = self.time;
+        encoder.copy_texture_to_buffer(
+            wgpu::TexelCopyTextureInfo {
+                texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            wgpu::TexelCopyBufferInfo {
+                buffer: &slot.buffer,
+                layout: wgpu::TexelCopyBufferLayout {
+                    offset: 0,
+                    bytes_per_row: Some(padded_row),
+                    rows_per_image: Some(size.1),
+                },
+            },
+            wgpu::Extent3d { width: size.0, height: size.1, depth_or_array_layers: 1 },
+        );
+        slot.set_state(ReadbackState::Copied);
+        // Skipped frames are dropped, not caught up
+        let interval = 1.0 / self.settings.fps.max(1.0) as f64;
+        self.next_capture = (self.next_capture + interval).max(self.time);
+        Ok(())
+    }
+
+    fn create_slot(device: &wgpu::Device, resources: &ResourceRegistry, size: (u32, u32), padded_row: u32) -> ReadbackSlot {
+        let buffer = resources.create_buffer(device, &wgpu::BufferDescriptor {
+            label: Some("capture readback"),
+            size: padded_row as u64 * size.1 as u64,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        }, "capture");
+        ReadbackSlot {
+            buffer,
+            size,
+            padded_row,
+            bgra: false,
+            time: 0.0,
+            state: Arc::new(Mutex::new(ReadbackState::Idle)),
+        }
+    }
+
+    // Call after submitting; the copies land once the device is polled
+    pub fn after_submit(&mut self) {
+        for slot in self.slots.iter().filter(|slot| slot.state() == ReadbackState::Copied) {
+            slot.set_state(ReadbackState::Mapping);
+            let state = slot.state.clone();
+            slot.buffer.slice(..).map_async(wgpu::MapMode::Read, move |result| {
+                let mut state = state.lock().unwrap_or_else(|e| e.into_inner());
+                *state = match result {
+                    Ok(()) => ReadbackState::Ready,
+                    Err(e) => {
+                        log::warn!("Capture readback failed: {}", e);
+                        ReadbackState::Idle
+                    }
+                };
+            });
+        }
+    }
+
+    fn collect(&mut self) {
+        // Oldest first, so the ring stays in time order
+        let mut ready: Vec<usize> = (0..self.slots.len()).filter(|&index| self.slots[index].state() == ReadbackState::Ready).collect();
+        ready.sort_by(|&a, &b| self.slots[a].time.total_cmp(&self.slots[b].time));
+        for index in ready {
+            let slot = &self.slots[index];
+            let frame = {
+                let data = slot.buffer.slice(..).get_mapped_range();
+                Self::downscale(&data, slot, self.settings.max_height)
+            };
+            slot.buffer.unmap();
+            slot.set_state(ReadbackState::Idle);
+            self.push(frame);
+        }
+    }
+
+    // Box filter by the smallest whole factor that fits `max_height`, dropping padding and
+    // swizzling BGRA on the way
+    fn downscale(data: &[u8], slot: &ReadbackSlot, max_height: u32) -> CapturedFrame {
+        let (width, height) = slot.size;
+        let factor = height.div_ceil(max_height.max(1)).max(1);
+        let (out_width, out_height) = ((width / factor).max(1), (height / factor).max(1));
+        let mut pixels = Vec::with_capacity(out_width as usize * out_height as usize * 4);
+        let samples = factor * factor;
+        for y in 0..out_height {
+            for x in 0..out_width {
+                let mut sum = [0u32; 4];
+                for sy in y * factor..((y + 1) * factor).min(height) {
+                    let row = sy as usize * slot.padded_row as usize;
+                    for sx in x * factor..((x + 1) * factor).min(width) {
+                        let texel = &data[row + sx as usize * 4..][..4];
+                        for (total, value) in sum.iter_mut().zip(texel) {
+                            *total += *value as u32;
+                        }
+                    }
+                }
+                if slot.bgra {
+                    sum.swap(0, 2);
+                }
+                pixels.extend(sum.map(|total| ((total + samples / 2) / samples) as u8));
+            }
+        }
+        CapturedFrame { time: slot.time, width: out_width, height: out_height, pixels: Arc::new(pixels) }
+    }
+
+    fn push(&mut self, frame: CapturedFrame) {
+        self.bytes += frame.pixels.len();
+        let oldest = frame.time - self.settings.seconds as f64;
+        self.frames.push_back(frame);
+        while let Some(first) = self.frames.front() {
+            if first.time >= oldest && self.bytes <= self.settings.memory_budget {
+                break;
+            }
+            self.bytes -= first.pixels.len();
+            self.frames.pop_front();
+        }
+    }
+
+    // Encodes the last `seconds` (or everything held) to `path`, MP4 or GIF by its
+    // extension, on a background thread. Only the frames since the last size change are
+    // used, as a clip has one resolution.
+    pub fn save_clip(&self, path: impl Into<PathBuf>, seconds: Option<f32>) -> Result<ClipJob, String> {
+        let path = path.into();
+        let format = ClipFormat::from_path(&path)?;
+        let last = self.frames.back().ok_or("No frames captured yet")?;
+        let start = seconds.map_or(f64::NEG_INFINITY, |seconds| last.time - seconds as f64);
+        let mut frames: Vec<CapturedFrame> = self
+            .frames
+            .iter()
+            .rev()
+            .take_while(|frame| frame.time >= start && (frame.width, frame.height) == (last.width, last.height))
+            .cloned()
+            .collect();
+        frames.reverse();
+        let fps = self.settings.fps;
+        let output = path.clone();
+        let thread = std::thread::spawn(move || encode(&output, format, fps, &frames));
+        Ok(ClipJob { path, thread })
+    }
+}
+
+fn encode(path: &Path, format: ClipFormat, fps: f32, frames: &[CapturedFrame]) -> Result<(), String> {
+    let Some(first) = frames.first() else { return Err("No frames to encode".to_string()) };
+    let size = format!("{}x{}", first.width, first.height);
+    let rate = format!("{}", fps.max(1.0));
+    let mut command = Command::new("ffmpeg");
+    command.args(["-y", "-v", "error", "-f", "rawvideo", "-pix_fmt", "rgba", "-s", &size, "-framerate", &rate, "-i", "-"]);
+    match format {
+        // yuv420p, which players expect, needs even dimensions
+        ClipFormat::Mp4 => command.args(["-c:v", "libx264", "-pix_fmt", "yuv420p", "-vf", "pad=ceil(iw/2)*2:ceil(ih/2)*2"]),
+        // A palette made from the clip itself looks far better than the default one
+        ClipFormat::Gif => command.args(["-vf", "split[a][b];[a]palettegen[p];[b][p]paletteuse"]),
+    };
+    let mut child = command
+        .arg(path)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to run ffmpeg: {}", e))?;
+    let mut stdin = child.stdin.take().ok_or("ffmpeg has no stdin")?;
+    let written = frames.iter().try_for_each(|frame| stdin.write_all(&frame.pixels));
+    // Closing stdin ends the stream
+    drop(stdin);
+    let result = child.wait_with_output().map_err(|e| format!("Failed to run ffmpeg: {}", e))?;
+    if !result.status.success() {
+        return Err(format!("ffmpeg failed on {}: {}", path.display(), String::from_utf8_lossy(&result.stderr).trim()));
+    }
+    written.map_err(|e| format!("Failed to write frames to ffmpeg: {}", e))
+}
@@ -0,0 +1,163 @@
+// src/clip.rs
+
+// Depth/stencil format for passes that clip with `StencilClip`
+pub const CLIP_STENCIL_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth24PlusStencil8;
+
+// Pixel rectangle, (0, 0) at the top left of the render target
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClipRect {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl ClipRect {
+    pub fn new(x: u32, y: u32, width: u32, height: u32) -> Self {
+        Self { x, y, width, height }
+    }
+
+    // Empty when the two don't overlap
+    pub fn intersect(&self, other: &ClipRect) -> ClipRect {
+        let left = self.x.max(other.x);
+        let top = self.y.max(other.y);
+        let right = (self.x + self.width).min(other.x + other.width);
+        let bottom = (self.y + self.height).min(other.y + other.height);
+        ClipRect::new(left, top, right.saturating_sub(left), bottom.saturating_sub(top))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.width == 0 || self.height == 0
+    }
+
+    pub fn contains(&self, x: u32, y: u32) -> bool {
+        x >= self.x && y >= self.y && x < self.x + self.width && y < self.y + self.height
+    }
+}
+
+// Nested rectangular clipping through the scissor rect. Each pushed rect is intersected
+// with its parent, so a scroll view inside a panel never draws outside either.
+pub struct ClipStack {
+    target: ClipRect,
+    stack: Vec<ClipRect>,
+}
+
+impl ClipStack {
+    pub fn new(target_width: u32, target_height: u32) -> Self {
+        Self { target: ClipRect::new(0, 0, target_width, target_height), stack: Vec::new() }
+    }
+
+    // The rect currently drawn into; the whole target when nothing is pushed
+    pub fn current(&self) -> ClipRect {
+        self.stack.last().copied().unwrap_or(self.target)
+    }
+
+    // Draw calls while the result is empty would be invisible and can be skipped
+    pub fn push(&mut self, render_pass: &mut wgpu::RenderPass<'_>, rect: ClipRect) -> ClipRect {
+        let clipped = self.current().intersect(&rect);
+        self.stack.push(clipped);
+        Self::apply(render_pass, clipped);
+        clipped
+    }
+
+    pub fn pop(&mut self, render_pass: &mut wgpu::RenderPass<'_>) {
+        self.stack.pop();
+        Self::apply(render_pass, self.current());
+    }
+
+    pub fn depth(&self) -> usize {
+        self.stack.len()
+    }
+
+    fn apply(render_pass: &mut wgpu::RenderPass<'_>, rect: ClipRect) {
+        // A zero-sized scissor is invalid on some backends; a 1x1 rect at the corner keeps
+        // the pass valid, and callers skip drawing anyway
+        if rect.is_empty() {
+            render_pass.set_scissor_rect(rect.x, rect.y, 1, 1);
+        } else {
+            render_pass.set_scissor_rect(rect.x, rect.y, rect.width, rect.height);
+        }
+    }
+}
+
+fn stencil_state(compare: wgpu::CompareFunction, pass_op: wgpu::StencilOperation) -> wgpu::DepthStencilState {
+    let face = wgpu::StencilFaceState {
+        compare,
+        fail_op: wgpu::StencilOperation::Keep,
+        depth_fail_op: wgpu::StencilOperation::Keep,
+        pass_op,
+    };
+    wgpu::DepthStencilState {
+        format: CLIP_STENCIL_FORMAT,
+        depth_write_enabled: false,
+        depth_compare: wgpu::CompareFunction::Always,
+        stencil: wgpu::StencilState { front: face, back: face, read_mask: 0xff, write_mask: 0xff },
+        bias: wgpu::DepthBiasState::default(),
+    }
+}
+
+// For pipelines drawing a clip shape into the stencil; pair it with a colour target whose
+// `write_mask` is empty so the shape itself stays invisible
+pub fn stencil_push_state() -> wgpu::DepthStencilState {
+    stencil_state(wgpu::CompareFunction::Equal, wgpu::StencilOperation::IncrementClamp)
+}
+
+// Same shape again to undo `stencil_push_state`
+pub fn stencil_pop_state() -> wgpu::DepthStencilState {
+    stencil_state(wgpu::CompareFunction::Equal, wgpu::StencilOperation::DecrementClamp)
+}
+
+// For pipelines drawing clipped content; any pipeline used between pushes needs this
+pub fn stencil_clipped_state() -> wgpu::DepthStencilState {
+    stencil_state(wgpu::CompareFunction::Equal, wgpu::StencilOperation::Keep)
+}
+
+// Clipping to arbitrary shapes (round minimaps, rotated panels) with the stencil buffer.
+// Nested shapes each add one stencil level, and content only draws where every level
+// covers it. The pass needs a `CLIP_STENCIL_FORMAT` attachment cleared to 0.
+pub struct StencilClip {
+    depth: u32,
+}
+
+impl StencilClip {
+    pub fn new() -> Self {
+        Self { depth: 0 }
+    }
+
+    pub fn depth(&self) -> u32 {
+        self.depth
+    }
+
+    // `draw_shape` draws the clip shape with a `stencil_push_state` pipeline; afterwards the
+    // stencil reference is set for `stencil_clipped_state` content
+    pub fn push<F>(&mut self, render_pass: &mut wgpu::RenderPass<'_>, draw_shape: F)
+    where
+        F: FnOnce(&mut wgpu::RenderPass<'_>),
+    {
+        render_pass.set_stencil_reference(self.depth);
+        draw_shape(render_pass);
+        self.depth = (self.depth + 1).min(u8::MAX as u32);
+        render_pass.set_stencil_reference(self.depth);
+    }
+
+    // `draw_shape` draws the same shape as the matching `push`, with a `stencil_pop_state`
+    // pipeline
+    pub fn pop<F>(&mut self, render_pass: &mut wgpu::RenderPass<'_>, draw_shape: F)
+    where
+        F: FnOnce(&mut wgpu::RenderPass<'_>),
+    {
+        if self.depth == 0 {
+            return;
+        }
+        render_pass.set_stencil_reference(self.depth);
+        draw_shape(render_pass);
+        self.depth -= 1;
+        render_pass.set_stencil_reference(self.depth);
+    }
+}
+
+impl Default for StencilClip {
+    fn default() -> Self {
+        Self::new()
+    }
+}
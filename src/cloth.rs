@@ -0,0 +1,437 @@
+// src/cloth.rs
+use crate::mesh::MeshBuilder;
+use crate::resource_registry::{ResourceRegistry, Tracked};
+use crate::upload::UploadBelt;
+use glam::{Mat4, Vec3};
+
+// Mirrors `MAX_COLLIDERS` in cloth.wgsl
+pub const MAX_CLOTH_COLLIDERS: usize = 16;
+const WORKGROUP_SIZE: u32 = 64;
+// Longest frame simulated; anything slower is slowed down rather than blown apart
+const MAX_FRAME_TIME: f32 = 1.0 / 30.0;
+
+// Mirrors `ClothCollider` in cloth.wgsl
+#[repr(C)]
+#[derive(Clone, Copy, Default, bytemuck::Pod, bytemuck::Zeroable)]
+struct GpuCollider {
+    a: [f32; 4],
+    b: [f32; 4],
+    kind: u32,
+    radius: f32,
+    _pad: [u32; 2],
+}
+
+// Mirrors `ClothParams` in cloth.wgsl
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct ClothParams {
+    anchor: [f32; 16],
+    gravity: [f32; 3],
+    dt: f32,
+    wind: [f32; 3],
+    time: f32,
+    damping: f32,
+    stiffness: f32,
+    bend_stiffness: f32,
+    turbulence: f32,
+    thickness: f32,
+    friction: f32,
+    columns: u32,
+    rows: u32,
+    collider_count: u32,
+    relaxation: f32,
+    _pad: [u32; 2],
+    colliders: [GpuCollider; MAX_CLOTH_COLLIDERS],
+}
+
+// Shapes the cloth is pushed out of, in world space
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ClothCollider {
+    Sphere { center: Vec3, radius: f32 },
+    Capsule { a: Vec3, b: Vec3, radius: f32 },
+    // Everything behind `dot(p, normal) = offset` is solid
+    Plane { normal: Vec3, offset: f32 },
+}
+
+impl ClothCollider {
+    fn to_gpu(self) -> GpuCollider {
+        match self {
+            ClothCollider::Sphere { center, radius } => {
+                GpuCollider { a: center.extend(0.0).into(), kind: 0, radius, ..Default::default() }
+            }
+            ClothCollider::Capsule { a, b, radius } => {
+                GpuCollider { a: a.extend(0.0).into(), b: b.extend(0.0).into(), kind: 1, radius, ..Default::default() }
+            }
+            ClothCollider::Plane { normal, offset } => {
+                GpuCollider { a: normal.normalize_or(Vec3::Y).extend(offset).into(), kind: 2, ..Default::default() }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ClothSettings {
+    // Quads across and down; the grid has one more point than quads each way
+    pub columns: u32,
+    pub rows: u32,
+    // Size of the sheet. It hangs from its top edge along -Y in local space, facing +Z.
+    pub width: f32,
+    pub height: f32,
+    pub gravity: Vec3,
+    pub wind: Vec3,
+    // 0 gives steady wind, 1 gusts between none and double strength
+    pub turbulence: f32,
+    // Fraction of velocity lost per substep
+    pub damping: f32,
+    // 0..=1 per constraint; bending is usually much softer than stretching
+    pub stiffness: f32,
+    pub bend_stiffness: f32,
+    pub substeps: u32,
+    // Constraint passes per substep; more makes the cloth stretch less
+    pub iterations: u32,
+    // Distance kept from colliders
+    pub thickness: f32,
+    // 0..=1, how much sliding along a collider is damped
+    pub friction: f32,
+}
+
+impl Default for ClothSettings {
+    fn default() -> Self {
+        Self {
+            columns: 32,
+            rows: 32,
+            width: 2.0,
+            height: 2.0,
+            gravity: Vec3::new(0.0, -9.81, 0.0),
+            wind: Vec3::ZERO,
+            turbulence: 0.5,
+            damping: 0.01,
+            stiffness: 1.0,
+            bend_stiffness: 0.2,
+            substeps: 4,
+            iterations: 8,
+            thickness: 0.02,
+            friction: 0.3,
+        }
+    }
+}
+
+// Verlet cloth simulated in compute shaders. Points live in storage buffers; each substep
+// integrates gravity and wind, relaxes the distance constraints and resolves collisions,
+// then the points are written out as `MeshVertex` data so the cloth draws through any
+// pipeline built on `MeshVertex::layout()`.
+pub struct GpuCloth {
+    pub settings: ClothSettings,
+    points: u32,
+    point_columns: u32,
+    index_count: u32,
+    // Local rest positions, w = inverse mass; re-uploaded when pins change. On the GPU they
+    // follow the previous positions in the state buffer.
+    rest: Vec<[f32; 4]>,
+    rest_dirty: bool,
+    anchor: Mat4,
+    colliders: Vec<ClothCollider>,
+    time: f32,
+    params_buffer: Tracked<wgpu::Buffer>,
+    position_buffer: Tracked<wgpu::Buffer>,
+    state_buffer: Tracked<wgpu::Buffer>,
+    vertex_buffer: Tracked<wgpu::Buffer>,
+    index_buffer: Tracked<wgpu::Buffer>,
+    _scratch_buffer: Tracked<wgpu::Buffer>,
+    // [positions -> scratch, scratch -> positions]
+    bind_groups: [wgpu::BindGroup; 2],
+    integrate_pipeline: wgpu::ComputePipeline,
+    solve_pipeline: wgpu::ComputePipeline,
+    collide_pipeline: wgpu::ComputePipeline,
+    write_pipeline: wgpu::ComputePipeline,
+}
+
+impl GpuCloth {
+    // A sheet placed by `transform`, with nothing pinned yet
+    pub fn new(
+        device: &wgpu::Device,
+        resources: &ResourceRegistry,
+        settings: ClothSettings,
+        transform: Mat4,
+    ) -> Result<Self, String> {
+        if settings.columns == 0 || settings.rows == 0 {
+            return Err("Cloth needs at least one column and one row".to_string());
+        }
+        if settings.width <= 0.0 || settings.height <= 0.0 {
+            return Err("Cloth width and height must be positive".to_string());
+        }
+        let grid = MeshBuilder::plane_grid(1.0, 1.0, settings.columns, settings.rows).build();
+        let rest: Vec<[f32; 4]> = grid
+            .vertices
+            .iter()
+            .map(|v| [(v.uv[0] - 0.5) * settings.width, -v.uv[1] * settings.height, 0.0, 1.0])
+            .collect();
+        let initial: Vec<[f32; 4]> = rest
+            .iter()
+            .map(|r| transform.transform_point3(Vec3::new(r[0], r[1], r[2])).extend(0.0).into())
+            .collect();
+
+        let storage = wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST;
+        let point_buffer = |label: &str, contents: &[[f32; 4]]| {
+            resources.create_buffer_init(device, &wgpu::util::BufferInitDescriptor {
+                label: Some(label),
+                contents: bytemuck::cast_slice(contents),
+                usage: storage,
+            }, "cloth")
+        };
+        let position_buffer = point_buffer("cloth positions", &initial);
+        let scratch_buffer = point_buffer("cloth scratch", &initial);
+        let state_buffer = point_buffer("cloth state", &[initial.as_slice(), rest.as_slice()].concat());
+        let vertex_buffer = resources.create_buffer_init(device, &wgpu::util::BufferInitDescriptor {
+            label: Some("cloth vertices"),
+            contents: bytemuck::cast_slice(&grid.vertices),
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_SRC,
+        }, "cloth");
+        let index_buffer = resources.create_buffer_init(device, &wgpu::util::BufferInitDescriptor {
+            label: Some("cloth indices"),
+            contents: bytemuck::cast_slice(&grid.indices),
+            usage: wgpu::BufferUsages::INDEX,
+        }, "cloth");
+        let params_buffer = resources.create_buffer(device, &wgpu::BufferDescriptor {
+            label: Some("cloth params"),
+            size: std::mem::size_of::<ClothParams>() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        }, "cloth");
+
+        let read_write = wgpu::BufferBindingType::Storage { read_only: false };
+        let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("cloth sim"),
+            entries: &[
+                layout_entry(0, wgpu::BufferBindingType::Uniform),
+                layout_entry(1, read_write),
+                layout_entry(2, read_write),
+                layout_entry(3, read_write),
+                layout_entry(4, read_write),
+            ],
+        });
+        let bind_groups = [(&position_buffer, &scratch_buffer), (&scratch_buffer, &position_buffer)].map(|(src, dst)| {
+            device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("cloth sim"),
+                layout: &layout,
+                entries: &[
+                    wgpu::BindGroupEntry { binding: 0, resource: params_buffer.as_entire_binding() },
+                    wgpu::BindGroupEntry { binding: 1, resource: src.as_entire_binding() },
+                    wgpu::BindGroupEntry { binding: 2, resource: dst.as_entire_binding() },
+                    wgpu::BindGroupEntry { binding: 3, resource: state_buffer.as_entire_binding() },
+                    wgpu::BindGroupEntry { binding: 4, resource: vertex_buffer.as_entire_binding() },
+                ],
+            })
+        });
+
+        let shader = device.create_shader_module(wgpu::include_wgsl!("cloth.wgsl"));
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("cloth sim"),
+            bind_group_layouts: &[&layout],
+            push_constant_ranges: &[],
+        });
+        let compute_pipeline = |entry_point: &str| {
+            device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+                label: Some(entry_point),
+                layout: Some(&pipeline_layout),
+                module: &shader,
+                entry_point: Some(entry_point),
+                compilation_options: Default::default(),
+                cache: None,
+            })
+        };
+
+        Ok(Self {
+            settings,
+            points: rest.len() as u32,
+            point_columns: settings.columns + 1,
+            index_count: grid.indices.len() as u32,
+            rest,
+            rest_dirty: false,
+            anchor: transform,
+            colliders: Vec::new(),
+            time: 0.0,
+            integrate_pipeline: compute_pipeline("integrate"),
+            solve_pipeline: compute_pipeline("solve"),
+            collide_pipeline: compute_pipeline("collide"),
+            write_pipeline: compute_pipeline("write_vertices"),
+            params_buffer,
+            position_buffer,
+            state_buffer,
+            vertex_buffer,
+            index_buffer,
+            _scratch_buffer: scratch_buffer,
+            bind_groups,
+        })
+    }
+
+    pub fn point_count(&self) -> u32 {
+        self.points
+    }
+
+    // Pinned points follow the transform; moving it drags the cloth along
+    pub fn set_transform(&mut self, transform: Mat4) {
+        self.anchor = transform;
+    }
+
+    pub fn transform(&self) -> Mat4 {
+        self.anchor
+    }
+
+    // Grid point at `column`, `row`, counted from the top-left corner
+    pub fn point_index(&self, column: u32, row: u32) -> Option<u32> {
+        let index = row.checked_mul(self.point_columns)?.checked_add(column)?;
+        (column < self.point_columns && index < self.points).then_some(index)
+    }
+
+    pub fn set_pinned(&mut self, column: u32, row: u32, pinned: bool) {
+        let Some(index) = self.point_index(column, row) else {
+            log::warn!("Cloth point ({}, {}) is outside the {}x{} grid", column, row, self.settings.columns, self.settings.rows);
+            return;
+        };
+        self.rest[index as usize][3] = if pinned { 0.0 } else { 1.0 };
+        self.rest_dirty = true;
+    }
+
+    pub fn pin(&mut self, column: u32, row: u32) {
+        self.set_pinned(column, row, true);
+    }
+
+    pub fn unpin(&mut self, column: u32, row: u32) {
+        self.set_pinned(column, row, false);
+    }
+
+    // Hangs the cloth like a curtain
+    pub fn pin_top_edge(&mut self) {
+        for column in 0..self.point_columns {
+            self.pin(column, 0);
+        }
+    }
+
+    // Hangs the cloth like a flag on two hooks
+    pub fn pin_top_corners(&mut self) {
+        self.pin(0, 0);
+        self.pin(self.point_columns - 1, 0);
+    }
+
+    pub fn unpin_all(&mut self) {
+        for r in &mut self.rest {
+            r[3] = 1.0;
+        }
+        self.rest_dirty = true;
+    }
+
+    // Replaces the colliders; beyond `MAX_CLOTH_COLLIDERS` the rest are ignored
+    pub fn set_colliders(&mut self, colliders: &[ClothCollider]) {
+        if colliders.len() > MAX_CLOTH_COLLIDERS {
+            log::warn!("Cloth supports {} colliders, ignoring {}", MAX_CLOTH_COLLIDERS, colliders.len() - MAX_CLOTH_COLLIDERS);
+        }
+        self.colliders = colliders.iter().take(MAX_CLOTH_COLLIDERS).copied().collect();
+    }
+
+    pub fn colliders(&self) -> &[ClothCollider] {
+        &self.colliders
+    }
+
+    // Puts every point back at its rest position under the current transform
+    pub fn reset(&mut self, device: &wgpu::Device, encoder: &mut wgpu::CommandEncoder, belt: &mut UploadBelt) {
+        let positions: Vec<[f32; 4]> = self
+            .rest
+            .iter()
+            .map(|r| self.anchor.transform_point3(Vec3::new(r[0], r[1], r[2])).extend(0.0).into())
+            .collect();
+        belt.write(device, encoder, &self.position_buffer, 0, bytemuck::cast_slice(&positions));
+        belt.write(device, encoder, &self.state_buffer, 0, bytemuck::cast_slice(&positions));
+    }
+
+    // Records this frame's simulation and vertex write into `encoder`
+    pub fn update(&mut self, device: &wgpu::Device, encoder: &mut wgpu::CommandEncoder, belt: &mut UploadBelt, dt: f32) {
+        if self.rest_dirty {
+            let offset = self.points as u64 * std::mem::size_of::<[f32; 4]>() as u64;
+            belt.write(device, encoder, &self.state_buffer, offset, bytemuck::cast_slice(&self.rest));
+            self.rest_dirty = false;
+        }
+        let dt = dt.clamp(0.0, MAX_FRAME_TIME);
+        self.time += dt;
+        let settings = &self.settings;
+        let substeps = settings.substeps.max(1);
+        let mut colliders = [GpuCollider::default(); MAX_CLOTH_COLLIDERS];
+        for (gpu, collider) in colliders.iter_mut().zip(&self.colliders) {
+            *gpu = collider.to_gpu();
+        }
+        let params = ClothParams {
+            anchor: self.anchor.to_cols_array(),
+            gravity: settings.gravity.into(),
+            dt: dt / substeps as f32,
+            wind: settings.wind.into(),
+            time: self.time,
+            damping: settings.damping.clamp(0.0, 1.0),
+            stiffness: settings.stiffness.clamp(0.0, 1.0),
+            bend_stiffness: settings.bend_stiffness.clamp(0.0, 1.0),
+            turbulence: settings.turbulence,
+            thickness: settings.thickness,
+            friction: settings.friction.clamp(0.0, 1.0),
+            columns: self.point_columns,
+            rows: settings.rows + 1,
+            collider_count: self.colliders.len() as u32,
+            // Over-relaxation; Jacobi averaging alone converges too slowly for cloth
+            relaxation: 1.5,
+            _pad: [0; 2],
+            colliders,
+        };
+        belt.write(device, encoder, &self.params_buffer, 0, bytemuck::bytes_of(&params));
+
+        let workgroups = self.points.div_ceil(WORKGROUP_SIZE);
+        let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+            label: Some("cloth sim"),
+            timestamp_writes: None,
+        });
+        for _ in 0..substeps {
+            pass.set_bind_group(0, &self.bind_groups[0], &[]);
+            pass.set_pipeline(&self.integrate_pipeline);
+            pass.dispatch_workgroups(workgroups, 1, 1);
+            // The prediction is in the scratch buffer; an odd number of passes brings the
+            // result back to the positions
+            pass.set_pipeline(&self.solve_pipeline);
+            for n in 0..settings.iterations.max(1) * 2 - 1 {
+                pass.set_bind_group(0, &self.bind_groups[1 - n as usize % 2], &[]);
+                pass.dispatch_workgroups(workgroups, 1, 1);
+            }
+            pass.set_bind_group(0, &self.bind_groups[0], &[]);
+            pass.set_pipeline(&self.collide_pipeline);
+            pass.dispatch_workgroups(workgroups, 1, 1);
+        }
+        pass.set_pipeline(&self.write_pipeline);
+        pass.dispatch_workgroups(workgroups, 1, 1);
+    }
+
+    // `MeshVertex` data written by the last `update`
+    pub fn vertex_buffer(&self) -> &wgpu::Buffer {
+        &self.vertex_buffer
+    }
+
+    pub fn index_buffer(&self) -> &wgpu::Buffer {
+        &self.index_buffer
+    }
+
+    pub fn index_count(&self) -> u32 {
+        self.index_count
+    }
+
+    // Binds the cloth like a `GpuMesh`; the caller sets a pipeline using `MeshVertex::layout()`.
+    // Both sides of the sheet are visible, so that pipeline should not cull back faces.
+    pub fn draw(&self, render_pass: &mut wgpu::RenderPass<'_>) {
+        render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+        render_pass.set_index_buffer(self.index_buffer.slice(..), wgpu::IndexFormat::Uint32);
+        render_pass.draw_indexed(0..self.index_count, 0, 0..1);
+    }
+}
+
+fn layout_entry(binding: u32, ty: wgpu::BufferBindingType) -> wgpu::BindGroupLayoutEntry {
+    wgpu::BindGroupLayoutEntry {
+        binding,
+        visibility: wgpu::ShaderStages::COMPUTE,
+        ty: wgpu::BindingType::Buffer { ty, has_dynamic_offset: false, min_binding_size: None },
+        count: None,
+    }
+}
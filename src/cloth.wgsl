@@ -0,0 +1,239 @@
+// GPU cloth: integrate -> solve (ping-pong, an odd number of passes) -> collide, per
+// substep, then write_vertices once per frame for the mesh pipeline
+
+const MAX_COLLIDERS: u32 = 16u;
+const SPHERE: u32 = 0u;
+const CAPSULE: u32 = 1u;
+const PLANE: u32 = 2u;
+
+// Sphere: centre a.xyz. Capsule: segment a.xyz -> b.xyz. Plane: normal a.xyz, offset a.w.
+struct ClothCollider {
+    a: vec4<f32>,
+    b: vec4<f32>,
+    kind: u32,
+    radius: f32,
+    _pad0: u32,
+    _pad1: u32,
+}
+
+struct ClothParams {
+    // Places pinned points: they sit at anchor * rest position
+    anchor: mat4x4<f32>,
+    gravity: vec3<f32>,
+    dt: f32,
+    wind: vec3<f32>,
+    time: f32,
+    damping: f32,
+    stiffness: f32,
+    bend_stiffness: f32,
+    turbulence: f32,
+    thickness: f32,
+    friction: f32,
+    // Points per row and per column of the grid
+    columns: u32,
+    rows: u32,
+    collider_count: u32,
+    relaxation: f32,
+    _pad0: u32,
+    _pad1: u32,
+    colliders: array<ClothCollider, MAX_COLLIDERS>,
+}
+
+@group(0) @binding(0) var<uniform> params: ClothParams;
+@group(0) @binding(1) var<storage, read_write> src: array<vec4<f32>>;
+@group(0) @binding(2) var<storage, read_write> dst: array<vec4<f32>>;
+// Previous positions for the first point_count() entries, rest data after them; one
+// buffer so the shader stays within the downlevel limit of four storage buffers
+@group(0) @binding(3) var<storage, read_write> state: array<vec4<f32>>;
+// MeshVertex: position, normal, uv as 8 floats
+@group(0) @binding(4) var<storage, read_write> vertices: array<f32>;
+
+fn point_count() -> u32 {
+    return params.columns * params.rows;
+}
+
+fn previous(i: u32) -> vec3<f32> {
+    return state[i].xyz;
+}
+
+fn set_previous(i: u32, p: vec3<f32>) {
+    state[i] = vec4<f32>(p, 0.0);
+}
+
+// xyz: position in the cloth's local space; w: inverse mass, 0 when pinned
+fn rest(i: u32) -> vec4<f32> {
+    return state[point_count() + i];
+}
+
+fn point_index(column: i32, row: i32) -> u32 {
+    return u32(row) * params.columns + u32(column);
+}
+
+fn in_grid(column: i32, row: i32) -> bool {
+    return column >= 0 && row >= 0 && column < i32(params.columns) && row < i32(params.rows);
+}
+
+// Central differences across the grid, clamped at the edges
+fn grid_normal(column: i32, row: i32) -> vec3<f32> {
+    let last = vec2<i32>(i32(params.columns) - 1, i32(params.rows) - 1);
+    let left = src[point_index(max(column - 1, 0), row)].xyz;
+    let right = src[point_index(min(column + 1, last.x), row)].xyz;
+    let up = src[point_index(column, max(row - 1, 0))].xyz;
+    let down = src[point_index(column, min(row + 1, last.y))].xyz;
+    let n = cross(down - up, right - left);
+    let len = length(n);
+    if len < 1e-8 {
+        return vec3<f32>(0.0, 0.0, 1.0);
+    }
+    return n / len;
+}
+
+// Reads `src`, writes the prediction to `dst`, so the normals see a consistent surface
+@compute @workgroup_size(64)
+fn integrate(@builtin(global_invocation_id) id: vec3<u32>) {
+    let i = id.x;
+    if i >= point_count() {
+        return;
+    }
+    let r = rest(i);
+    if r.w == 0.0 {
+        let pinned = (params.anchor * vec4<f32>(r.xyz, 1.0)).xyz;
+        dst[i] = vec4<f32>(pinned, 0.0);
+        set_previous(i, pinned);
+        return;
+    }
+    let column = i32(i % params.columns);
+    let row = i32(i / params.columns);
+    let p = src[i].xyz;
+    let velocity = (p - previous(i)) * (1.0 - params.damping);
+
+    // Wind pushes along the surface normal, by how squarely it hits the cloth; the gusts
+    // vary across the cloth so it ripples instead of swinging as one sheet
+    let phase = params.time * 3.0 + f32(column) * 0.37 + f32(row) * 0.23;
+    let gust = 1.0 + params.turbulence * (sin(phase) * 0.6 + sin(phase * 2.7 + 1.3) * 0.4);
+    let n = grid_normal(column, row);
+    let relative_wind = params.wind * gust - velocity / max(params.dt, 1e-6);
+    let acceleration = params.gravity + n * dot(n, relative_wind) * r.w;
+
+    set_previous(i, p);
+    dst[i] = vec4<f32>(p + velocity + acceleration * params.dt * params.dt, 0.0);
+}
+
+// Jacobi pass over every constraint touching a point: structural and shear neighbours at
+// `stiffness`, two-apart neighbours at `bend_stiffness`. Reads `src`, writes `dst`, so
+// points never race each other.
+@compute @workgroup_size(64)
+fn solve(@builtin(global_invocation_id) id: vec3<u32>) {
+    let i = id.x;
+    if i >= point_count() {
+        return;
+    }
+    let p = src[i].xyz;
+    let r = rest(i);
+    if r.w == 0.0 {
+        dst[i] = vec4<f32>(p, 0.0);
+        return;
+    }
+    let column = i32(i % params.columns);
+    let row = i32(i / params.columns);
+    var offsets = array<vec2<i32>, 12>(
+        vec2<i32>(1, 0), vec2<i32>(-1, 0), vec2<i32>(0, 1), vec2<i32>(0, -1),
+        vec2<i32>(1, 1), vec2<i32>(-1, -1), vec2<i32>(1, -1), vec2<i32>(-1, 1),
+        vec2<i32>(2, 0), vec2<i32>(-2, 0), vec2<i32>(0, 2), vec2<i32>(0, -2),
+    );
+    var correction = vec3<f32>(0.0);
+    var count = 0.0;
+    for (var k = 0u; k < 12u; k++) {
+        let c = column + offsets[k].x;
+        let rw = row + offsets[k].y;
+        if !in_grid(c, rw) {
+            continue;
+        }
+        let j = point_index(c, rw);
+        let other = rest(j);
+        let d = src[j].xyz - p;
+        let len = length(d);
+        if len < 1e-8 {
+            continue;
+        }
+        let rest_length = length(other.xyz - r.xyz);
+        let share = r.w / (r.w + other.w);
+        let k_stiffness = select(params.stiffness, params.bend_stiffness, k >= 8u);
+        correction += d * ((len - rest_length) / len) * share * k_stiffness;
+        count += 1.0;
+    }
+    dst[i] = vec4<f32>(p + correction * (params.relaxation / max(count, 1.0)), 0.0);
+}
+
+fn closest_on_segment(p: vec3<f32>, a: vec3<f32>, b: vec3<f32>) -> vec3<f32> {
+    let ab = b - a;
+    let t = clamp(dot(p - a, ab) / max(dot(ab, ab), 1e-8), 0.0, 1.0);
+    return a + ab * t;
+}
+
+@compute @workgroup_size(64)
+fn collide(@builtin(global_invocation_id) id: vec3<u32>) {
+    let i = id.x;
+    if i >= point_count() || rest(i).w == 0.0 {
+        return;
+    }
+    var p = src[i].xyz;
+    var hit = false;
+    var hit_normal = vec3<f32>(0.0);
+    for (var k = 0u; k < min(params.collider_count, MAX_COLLIDERS); k++) {
+        let collider = params.colliders[k];
+        var normal = vec3<f32>(0.0);
+        var depth = 0.0;
+        if collider.kind == PLANE {
+            normal = collider.a.xyz;
+            depth = params.thickness - (dot(p, normal) - collider.a.w);
+        } else {
+            var center = collider.a.xyz;
+            if collider.kind == CAPSULE {
+                center = closest_on_segment(p, collider.a.xyz, collider.b.xyz);
+            }
+            let d = p - center;
+            let len = length(d);
+            if len < 1e-8 {
+                continue;
+            }
+            normal = d / len;
+            depth = collider.radius + params.thickness - len;
+        }
+        if depth > 0.0 {
+            p += normal * depth;
+            hit = true;
+            hit_normal = normal;
+        }
+    }
+    if hit {
+        // Friction: drop part of the velocity along the surface by moving `previous` with it
+        let prev = previous(i);
+        let velocity = p - prev;
+        let tangent = velocity - hit_normal * dot(velocity, hit_normal);
+        set_previous(i, prev + tangent * params.friction);
+        src[i] = vec4<f32>(p, 0.0);
+    }
+}
+
+@compute @workgroup_size(64)
+fn write_vertices(@builtin(global_invocation_id) id: vec3<u32>) {
+    let i = id.x;
+    if i >= point_count() {
+        return;
+    }
+    let column = i32(i % params.columns);
+    let row = i32(i / params.columns);
+    let p = src[i].xyz;
+    let n = grid_normal(column, row);
+    let uv = vec2<f32>(f32(column) / f32(max(params.columns - 1u, 1u)), f32(row) / f32(max(params.rows - 1u, 1u)));
+    let base = i * 8u;
+    vertices[base + 0u] = p.x;
+    vertices[base + 1u] = p.y;
+    vertices[base + 2u] = p.z;
+    vertices[base + 3u] = n.x;
+    vertices[base + 4u] = n.y;
+    vertices[base + 5u] = n.z;
+    vertices[base + 6u] = uv.x;
+    vertices[base + 7u] = uv.y;
+}
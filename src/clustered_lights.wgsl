@@ -0,0 +1,51 @@
+// Forward+ shading helpers; prepend to a fragment shader (see `CLUSTERED_SHADING_WGSL`)
+// and bind `ClusteredLights::shading_bind_group` at group 1
+
+struct PointLight {
+    position: vec3<f32>,
+    range: f32,
+    color: vec3<f32>,
+    intensity: f32,
+}
+
+struct ClusterParams {
+    view: mat4x4<f32>,
+    inv_proj: mat4x4<f32>,
+    screen_size: vec2<f32>,
+    near: f32,
+    far: f32,
+    grid: vec3<u32>,
+    light_count: u32,
+    max_lights_per_cluster: u32,
+}
+
+@group(1) @binding(0) var<uniform> cluster_params: ClusterParams;
+@group(1) @binding(1) var<storage, read> cluster_light_data: array<PointLight>;
+@group(1) @binding(2) var<storage, read> cluster_counts: array<u32>;
+@group(1) @binding(3) var<storage, read> cluster_lights: array<u32>;
+
+fn cluster_index(frag_coord: vec4<f32>, view_depth: f32) -> u32 {
+    let grid = cluster_params.grid;
+    let tile = min(vec2<u32>(frag_coord.xy / cluster_params.screen_size * vec2<f32>(grid.xy)), grid.xy - 1u);
+    let slice_scale = f32(grid.z) / log(cluster_params.far / cluster_params.near);
+    let slice = u32(clamp(log(view_depth / cluster_params.near) * slice_scale, 0.0, f32(grid.z - 1u)));
+    return tile.x + tile.y * grid.x + slice * grid.x * grid.y;
+}
+
+// Lambert diffuse from the lights binned into this fragment's cluster
+fn shade_clustered_lights(frag_coord: vec4<f32>, world_position: vec3<f32>, normal: vec3<f32>) -> vec3<f32> {
+    let view_depth = -(cluster_params.view * vec4<f32>(world_position, 1.0)).z;
+    let cluster = cluster_index(frag_coord, view_depth);
+    let base = cluster * cluster_params.max_lights_per_cluster;
+    var total = vec3<f32>(0.0);
+    for (var i = 0u; i < cluster_counts[cluster]; i++) {
+        let light = cluster_light_data[cluster_lights[base + i]];
+        let to_light = light.position - world_position;
+        let distance = length(to_light);
+        // Smooth window so the contribution reaches zero exactly at `range`
+        let falloff = pow(clamp(1.0 - pow(distance / light.range, 4.0), 0.0, 1.0), 2.0) / (distance * distance + 1.0);
+        let diffuse = max(dot(normal, to_light / max(distance, 1e-4)), 0.0);
+        total += light.color * light.intensity * diffuse * falloff;
+    }
+    return total;
+}
@@ -0,0 +1,273 @@
+// src/color.rs
+use glam::{Vec3, Vec4};
+
+// Linear RGBA, straight (not premultiplied) alpha; what shaders expect. Anything authored
+// by eye (hex codes, HSV, palettes) is sRGB and converted on the way in.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Color {
+    pub r: f32,
+    pub g: f32,
+    pub b: f32,
+    pub a: f32,
+}
+
+impl Color {
+    pub const WHITE: Color = Color::rgb(1.0, 1.0, 1.0);
+    pub const BLACK: Color = Color::rgb(0.0, 0.0, 0.0);
+    pub const TRANSPARENT: Color = Color::rgba(0.0, 0.0, 0.0, 0.0);
+
+    pub const fn rgb(r: f32, g: f32, b: f32) -> Self {
+        Self { r, g, b, a: 1.0 }
+    }
+
+    pub const fn rgba(r: f32, g: f32, b: f32, a: f32) -> Self {
+        Self { r, g, b, a }
+    }
+
+    pub fn with_alpha(self, a: f32) -> Self {
+        Self { a, ..self }
+    }
+
+    pub fn to_array(self) -> [f32; 4] {
+        [self.r, self.g, self.b, self.a]
+    }
+
+    pub fn rgb_array(self) -> [f32; 3] {
+        [self.r, self.g, self.b]
+    }
+
+    // From sRGB-encoded components, as colour pickers and image editors show them
+    pub fn from_srgb(r: f32, g: f32, b: f32) -> Self {
+        Self::rgb(srgb_to_linear(r), srgb_to_linear(g), srgb_to_linear(b))
+    }
+
+    pub fn from_srgb_u8(r: u8, g: u8, b: u8, a: u8) -> Self {
+        Self::from_srgb(r as f32 / 255.0, g as f32 / 255.0, b as f32 / 255.0).with_alpha(a as f32 / 255.0)
+    }
+
+    // sRGB-encoded components; alpha is never encoded
+    pub fn to_srgb(self) -> [f32; 4] {
+        [linear_to_srgb(self.r), linear_to_srgb(self.g), linear_to_srgb(self.b), self.a]
+    }
+
+    // `0xRRGGBB`, sRGB-encoded like web colours
+    pub fn hex(rgb: u32) -> Self {
+        Self::from_srgb_u8((rgb >> 16) as u8, (rgb >> 8) as u8, rgb as u8, 255)
+    }
+
+    // "#RGB", "#RRGGBB" or "#RRGGBBAA", with or without the '#'
+    pub fn from_hex(hex: &str) -> Result<Self, String> {
+        let digits = hex.strip_prefix('#').unwrap_or(hex);
+        let value = u32::from_str_radix(digits, 16).map_err(|_| format!("Invalid hex colour '{}'", hex))?;
+        let byte = |shift: u32| (value >> shift) as u8;
+        match digits.len() {
+            3 => {
+                let nibble = |shift: u32| ((value >> shift) & 0xf) as u8 * 17;
+                Ok(Self::from_srgb_u8(nibble(8), nibble(4), nibble(0), 255))
+            }
+            6 => Ok(Self::from_srgb_u8(byte(16), byte(8), byte(0), 255)),
+            8 => Ok(Self::from_srgb_u8(byte(24), byte(16), byte(8), byte(0))),
+            _ => Err(format!("Hex colour '{}' needs 3, 6 or 8 digits", hex)),
+        }
+    }
+
+    // "#RRGGBBAA", sRGB-encoded
+    pub fn to_hex(self) -> String {
+        let [r, g, b, a] = self.to_srgb().map(|c| (c.clamp(0.0, 1.0) * 255.0).round() as u8);
+        format!("#{:02X}{:02X}{:02X}{:02X}", r, g, b, a)
+    }
+
+    // Hue in degrees, saturation and value 0..1, over sRGB-encoded values like every
+    // colour picker
+    pub fn from_hsv(hue: f32, saturation: f32, value: f32) -> Self {
+        let h = hue.rem_euclid(360.0) / 60.0;
+        let chroma = value * saturation;
+        let x = chroma * (1.0 - (h % 2.0 - 1.0).abs());
+        let (r, g, b) = match h as u32 {
+            0 => (chroma, x, 0.0),
+            1 => (x, chroma, 0.0),
+            2 => (0.0, chroma, x),
+            3 => (0.0, x, chroma),
+            4 => (x, 0.0, chroma),
+            _ => (chroma, 0.0, x),
+        };
+        let m = value - chroma;
+        Self::from_srgb(r + m, g + m, b + m)
+    }
+
+    // (hue degrees, saturation, value), inverse of `from_hsv`
+    pub fn to_hsv(self) -> (f32, f32, f32) {
+        let [r, g, b, _] = self.to_srgb();
+        let max = r.max(g).max(b);
+        let delta = max - r.min(g).min(b);
+        let hue = if delta == 0.0 {
+            0.0
+        } else if max == r {
+            60.0 * ((g - b) / delta).rem_euclid(6.0)
+        } else if max == g {
+            60.0 * ((b - r) / delta + 2.0)
+        } else {
+            60.0 * ((r - g) / delta + 4.0)
+        };
+        let saturation = if max > 0.0 { delta / max } else { 0.0 };
+        (hue, saturation, max)
+    }
+
+    // In linear space, which is how light mixes
+    pub fn lerp(self, other: Color, t: f32) -> Self {
+        Vec4::from(self).lerp(Vec4::from(other), t).into()
+    }
+
+    // Scales rgb, leaving alpha, e.g. for light intensity
+    pub fn scaled(self, factor: f32) -> Self {
+        Self { r: self.r * factor, g: self.g * factor, b: self.b * factor, a: self.a }
+    }
+
+    pub fn rgb_vec(self) -> Vec3 {
+        Vec3::new(self.r, self.g, self.b)
+    }
+
+    // Relative luminance, from linear values
+    pub fn luminance(self) -> f32 {
+        0.2126 * self.r + 0.7152 * self.g + 0.0722 * self.b
+    }
+}
+
+impl From<[f32; 4]> for Color {
+    fn from(c: [f32; 4]) -> Self {
+        Self::rgba(c[0], c[1], c[2], c[3])
+    }
+}
+
+impl From<[f32; 3]> for Color {
+    fn from(c: [f32; 3]) -> Self {
+        Self::rgb(c[0], c[1], c[2])
+    }
+}
+
+impl From<Color> for [f32; 4] {
+    fn from(c: Color) -> Self {
+        c.to_array()
+    }
+}
+
+impl From<Vec4> for Color {
+    fn from(c: Vec4) -> Self {
+        Self::rgba(c.x, c.y, c.z, c.w)
+    }
+}
+
+impl From<Color> for Vec4 {
+    fn from(c: Color) -> Self {
+        Vec4::new(c.r, c.g, c.b, c.a)
+    }
+}
+
+impl From<Vec3> for Color {
+    fn from(c: Vec3) -> Self {
+        Self::rgb(c.x, c.y, c.z)
+    }
+}
+
+pub fn srgb_to_linear(c: f32) -> f32 {
+    if c <= 0.04045 { c / 12.92 } else { ((c + 0.055) / 1.055).powf(2.4) }
+}
+
+pub fn linear_to_srgb(c: f32) -> f32 {
+    if c <= 0.0031308 { c * 12.92 } else { 1.055 * c.powf(1.0 / 2.4) - 0.055 }
+}
+
+// Colours are linear, which is what wgpu clear values take for any target format
+impl From<Color> for wgpu::Color {
+    fn from(c: Color) -> Self {
+        wgpu::Color { r: c.r as f64, g: c.g as f64, b: c.b as f64, a: c.a as f64 }
+    }
+}
+
+// Named colours, as their web sRGB values
+pub mod named {
+    use super::Color;
+
+    pub fn red() -> Color {
+        Color::hex(0xFF0000)
+    }
+
+    pub fn green() -> Color {
+        Color::hex(0x00FF00)
+    }
+
+    pub fn blue() -> Color {
+        Color::hex(0x0000FF)
+    }
+
+    pub fn yellow() -> Color {
+        Color::hex(0xFFFF00)
+    }
+
+    pub fn cyan() -> Color {
+        Color::hex(0x00FFFF)
+    }
+
+    pub fn magenta() -> Color {
+        Color::hex(0xFF00FF)
+    }
+
+    pub fn orange() -> Color {
+        Color::hex(0xFFA500)
+    }
+
+    pub fn gray() -> Color {
+        Color::hex(0x808080)
+    }
+
+    pub fn cornflower_blue() -> Color {
+        Color::hex(0x6495ED)
+    }
+}
+
+// An ordered set of colours, e.g. a pixel-art game's fixed palette
+#[derive(Debug, Clone, PartialEq)]
+pub struct Palette {
+    pub name: String,
+    pub colors: Vec<Color>,
+}
+
+impl Palette {
+    pub fn from_hex(name: &str, hex: &[&str]) -> Result<Self, String> {
+        let colors = hex.iter().map(|h| Color::from_hex(h)).collect::<Result<_, _>>()?;
+        Ok(Self { name: name.to_string(), colors })
+    }
+
+    fn from_rgb(name: &str, rgb: &[u32]) -> Self {
+        Self { name: name.to_string(), colors: rgb.iter().map(|&c| Color::hex(c)).collect() }
+    }
+
+    pub fn pico8() -> Self {
+        Self::from_rgb("pico-8", &[
+            0x000000, 0x1D2B53, 0x7E2553, 0x008751, 0xAB5236, 0x5F574F, 0xC2C3C7, 0xFFF1E8,
+            0xFF004D, 0xFFA300, 0xFFEC27, 0x00E436, 0x29ADFF, 0x83769C, 0xFF77A8, 0xFFCCAA,
+        ])
+    }
+
+    // The four greens of the original handheld
+    pub fn game_boy() -> Self {
+        Self::from_rgb("game boy", &[0x0F380F, 0x306230, 0x8BAC0F, 0x9BBC0F])
+    }
+
+    // Wraps around, so indices can count up forever
+    pub fn get(&self, index: usize) -> Color {
+        if self.colors.is_empty() { Color::BLACK } else { self.colors[index % self.colors.len()] }
+    }
+
+    // Closest entry by sRGB distance, e.g. to snap colours to the palette
+    pub fn nearest(&self, color: Color) -> Option<Color> {
+        let target = Vec4::from(color.to_srgb()).truncate();
+        self.colors
+            .iter()
+            .min_by(|a, b| {
+                let distance = |c: &Color| Vec4::from(c.to_srgb()).truncate().distance_squared(target);
+                distance(a).total_cmp(&distance(b))
+            })
+            .copied()
+    }
+}
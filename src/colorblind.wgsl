@@ -0,0 +1,12 @@
+// Colour vision filter, added to a `ScreenEffectChain` by `AccessibilitySettings::apply_filter`.
+// `effect.params[0..3]` are the rows of the colour matrix and `effect.params[3].x` how much
+// of it to apply. Expects linear colour.
+
+@fragment
+fn fs_main(in: EffectVertex) -> @location(0) vec4<f32> {
+    let color = sample_source(in.uv);
+    // Built from rows, so the colour multiplies from the left
+    let color_matrix = mat3x3<f32>(effect.params[0].xyz, effect.params[1].xyz, effect.params[2].xyz);
+    let filtered = max(color.rgb * color_matrix, vec3<f32>(0.0));
+    return vec4<f32>(mix(color.rgb, filtered, effect.params[3].x), color.a);
+}
@@ -0,0 +1,257 @@
+// src/cutscene.rs
+use crate::render_texture::ViewCamera;
+use glam::Vec3;
+use serde::Deserialize;
+use std::sync::Arc;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+pub enum Ease {
+    Linear,
+    #[default]
+    Smooth,
+}
+
+// Camera pose at `time`; the camera eases from each key to the next
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct CameraKey {
+    pub time: f32,
+    pub position: [f32; 3],
+    pub target: [f32; 3],
+    #[serde(default = "default_fov")]
+    pub fov_y_degrees: f32,
+    // How the move into the next key is paced
+    #[serde(default)]
+    pub ease: Ease,
+}
+
+fn default_fov() -> f32 {
+    60.0
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct AnimationCue {
+    pub time: f32,
+    // Name the game resolves to an entity, e.g. "hero"
+    pub target: String,
+    // Trigger set on the target's `AnimationController`
+    pub trigger: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct AudioCue {
+    pub time: f32,
+    pub sound: String,
+    #[serde(default = "default_volume")]
+    pub volume: f32,
+}
+
+fn default_volume() -> f32 {
+    1.0
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct EventCue {
+    pub time: f32,
+    pub name: String,
+}
+
+// A scripted sequence, loaded from RON, e.g.
+// `(name: "intro", duration: 8.0, camera: [(time: 0.0, position: (0, 2, 10), target: (0, 1, 0))],
+//   events: [(time: 4.0, name: "open_gate")])`
+#[derive(Debug, Clone, Deserialize)]
+pub struct Timeline {
+    pub name: String,
+    pub duration: f32,
+    #[serde(default = "default_skippable")]
+    pub skippable: bool,
+    #[serde(default)]
+    pub camera: Vec<CameraKey>,
+    #[serde(default)]
+    pub animations: Vec<AnimationCue>,
+    #[serde(default)]
+    pub audio: Vec<AudioCue>,
+    #[serde(default)]
+    pub events: Vec<EventCue>,
+}
+
+fn default_skippable() -> bool {
+    true
+}
+
+impl Timeline {
+    pub fn from_ron(source: &str) -> Result<Self, String> {
+        let mut timeline: Self = ron::from_str(source).map_err(|e| format!("Failed to parse timeline: {}", e))?;
+        if !timeline.duration.is_finite() || timeline.duration <= 0.0 {
+            return Err(format!("Timeline '{}' needs a positive duration", timeline.name));
+        }
+        let times = timeline.camera.iter().map(|k| k.time)
+            .chain(timeline.animations.iter().map(|c| c.time))
+            .chain(timeline.audio.iter().map(|c| c.time))
+            .chain(timeline.events.iter().map(|c| c.time));
+        if let Some(time) = times.into_iter().find(|t| !(0.0..=timeline.duration).contains(t)) {
+            return Err(format!("Timeline '{}' has a key at {}s, outside 0..{}", timeline.name, time, timeline.duration));
+        }
+        // Cues fire in time order, and camera keys are searched in order
+        timeline.camera.sort_by(|a, b| a.time.total_cmp(&b.time));
+        timeline.animations.sort_by(|a, b| a.time.total_cmp(&b.time));
+        timeline.audio.sort_by(|a, b| a.time.total_cmp(&b.time));
+        timeline.events.sort_by(|a, b| a.time.total_cmp(&b.time));
+        Ok(timeline)
+    }
+
+    pub fn load(path: &str) -> Result<Self, String> {
+        let source = std::fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+        Self::from_ron(&source)
+    }
+
+    // Camera along the track at `time`; `None` when the timeline has no camera keys
+    pub fn camera_at(&self, time: f32, aspect: f32) -> Option<ViewCamera> {
+        let camera = |position: Vec3, target: Vec3, fov: f32| {
+            ViewCamera::perspective(position, target, fov.to_radians(), aspect, 0.1, 1000.0)
+        };
+        let next = self.camera.iter().position(|k| k.time > time);
+        let key = match next {
+            Some(0) => &self.camera[0],
+            None => self.camera.last()?,
+            Some(i) => {
+                let (a, b) = (&self.camera[i - 1], &self.camera[i]);
+                let mut t = (time - a.time) / (b.time - a.time).max(f32::EPSILON);
+                if a.ease == Ease::Smooth {
+                    t = t * t * (3.0 - 2.0 * t);
+                }
+                return Some(camera(
+                    Vec3::from(a.position).lerp(Vec3::from(b.position), t),
+                    Vec3::from(a.target).lerp(Vec3::from(b.target), t),
+                    a.fov_y_degrees + (b.fov_y_degrees - a.fov_y_degrees) * t,
+                ));
+            }
+        };
+        Some(camera(Vec3::from(key.position), Vec3::from(key.target), key.fov_y_degrees))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CutsceneCue {
+    Animation { target: String, trigger: String },
+    Audio { sound: String, volume: f32 },
+    Event(String),
+    Finished { skipped: bool },
+}
+
+// Component playing a `Timeline`. `update` advances the playhead and queues every cue it
+// passes; the game routes them to animation controllers, audio and gameplay, and takes
+// the camera from `camera` while the cutscene runs.
+#[derive(Debug, Clone)]
+pub struct CutscenePlayer {
+    pub timeline: Arc<Timeline>,
+    pub speed: f32,
+    time: f32,
+    playing: bool,
+    cues: Vec<CutsceneCue>,
+}
+
+impl CutscenePlayer {
+    pub fn new(timeline: Arc<Timeline>) -> Self {
+        Self { timeline, speed: 1.0, time: 0.0, playing: true, cues: Vec::new() }
+    }
+
+    pub fn time(&self) -> f32 {
+        self.time
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.playing
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.time >= self.timeline.duration
+    }
+
+    pub fn pause(&mut self) {
+        self.playing = false;
+    }
+
+    pub fn resume(&mut self) {
+        self.playing = !self.is_finished();
+    }
+
+    pub fn update(&mut self, delta_time: f32) {
+        if !self.playing {
+            return;
+        }
+        let from = self.time;
+        self.time = (self.time + delta_time * self.speed).min(self.timeline.duration);
+        self.fire(from, self.time, true);
+        if self.is_finished() {
+            self.playing = false;
+            self.cues.push(CutsceneCue::Finished { skipped: false });
+        }
+    }
+
+    // Jumps to the end. Gameplay events still fire so the world ends up as if the
+    // cutscene had played; animation and audio cues are dropped. False if not skippable.
+    pub fn skip(&mut self) -> bool {
+        if !self.timeline.skippable || self.is_finished() {
+            return false;
+        }
+        let from = self.time;
+        self.time = self.timeline.duration;
+        self.fire(from, self.time, false);
+        self.playing = false;
+        self.cues.push(CutsceneCue::Finished { skipped: true });
+        true
+    }
+
+    // Moves the playhead without firing anything, for scrubbing in the editor
+    pub fn seek(&mut self, time: f32) {
+        self.time = time.clamp(0.0, self.timeline.duration);
+    }
+
+    // Queues cues in (from, to]; a cue at 0 fires on the first update
+    fn fire(&mut self, from: f32, to: f32, presentation: bool) {
+        let passed = |time: f32| (time > from || (from == 0.0 && time == 0.0)) && time <= to;
+        let timeline = &self.timeline;
+        let mut fired: Vec<(f32, CutsceneCue)> = Vec::new();
+        if presentation {
+            fired.extend(timeline.animations.iter().filter(|c| passed(c.time)).map(|c| {
+                (c.time, CutsceneCue::Animation { target: c.target.clone(), trigger: c.trigger.clone() })
+            }));
+            fired.extend(
+                timeline.audio.iter().filter(|c| passed(c.time)).map(|c| (c.time, CutsceneCue::Audio { sound: c.sound.clone(), volume: c.volume })),
+            );
+        }
+        fired.extend(timeline.events.iter().filter(|c| passed(c.time)).map(|c| (c.time, CutsceneCue::Event(c.name.clone()))));
+        // Stable, so cues at the same time keep track order
+        fired.sort_by(|a, b| a.0.total_cmp(&b.0));
+        self.cues.extend(fired.into_iter().map(|(_, cue)| cue));
+    }
+
+    pub fn camera(&self, aspect: f32) -> Option<ViewCamera> {
+        self.timeline.camera_at(self.time, aspect)
+    }
+
+    pub fn drain_cues(&mut self) -> impl Iterator<Item = CutsceneCue> + '_ {
+        self.cues.drain(..)
+    }
+
+    // Plain-text view of the tracks around the playhead, for the editor panel and logs
+    pub fn panel(&self) -> String {
+        let timeline = &self.timeline;
+        let state = if self.is_finished() { "finished" } else if self.playing { "playing" } else { "paused" };
+        let mut out = format!("{} {:.2}/{:.2}s {}\n", timeline.name, self.time, timeline.duration, state);
+        let marker = |time: f32| if time <= self.time { '*' } else { ' ' };
+        for key in &timeline.camera {
+            out += &format!("  {} {:>7.2}s camera  {:?} -> {:?}\n", marker(key.time), key.time, key.position, key.target);
+        }
+        for cue in &timeline.animations {
+            out += &format!("  {} {:>7.2}s anim    {} {}\n", marker(cue.time), cue.time, cue.target, cue.trigger);
+        }
+        for cue in &timeline.audio {
+            out += &format!("  {} {:>7.2}s audio   {} ({:.2})\n", marker(cue.time), cue.time, cue.sound, cue.volume);
+        }
+        for cue in &timeline.events {
+            out += &format!("  {} {:>7.2}s event   {}\n", marker(cue.time), cue.time, cue.name);
+        }
+        out
+    }
+}
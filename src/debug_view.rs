@@ -0,0 +1,319 @@
+// src/debug_view.rs
+use crate::bind_cache::BindGroupCache;
+use crate::capabilities::GpuCapabilities;
+use crate::gpu_buffer::{GpuStruct, UniformBuffer};
+use crate::mesh::{GpuMesh, MeshVertex};
+use crate::resource_registry::{ResourceRegistry, Tracked};
+use crate::upload::UploadBelt;
+use glam::Mat4;
+
+const DEBUG_DEPTH_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth32Float;
+
+// What the debug pass shows in place of the normal shading
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum DebugView {
+    #[default]
+    Off,
+    Wireframe,
+    // Brighter where more fragments land on the same pixel
+    Overdraw,
+    Normals,
+    Depth,
+    UvChecker,
+    // Geometry under a plain white key light, without materials
+    LightingOnly,
+}
+
+impl DebugView {
+    pub const ALL: [DebugView; 7] = [
+        DebugView::Off,
+        DebugView::Wireframe,
+        DebugView::Overdraw,
+        DebugView::Normals,
+        DebugView::Depth,
+        DebugView::UvChecker,
+        DebugView::LightingOnly,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            DebugView::Off => "off",
+            DebugView::Wireframe => "wireframe",
+            DebugView::Overdraw => "overdraw",
+            DebugView::Normals => "normals",
+            DebugView::Depth => "depth",
+            DebugView::UvChecker => "uv_checker",
+            DebugView::LightingOnly => "lighting_only",
+        }
+    }
+
+    pub fn parse(name: &str) -> Result<Self, String> {
+        Self::ALL
+            .into_iter()
+            .find(|view| view.name().eq_ignore_ascii_case(name.trim()))
+            .ok_or_else(|| format!("Unknown debug view '{}'", name))
+    }
+
+    // Next view in `ALL`, wrapping back to `Off`
+    pub fn cycle(self) -> Self {
+        let index = Self::ALL.iter().position(|view| *view == self).unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+
+    // Console and shortcut commands: `debug_view.cycle` or `debug_view.<name>`, e.g.
+    // `debug_view.wireframe`. Returns false for commands that aren't debug view ones.
+    pub fn apply_command(&mut self, command: &str) -> bool {
+        let Some(argument) = command.strip_prefix("debug_view.") else { return false };
+        if argument == "cycle" {
+            *self = self.cycle();
+            return true;
+        }
+        match Self::parse(argument) {
+            Ok(view) => {
+                *self = view;
+                true
+            }
+            Err(_) => false,
+        }
+    }
+
+    fn fragment_entry(self) -> &'static str {
+        match self {
+            DebugView::Off | DebugView::Wireframe => "fs_wireframe",
+            DebugView::Overdraw => "fs_overdraw",
+            DebugView::Normals => "fs_normals",
+            DebugView::Depth => "fs_depth",
+            DebugView::UvChecker => "fs_uv_checker",
+            DebugView::LightingOnly => "fs_lighting",
+        }
+    }
+}
+
+impl std::fmt::Display for DebugView {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+// One mesh drawn by the debug pass
+pub struct DebugDraw<'a> {
+    pub mesh: &'a GpuMesh,
+    pub transform: Mat4,
+}
+
+// Mirrors `DebugObject` in debug_view.wgsl
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct DebugObject {
+    clip_from_local: [f32; 16],
+    world_from_local: [f32; 16],
+    params: [f32; 4],
+}
+
+impl GpuStruct for DebugObject {}
+
+// Draws meshes with a debug shader over a colour target, in place of the game's own
+// passes while a view is active. Each view is a pipeline variant of one shader, built the
+// first time it's shown. Wireframe uses line rasterization where the device has
+// POLYGON_MODE_LINE and flat-shaded facets elsewhere.
+pub struct DebugViewPass {
+    pub view: DebugView,
+    // View distances mapped to white and black by `DebugView::Depth`
+    pub depth_range: (f32, f32),
+    // Checker cells per UV unit
+    pub checker_scale: f32,
+    objects: UniformBuffer<DebugObject>,
+    layout: wgpu::BindGroupLayout,
+    pipeline_layout: wgpu::PipelineLayout,
+    shader: wgpu::ShaderModule,
+    pipelines: Vec<(DebugView, wgpu::RenderPipeline)>,
+    color_format: wgpu::TextureFormat,
+    line_mode: bool,
+    depth: Tracked<wgpu::Texture>,
+    max_objects: u32,
+}
+
+impl DebugViewPass {
+    pub fn new(
+        device: &wgpu::Device,
+        resources: &ResourceRegistry,
+        capabilities: &GpuCapabilities,
+        color_format: wgpu::TextureFormat,
+        width: u32,
+        height: u32,
+        max_objects: u32,
+    ) -> Self {
+        // Dynamic offsets need more than one slot
+        let max_objects = max_objects.max(2);
+        let objects = UniformBuffer::new(device, resources, "debug view objects", max_objects);
+        let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("debug view"),
+            entries: &[objects.layout_entry(0, wgpu::ShaderStages::VERTEX_FRAGMENT)],
+        });
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("debug view"),
+            bind_group_layouts: &[&layout],
+            push_constant_ranges: &[],
+        });
+        let shader = device.create_shader_module(wgpu::include_wgsl!("debug_view.wgsl"));
+        let line_mode = capabilities.has(wgpu::Features::POLYGON_MODE_LINE);
+        if !line_mode {
+            log::info!("POLYGON_MODE_LINE unavailable; the wireframe debug view shows facets instead");
+        }
+        Self {
+            view: DebugView::Off,
+            depth_range: (0.1, 100.0),
+            checker_scale: 8.0,
+            objects,
+            layout,
+            pipeline_layout,
+            shader,
+            pipelines: Vec::new(),
+            color_format,
+            line_mode,
+            depth: Self::create_depth(device, resources, width, height),
+            max_objects,
+        }
+    }
+
+    fn create_depth(device: &wgpu::Device, resources: &ResourceRegistry, width: u32, height: u32) -> Tracked<wgpu::Texture> {
+        resources.create_texture(device, &wgpu::TextureDescriptor {
+            label: Some("debug view depth"),
+            size: wgpu::Extent3d { width: width.max(1), height: height.max(1), depth_or_array_layers: 1 },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: DEBUG_DEPTH_FORMAT,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
+        }, "debug view")
+    }
+
+    pub fn resize(&mut self, device: &wgpu::Device, resources: &ResourceRegistry, width: u32, height: u32) {
+        if (self.depth.width(), self.depth.height()) != (width.max(1), height.max(1)) {
+            self.depth = Self::create_depth(device, resources, width, height);
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.view != DebugView::Off
+    }
+
+    // False when wireframe falls back to facets
+    pub fn has_line_mode(&self) -> bool {
+        self.line_mode
+    }
+
+    fn create_pipeline(&self, device: &wgpu::Device, view: DebugView) -> wgpu::RenderPipeline {
+        let overdraw = view == DebugView::Overdraw;
+        let polygon_mode = if view == DebugView::Wireframe && self.line_mode { wgpu::PolygonMode::Line } else { wgpu::PolygonMode::Fill };
+        let additive = wgpu::BlendComponent {
+            src_factor: wgpu::BlendFactor::One,
+            dst_factor: wgpu::BlendFactor::One,
+            operation: wgpu::BlendOperation::Add,
+        };
+        device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some(view.name()),
+            layout: Some(&self.pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &self.shader,
+                entry_point: Some("vs_main"),
+                buffers: &[MeshVertex::layout()],
+                compilation_options: Default::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &self.shader,
+                entry_point: Some(view.fragment_entry()),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: self.color_format,
+                    blend: overdraw.then_some(wgpu::BlendState { color: additive, alpha: additive }),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: Default::default(),
+            }),
+            // No culling, so back faces and inside-out meshes show up too
+            primitive: wgpu::PrimitiveState { polygon_mode, ..Default::default() },
+            // Overdraw counts every fragment, hidden or not
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: DEBUG_DEPTH_FORMAT,
+                depth_write_enabled: !overdraw,
+                depth_compare: if overdraw { wgpu::CompareFunction::Always } else { wgpu::CompareFunction::Less },
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        })
+    }
+
+    // Clears `target` and draws `draws` with the current view; does nothing while `Off`.
+    // `target` must have the colour format given to `new` and the size of the last `resize`.
+    // Returns the draw calls issued; draws past `max_objects` are skipped.
+    #[allow(clippy::too_many_arguments)]
+    pub fn render(
+        &mut self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        belt: &mut UploadBelt,
+        bind_groups: &mut BindGroupCache,
+        target: &wgpu::TextureView,
+        view_proj: Mat4,
+        draws: &[DebugDraw<'_>],
+    ) -> u32 {
+        let view = self.view;
+        if view == DebugView::Off {
+            return 0;
+        }
+        if !self.pipelines.iter().any(|(built, _)| *built == view) {
+            let pipeline = self.create_pipeline(device, view);
+            self.pipelines.push((view, pipeline));
+        }
+        if draws.len() > self.max_objects as usize {
+            log::warn!("Debug view drawing {} of {} meshes; raise max_objects", self.max_objects, draws.len());
+        }
+
+        self.objects.begin_frame();
+        let params = [self.depth_range.0, self.depth_range.1, self.checker_scale, 0.0];
+        let mut offsets = Vec::with_capacity(draws.len());
+        for draw in draws.iter().take(self.max_objects as usize) {
+            let object = DebugObject {
+                clip_from_local: (view_proj * draw.transform).to_cols_array(),
+                world_from_local: draw.transform.to_cols_array(),
+                params,
+            };
+            if let Ok(offset) = self.objects.push(device, encoder, belt, &object) {
+                offsets.push(offset);
+            }
+        }
+
+        let depth_view = self.depth.create_view(&wgpu::TextureViewDescriptor::default());
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("debug view"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: target,
+                resolve_target: None,
+                ops: wgpu::Operations { load: wgpu::LoadOp::Clear(wgpu::Color::BLACK), store: wgpu::StoreOp::Store },
+                depth_slice: None,
+            })],
+            depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                view: &depth_view,
+                depth_ops: Some(wgpu::Operations { load: wgpu::LoadOp::Clear(1.0), store: wgpu::StoreOp::Discard }),
+                stencil_ops: None,
+            }),
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        if offsets.is_empty() {
+            return 0;
+        }
+        let Some((_, pipeline)) = self.pipelines.iter().find(|(built, _)| *built == view) else { return 0 };
+        let bind_group = self.objects.bind_group(device, bind_groups, &self.layout);
+        render_pass.set_pipeline(pipeline);
+        for (draw, offset) in draws.iter().zip(&offsets) {
+            render_pass.set_bind_group(0, &bind_group, &[*offset]);
+            draw.mesh.draw(&mut render_pass);
+        }
+        offsets.len() as u32
+    }
+}
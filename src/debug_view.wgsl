@@ -0,0 +1,78 @@
+// Renderer debug views: one vertex stage, a fragment entry point per view
+
+struct DebugObject {
+    clip_from_local: mat4x4<f32>,
+    world_from_local: mat4x4<f32>,
+    // Near and far distances for the depth view, UV checker cells per unit, unused
+    params: vec4<f32>,
+}
+
+@group(0) @binding(0) var<uniform> object: DebugObject;
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) world_position: vec3<f32>,
+    @location(1) normal: vec3<f32>,
+    @location(2) uv: vec2<f32>,
+    @location(3) view_depth: f32,
+}
+
+@vertex
+fn vs_main(
+    @location(0) position: vec3<f32>,
+    @location(1) normal: vec3<f32>,
+    @location(2) uv: vec2<f32>,
+) -> VertexOutput {
+    var out: VertexOutput;
+    out.position = object.clip_from_local * vec4<f32>(position, 1.0);
+    out.world_position = (object.world_from_local * vec4<f32>(position, 1.0)).xyz;
+    // Fine for uniform scale; debug views don't need the inverse transpose
+    out.normal = (object.world_from_local * vec4<f32>(normal, 0.0)).xyz;
+    out.uv = uv;
+    out.view_depth = out.position.w;
+    return out;
+}
+
+// Lines with POLYGON_MODE_LINE; filled facets where that isn't supported
+@fragment
+fn fs_wireframe(in: VertexOutput) -> @location(0) vec4<f32> {
+    let face = normalize(cross(dpdx(in.world_position), dpdy(in.world_position)));
+    return vec4<f32>(abs(face) * 0.5 + 0.5, 1.0);
+}
+
+// Each layer adds a little heat; blended additively with no depth test
+@fragment
+fn fs_overdraw() -> @location(0) vec4<f32> {
+    return vec4<f32>(0.12, 0.05, 0.02, 1.0);
+}
+
+@fragment
+fn fs_normals(in: VertexOutput) -> @location(0) vec4<f32> {
+    return vec4<f32>(normalize(in.normal) * 0.5 + 0.5, 1.0);
+}
+
+// Linear view distance, white near to black far
+@fragment
+fn fs_depth(in: VertexOutput) -> @location(0) vec4<f32> {
+    let near = object.params.x;
+    let far = max(object.params.y, near + 0.001);
+    let d = 1.0 - clamp((in.view_depth - near) / (far - near), 0.0, 1.0);
+    return vec4<f32>(d, d, d, 1.0);
+}
+
+// Checker cells tinted by the UV itself, so stretching and seams both show
+@fragment
+fn fs_uv_checker(in: VertexOutput) -> @location(0) vec4<f32> {
+    let cell = floor(in.uv * object.params.z);
+    let checker = select(0.35, 1.0, ((i32(cell.x) + i32(cell.y)) & 1) == 0);
+    return vec4<f32>(vec3<f32>(fract(in.uv), 1.0) * checker, 1.0);
+}
+
+// White albedo under a fixed key light plus ambient
+@fragment
+fn fs_lighting(in: VertexOutput) -> @location(0) vec4<f32> {
+    let light = normalize(vec3<f32>(0.4, 0.8, 0.45));
+    let diffuse = max(dot(normalize(in.normal), light), 0.0);
+    let l = 0.15 + 0.85 * diffuse;
+    return vec4<f32>(l, l, l, 1.0);
+}
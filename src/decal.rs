@@ -0,0 +1,346 @@
+// src/decal.rs
+use crate::bind_cache::{BindGroupCache, BindingKey, SamplerCache};
+use crate::resource_registry::ResourceRegistry;
+use crate::upload::{DynamicBuffer, UploadBelt};
+use glam::{Mat4, Vec4};
+
+// Mirrors `DecalParams` in decal.wgsl
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct DecalParams {
+    view_proj: [f32; 16],
+    inv_view_proj: [f32; 16],
+    screen_size: [f32; 2],
+    _pad: [f32; 2],
+}
+
+// Mirrors `DecalInstance` in decal.wgsl
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct DecalInstance {
+    model: [f32; 16],
+    inv_model: [f32; 16],
+    tint: [f32; 4],
+    uv_rect: [f32; 4],
+}
+
+impl DecalInstance {
+    const ATTRIBUTES: [wgpu::VertexAttribute; 10] = wgpu::vertex_attr_array![
+        0 => Float32x4, 1 => Float32x4, 2 => Float32x4, 3 => Float32x4,
+        4 => Float32x4, 5 => Float32x4, 6 => Float32x4, 7 => Float32x4,
+        8 => Float32x4, 9 => Float32x4,
+    ];
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Decal {
+    // Maps the unit box (-0.5..0.5) to world space; the texture projects along local -Y
+    pub transform: Mat4,
+    pub tint: Vec4,
+    // Atlas region as (x, y, width, height) in UV units
+    pub uv_rect: Vec4,
+    // Seconds until removal; `None` keeps it until the pool needs the slot
+    pub lifetime: Option<f32>,
+    // Seconds over which it fades out at the end of its lifetime
+    pub fade_time: f32,
+}
+
+impl Decal {
+    pub fn new(transform: Mat4) -> Self {
+        Self {
+            transform,
+            tint: Vec4::ONE,
+            uv_rect: Vec4::new(0.0, 0.0, 1.0, 1.0),
+            lifetime: None,
+            fade_time: 0.0,
+        }
+    }
+
+    pub fn with_tint(mut self, tint: Vec4) -> Self {
+        self.tint = tint;
+        self
+    }
+
+    pub fn with_uv_rect(mut self, uv_rect: Vec4) -> Self {
+        self.uv_rect = uv_rect;
+        self
+    }
+
+    pub fn with_lifetime(mut self, lifetime: f32, fade_time: f32) -> Self {
+        self.lifetime = Some(lifetime);
+        self.fade_time = fade_time;
+        self
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DecalHandle {
+    index: u32,
+    generation: u32,
+}
+
+struct Slot {
+    decal: Option<Decal>,
+    age: f32,
+    generation: u32,
+    // Spawn order, so a full pool recycles the oldest decal first
+    spawned: u64,
+}
+
+// Fixed-capacity decal storage. Spawning into a full pool replaces the oldest decal, so
+// bullet holes and blood splats can be spawned freely without unbounded growth.
+pub struct DecalPool {
+    slots: Vec<Slot>,
+    spawn_count: u64,
+}
+
+impl DecalPool {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            slots: (0..capacity.max(1)).map(|_| Slot { decal: None, age: 0.0, generation: 0, spawned: 0 }).collect(),
+            spawn_count: 0,
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.slots.len()
+    }
+
+    pub fn len(&self) -> usize {
+        self.slots.iter().filter(|slot| slot.decal.is_some()).count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn spawn(&mut self, decal: Decal) -> DecalHandle {
+        let index = self.slots.iter().position(|slot| slot.decal.is_none()).unwrap_or_else(|| {
+            self.slots.iter().enumerate().min_by_key(|(_, slot)| slot.spawned).map(|(i, _)| i).unwrap_or(0)
+        });
+        self.spawn_count += 1;
+        let slot = &mut self.slots[index];
+        slot.decal = Some(decal);
+        slot.age = 0.0;
+        slot.generation = slot.generation.wrapping_add(1);
+        slot.spawned = self.spawn_count;
+        DecalHandle { index: index as u32, generation: slot.generation }
+    }
+
+    // False if the decal already expired or its slot was recycled
+    pub fn remove(&mut self, handle: DecalHandle) -> bool {
+        match self.slots.get_mut(handle.index as usize) {
+            Some(slot) if slot.generation == handle.generation && slot.decal.is_some() => {
+                slot.decal = None;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn get_mut(&mut self, handle: DecalHandle) -> Option<&mut Decal> {
+        self.slots
+            .get_mut(handle.index as usize)
+            .filter(|slot| slot.generation == handle.generation)
+            .and_then(|slot| slot.decal.as_mut())
+    }
+
+    // Ages every decal and frees the ones whose lifetime ran out
+    pub fn update(&mut self, delta_time: f32) {
+        for slot in &mut self.slots {
+            let Some(decal) = &slot.decal else { continue };
+            slot.age += delta_time;
+            if decal.lifetime.is_some_and(|lifetime| slot.age >= lifetime) {
+                slot.decal = None;
+            }
+        }
+    }
+
+    pub fn clear(&mut self) {
+        for slot in &mut self.slots {
+            slot.decal = None;
+        }
+    }
+
+    fn instances(&self) -> Vec<DecalInstance> {
+        self.slots
+            .iter()
+            .filter_map(|slot| {
+                let decal = slot.decal.as_ref()?;
+                let fade = match decal.lifetime {
+                    Some(lifetime) if decal.fade_time > 0.0 => ((lifetime - slot.age) / decal.fade_time).clamp(0.0, 1.0),
+                    _ => 1.0,
+                };
+                Some(DecalInstance {
+                    model: decal.transform.to_cols_array(),
+                    inv_model: decal.transform.inverse().to_cols_array(),
+                    tint: (decal.tint * Vec4::new(1.0, 1.0, 1.0, fade)).into(),
+                    uv_rect: decal.uv_rect.into(),
+                })
+            })
+            .collect()
+    }
+}
+
+// Draws a `DecalPool` over an already-rendered scene. Needs the scene depth as a texture,
+// so it runs in its own pass after the opaque geometry and before transparents.
+pub struct DecalRenderer {
+    params: DynamicBuffer,
+    instances: DynamicBuffer,
+    instance_count: u32,
+    layout: wgpu::BindGroupLayout,
+    pipeline: wgpu::RenderPipeline,
+}
+
+impl DecalRenderer {
+    pub fn new(device: &wgpu::Device, resources: &ResourceRegistry, color_format: wgpu::TextureFormat) -> Self {
+        let texture_entry = |binding, sample_type| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Texture {
+                sample_type,
+                view_dimension: wgpu::TextureViewDimension::D2,
+                multisampled: false,
+            },
+            count: None,
+        };
+        let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("decals"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                texture_entry(1, wgpu::TextureSampleType::Depth),
+                texture_entry(2, wgpu::TextureSampleType::Float { filterable: true }),
+                wgpu::BindGroupLayoutEntry {
+                    binding: 3,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+        });
+        let shader = device.create_shader_module(wgpu::include_wgsl!("decal.wgsl"));
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("decals"),
+            bind_group_layouts: &[&layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("decals"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: Some("vs_main"),
+                buffers: &[wgpu::VertexBufferLayout {
+                    array_stride: std::mem::size_of::<DecalInstance>() as wgpu::BufferAddress,
+                    step_mode: wgpu::VertexStepMode::Instance,
+                    attributes: &DecalInstance::ATTRIBUTES,
+                }],
+                compilation_options: Default::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: Some("fs_main"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: color_format,
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::COLOR,
+                })],
+                compilation_options: Default::default(),
+            }),
+            // Back faces only, so each pixel is shaded once and the box still draws with
+            // the camera inside it
+            primitive: wgpu::PrimitiveState { cull_mode: Some(wgpu::Face::Front), ..Default::default() },
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        });
+
+        Self {
+            params: DynamicBuffer::new(
+                device,
+                resources,
+                "decal params",
+                wgpu::BufferUsages::UNIFORM,
+                std::mem::size_of::<DecalParams>() as u64,
+            ),
+            instances: DynamicBuffer::new(
+                device,
+                resources,
+                "decal instances",
+                wgpu::BufferUsages::VERTEX,
+                std::mem::size_of::<DecalInstance>() as u64 * 64,
+            ),
+            instance_count: 0,
+            layout,
+            pipeline,
+        }
+    }
+
+    // Uploads the pool's live decals and the camera for this frame
+    #[allow(clippy::too_many_arguments)]
+    pub fn prepare(
+        &mut self,
+        device: &wgpu::Device,
+        resources: &ResourceRegistry,
+        encoder: &mut wgpu::CommandEncoder,
+        belt: &mut UploadBelt,
+        pool: &DecalPool,
+        view_proj: Mat4,
+        screen_size: (u32, u32),
+    ) {
+        let instances = pool.instances();
+        self.instance_count = instances.len() as u32;
+        if instances.is_empty() {
+            return;
+        }
+        let params = DecalParams {
+            view_proj: view_proj.to_cols_array(),
+            inv_view_proj: view_proj.inverse().to_cols_array(),
+            screen_size: [screen_size.0.max(1) as f32, screen_size.1.max(1) as f32],
+            _pad: [0.0; 2],
+        };
+        self.params.upload(device, resources, encoder, belt, bytemuck::bytes_of(&params));
+        self.instances.upload(device, resources, encoder, belt, bytemuck::cast_slice(&instances));
+    }
+
+    // `depth` must not be attached to `render_pass`; `atlas` holds every decal image
+    pub fn draw(
+        &self,
+        device: &wgpu::Device,
+        bind_groups: &mut BindGroupCache,
+        samplers: &mut SamplerCache,
+        render_pass: &mut wgpu::RenderPass<'_>,
+        depth: &wgpu::TextureView,
+        atlas: &wgpu::TextureView,
+    ) {
+        if self.instance_count == 0 {
+            return;
+        }
+        let sampler = samplers.get(device, &wgpu::SamplerDescriptor {
+            label: Some("decal atlas"),
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+        let bind_group = bind_groups.get_or_create(device, "decals", &self.layout, &[
+            (0, BindingKey::buffer(self.params.buffer())),
+            (1, BindingKey::TextureView(depth.clone())),
+            (2, BindingKey::TextureView(atlas.clone())),
+            (3, BindingKey::Sampler(sampler)),
+        ]);
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(0, &bind_group, &[]);
+        render_pass.set_vertex_buffer(0, self.instances.buffer().slice(..));
+        render_pass.draw(0..36, 0..self.instance_count);
+    }
+}
@@ -0,0 +1,78 @@
+// Projected decals: each instance draws its box, reconstructs the surface under every
+// covered pixel from depth and projects the decal texture onto it along the box's -Y axis
+
+struct DecalParams {
+    view_proj: mat4x4<f32>,
+    inv_view_proj: mat4x4<f32>,
+    screen_size: vec2<f32>,
+}
+
+@group(0) @binding(0) var<uniform> params: DecalParams;
+@group(0) @binding(1) var depth_texture: texture_depth_2d;
+@group(0) @binding(2) var atlas: texture_2d<f32>;
+@group(0) @binding(3) var atlas_sampler: sampler;
+
+struct DecalInstance {
+    @location(0) model_0: vec4<f32>,
+    @location(1) model_1: vec4<f32>,
+    @location(2) model_2: vec4<f32>,
+    @location(3) model_3: vec4<f32>,
+    @location(4) inv_model_0: vec4<f32>,
+    @location(5) inv_model_1: vec4<f32>,
+    @location(6) inv_model_2: vec4<f32>,
+    @location(7) inv_model_3: vec4<f32>,
+    @location(8) tint: vec4<f32>,
+    @location(9) uv_rect: vec4<f32>,
+}
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) inv_model_0: vec4<f32>,
+    @location(1) inv_model_1: vec4<f32>,
+    @location(2) inv_model_2: vec4<f32>,
+    @location(3) inv_model_3: vec4<f32>,
+    @location(4) tint: vec4<f32>,
+    @location(5) uv_rect: vec4<f32>,
+}
+
+// Two triangles per face; each index picks a corner by its x/y/z bits
+const CUBE_INDICES = array<u32, 36>(
+    0u, 1u, 3u, 0u, 3u, 2u,
+    4u, 6u, 7u, 4u, 7u, 5u,
+    0u, 4u, 5u, 0u, 5u, 1u,
+    2u, 3u, 7u, 2u, 7u, 6u,
+    0u, 2u, 6u, 0u, 6u, 4u,
+    1u, 5u, 7u, 1u, 7u, 3u,
+);
+
+@vertex
+fn vs_main(@builtin(vertex_index) vertex_index: u32, instance: DecalInstance) -> VertexOutput {
+    let corner = CUBE_INDICES[vertex_index];
+    let local = vec3<f32>(f32(corner & 1u), f32((corner >> 1u) & 1u), f32((corner >> 2u) & 1u)) - 0.5;
+    let model = mat4x4<f32>(instance.model_0, instance.model_1, instance.model_2, instance.model_3);
+    var out: VertexOutput;
+    out.position = params.view_proj * model * vec4<f32>(local, 1.0);
+    out.inv_model_0 = instance.inv_model_0;
+    out.inv_model_1 = instance.inv_model_1;
+    out.inv_model_2 = instance.inv_model_2;
+    out.inv_model_3 = instance.inv_model_3;
+    out.tint = instance.tint;
+    out.uv_rect = instance.uv_rect;
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let pixel = vec2<i32>(in.position.xy);
+    let depth = textureLoad(depth_texture, pixel, 0);
+    let uv = in.position.xy / params.screen_size;
+    let world = params.inv_view_proj * vec4<f32>(uv.x * 2.0 - 1.0, 1.0 - uv.y * 2.0, depth, 1.0);
+    let inv_model = mat4x4<f32>(in.inv_model_0, in.inv_model_1, in.inv_model_2, in.inv_model_3);
+    let local = (inv_model * vec4<f32>(world.xyz / world.w, 1.0)).xyz;
+    // Surfaces outside the box aren't covered, even if the box is in front of them
+    if (any(abs(local) > vec3<f32>(0.5))) {
+        discard;
+    }
+    let decal_uv = in.uv_rect.xy + (vec2<f32>(local.x, -local.z) + 0.5) * in.uv_rect.zw;
+    return textureSampleLevel(atlas, atlas_sampler, decal_uv, 0.0) * in.tint;
+}
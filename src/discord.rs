@@ -0,0 +1,292 @@
+// src/discord.rs
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+// What the player's Discord profile shows, e.g. details "In Level 3", state "Solo" and an
+// elapsed timer from `start_time`
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Presence {
+    pub details: Option<String>,
+    pub state: Option<String>,
+    // Counts up from here
+    pub start_time: Option<SystemTime>,
+    // Counts down to here instead, e.g. a match timer
+    pub end_time: Option<SystemTime>,
+    // Asset keys uploaded in the Discord developer portal, and their hover text
+    pub large_image: Option<String>,
+    pub large_text: Option<String>,
+    pub small_image: Option<String>,
+    pub small_text: Option<String>,
+    // Party id, current size and maximum size: "2 of 4"
+    pub party: Option<(String, u32, u32)>,
+}
+
+impl Presence {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_details(mut self, details: &str) -> Self {
+        self.details = Some(details.to_string());
+        self
+    }
+
+    pub fn with_state(mut self, state: &str) -> Self {
+        self.state = Some(state.to_string());
+        self
+    }
+
+    // Shows time elapsed since `start`, e.g. when the level began
+    pub fn with_elapsed_since(mut self, start: SystemTime) -> Self {
+        self.start_time = Some(start);
+        self
+    }
+
+    pub fn with_remaining_until(mut self, end: SystemTime) -> Self {
+        self.end_time = Some(end);
+        self
+    }
+
+    pub fn with_large_image(mut self, key: &str, text: &str) -> Self {
+        self.large_image = Some(key.to_string());
+        self.large_text = Some(text.to_string());
+        self
+    }
+
+    pub fn with_small_image(mut self, key: &str, text: &str) -> Self {
+        self.small_image = Some(key.to_string());
+        self.small_text = Some(text.to_string());
+        self
+    }
+
+    pub fn with_party(mut self, id: &str, size: u32, max: u32) -> Self {
+        self.party = Some((id.to_string(), size, max));
+        self
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PresenceStatus {
+    // Built without the `discord` feature
+    Disabled,
+    Connecting,
+    Connected,
+    // Discord isn't running or the connection dropped; retried in the background
+    Disconnected(String),
+}
+
+#[cfg(feature = "discord")]
+enum Command {
+    Set(Box<Presence>),
+    Clear,
+    Shutdown,
+}
+
+// Discord Rich Presence as an engine service. Game code calls `set` whenever what it shows
+// changes; a background thread owns the IPC connection, reconnects when Discord starts or
+// restarts, rate-limits updates and re-sends the latest presence after reconnecting.
+// Without the `discord` feature this is a stub that only remembers the presence.
+pub struct DiscordPresence {
+    current: Option<Presence>,
+    status: Arc<Mutex<PresenceStatus>>,
+    #[cfg(feature = "discord")]
+    sender: std::sync::mpsc::Sender<Command>,
+    #[cfg(feature = "discord")]
+    thread: Option<std::thread::JoinHandle<()>>,
+}
+
+impl DiscordPresence {
+    // `client_id` is the application id from the Discord developer portal
+    pub fn start(client_id: &str) -> Self {
+        #[cfg(feature = "discord")]
+        {
+            let (sender, receiver) = std::sync::mpsc::channel();
+            let status = Arc::new(Mutex::new(PresenceStatus::Connecting));
+            let thread_status = status.clone();
+            let client_id = client_id.to_string();
+            let thread = std::thread::spawn(move || ipc::run(&client_id, receiver, thread_status));
+            Self { current: None, status, sender, thread: Some(thread) }
+        }
+        #[cfg(not(feature = "discord"))]
+        {
+            let _ = client_id;
+            Self { current: None, status: Arc::new(Mutex::new(PresenceStatus::Disabled)) }
+        }
+    }
+
+    pub fn status(&self) -> PresenceStatus {
+        self.lock_status().clone()
+    }
+
+    pub fn current(&self) -> Option<&Presence> {
+        self.current.as_ref()
+    }
+
+    // Cheap to call every frame; only changes are sent
+    pub fn set(&mut self, presence: Presence) {
+        if self.current.as_ref() != Some(&presence) {
+            self.current = Some(presence.clone());
+            #[cfg(feature = "discord")]
+            self.send(Command::Set(Box::new(presence)));
+        }
+    }
+
+    pub fn clear(&mut self) {
+        if self.current.take().is_some() {
+            #[cfg(feature = "discord")]
+            self.send(Command::Clear);
+        }
+    }
+
+    #[cfg(feature = "discord")]
+    fn send(&self, command: Command) {
+        // Only fails if the thread has already exited
+        let _ = self.sender.send(command);
+    }
+
+    fn lock_status(&self) -> std::sync::MutexGuard<'_, PresenceStatus> {
+        self.status.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(feature = "discord")]
+impl Drop for DiscordPresence {
+    // Clears the presence so the profile doesn't keep showing the game
+    fn drop(&mut self) {
+        self.send(Command::Shutdown);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+#[cfg(feature = "discord")]
+mod ipc {
+    use super::{Command, Presence, PresenceStatus};
+    use discord_rich_presence::{activity, DiscordIpc, DiscordIpcClient};
+    use std::sync::mpsc::{Receiver, RecvTimeoutError};
+    use std::sync::{Arc, Mutex};
+    use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+    // How long to wait before trying Discord again after it wasn't running or dropped us
+    const RECONNECT_INTERVAL: Duration = Duration::from_secs(15);
+    // Discord allows five activity updates per 20 seconds; faster changes are coalesced
+    const MIN_UPDATE_INTERVAL: Duration = Duration::from_secs(4);
+
+    fn millis(time: SystemTime) -> i64 {
+        time.duration_since(UNIX_EPOCH).map_or(0, |since| since.as_millis() as i64)
+    }
+
+    fn send(client: &mut DiscordIpcClient, presence: Option<&Presence>) -> Result<(), String> {
+        let Some(presence) = presence else {
+            return client.clear_activity().map_err(|e| e.to_string());
+        };
+        let mut activity = activity::Activity::new();
+        if let Some(details) = &presence.details {
+            activity = activity.details(details.as_str());
+        }
+        if let Some(state) = &presence.state {
+            activity = activity.state(state.as_str());
+        }
+        if presence.start_time.is_some() || presence.end_time.is_some() {
+            let mut timestamps = activity::Timestamps::new();
+            if let Some(start) = presence.start_time {
+                timestamps = timestamps.start(millis(start));
+            }
+            if let Some(end) = presence.end_time {
+                timestamps = timestamps.end(millis(end));
+            }
+            activity = activity.timestamps(timestamps);
+        }
+        let mut assets = activity::Assets::new();
+        if let Some(image) = &presence.large_image {
+            assets = assets.large_image(image.as_str());
+        }
+        if let Some(text) = &presence.large_text {
+            assets = assets.large_text(text.as_str());
+        }
+        if let Some(image) = &presence.small_image {
+            assets = assets.small_image(image.as_str());
+        }
+        if let Some(text) = &presence.small_text {
+            assets = assets.small_text(text.as_str());
+        }
+        activity = activity.assets(assets);
+        if let Some((id, size, max)) = &presence.party {
+            activity = activity.party(activity::Party::new().id(id.as_str()).size([*size as i32, *max as i32]));
+        }
+        client.set_activity(activity).map_err(|e| e.to_string())
+    }
+
+    // Owns the connection until `Shutdown` or the service is dropped
+    pub(super) fn run(client_id: &str, receiver: Receiver<Command>, status: Arc<Mutex<PresenceStatus>>) {
+        let set_status = |new: PresenceStatus| *status.lock().unwrap_or_else(|e| e.into_inner()) = new;
+        let mut client: Option<DiscordIpcClient> = None;
+        let mut desired: Option<Presence> = None;
+        // The latest presence hasn't reached Discord yet
+        let mut dirty = false;
+        let mut next_connect = Instant::now();
+        let mut next_update = Instant::now();
+        loop {
+            let now = Instant::now();
+            let wake = match (&client, dirty) {
+                (None, _) => next_connect,
+                (Some(_), true) => next_update,
+                (Some(_), false) => now + RECONNECT_INTERVAL,
+            };
+            match receiver.recv_timeout(wake.saturating_duration_since(now)) {
+                Ok(Command::Set(presence)) => {
+                    desired = Some(*presence);
+                    dirty = true;
+                }
+                Ok(Command::Clear) => {
+                    desired = None;
+                    dirty = true;
+                }
+                Ok(Command::Shutdown) | Err(RecvTimeoutError::Disconnected) => break,
+                Err(RecvTimeoutError::Timeout) => {}
+            }
+
+            let now = Instant::now();
+            if client.is_none() && now >= next_connect {
+                let mut connecting = DiscordIpcClient::new(client_id);
+                match connecting.connect() {
+                    Ok(()) => {
+                        log::info!("Connected to Discord");
+                        set_status(PresenceStatus::Connected);
+                        client = Some(connecting);
+                        // Discord forgets the presence when it restarts
+                        dirty = desired.is_some();
+                    }
+                    Err(e) => {
+                        set_status(PresenceStatus::Disconnected(e.to_string()));
+                        next_connect = now + RECONNECT_INTERVAL;
+                    }
+                }
+            }
+            if let Some(connected) = &mut client {
+                if dirty && now >= next_update {
+                    match send(connected, desired.as_ref()) {
+                        Ok(()) => {
+                            dirty = false;
+                            next_update = now + MIN_UPDATE_INTERVAL;
+                        }
+                        Err(e) => {
+                            log::warn!("Lost connection to Discord: {}", e);
+                            set_status(PresenceStatus::Disconnected(e));
+                            let _ = connected.close();
+                            client = None;
+                            next_connect = now + RECONNECT_INTERVAL;
+                        }
+                    }
+                }
+            }
+        }
+
+        if let Some(mut client) = client {
+            let _ = client.clear_activity();
+            let _ = client.close();
+        }
+        set_status(PresenceStatus::Disabled);
+    }
+}
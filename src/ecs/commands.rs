@@ -0,0 +1,66 @@
+// src/ecs/commands.rs
+use super::world::{Entity, World};
+use std::cell::RefCell;
+
+type Command = Box<dyn FnOnce(&mut World)>;
+
+// World changes queued while systems iterate and applied together at the next stage
+// boundary. Queuing only needs `&World`, so a system can despawn what it is looking at.
+#[derive(Default)]
+pub struct Commands {
+    queue: RefCell<Vec<Command>>,
+}
+
+impl Commands {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&self, command: impl FnOnce(&mut World) + 'static) {
+        self.queue.borrow_mut().push(Box::new(command));
+    }
+
+    // The entity only exists once the queue is applied; set it up in `build`
+    pub fn spawn(&self, build: impl FnOnce(&mut World, Entity) + 'static) {
+        self.add(move |world| {
+            let entity = world.spawn();
+            build(world, entity);
+        });
+    }
+
+    pub fn despawn(&self, entity: Entity) {
+        self.add(move |world| {
+            world.despawn(entity);
+        });
+    }
+
+    pub fn despawn_recursive(&self, entity: Entity) {
+        self.add(move |world| {
+            world.despawn_recursive(entity);
+        });
+    }
+
+    pub fn insert<T: 'static>(&self, entity: Entity, component: T) {
+        self.add(move |world| {
+            world.insert(entity, component);
+        });
+    }
+
+    pub fn remove<T: 'static>(&self, entity: Entity) {
+        self.add(move |world| {
+            world.remove::<T>(entity);
+        });
+    }
+
+    pub fn len(&self) -> usize {
+        self.queue.borrow().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.queue.borrow().is_empty()
+    }
+
+    pub(crate) fn take(&self) -> Vec<Command> {
+        std::mem::take(&mut *self.queue.borrow_mut())
+    }
+}
@@ -0,0 +1,65 @@
+// src/ecs/hierarchy.rs
+use super::world::{Entity, World};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Parent(pub Entity);
+
+// Kept in step with `Parent` by `set_parent`/`remove_parent`; don't edit directly
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Children(pub Vec<Entity>);
+
+impl World {
+    // Re-parents `child`; false if either is dead or `parent` is `child` or below it
+    pub fn set_parent(&mut self, child: Entity, parent: Entity) -> bool {
+        if !self.is_alive(child) || !self.is_alive(parent) {
+            return false;
+        }
+        let mut ancestor = Some(parent);
+        while let Some(entity) = ancestor {
+            if entity == child {
+                return false;
+            }
+            ancestor = self.get::<Parent>(entity).map(|p| p.0);
+        }
+        self.remove_parent(child);
+        self.insert(child, Parent(parent));
+        match self.get_mut::<Children>(parent) {
+            Some(mut children) => children.0.push(child),
+            None => {
+                self.insert(parent, Children(vec![child]));
+            }
+        }
+        true
+    }
+
+    pub fn remove_parent(&mut self, child: Entity) {
+        let Some(Parent(parent)) = self.remove::<Parent>(child) else { return };
+        let now_empty = match self.get_mut::<Children>(parent) {
+            Some(mut children) => {
+                children.0.retain(|&c| c != child);
+                children.0.is_empty()
+            }
+            None => false,
+        };
+        if now_empty {
+            self.remove::<Children>(parent);
+        }
+    }
+
+    // Despawns `entity` and everything below it. Plain `despawn` leaves children alive,
+    // still pointing at a dead parent.
+    pub fn despawn_recursive(&mut self, entity: Entity) -> bool {
+        if !self.is_alive(entity) {
+            return false;
+        }
+        self.remove_parent(entity);
+        let mut stack = vec![entity];
+        while let Some(next) = stack.pop() {
+            if let Some(children) = self.remove::<Children>(next) {
+                stack.extend(children.0);
+            }
+            self.despawn(next);
+        }
+        true
+    }
+}
@@ -0,0 +1,21 @@
+// src/ecs/lifetime.rs
+use super::world::World;
+
+// Seconds left before the entity and its children are despawned, e.g. for effects and
+// projectiles
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Lifetime(pub f32);
+
+// System counting lifetimes down; expired entities are despawned when commands are applied
+pub fn tick_lifetimes(world: &mut World, delta_time: f64) {
+    let mut expired = Vec::new();
+    for (entity, mut lifetime) in world.query_mut::<Lifetime>() {
+        lifetime.0 -= delta_time as f32;
+        if lifetime.0 <= 0.0 {
+            expired.push(entity);
+        }
+    }
+    for entity in expired {
+        world.commands().despawn_recursive(entity);
+    }
+}
@@ -0,0 +1,8 @@
+// src/ecs/mod.rs
+pub mod commands;
+pub mod hierarchy;
+pub mod lifetime;
+pub mod pool;
+pub mod resources;
+pub mod schedule;
+pub mod world;
@@ -0,0 +1,106 @@
+// src/ecs/pool.rs
+use super::hierarchy::Children;
+use super::world::{Entity, World};
+
+// Sets up a fresh instance's components. Runs on every acquire, so an entity coming back
+// from the pool starts exactly like a new one.
+pub trait Prefab {
+    fn build(&self, world: &mut World, entity: Entity);
+}
+
+impl<F: Fn(&mut World, Entity)> Prefab for F {
+    fn build(&self, world: &mut World, entity: Entity) {
+        self(world, entity)
+    }
+}
+
+// Marks idle entities parked in a pool; they have no other components, so systems never
+// see them
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Pooled;
+
+// Reuses entities of one kind instead of spawning and despawning them, for bullets, hit
+// sparks and the like. Released entities keep their handle and generation, so stale
+// handles to a reused entity aren't detected; drop them on release. Children aren't
+// released with their parent.
+pub struct Pool<P: Prefab> {
+    prefab: P,
+    idle: Vec<Entity>,
+    active: usize,
+}
+
+impl<P: Prefab> Pool<P> {
+    pub fn new(prefab: P) -> Self {
+        Self { prefab, idle: Vec::new(), active: 0 }
+    }
+
+    // Creates idle entities until `count` are waiting. Each is built once, so component
+    // storages grow to size now rather than mid-game.
+    pub fn prewarm(&mut self, world: &mut World, count: usize) {
+        while self.idle.len() < count {
+            let entity = world.spawn();
+            self.prefab.build(world, entity);
+            world.clear_components(entity);
+            world.insert(entity, Pooled);
+            self.idle.push(entity);
+        }
+    }
+
+    // An entity with the prefab's components, reusing an idle one when there is one
+    pub fn acquire(&mut self, world: &mut World) -> Entity {
+        let entity = loop {
+            match self.idle.pop() {
+                // Skip anything despawned behind the pool's back
+                Some(entity) if world.is_alive(entity) => break entity,
+                Some(_) => continue,
+                None => break world.spawn(),
+            }
+        };
+        world.remove::<Pooled>(entity);
+        self.prefab.build(world, entity);
+        self.active += 1;
+        entity
+    }
+
+    // Like `acquire`, then lets the caller adjust the instance, e.g. its position
+    pub fn acquire_with(&mut self, world: &mut World, setup: impl FnOnce(&mut World, Entity)) -> Entity {
+        let entity = self.acquire(world);
+        setup(world, entity);
+        entity
+    }
+
+    // Strips the entity's components and parks it; false if it is dead or already idle.
+    // It is detached from its parent first, and its children are orphaned, so neither side
+    // keeps pointing at an entity that will be handed out again.
+    pub fn release(&mut self, world: &mut World, entity: Entity) -> bool {
+        if world.has::<Pooled>(entity) || !world.is_alive(entity) {
+            return false;
+        }
+        world.remove_parent(entity);
+        let children = world.get::<Children>(entity).map(|children| children.0.clone()).unwrap_or_default();
+        for child in children {
+            world.remove_parent(child);
+        }
+        world.clear_components(entity);
+        world.insert(entity, Pooled);
+        self.idle.push(entity);
+        self.active = self.active.saturating_sub(1);
+        true
+    }
+
+    // Entities handed out and not yet released
+    pub fn active(&self) -> usize {
+        self.active
+    }
+
+    pub fn idle(&self) -> usize {
+        self.idle.len()
+    }
+
+    // Despawns every idle entity, e.g. when leaving a level
+    pub fn shrink(&mut self, world: &mut World) {
+        for entity in self.idle.drain(..) {
+            world.despawn(entity);
+        }
+    }
+}
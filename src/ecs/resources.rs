@@ -0,0 +1,68 @@
+// src/ecs/resources.rs
+use crate::window::DisplayMetrics;
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+
+// One value per type, shared by all systems: time, input, window info and whatever the game
+// adds. Systems reach them through `World::resources`.
+#[derive(Default)]
+pub struct Resources {
+    map: HashMap<TypeId, Box<dyn Any>>,
+}
+
+impl Resources {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Replaces and returns any previous value of the same type
+    pub fn insert<T: 'static>(&mut self, value: T) -> Option<T> {
+        self.map.insert(TypeId::of::<T>(), Box::new(value)).and_then(|old| old.downcast().ok().map(|old| *old))
+    }
+
+    pub fn remove<T: 'static>(&mut self) -> Option<T> {
+        self.map.remove(&TypeId::of::<T>()).and_then(|value| value.downcast().ok().map(|value| *value))
+    }
+
+    pub fn contains<T: 'static>(&self) -> bool {
+        self.map.contains_key(&TypeId::of::<T>())
+    }
+
+    pub fn get<T: 'static>(&self) -> Option<&T> {
+        self.map.get(&TypeId::of::<T>())?.downcast_ref()
+    }
+
+    pub fn get_mut<T: 'static>(&mut self) -> Option<&mut T> {
+        self.map.get_mut(&TypeId::of::<T>())?.downcast_mut()
+    }
+
+    pub fn get_or_insert_with<T: 'static>(&mut self, create: impl FnOnce() -> T) -> &mut T {
+        self.map
+            .entry(TypeId::of::<T>())
+            .or_insert_with(|| Box::new(create()))
+            .downcast_mut()
+            .expect("resource matches its TypeId")
+    }
+}
+
+// Frame timing, refreshed by the app before each frame's stages run
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Time {
+    // Seconds since the last frame
+    pub delta: f64,
+    // Step `FixedUpdate` systems advance by
+    pub fixed_delta: f64,
+    // Fraction of a fixed step since the last `FixedUpdate`, for interpolating
+    pub alpha: f64,
+    // Seconds since the app started
+    pub elapsed: f64,
+    pub frame: u64,
+}
+
+// The main window as systems see it
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WindowInfo {
+    pub metrics: DisplayMetrics,
+    pub refresh_rate_hz: Option<f32>,
+    pub focused: bool,
+}
@@ -0,0 +1,230 @@
+// src/ecs/schedule.rs
+use std::collections::HashMap;
+use std::fmt::Write as _;
+
+// Stages run in this order each frame; `FixedUpdate` once per fixed step
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Stage {
+    PreUpdate,
+    FixedUpdate,
+    PostUpdate,
+    // Copies what the renderer needs out of the world; last, after everything has moved
+    RenderExtract,
+}
+
+impl Stage {
+    pub const ALL: [Stage; 4] = [Stage::PreUpdate, Stage::FixedUpdate, Stage::PostUpdate, Stage::RenderExtract];
+}
+
+pub type SystemFn<W> = Box<dyn FnMut(&mut W, f64)>;
+
+// A system and where it runs. Its name doubles as a label; `in_set` adds more, so ordering
+// can target a whole group such as "physics".
+pub struct System<W> {
+    name: &'static str,
+    stage: Stage,
+    labels: Vec<&'static str>,
+    before: Vec<&'static str>,
+    after: Vec<&'static str>,
+    run: SystemFn<W>,
+}
+
+impl<W> System<W> {
+    pub fn new(stage: Stage, name: &'static str, run: impl FnMut(&mut W, f64) + 'static) -> Self {
+        Self { name, stage, labels: vec![name], before: Vec::new(), after: Vec::new(), run: Box::new(run) }
+    }
+
+    pub fn in_set(mut self, label: &'static str) -> Self {
+        self.labels.push(label);
+        self
+    }
+
+    // Runs before every system in this stage carrying `label`
+    pub fn before(mut self, label: &'static str) -> Self {
+        self.before.push(label);
+        self
+    }
+
+    // Runs after every system in this stage carrying `label`
+    pub fn after(mut self, label: &'static str) -> Self {
+        self.after.push(label);
+        self
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    pub fn stage(&self) -> Stage {
+        self.stage
+    }
+}
+
+// Systems grouped into stages and ordered by their constraints. Unconstrained systems keep
+// the order they were added in. Constraints only order systems within one stage; across
+// stages the stage order decides.
+pub struct Schedule<W> {
+    systems: Vec<System<W>>,
+    // Per stage, indices into `systems` in run order; `None` until rebuilt
+    order: Option<HashMap<Stage, Vec<usize>>>,
+    // Runs after every stage, e.g. `World::apply_commands`
+    stage_end: Option<fn(&mut W)>,
+}
+
+impl<W> Default for Schedule<W> {
+    fn default() -> Self {
+        Self { systems: Vec::new(), order: None, stage_end: None }
+    }
+}
+
+impl<W> Schedule<W> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set_stage_end(&mut self, stage_end: fn(&mut W)) {
+        self.stage_end = Some(stage_end);
+    }
+
+    pub fn add_system(&mut self, system: System<W>) -> &mut Self {
+        self.systems.push(system);
+        self.order = None;
+        self
+    }
+
+    pub fn remove_system(&mut self, name: &str) -> bool {
+        let count = self.systems.len();
+        self.systems.retain(|s| s.name != name);
+        self.order = None;
+        self.systems.len() != count
+    }
+
+    pub fn len(&self) -> usize {
+        self.systems.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.systems.is_empty()
+    }
+
+    // Sorts every stage; reports unknown labels and ordering cycles. Runs lazily on the
+    // first `run_stage` after a change, but calling it at startup surfaces errors early.
+    pub fn build(&mut self) -> Result<(), String> {
+        if self.order.is_some() {
+            return Ok(());
+        }
+        let mut names = HashMap::new();
+        for system in &self.systems {
+            if names.insert(system.name, system.stage).is_some() {
+                return Err(format!("Duplicate system name '{}'", system.name));
+            }
+        }
+        for system in &self.systems {
+            for label in system.before.iter().chain(&system.after) {
+                if !self.systems.iter().any(|s| s.labels.contains(label)) {
+                    return Err(format!("System '{}' is ordered against unknown label '{}'", system.name, label));
+                }
+            }
+        }
+        let mut order = HashMap::new();
+        for stage in Stage::ALL {
+            order.insert(stage, self.sort_stage(stage)?);
+        }
+        self.order = Some(order);
+        Ok(())
+    }
+
+    // Kahn's algorithm, always taking the earliest-added ready system so the result is stable
+    fn sort_stage(&self, stage: Stage) -> Result<Vec<usize>, String> {
+        let members: Vec<usize> = (0..self.systems.len()).filter(|&i| self.systems[i].stage == stage).collect();
+        let has_label = |i: usize, label: &str| self.systems[i].labels.contains(&label);
+        // edges[a] holds systems that must run after a
+        let mut edges: HashMap<usize, Vec<usize>> = HashMap::new();
+        let mut incoming: HashMap<usize, usize> = members.iter().map(|&i| (i, 0)).collect();
+        for &i in &members {
+            for &j in &members {
+                if i == j {
+                    continue;
+                }
+                let i_before_j = self.systems[i].before.iter().any(|l| has_label(j, l))
+                    || self.systems[j].after.iter().any(|l| has_label(i, l));
+                if i_before_j {
+                    edges.entry(i).or_default().push(j);
+                    *incoming.entry(j).or_default() += 1;
+                }
+            }
+        }
+
+        let mut sorted = Vec::with_capacity(members.len());
+        let mut ready: Vec<usize> = members.iter().copied().filter(|i| incoming[i] == 0).collect();
+        while let Some(pos) = ready.iter().enumerate().min_by_key(|(_, &i)| i).map(|(pos, _)| pos) {
+            let next = ready.swap_remove(pos);
+            sorted.push(next);
+            for &after in edges.get(&next).map(Vec::as_slice).unwrap_or(&[]) {
+                let count = incoming.get_mut(&after).expect("edge target is a stage member");
+                *count -= 1;
+                if *count == 0 {
+                    ready.push(after);
+                }
+            }
+        }
+        if sorted.len() != members.len() {
+            let cycle: Vec<&str> = members.iter().filter(|i| !sorted.contains(i)).map(|&i| self.systems[i].name).collect();
+            return Err(format!("Ordering cycle in {:?} among: {}", stage, cycle.join(", ")));
+        }
+        Ok(sorted)
+    }
+
+    pub fn run_stage(&mut self, stage: Stage, world: &mut W, delta_time: f64) {
+        if let Err(e) = self.build() {
+            log::error!("Schedule not run: {}", e);
+            return;
+        }
+        let Some(order) = self.order.as_ref().and_then(|order| order.get(&stage)) else { return };
+        for &i in order {
+            (self.systems[i].run)(world, delta_time);
+        }
+        if let Some(stage_end) = self.stage_end {
+            stage_end(world);
+        }
+    }
+
+    // One frame: `fixed_steps` runs of `FixedUpdate` at `fixed_delta` between the variable
+    // stages, as counted by `GameLoop::tick`
+    pub fn run_frame(&mut self, world: &mut W, delta_time: f64, fixed_steps: u32, fixed_delta: f64) {
+        self.run_stage(Stage::PreUpdate, world, delta_time);
+        for _ in 0..fixed_steps {
+            self.run_stage(Stage::FixedUpdate, world, fixed_delta);
+        }
+        self.run_stage(Stage::PostUpdate, world, delta_time);
+        self.run_stage(Stage::RenderExtract, world, delta_time);
+    }
+
+    // The resolved run order with each system's labels and constraints, for debugging
+    pub fn dump(&mut self) -> String {
+        let mut out = String::new();
+        if let Err(e) = self.build() {
+            let _ = writeln!(out, "Schedule is invalid: {}", e);
+            return out;
+        }
+        let Some(order) = &self.order else { return out };
+        for stage in Stage::ALL {
+            let _ = writeln!(out, "{:?}:", stage);
+            for (n, &i) in order[&stage].iter().enumerate() {
+                let system = &self.systems[i];
+                let _ = write!(out, "  {}. {}", n + 1, system.name);
+                if system.labels.len() > 1 {
+                    let _ = write!(out, " [{}]", system.labels[1..].join(", "));
+                }
+                if !system.after.is_empty() {
+                    let _ = write!(out, " after({})", system.after.join(", "));
+                }
+                if !system.before.is_empty() {
+                    let _ = write!(out, " before({})", system.before.join(", "));
+                }
+                out.push('\n');
+            }
+        }
+        out
+    }
+}
@@ -0,0 +1,454 @@
+// src/ecs/world.rs
+use super::commands::Commands;
+use super::resources::Resources;
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::marker::PhantomData;
+use std::ops::{Deref, DerefMut};
+
+// World change counter. Components remember the tick they were added and last changed at;
+// u64 so it never wraps in practice.
+pub type Tick = u64;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Entity {
+    index: u32,
+    generation: u32,
+}
+
+impl Entity {
+    pub fn index(&self) -> u32 {
+        self.index
+    }
+
+    pub fn generation(&self) -> u32 {
+        self.generation
+    }
+
+    // From ids stored outside the world, e.g. read back from the GPU; may no longer be alive
+    pub(crate) fn from_raw(index: u32, generation: u32) -> Self {
+        Self { index, generation }
+    }
+}
+
+// Sparse set per component type: dense arrays for iteration, `sparse` from entity index to
+// dense slot for lookup
+struct Storage<T> {
+    dense: Vec<T>,
+    entities: Vec<Entity>,
+    added: Vec<Tick>,
+    changed: Vec<Tick>,
+    sparse: Vec<Option<u32>>,
+}
+
+impl<T> Storage<T> {
+    fn new() -> Self {
+        Self { dense: Vec::new(), entities: Vec::new(), added: Vec::new(), changed: Vec::new(), sparse: Vec::new() }
+    }
+
+    fn slot(&self, entity: Entity) -> Option<usize> {
+        let slot = (*self.sparse.get(entity.index as usize)?)? as usize;
+        (self.entities[slot] == entity).then_some(slot)
+    }
+
+    fn insert(&mut self, entity: Entity, component: T, tick: Tick) {
+        if let Some(slot) = self.slot(entity) {
+            self.dense[slot] = component;
+            self.changed[slot] = tick;
+            return;
+        }
+        let index = entity.index as usize;
+        if self.sparse.len() <= index {
+            self.sparse.resize(index + 1, None);
+        }
+        self.sparse[index] = Some(self.dense.len() as u32);
+        self.dense.push(component);
+        self.entities.push(entity);
+        self.added.push(tick);
+        self.changed.push(tick);
+    }
+
+    fn remove(&mut self, entity: Entity) -> Option<T> {
+        let slot = self.slot(entity)?;
+        self.sparse[entity.index as usize] = None;
+        let last = self.dense.len() - 1;
+        if slot != last {
+            self.sparse[self.entities[last].index as usize] = Some(slot as u32);
+        }
+        self.entities.swap_remove(slot);
+        self.added.swap_remove(slot);
+        self.changed.swap_remove(slot);
+        Some(self.dense.swap_remove(slot))
+    }
+}
+
+// Type-erased access to a `Storage<T>`, for despawning without knowing the component types
+trait AnyStorage: Any {
+    fn remove_entity(&mut self, entity: Entity);
+    fn contains(&self, entity: Entity) -> bool;
+    fn stats(&self) -> ComponentStats;
+    fn as_any(&self) -> &dyn Any;
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
+impl<T: 'static> AnyStorage for Storage<T> {
+    fn remove_entity(&mut self, entity: Entity) {
+        self.remove(entity);
+    }
+
+    fn contains(&self, entity: Entity) -> bool {
+        self.slot(entity).is_some()
+    }
+
+    fn stats(&self) -> ComponentStats {
+        let per_slot = std::mem::size_of::<T>() + std::mem::size_of::<Entity>() + 2 * std::mem::size_of::<Tick>();
+        ComponentStats {
+            name: std::any::type_name::<T>(),
+            count: self.dense.len(),
+            component_size: std::mem::size_of::<T>(),
+            // Reserved rather than used, since that is what the storage holds on to. Heap
+            // memory owned by the components themselves isn't counted.
+            bytes: self.dense.capacity() * per_slot + self.sparse.capacity() * std::mem::size_of::<Option<u32>>(),
+        }
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ComponentStats {
+    pub name: &'static str,
+    pub count: usize,
+    pub component_size: usize,
+    pub bytes: usize,
+}
+
+// Entities sharing exactly one set of component types. Storage is per component, not per
+// archetype, so these are grouped on demand for inspection only.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArchetypeStats {
+    // Sorted component type names
+    pub components: Vec<&'static str>,
+    pub entity_count: usize,
+}
+
+// Snapshot for the world inspector and debug overlay; `World::stats` walks every entity,
+// so take it on demand rather than every frame
+#[derive(Debug, Clone, Default)]
+pub struct WorldStats {
+    pub entity_count: usize,
+    // Largest first
+    pub archetypes: Vec<ArchetypeStats>,
+    // Largest first
+    pub components: Vec<ComponentStats>,
+    // Component storage plus entity bookkeeping
+    pub total_bytes: usize,
+}
+
+impl WorldStats {
+    // Plain-text summary for overlays and logs
+    pub fn report(&self) -> String {
+        let mut out = format!(
+            "{} entities, {} archetypes, {:.1} KiB\n",
+            self.entity_count,
+            self.archetypes.len(),
+            self.total_bytes as f64 / 1024.0
+        );
+        for c in &self.components {
+            out += &format!("  {:<40} {:>7} x {:>4} B  {:>9.1} KiB\n", c.name, c.count, c.component_size, c.bytes as f64 / 1024.0);
+        }
+        for a in &self.archetypes {
+            out += &format!("  {:>7}  [{}]\n", a.entity_count, a.components.join(", "));
+        }
+        out
+    }
+}
+
+// Mutable access that marks the component changed, but only when actually written through
+pub struct Mut<'a, T> {
+    value: &'a mut T,
+    changed: &'a mut Tick,
+    tick: Tick,
+}
+
+impl<T> Deref for Mut<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.value
+    }
+}
+
+impl<T> DerefMut for Mut<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        *self.changed = self.tick;
+        self.value
+    }
+}
+
+// Query filters over component ticks, e.g. `world.query_filtered::<Transform, Changed<Transform>>(since)`
+pub trait Filter {
+    fn matches(world: &World, entity: Entity, since: Tick) -> bool;
+}
+
+// Added or written through `Mut` after `since`. Newly added components count as changed.
+pub struct Changed<T>(PhantomData<T>);
+// Added after `since`
+pub struct Added<T>(PhantomData<T>);
+// Has the component, whatever its ticks
+pub struct With<T>(PhantomData<T>);
+
+impl<T: 'static> Filter for Changed<T> {
+    fn matches(world: &World, entity: Entity, since: Tick) -> bool {
+        world.ticks::<T>(entity).is_some_and(|(_, changed)| changed > since)
+    }
+}
+
+impl<T: 'static> Filter for Added<T> {
+    fn matches(world: &World, entity: Entity, since: Tick) -> bool {
+        world.ticks::<T>(entity).is_some_and(|(added, _)| added > since)
+    }
+}
+
+impl<T: 'static> Filter for With<T> {
+    fn matches(world: &World, entity: Entity, _since: Tick) -> bool {
+        world.has::<T>(entity)
+    }
+}
+
+// Remembers when a system last looked, so each run only sees what changed since. Take
+// `since` before querying; the tick moves on so writes made afterwards show up next time.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ChangeCursor {
+    last: Tick,
+}
+
+impl ChangeCursor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn since(&mut self, world: &mut World) -> Tick {
+        let since = self.last;
+        self.last = world.advance_tick();
+        since
+    }
+}
+
+// Entities and their components, plus the shared `resources`. Any `'static` type can be a
+// component.
+pub struct World {
+    pub resources: Resources,
+    generations: Vec<u32>,
+    alive: Vec<bool>,
+    free: Vec<u32>,
+    storages: HashMap<TypeId, Box<dyn AnyStorage>>,
+    commands: Commands,
+    tick: Tick,
+}
+
+impl Default for World {
+    fn default() -> Self {
+        Self {
+            resources: Resources::new(),
+            generations: Vec::new(),
+            alive: Vec::new(),
+            free: Vec::new(),
+            storages: HashMap::new(),
+            commands: Commands::new(),
+            tick: 1,
+        }
+    }
+}
+
+impl World {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn tick(&self) -> Tick {
+        self.tick
+    }
+
+    // Returns the tick before advancing
+    pub fn advance_tick(&mut self) -> Tick {
+        self.tick += 1;
+        self.tick - 1
+    }
+
+    // Component type names on `entity`, sorted; for the inspector's entity view
+    pub fn component_names(&self, entity: Entity) -> Vec<&'static str> {
+        let mut names: Vec<_> = self.storages.values().filter(|s| s.contains(entity)).map(|s| s.stats().name).collect();
+        names.sort_unstable();
+        names
+    }
+
+    pub fn entities(&self) -> impl Iterator<Item = Entity> + '_ {
+        self.alive
+            .iter()
+            .enumerate()
+            .filter(|(_, &alive)| alive)
+            .map(|(index, _)| Entity { index: index as u32, generation: self.generations[index] })
+    }
+
+    pub fn stats(&self) -> WorldStats {
+        let mut components: Vec<ComponentStats> = self.storages.values().map(|s| s.stats()).collect();
+        components.sort_by(|a, b| b.bytes.cmp(&a.bytes).then(a.name.cmp(b.name)));
+
+        let mut archetypes: HashMap<Vec<&'static str>, usize> = HashMap::new();
+        for entity in self.entities() {
+            *archetypes.entry(self.component_names(entity)).or_default() += 1;
+        }
+        let mut archetypes: Vec<ArchetypeStats> =
+            archetypes.into_iter().map(|(components, entity_count)| ArchetypeStats { components, entity_count }).collect();
+        archetypes.sort_by(|a, b| b.entity_count.cmp(&a.entity_count).then_with(|| a.components.cmp(&b.components)));
+
+        let bookkeeping = self.generations.capacity() * std::mem::size_of::<u32>()
+            + self.alive.capacity()
+            + self.free.capacity() * std::mem::size_of::<u32>();
+        WorldStats {
+            entity_count: self.entity_count(),
+            total_bytes: components.iter().map(|c| c.bytes).sum::<usize>() + bookkeeping,
+            archetypes,
+            components,
+        }
+    }
+
+    pub fn commands(&self) -> &Commands {
+        &self.commands
+    }
+
+    // Runs queued commands in order, including any they queue themselves
+    pub fn apply_commands(&mut self) {
+        loop {
+            let queued = self.commands.take();
+            if queued.is_empty() {
+                break;
+            }
+            for command in queued {
+                command(self);
+            }
+        }
+    }
+
+    pub fn spawn(&mut self) -> Entity {
+        if let Some(index) = self.free.pop() {
+            self.alive[index as usize] = true;
+            return Entity { index, generation: self.generations[index as usize] };
+        }
+        self.generations.push(0);
+        self.alive.push(true);
+        Entity { index: self.generations.len() as u32 - 1, generation: 0 }
+    }
+
+    // Removes the entity and all its components; its handle goes stale
+    pub fn despawn(&mut self, entity: Entity) -> bool {
+        if !self.clear_components(entity) {
+            return false;
+        }
+        let index = entity.index as usize;
+        self.alive[index] = false;
+        self.generations[index] = self.generations[index].wrapping_add(1);
+        self.free.push(entity.index);
+        true
+    }
+
+    // Strips every component but keeps the entity and its handle, e.g. for pooling
+    pub fn clear_components(&mut self, entity: Entity) -> bool {
+        if !self.is_alive(entity) {
+            return false;
+        }
+        for storage in self.storages.values_mut() {
+            storage.remove_entity(entity);
+        }
+        true
+    }
+
+    pub fn is_alive(&self, entity: Entity) -> bool {
+        let index = entity.index as usize;
+        self.alive.get(index).copied().unwrap_or(false) && self.generations[index] == entity.generation
+    }
+
+    pub fn entity_count(&self) -> usize {
+        self.alive.iter().filter(|&&alive| alive).count()
+    }
+
+    fn storage<T: 'static>(&self) -> Option<&Storage<T>> {
+        self.storages.get(&TypeId::of::<T>())?.as_any().downcast_ref()
+    }
+
+    fn storage_mut<T: 'static>(&mut self) -> Option<&mut Storage<T>> {
+        self.storages.get_mut(&TypeId::of::<T>())?.as_any_mut().downcast_mut()
+    }
+
+    // Replacing an existing component counts as a change, not an add
+    pub fn insert<T: 'static>(&mut self, entity: Entity, component: T) -> bool {
+        if !self.is_alive(entity) {
+            return false;
+        }
+        let tick = self.tick;
+        self.storages
+            .entry(TypeId::of::<T>())
+            .or_insert_with(|| Box::new(Storage::<T>::new()))
+            .as_any_mut()
+            .downcast_mut::<Storage<T>>()
+            .expect("storage matches its TypeId")
+            .insert(entity, component, tick);
+        true
+    }
+
+    pub fn remove<T: 'static>(&mut self, entity: Entity) -> Option<T> {
+        self.storage_mut::<T>()?.remove(entity)
+    }
+
+    pub fn has<T: 'static>(&self, entity: Entity) -> bool {
+        self.storage::<T>().is_some_and(|s| s.slot(entity).is_some())
+    }
+
+    pub fn get<T: 'static>(&self, entity: Entity) -> Option<&T> {
+        let storage = self.storage::<T>()?;
+        storage.slot(entity).map(|slot| &storage.dense[slot])
+    }
+
+    pub fn get_mut<T: 'static>(&mut self, entity: Entity) -> Option<Mut<'_, T>> {
+        let tick = self.tick;
+        let storage = self.storage_mut::<T>()?;
+        let slot = storage.slot(entity)?;
+        Some(Mut { value: &mut storage.dense[slot], changed: &mut storage.changed[slot], tick })
+    }
+
+    // (added, last changed)
+    pub fn ticks<T: 'static>(&self, entity: Entity) -> Option<(Tick, Tick)> {
+        let storage = self.storage::<T>()?;
+        storage.slot(entity).map(|slot| (storage.added[slot], storage.changed[slot]))
+    }
+
+    pub fn query<T: 'static>(&self) -> impl Iterator<Item = (Entity, &T)> {
+        self.storage::<T>()
+            .into_iter()
+            .flat_map(|storage| storage.entities.iter().copied().zip(storage.dense.iter()))
+    }
+
+    // Entities with a `T` passing filter `F` relative to `since`, usually from a `ChangeCursor`
+    pub fn query_filtered<T: 'static, F: Filter>(&self, since: Tick) -> impl Iterator<Item = (Entity, &T)> {
+        self.query::<T>().filter(move |(entity, _)| F::matches(self, *entity, since))
+    }
+
+    pub fn query_mut<T: 'static>(&mut self) -> impl Iterator<Item = (Entity, Mut<'_, T>)> {
+        let tick = self.tick;
+        self.storage_mut::<T>().into_iter().flat_map(move |storage| {
+            storage
+                .entities
+                .iter()
+                .copied()
+                .zip(storage.dense.iter_mut().zip(storage.changed.iter_mut()))
+                .map(move |(entity, (value, changed))| (entity, Mut { value, changed, tick }))
+        })
+    }
+}
@@ -0,0 +1,352 @@
+// src/environment.rs
+use crate::atmosphere::{sun_direction, Atmosphere};
+use crate::particles::{EmitterSettings, GpuParticles};
+use glam::{Vec2, Vec3};
+use serde::Deserialize;
+
+// In-game time of day, advanced from real frame time
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GameClock {
+    // Hours since midnight, 0..24
+    pub hours: f32,
+    // Whole days elapsed
+    pub day: u32,
+    // Real seconds per in-game day
+    pub day_length: f32,
+    pub paused: bool,
+}
+
+impl GameClock {
+    pub fn new(hours: f32, day_length: f32) -> Self {
+        Self { hours: hours.rem_euclid(24.0), day: 0, day_length, paused: false }
+    }
+
+    pub fn advance(&mut self, dt: f32) {
+        if self.paused || self.day_length <= 0.0 {
+            return;
+        }
+        self.hours += dt / self.day_length * 24.0;
+        while self.hours >= 24.0 {
+            self.hours -= 24.0;
+            self.day += 1;
+        }
+    }
+
+    // Jumps to `hours` on the same day, e.g. after sleeping
+    pub fn set_time(&mut self, hours: f32) {
+        self.hours = hours.rem_euclid(24.0);
+    }
+
+    // Hours and minutes, for a HUD clock
+    pub fn hours_minutes(&self) -> (u32, u32) {
+        let minutes = (self.hours * 60.0) as u32;
+        (minutes / 60 % 24, minutes % 60)
+    }
+}
+
+// Lighting at one time of day; the controller blends between neighbouring keys
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub struct LightingKey {
+    pub hours: f32,
+    pub sun_intensity: f32,
+    pub ambient: [f32; 3],
+    pub fog_color: [f32; 3],
+    pub fog_density: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+pub enum WeatherKind {
+    #[default]
+    Clear,
+    Rain,
+    Snow,
+}
+
+// Precipitation look and how much it dims the scene, at full strength. Particle values are
+// in the particle system's clip-space units.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub struct WeatherPreset {
+    // Particles per second
+    pub rate: f32,
+    pub direction: [f32; 2],
+    pub speed: f32,
+    pub spread: f32,
+    pub gravity: [f32; 2],
+    pub size: f32,
+    pub lifetime: f32,
+    pub color: [f32; 4],
+    // Added to the time-of-day fog density
+    pub fog_density: f32,
+    // Multipliers on sun intensity and ambient light
+    pub sun_dim: f32,
+    pub ambient_dim: f32,
+}
+
+impl WeatherPreset {
+    pub fn rain() -> Self {
+        Self {
+            rate: 1500.0,
+            direction: [0.1, -1.0],
+            speed: 2.5,
+            spread: 0.05,
+            gravity: [0.0, -2.0],
+            size: 0.004,
+            lifetime: 0.9,
+            color: [0.7, 0.75, 0.85, 0.6],
+            fog_density: 0.01,
+            sun_dim: 0.3,
+            ambient_dim: 0.7,
+        }
+    }
+
+    pub fn snow() -> Self {
+        Self {
+            rate: 400.0,
+            direction: [0.0, -1.0],
+            speed: 0.3,
+            spread: 0.8,
+            gravity: [0.02, -0.05],
+            size: 0.008,
+            lifetime: 6.0,
+            color: [1.0, 1.0, 1.0, 0.9],
+            fog_density: 0.02,
+            sun_dim: 0.5,
+            ambient_dim: 0.9,
+        }
+    }
+}
+
+// Day/night and weather configuration, loaded from RON, e.g.
+// `(day_length: 600.0, start_hours: 8.0, keys: [(hours: 12.0, sun_intensity: 20.0, ...)])`
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct EnvironmentSettings {
+    // Real seconds per in-game day
+    pub day_length: f32,
+    pub start_hours: f32,
+    // Sun height at noon, in radians
+    pub max_sun_elevation: f32,
+    // Lighting keys by time of day; wraps around midnight
+    pub keys: Vec<LightingKey>,
+    pub rain: WeatherPreset,
+    pub snow: WeatherPreset,
+    // Seconds for weather to fade fully in or out
+    pub transition: f32,
+    // Where precipitation spawns: a box above the top of the screen
+    pub spawn_center: [f32; 2],
+    pub spawn_extent: [f32; 2],
+}
+
+impl Default for EnvironmentSettings {
+    fn default() -> Self {
+        let key = |hours, sun_intensity, ambient, fog_color, fog_density| LightingKey {
+            hours,
+            sun_intensity,
+            ambient,
+            fog_color,
+            fog_density,
+        };
+        Self {
+            day_length: 1200.0,
+            start_hours: 8.0,
+            max_sun_elevation: 1.1,
+            keys: vec![
+                key(0.0, 0.0, [0.03, 0.04, 0.08], [0.02, 0.03, 0.06], 0.002),
+                key(5.5, 0.0, [0.05, 0.05, 0.1], [0.1, 0.1, 0.15], 0.004),
+                key(7.0, 12.0, [0.35, 0.28, 0.25], [0.8, 0.6, 0.45], 0.006),
+                key(12.0, 20.0, [0.45, 0.5, 0.55], [0.6, 0.7, 0.8], 0.002),
+                key(18.0, 12.0, [0.4, 0.28, 0.22], [0.85, 0.55, 0.4], 0.004),
+                key(19.5, 0.0, [0.06, 0.06, 0.12], [0.1, 0.1, 0.18], 0.003),
+            ],
+            rain: WeatherPreset::rain(),
+            snow: WeatherPreset::snow(),
+            transition: 8.0,
+            spawn_center: [0.0, 1.1],
+            spawn_extent: [1.3, 0.05],
+        }
+    }
+}
+
+impl EnvironmentSettings {
+    pub fn from_ron(source: &str) -> Result<Self, String> {
+        let mut settings: Self = ron::from_str(source).map_err(|e| format!("Failed to parse environment settings: {}", e))?;
+        if settings.keys.is_empty() {
+            return Err("Environment settings need at least one lighting key".to_string());
+        }
+        if let Some(key) = settings.keys.iter().find(|k| !(0.0..24.0).contains(&k.hours)) {
+            return Err(format!("Lighting key at {} hours is outside 0..24", key.hours));
+        }
+        settings.keys.sort_by(|a, b| a.hours.total_cmp(&b.hours));
+        Ok(settings)
+    }
+
+    pub fn load(path: &str) -> Result<Self, String> {
+        let source = std::fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+        Self::from_ron(&source)
+    }
+}
+
+// Animates the sun, ambient light and fog over a `GameClock`, and fades rain or snow in
+// and out. `apply` writes the result into a scene's `Atmosphere` and a particle system
+// dedicated to precipitation; `light_direction` feeds shadow cascades.
+pub struct EnvironmentController {
+    pub settings: EnvironmentSettings,
+    pub clock: GameClock,
+    // Precipitation currently showing, and what it is heading towards
+    active: WeatherKind,
+    target: WeatherKind,
+    // 0..=1 strength of `active`
+    intensity: f32,
+    // Blended by `update`
+    key: LightingKey,
+}
+
+impl EnvironmentController {
+    pub fn new(mut settings: EnvironmentSettings) -> Self {
+        settings.keys.sort_by(|a, b| a.hours.total_cmp(&b.hours));
+        let clock = GameClock::new(settings.start_hours, settings.day_length);
+        let mut controller = Self {
+            key: LightingKey { hours: 0.0, sun_intensity: 0.0, ambient: [0.0; 3], fog_color: [0.0; 3], fog_density: 0.0 },
+            settings,
+            clock,
+            active: WeatherKind::Clear,
+            target: WeatherKind::Clear,
+            intensity: 0.0,
+        };
+        controller.key = controller.lighting_at(controller.clock.hours);
+        controller
+    }
+
+    // Fades the current weather out, then the new one in
+    pub fn set_weather(&mut self, weather: WeatherKind) {
+        self.target = weather;
+    }
+
+    // Switches immediately, e.g. when loading a save
+    pub fn set_weather_now(&mut self, weather: WeatherKind) {
+        self.active = weather;
+        self.target = weather;
+        self.intensity = if weather == WeatherKind::Clear { 0.0 } else { 1.0 };
+    }
+
+    pub fn weather(&self) -> WeatherKind {
+        self.target
+    }
+
+    // Strength of the precipitation being shown, 0..=1
+    pub fn weather_intensity(&self) -> f32 {
+        if self.active == WeatherKind::Clear { 0.0 } else { self.intensity }
+    }
+
+    pub fn update(&mut self, dt: f32) {
+        self.clock.day_length = self.settings.day_length;
+        self.clock.advance(dt);
+        let step = if self.settings.transition > 0.0 { dt / self.settings.transition } else { 1.0 };
+        if self.active == self.target {
+            self.intensity = (self.intensity + step).min(1.0);
+        } else {
+            self.intensity -= step;
+            if self.intensity <= 0.0 || self.active == WeatherKind::Clear {
+                self.active = self.target;
+                self.intensity = 0.0;
+            }
+        }
+        self.key = self.lighting_at(self.clock.hours);
+    }
+
+    // Lighting keys blended for `hours`, wrapping from the last key to the first
+    pub fn lighting_at(&self, hours: f32) -> LightingKey {
+        let keys = &self.settings.keys;
+        let Some(&first) = keys.first() else { return self.key };
+        let last = keys[keys.len() - 1];
+        let next = keys.iter().position(|k| k.hours > hours);
+        let (a, b) = match next {
+            Some(0) | None => (last, first),
+            Some(i) => (keys[i - 1], keys[i]),
+        };
+        let span = (b.hours - a.hours).rem_euclid(24.0);
+        let t = if span > 0.0 { (hours - a.hours).rem_euclid(24.0) / span } else { 0.0 };
+        let mix = |x: [f32; 3], y: [f32; 3]| Vec3::from(x).lerp(Vec3::from(y), t).to_array();
+        LightingKey {
+            hours,
+            sun_intensity: a.sun_intensity + (b.sun_intensity - a.sun_intensity) * t,
+            ambient: mix(a.ambient, b.ambient),
+            fog_color: mix(a.fog_color, b.fog_color),
+            fog_density: a.fog_density + (b.fog_density - a.fog_density) * t,
+        }
+    }
+
+    fn preset(&self) -> Option<&WeatherPreset> {
+        match self.active {
+            WeatherKind::Clear => None,
+            WeatherKind::Rain => Some(&self.settings.rain),
+            WeatherKind::Snow => Some(&self.settings.snow),
+        }
+    }
+
+    // Lerps a clear-sky value towards the weather's multiplier by the current intensity
+    fn dim(&self, dim: impl Fn(&WeatherPreset) -> f32) -> f32 {
+        self.preset().map_or(1.0, |p| 1.0 + (dim(p) - 1.0) * self.intensity)
+    }
+
+    // Direction towards the sun
+    pub fn sun_direction(&self) -> Vec3 {
+        sun_direction(self.clock.hours, self.settings.max_sun_elevation)
+    }
+
+    // Direction sunlight travels, for `CascadedShadowMaps::update`
+    pub fn light_direction(&self) -> Vec3 {
+        -self.sun_direction()
+    }
+
+    pub fn sun_intensity(&self) -> f32 {
+        self.key.sun_intensity * self.dim(|p| p.sun_dim)
+    }
+
+    // Ambient term for lit shaders, multiplied by SSAO where that's enabled
+    pub fn ambient(&self) -> Vec3 {
+        Vec3::from(self.key.ambient) * self.dim(|p| p.ambient_dim)
+    }
+
+    pub fn fog_density(&self) -> f32 {
+        self.key.fog_density + self.preset().map_or(0.0, |p| p.fog_density * self.intensity)
+    }
+
+    // Writes sun and fog into `atmosphere`; a scene without a sky only gets fog. Pass the
+    // particle system that shows precipitation; its emitter is replaced every call.
+    pub fn apply(&self, atmosphere: &mut Atmosphere, particles: Option<&mut GpuParticles>) {
+        atmosphere.fog.color = Vec3::from(self.key.fog_color);
+        atmosphere.fog.density = self.fog_density();
+        if let Some(sky) = &mut atmosphere.sky {
+            sky.sun_direction = self.sun_direction();
+            sky.sun_intensity = self.sun_intensity();
+        }
+        if let Some(particles) = particles {
+            particles.emitter = self.emitter();
+        }
+    }
+
+    // Emitter settings for the current precipitation; clear weather emits nothing
+    pub fn emitter(&self) -> EmitterSettings {
+        let position = Vec2::from(self.settings.spawn_center);
+        let extent = Vec2::from(self.settings.spawn_extent);
+        let Some(preset) = self.preset() else {
+            return EmitterSettings { position, extent, rate: 0.0, ..Default::default() };
+        };
+        let mut end_color = preset.color;
+        end_color[3] = 0.0;
+        EmitterSettings {
+            position,
+            extent,
+            direction: Vec2::from(preset.direction).normalize_or(Vec2::NEG_Y),
+            speed: preset.speed,
+            spread: preset.spread,
+            lifetime: preset.lifetime,
+            size: preset.size,
+            rate: preset.rate * self.intensity,
+            gravity: Vec2::from(preset.gravity),
+            start_color: preset.color,
+            end_color,
+        }
+    }
+}
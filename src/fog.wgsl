@@ -0,0 +1,39 @@
+// Distance and height fog for forward shaders; prepend to a fragment shader (see
+// `FOG_WGSL`) and bind `AtmosphereRenderer::bind_group` at group 1
+
+struct AtmosphereParams {
+    inv_view_proj: mat4x4<f32>,
+    camera_position: vec3<f32>,
+    fog_density: f32,
+    fog_color: vec3<f32>,
+    fog_start: f32,
+    sun_direction: vec3<f32>,
+    height_falloff: f32,
+    rayleigh: vec3<f32>,
+    fog_base_height: f32,
+    mie: f32,
+    mie_g: f32,
+    sun_intensity: f32,
+    sun_scatter: f32,
+}
+
+@group(1) @binding(0) var<uniform> atmosphere: AtmosphereParams;
+
+// Fog density integrated along the view ray through an exponential height falloff
+fn fog_amount(world_position: vec3<f32>) -> f32 {
+    let ray = world_position - atmosphere.camera_position;
+    let distance = max(length(ray) - atmosphere.fog_start, 0.0);
+    let falloff = atmosphere.height_falloff;
+    let camera_density = atmosphere.fog_density * exp(-falloff * (atmosphere.camera_position.y - atmosphere.fog_base_height));
+    let rise = falloff * ray.y;
+    let height_term = select(1.0, (1.0 - exp(-rise)) / rise, abs(rise) > 1e-4);
+    return 1.0 - exp(-camera_density * height_term * distance);
+}
+
+fn apply_fog(color: vec3<f32>, world_position: vec3<f32>) -> vec3<f32> {
+    let view_direction = normalize(world_position - atmosphere.camera_position);
+    // Fog glows towards the sun
+    let glow = pow(max(dot(view_direction, atmosphere.sun_direction), 0.0), 8.0) * atmosphere.sun_scatter;
+    let fog_color = atmosphere.fog_color + vec3<f32>(1.0, 0.9, 0.7) * glow;
+    return mix(color, fog_color, fog_amount(world_position));
+}
@@ -0,0 +1,340 @@
+// src/foliage.rs
+use crate::bind_cache::{BindGroupCache, BindingKey};
+use crate::mesh::{GpuMesh, Mesh, MeshVertex};
+use crate::procgen::{poisson::poisson_disk, Rng};
+use crate::resource_registry::{ResourceRegistry, Tracked};
+use crate::terrain::Heightfield;
+use crate::upload::UploadBelt;
+use glam::{Mat4, Vec2, Vec3};
+
+// One placed plant, at vertex slot 1 of the foliage pass, locations 3..=6
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct FoliageInstance {
+    pub position: [f32; 3],
+    pub scale: f32,
+    pub color: [f32; 3],
+    // Rotation about +Y, in radians
+    pub yaw: f32,
+}
+
+impl FoliageInstance {
+    const ATTRIBUTES: [wgpu::VertexAttribute; 4] =
+        wgpu::vertex_attr_array![3 => Float32x3, 4 => Float32, 5 => Float32x3, 6 => Float32];
+
+    pub fn layout() -> wgpu::VertexBufferLayout<'static> {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<FoliageInstance>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Instance,
+            attributes: &Self::ATTRIBUTES,
+        }
+    }
+}
+
+// Mirrors `FoliageParams` in foliage.wgsl
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct FoliageParams {
+    view_proj: [f32; 16],
+    camera_position: [f32; 3],
+    time: f32,
+    sun_direction: [f32; 3],
+    wind_strength: f32,
+    sun_color: [f32; 3],
+    wind_frequency: f32,
+    ambient: [f32; 3],
+    fade_start: f32,
+    wind_direction: [f32; 2],
+    fade_end: f32,
+    _pad: f32,
+}
+
+// Scatter density 0..=1 over a world XZ rectangle, e.g. painted in an editor
+#[derive(Debug, Clone)]
+pub struct DensityMap {
+    width: u32,
+    height: u32,
+    values: Vec<f32>,
+    pub min: Vec2,
+    pub max: Vec2,
+}
+
+impl DensityMap {
+    pub fn from_fn(width: u32, height: u32, min: Vec2, max: Vec2, density: impl Fn(u32, u32) -> f32) -> Result<Self, String> {
+        if width == 0 || height == 0 {
+            return Err("A density map needs at least one texel".to_string());
+        }
+        let values = (0..height).flat_map(|y| (0..width).map(move |x| (x, y))).map(|(x, y)| density(x, y)).collect();
+        Ok(Self { width, height, values, min, max })
+    }
+
+    // Headerless 8-bit values, row 0 at `min.y`
+    pub fn from_r8(bytes: &[u8], width: u32, height: u32, min: Vec2, max: Vec2) -> Result<Self, String> {
+        if bytes.len() != (width * height) as usize {
+            return Err(format!("Expected {} bytes for a {}x{} density map, got {}", width * height, width, height, bytes.len()));
+        }
+        Self::from_fn(width, height, min, max, |x, y| bytes[(y * width + x) as usize] as f32 / 255.0)
+    }
+
+    // Bilinear density at a world XZ point; 0.0 outside the map
+    pub fn sample(&self, point: Vec2) -> f32 {
+        let uv = (point - self.min) / (self.max - self.min);
+        if uv.cmplt(Vec2::ZERO).any() || uv.cmpgt(Vec2::ONE).any() {
+            return 0.0;
+        }
+        let texel = (uv * Vec2::new(self.width as f32, self.height as f32) - 0.5).max(Vec2::ZERO);
+        let (x0, y0) = (texel.x as u32, texel.y as u32);
+        let (x1, y1) = ((x0 + 1).min(self.width - 1), (y0 + 1).min(self.height - 1));
+        let f = texel - texel.floor();
+        let v = |x: u32, y: u32| self.values[(y * self.width + x) as usize];
+        let bottom = v(x0, y0) + (v(x1, y0) - v(x0, y0)) * f.x;
+        let top = v(x0, y1) + (v(x1, y1) - v(x0, y1)) * f.x;
+        bottom + (top - bottom) * f.y
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ScatterSettings {
+    // Minimum distance between instances
+    pub spacing: f32,
+    pub scale: (f32, f32),
+    // Steepest ground allowed, 0.0 flat to 1.0 vertical
+    pub max_slope: f32,
+    // World heights allowed
+    pub height: (f32, f32),
+    pub color: Vec3,
+    // Random brightness change per instance, as a fraction
+    pub color_variation: f32,
+}
+
+impl Default for ScatterSettings {
+    fn default() -> Self {
+        Self {
+            spacing: 1.0,
+            scale: (0.8, 1.2),
+            max_slope: 0.3,
+            height: (f32::MIN, f32::MAX),
+            color: Vec3::new(0.3, 0.55, 0.2),
+            color_variation: 0.15,
+        }
+    }
+}
+
+// Evenly spread instances over `min..max` in XZ. `density` thins them out (0.0 none,
+// 1.0 as many as the spacing allows) and `ground` gives the height and normal under a
+// point, or `None` where nothing may grow.
+pub fn scatter(
+    rng: &mut Rng,
+    min: Vec2,
+    max: Vec2,
+    settings: &ScatterSettings,
+    density: impl Fn(Vec2) -> f32,
+    ground: impl Fn(Vec2) -> Option<(f32, Vec3)>,
+) -> Vec<FoliageInstance> {
+    let points = poisson_disk(rng, min, max, settings.spacing);
+    let mut instances = Vec::new();
+    for point in points {
+        let Some((height, normal)) = ground(point) else { continue };
+        if 1.0 - normal.y > settings.max_slope || height < settings.height.0 || height > settings.height.1 {
+            continue;
+        }
+        if !rng.chance(density(point).clamp(0.0, 1.0)) {
+            continue;
+        }
+        let brightness = 1.0 + rng.range_f32(-1.0, 1.0) * settings.color_variation;
+        instances.push(FoliageInstance {
+            position: [point.x, height, point.y],
+            scale: rng.range_f32(settings.scale.0, settings.scale.1),
+            color: (settings.color * brightness).into(),
+            yaw: rng.range_f32(0.0, std::f32::consts::TAU),
+        });
+    }
+    instances
+}
+
+// `scatter` across a whole heightfield, optionally weighted by a density map
+pub fn scatter_on_heightfield(
+    rng: &mut Rng,
+    heightfield: &Heightfield,
+    settings: &ScatterSettings,
+    density: Option<&DensityMap>,
+) -> Vec<FoliageInstance> {
+    let min = Vec2::new(heightfield.origin.x, heightfield.origin.z);
+    scatter(
+        rng,
+        min,
+        min + heightfield.size,
+        settings,
+        |p| density.map_or(1.0, |map| map.sample(p)),
+        |p| Some((heightfield.height_at(p.x, p.y)?, heightfield.normal_at(p.x, p.y)?)),
+    )
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FoliageSettings {
+    // Horizontal direction the wind blows towards
+    pub wind_direction: Vec2,
+    // Tip offset per unit of height squared
+    pub wind_strength: f32,
+    // Sway speed in radians per second
+    pub wind_frequency: f32,
+    // Instances start dithering out at `fade_start` and are gone by `fade_end`
+    pub fade_start: f32,
+    pub fade_end: f32,
+}
+
+impl Default for FoliageSettings {
+    fn default() -> Self {
+        Self { wind_direction: Vec2::X, wind_strength: 0.15, wind_frequency: 1.5, fade_start: 60.0, fade_end: 80.0 }
+    }
+}
+
+// One mesh and every instance of it
+struct FoliageBatch {
+    mesh: GpuMesh,
+    instances: Tracked<wgpu::Buffer>,
+    count: u32,
+}
+
+// Draws any number of foliage meshes, each instanced in one draw call. Meshes should stand
+// on y = 0 and grow towards +Y, since wind bends vertices by their height.
+pub struct FoliageRenderer {
+    pub settings: FoliageSettings,
+    batches: Vec<FoliageBatch>,
+    params_buffer: Tracked<wgpu::Buffer>,
+    layout: wgpu::BindGroupLayout,
+    pipeline: wgpu::RenderPipeline,
+}
+
+impl FoliageRenderer {
+    // `depth_format` must match the pass the foliage is drawn in
+    pub fn new(
+        device: &wgpu::Device,
+        resources: &ResourceRegistry,
+        color_format: wgpu::TextureFormat,
+        depth_format: Option<wgpu::TextureFormat>,
+    ) -> Self {
+        let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("foliage"),
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+        });
+        let shader = device.create_shader_module(wgpu::include_wgsl!("foliage.wgsl"));
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("foliage"),
+            bind_group_layouts: &[&layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("foliage"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: Some("vs_main"),
+                buffers: &[MeshVertex::layout(), FoliageInstance::layout()],
+                compilation_options: Default::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: Some("fs_main"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: color_format,
+                    blend: None,
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: Default::default(),
+            }),
+            // Cards are seen from both sides
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: depth_format.map(|format| wgpu::DepthStencilState {
+                format,
+                depth_write_enabled: true,
+                depth_compare: wgpu::CompareFunction::LessEqual,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        });
+        let params_buffer = resources.create_buffer(device, &wgpu::BufferDescriptor {
+            label: Some("foliage params"),
+            size: std::mem::size_of::<FoliageParams>() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        }, "foliage");
+        Self { settings: FoliageSettings::default(), batches: Vec::new(), params_buffer, layout, pipeline }
+    }
+
+    // Uploads a mesh with its instances; returns the batch index
+    pub fn add(&mut self, device: &wgpu::Device, resources: &ResourceRegistry, mesh: &Mesh, instances: &[FoliageInstance]) -> usize {
+        let buffer = resources.create_buffer_init(device, &wgpu::util::BufferInitDescriptor {
+            label: Some("foliage instances"),
+            // A zero-sized vertex buffer can't be bound, so keep room for one
+            contents: if instances.is_empty() { &[0; std::mem::size_of::<FoliageInstance>()] } else { bytemuck::cast_slice(instances) },
+            usage: wgpu::BufferUsages::VERTEX,
+        }, "foliage");
+        self.batches.push(FoliageBatch { mesh: mesh.upload(device, "foliage"), instances: buffer, count: instances.len() as u32 });
+        self.batches.len() - 1
+    }
+
+    pub fn clear(&mut self) {
+        self.batches.clear();
+    }
+
+    pub fn instance_count(&self) -> u32 {
+        self.batches.iter().map(|b| b.count).sum()
+    }
+
+    // `sun_direction` points towards the sun and `sun_color` includes its intensity
+    #[allow(clippy::too_many_arguments)]
+    pub fn prepare(
+        &self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        belt: &mut UploadBelt,
+        view_proj: Mat4,
+        camera_position: Vec3,
+        time: f32,
+        sun_direction: Vec3,
+        sun_color: Vec3,
+        ambient: Vec3,
+    ) {
+        let settings = &self.settings;
+        let params = FoliageParams {
+            view_proj: view_proj.to_cols_array(),
+            camera_position: camera_position.into(),
+            time,
+            sun_direction: sun_direction.normalize_or(Vec3::Y).into(),
+            wind_strength: settings.wind_strength,
+            sun_color: sun_color.into(),
+            wind_frequency: settings.wind_frequency,
+            ambient: ambient.into(),
+            fade_start: settings.fade_start,
+            wind_direction: settings.wind_direction.normalize_or_zero().into(),
+            fade_end: settings.fade_end.max(settings.fade_start + f32::EPSILON),
+            _pad: 0.0,
+        };
+        belt.write(device, encoder, &self.params_buffer, 0, bytemuck::bytes_of(&params));
+    }
+
+    pub fn draw(&self, device: &wgpu::Device, bind_groups: &mut BindGroupCache, render_pass: &mut wgpu::RenderPass<'_>) {
+        let bind_group = bind_groups.get_or_create(device, "foliage", &self.layout, &[(0, BindingKey::buffer(&self.params_buffer))]);
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(0, &bind_group, &[]);
+        for batch in self.batches.iter().filter(|b| b.count > 0) {
+            render_pass.set_vertex_buffer(0, batch.mesh.vertex_buffer.slice(..));
+            render_pass.set_vertex_buffer(1, batch.instances.slice(..));
+            render_pass.set_index_buffer(batch.mesh.index_buffer.slice(..), wgpu::IndexFormat::Uint32);
+            render_pass.draw_indexed(0..batch.mesh.index_count, 0, 0..batch.count);
+        }
+    }
+}
@@ -0,0 +1,85 @@
+// Instanced foliage: each instance is a mesh placed by position, yaw and scale. Vertices
+// sway with the wind in proportion to their height, and instances fade out with distance
+// through a dither pattern, so no blending or sorting is needed.
+
+struct FoliageParams {
+    view_proj: mat4x4<f32>,
+    camera_position: vec3<f32>,
+    time: f32,
+    sun_direction: vec3<f32>,
+    wind_strength: f32,
+    sun_color: vec3<f32>,
+    wind_frequency: f32,
+    ambient: vec3<f32>,
+    fade_start: f32,
+    wind_direction: vec2<f32>,
+    fade_end: f32,
+    _pad: f32,
+}
+
+@group(0) @binding(0) var<uniform> params: FoliageParams;
+
+struct VertexInput {
+    @location(0) position: vec3<f32>,
+    @location(1) normal: vec3<f32>,
+    @location(2) uv: vec2<f32>,
+    @location(3) instance_position: vec3<f32>,
+    @location(4) scale: f32,
+    @location(5) color: vec3<f32>,
+    @location(6) yaw: f32,
+}
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) normal: vec3<f32>,
+    @location(1) color: vec3<f32>,
+    @location(2) fade: f32,
+}
+
+fn rotate_y(v: vec3<f32>, angle: f32) -> vec3<f32> {
+    let c = cos(angle);
+    let s = sin(angle);
+    return vec3<f32>(c * v.x + s * v.z, v.y, c * v.z - s * v.x);
+}
+
+@vertex
+fn vs_main(in: VertexInput) -> VertexOutput {
+    var out: VertexOutput;
+    let distance = length(in.instance_position - params.camera_position);
+    out.fade = 1.0 - smoothstep(params.fade_start, params.fade_end, distance);
+    if out.fade <= 0.0 {
+        // Outside the clip volume, so the whole instance is dropped before rasterising
+        out.position = vec4<f32>(2.0, 2.0, 2.0, 1.0);
+        return out;
+    }
+
+    let local = rotate_y(in.position * in.scale, in.yaw);
+    // Each instance sways slightly out of phase with its neighbours
+    let phase = dot(in.instance_position.xz, vec2<f32>(0.37, 0.21));
+    let sway = 0.6 + 0.4 * sin(params.time * params.wind_frequency + phase);
+    // Tips bend most; the base stays planted
+    let bend = max(in.position.y, 0.0) * in.position.y * in.scale * params.wind_strength * sway;
+    let wind = vec3<f32>(params.wind_direction.x, 0.0, params.wind_direction.y) * bend;
+
+    let world = in.instance_position + local + wind;
+    out.position = params.view_proj * vec4<f32>(world, 1.0);
+    out.normal = rotate_y(in.normal, in.yaw);
+    out.color = in.color;
+    return out;
+}
+
+// 4x4 ordered dither threshold for a pixel
+fn bayer(pixel: vec2<u32>) -> f32 {
+    let matrix = array<f32, 16>(0.0, 8.0, 2.0, 10.0, 12.0, 4.0, 14.0, 6.0, 3.0, 11.0, 1.0, 9.0, 15.0, 7.0, 13.0, 5.0);
+    return (matrix[(pixel.y % 4u) * 4u + pixel.x % 4u] + 0.5) / 16.0;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    if in.fade < bayer(vec2<u32>(in.position.xy)) {
+        discard;
+    }
+    // Foliage is thin, so light both faces, with a wrap term for softer shading
+    let diffuse = abs(dot(normalize(in.normal), normalize(params.sun_direction))) * 0.7 + 0.3;
+    return vec4<f32>(in.color * (params.sun_color * diffuse + params.ambient), 1.0);
+}
@@ -0,0 +1,72 @@
+// src/frame_capture.rs
+
+// Shortcut and console command for `FrameCapture::apply_command`, e.g. bound to F12
+pub const CAPTURE_FRAME_COMMAND: &str = "capture_frame";
+
+// Captures the next rendered frame in an attached graphics debugger: RenderDoc through its
+// in-application API, or Xcode on Metal. Launch the game from RenderDoc (or inject it) and
+// trigger a capture from code or a hotkey instead of RenderDoc's own overlay, which doesn't
+// see frames that never present, e.g. headless rendering. Without a debugger attached a
+// capture does nothing.
+#[derive(Debug, Clone, Default)]
+pub struct FrameCapture {
+    requested: bool,
+    active: bool,
+    // Captures taken so far
+    count: u32,
+}
+
+impl FrameCapture {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Captures the next frame `Renderer::render` records
+    pub fn request(&mut self) {
+        self.requested = true;
+    }
+
+    // Handles `CAPTURE_FRAME_COMMAND`; false for any other command
+    pub fn apply_command(&mut self, command: &str) -> bool {
+        if command != CAPTURE_FRAME_COMMAND {
+            return false;
+        }
+        self.request();
+        true
+    }
+
+    pub fn is_requested(&self) -> bool {
+        self.requested
+    }
+
+    pub fn count(&self) -> u32 {
+        self.count
+    }
+
+    // Starts a requested capture; call before recording the frame's commands
+    pub fn begin_frame(&mut self, device: &wgpu::Device) {
+        if !std::mem::take(&mut self.requested) || self.active {
+            return;
+        }
+        // SAFETY: `active` keeps captures from nesting; the debugger's own rules are on
+        // whoever attached it
+        unsafe { device.start_graphics_debugger_capture() };
+        self.active = true;
+    }
+
+    // Ends the capture after the frame is submitted and presented. Waits for the GPU first,
+    // so the capture holds the frame's execution as well as its recording.
+    pub fn end_frame(&mut self, device: &wgpu::Device) {
+        if !self.active {
+            return;
+        }
+        if let Err(e) = device.poll(wgpu::PollType::wait_indefinitely()) {
+            log::warn!("Waiting for the captured frame failed: {}", e);
+        }
+        // SAFETY: the capture started in `begin_frame` is still active
+        unsafe { device.stop_graphics_debugger_capture() };
+        self.active = false;
+        self.count += 1;
+        log::info!("Captured frame {} for the attached graphics debugger", self.count);
+    }
+}
@@ -0,0 +1,111 @@
+// src/frame_pacing.rs
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+const DEFAULT_REFRESH_HZ: f32 = 60.0;
+// OS sleeps overshoot by up to a millisecond or two; the last stretch is spun instead
+const DEFAULT_SPIN: Duration = Duration::from_micros(1500);
+const HISTORY: usize = 120;
+
+// Schedules frames on the display's refresh interval. The event loop sleeps with
+// `ControlFlow::WaitUntil(wake_time())`, then `wait` spins out the remainder so frames
+// start at even intervals instead of free-running as fast as `Poll` allows.
+pub struct FramePacer {
+    refresh_interval: Duration,
+    // Frames are paced at every `divisor`-th refresh, e.g. 2 for 30 fps on a 60 Hz display
+    divisor: u32,
+    pub spin: Duration,
+    last_frame: Instant,
+    intervals: VecDeque<Duration>,
+}
+
+impl FramePacer {
+    pub fn new(refresh_hz: f32) -> Self {
+        let mut pacer = Self {
+            refresh_interval: Duration::ZERO,
+            divisor: 1,
+            spin: DEFAULT_SPIN,
+            last_frame: Instant::now(),
+            intervals: VecDeque::with_capacity(HISTORY),
+        };
+        pacer.set_refresh_rate(refresh_hz);
+        pacer
+    }
+
+    // From `WindowManager::refresh_rate_hz`; call again when the window changes monitor
+    pub fn set_refresh_rate(&mut self, refresh_hz: f32) {
+        let hz = if refresh_hz.is_finite() && refresh_hz > 1.0 { refresh_hz } else { DEFAULT_REFRESH_HZ };
+        self.refresh_interval = Duration::from_secs_f64(1.0 / hz as f64);
+    }
+
+    pub fn set_divisor(&mut self, divisor: u32) {
+        self.divisor = divisor.max(1);
+    }
+
+    pub fn target_interval(&self) -> Duration {
+        self.refresh_interval * self.divisor
+    }
+
+    // When the next frame should start
+    pub fn deadline(&self) -> Instant {
+        self.last_frame + self.target_interval()
+    }
+
+    // When the event loop should wake up to start spinning
+    pub fn wake_time(&self) -> Instant {
+        self.deadline().checked_sub(self.spin).unwrap_or_else(|| self.deadline())
+    }
+
+    pub fn is_ready(&self, now: Instant) -> bool {
+        now >= self.wake_time()
+    }
+
+    // Sleeps until shortly before the deadline, then spins until it
+    pub fn wait(&self) {
+        let deadline = self.deadline();
+        let wake = self.wake_time();
+        let now = Instant::now();
+        if wake > now {
+            std::thread::sleep(wake - now);
+        }
+        while Instant::now() < deadline {
+            std::hint::spin_loop();
+        }
+    }
+
+    // Call as the frame starts. Stays on the deadline grid so small wake-up delays don't
+    // accumulate into drift, but re-syncs after a long stall instead of rushing to catch up.
+    pub fn begin_frame(&mut self, now: Instant) {
+        if self.intervals.len() == HISTORY {
+            self.intervals.pop_front();
+        }
+        self.intervals.push_back(now.saturating_duration_since(self.last_frame));
+        let deadline = self.deadline();
+        self.last_frame = if now.saturating_duration_since(deadline) > self.target_interval() { now } else { deadline.min(now) };
+    }
+
+    // Mean of recent measured frame intervals
+    pub fn average_interval(&self) -> Duration {
+        if self.intervals.is_empty() {
+            return self.target_interval();
+        }
+        self.intervals.iter().sum::<Duration>() / self.intervals.len() as u32
+    }
+
+    // Standard deviation of recent frame intervals; low values mean smooth pacing
+    pub fn jitter(&self) -> Duration {
+        if self.intervals.len() < 2 {
+            return Duration::ZERO;
+        }
+        let mean = self.average_interval().as_secs_f64();
+        let variance = self.intervals.iter().map(|interval| (interval.as_secs_f64() - mean).powi(2)).sum::<f64>()
+            / self.intervals.len() as f64;
+        Duration::from_secs_f64(variance.sqrt())
+    }
+}
+
+impl Default for FramePacer {
+    fn default() -> Self {
+        Self::new(DEFAULT_REFRESH_HZ)
+    }
+}
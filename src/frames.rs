@@ -0,0 +1,79 @@
+// src/frames.rs
+use crate::upload::{UploadBelt, FRAMES_IN_FLIGHT};
+use std::time::{Duration, Instant};
+
+struct FrameSlot {
+    // `None` while this slot's frame is the one being recorded; the renderer holds it then
+    belt: Option<UploadBelt>,
+    // The last submit made with this slot; waited on before the slot is reused
+    submission: Option<wgpu::SubmissionIndex>,
+}
+
+// Lets the CPU record up to `FRAMES_IN_FLIGHT` frames ahead of the GPU. Each frame slot
+// owns its upload belt, so staging memory written for frame N isn't recycled until the GPU
+// has finished frame N; per-frame uniforms and storage live in the rings of `gpu_buffer`.
+// Only a slot's own submission is waited on, so the usual case never blocks.
+pub struct FramesInFlight {
+    slots: Vec<FrameSlot>,
+    current: usize,
+    frame_number: u64,
+    // Time `begin` spent waiting for the GPU in the last frame
+    stall: Duration,
+}
+
+impl FramesInFlight {
+    // `chunk_size` is each frame's upload belt chunk size
+    pub fn new(chunk_size: wgpu::BufferAddress) -> Self {
+        Self {
+            slots: (0..FRAMES_IN_FLIGHT)
+                .map(|index| FrameSlot {
+                    belt: (index != 0).then(|| UploadBelt::new(chunk_size)),
+                    submission: None,
+                })
+                .collect(),
+            current: 0,
+            frame_number: 0,
+            stall: Duration::ZERO,
+        }
+    }
+
+    // Slot being recorded, 0..FRAMES_IN_FLIGHT
+    pub fn index(&self) -> usize {
+        self.current
+    }
+
+    pub fn frame_number(&self) -> u64 {
+        self.frame_number
+    }
+
+    pub fn last_stall(&self) -> Duration {
+        self.stall
+    }
+
+    // Moves to the next slot: waits until the GPU has finished that slot's previous frame,
+    // then swaps its upload belt into `belt`, which must be the one `new` or the last
+    // `begin` handed out
+    pub fn begin(&mut self, device: &wgpu::Device, belt: &mut UploadBelt) {
+        let next = (self.current + 1) % self.slots.len();
+        let started = Instant::now();
+        if let Some(submission) = self.slots[next].submission.take() {
+            let wait = wgpu::PollType::Wait { submission_index: Some(submission), timeout: None };
+            if let Err(e) = device.poll(wait) {
+                log::warn!("Waiting for frame {} failed: {}", self.frame_number + 1 - self.slots.len() as u64, e);
+            }
+        }
+        self.stall = started.elapsed();
+
+        let Some(mut next_belt) = self.slots[next].belt.take() else { return };
+        // Its chunks finished mapping during the wait and can be reused
+        next_belt.recall();
+        self.slots[self.current].belt = Some(std::mem::replace(belt, next_belt));
+        self.current = next;
+        self.frame_number += 1;
+    }
+
+    // Records the frame's submission, so the slot isn't reused before the GPU is done with it
+    pub fn end(&mut self, submission: wgpu::SubmissionIndex) {
+        self.slots[self.current].submission = Some(submission);
+    }
+}
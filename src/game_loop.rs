@@ -30,4 +30,21 @@ impl GameLoop {
 
         (delta_time.as_secs_f64(), update_count)
     }
+
+    // Forgets time since the last tick, e.g. after the loop slept through an idle period
+    pub fn reset(&mut self) {
+        self.last_update = Instant::now();
+        self.accumulated_time = Duration::ZERO;
+    }
+
+    // Fixed timestep each update should advance by; keeps the simulation deterministic
+    pub fn fixed_delta(&self) -> f64 {
+        self.update_rate.as_secs_f64()
+    }
+
+    // How far into the next fixed update the frame is, 0..1; for drawing between the last
+    // two fixed states
+    pub fn alpha(&self) -> f64 {
+        self.accumulated_time.as_secs_f64() / self.update_rate.as_secs_f64()
+    }
 }
\ No newline at end of file
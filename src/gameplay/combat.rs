@@ -0,0 +1,139 @@
+// src/gameplay/combat.rs
+use crate::ecs::world::{Entity, World};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Health {
+    pub current: f32,
+    pub max: f32,
+    // Invulnerability granted after each hit, e.g. a player's flashing i-frames
+    pub hit_invulnerability: f32,
+}
+
+impl Health {
+    pub fn new(max: f32) -> Self {
+        Self { current: max, max, hit_invulnerability: 0.0 }
+    }
+
+    pub fn with_invulnerability(mut self, seconds: f32) -> Self {
+        self.hit_invulnerability = seconds;
+        self
+    }
+
+    pub fn is_dead(&self) -> bool {
+        self.current <= 0.0
+    }
+
+    pub fn fraction(&self) -> f32 {
+        if self.max > 0.0 { (self.current / self.max).clamp(0.0, 1.0) } else { 0.0 }
+    }
+
+    // Restores up to `max`; the dead stay dead
+    pub fn heal(&mut self, amount: f32) {
+        if !self.is_dead() {
+            self.current = (self.current + amount).min(self.max);
+        }
+    }
+}
+
+// What an entity deals when it hits something, e.g. a bullet or spikes
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Damage(pub f32);
+
+// Entities on the same team don't hurt each other
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Team(pub u32);
+
+// Seconds left during which damage is ignored; removed when it runs out
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Invulnerable(pub f32);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CombatEvent {
+    DamageTaken { target: Entity, source: Option<Entity>, amount: f32, remaining: f32 },
+    // Health reached zero; despawning or playing a death animation is up to the game
+    Died { entity: Entity, killer: Option<Entity> },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct DamageRequest {
+    target: Entity,
+    source: Option<Entity>,
+    // `None` takes the source's `Damage`
+    amount: Option<f32>,
+}
+
+// Resource collecting hits for `resolve_damage` and holding what came of them. Gameplay
+// code reports hits whenever it finds them; teams, invulnerability and death are all
+// settled in one place.
+#[derive(Debug, Default)]
+pub struct Combat {
+    pending: Vec<DamageRequest>,
+    events: Vec<CombatEvent>,
+}
+
+impl Combat {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // `source` hit `target` for its `Damage`, e.g. from a projectile impact
+    pub fn hit(&mut self, source: Entity, target: Entity) {
+        self.pending.push(DamageRequest { target, source: Some(source), amount: None });
+    }
+
+    // A set amount, with or without someone to blame, e.g. fall damage or poison
+    pub fn damage(&mut self, target: Entity, amount: f32, source: Option<Entity>) {
+        self.pending.push(DamageRequest { target, source, amount: Some(amount) });
+    }
+
+    // What the last `resolve_damage` run produced, in order
+    pub fn events(&self) -> &[CombatEvent] {
+        &self.events
+    }
+}
+
+// System applying queued hits to `Health`. Same-team hits, invulnerable and already dead
+// targets are skipped. Events replace the previous run's, so read them after this runs.
+pub fn resolve_damage(world: &mut World, delta_time: f64) {
+    let mut expired = Vec::new();
+    for (entity, mut invulnerable) in world.query_mut::<Invulnerable>() {
+        invulnerable.0 -= delta_time as f32;
+        if invulnerable.0 <= 0.0 {
+            expired.push(entity);
+        }
+    }
+    for entity in expired {
+        world.remove::<Invulnerable>(entity);
+    }
+
+    let Some(combat) = world.resources.get_mut::<Combat>() else { return };
+    combat.events.clear();
+    let pending = std::mem::take(&mut combat.pending);
+    let mut events = Vec::new();
+    for request in &pending {
+        let Some(amount) = request.amount.or_else(|| world.get::<Damage>(request.source?).map(|d| d.0)) else { continue };
+        let target = request.target;
+        let team = |entity| world.get::<Team>(entity).copied();
+        if amount <= 0.0 || world.has::<Invulnerable>(target) || request.source.and_then(team).is_some_and(|t| team(target) == Some(t)) {
+            continue;
+        }
+        let Some(mut health) = world.get_mut::<Health>(target) else { continue };
+        if health.is_dead() {
+            continue;
+        }
+        health.current = (health.current - amount).max(0.0);
+        let (remaining, window) = (health.current, health.hit_invulnerability);
+        events.push(CombatEvent::DamageTaken { target, source: request.source, amount, remaining });
+        if remaining <= 0.0 {
+            events.push(CombatEvent::Died { entity: target, killer: request.source });
+        } else if window > 0.0 {
+            world.insert(target, Invulnerable(window));
+        }
+    }
+
+    let combat = world.resources.get_mut::<Combat>().expect("checked above");
+    combat.events = events;
+    // Keep the allocation for next frame's hits
+    combat.pending = pending;
+    combat.pending.clear();
+}
@@ -0,0 +1,201 @@
+// src/gameplay/dialogue.rs
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+use winit::event::{ElementState, KeyEvent, WindowEvent};
+use winit::keyboard::{KeyCode, PhysicalKey};
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct DialogueChoice {
+    pub text: String,
+    // Node the choice leads to; `None` ends the conversation
+    #[serde(default)]
+    pub next: Option<String>,
+    // Flag that must be set for the choice to be offered
+    #[serde(default)]
+    pub requires: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct DialogueNode {
+    pub id: String,
+    #[serde(default)]
+    pub speaker: String,
+    pub text: String,
+    // Followed when there are no choices; `None` ends the conversation
+    #[serde(default)]
+    pub next: Option<String>,
+    #[serde(default)]
+    pub choices: Vec<DialogueChoice>,
+    // Gameplay events fired on reaching the node, e.g. "open_gate"
+    #[serde(default)]
+    pub events: Vec<String>,
+    // Flags set on reaching the node, for later `requires`
+    #[serde(default)]
+    pub set_flags: Vec<String>,
+}
+
+// A branching conversation, loaded from RON, e.g.
+// `(start: "hello", nodes: [(id: "hello", speaker: "Guard", text: "Halt!", choices: [(text: "Sorry", next: Some("bye"))]), ...])`
+#[derive(Debug, Clone)]
+pub struct Dialogue {
+    pub start: String,
+    nodes: Vec<DialogueNode>,
+    index: HashMap<String, usize>,
+}
+
+#[derive(Deserialize)]
+struct DialogueFile {
+    start: String,
+    nodes: Vec<DialogueNode>,
+}
+
+impl Dialogue {
+    pub fn from_ron(source: &str) -> Result<Self, String> {
+        let file: DialogueFile = ron::from_str(source).map_err(|e| format!("Failed to parse dialogue: {}", e))?;
+        let mut index = HashMap::new();
+        for (i, node) in file.nodes.iter().enumerate() {
+            if index.insert(node.id.clone(), i).is_some() {
+                return Err(format!("Dialogue node {} is defined twice", node.id));
+            }
+        }
+        let missing = |target: &Option<String>| target.as_ref().filter(|t| !index.contains_key(*t)).cloned();
+        if !index.contains_key(&file.start) {
+            return Err(format!("Dialogue starts at missing node {}", file.start));
+        }
+        for node in &file.nodes {
+            let targets = std::iter::once(&node.next).chain(node.choices.iter().map(|c| &c.next));
+            if let Some(target) = targets.filter_map(missing).next() {
+                return Err(format!("Dialogue node {} leads to missing node {}", node.id, target));
+            }
+        }
+        Ok(Self { start: file.start, nodes: file.nodes, index })
+    }
+
+    pub fn load(path: &str) -> Result<Self, String> {
+        let source = std::fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+        Self::from_ron(&source)
+    }
+
+    pub fn node(&self, id: &str) -> Option<&DialogueNode> {
+        self.index.get(id).map(|&i| &self.nodes[i])
+    }
+}
+
+// What the player did, from whatever input the game uses
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DialogueInput {
+    Continue,
+    // Index into `DialogueRunner::choices`
+    Choose(usize),
+}
+
+impl DialogueInput {
+    // Default keys: Enter or Space to continue, 1-9 to pick a choice
+    pub fn from_window_event(event: &WindowEvent) -> Option<Self> {
+        let WindowEvent::KeyboardInput {
+            event: KeyEvent { physical_key: PhysicalKey::Code(code), state: ElementState::Pressed, repeat: false, .. },
+            ..
+        } = event
+        else {
+            return None;
+        };
+        const DIGITS: [KeyCode; 9] = [
+            KeyCode::Digit1,
+            KeyCode::Digit2,
+            KeyCode::Digit3,
+            KeyCode::Digit4,
+            KeyCode::Digit5,
+            KeyCode::Digit6,
+            KeyCode::Digit7,
+            KeyCode::Digit8,
+            KeyCode::Digit9,
+        ];
+        match code {
+            KeyCode::Enter | KeyCode::Space => Some(Self::Continue),
+            code => DIGITS.iter().position(|d| d == code).map(Self::Choose),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DialogueEvent {
+    // A node's gameplay event
+    Fired(String),
+    Ended,
+}
+
+// Plays one conversation at a time. The UI shows `current` and `choices` and feeds player
+// input to `handle_input`; the game drains events to react to what was said. Flags persist
+// across conversations, so earlier answers can unlock later choices.
+#[derive(Debug, Default)]
+pub struct DialogueRunner {
+    pub flags: HashSet<String>,
+    dialogue: Option<Dialogue>,
+    current: Option<usize>,
+    events: Vec<DialogueEvent>,
+}
+
+impl DialogueRunner {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn start(&mut self, dialogue: Dialogue) {
+        let start = dialogue.index[&dialogue.start];
+        self.dialogue = Some(dialogue);
+        self.enter(Some(start));
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.current.is_some()
+    }
+
+    pub fn current(&self) -> Option<&DialogueNode> {
+        Some(&self.dialogue.as_ref()?.nodes[self.current?])
+    }
+
+    // Choices on the current node whose flags are set, in authored order
+    pub fn choices(&self) -> Vec<&DialogueChoice> {
+        self.current()
+            .map(|node| node.choices.iter().filter(|c| c.requires.as_ref().is_none_or(|f| self.flags.contains(f))).collect())
+            .unwrap_or_default()
+    }
+
+    // Moves the conversation on. `Continue` is ignored while choices are offered and
+    // choices out of range are ignored, so stray key presses are harmless.
+    pub fn handle_input(&mut self, input: DialogueInput) {
+        let Some(node) = self.current() else { return };
+        let next = match input {
+            DialogueInput::Continue if node.choices.is_empty() => node.next.clone(),
+            DialogueInput::Choose(i) => match self.choices().get(i) {
+                Some(choice) => choice.next.clone(),
+                None => return,
+            },
+            DialogueInput::Continue => return,
+        };
+        let next = next.and_then(|id| self.dialogue.as_ref()?.index.get(&id).copied());
+        self.enter(next);
+    }
+
+    // Ends the conversation early, e.g. when the player walks away
+    pub fn stop(&mut self) {
+        if self.current.is_some() {
+            self.enter(None);
+        }
+    }
+
+    fn enter(&mut self, node: Option<usize>) {
+        self.current = node;
+        let Some(node) = self.current() else {
+            self.events.push(DialogueEvent::Ended);
+            return;
+        };
+        let (fired, flags) = (node.events.clone(), node.set_flags.clone());
+        self.events.extend(fired.into_iter().map(DialogueEvent::Fired));
+        self.flags.extend(flags);
+    }
+
+    pub fn drain_events(&mut self) -> impl Iterator<Item = DialogueEvent> + '_ {
+        self.events.drain(..)
+    }
+}
@@ -0,0 +1,229 @@
+// src/gameplay/inventory.rs
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+// One kind of item as authored in the item database
+#[derive(Debug, Clone, Deserialize)]
+pub struct ItemDef {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub icon: Option<String>,
+    // 1 for unstackable items like weapons
+    #[serde(default = "default_max_stack")]
+    pub max_stack: u32,
+    // Equipment slot the item goes in, e.g. "head"; `None` for items that can't be worn
+    #[serde(default)]
+    pub equip_slot: Option<String>,
+    // Free-form numbers for game code, e.g. `{"damage": 12.0, "weight": 3.5}`
+    #[serde(default)]
+    pub stats: BTreeMap<String, f32>,
+}
+
+fn default_max_stack() -> u32 {
+    1
+}
+
+// Every item in the game, loaded from RON, e.g.
+// `(equip_slots: ["head", "hand"], items: [(id: "potion", name: "Potion", max_stack: 10), ...])`
+#[derive(Debug, Clone, Default)]
+pub struct ItemDatabase {
+    pub equip_slots: Vec<String>,
+    items: Vec<ItemDef>,
+    index: HashMap<String, usize>,
+}
+
+#[derive(Deserialize)]
+struct ItemDatabaseFile {
+    #[serde(default)]
+    equip_slots: Vec<String>,
+    items: Vec<ItemDef>,
+}
+
+impl ItemDatabase {
+    pub fn from_ron(source: &str) -> Result<Self, String> {
+        let file: ItemDatabaseFile = ron::from_str(source).map_err(|e| format!("Failed to parse item database: {}", e))?;
+        let mut index = HashMap::new();
+        for (i, item) in file.items.iter().enumerate() {
+            if index.insert(item.id.clone(), i).is_some() {
+                return Err(format!("Item {} is defined twice", item.id));
+            }
+            if item.max_stack == 0 {
+                return Err(format!("Item {} needs a max_stack of at least 1", item.id));
+            }
+            if let Some(slot) = item.equip_slot.as_ref().filter(|s| !file.equip_slots.contains(s)) {
+                return Err(format!("Item {} goes in unknown equip slot {}", item.id, slot));
+            }
+        }
+        Ok(Self { equip_slots: file.equip_slots, items: file.items, index })
+    }
+
+    pub fn load(path: &str) -> Result<Self, String> {
+        let source = std::fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+        Self::from_ron(&source)
+    }
+
+    pub fn get(&self, id: &str) -> Option<&ItemDef> {
+        self.index.get(id).map(|&i| &self.items[i])
+    }
+
+    pub fn items(&self) -> &[ItemDef] {
+        &self.items
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ItemStack {
+    pub item: String,
+    pub count: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InventoryEvent {
+    Added { item: String, count: u32 },
+    Removed { item: String, count: u32 },
+    Equipped { slot: String, item: String },
+    Unequipped { slot: String, item: String },
+}
+
+// Component holding an entity's items: a fixed number of bag slots plus worn equipment.
+// Serializes for save games; item ids are looked up in an `ItemDatabase` when adding or
+// equipping. Changes are recorded as events until drained.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Inventory {
+    pub slots: Vec<Option<ItemStack>>,
+    pub equipment: BTreeMap<String, ItemStack>,
+    #[serde(skip)]
+    events: Vec<InventoryEvent>,
+}
+
+impl Inventory {
+    pub fn new(slot_count: usize) -> Self {
+        Self { slots: vec![None; slot_count], equipment: BTreeMap::new(), events: Vec::new() }
+    }
+
+    // Tops up existing stacks first, then fills empty slots. Returns how many didn't fit.
+    pub fn add(&mut self, items: &ItemDatabase, item: &str, count: u32) -> Result<u32, String> {
+        let max_stack = items.get(item).ok_or_else(|| format!("Unknown item {}", item))?.max_stack;
+        let mut left = count;
+        for stack in self.slots.iter_mut().flatten().filter(|s| s.item == item) {
+            let moved = left.min(max_stack.saturating_sub(stack.count));
+            stack.count += moved;
+            left -= moved;
+        }
+        for slot in self.slots.iter_mut().filter(|s| s.is_none()) {
+            if left == 0 {
+                break;
+            }
+            let moved = left.min(max_stack);
+            *slot = Some(ItemStack { item: item.to_string(), count: moved });
+            left -= moved;
+        }
+        if left < count {
+            self.events.push(InventoryEvent::Added { item: item.to_string(), count: count - left });
+        }
+        Ok(left)
+    }
+
+    // Takes up to `count` from the bag, last slots first. Returns how many were removed.
+    pub fn remove(&mut self, item: &str, count: u32) -> u32 {
+        let mut left = count;
+        for slot in self.slots.iter_mut().rev() {
+            let Some(stack) = slot.as_mut().filter(|s| s.item == item) else { continue };
+            let taken = left.min(stack.count);
+            stack.count -= taken;
+            left -= taken;
+            if stack.count == 0 {
+                *slot = None;
+            }
+            if left == 0 {
+                break;
+            }
+        }
+        if left < count {
+            self.events.push(InventoryEvent::Removed { item: item.to_string(), count: count - left });
+        }
+        count - left
+    }
+
+    // In the bag; equipment isn't counted
+    pub fn count(&self, item: &str) -> u32 {
+        self.slots.iter().flatten().filter(|s| s.item == item).map(|s| s.count).sum()
+    }
+
+    // Drag and drop between bag slots: merges matching stacks up to their limit, swaps otherwise
+    pub fn move_slot(&mut self, items: &ItemDatabase, from: usize, to: usize) -> Result<(), String> {
+        if from >= self.slots.len() || to >= self.slots.len() {
+            return Err(format!("Slot {} or {} is out of range", from, to));
+        }
+        if from == to {
+            return Ok(());
+        }
+        if let (Some(a), Some(b)) = (&self.slots[from], &self.slots[to]) {
+            if a.item == b.item {
+                let max_stack = items.get(&a.item).map_or(1, |d| d.max_stack);
+                let moved = a.count.min(max_stack.saturating_sub(b.count));
+                self.slots[to].as_mut().expect("checked above").count += moved;
+                let source = self.slots[from].as_mut().expect("checked above");
+                source.count -= moved;
+                if source.count == 0 {
+                    self.slots[from] = None;
+                }
+                return Ok(());
+            }
+        }
+        self.slots.swap(from, to);
+        Ok(())
+    }
+
+    // Wears the item in bag slot `slot`; whatever was in its equip slot goes back to the bag
+    pub fn equip(&mut self, items: &ItemDatabase, slot: usize) -> Result<(), String> {
+        let stack = self.slots.get(slot).cloned().flatten().ok_or_else(|| format!("Slot {} is empty", slot))?;
+        let def = items.get(&stack.item).ok_or_else(|| format!("Unknown item {}", stack.item))?;
+        let equip_slot = def.equip_slot.clone().ok_or_else(|| format!("Item {} can't be equipped", stack.item))?;
+        // One of a stack is worn, the rest stay in the bag
+        let worn = ItemStack { item: stack.item.clone(), count: 1 };
+        if stack.count > 1 {
+            self.slots[slot].as_mut().expect("checked above").count -= 1;
+        } else {
+            self.slots[slot] = None;
+        }
+        if let Some(previous) = self.equipment.remove(&equip_slot) {
+            if let Some(free) = self.slots.iter_mut().find(|s| s.is_none()) {
+                *free = Some(previous.clone());
+            } else {
+                // No room to take it off: undo
+                self.equipment.insert(equip_slot, previous);
+                self.slots[slot] = Some(stack);
+                return Err("No free slot for the item being replaced".to_string());
+            }
+            self.events.push(InventoryEvent::Unequipped { slot: equip_slot.clone(), item: previous.item });
+        }
+        self.events.push(InventoryEvent::Equipped { slot: equip_slot.clone(), item: worn.item.clone() });
+        self.equipment.insert(equip_slot, worn);
+        Ok(())
+    }
+
+    // Moves the item worn in `equip_slot` back to the first free bag slot
+    pub fn unequip(&mut self, equip_slot: &str) -> Result<(), String> {
+        if !self.equipment.contains_key(equip_slot) {
+            return Err(format!("Nothing is equipped in {}", equip_slot));
+        }
+        let free = self.slots.iter_mut().find(|s| s.is_none()).ok_or("No free slot to unequip into")?;
+        let stack = self.equipment.remove(equip_slot).expect("checked above");
+        self.events.push(InventoryEvent::Unequipped { slot: equip_slot.to_string(), item: stack.item.clone() });
+        *free = Some(stack);
+        Ok(())
+    }
+
+    pub fn equipped(&self, equip_slot: &str) -> Option<&ItemStack> {
+        self.equipment.get(equip_slot)
+    }
+
+    // Changes since the last call, in order, e.g. for UI refreshes and pickup messages
+    pub fn drain_events(&mut self) -> impl Iterator<Item = InventoryEvent> + '_ {
+        self.events.drain(..)
+    }
+}
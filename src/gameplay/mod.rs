@@ -0,0 +1,4 @@
+// src/gameplay/mod.rs
+pub mod combat;
+pub mod inventory;
+pub mod dialogue;
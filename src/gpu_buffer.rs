@@ -0,0 +1,257 @@
+// src/gpu_buffer.rs
+use crate::bind_cache::{BindGroupCache, BindingKey};
+use crate::resource_registry::{ResourceRegistry, Tracked};
+use crate::upload::{UploadBelt, FRAMES_IN_FLIGHT};
+use std::marker::PhantomData;
+
+// A `#[repr(C)]` Pod struct mirrored by a WGSL struct. `WGSL_ALIGN` is the WGSL struct's
+// alignment: 16 if any member is a vec3, vec4 or matrix, 8 for a vec2, otherwise 4. The
+// Rust side may leave out trailing padding; the wrappers below add it when writing.
+pub trait GpuStruct: bytemuck::Pod {
+    const WGSL_ALIGN: u64 = 16;
+}
+
+// Array stride of `T` in the uniform address space (std140 rules: multiples of 16)
+pub fn std140_stride<T: GpuStruct>() -> u64 {
+    wgpu::util::align_to(std::mem::size_of::<T>() as u64, T::WGSL_ALIGN.max(16))
+}
+
+// Array stride of `T` in the storage address space (std430 rules)
+pub fn std430_stride<T: GpuStruct>() -> u64 {
+    wgpu::util::align_to(std::mem::size_of::<T>() as u64, T::WGSL_ALIGN)
+}
+
+// One buffer split into a region per frame in flight, so the CPU writes frame N+1's data
+// while the GPU may still read frame N's
+struct FrameRing {
+    buffer: Tracked<wgpu::Buffer>,
+    label: &'static str,
+    usage: wgpu::BufferUsages,
+    region: u64,
+    frame: u64,
+}
+
+impl FrameRing {
+    fn new(device: &wgpu::Device, resources: &ResourceRegistry, label: &'static str, usage: wgpu::BufferUsages, region: u64) -> Self {
+        let usage = usage | wgpu::BufferUsages::COPY_DST;
+        Self { buffer: Self::allocate(device, resources, label, usage, region), label, usage, region, frame: 0 }
+    }
+
+    fn allocate(
+        device: &wgpu::Device,
+        resources: &ResourceRegistry,
+        label: &'static str,
+        usage: wgpu::BufferUsages,
+        region: u64,
+    ) -> Tracked<wgpu::Buffer> {
+        resources.create_buffer(device, &wgpu::BufferDescriptor {
+            label: Some(label),
+            size: region * FRAMES_IN_FLIGHT as u64,
+            usage,
+            mapped_at_creation: false,
+        }, label)
+    }
+
+    fn advance(&mut self) {
+        self.frame = (self.frame + 1) % FRAMES_IN_FLIGHT as u64;
+    }
+
+    fn offset(&self) -> u64 {
+        self.frame * self.region
+    }
+
+    // Earlier frames' commands keep the old buffer alive until they finish
+    fn grow(&mut self, device: &wgpu::Device, resources: &ResourceRegistry, region: u64) {
+        log::debug!("Growing {} buffer to {} bytes per frame", self.label, region);
+        self.region = region;
+        self.buffer = Self::allocate(device, resources, self.label, self.usage, region);
+    }
+}
+
+// Copies `items` into `scratch` at `stride` bytes apart, zero-filling the padding
+fn pack<T: bytemuck::Pod>(items: &[T], stride: u64, scratch: &mut Vec<u8>) {
+    scratch.clear();
+    scratch.resize(items.len() * stride as usize, 0);
+    for (item, slot) in items.iter().zip(scratch.chunks_exact_mut(stride as usize)) {
+        let bytes = bytemuck::bytes_of(item);
+        slot[..bytes.len()].copy_from_slice(bytes);
+    }
+}
+
+// Shader parameters in a uniform buffer, ring-buffered across frames in flight. Either one
+// value per frame (`set`) or up to `slots` values addressed by dynamic offset (`begin_frame`
+// then `push`), e.g. per-object transforms drawn with one bind group.
+pub struct UniformBuffer<T: GpuStruct> {
+    ring: FrameRing,
+    // Slot size: the std140 size rounded up to the device's dynamic offset alignment
+    stride: u64,
+    slots: u32,
+    used: u32,
+    scratch: Vec<u8>,
+    _marker: PhantomData<T>,
+}
+
+impl<T: GpuStruct> UniformBuffer<T> {
+    pub fn new(device: &wgpu::Device, resources: &ResourceRegistry, label: &'static str, slots: u32) -> Self {
+        let slots = slots.max(1);
+        let alignment = device.limits().min_uniform_buffer_offset_alignment as u64;
+        let stride = wgpu::util::align_to(std140_stride::<T>(), alignment);
+        Self {
+            ring: FrameRing::new(device, resources, label, wgpu::BufferUsages::UNIFORM, stride * slots as u64),
+            stride,
+            slots,
+            used: 0,
+            scratch: Vec::new(),
+            _marker: PhantomData,
+        }
+    }
+
+    // For the bind group layout; dynamic offsets when there is more than one slot
+    pub fn layout_entry(&self, binding: u32, visibility: wgpu::ShaderStages) -> wgpu::BindGroupLayoutEntry {
+        wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Uniform,
+                has_dynamic_offset: self.slots > 1,
+                min_binding_size: wgpu::BufferSize::new(std140_stride::<T>()),
+            },
+            count: None,
+        }
+    }
+
+    // The current frame's value, replacing the last one
+    pub fn set(&mut self, device: &wgpu::Device, encoder: &mut wgpu::CommandEncoder, belt: &mut UploadBelt, value: &T) {
+        self.begin_frame();
+        let _ = self.push(device, encoder, belt, value);
+    }
+
+    // Moves to the next frame's region; call once per frame before `push`
+    pub fn begin_frame(&mut self) {
+        self.ring.advance();
+        self.used = 0;
+    }
+
+    // Returns the dynamic offset to pass to `set_bind_group`
+    pub fn push(
+        &mut self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        belt: &mut UploadBelt,
+        value: &T,
+    ) -> Result<u32, String> {
+        if self.used >= self.slots {
+            return Err(format!("Uniform buffer {} is full ({} slots)", self.ring.label, self.slots));
+        }
+        let offset = self.used as u64 * self.stride;
+        pack(std::slice::from_ref(value), std140_stride::<T>(), &mut self.scratch);
+        belt.write(device, encoder, &self.ring.buffer, self.ring.offset() + offset, &self.scratch);
+        self.used += 1;
+        Ok(offset as u32)
+    }
+
+    // One slot of the current frame's region; dynamic offsets move it along
+    pub fn binding(&self) -> BindingKey {
+        BindingKey::Buffer { buffer: self.ring.buffer.clone(), offset: self.ring.offset(), size: Some(std140_stride::<T>()) }
+    }
+
+    // For layouts holding only this buffer, at binding 0
+    pub fn bind_group(&self, device: &wgpu::Device, bind_groups: &mut BindGroupCache, layout: &wgpu::BindGroupLayout) -> wgpu::BindGroup {
+        bind_groups.get_or_create(device, self.ring.label, layout, &[(0, self.binding())])
+    }
+}
+
+// A growable array in a storage buffer (std430 layout), edited on the CPU and uploaded into
+// the next frame's region of a ring, e.g. light lists or instance data.
+pub struct StorageVec<T: GpuStruct> {
+    items: Vec<T>,
+    ring: FrameRing,
+    // Elements that fit in one frame's region
+    capacity: u64,
+    scratch: Vec<u8>,
+}
+
+impl<T: GpuStruct> StorageVec<T> {
+    pub fn new(device: &wgpu::Device, resources: &ResourceRegistry, label: &'static str, capacity: usize) -> Self {
+        let capacity = capacity.max(1) as u64;
+        Self {
+            items: Vec::with_capacity(capacity as usize),
+            ring: FrameRing::new(device, resources, label, wgpu::BufferUsages::STORAGE, Self::region_size(device, capacity)),
+            capacity,
+            scratch: Vec::new(),
+        }
+    }
+
+    fn region_size(device: &wgpu::Device, capacity: u64) -> u64 {
+        let alignment = device.limits().min_storage_buffer_offset_alignment as u64;
+        wgpu::util::align_to(capacity * std430_stride::<T>(), alignment)
+    }
+
+    pub fn layout_entry(binding: u32, visibility: wgpu::ShaderStages, read_only: bool) -> wgpu::BindGroupLayoutEntry {
+        wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Storage { read_only },
+                has_dynamic_offset: false,
+                min_binding_size: wgpu::BufferSize::new(std430_stride::<T>()),
+            },
+            count: None,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    pub fn push(&mut self, item: T) {
+        self.items.push(item);
+    }
+
+    pub fn clear(&mut self) {
+        self.items.clear();
+    }
+
+    pub fn items(&self) -> &[T] {
+        &self.items
+    }
+
+    pub fn items_mut(&mut self) -> &mut Vec<T> {
+        &mut self.items
+    }
+
+    // Writes the items into the next frame's region, growing to the next power of two if needed
+    pub fn upload(
+        &mut self,
+        device: &wgpu::Device,
+        resources: &ResourceRegistry,
+        encoder: &mut wgpu::CommandEncoder,
+        belt: &mut UploadBelt,
+    ) {
+        let len = self.items.len() as u64;
+        if len > self.capacity {
+            self.capacity = len.next_power_of_two();
+            self.ring.grow(device, resources, Self::region_size(device, self.capacity));
+        }
+        self.ring.advance();
+        if !self.items.is_empty() {
+            pack(&self.items, std430_stride::<T>(), &mut self.scratch);
+            belt.write(device, encoder, &self.ring.buffer, self.ring.offset(), &self.scratch);
+        }
+    }
+
+    // The uploaded items; WGSL's `arrayLength` sees at least one element
+    pub fn binding(&self) -> BindingKey {
+        let len = self.items.len().max(1) as u64;
+        BindingKey::Buffer { buffer: self.ring.buffer.clone(), offset: self.ring.offset(), size: Some(len * std430_stride::<T>()) }
+    }
+
+    // For layouts holding only this buffer, at binding 0
+    pub fn bind_group(&self, device: &wgpu::Device, bind_groups: &mut BindGroupCache, layout: &wgpu::BindGroupLayout) -> wgpu::BindGroup {
+        bind_groups.get_or_create(device, self.ring.label, layout, &[(0, self.binding())])
+    }
+}
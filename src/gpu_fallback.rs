@@ -0,0 +1,198 @@
+// src/gpu_fallback.rs
+use std::fmt;
+use std::sync::Arc;
+use winit::window::Window;
+use crate::capabilities::BINDLESS_FEATURES;
+
+// How far down the fallback chain device creation had to go. Anything past `Full` is
+// worth showing in a settings screen or bug report: it usually means an old or broken driver.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GpuTier {
+    // Preferred adapter with the engine's usual limits and optional features
+    Full,
+    // Same adapter, WebGL2 limits and no optional features
+    Downlevel,
+    // A backend other than the one picked first, e.g. GL when Vulkan fails
+    AlternateBackend(wgpu::Backend),
+    // CPU rasterizer (WARP, llvmpipe, SwiftShader); slow but draws everything
+    Software,
+    // A device, but the main pipeline failed to build: frames are cleared and nothing is drawn
+    ClearOnly,
+}
+
+impl fmt::Display for GpuTier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Full => write!(f, "full"),
+            Self::Downlevel => write!(f, "downlevel (WebGL2 limits)"),
+            Self::AlternateBackend(backend) => write!(f, "alternate backend ({:?})", backend),
+            Self::Software => write!(f, "software rasterizer"),
+            Self::ClearOnly => write!(f, "clear-only"),
+        }
+    }
+}
+
+// Which tier was selected, on what, and why the tiers above it were skipped
+#[derive(Debug, Clone)]
+pub struct GpuSelection {
+    pub tier: GpuTier,
+    pub adapter: wgpu::AdapterInfo,
+    // One line per failed attempt, in order
+    pub failures: Vec<String>,
+}
+
+pub(crate) struct AcquiredGpu {
+    pub adapter: wgpu::Adapter,
+    pub device: wgpu::Device,
+    pub queue: wgpu::Queue,
+    pub surface: Option<wgpu::Surface<'static>>,
+    pub selection: GpuSelection,
+}
+
+#[derive(Clone, Copy)]
+struct Attempt {
+    tier: GpuTier,
+    backends: wgpu::Backends,
+    force_fallback_adapter: bool,
+    // Engine limits and optional features; WebGL2 limits and none otherwise
+    full: bool,
+}
+
+const ALTERNATE_BACKENDS: [wgpu::Backend; 4] =
+    [wgpu::Backend::Vulkan, wgpu::Backend::Dx12, wgpu::Backend::Metal, wgpu::Backend::Gl];
+
+// Optional features the engine uses when the adapter has them
+fn optional_features(adapter: &wgpu::Adapter) -> wgpu::Features {
+    // Compressed formats cut VRAM and load times where the adapter has them
+    // Per-draw instance offsets in indirect args need INDIRECT_FIRST_INSTANCE
+    let mut features = crate::texture::compression_features(adapter.features())
+        | (adapter.features() & wgpu::Features::INDIRECT_FIRST_INSTANCE)
+        // Line rasterization for the wireframe debug view
+        | (adapter.features() & wgpu::Features::POLYGON_MODE_LINE);
+    // Bindless textures only as a set; any one alone is no use
+    if adapter.features().contains(BINDLESS_FEATURES) {
+        features |= BINDLESS_FEATURES;
+    }
+    features
+}
+
+async fn try_attempt(
+    attempt: Attempt,
+    window: Option<&Arc<Window>>,
+    power_preference: wgpu::PowerPreference,
+    flags: wgpu::InstanceFlags,
+    adapter_backend: &mut Option<wgpu::Backend>,
+) -> Result<AcquiredGpu, String> {
+    let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor {
+        backends: attempt.backends,
+        flags,
+        ..Default::default()
+    });
+    // Each instance needs its own surface
+    let surface = match window {
+        Some(window) => {
+            Some(instance.create_surface(window.clone()).map_err(|e| format!("Failed to create surface: {}", e))?)
+        }
+        None => None,
+    };
+    let adapter = instance
+        .request_adapter(&wgpu::RequestAdapterOptions {
+            power_preference,
+            compatible_surface: surface.as_ref(),
+            force_fallback_adapter: attempt.force_fallback_adapter,
+        })
+        .await
+        .map_err(|e| format!("No adapter: {}", e))?;
+    let info = adapter.get_info();
+    adapter_backend.get_or_insert(info.backend);
+    if let Some(surface) = &surface {
+        // An empty list would leave nothing to configure the surface with
+        let caps = surface.get_capabilities(&adapter);
+        if caps.formats.is_empty() || caps.alpha_modes.is_empty() {
+            return Err(format!("{} ({:?}) can't present to this window", info.name, info.backend));
+        }
+    }
+
+    let (required_features, limits) = if attempt.full {
+        let adapter_limits = adapter.limits();
+        let limits = wgpu::Limits {
+            // Zero unless asked for, which would leave bindless unusable
+            max_binding_array_elements_per_shader_stage: adapter_limits.max_binding_array_elements_per_shader_stage,
+            max_binding_array_sampler_elements_per_shader_stage: adapter_limits
+                .max_binding_array_sampler_elements_per_shader_stage,
+            ..wgpu::Limits::downlevel_defaults()
+        };
+        (optional_features(&adapter), limits)
+    } else {
+        (wgpu::Features::empty(), wgpu::Limits::downlevel_webgl2_defaults())
+    };
+    let (device, queue) = adapter
+        .request_device(&wgpu::DeviceDescriptor {
+            label: Some("vellum"),
+            required_features,
+            // Resolution limits still follow the adapter, so large textures keep working
+            required_limits: limits.using_resolution(adapter.limits()),
+            memory_hints: wgpu::MemoryHints::default(),
+            experimental_features: wgpu::ExperimentalFeatures::default(),
+            trace: wgpu::Trace::Off,
+        })
+        .await
+        .map_err(|e| format!("{} ({:?}): failed to request device: {}", info.name, info.backend, e))?;
+
+    Ok(AcquiredGpu {
+        adapter,
+        device,
+        queue,
+        surface,
+        selection: GpuSelection { tier: attempt.tier, adapter: info, failures: Vec::new() },
+    })
+}
+
+// Walks the fallback chain until a device comes up: the preferred adapter with full then
+// WebGL2 limits, every other backend, then a CPU rasterizer. Errors only when all of them fail.
+// `flags` turn on backend validation and debug labels, see `GpuValidation::instance_flags`.
+pub(crate) async fn acquire_gpu(
+    window: Option<&Arc<Window>>,
+    power_preference: wgpu::PowerPreference,
+    flags: wgpu::InstanceFlags,
+) -> Result<AcquiredGpu, String> {
+    let primary = |tier, full| Attempt { tier, backends: wgpu::Backends::all(), force_fallback_adapter: false, full };
+    let mut attempts = vec![primary(GpuTier::Full, true), primary(GpuTier::Downlevel, false)];
+    let mut failures = Vec::new();
+    // The backend the preferred adapter is on, so it isn't retried as an alternate
+    let mut preferred_backend = None;
+
+    let mut next = 0;
+    while let Some(&attempt) = attempts.get(next) {
+        match try_attempt(attempt, window, power_preference, flags, &mut preferred_backend).await {
+            Ok(mut acquired) => {
+                let info = &acquired.selection.adapter;
+                log::info!("Using adapter: {} ({:?}), GPU tier: {}", info.name, info.backend, attempt.tier);
+                acquired.selection.failures = failures;
+                return Ok(acquired);
+            }
+            Err(e) => {
+                log::warn!("GPU tier {} failed: {}", attempt.tier, e);
+                failures.push(format!("{}: {}", attempt.tier, e));
+            }
+        }
+        next += 1;
+        if next == 2 {
+            attempts.extend(ALTERNATE_BACKENDS.into_iter().filter(|backend| Some(*backend) != preferred_backend).map(
+                |backend| Attempt {
+                    tier: GpuTier::AlternateBackend(backend),
+                    backends: wgpu::Backends::from(backend),
+                    force_fallback_adapter: false,
+                    full: false,
+                },
+            ));
+            attempts.push(Attempt {
+                tier: GpuTier::Software,
+                backends: wgpu::Backends::all(),
+                force_fallback_adapter: true,
+                full: false,
+            });
+        }
+    }
+    Err(format!("No usable GPU device:\n  {}", failures.join("\n  ")))
+}
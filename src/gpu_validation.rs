@@ -0,0 +1,82 @@
+// src/gpu_validation.rs
+
+// Environment variable that turns validation on without a code change, e.g. for bug reports
+pub const VALIDATION_ENV: &str = "VELLUM_GPU_VALIDATION";
+// Oldest errors are dropped past this, so a per-frame error can't grow the list forever
+const MAX_RECORDED_ERRORS: usize = 64;
+
+// Developer mode for tracking down wgpu validation errors. When enabled the device is
+// created with backend validation layers and debug labels, and the renderer brackets its
+// uploads and pass encoding in error scopes, so an error is logged with the scope it came
+// from (wgpu's message names the labelled resources involved) instead of reaching the
+// uncaptured error handler. Off by default: popping a scope waits on the device.
+#[derive(Debug, Clone, Default)]
+pub struct GpuValidation {
+    enabled: bool,
+    // "scope: message", oldest first
+    errors: Vec<String>,
+    // Scopes pushed and not yet popped
+    open: u32,
+}
+
+impl GpuValidation {
+    pub fn new(enabled: bool) -> Self {
+        Self { enabled, ..Self::default() }
+    }
+
+    // Enabled when `VELLUM_GPU_VALIDATION` is set to anything but `0`
+    pub fn from_env() -> Self {
+        Self::new(std::env::var(VALIDATION_ENV).is_ok_and(|value| value != "0"))
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    // Takes effect for scopes from the next frame. Backend validation layers only follow
+    // the setting at device creation.
+    pub fn set_enabled(&mut self, enabled: bool) {
+        if self.open == 0 {
+            self.enabled = enabled;
+        }
+    }
+
+    // Instance flags for the device; wgpu's `WGPU_VALIDATION` and `WGPU_DEBUG` still apply
+    pub fn instance_flags(&self) -> wgpu::InstanceFlags {
+        let flags = if self.enabled { wgpu::InstanceFlags::debugging() } else { wgpu::InstanceFlags::from_build_config() };
+        flags.with_env()
+    }
+
+    // Starts a scope; every `push` needs a matching `pop`
+    pub fn push(&mut self, device: &wgpu::Device) {
+        if self.enabled {
+            device.push_error_scope(wgpu::ErrorFilter::Validation);
+            self.open += 1;
+        }
+    }
+
+    // Ends the innermost scope, logging what it caught under `scope`. Returns whether it
+    // caught an error.
+    pub fn pop(&mut self, device: &wgpu::Device, scope: &str) -> bool {
+        if self.open == 0 {
+            return false;
+        }
+        self.open -= 1;
+        let Some(error) = pollster::block_on(device.pop_error_scope()) else { return false };
+        log::error!("GPU validation error in {}: {}", scope, error);
+        if self.errors.len() == MAX_RECORDED_ERRORS {
+            self.errors.remove(0);
+        }
+        self.errors.push(format!("{}: {}", scope, error));
+        true
+    }
+
+    // Errors caught so far, for the debug overlay or a bug report
+    pub fn errors(&self) -> &[String] {
+        &self.errors
+    }
+
+    pub fn clear_errors(&mut self) {
+        self.errors.clear();
+    }
+}
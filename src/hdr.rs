@@ -0,0 +1,305 @@
+// src/hdr.rs
+use crate::bind_cache::{BindGroupCache, BindingKey};
+use crate::resource_registry::{ResourceRegistry, Tracked};
+use crate::upload::UploadBelt;
+
+pub const HDR_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba16Float;
+const HISTOGRAM_BINS: u64 = 256;
+const HISTOGRAM_TILE: u32 = 16;
+
+// Mirrors `HdrParams` in hdr_histogram.wgsl and tonemap.wgsl
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct HdrParams {
+    min_log_luminance: f32,
+    inv_log_luminance_range: f32,
+    log_luminance_range: f32,
+    delta_time: f32,
+    adaptation_rate: f32,
+    exposure_compensation: f32,
+    tonemapper: u32,
+    encode_srgb: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tonemapper {
+    Reinhard,
+    Aces,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ExposureSettings {
+    // Luminance range the histogram covers, in stops (log2)
+    pub min_log_luminance: f32,
+    pub max_log_luminance: f32,
+    // Higher adapts faster; roughly the inverse of the adaptation time in seconds
+    pub adaptation_rate: f32,
+    // Manual bias on top of the automatic exposure, in stops
+    pub exposure_compensation: f32,
+}
+
+impl Default for ExposureSettings {
+    fn default() -> Self {
+        Self {
+            min_log_luminance: -8.0,
+            max_log_luminance: 4.0,
+            adaptation_rate: 1.5,
+            exposure_compensation: 0.0,
+        }
+    }
+}
+
+// Scene renders into an Rgba16Float target; `render` measures its luminance on the GPU,
+// adapts exposure over time and tonemaps it into the swapchain format.
+pub struct HdrPipeline {
+    pub tonemapper: Tonemapper,
+    pub exposure: ExposureSettings,
+    size: (u32, u32),
+    target: Tracked<wgpu::Texture>,
+    target_view: wgpu::TextureView,
+    encode_srgb: bool,
+    params_buffer: Tracked<wgpu::Buffer>,
+    histogram: Tracked<wgpu::Buffer>,
+    adapted_luminance: Tracked<wgpu::Buffer>,
+    histogram_layout: wgpu::BindGroupLayout,
+    tonemap_layout: wgpu::BindGroupLayout,
+    histogram_pipeline: wgpu::ComputePipeline,
+    average_pipeline: wgpu::ComputePipeline,
+    tonemap_pipeline: wgpu::RenderPipeline,
+}
+
+impl HdrPipeline {
+    pub fn new(
+        device: &wgpu::Device,
+        resources: &ResourceRegistry,
+        output_format: wgpu::TextureFormat,
+        width: u32,
+        height: u32,
+    ) -> Self {
+        let params_buffer = resources.create_buffer(device, &wgpu::BufferDescriptor {
+            label: Some("hdr params"),
+            size: std::mem::size_of::<HdrParams>() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        }, "hdr");
+        let histogram = resources.create_buffer(device, &wgpu::BufferDescriptor {
+            label: Some("luminance histogram"),
+            size: HISTOGRAM_BINS * 4,
+            usage: wgpu::BufferUsages::STORAGE,
+            mapped_at_creation: false,
+        }, "hdr");
+        // Starts at middle grey so the first frames aren't wildly over- or underexposed
+        let adapted_luminance = resources.create_buffer_init(device, &wgpu::util::BufferInitDescriptor {
+            label: Some("adapted luminance"),
+            contents: bytemuck::bytes_of(&0.18f32),
+            usage: wgpu::BufferUsages::STORAGE,
+        }, "hdr");
+
+        let entry = |binding, visibility, ty| wgpu::BindGroupLayoutEntry { binding, visibility, ty, count: None };
+        let uniform = wgpu::BindingType::Buffer {
+            ty: wgpu::BufferBindingType::Uniform,
+            has_dynamic_offset: false,
+            min_binding_size: None,
+        };
+        let hdr_texture = wgpu::BindingType::Texture {
+            sample_type: wgpu::TextureSampleType::Float { filterable: false },
+            view_dimension: wgpu::TextureViewDimension::D2,
+            multisampled: false,
+        };
+        let storage = |read_only| wgpu::BindingType::Buffer {
+            ty: wgpu::BufferBindingType::Storage { read_only },
+            has_dynamic_offset: false,
+            min_binding_size: None,
+        };
+        let compute = wgpu::ShaderStages::COMPUTE;
+        let fragment = wgpu::ShaderStages::FRAGMENT;
+        let histogram_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("luminance histogram"),
+            entries: &[
+                entry(0, compute, uniform),
+                entry(1, compute, hdr_texture),
+                entry(2, compute, storage(false)),
+                entry(3, compute, storage(false)),
+            ],
+        });
+        let tonemap_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("tonemap"),
+            entries: &[
+                entry(0, fragment, uniform),
+                entry(1, fragment, hdr_texture),
+                entry(2, fragment, storage(true)),
+            ],
+        });
+
+        let histogram_shader = device.create_shader_module(wgpu::include_wgsl!("hdr_histogram.wgsl"));
+        let histogram_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("luminance histogram"),
+            bind_group_layouts: &[&histogram_layout],
+            push_constant_ranges: &[],
+        });
+        let compute_pipeline = |label, entry_point| device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some(label),
+            layout: Some(&histogram_pipeline_layout),
+            module: &histogram_shader,
+            entry_point: Some(entry_point),
+            compilation_options: Default::default(),
+            cache: None,
+        });
+        let histogram_pipeline = compute_pipeline("luminance histogram", "build_histogram");
+        let average_pipeline = compute_pipeline("average luminance", "average_luminance");
+
+        let tonemap_shader = device.create_shader_module(wgpu::include_wgsl!("tonemap.wgsl"));
+        let tonemap_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("tonemap"),
+            bind_group_layouts: &[&tonemap_layout],
+            push_constant_ranges: &[],
+        });
+        let tonemap_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("tonemap"),
+            layout: Some(&tonemap_pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &tonemap_shader,
+                entry_point: Some("vs_fullscreen"),
+                buffers: &[],
+                compilation_options: Default::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &tonemap_shader,
+                entry_point: Some("fs_tonemap"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: output_format,
+                    blend: None,
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: Default::default(),
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        });
+
+        let size = (width.max(1), height.max(1));
+        let (target, target_view) = Self::create_target(device, resources, size);
+        Self {
+            tonemapper: Tonemapper::Aces,
+            exposure: ExposureSettings::default(),
+            size,
+            target,
+            target_view,
+            // sRGB swapchains encode on write; anything else needs it done in the shader
+            encode_srgb: !output_format.is_srgb(),
+            params_buffer,
+            histogram,
+            adapted_luminance,
+            histogram_layout,
+            tonemap_layout,
+            histogram_pipeline,
+            average_pipeline,
+            tonemap_pipeline,
+        }
+    }
+
+    fn create_target(device: &wgpu::Device, resources: &ResourceRegistry, size: (u32, u32)) -> (Tracked<wgpu::Texture>, wgpu::TextureView) {
+        let texture = resources.create_texture(device, &wgpu::TextureDescriptor {
+            label: Some("hdr target"),
+            size: wgpu::Extent3d { width: size.0, height: size.1, depth_or_array_layers: 1 },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: HDR_FORMAT,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        }, "hdr");
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        (texture, view)
+    }
+
+    pub fn resize(&mut self, device: &wgpu::Device, resources: &ResourceRegistry, width: u32, height: u32) {
+        let size = (width.max(1), height.max(1));
+        if size == self.size {
+            return;
+        }
+        self.size = size;
+        (self.target, self.target_view) = Self::create_target(device, resources, size);
+    }
+
+    // Render the scene into this instead of the swapchain
+    pub fn target_view(&self) -> &wgpu::TextureView {
+        &self.target_view
+    }
+
+    pub fn target_texture(&self) -> &wgpu::Texture {
+        &self.target
+    }
+
+    // Measures the HDR target, adapts exposure and tonemaps into `output`
+    pub fn render(
+        &self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        belt: &mut UploadBelt,
+        bind_groups: &mut BindGroupCache,
+        delta_time: f32,
+        output: &wgpu::TextureView,
+    ) {
+        let log_range = (self.exposure.max_log_luminance - self.exposure.min_log_luminance).max(f32::EPSILON);
+        let params = HdrParams {
+            min_log_luminance: self.exposure.min_log_luminance,
+            inv_log_luminance_range: 1.0 / log_range,
+            log_luminance_range: log_range,
+            delta_time,
+            adaptation_rate: self.exposure.adaptation_rate,
+            exposure_compensation: self.exposure.exposure_compensation,
+            tonemapper: match self.tonemapper {
+                Tonemapper::Reinhard => 0,
+                Tonemapper::Aces => 1,
+            },
+            encode_srgb: self.encode_srgb as u32,
+        };
+        belt.write(device, encoder, &self.params_buffer, 0, bytemuck::bytes_of(&params));
+
+        let histogram_bind_group = bind_groups.get_or_create(device, "luminance histogram", &self.histogram_layout, &[
+            (0, BindingKey::buffer(&self.params_buffer)),
+            (1, BindingKey::TextureView(self.target_view.clone())),
+            (2, BindingKey::buffer(&self.histogram)),
+            (3, BindingKey::buffer(&self.adapted_luminance)),
+        ]);
+        {
+            let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                label: Some("auto exposure"),
+                timestamp_writes: None,
+            });
+            pass.set_bind_group(0, &histogram_bind_group, &[]);
+            pass.set_pipeline(&self.histogram_pipeline);
+            pass.dispatch_workgroups(self.size.0.div_ceil(HISTOGRAM_TILE), self.size.1.div_ceil(HISTOGRAM_TILE), 1);
+            pass.set_pipeline(&self.average_pipeline);
+            pass.dispatch_workgroups(1, 1, 1);
+        }
+
+        let tonemap_bind_group = bind_groups.get_or_create(device, "tonemap", &self.tonemap_layout, &[
+            (0, BindingKey::buffer(&self.params_buffer)),
+            (1, BindingKey::TextureView(self.target_view.clone())),
+            (2, BindingKey::buffer(&self.adapted_luminance)),
+        ]);
+        let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("tonemap"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: output,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                    store: wgpu::StoreOp::Store,
+                },
+                depth_slice: None,
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        pass.set_pipeline(&self.tonemap_pipeline);
+        pass.set_bind_group(0, &tonemap_bind_group, &[]);
+        pass.draw(0..3, 0..1);
+    }
+}
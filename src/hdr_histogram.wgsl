@@ -0,0 +1,76 @@
+// Auto-exposure: bins the HDR frame's log luminance into a 256-bucket histogram, then
+// one workgroup averages it and eases the adapted luminance towards the result
+
+struct HdrParams {
+    min_log_luminance: f32,
+    inv_log_luminance_range: f32,
+    log_luminance_range: f32,
+    delta_time: f32,
+    adaptation_rate: f32,
+    exposure_compensation: f32,
+    tonemapper: u32,
+    encode_srgb: u32,
+}
+
+struct Exposure {
+    luminance: f32,
+}
+
+@group(0) @binding(0) var<uniform> params: HdrParams;
+@group(0) @binding(1) var hdr_texture: texture_2d<f32>;
+@group(0) @binding(2) var<storage, read_write> histogram: array<atomic<u32>, 256>;
+@group(0) @binding(3) var<storage, read_write> exposure: Exposure;
+
+var<workgroup> local_bins: array<atomic<u32>, 256>;
+var<workgroup> weighted: array<f32, 256>;
+
+// Bin 0 holds near-black pixels, which are left out of the average
+fn luminance_bin(color: vec3<f32>) -> u32 {
+    let luminance = dot(color, vec3<f32>(0.2126, 0.7152, 0.0722));
+    if (luminance < 1e-4) {
+        return 0u;
+    }
+    let t = clamp((log2(luminance) - params.min_log_luminance) * params.inv_log_luminance_range, 0.0, 1.0);
+    return u32(t * 254.0 + 1.0);
+}
+
+@compute @workgroup_size(16, 16)
+fn build_histogram(
+    @builtin(global_invocation_id) id: vec3<u32>,
+    @builtin(local_invocation_index) local_index: u32,
+) {
+    atomicStore(&local_bins[local_index], 0u);
+    workgroupBarrier();
+    let size = textureDimensions(hdr_texture);
+    if (id.x < size.x && id.y < size.y) {
+        let color = textureLoad(hdr_texture, id.xy, 0).rgb;
+        atomicAdd(&local_bins[luminance_bin(color)], 1u);
+    }
+    workgroupBarrier();
+    atomicAdd(&histogram[local_index], atomicLoad(&local_bins[local_index]));
+}
+
+@compute @workgroup_size(256)
+fn average_luminance(@builtin(local_invocation_index) local_index: u32) {
+    let count = atomicLoad(&histogram[local_index]);
+    weighted[local_index] = f32(count) * f32(local_index);
+    // Cleared here so the next frame starts from an empty histogram
+    atomicStore(&histogram[local_index], 0u);
+    workgroupBarrier();
+
+    for (var stride = 128u; stride > 0u; stride >>= 1u) {
+        if (local_index < stride) {
+            weighted[local_index] += weighted[local_index + stride];
+        }
+        workgroupBarrier();
+    }
+
+    if (local_index == 0u) {
+        let size = textureDimensions(hdr_texture);
+        let lit_pixels = max(f32(size.x * size.y) - f32(count), 1.0);
+        let average_bin = weighted[0] / lit_pixels - 1.0;
+        let target_luminance = exp2(average_bin / 254.0 * params.log_luminance_range + params.min_log_luminance);
+        let blend = 1.0 - exp(-params.delta_time * params.adaptation_rate);
+        exposure.luminance = exposure.luminance + (target_luminance - exposure.luminance) * blend;
+    }
+}
@@ -0,0 +1,235 @@
+// src/hot_reload.rs
+use crate::scene::Scene;
+use std::ffi::c_void;
+
+// Bumped whenever `GameApi` changes shape; libraries built against another version are refused
+pub const GAME_ABI_VERSION: u32 = 1;
+// Symbol `export_game!` defines in the gameplay library
+pub const GAME_API_SYMBOL: &[u8] = b"vellum_game_api";
+
+// Gameplay code that can be swapped out while the engine runs. The state crosses reloads
+// only through `save`/`load`, so the struct's layout is free to change between builds.
+pub trait HotGame: Default {
+    fn update(&mut self, scene: &mut Scene, delta_time: f64);
+
+    // Whatever `load` needs to pick up where this instance left off, e.g. RON
+    fn save(&self) -> Vec<u8> {
+        Vec::new()
+    }
+
+    // Called on a fresh instance after a reload; bytes from an older build may not parse
+    fn load(&mut self, _bytes: &[u8]) {}
+}
+
+// Receives `save` output in engine-owned memory, so nothing is freed by the wrong allocator
+pub type SaveSink = extern "C" fn(sink: *mut c_void, bytes: *const u8, len: usize);
+
+// Function table the gameplay library hands the engine. `Scene` crosses the boundary by
+// pointer, so the library must be built against the same engine source and compiler.
+#[repr(C)]
+pub struct GameApi {
+    pub abi_version: u32,
+    pub create: extern "C" fn() -> *mut c_void,
+    pub destroy: extern "C" fn(game: *mut c_void),
+    // False if the game panicked
+    pub update: extern "C" fn(game: *mut c_void, scene: *mut Scene, delta_time: f64) -> bool,
+    pub save: extern "C" fn(game: *const c_void, sink: *mut c_void, write: SaveSink),
+    pub load: extern "C" fn(game: *mut c_void, bytes: *const u8, len: usize),
+}
+
+// Exports a `HotGame` from a `crate-type = ["cdylib"]` gameplay crate
+#[macro_export]
+macro_rules! export_game {
+    ($game:ty) => {
+        #[no_mangle]
+        pub extern "C" fn vellum_game_api() -> $crate::hot_reload::GameApi {
+            use std::ffi::c_void;
+            use $crate::hot_reload::{HotGame, SaveSink, GAME_ABI_VERSION};
+
+            extern "C" fn create() -> *mut c_void {
+                Box::into_raw(Box::new(<$game>::default())) as *mut c_void
+            }
+            extern "C" fn destroy(game: *mut c_void) {
+                drop(unsafe { Box::from_raw(game as *mut $game) });
+            }
+            extern "C" fn update(game: *mut c_void, scene: *mut $crate::scene::Scene, delta_time: f64) -> bool {
+                let (game, scene) = unsafe { (&mut *(game as *mut $game), &mut *scene) };
+                std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| game.update(scene, delta_time))).is_ok()
+            }
+            extern "C" fn save(game: *const c_void, sink: *mut c_void, write: SaveSink) {
+                let bytes = unsafe { &*(game as *const $game) }.save();
+                write(sink, bytes.as_ptr(), bytes.len());
+            }
+            extern "C" fn load(game: *mut c_void, bytes: *const u8, len: usize) {
+                let bytes = if len == 0 { &[][..] } else { unsafe { std::slice::from_raw_parts(bytes, len) } };
+                unsafe { &mut *(game as *mut $game) }.load(bytes);
+            }
+            $crate::hot_reload::GameApi { abi_version: GAME_ABI_VERSION, create, destroy, update, save, load }
+        }
+    };
+}
+
+#[cfg(feature = "hot-reload")]
+pub use reloader::HotReloader;
+
+#[cfg(feature = "hot-reload")]
+mod reloader {
+    use super::{GameApi, GAME_ABI_VERSION, GAME_API_SYMBOL};
+    use crate::scene::Scene;
+    use std::ffi::c_void;
+    use std::path::{Path, PathBuf};
+    use std::time::{Duration, Instant, SystemTime};
+
+    // How often the library's timestamp is checked
+    const POLL_INTERVAL: Duration = Duration::from_millis(250);
+    // The linker writes the library in several steps; wait for it to settle
+    const SETTLE_TIME: Duration = Duration::from_millis(300);
+
+    extern "C" fn collect_bytes(sink: *mut c_void, bytes: *const u8, len: usize) {
+        let out = unsafe { &mut *(sink as *mut Vec<u8>) };
+        if len > 0 {
+            out.extend_from_slice(unsafe { std::slice::from_raw_parts(bytes, len) });
+        }
+    }
+
+    struct LoadedGame {
+        api: GameApi,
+        instance: *mut c_void,
+        // Dropped last; `api` points into it
+        _library: libloading::Library,
+        shadow_path: PathBuf,
+    }
+
+    impl Drop for LoadedGame {
+        fn drop(&mut self) {
+            (self.api.destroy)(self.instance);
+            let _ = std::fs::remove_file(&self.shadow_path);
+        }
+    }
+
+    // Loads gameplay code exported with `export_game!` and swaps in a new build whenever
+    // the library file changes. Each build is loaded from a copy so the original stays
+    // writable for the linker. A failed reload keeps the previous build running.
+    pub struct HotReloader {
+        path: PathBuf,
+        game: Option<LoadedGame>,
+        loaded_modified: Option<SystemTime>,
+        last_poll: Instant,
+        generation: u32,
+        last_error: Option<String>,
+    }
+
+    impl HotReloader {
+        pub fn new(path: impl Into<PathBuf>) -> Result<Self, String> {
+            let mut reloader = Self {
+                path: path.into(),
+                game: None,
+                loaded_modified: None,
+                last_poll: Instant::now(),
+                generation: 0,
+                last_error: None,
+            };
+            reloader.reload()?;
+            Ok(reloader)
+        }
+
+        pub fn path(&self) -> &Path {
+            &self.path
+        }
+
+        // Builds loaded so far, including the first
+        pub fn generation(&self) -> u32 {
+            self.generation
+        }
+
+        // Why the last reload or update failed, if it did
+        pub fn last_error(&self) -> Option<&str> {
+            self.last_error.as_deref()
+        }
+
+        fn modified(&self) -> Option<SystemTime> {
+            std::fs::metadata(&self.path).and_then(|m| m.modified()).ok()
+        }
+
+        // Reloads if the library was rebuilt; cheap to call every frame. True on reload.
+        pub fn poll(&mut self) -> bool {
+            if self.last_poll.elapsed() < POLL_INTERVAL {
+                return false;
+            }
+            self.last_poll = Instant::now();
+            let Some(modified) = self.modified() else { return false };
+            if Some(modified) == self.loaded_modified || modified.elapsed().is_ok_and(|age| age < SETTLE_TIME) {
+                return false;
+            }
+            match self.reload() {
+                Ok(()) => true,
+                Err(e) => {
+                    log::error!("Hot reload failed, keeping the previous build: {}", e);
+                    // Don't retry the same broken build every poll
+                    self.loaded_modified = Some(modified);
+                    self.last_error = Some(e);
+                    false
+                }
+            }
+        }
+
+        // Loads the library now, handing the running game's saved state to the new build
+        pub fn reload(&mut self) -> Result<(), String> {
+            let modified = self.modified();
+            let shadow_path = self.shadow_path();
+            std::fs::copy(&self.path, &shadow_path)
+                .map_err(|e| format!("Failed to copy {}: {}", self.path.display(), e))?;
+            let loaded = match Self::load_library(&shadow_path) {
+                Ok(loaded) => loaded,
+                Err(e) => {
+                    let _ = std::fs::remove_file(&shadow_path);
+                    return Err(e);
+                }
+            };
+
+            let mut state = Vec::new();
+            if let Some(old) = &self.game {
+                (old.api.save)(old.instance, &mut state as *mut Vec<u8> as *mut c_void, collect_bytes);
+            }
+            let instance = (loaded.1.create)();
+            (loaded.1.load)(instance, state.as_ptr(), state.len());
+            // The old build is unloaded only after its state has been handed over
+            self.game = Some(LoadedGame { api: loaded.1, instance, _library: loaded.0, shadow_path });
+            self.loaded_modified = modified;
+            self.generation += 1;
+            self.last_error = None;
+            log::info!("Loaded gameplay build {} from {} ({} bytes of state)", self.generation, self.path.display(), state.len());
+            Ok(())
+        }
+
+        fn shadow_path(&self) -> PathBuf {
+            let name = self.path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+            std::env::temp_dir().join(format!("vellum-{}-{}-{}", std::process::id(), self.generation, name))
+        }
+
+        fn load_library(path: &Path) -> Result<(libloading::Library, GameApi), String> {
+            // Loading runs the library's initializers; it must be gameplay code built for this engine
+            let library = unsafe { libloading::Library::new(path) }
+                .map_err(|e| format!("Failed to load {}: {}", path.display(), e))?;
+            let api = unsafe {
+                let entry = library
+                    .get::<extern "C" fn() -> GameApi>(GAME_API_SYMBOL)
+                    .map_err(|e| format!("{} has no game API (missing export_game!?): {}", path.display(), e))?;
+                entry()
+            };
+            if api.abi_version != GAME_ABI_VERSION {
+                return Err(format!("{} uses game ABI {}, expected {}", path.display(), api.abi_version, GAME_ABI_VERSION));
+            }
+            Ok((library, api))
+        }
+
+        // Runs one update of the current build. A panic is reported and the build keeps
+        // running, so the next reload can fix it.
+        pub fn update(&mut self, scene: &mut Scene, delta_time: f64) {
+            let Some(game) = &self.game else { return };
+            if !(game.api.update)(game.instance, scene, delta_time) {
+                self.last_error = Some(format!("Gameplay build {} panicked in update", self.generation));
+            }
+        }
+    }
+}
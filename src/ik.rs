@@ -0,0 +1,139 @@
+// src/ik.rs
+use glam::Vec2;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TwoBoneSolution {
+    pub joint: Vec2,
+    pub end: Vec2,
+    // World-space angle of the upper bone, and the lower bone's angle relative to it
+    pub upper_angle: f32,
+    pub lower_angle: f32,
+    // False if the target was out of reach and the limb is fully stretched towards it
+    pub reached: bool,
+}
+
+// Analytic two-bone solve (arms, legs). `bend_positive` picks which side the joint
+// bends to: counter-clockwise of the root->target line when true.
+pub fn solve_two_bone(root: Vec2, upper_length: f32, lower_length: f32, target: Vec2, bend_positive: bool) -> TwoBoneSolution {
+    let to_target = target - root;
+    let max_reach = upper_length + lower_length;
+    let min_reach = (upper_length - lower_length).abs();
+    let distance = to_target.length().clamp(min_reach.max(f32::EPSILON), max_reach);
+    let base_angle = to_target.y.atan2(to_target.x);
+
+    // Law of cosines for the angle at the root between the target line and the upper bone
+    let cos_root = ((upper_length * upper_length + distance * distance - lower_length * lower_length)
+        / (2.0 * upper_length * distance))
+        .clamp(-1.0, 1.0);
+    let cos_joint = ((upper_length * upper_length + lower_length * lower_length - distance * distance)
+        / (2.0 * upper_length * lower_length))
+        .clamp(-1.0, 1.0);
+    let sign = if bend_positive { 1.0 } else { -1.0 };
+
+    let upper_angle = base_angle + sign * cos_root.acos();
+    let lower_angle = -sign * (std::f32::consts::PI - cos_joint.acos());
+    let joint = root + Vec2::from_angle(upper_angle) * upper_length;
+    let end = joint + Vec2::from_angle(upper_angle + lower_angle) * lower_length;
+    TwoBoneSolution {
+        joint,
+        end,
+        upper_angle,
+        lower_angle,
+        reached: to_target.length() <= max_reach && to_target.length() >= min_reach,
+    }
+}
+
+// Chain of joints solved with FABRIK; works for long limbs, tails and ropes
+#[derive(Debug, Clone)]
+pub struct IkChain {
+    pub joints: Vec<Vec2>,
+    lengths: Vec<f32>,
+    pub iterations: u32,
+    pub tolerance: f32,
+    // Ropes usually hang from a fixed anchor; set false to let the root drift too
+    pub pinned_root: bool,
+}
+
+impl IkChain {
+    // Bone lengths are taken from the initial joint positions
+    pub fn new(joints: Vec<Vec2>) -> Self {
+        let lengths = joints.windows(2).map(|w| w[0].distance(w[1])).collect();
+        Self {
+            joints,
+            lengths,
+            iterations: 10,
+            tolerance: 0.01,
+            pinned_root: true,
+        }
+    }
+
+    // Straight chain of `segments` bones, e.g. for a rope hanging from `anchor`
+    pub fn rope(anchor: Vec2, direction: Vec2, segments: usize, segment_length: f32) -> Self {
+        let direction = direction.normalize_or(Vec2::NEG_Y);
+        Self::new((0..=segments).map(|i| anchor + direction * segment_length * i as f32).collect())
+    }
+
+    pub fn total_length(&self) -> f32 {
+        self.lengths.iter().sum()
+    }
+
+    pub fn end(&self) -> Vec2 {
+        self.joints.last().copied().unwrap_or_default()
+    }
+
+    // Returns true if the end effector got within `tolerance` of the target
+    pub fn solve(&mut self, target: Vec2) -> bool {
+        let n = self.joints.len();
+        if n < 2 {
+            return false;
+        }
+        let root = self.joints[0];
+
+        if self.pinned_root && root.distance(target) > self.total_length() {
+            // Unreachable: stretch straight towards the target
+            let direction = (target - root).normalize_or_zero();
+            for i in 1..n {
+                self.joints[i] = self.joints[i - 1] + direction * self.lengths[i - 1];
+            }
+            return false;
+        }
+
+        for _ in 0..self.iterations {
+            if self.end().distance(target) <= self.tolerance {
+                return true;
+            }
+            // Backward pass: pin the end to the target and walk towards the root
+            self.joints[n - 1] = target;
+            for i in (0..n - 1).rev() {
+                let direction = (self.joints[i] - self.joints[i + 1]).normalize_or_zero();
+                self.joints[i] = self.joints[i + 1] + direction * self.lengths[i];
+            }
+            if !self.pinned_root {
+                continue;
+            }
+            // Forward pass: pin the root back in place and walk out again
+            self.joints[0] = root;
+            for i in 1..n {
+                let direction = (self.joints[i] - self.joints[i - 1]).normalize_or_zero();
+                self.joints[i] = self.joints[i - 1] + direction * self.lengths[i - 1];
+            }
+        }
+        self.end().distance(target) <= self.tolerance
+    }
+
+    // Bone rotations relative to their parent bone (the first one is world-space),
+    // ready to be written back into a transform hierarchy
+    pub fn local_angles(&self) -> Vec<f32> {
+        let mut parent_angle = 0.0;
+        self.joints
+            .windows(2)
+            .map(|w| {
+                let bone = w[1] - w[0];
+                let angle = bone.y.atan2(bone.x);
+                let local = angle - parent_angle;
+                parent_angle = angle;
+                local
+            })
+            .collect()
+    }
+}
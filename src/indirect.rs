@@ -0,0 +1,283 @@
+// src/indirect.rs
+use crate::bind_cache::{BindGroupCache, BindingKey};
+use crate::mesh::GpuMesh;
+use crate::resource_registry::ResourceRegistry;
+use crate::upload::{DynamicBuffer, UploadBelt};
+use glam::Vec2;
+
+// Mirrors `wgpu::util::DrawIndexedIndirectArgs` and `DrawArgs` in indirect_cull.wgsl.
+// A non-zero `first_instance` needs `Features::INDIRECT_FIRST_INSTANCE`.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct DrawIndexedArgs {
+    pub index_count: u32,
+    pub instance_count: u32,
+    pub first_index: u32,
+    pub base_vertex: i32,
+    pub first_instance: u32,
+}
+
+impl DrawIndexedArgs {
+    pub fn mesh(mesh: &GpuMesh) -> Self {
+        Self { index_count: mesh.index_count, instance_count: 1, ..Default::default() }
+    }
+
+    pub fn with_instances(mut self, first_instance: u32, instance_count: u32) -> Self {
+        self.first_instance = first_instance;
+        self.instance_count = instance_count;
+        self
+    }
+}
+
+const ARGS_SIZE: u64 = std::mem::size_of::<DrawIndexedArgs>() as u64;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IndirectMode {
+    // Whole list in one `multi_draw_indexed_indirect` call
+    MultiDraw,
+    // No indirect execution (e.g. WebGL2): CPU-side args drawn one by one, unculled
+    Direct,
+}
+
+impl IndirectMode {
+    pub fn detect(adapter: &wgpu::Adapter) -> Self {
+        let flags = adapter.get_downlevel_capabilities().flags;
+        if flags.contains(wgpu::DownlevelFlags::INDIRECT_EXECUTION) {
+            Self::MultiDraw
+        } else {
+            Self::Direct
+        }
+    }
+}
+
+// List of indexed draws over shared vertex/index buffers. Args are either built on the
+// CPU with `push` or written on the GPU (see `GpuCuller`); both end up in one buffer.
+pub struct IndirectDrawList {
+    args: Vec<DrawIndexedArgs>,
+    buffer: DynamicBuffer,
+    mode: IndirectMode,
+    dirty: bool,
+}
+
+impl IndirectDrawList {
+    pub fn new(device: &wgpu::Device, resources: &ResourceRegistry, label: &'static str, mode: IndirectMode) -> Self {
+        Self {
+            args: Vec::new(),
+            buffer: DynamicBuffer::new(
+                device,
+                resources,
+                label,
+                // COPY_SRC so GPU-built args can be read back for debugging
+                wgpu::BufferUsages::INDIRECT | wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC,
+                ARGS_SIZE * 64,
+            ),
+            mode,
+            dirty: false,
+        }
+    }
+
+    pub fn mode(&self) -> IndirectMode {
+        self.mode
+    }
+
+    pub fn len(&self) -> usize {
+        self.args.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.args.is_empty()
+    }
+
+    // Returns the draw index, which is also its slot in the args buffer
+    pub fn push(&mut self, args: DrawIndexedArgs) -> u32 {
+        self.args.push(args);
+        self.dirty = true;
+        self.args.len() as u32 - 1
+    }
+
+    pub fn set(&mut self, index: u32, args: DrawIndexedArgs) {
+        if let Some(slot) = self.args.get_mut(index as usize) {
+            *slot = args;
+            self.dirty = true;
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.args.clear();
+        self.dirty = true;
+    }
+
+    pub fn args_buffer(&self) -> &wgpu::Buffer {
+        self.buffer.buffer()
+    }
+
+    // Writes CPU-built args; skipped when nothing changed since the last upload
+    pub fn upload(
+        &mut self,
+        device: &wgpu::Device,
+        resources: &ResourceRegistry,
+        encoder: &mut wgpu::CommandEncoder,
+        belt: &mut UploadBelt,
+    ) {
+        if !self.dirty || self.args.is_empty() {
+            return;
+        }
+        self.buffer.upload(device, resources, encoder, belt, bytemuck::cast_slice(&self.args));
+        self.dirty = false;
+    }
+
+    // Expects the pipeline, bind groups, vertex and index buffers to be set already
+    pub fn draw(&self, render_pass: &mut wgpu::RenderPass<'_>) {
+        if self.args.is_empty() {
+            return;
+        }
+        match self.mode {
+            IndirectMode::MultiDraw => {
+                render_pass.multi_draw_indexed_indirect(self.buffer.buffer(), 0, self.args.len() as u32);
+            }
+            IndirectMode::Direct => {
+                for args in &self.args {
+                    let indices = args.first_index..args.first_index + args.index_count;
+                    let instances = args.first_instance..args.first_instance + args.instance_count;
+                    render_pass.draw_indexed(indices, args.base_vertex, instances);
+                }
+            }
+        }
+    }
+}
+
+// Mirrors `CullObject` in indirect_cull.wgsl
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct CullObject {
+    pub bounds_min: [f32; 2],
+    pub bounds_max: [f32; 2],
+    pub args: DrawIndexedArgs,
+    _pad: u32,
+}
+
+impl CullObject {
+    pub fn new(bounds_min: Vec2, bounds_max: Vec2, args: DrawIndexedArgs) -> Self {
+        Self { bounds_min: bounds_min.into(), bounds_max: bounds_max.into(), args, _pad: 0 }
+    }
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct CullParams {
+    view_min: [f32; 2],
+    view_max: [f32; 2],
+    object_count: u32,
+    _pad: [u32; 3],
+}
+
+const WORKGROUP_SIZE: u32 = 64;
+
+// Builds draw args on the GPU: objects whose bounds miss the view rect get an instance
+// count of zero, so culled draws cost nothing without a CPU round trip.
+pub struct GpuCuller {
+    objects: DynamicBuffer,
+    params: DynamicBuffer,
+    layout: wgpu::BindGroupLayout,
+    pipeline: wgpu::ComputePipeline,
+}
+
+impl GpuCuller {
+    pub fn new(device: &wgpu::Device, resources: &ResourceRegistry) -> Self {
+        let buffer_entry = |binding, ty| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::COMPUTE,
+            ty: wgpu::BindingType::Buffer { ty, has_dynamic_offset: false, min_binding_size: None },
+            count: None,
+        };
+        let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("indirect cull"),
+            entries: &[
+                buffer_entry(0, wgpu::BufferBindingType::Uniform),
+                buffer_entry(1, wgpu::BufferBindingType::Storage { read_only: true }),
+                buffer_entry(2, wgpu::BufferBindingType::Storage { read_only: false }),
+            ],
+        });
+        let shader = device.create_shader_module(wgpu::include_wgsl!("indirect_cull.wgsl"));
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("indirect cull"),
+            bind_group_layouts: &[&layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("indirect cull"),
+            layout: Some(&pipeline_layout),
+            module: &shader,
+            entry_point: Some("cull"),
+            compilation_options: Default::default(),
+            cache: None,
+        });
+
+        Self {
+            objects: DynamicBuffer::new(
+                device,
+                resources,
+                "cull objects",
+                wgpu::BufferUsages::STORAGE,
+                std::mem::size_of::<CullObject>() as u64 * 64,
+            ),
+            params: DynamicBuffer::new(
+                device,
+                resources,
+                "cull params",
+                wgpu::BufferUsages::UNIFORM,
+                std::mem::size_of::<CullParams>() as u64,
+            ),
+            layout,
+            pipeline,
+        }
+    }
+
+    // Replaces `out` with one draw per object, culled against the view rect on the GPU.
+    // In `IndirectMode::Direct` there is nothing to execute GPU args, so `out` keeps the
+    // unculled CPU copy and every object is drawn.
+    #[allow(clippy::too_many_arguments)]
+    pub fn cull(
+        &mut self,
+        device: &wgpu::Device,
+        resources: &ResourceRegistry,
+        encoder: &mut wgpu::CommandEncoder,
+        belt: &mut UploadBelt,
+        bind_groups: &mut BindGroupCache,
+        view_min: Vec2,
+        view_max: Vec2,
+        objects: &[CullObject],
+        out: &mut IndirectDrawList,
+    ) {
+        out.args.clear();
+        out.args.extend(objects.iter().map(|object| object.args));
+        out.dirty = true;
+        if objects.is_empty() || out.mode == IndirectMode::Direct {
+            return;
+        }
+        // Sizes the args buffer; the cull pass overwrites the contents right after
+        out.upload(device, resources, encoder, belt);
+
+        let params = CullParams {
+            view_min: view_min.into(),
+            view_max: view_max.into(),
+            object_count: objects.len() as u32,
+            _pad: [0; 3],
+        };
+        self.params.upload(device, resources, encoder, belt, bytemuck::bytes_of(&params));
+        self.objects.upload(device, resources, encoder, belt, bytemuck::cast_slice(objects));
+
+        let bind_group = bind_groups.get_or_create(device, "indirect cull", &self.layout, &[
+            (0, BindingKey::buffer(self.params.buffer())),
+            (1, BindingKey::buffer(self.objects.buffer())),
+            (2, BindingKey::buffer(out.args_buffer())),
+        ]);
+        let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+            label: Some("indirect cull"),
+            timestamp_writes: None,
+        });
+        pass.set_pipeline(&self.pipeline);
+        pass.set_bind_group(0, &bind_group, &[]);
+        pass.dispatch_workgroups((objects.len() as u32).div_ceil(WORKGROUP_SIZE), 1, 1);
+    }
+}
@@ -0,0 +1,39 @@
+// GPU culling: copies each object's draw args, zeroing the instance count when it misses the view
+struct DrawArgs {
+    index_count: u32,
+    instance_count: u32,
+    first_index: u32,
+    base_vertex: i32,
+    first_instance: u32,
+}
+
+struct CullObject {
+    bounds_min: vec2<f32>,
+    bounds_max: vec2<f32>,
+    args: DrawArgs,
+}
+
+struct CullParams {
+    view_min: vec2<f32>,
+    view_max: vec2<f32>,
+    object_count: u32,
+}
+
+@group(0) @binding(0) var<uniform> params: CullParams;
+@group(0) @binding(1) var<storage, read> objects: array<CullObject>;
+@group(0) @binding(2) var<storage, read_write> draws: array<DrawArgs>;
+
+@compute @workgroup_size(64)
+fn cull(@builtin(global_invocation_id) id: vec3<u32>) {
+    let index = id.x;
+    if (index >= params.object_count) {
+        return;
+    }
+    let object = objects[index];
+    var args = object.args;
+    let visible = all(object.bounds_max >= params.view_min) && all(object.bounds_min <= params.view_max);
+    if (!visible) {
+        args.instance_count = 0u;
+    }
+    draws[index] = args;
+}
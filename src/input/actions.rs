@@ -0,0 +1,272 @@
+// src/input/actions.rs
+use super::gamepad::GamepadButton;
+use super::players::InputDevice;
+use super::{InputEvent, InputManager};
+use crate::ecs::world::World;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use winit::event::MouseButton;
+use winit::keyboard::{KeyCode, PhysicalKey};
+
+// A physical input an action can be bound to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Binding {
+    Key(KeyCode),
+    Mouse(MouseButton),
+    Gamepad(GamepadButton),
+}
+
+impl Binding {
+    // On `device` only, or any device for `None`
+    fn is_held(&self, input: &InputManager, device: Option<InputDevice>) -> bool {
+        match (*self, device) {
+            (Binding::Key(_) | Binding::Mouse(_), Some(InputDevice::Gamepad(_))) => false,
+            (Binding::Key(key), _) => input.is_key_pressed(PhysicalKey::Code(key)),
+            (Binding::Mouse(button), _) => input.is_mouse_pressed(button),
+            (Binding::Gamepad(button), Some(InputDevice::Gamepad(gamepad))) => input.is_gamepad_pressed(gamepad, button),
+            (Binding::Gamepad(_), Some(InputDevice::KeyboardMouse)) => false,
+            (Binding::Gamepad(button), None) => input.any_gamepad_pressed(button),
+        }
+    }
+
+    fn pressed_by(&self, event: &InputEvent) -> bool {
+        match (*self, *event) {
+            (Binding::Key(key), InputEvent::Key { key: PhysicalKey::Code(pressed), pressed: true }) => key == pressed,
+            (Binding::Mouse(button), InputEvent::MouseButton { button: pressed, pressed: true }) => button == pressed,
+            (Binding::Gamepad(button), InputEvent::GamepadButton { button: pressed, pressed: true, .. }) => button == pressed,
+            _ => false,
+        }
+    }
+}
+
+// How long an input stays usable after it happened
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BufferWindow {
+    Ticks(u32),
+    Millis(u32),
+}
+
+impl BufferWindow {
+    // In whole fixed ticks, rounded up so a window never comes out shorter than asked
+    pub fn ticks(&self, fixed_delta: f64) -> u32 {
+        match *self {
+            BufferWindow::Ticks(ticks) => ticks,
+            BufferWindow::Millis(ms) if fixed_delta > 0.0 => (ms as f64 / 1000.0 / fixed_delta - 1e-9).ceil().max(0.0) as u32,
+            BufferWindow::Millis(_) => 0,
+        }
+    }
+}
+
+// Named actions and what triggers them, loadable from RON:
+//
+//     (bindings: {"jump": [Key(Space), Key(KeyW)]}, buffers: {"jump": Millis(100)}, toggles: ["crouch"])
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ActionMap {
+    pub bindings: BTreeMap<String, Vec<Binding>>,
+    // Actions listed here count as pressed for a while after the press, see `Actions::buffered`
+    #[serde(default)]
+    pub buffers: BTreeMap<String, BufferWindow>,
+    // Actions that latch: one press turns them on and the next turns them off, e.g.
+    // sprint or aim for players who can't hold a button
+    #[serde(default)]
+    pub toggles: BTreeSet<String>,
+}
+
+impl ActionMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn from_ron(source: &str) -> Result<Self, String> {
+        ron::from_str(source).map_err(|e| format!("Failed to parse action map: {}", e))
+    }
+
+    pub fn load(path: &str) -> Result<Self, String> {
+        let source = std::fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+        Self::from_ron(&source)
+    }
+
+    pub fn with_binding(mut self, action: &str, binding: Binding) -> Self {
+        self.bind(action, binding);
+        self
+    }
+
+    pub fn with_buffer(mut self, action: &str, window: BufferWindow) -> Self {
+        self.buffers.insert(action.to_string(), window);
+        self
+    }
+
+    pub fn with_toggle(mut self, action: &str) -> Self {
+        self.toggles.insert(action.to_string());
+        self
+    }
+
+    pub fn bind(&mut self, action: &str, binding: Binding) {
+        let bindings = self.bindings.entry(action.to_string()).or_default();
+        if !bindings.contains(&binding) {
+            bindings.push(binding);
+        }
+    }
+
+    // Replaces every binding of `action`, e.g. from a remapping menu
+    pub fn rebind(&mut self, action: &str, bindings: Vec<Binding>) {
+        self.bindings.insert(action.to_string(), bindings);
+    }
+
+    pub fn bindings(&self, action: &str) -> &[Binding] {
+        self.bindings.get(action).map_or(&[], Vec::as_slice)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ActionState {
+    pub pressed: bool,
+    pub just_pressed: bool,
+    pub just_released: bool,
+    // Fixed ticks the action has been held, counting this one
+    pub held_ticks: u32,
+    // Fixed ticks since the last press; 0 on the tick it happened
+    ticks_since_press: Option<u32>,
+    // The last press was used up by `consume_buffered`
+    consumed: bool,
+    // A binding is physically down; differs from `pressed` for toggles
+    held: bool,
+}
+
+// Action states, advanced once per fixed update by `update_actions` so gameplay sees the
+// same presses on the same ticks however frames fall. Presses that start and end between
+// two ticks still register for one tick.
+#[derive(Debug, Clone, Default)]
+pub struct Actions {
+    pub map: ActionMap,
+    // Only this device drives the actions, e.g. one local player's gamepad; `None` takes
+    // every device
+    pub device: Option<InputDevice>,
+    states: HashMap<String, ActionState>,
+    // Presses seen since the last tick
+    pending: HashSet<String>,
+    fixed_delta: f64,
+}
+
+impl Actions {
+    pub fn new(map: ActionMap) -> Self {
+        Self { map, ..Default::default() }
+    }
+
+    // Feed every input event, as `dispatch_input` does
+    pub fn handle(&mut self, event: &InputEvent) {
+        if self.device.is_some_and(|device| device != event.device()) {
+            return;
+        }
+        for (action, bindings) in &self.map.bindings {
+            if bindings.iter().any(|binding| binding.pressed_by(event)) {
+                self.pending.insert(action.clone());
+            }
+        }
+    }
+
+    pub fn update(&mut self, input: &InputManager, fixed_delta: f64) {
+        self.fixed_delta = fixed_delta;
+        for (action, bindings) in &self.map.bindings {
+            let state = self.states.entry(action.clone()).or_default();
+            let pressed_now = self.pending.contains(action);
+            let held = pressed_now || bindings.iter().any(|binding| binding.is_held(input, self.device));
+            // A press while already down means it was released and pressed again in between
+            let press = pressed_now || held && !state.held;
+            let toggle = self.map.toggles.contains(action);
+            let down = if toggle { state.pressed != press } else { held };
+            state.just_pressed = if toggle { down && !state.pressed } else { press };
+            state.just_released = !down && state.pressed;
+            state.pressed = down;
+            state.held = held;
+            state.held_ticks = if down { state.held_ticks.saturating_add(1) } else { 0 };
+            state.ticks_since_press = match (state.just_pressed, state.ticks_since_press) {
+                (true, _) => {
+                    state.consumed = false;
+                    Some(0)
+                }
+                (false, since) => since.map(|ticks| ticks.saturating_add(1)),
+            };
+        }
+        self.pending.clear();
+    }
+
+    pub fn state(&self, action: &str) -> ActionState {
+        self.states.get(action).copied().unwrap_or_default()
+    }
+
+    pub fn pressed(&self, action: &str) -> bool {
+        self.state(action).pressed
+    }
+
+    pub fn just_pressed(&self, action: &str) -> bool {
+        self.state(action).just_pressed
+    }
+
+    pub fn just_released(&self, action: &str) -> bool {
+        self.state(action).just_released
+    }
+
+    // Pressed within the action's buffer window and not yet consumed, so a jump pressed a
+    // few frames before landing still happens. Without a window only this tick's press counts.
+    pub fn buffered(&self, action: &str) -> bool {
+        let window = self.map.buffers.get(action).map_or(0, |window| window.ticks(self.fixed_delta));
+        let state = self.state(action);
+        !state.consumed && state.ticks_since_press.is_some_and(|ticks| ticks <= window)
+    }
+
+    // `buffered`, using the press up so it only fires once
+    pub fn consume_buffered(&mut self, action: &str) -> bool {
+        if !self.buffered(action) {
+            return false;
+        }
+        if let Some(state) = self.states.get_mut(action) {
+            state.consumed = true;
+        }
+        true
+    }
+}
+
+// Grace period after walking off a ledge in which a jump is still allowed. Update it each
+// fixed tick with whether the character is grounded.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CoyoteTime {
+    pub window: BufferWindow,
+    ticks_since_grounded: Option<u32>,
+}
+
+impl CoyoteTime {
+    pub fn new(window: BufferWindow) -> Self {
+        Self { window, ticks_since_grounded: None }
+    }
+
+    pub fn update(&mut self, grounded: bool) {
+        self.ticks_since_grounded = match (grounded, self.ticks_since_grounded) {
+            (true, _) => Some(0),
+            (false, since) => since.map(|ticks| ticks.saturating_add(1)),
+        };
+    }
+
+    pub fn available(&self, fixed_delta: f64) -> bool {
+        self.ticks_since_grounded.is_some_and(|ticks| ticks <= self.window.ticks(fixed_delta))
+    }
+
+    // `available`, then closes the window so the grace jump can't repeat
+    pub fn consume(&mut self, fixed_delta: f64) -> bool {
+        let available = self.available(fixed_delta);
+        if available {
+            self.ticks_since_grounded = None;
+        }
+        available
+    }
+}
+
+// Run in `FixedUpdate` after `step_input_stream`; also advances `PlayerActions`
+pub fn update_actions(world: &mut World, delta_time: f64) {
+    super::players::update_player_actions(world, delta_time);
+    let Some(mut actions) = world.resources.remove::<Actions>() else { return };
+    if let Some(input) = world.resources.get::<InputManager>() {
+        actions.update(input, delta_time);
+    }
+    world.resources.insert(actions);
+}
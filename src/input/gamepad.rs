@@ -0,0 +1,241 @@
+// src/input/gamepad.rs
+use super::haptics::Haptics;
+use super::{process_input, InputEvent};
+use crate::ecs::world::World;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct GamepadId(pub usize);
+
+// Buttons by position, Xbox-style layout: `South` is A on Xbox and Cross on PlayStation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum GamepadButton {
+    South,
+    East,
+    North,
+    West,
+    LeftBumper,
+    RightBumper,
+    LeftTrigger,
+    RightTrigger,
+    Select,
+    Start,
+    Guide,
+    LeftStick,
+    RightStick,
+    DPadUp,
+    DPadDown,
+    DPadLeft,
+    DPadRight,
+}
+
+#[cfg(feature = "gamepad")]
+impl GamepadButton {
+    fn from_gilrs(button: gilrs::Button) -> Option<Self> {
+        use gilrs::Button;
+        Some(match button {
+            Button::South => GamepadButton::South,
+            Button::East => GamepadButton::East,
+            Button::North => GamepadButton::North,
+            Button::West => GamepadButton::West,
+            Button::LeftTrigger => GamepadButton::LeftBumper,
+            Button::RightTrigger => GamepadButton::RightBumper,
+            Button::LeftTrigger2 => GamepadButton::LeftTrigger,
+            Button::RightTrigger2 => GamepadButton::RightTrigger,
+            Button::Select => GamepadButton::Select,
+            Button::Start => GamepadButton::Start,
+            Button::Mode => GamepadButton::Guide,
+            Button::LeftThumb => GamepadButton::LeftStick,
+            Button::RightThumb => GamepadButton::RightStick,
+            Button::DPadUp => GamepadButton::DPadUp,
+            Button::DPadDown => GamepadButton::DPadDown,
+            Button::DPadLeft => GamepadButton::DPadLeft,
+            Button::DPadRight => GamepadButton::DPadRight,
+            _ => return None,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GamepadInfo {
+    pub id: GamepadId,
+    pub name: String,
+    pub supports_rumble: bool,
+}
+
+// Connected controllers, through gilrs when the `gamepad` feature is on. Without it no
+// gamepad ever connects, so games can use the same code paths either way.
+pub struct Gamepads {
+    connected: Vec<GamepadInfo>,
+    #[cfg(feature = "gamepad")]
+    gilrs: Option<gilrs::Gilrs>,
+    // Effects are stopped when dropped, so each is kept until its pattern has finished
+    #[cfg(feature = "gamepad")]
+    effects: Vec<(gilrs::ff::Effect, f32)>,
+}
+
+impl Gamepads {
+    pub fn new() -> Self {
+        #[cfg(feature = "gamepad")]
+        {
+            let gilrs = match gilrs::Gilrs::new() {
+                Ok(gilrs) => Some(gilrs),
+                Err(e) => {
+                    log::warn!("Gamepads unavailable: {}", e);
+                    None
+                }
+            };
+            let mut gamepads = Self { connected: Vec::new(), gilrs, effects: Vec::new() };
+            gamepads.refresh();
+            gamepads
+        }
+        #[cfg(not(feature = "gamepad"))]
+        Self { connected: Vec::new() }
+    }
+
+    pub fn connected(&self) -> &[GamepadInfo] {
+        &self.connected
+    }
+
+    pub fn get(&self, id: GamepadId) -> Option<&GamepadInfo> {
+        self.connected.iter().find(|info| info.id == id)
+    }
+
+    // Drains backend events into input events; call once per frame
+    pub fn poll(&mut self) -> Vec<InputEvent> {
+        #[cfg(feature = "gamepad")]
+        {
+            use gilrs::EventType;
+
+            let mut events = Vec::new();
+            let Some(gilrs) = &mut self.gilrs else { return events };
+            let mut changed = false;
+            while let Some(event) = gilrs.next_event() {
+                let gamepad = GamepadId(event.id.into());
+                let button = |button, pressed| {
+                    GamepadButton::from_gilrs(button).map(|button| InputEvent::GamepadButton { gamepad, button, pressed })
+                };
+                let input = match event.event {
+                    EventType::ButtonPressed(pressed, _) => button(pressed, true),
+                    EventType::ButtonReleased(released, _) => button(released, false),
+                    EventType::Connected => Some(InputEvent::GamepadConnected(gamepad)),
+                    EventType::Disconnected => Some(InputEvent::GamepadDisconnected(gamepad)),
+                    _ => None,
+                };
+                changed |= matches!(event.event, EventType::Connected | EventType::Disconnected);
+                events.extend(input);
+            }
+            if changed {
+                self.refresh();
+            }
+            events
+        }
+        #[cfg(not(feature = "gamepad"))]
+        Vec::new()
+    }
+
+    #[cfg(feature = "gamepad")]
+    fn refresh(&mut self) {
+        let Some(gilrs) = &self.gilrs else { return };
+        self.connected = gilrs
+            .gamepads()
+            .map(|(id, gamepad)| GamepadInfo {
+                id: GamepadId(id.into()),
+                name: gamepad.name().to_string(),
+                supports_rumble: gamepad.is_ff_supported(),
+            })
+            .collect();
+    }
+
+    // Starts what `haptics` queued and retires finished effects
+    pub fn apply_haptics(&mut self, haptics: &mut Haptics, delta_time: f32) {
+        let (stop, requests) = haptics.drain();
+        #[cfg(feature = "gamepad")]
+        {
+            self.effects.retain_mut(|(_, remaining)| {
+                *remaining -= delta_time;
+                *remaining > 0.0 && !stop
+            });
+            for request in requests {
+                if let Err(e) = self.start_effect(&request) {
+                    log::warn!("Failed to play rumble: {}", e);
+                }
+            }
+        }
+        #[cfg(not(feature = "gamepad"))]
+        let _ = (stop, requests, delta_time);
+    }
+
+    #[cfg(feature = "gamepad")]
+    fn start_effect(&mut self, request: &super::haptics::HapticRequest) -> Result<(), String> {
+        use gilrs::ff::{BaseEffect, BaseEffectType, EffectBuilder, Envelope, Repeat, Replay, Ticks};
+
+        let Some(gilrs) = &mut self.gilrs else { return Ok(()) };
+        let ids: Vec<gilrs::GamepadId> = gilrs
+            .gamepads()
+            .filter(|(id, gamepad)| gamepad.is_ff_supported() && request.gamepad.is_none_or(|target| target.0 == usize::from(*id)))
+            .map(|(id, _)| id)
+            .collect();
+        if ids.is_empty() {
+            return Ok(());
+        }
+        // gilrs schedules in 50 ms ticks; `from_ms` rounds up, so whole ticks convert exactly
+        let ticks = |seconds: f32| (seconds.max(0.0) * 20.0).round() as u32;
+        let from_ticks = |count: u32| Ticks::from_ms(count * 50);
+        let length = |pulse: &super::haptics::Rumble| ticks(pulse.delay) + ticks(pulse.duration).max(1);
+        let total = request.pattern.pulses.iter().map(length).sum::<u32>();
+        let mut builder = EffectBuilder::new();
+        let mut start = 0;
+        for pulse in &request.pattern.pulses {
+            start += ticks(pulse.delay);
+            let play_for = ticks(pulse.duration).max(1);
+            // gilrs needs both ramps to fit inside the pulse
+            let (mut attack, mut fade) = (ticks(pulse.envelope.attack), ticks(pulse.envelope.fade));
+            if attack + fade >= play_for {
+                let fit = (play_for - 1) as f32 / (attack + fade) as f32;
+                (attack, fade) = ((attack as f32 * fit) as u32, (fade as f32 * fit) as u32);
+            }
+            let envelope = Envelope {
+                attack_length: from_ticks(attack),
+                attack_level: pulse.envelope.attack_level,
+                fade_length: from_ticks(fade),
+                fade_level: pulse.envelope.fade_level,
+            };
+            // Each pulse plays once: the gap before it repeats is longer than the pattern
+            let scheduling = Replay { after: from_ticks(start), play_for: from_ticks(play_for), with_delay: from_ticks(total) };
+            let magnitude = |level: f32| ((level * request.strength).clamp(0.0, 1.0) * u16::MAX as f32) as u16;
+            for kind in [BaseEffectType::Strong { magnitude: magnitude(pulse.strong) }, BaseEffectType::Weak { magnitude: magnitude(pulse.weak) }] {
+                builder.add_effect(BaseEffect { kind, scheduling, envelope });
+            }
+            start += play_for;
+        }
+        let effect = builder
+            .gamepads(&ids)
+            .repeat(Repeat::For(from_ticks(total)))
+            .finish(gilrs)
+            .map_err(|e| e.to_string())?;
+        effect.play().map_err(|e| e.to_string())?;
+        self.effects.push((effect, total as f32 / 20.0));
+        Ok(())
+    }
+}
+
+impl Default for Gamepads {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Run in `PreUpdate`: polls a `Gamepads` resource, feeds its events through
+// `process_input` and plays what the `Haptics` resource queued last frame
+pub fn poll_gamepads(world: &mut World, delta_time: f64) {
+    let Some(mut gamepads) = world.resources.remove::<Gamepads>() else { return };
+    let events = gamepads.poll();
+    if let Some(haptics) = world.resources.get_mut::<Haptics>() {
+        gamepads.apply_haptics(haptics, delta_time as f32);
+    }
+    world.resources.insert(gamepads);
+    for event in events {
+        process_input(world, event);
+    }
+}
@@ -0,0 +1,152 @@
+// src/input/haptics.rs
+use super::gamepad::GamepadId;
+use serde::{Deserialize, Serialize};
+
+// Ramps at the start and end of a pulse, as a fraction of full strength
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub struct RumbleEnvelope {
+    // Seconds to ramp up from `attack_level`
+    pub attack: f32,
+    pub attack_level: f32,
+    // Seconds to ramp down to `fade_level`
+    pub fade: f32,
+    pub fade_level: f32,
+}
+
+// One burst on both motors. The strong (low-frequency) motor is a heavy thud; the weak
+// (high-frequency) one is a buzz.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Rumble {
+    // 0..1
+    pub strong: f32,
+    pub weak: f32,
+    // Seconds
+    pub duration: f32,
+    // Seconds of silence after the previous pulse
+    #[serde(default)]
+    pub delay: f32,
+    #[serde(default)]
+    pub envelope: RumbleEnvelope,
+}
+
+impl Rumble {
+    pub fn new(strong: f32, weak: f32, duration: f32) -> Self {
+        Self { strong, weak, duration, delay: 0.0, envelope: RumbleEnvelope::default() }
+    }
+
+    pub fn with_delay(mut self, delay: f32) -> Self {
+        self.delay = delay;
+        self
+    }
+
+    // Ramps in over `attack` and out over `fade` seconds, from and to silence
+    pub fn with_ramps(mut self, attack: f32, fade: f32) -> Self {
+        self.envelope = RumbleEnvelope { attack, attack_level: 0.0, fade, fade_level: 0.0 };
+        self
+    }
+}
+
+// Pulses played one after another, e.g. a heartbeat. Patterns are plain data, so they can
+// live in RON alongside the rest of a weapon or hit definition.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct HapticPattern {
+    pub pulses: Vec<Rumble>,
+}
+
+impl HapticPattern {
+    pub fn new(pulse: Rumble) -> Self {
+        Self { pulses: vec![pulse] }
+    }
+
+    pub fn then(mut self, pulse: Rumble) -> Self {
+        self.pulses.push(pulse);
+        self
+    }
+
+    pub fn from_ron(source: &str) -> Result<Self, String> {
+        ron::from_str(source).map_err(|e| format!("Failed to parse haptic pattern: {}", e))
+    }
+
+    // Short weak buzz, e.g. UI confirmation or footsteps
+    pub fn tap() -> Self {
+        Self::new(Rumble::new(0.0, 0.4, 0.05))
+    }
+
+    // Heavy hit that tails off
+    pub fn impact() -> Self {
+        Self::new(Rumble::new(1.0, 0.6, 0.3).with_ramps(0.0, 0.2))
+    }
+
+    pub fn heartbeat() -> Self {
+        Self::new(Rumble::new(0.7, 0.0, 0.1)).then(Rumble::new(0.4, 0.0, 0.1).with_delay(0.12))
+    }
+
+    // Seconds from start to the end of the last pulse
+    pub fn duration(&self) -> f32 {
+        self.pulses.iter().map(|pulse| pulse.delay.max(0.0) + pulse.duration.max(0.0)).sum()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct HapticRequest {
+    // `None` plays on every connected gamepad
+    pub gamepad: Option<GamepadId>,
+    pub pattern: HapticPattern,
+    // Multiplies every pulse's motors; already includes `Haptics::strength`
+    pub strength: f32,
+}
+
+// Gameplay's side of force feedback: `play` queues a pattern and the gamepad backend
+// (`Gamepads::apply_haptics`) sends it to the controllers. Without a backend, or with
+// `enabled` off, requests are simply dropped.
+#[derive(Debug, Clone)]
+pub struct Haptics {
+    pub enabled: bool,
+    // Global scale, e.g. a settings slider; 0 mutes without losing the preference
+    pub strength: f32,
+    requests: Vec<HapticRequest>,
+    stop_requested: bool,
+}
+
+impl Default for Haptics {
+    fn default() -> Self {
+        Self { enabled: true, strength: 1.0, requests: Vec::new(), stop_requested: false }
+    }
+}
+
+impl Haptics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn play(&mut self, pattern: &HapticPattern) {
+        self.queue(None, pattern, 1.0);
+    }
+
+    pub fn play_on(&mut self, gamepad: GamepadId, pattern: &HapticPattern) {
+        self.queue(Some(gamepad), pattern, 1.0);
+    }
+
+    // `scale` on top of `strength`, e.g. by distance to an explosion
+    pub fn play_scaled(&mut self, gamepad: Option<GamepadId>, pattern: &HapticPattern, scale: f32) {
+        self.queue(gamepad, pattern, scale);
+    }
+
+    fn queue(&mut self, gamepad: Option<GamepadId>, pattern: &HapticPattern, scale: f32) {
+        let strength = (self.strength * scale).clamp(0.0, 1.0);
+        if self.enabled && strength > 0.0 && !pattern.pulses.is_empty() {
+            self.requests.push(HapticRequest { gamepad, pattern: pattern.clone(), strength });
+        }
+    }
+
+    // Cuts off everything playing, e.g. on pause
+    pub fn stop_all(&mut self) {
+        self.requests.clear();
+        self.stop_requested = true;
+    }
+
+    // For the backend: whether to stop what is playing, then what to start
+    pub fn drain(&mut self) -> (bool, Vec<HapticRequest>) {
+        (std::mem::take(&mut self.stop_requested), std::mem::take(&mut self.requests))
+    }
+}
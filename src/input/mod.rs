@@ -0,0 +1,225 @@
+// src/input/mod.rs
+pub mod actions;
+pub mod gamepad;
+pub mod haptics;
+pub mod playback;
+pub mod players;
+pub mod shortcuts;
+
+use crate::ecs::world::World;
+use gamepad::{GamepadButton, GamepadId};
+use players::InputDevice;
+use serde::{Deserialize, Serialize};
+use winit::event::{WindowEvent, ElementState, KeyEvent, MouseButton};
+use winit::keyboard::{KeyCode, PhysicalKey}; // FIXED: Changed to PhysicalKey
+use std::collections::HashSet;
+
+// The input `InputManager` tracks, separate from winit so recordings and tests can
+// construct it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum InputEvent {
+    Key { key: PhysicalKey, pressed: bool },
+    MouseButton { button: MouseButton, pressed: bool },
+    GamepadButton { gamepad: GamepadId, button: GamepadButton, pressed: bool },
+    GamepadConnected(GamepadId),
+    GamepadDisconnected(GamepadId),
+}
+
+// Modifier keys held, either side counting
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub struct Modifiers {
+    pub ctrl: bool,
+    pub shift: bool,
+    pub alt: bool,
+    // Cmd on macOS, the Windows key elsewhere
+    pub logo: bool,
+}
+
+impl Modifiers {
+    pub const NONE: Modifiers = Modifiers { ctrl: false, shift: false, alt: false, logo: false };
+    pub const CTRL: Modifiers = Modifiers { ctrl: true, ..Self::NONE };
+    pub const SHIFT: Modifiers = Modifiers { shift: true, ..Self::NONE };
+    pub const ALT: Modifiers = Modifiers { alt: true, ..Self::NONE };
+    pub const LOGO: Modifiers = Modifiers { logo: true, ..Self::NONE };
+
+    pub fn is_empty(&self) -> bool {
+        *self == Self::NONE
+    }
+
+    // The modifier `key` is, or `NONE`
+    pub fn of_key(key: KeyCode) -> Modifiers {
+        match key {
+            KeyCode::ControlLeft | KeyCode::ControlRight => Self::CTRL,
+            KeyCode::ShiftLeft | KeyCode::ShiftRight => Self::SHIFT,
+            KeyCode::AltLeft | KeyCode::AltRight => Self::ALT,
+            KeyCode::SuperLeft | KeyCode::SuperRight => Self::LOGO,
+            _ => Self::NONE,
+        }
+    }
+
+    pub fn without(self, other: Modifiers) -> Modifiers {
+        Modifiers {
+            ctrl: self.ctrl && !other.ctrl,
+            shift: self.shift && !other.shift,
+            alt: self.alt && !other.alt,
+            logo: self.logo && !other.logo,
+        }
+    }
+}
+
+impl std::ops::BitOr for Modifiers {
+    type Output = Modifiers;
+
+    fn bitor(self, other: Modifiers) -> Modifiers {
+        Modifiers {
+            ctrl: self.ctrl || other.ctrl,
+            shift: self.shift || other.shift,
+            alt: self.alt || other.alt,
+            logo: self.logo || other.logo,
+        }
+    }
+}
+
+impl InputEvent {
+    pub fn from_window_event(event: &WindowEvent) -> Option<Self> {
+        match event {
+            WindowEvent::KeyboardInput { event: KeyEvent { physical_key, state, repeat: false, .. }, .. } => {
+                Some(InputEvent::Key { key: *physical_key, pressed: *state == ElementState::Pressed })
+            }
+            WindowEvent::MouseInput { button, state, .. } => {
+                Some(InputEvent::MouseButton { button: *button, pressed: *state == ElementState::Pressed })
+            }
+            _ => None,
+        }
+    }
+
+    pub fn device(&self) -> InputDevice {
+        match *self {
+            InputEvent::Key { .. } | InputEvent::MouseButton { .. } => InputDevice::KeyboardMouse,
+            InputEvent::GamepadButton { gamepad, .. } | InputEvent::GamepadConnected(gamepad) | InputEvent::GamepadDisconnected(gamepad) => {
+                InputDevice::Gamepad(gamepad)
+            }
+        }
+    }
+}
+
+pub struct InputManager {
+    keys_pressed: HashSet<PhysicalKey>, // FIXED: Changed from NamedKey to PhysicalKey
+    mouse_pressed: HashSet<MouseButton>,
+    gamepad_pressed: HashSet<(GamepadId, GamepadButton)>,
+}
+
+impl InputManager {
+    pub fn new() -> Self {
+        Self {
+            keys_pressed: HashSet::new(),
+            mouse_pressed: HashSet::new(),
+            gamepad_pressed: HashSet::new(),
+        }
+    }
+
+    // Returns the event it understood, e.g. for an `InputRecorder`
+    pub fn handle_event(&mut self, event: &WindowEvent) -> Option<InputEvent> {
+        let event = InputEvent::from_window_event(event)?;
+        self.apply(event);
+        Some(event)
+    }
+
+    pub fn apply(&mut self, event: InputEvent) {
+        match event {
+            InputEvent::Key { key, pressed: true } => {
+                self.keys_pressed.insert(key);
+            }
+            InputEvent::Key { key, pressed: false } => {
+                self.keys_pressed.remove(&key);
+            }
+            InputEvent::MouseButton { button, pressed: true } => {
+                self.mouse_pressed.insert(button);
+            }
+            InputEvent::MouseButton { button, pressed: false } => {
+                self.mouse_pressed.remove(&button);
+            }
+            InputEvent::GamepadButton { gamepad, button, pressed: true } => {
+                self.gamepad_pressed.insert((gamepad, button));
+            }
+            InputEvent::GamepadButton { gamepad, button, pressed: false } => {
+                self.gamepad_pressed.remove(&(gamepad, button));
+            }
+            InputEvent::GamepadConnected(_) => {}
+            InputEvent::GamepadDisconnected(gamepad) => {
+                self.gamepad_pressed.retain(|(id, _)| *id != gamepad);
+            }
+        }
+    }
+
+    // Releases everything, e.g. when playback takes over from the real devices
+    pub fn clear(&mut self) {
+        self.keys_pressed.clear();
+        self.mouse_pressed.clear();
+        self.gamepad_pressed.clear();
+    }
+
+    pub fn is_key_pressed(&self, key: PhysicalKey) -> bool { // FIXED: Changed parameter type
+        self.keys_pressed.contains(&key)
+    }
+
+    pub fn is_mouse_pressed(&self, button: MouseButton) -> bool {
+        self.mouse_pressed.contains(&button)
+    }
+
+    pub fn is_gamepad_pressed(&self, gamepad: GamepadId, button: GamepadButton) -> bool {
+        self.gamepad_pressed.contains(&(gamepad, button))
+    }
+
+    pub fn any_gamepad_pressed(&self, button: GamepadButton) -> bool {
+        self.gamepad_pressed.iter().any(|(_, pressed)| *pressed == button)
+    }
+
+    pub fn modifiers(&self) -> Modifiers {
+        let held = |left, right| self.is_key_pressed(PhysicalKey::Code(left)) || self.is_key_pressed(PhysicalKey::Code(right));
+        Modifiers {
+            ctrl: held(KeyCode::ControlLeft, KeyCode::ControlRight),
+            shift: held(KeyCode::ShiftLeft, KeyCode::ShiftRight),
+            alt: held(KeyCode::AltLeft, KeyCode::AltRight),
+            logo: held(KeyCode::SuperLeft, KeyCode::SuperRight),
+        }
+    }
+}
+
+impl Default for InputManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Live input from any device: applies it to `InputManager`, records it if an
+// `InputRecorder` is running, then dispatches it. Ignored while an `InputPlayback` runs.
+pub fn process_input(world: &mut World, event: InputEvent) {
+    if world.resources.get::<playback::InputPlayback>().is_some_and(|playback| !playback.is_finished()) {
+        return;
+    }
+    let Some(input) = world.resources.get_mut::<InputManager>() else { return };
+    input.apply(event);
+    if let Some(recorder) = world.resources.get_mut::<playback::InputRecorder>() {
+        recorder.record(event);
+    }
+    dispatch_input(world, event);
+}
+
+// Passes an event already applied to `InputManager` on to the `ShortcutManager`,
+// `Actions`, `PlayerDevices` and `PlayerActions` resources, if present
+pub fn dispatch_input(world: &mut World, event: InputEvent) {
+    let modifiers = world.resources.get::<InputManager>().map(InputManager::modifiers).unwrap_or_default();
+    if let Some(shortcuts) = world.resources.get_mut::<shortcuts::ShortcutManager>() {
+        shortcuts.handle(event, modifiers);
+    }
+    if let Some(actions) = world.resources.get_mut::<actions::Actions>() {
+        actions.handle(&event);
+    }
+    if let Some(players) = world.resources.get_mut::<players::PlayerDevices>() {
+        players.handle(&event);
+    }
+    if let Some(actions) = world.resources.get_mut::<players::PlayerActions>() {
+        actions.handle(&event);
+    }
+}
@@ -0,0 +1,167 @@
+// src/input/playback.rs
+use super::{dispatch_input, InputEvent, InputManager};
+use crate::ecs::world::World;
+use serde::{Deserialize, Serialize};
+use winit::keyboard::{KeyCode, PhysicalKey};
+
+// Input stamped with the fixed-update tick it applies on, so a replay lands on the same
+// simulation steps regardless of frame rate
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecordedInput {
+    pub tick: u64,
+    pub event: InputEvent,
+}
+
+// A stream of input, captured with `InputRecorder` or built in code for a test:
+//
+//     InputRecording::new().hold(0, KeyCode::KeyD, 30).tap(40, KeyCode::Space)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct InputRecording {
+    // Sorted by tick; events on the same tick apply in order
+    pub events: Vec<RecordedInput>,
+}
+
+impl InputRecording {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn from_ron(source: &str) -> Result<Self, String> {
+        let mut recording: Self = ron::from_str(source).map_err(|e| format!("Failed to parse input recording: {}", e))?;
+        recording.events.sort_by_key(|input| input.tick);
+        Ok(recording)
+    }
+
+    pub fn load(path: &str) -> Result<Self, String> {
+        let source = std::fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+        Self::from_ron(&source)
+    }
+
+    pub fn to_ron(&self) -> Result<String, String> {
+        ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|e| format!("Failed to write input recording: {}", e))
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), String> {
+        std::fs::write(path, self.to_ron()?).map_err(|e| format!("Failed to write {}: {}", path, e))
+    }
+
+    // Inserted after any events already on `tick`
+    pub fn push(&mut self, tick: u64, event: InputEvent) {
+        let index = self.events.partition_point(|input| input.tick <= tick);
+        self.events.insert(index, RecordedInput { tick, event });
+    }
+
+    pub fn key(mut self, tick: u64, key: KeyCode, pressed: bool) -> Self {
+        self.push(tick, InputEvent::Key { key: PhysicalKey::Code(key), pressed });
+        self
+    }
+
+    // Down on `tick`, up `ticks` later
+    pub fn hold(self, tick: u64, key: KeyCode, ticks: u64) -> Self {
+        self.key(tick, key, true).key(tick + ticks.max(1), key, false)
+    }
+
+    // Down for a single tick
+    pub fn tap(self, tick: u64, key: KeyCode) -> Self {
+        self.hold(tick, key, 1)
+    }
+
+    // Tick after the last event
+    pub fn duration(&self) -> u64 {
+        self.events.last().map_or(0, |input| input.tick + 1)
+    }
+}
+
+// Captures live input into an `InputRecording`. As a resource, the app feeds it every
+// event `InputManager` handles and `step_input_stream` advances its tick.
+#[derive(Debug, Clone, Default)]
+pub struct InputRecorder {
+    recording: InputRecording,
+    tick: u64,
+}
+
+impl InputRecorder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record(&mut self, event: InputEvent) {
+        self.recording.events.push(RecordedInput { tick: self.tick, event });
+    }
+
+    pub fn tick(&self) -> u64 {
+        self.tick
+    }
+
+    pub fn next_tick(&mut self) {
+        self.tick += 1;
+    }
+
+    pub fn recording(&self) -> &InputRecording {
+        &self.recording
+    }
+
+    pub fn finish(self) -> InputRecording {
+        self.recording
+    }
+}
+
+// Drives `InputManager` from a recording instead of devices, for headless integration
+// tests of gameplay. While it runs as a resource the app ignores real input.
+#[derive(Debug, Clone)]
+pub struct InputPlayback {
+    recording: InputRecording,
+    cursor: usize,
+    tick: u64,
+}
+
+impl InputPlayback {
+    pub fn new(recording: InputRecording) -> Self {
+        Self { recording, cursor: 0, tick: 0 }
+    }
+
+    pub fn tick(&self) -> u64 {
+        self.tick
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.cursor >= self.recording.events.len()
+    }
+
+    // Applies this tick's events, then moves to the next tick; returns what was applied
+    pub fn step(&mut self, input: &mut InputManager) -> &[RecordedInput] {
+        let start = self.cursor;
+        while let Some(recorded) = self.recording.events.get(self.cursor).filter(|r| r.tick <= self.tick) {
+            input.apply(recorded.event);
+            self.cursor += 1;
+        }
+        self.tick += 1;
+        &self.recording.events[start..self.cursor]
+    }
+
+    // Back to tick 0, releasing anything held
+    pub fn restart(&mut self, input: &mut InputManager) {
+        self.cursor = 0;
+        self.tick = 0;
+        input.clear();
+    }
+}
+
+// Run first in `FixedUpdate`: replays an `InputPlayback` resource into `InputManager` (and
+// shortcuts and actions) and advances an `InputRecorder` resource to the next tick
+pub fn step_input_stream(world: &mut World, _delta_time: f64) {
+    if let Some(mut playback) = world.resources.remove::<InputPlayback>() {
+        let applied = match world.resources.get_mut::<InputManager>() {
+            Some(input) => playback.step(input).to_vec(),
+            None => Vec::new(),
+        };
+        world.resources.insert(playback);
+        for recorded in applied {
+            dispatch_input(world, recorded.event);
+        }
+    }
+    if let Some(recorder) = world.resources.get_mut::<InputRecorder>() {
+        recorder.next_tick();
+    }
+}
@@ -0,0 +1,184 @@
+// src/input/players.rs
+use super::actions::{ActionMap, Actions};
+use super::gamepad::GamepadId;
+use super::{InputEvent, InputManager};
+use crate::ecs::world::World;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+
+// Keyboard and mouse count as one device, as they are one player's in local co-op
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum InputDevice {
+    KeyboardMouse,
+    Gamepad(GamepadId),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlayerEvent {
+    DeviceConnected(InputDevice),
+    // `player` had the device; the slot keeps it and waits for it to come back
+    DeviceDisconnected { device: InputDevice, player: Option<usize> },
+    // A player's device is back, e.g. after batteries were swapped
+    DeviceReconnected { device: InputDevice, player: usize },
+    Joined { player: usize, device: InputDevice },
+    Left { player: usize, device: InputDevice },
+}
+
+// Which device drives which local player slot. With `auto_join`, pressing a button on an
+// unassigned device takes the first free slot, the usual "press start to join" flow.
+// Gamepads keep their id across a reconnect, so a pulled cable hands the controller back
+// to the same player.
+#[derive(Debug, Clone)]
+pub struct PlayerDevices {
+    pub auto_join: bool,
+    slots: Vec<Option<InputDevice>>,
+    connected: BTreeSet<InputDevice>,
+    events: Vec<PlayerEvent>,
+}
+
+impl PlayerDevices {
+    pub fn new(max_players: usize) -> Self {
+        Self {
+            auto_join: true,
+            slots: vec![None; max_players],
+            connected: BTreeSet::from([InputDevice::KeyboardMouse]),
+            events: Vec::new(),
+        }
+    }
+
+    pub fn max_players(&self) -> usize {
+        self.slots.len()
+    }
+
+    pub fn device(&self, player: usize) -> Option<InputDevice> {
+        self.slots.get(player).copied().flatten()
+    }
+
+    pub fn player_for(&self, device: InputDevice) -> Option<usize> {
+        self.slots.iter().position(|slot| *slot == Some(device))
+    }
+
+    // Has a device and it is plugged in
+    pub fn is_active(&self, player: usize) -> bool {
+        self.device(player).is_some_and(|device| self.connected.contains(&device))
+    }
+
+    pub fn connected(&self) -> impl Iterator<Item = InputDevice> + '_ {
+        self.connected.iter().copied()
+    }
+
+    // Connected devices no player has, e.g. for a "press start" prompt
+    pub fn unassigned(&self) -> impl Iterator<Item = InputDevice> + '_ {
+        self.connected.iter().copied().filter(|device| self.player_for(*device).is_none())
+    }
+
+    // Moves `device` to `player`, taking it from whoever had it
+    pub fn assign(&mut self, player: usize, device: InputDevice) -> Result<(), String> {
+        if player >= self.slots.len() {
+            return Err(format!("Player {} is outside the {} player slots", player, self.slots.len()));
+        }
+        if self.slots[player] == Some(device) {
+            return Ok(());
+        }
+        if let Some(previous) = self.player_for(device) {
+            self.unassign(previous);
+        }
+        self.unassign(player);
+        self.slots[player] = Some(device);
+        self.events.push(PlayerEvent::Joined { player, device });
+        Ok(())
+    }
+
+    pub fn unassign(&mut self, player: usize) -> Option<InputDevice> {
+        let device = self.slots.get_mut(player)?.take()?;
+        self.events.push(PlayerEvent::Left { player, device });
+        Some(device)
+    }
+
+    // Joins `device` to the first free slot; `None` if full or it already has one
+    pub fn join(&mut self, device: InputDevice) -> Option<usize> {
+        if self.player_for(device).is_some() {
+            return None;
+        }
+        let player = self.slots.iter().position(Option::is_none)?;
+        self.slots[player] = Some(device);
+        self.events.push(PlayerEvent::Joined { player, device });
+        Some(player)
+    }
+
+    // Feed every input event, as `dispatch_input` does
+    pub fn handle(&mut self, event: &InputEvent) {
+        let device = event.device();
+        match *event {
+            InputEvent::GamepadConnected(_) if self.connected.insert(device) => {
+                self.events.push(match self.player_for(device) {
+                    Some(player) => PlayerEvent::DeviceReconnected { device, player },
+                    None => PlayerEvent::DeviceConnected(device),
+                });
+            }
+            InputEvent::GamepadDisconnected(_) if self.connected.remove(&device) => {
+                self.events.push(PlayerEvent::DeviceDisconnected { device, player: self.player_for(device) });
+            }
+            InputEvent::Key { pressed: true, .. } | InputEvent::MouseButton { pressed: true, .. } | InputEvent::GamepadButton { pressed: true, .. } => {
+                // Pads already plugged in at startup never sent `GamepadConnected`
+                if self.connected.insert(device) {
+                    self.events.push(PlayerEvent::DeviceConnected(device));
+                }
+                if self.auto_join {
+                    self.join(device);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    pub fn drain_events(&mut self) -> impl Iterator<Item = PlayerEvent> + '_ {
+        self.events.drain(..)
+    }
+}
+
+// One `Actions` per player slot, each reading only that player's device. `update_actions`
+// keeps the devices in step with a `PlayerDevices` resource; slots without a device see
+// nothing pressed.
+#[derive(Debug, Clone)]
+pub struct PlayerActions {
+    players: Vec<Actions>,
+}
+
+impl PlayerActions {
+    pub fn new(map: &ActionMap, max_players: usize) -> Self {
+        Self { players: (0..max_players).map(|_| Actions::new(map.clone())).collect() }
+    }
+
+    pub fn player(&self, player: usize) -> Option<&Actions> {
+        self.players.get(player)
+    }
+
+    pub fn player_mut(&mut self, player: usize) -> Option<&mut Actions> {
+        self.players.get_mut(player)
+    }
+
+    pub fn handle(&mut self, event: &InputEvent) {
+        for actions in self.players.iter_mut().filter(|actions| actions.device.is_some()) {
+            actions.handle(event);
+        }
+    }
+
+    pub fn update(&mut self, input: &InputManager, devices: Option<&PlayerDevices>, fixed_delta: f64) {
+        let nothing = InputManager::new();
+        for (player, actions) in self.players.iter_mut().enumerate() {
+            actions.device = devices.and_then(|devices| devices.device(player));
+            // `None` would mean every device here, not no device
+            let input = if actions.device.is_some() { input } else { &nothing };
+            actions.update(input, fixed_delta);
+        }
+    }
+}
+
+pub(super) fn update_player_actions(world: &mut World, delta_time: f64) {
+    let Some(mut actions) = world.resources.remove::<PlayerActions>() else { return };
+    if let Some(input) = world.resources.get::<InputManager>() {
+        actions.update(input, world.resources.get::<PlayerDevices>(), delta_time);
+    }
+    world.resources.insert(actions);
+}
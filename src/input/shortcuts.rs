@@ -0,0 +1,255 @@
+// src/input/shortcuts.rs
+use super::{InputEvent, Modifiers};
+use serde::Deserialize;
+use std::fmt;
+use winit::event::MouseButton;
+use winit::keyboard::{KeyCode, PhysicalKey};
+
+// Always active, under whichever context is on top
+pub const GLOBAL: &str = "global";
+pub const GAMEPLAY: &str = "gameplay";
+pub const EDITOR: &str = "editor";
+pub const CONSOLE: &str = "console";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Trigger {
+    Key(KeyCode),
+    Mouse(MouseButton),
+}
+
+// Modifiers plus the key or button that fires, e.g. Ctrl+S or Shift+Click. Modifiers
+// must match exactly, so Ctrl+S doesn't fire for Ctrl+Shift+S.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Chord {
+    pub modifiers: Modifiers,
+    pub trigger: Trigger,
+}
+
+impl Chord {
+    pub fn key(modifiers: Modifiers, key: KeyCode) -> Self {
+        Self { modifiers, trigger: Trigger::Key(key) }
+    }
+
+    pub fn mouse(modifiers: Modifiers, button: MouseButton) -> Self {
+        Self { modifiers, trigger: Trigger::Mouse(button) }
+    }
+
+    // "Ctrl+S", "Ctrl+Shift+Z", "Shift+Click", "Alt+F4". Case-insensitive; keys are single
+    // letters and digits, the common names below, or any winit `KeyCode` name.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut modifiers = Modifiers::NONE;
+        let mut trigger = None;
+        for part in text.split('+').map(str::trim) {
+            if trigger.is_some() {
+                return Err(format!("Shortcut '{}' has more than one non-modifier key", text));
+            }
+            match part.to_ascii_lowercase().as_str() {
+                "ctrl" | "control" => modifiers.ctrl = true,
+                "shift" => modifiers.shift = true,
+                "alt" | "option" => modifiers.alt = true,
+                "cmd" | "super" | "win" | "logo" => modifiers.logo = true,
+                _ => trigger = Some(parse_trigger(part).ok_or_else(|| format!("Unknown key '{}' in shortcut '{}'", part, text))?),
+            }
+        }
+        trigger
+            .map(|trigger| Self { modifiers, trigger })
+            .ok_or_else(|| format!("Shortcut '{}' has no key", text))
+    }
+}
+
+fn parse_trigger(name: &str) -> Option<Trigger> {
+    let lower = name.to_ascii_lowercase();
+    let mouse = match lower.as_str() {
+        "click" | "leftclick" => Some(MouseButton::Left),
+        "rightclick" => Some(MouseButton::Right),
+        "middleclick" => Some(MouseButton::Middle),
+        _ => None,
+    };
+    if let Some(button) = mouse {
+        return Some(Trigger::Mouse(button));
+    }
+    let alias = match lower.as_str() {
+        "esc" => "Escape",
+        "del" => "Delete",
+        "return" => "Enter",
+        "up" => "ArrowUp",
+        "down" => "ArrowDown",
+        "left" => "ArrowLeft",
+        "right" => "ArrowRight",
+        "pgup" => "PageUp",
+        "pgdn" => "PageDown",
+        "plus" | "=" => "Equal",
+        "-" => "Minus",
+        "`" | "~" => "Backquote",
+        _ => "",
+    };
+    let name = match (alias, name.chars().next()) {
+        ("", Some(c)) if name.len() == 1 && c.is_ascii_alphabetic() => format!("Key{}", c.to_ascii_uppercase()),
+        ("", Some(c)) if name.len() == 1 && c.is_ascii_digit() => format!("Digit{}", c),
+        ("", _) => {
+            // winit's own names, e.g. "space" or "NumpadEnter"
+            let mut chars = name.chars();
+            chars.next().map(|c| c.to_ascii_uppercase().to_string() + chars.as_str()).unwrap_or_default()
+        }
+        (alias, _) => alias.to_string(),
+    };
+    ron::from_str::<KeyCode>(&name).ok().map(Trigger::Key)
+}
+
+impl fmt::Display for Chord {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (held, name) in [(self.modifiers.ctrl, "Ctrl"), (self.modifiers.shift, "Shift"), (self.modifiers.alt, "Alt"), (self.modifiers.logo, "Cmd")] {
+            if held {
+                write!(f, "{}+", name)?;
+            }
+        }
+        match self.trigger {
+            Trigger::Key(key) => {
+                let name = format!("{:?}", key);
+                let short = name.strip_prefix("Key").or_else(|| name.strip_prefix("Digit")).or_else(|| name.strip_prefix("Arrow"));
+                write!(f, "{}", short.filter(|s| !s.is_empty()).unwrap_or(&name))
+            }
+            Trigger::Mouse(MouseButton::Left) => write!(f, "Click"),
+            Trigger::Mouse(MouseButton::Right) => write!(f, "RightClick"),
+            Trigger::Mouse(MouseButton::Middle) => write!(f, "MiddleClick"),
+            Trigger::Mouse(button) => write!(f, "{:?}", button),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Shortcut {
+    pub context: String,
+    pub chord: Chord,
+    pub command: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ShortcutEntry {
+    context: String,
+    chord: String,
+    command: String,
+}
+
+// Maps chords to named commands per context. Only the top context on the stack and
+// `GLOBAL` are live, so opening the console stops gameplay keys firing underneath it.
+// Pressed shortcuts queue their command for `drain_commands`.
+#[derive(Debug, Clone)]
+pub struct ShortcutManager {
+    shortcuts: Vec<Shortcut>,
+    contexts: Vec<String>,
+    commands: Vec<String>,
+}
+
+impl Default for ShortcutManager {
+    fn default() -> Self {
+        Self { shortcuts: Vec::new(), contexts: vec![GAMEPLAY.to_string()], commands: Vec::new() }
+    }
+}
+
+impl ShortcutManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // A list of `(context: "editor", chord: "Ctrl+S", command: "save")` entries
+    pub fn from_ron(source: &str) -> Result<Self, String> {
+        let entries: Vec<ShortcutEntry> = ron::from_str(source).map_err(|e| format!("Failed to parse shortcuts: {}", e))?;
+        let mut manager = Self::new();
+        for entry in entries {
+            manager.bind(&entry.context, &entry.chord, &entry.command)?;
+        }
+        Ok(manager)
+    }
+
+    pub fn load(path: &str) -> Result<Self, String> {
+        let source = std::fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+        Self::from_ron(&source)
+    }
+
+    pub fn bind(&mut self, context: &str, chord: &str, command: &str) -> Result<(), String> {
+        self.bind_chord(context, Chord::parse(chord)?, command)
+    }
+
+    // Fails if the chord already runs another command where both could be live: the same
+    // context, or either one global
+    pub fn bind_chord(&mut self, context: &str, chord: Chord, command: &str) -> Result<(), String> {
+        if let Some(existing) = self.conflict(context, chord) {
+            if existing.command == command && existing.context == context {
+                return Ok(());
+            }
+            return Err(format!(
+                "{} for '{}' in {} conflicts with '{}' in {}",
+                chord, command, context, existing.command, existing.context
+            ));
+        }
+        self.shortcuts.push(Shortcut { context: context.to_string(), chord, command: command.to_string() });
+        Ok(())
+    }
+
+    pub fn conflict(&self, context: &str, chord: Chord) -> Option<&Shortcut> {
+        self.shortcuts.iter().find(|s| {
+            s.chord == chord && (s.context == context || s.context == GLOBAL || context == GLOBAL)
+        })
+    }
+
+    // Removes every chord bound to `command`, e.g. before rebinding it; returns how many
+    pub fn unbind(&mut self, command: &str) -> usize {
+        let before = self.shortcuts.len();
+        self.shortcuts.retain(|s| s.command != command);
+        before - self.shortcuts.len()
+    }
+
+    pub fn shortcuts(&self) -> &[Shortcut] {
+        &self.shortcuts
+    }
+
+    // Chords that run `command`, e.g. for menu labels
+    pub fn chords_for<'a>(&'a self, command: &'a str) -> impl Iterator<Item = Chord> + 'a {
+        self.shortcuts.iter().filter(move |s| s.command == command).map(|s| s.chord)
+    }
+
+    pub fn context(&self) -> &str {
+        self.contexts.last().map_or(GLOBAL, String::as_str)
+    }
+
+    pub fn push_context(&mut self, context: &str) {
+        self.contexts.push(context.to_string());
+    }
+
+    // The bottom context stays
+    pub fn pop_context(&mut self) -> Option<String> {
+        if self.contexts.len() > 1 { self.contexts.pop() } else { None }
+    }
+
+    // Fires on press. `modifiers` is what is held now, as `InputManager::modifiers` reports
+    // after applying `event`.
+    pub fn handle(&mut self, event: InputEvent, modifiers: Modifiers) -> Option<&str> {
+        let (trigger, modifiers) = match event {
+            // A modifier doesn't count as held for itself
+            InputEvent::Key { key: PhysicalKey::Code(key), pressed: true } => {
+                (Trigger::Key(key), modifiers.without(Modifiers::of_key(key)))
+            }
+            InputEvent::MouseButton { button, pressed: true } => (Trigger::Mouse(button), modifiers),
+            _ => return None,
+        };
+        let chord = Chord { modifiers, trigger };
+        let context = self.context();
+        let shortcut = self
+            .shortcuts
+            .iter()
+            .find(|s| s.chord == chord && s.context == context)
+            .or_else(|| self.shortcuts.iter().find(|s| s.chord == chord && s.context == GLOBAL))?;
+        self.commands.push(shortcut.command.clone());
+        self.commands.last().map(String::as_str)
+    }
+
+    pub fn drain_commands(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.commands)
+    }
+
+    // Drops queued commands `keep` rejects, e.g. ones the engine handled itself
+    pub fn retain_commands(&mut self, mut keep: impl FnMut(&str) -> bool) {
+        self.commands.retain(|command| keep(command));
+    }
+}
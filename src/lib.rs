@@ -0,0 +1,81 @@
+// src/lib.rs
+pub mod window;
+pub mod renderer;
+pub mod game_loop;
+pub mod input;
+pub mod scene;
+pub mod app;
+pub mod net;
+pub mod ai;
+pub mod animation;
+pub mod ik;
+pub mod mesh;
+pub mod texture;
+pub mod resource_registry;
+pub mod upload;
+pub mod bind_cache;
+pub mod particles;
+pub mod indirect;
+pub mod occlusion;
+pub mod lod;
+pub mod light_cluster;
+pub mod shadow;
+#[cfg(feature = "ssao")]
+pub mod ssao;
+pub mod taa;
+pub mod hdr;
+pub mod ssr;
+pub mod decal;
+pub mod atmosphere;
+pub mod water;
+pub mod outline;
+pub mod screen_effect;
+pub mod render_texture;
+pub mod split_screen;
+pub mod clip;
+pub mod resolution;
+pub mod pixel_camera;
+pub mod frame_pacing;
+pub mod power;
+pub mod regression;
+pub mod bench;
+pub mod hot_reload;
+pub mod ecs;
+pub mod physics;
+#[cfg(feature = "rapier3d")]
+pub mod physics3d;
+pub mod cloth;
+pub mod terrain2d;
+pub mod tilemap;
+pub mod procgen;
+pub mod environment;
+pub mod terrain;
+pub mod foliage;
+pub mod trail;
+pub mod lighting2d;
+pub mod projectile;
+pub mod gameplay;
+pub mod cutscene;
+pub mod camera;
+pub mod math;
+pub mod color;
+pub mod accessibility;
+pub mod capture;
+pub mod steam;
+pub mod discord;
+pub mod telemetry;
+pub mod gpu_fallback;
+pub mod capabilities;
+pub mod sprite_batch;
+pub mod gpu_buffer;
+pub mod frames;
+pub mod readback;
+pub mod picking;
+pub mod debug_view;
+pub mod shader_errors;
+pub mod gpu_validation;
+pub mod frame_capture;
+pub mod text;
+pub mod ui;
+pub mod state;
+pub mod loading;
@@ -0,0 +1,235 @@
+// src/light_cluster.rs
+use crate::bind_cache::{BindGroupCache, BindingKey};
+use crate::resource_registry::{ResourceRegistry, Tracked};
+use crate::upload::{DynamicBuffer, UploadBelt};
+use glam::{Mat4, UVec2, Vec3};
+
+// Prepend to a forward fragment shader to get `shade_clustered_lights`
+pub const CLUSTERED_SHADING_WGSL: &str = include_str!("clustered_lights.wgsl");
+// Bind group index the shading helpers expect
+pub const LIGHT_GROUP: u32 = 1;
+
+// Mirrors `PointLight` in light_cluster.wgsl and clustered_lights.wgsl
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct PointLight {
+    pub position: [f32; 3],
+    pub range: f32,
+    pub color: [f32; 3],
+    pub intensity: f32,
+}
+
+impl PointLight {
+    pub fn new(position: Vec3, range: f32, color: Vec3, intensity: f32) -> Self {
+        Self { position: position.into(), range, color: color.into(), intensity }
+    }
+}
+
+// Mirrors `ClusterParams` in both shaders
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct ClusterParams {
+    view: [f32; 16],
+    inv_proj: [f32; 16],
+    screen_size: [f32; 2],
+    near: f32,
+    far: f32,
+    grid: [u32; 3],
+    light_count: u32,
+    max_lights_per_cluster: u32,
+    _pad: [u32; 3],
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClusterConfig {
+    // Screen tiles across and down, and exponential depth slices between near and far
+    pub tiles_x: u32,
+    pub tiles_y: u32,
+    pub slices: u32,
+    // Lights past this in one cluster are dropped, so keep it above the expected overlap
+    pub max_lights_per_cluster: u32,
+}
+
+impl Default for ClusterConfig {
+    fn default() -> Self {
+        Self { tiles_x: 16, tiles_y: 9, slices: 24, max_lights_per_cluster: 64 }
+    }
+}
+
+impl ClusterConfig {
+    pub fn cluster_count(&self) -> u32 {
+        self.tiles_x * self.tiles_y * self.slices
+    }
+}
+
+// Perspective camera the clusters are built for; `near`/`far` must match `projection`
+#[derive(Debug, Clone, Copy)]
+pub struct ClusterCamera {
+    pub view: Mat4,
+    pub projection: Mat4,
+    pub near: f32,
+    pub far: f32,
+    pub screen_size: UVec2,
+}
+
+const WORKGROUP_SIZE: u32 = 64;
+
+// Forward+ light culling: a compute pass bins lights into a froxel grid each frame, so
+// fragments only loop over the few lights that can reach their cluster.
+pub struct ClusteredLights {
+    config: ClusterConfig,
+    lights: DynamicBuffer,
+    params: DynamicBuffer,
+    counts: Tracked<wgpu::Buffer>,
+    indices: Tracked<wgpu::Buffer>,
+    cull_layout: wgpu::BindGroupLayout,
+    shading_layout: wgpu::BindGroupLayout,
+    pipeline: wgpu::ComputePipeline,
+    light_count: u32,
+}
+
+impl ClusteredLights {
+    pub fn new(device: &wgpu::Device, resources: &ResourceRegistry, config: ClusterConfig) -> Result<Self, String> {
+        if config.cluster_count() == 0 || config.max_lights_per_cluster == 0 {
+            return Err("Cluster grid and per-cluster light limit must be non-zero".to_string());
+        }
+        let counts = resources.create_buffer(device, &wgpu::BufferDescriptor {
+            label: Some("cluster light counts"),
+            size: config.cluster_count() as u64 * 4,
+            usage: wgpu::BufferUsages::STORAGE,
+            mapped_at_creation: false,
+        }, "light clusters");
+        let indices = resources.create_buffer(device, &wgpu::BufferDescriptor {
+            label: Some("cluster light indices"),
+            size: config.cluster_count() as u64 * config.max_lights_per_cluster as u64 * 4,
+            usage: wgpu::BufferUsages::STORAGE,
+            mapped_at_creation: false,
+        }, "light clusters");
+
+        let layout = |label, visibility, writable: bool| {
+            let entry = |binding, ty| wgpu::BindGroupLayoutEntry {
+                binding,
+                visibility,
+                ty: wgpu::BindingType::Buffer { ty, has_dynamic_offset: false, min_binding_size: None },
+                count: None,
+            };
+            let output = wgpu::BufferBindingType::Storage { read_only: !writable };
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some(label),
+                entries: &[
+                    entry(0, wgpu::BufferBindingType::Uniform),
+                    entry(1, wgpu::BufferBindingType::Storage { read_only: true }),
+                    entry(2, output),
+                    entry(3, output),
+                ],
+            })
+        };
+        let cull_layout = layout("light cluster cull", wgpu::ShaderStages::COMPUTE, true);
+        let shading_layout = layout("clustered lights", wgpu::ShaderStages::FRAGMENT, false);
+
+        let shader = device.create_shader_module(wgpu::include_wgsl!("light_cluster.wgsl"));
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("light cluster cull"),
+            bind_group_layouts: &[&cull_layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("light cluster cull"),
+            layout: Some(&pipeline_layout),
+            module: &shader,
+            entry_point: Some("cull_lights"),
+            compilation_options: Default::default(),
+            cache: None,
+        });
+
+        Ok(Self {
+            config,
+            lights: DynamicBuffer::new(
+                device,
+                resources,
+                "point lights",
+                wgpu::BufferUsages::STORAGE,
+                std::mem::size_of::<PointLight>() as u64 * 64,
+            ),
+            params: DynamicBuffer::new(
+                device,
+                resources,
+                "cluster params",
+                wgpu::BufferUsages::UNIFORM,
+                std::mem::size_of::<ClusterParams>() as u64,
+            ),
+            counts,
+            indices,
+            cull_layout,
+            shading_layout,
+            pipeline,
+            light_count: 0,
+        })
+    }
+
+    pub fn config(&self) -> ClusterConfig {
+        self.config
+    }
+
+    pub fn light_count(&self) -> u32 {
+        self.light_count
+    }
+
+    // For the forward pipeline layout, at `LIGHT_GROUP`
+    pub fn shading_layout(&self) -> &wgpu::BindGroupLayout {
+        &self.shading_layout
+    }
+
+    pub fn shading_bind_group(&self, device: &wgpu::Device, bind_groups: &mut BindGroupCache) -> wgpu::BindGroup {
+        bind_groups.get_or_create(device, "clustered lights", &self.shading_layout, &self.bindings())
+    }
+
+    // Uploads `lights` and rebins them for `camera`; record before the forward pass
+    #[allow(clippy::too_many_arguments)]
+    pub fn cull(
+        &mut self,
+        device: &wgpu::Device,
+        resources: &ResourceRegistry,
+        encoder: &mut wgpu::CommandEncoder,
+        belt: &mut UploadBelt,
+        bind_groups: &mut BindGroupCache,
+        camera: &ClusterCamera,
+        lights: &[PointLight],
+    ) {
+        self.light_count = lights.len() as u32;
+        if !lights.is_empty() {
+            self.lights.upload(device, resources, encoder, belt, bytemuck::cast_slice(lights));
+        }
+        let params = ClusterParams {
+            view: camera.view.to_cols_array(),
+            inv_proj: camera.projection.inverse().to_cols_array(),
+            screen_size: camera.screen_size.max(UVec2::ONE).as_vec2().into(),
+            near: camera.near,
+            far: camera.far,
+            grid: [self.config.tiles_x, self.config.tiles_y, self.config.slices],
+            light_count: self.light_count,
+            max_lights_per_cluster: self.config.max_lights_per_cluster,
+            _pad: [0; 3],
+        };
+        self.params.upload(device, resources, encoder, belt, bytemuck::bytes_of(&params));
+
+        // Runs even without lights so last frame's counts get cleared
+        let bind_group = bind_groups.get_or_create(device, "light cluster cull", &self.cull_layout, &self.bindings());
+        let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+            label: Some("light cluster cull"),
+            timestamp_writes: None,
+        });
+        pass.set_pipeline(&self.pipeline);
+        pass.set_bind_group(0, &bind_group, &[]);
+        pass.dispatch_workgroups(self.config.cluster_count().div_ceil(WORKGROUP_SIZE), 1, 1);
+    }
+
+    fn bindings(&self) -> [(u32, BindingKey); 4] {
+        [
+            (0, BindingKey::buffer(self.params.buffer())),
+            (1, BindingKey::buffer(self.lights.buffer())),
+            (2, BindingKey::buffer(&self.counts)),
+            (3, BindingKey::buffer(&self.indices)),
+        ]
+    }
+}
@@ -0,0 +1,78 @@
+// Clustered light culling: one invocation per view-space cluster writes the lights
+// whose range sphere touches the cluster's bounding box
+
+struct PointLight {
+    position: vec3<f32>,
+    range: f32,
+    color: vec3<f32>,
+    intensity: f32,
+}
+
+struct ClusterParams {
+    view: mat4x4<f32>,
+    inv_proj: mat4x4<f32>,
+    screen_size: vec2<f32>,
+    near: f32,
+    far: f32,
+    grid: vec3<u32>,
+    light_count: u32,
+    max_lights_per_cluster: u32,
+}
+
+@group(0) @binding(0) var<uniform> params: ClusterParams;
+@group(0) @binding(1) var<storage, read> lights: array<PointLight>;
+@group(0) @binding(2) var<storage, read_write> cluster_counts: array<u32>;
+@group(0) @binding(3) var<storage, read_write> cluster_lights: array<u32>;
+
+// Point on the near plane under a screen position, in view space
+fn screen_to_view(screen: vec2<f32>) -> vec3<f32> {
+    let ndc = vec2<f32>(screen.x / params.screen_size.x * 2.0 - 1.0, 1.0 - screen.y / params.screen_size.y * 2.0);
+    let view = params.inv_proj * vec4<f32>(ndc, 0.0, 1.0);
+    return view.xyz / view.w;
+}
+
+// Where the eye ray through `point` crosses the plane at view depth `depth`
+fn at_depth(point: vec3<f32>, depth: f32) -> vec3<f32> {
+    return point * (depth / -point.z);
+}
+
+@compute @workgroup_size(64)
+fn cull_lights(@builtin(global_invocation_id) id: vec3<u32>) {
+    let cluster = id.x;
+    let cluster_total = params.grid.x * params.grid.y * params.grid.z;
+    if (cluster >= cluster_total) {
+        return;
+    }
+    let x = cluster % params.grid.x;
+    let y = (cluster / params.grid.x) % params.grid.y;
+    let z = cluster / (params.grid.x * params.grid.y);
+
+    // Exponential slices keep clusters roughly cubic along the view direction
+    let depth_ratio = params.far / params.near;
+    let slice_near = params.near * pow(depth_ratio, f32(z) / f32(params.grid.z));
+    let slice_far = params.near * pow(depth_ratio, f32(z + 1u) / f32(params.grid.z));
+
+    let tile_size = params.screen_size / vec2<f32>(params.grid.xy);
+    let corner_min = screen_to_view(vec2<f32>(f32(x), f32(y + 1u)) * tile_size);
+    let corner_max = screen_to_view(vec2<f32>(f32(x + 1u), f32(y)) * tile_size);
+    let a = at_depth(corner_min, slice_near);
+    let b = at_depth(corner_max, slice_near);
+    let c = at_depth(corner_min, slice_far);
+    let d = at_depth(corner_max, slice_far);
+    let box_min = min(min(a, b), min(c, d));
+    let box_max = max(max(a, b), max(c, d));
+
+    var count = 0u;
+    let base = cluster * params.max_lights_per_cluster;
+    for (var i = 0u; i < params.light_count && count < params.max_lights_per_cluster; i++) {
+        let light = lights[i];
+        let center = (params.view * vec4<f32>(light.position, 1.0)).xyz;
+        let closest = clamp(center, box_min, box_max);
+        let offset = center - closest;
+        if (dot(offset, offset) <= light.range * light.range) {
+            cluster_lights[base + count] = i;
+            count++;
+        }
+    }
+    cluster_counts[cluster] = count;
+}
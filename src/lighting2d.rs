@@ -0,0 +1,440 @@
+// src/lighting2d.rs
+use crate::bind_cache::{BindGroupCache, BindingKey, SamplerCache};
+use crate::math::{Color, Mat4, Vec2};
+use crate::physics::collision::Collider;
+use crate::physics::shape::Shape;
+use crate::resource_registry::{ResourceRegistry, Tracked};
+use crate::upload::{DynamicBuffer, UploadBelt};
+use std::f32::consts::{PI, TAU};
+
+// Angular bins per light in the shadow map; must match lighting2d.wgsl
+pub const SHADOW_RESOLUTION: usize = 512;
+// Shadowed lights per frame; lights past this still shine, without shadows
+pub const MAX_SHADOWED_LIGHTS: usize = 64;
+
+pub const LIGHTMAP_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba16Float;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Light2D {
+    pub position: Vec2,
+    pub radius: f32,
+    pub color: Color,
+    pub intensity: f32,
+    // Size of the light's body; larger gives softer shadow edges
+    pub source_radius: f32,
+    pub shadows: bool,
+}
+
+impl Light2D {
+    pub fn new(position: Vec2, radius: f32, color: Color, intensity: f32) -> Self {
+        Self { position, radius, color, intensity, source_radius: radius * 0.05, shadows: true }
+    }
+}
+
+// Component outlining what blocks light: a polyline, closed into a polygon unless `open`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Occluder2D {
+    pub points: Vec<Vec2>,
+    pub open: bool,
+}
+
+impl Occluder2D {
+    pub fn polygon(points: Vec<Vec2>) -> Self {
+        Self { points, open: false }
+    }
+
+    // A single wall edge or ledge
+    pub fn line(a: Vec2, b: Vec2) -> Self {
+        Self { points: vec![a, b], open: true }
+    }
+
+    // Outline of a collision shape, with curves split into short edges
+    pub fn from_shape(shape: &Shape, position: Vec2) -> Self {
+        const ARC_SEGMENTS: u32 = 8;
+        if let Shape::Polygon(polygon) = shape {
+            return Self::polygon(polygon.points().iter().map(|p| position + *p).collect());
+        }
+        let (half, radius) = shape.rounded_rect();
+        if radius <= 0.0 {
+            return Self::polygon(
+                [Vec2::new(-1.0, -1.0), Vec2::new(1.0, -1.0), Vec2::new(1.0, 1.0), Vec2::new(-1.0, 1.0)]
+                    .map(|c| position + c * half)
+                    .to_vec(),
+            );
+        }
+        // One quarter arc per corner of the inner rectangle
+        let corners = [Vec2::new(1.0, 1.0), Vec2::new(-1.0, 1.0), Vec2::new(-1.0, -1.0), Vec2::new(1.0, -1.0)];
+        let mut points = Vec::new();
+        for (quarter, corner) in corners.iter().enumerate() {
+            for step in 0..=ARC_SEGMENTS {
+                let angle = (quarter as f32 + step as f32 / ARC_SEGMENTS as f32) * PI * 0.5;
+                points.push(position + *corner * half + Vec2::from_angle(angle) * radius);
+            }
+        }
+        points.dedup_by(|a, b| a.distance_squared(*b) < 1e-8);
+        Self::polygon(points)
+    }
+
+    pub fn from_collider(collider: &Collider) -> Self {
+        Self::from_shape(&collider.shape, collider.position)
+    }
+
+    pub fn segments(&self) -> impl Iterator<Item = (Vec2, Vec2)> + '_ {
+        let count = self.points.len();
+        let closing = if self.open || count < 3 { 0 } else { 1 };
+        (0..(count.saturating_sub(1) + closing)).map(move |i| (self.points[i], self.points[(i + 1) % count]))
+    }
+}
+
+// Distance from `light` to the nearest occluder edge in each of `SHADOW_RESOLUTION`
+// directions, starting at -PI like `atan2`; `light.radius` where nothing blocks
+pub fn shadow_map(light: &Light2D, occluders: &[Occluder2D], distances: &mut [f32]) {
+    distances.fill(light.radius);
+    let bins = distances.len();
+    let bin_angle = TAU / bins as f32;
+    for (a, b) in occluders.iter().flat_map(|o| o.segments()) {
+        let (a, b) = (a - light.position, b - light.position);
+        // Skip edges entirely out of reach
+        let edge = b - a;
+        let t = (-a.dot(edge) / edge.length_squared().max(f32::EPSILON)).clamp(0.0, 1.0);
+        if (a + edge * t).length() >= light.radius {
+            continue;
+        }
+        // Bins the edge covers, going the short way round
+        let (angle_a, angle_b) = (a.y.atan2(a.x), b.y.atan2(b.x));
+        let mut span = angle_b - angle_a;
+        if span > PI {
+            span -= TAU;
+        } else if span < -PI {
+            span += TAU;
+        }
+        let start = angle_a.min(angle_a + span);
+        let first = ((start + PI) / bin_angle - 0.5).ceil() as i64;
+        let last = ((start + span.abs() + PI) / bin_angle - 0.5).floor() as i64;
+        for bin in first..=last {
+            let index = bin.rem_euclid(bins as i64) as usize;
+            let direction = Vec2::from_angle((index as f32 + 0.5) * bin_angle - PI);
+            // Ray from the light against the edge
+            let denom = direction.perp_dot(edge);
+            if denom.abs() < f32::EPSILON {
+                continue;
+            }
+            let distance = a.perp_dot(edge) / denom;
+            if distance >= 0.0 && distance < distances[index] {
+                distances[index] = distance;
+            }
+        }
+    }
+}
+
+// One light per instance, at vertex slot 0 of the light pass
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct LightInstance {
+    position: [f32; 2],
+    radius: f32,
+    source_radius: f32,
+    color: [f32; 3],
+    shadow_row: i32,
+}
+
+impl LightInstance {
+    const ATTRIBUTES: [wgpu::VertexAttribute; 5] =
+        wgpu::vertex_attr_array![0 => Float32x2, 1 => Float32, 2 => Float32, 3 => Float32x3, 4 => Sint32];
+}
+
+// Mirrors `LightParams` in lighting2d.wgsl
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct LightParams {
+    view_proj: [f32; 16],
+}
+
+// Point lights for 2D scenes with soft shadows behind occluders. Each frame: `prepare`
+// builds every shadowed light's polar shadow map, `render_lightmap` accumulates the lights
+// over `ambient`, and `composite` multiplies the result over the already drawn scene.
+pub struct Lighting2D {
+    pub ambient: Color,
+    pub lights: Vec<Light2D>,
+    size: (u32, u32),
+    _lightmap: Tracked<wgpu::Texture>,
+    lightmap_view: wgpu::TextureView,
+    instances: DynamicBuffer,
+    light_count: u32,
+    shadow_buffer: Tracked<wgpu::Buffer>,
+    shadow_scratch: Vec<f32>,
+    params_buffer: Tracked<wgpu::Buffer>,
+    light_layout: wgpu::BindGroupLayout,
+    light_pipeline: wgpu::RenderPipeline,
+    composite_layout: wgpu::BindGroupLayout,
+    composite_pipeline: wgpu::RenderPipeline,
+}
+
+impl Lighting2D {
+    // `color_format` is the scene target `composite` draws into
+    pub fn new(device: &wgpu::Device, resources: &ResourceRegistry, color_format: wgpu::TextureFormat, width: u32, height: u32) -> Self {
+        let shader = device.create_shader_module(wgpu::include_wgsl!("lighting2d.wgsl"));
+        let light_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("2d lights"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::VERTEX,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Storage { read_only: true },
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+            ],
+        });
+        let composite_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("2d light composite"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 3,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+        });
+        let pipeline = |label, layout: &wgpu::BindGroupLayout, vertex, fragment, buffers: &[wgpu::VertexBufferLayout], format, blend| {
+            let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some(label),
+                bind_group_layouts: &[layout],
+                push_constant_ranges: &[],
+            });
+            device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some(label),
+                layout: Some(&pipeline_layout),
+                vertex: wgpu::VertexState {
+                    module: &shader,
+                    entry_point: Some(vertex),
+                    buffers,
+                    compilation_options: Default::default(),
+                },
+                fragment: Some(wgpu::FragmentState {
+                    module: &shader,
+                    entry_point: Some(fragment),
+                    targets: &[Some(wgpu::ColorTargetState { format, blend: Some(blend), write_mask: wgpu::ColorWrites::ALL })],
+                    compilation_options: Default::default(),
+                }),
+                primitive: wgpu::PrimitiveState::default(),
+                depth_stencil: None,
+                multisample: wgpu::MultisampleState::default(),
+                multiview: None,
+                cache: None,
+            })
+        };
+        let additive = wgpu::BlendComponent {
+            src_factor: wgpu::BlendFactor::One,
+            dst_factor: wgpu::BlendFactor::One,
+            operation: wgpu::BlendOperation::Add,
+        };
+        let light_pipeline = pipeline(
+            "2d lights",
+            &light_layout,
+            "vs_light",
+            "fs_light",
+            &[wgpu::VertexBufferLayout {
+                array_stride: std::mem::size_of::<LightInstance>() as wgpu::BufferAddress,
+                step_mode: wgpu::VertexStepMode::Instance,
+                attributes: &LightInstance::ATTRIBUTES,
+            }],
+            LIGHTMAP_FORMAT,
+            wgpu::BlendState { color: additive, alpha: additive },
+        );
+        // Scene colour times light
+        let multiply = wgpu::BlendComponent {
+            src_factor: wgpu::BlendFactor::Dst,
+            dst_factor: wgpu::BlendFactor::Zero,
+            operation: wgpu::BlendOperation::Add,
+        };
+        let composite_pipeline = pipeline(
+            "2d light composite",
+            &composite_layout,
+            "vs_composite",
+            "fs_composite",
+            &[],
+            color_format,
+            wgpu::BlendState { color: multiply, alpha: wgpu::BlendComponent::OVER },
+        );
+
+        let shadow_buffer = resources.create_buffer(device, &wgpu::BufferDescriptor {
+            label: Some("2d shadow maps"),
+            size: (SHADOW_RESOLUTION * MAX_SHADOWED_LIGHTS * std::mem::size_of::<f32>()) as u64,
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        }, "lighting2d");
+        let params_buffer = resources.create_buffer(device, &wgpu::BufferDescriptor {
+            label: Some("2d light params"),
+            size: std::mem::size_of::<LightParams>() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        }, "lighting2d");
+        let instances = DynamicBuffer::new(device, resources, "2d light instances", wgpu::BufferUsages::VERTEX, 4096);
+        let size = (width.max(1), height.max(1));
+        let lightmap = Self::create_lightmap(device, resources, size);
+        Self {
+            ambient: Color::rgb(0.1, 0.1, 0.1),
+            lights: Vec::new(),
+            size,
+            lightmap_view: lightmap.create_view(&wgpu::TextureViewDescriptor::default()),
+            _lightmap: lightmap,
+            instances,
+            light_count: 0,
+            shadow_buffer,
+            shadow_scratch: Vec::new(),
+            params_buffer,
+            light_layout,
+            light_pipeline,
+            composite_layout,
+            composite_pipeline,
+        }
+    }
+
+    fn create_lightmap(device: &wgpu::Device, resources: &ResourceRegistry, size: (u32, u32)) -> Tracked<wgpu::Texture> {
+        resources.create_texture(device, &wgpu::TextureDescriptor {
+            label: Some("2d lightmap"),
+            size: wgpu::Extent3d { width: size.0, height: size.1, depth_or_array_layers: 1 },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: LIGHTMAP_FORMAT,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        }, "lighting2d")
+    }
+
+    pub fn resize(&mut self, device: &wgpu::Device, resources: &ResourceRegistry, width: u32, height: u32) {
+        let size = (width.max(1), height.max(1));
+        if size == self.size {
+            return;
+        }
+        self.size = size;
+        let lightmap = Self::create_lightmap(device, resources, size);
+        self.lightmap_view = lightmap.create_view(&wgpu::TextureViewDescriptor::default());
+        self._lightmap = lightmap;
+    }
+
+    // Accumulated light, e.g. for effects that want it without the multiply
+    pub fn lightmap_view(&self) -> &wgpu::TextureView {
+        &self.lightmap_view
+    }
+
+    // Builds shadow maps against `occluders` and uploads this frame's lights. `view_proj`
+    // maps world positions to clip space, as for the scene itself.
+    #[allow(clippy::too_many_arguments)]
+    pub fn prepare(
+        &mut self,
+        device: &wgpu::Device,
+        resources: &ResourceRegistry,
+        encoder: &mut wgpu::CommandEncoder,
+        belt: &mut UploadBelt,
+        view_proj: Mat4,
+        occluders: &[Occluder2D],
+    ) {
+        let mut instances = Vec::with_capacity(self.lights.len());
+        let mut rows = 0;
+        self.shadow_scratch.clear();
+        for light in &self.lights {
+            let shadowed = light.shadows && rows < MAX_SHADOWED_LIGHTS && !occluders.is_empty();
+            if shadowed {
+                let start = self.shadow_scratch.len();
+                self.shadow_scratch.resize(start + SHADOW_RESOLUTION, 0.0);
+                shadow_map(light, occluders, &mut self.shadow_scratch[start..]);
+            }
+            instances.push(LightInstance {
+                position: light.position.into(),
+                radius: light.radius,
+                source_radius: light.source_radius,
+                color: light.color.scaled(light.intensity).rgb_array(),
+                shadow_row: if shadowed { rows as i32 } else { -1 },
+            });
+            rows += shadowed as usize;
+        }
+        self.light_count = instances.len() as u32;
+        if !instances.is_empty() {
+            self.instances.upload(device, resources, encoder, belt, bytemuck::cast_slice(&instances));
+        }
+        if !self.shadow_scratch.is_empty() {
+            belt.write(device, encoder, &self.shadow_buffer, 0, bytemuck::cast_slice(&self.shadow_scratch));
+        }
+        let params = LightParams { view_proj: view_proj.to_cols_array() };
+        belt.write(device, encoder, &self.params_buffer, 0, bytemuck::bytes_of(&params));
+    }
+
+    pub fn render_lightmap(&self, device: &wgpu::Device, bind_groups: &mut BindGroupCache, encoder: &mut wgpu::CommandEncoder) {
+        let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("2d lightmap"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: &self.lightmap_view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(self.ambient.with_alpha(1.0).into()),
+                    store: wgpu::StoreOp::Store,
+                },
+                depth_slice: None,
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        if self.light_count == 0 {
+            return;
+        }
+        let bind_group = bind_groups.get_or_create(device, "2d lights", &self.light_layout, &[
+            (0, BindingKey::buffer(&self.params_buffer)),
+            (1, BindingKey::buffer(&self.shadow_buffer)),
+        ]);
+        pass.set_pipeline(&self.light_pipeline);
+        pass.set_bind_group(0, &bind_group, &[]);
+        pass.set_vertex_buffer(0, self.instances.buffer().slice(..));
+        pass.draw(0..6, 0..self.light_count);
+    }
+
+    // Multiplies the lightmap over whatever `render_pass` has drawn
+    pub fn composite(
+        &self,
+        device: &wgpu::Device,
+        bind_groups: &mut BindGroupCache,
+        samplers: &mut SamplerCache,
+        render_pass: &mut wgpu::RenderPass<'_>,
+    ) {
+        let sampler = samplers.get(device, &wgpu::SamplerDescriptor {
+            label: Some("2d lightmap"),
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+        let bind_group = bind_groups.get_or_create(device, "2d light composite", &self.composite_layout, &[
+            (2, BindingKey::TextureView(self.lightmap_view.clone())),
+            (3, BindingKey::Sampler(sampler)),
+        ]);
+        render_pass.set_pipeline(&self.composite_pipeline);
+        render_pass.set_bind_group(0, &bind_group, &[]);
+        render_pass.draw(0..3, 0..1);
+    }
+}
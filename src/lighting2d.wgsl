@@ -0,0 +1,108 @@
+// 2D lighting: each light is a quad drawn additively into a lightmap cleared to the
+// ambient colour, shadowed by its row of a polar shadow map (distance to the nearest
+// occluder per angle). The lightmap is then multiplied over the scene.
+
+const SHADOW_RESOLUTION: u32 = 512u;
+const PI: f32 = 3.14159265;
+const TAPS: i32 = 9;
+
+struct LightParams {
+    view_proj: mat4x4<f32>,
+}
+
+@group(0) @binding(0) var<uniform> params: LightParams;
+@group(0) @binding(1) var<storage, read> shadow_map: array<f32>;
+
+struct LightInput {
+    @location(0) position: vec2<f32>,
+    @location(1) radius: f32,
+    @location(2) source_radius: f32,
+    @location(3) color: vec3<f32>,
+    // -1 for lights that don't cast shadows
+    @location(4) shadow_row: i32,
+}
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    // Offset from the light, in world units
+    @location(0) offset: vec2<f32>,
+    @location(1) color: vec3<f32>,
+    @location(2) @interpolate(flat) radius: f32,
+    @location(3) @interpolate(flat) source_radius: f32,
+    @location(4) @interpolate(flat) shadow_row: i32,
+}
+
+@vertex
+fn vs_light(@builtin(vertex_index) index: u32, light: LightInput) -> VertexOutput {
+    let corners = array<vec2<f32>, 6>(
+        vec2<f32>(-1.0, -1.0), vec2<f32>(1.0, -1.0), vec2<f32>(1.0, 1.0),
+        vec2<f32>(-1.0, -1.0), vec2<f32>(1.0, 1.0), vec2<f32>(-1.0, 1.0),
+    );
+    var out: VertexOutput;
+    out.offset = corners[index] * light.radius;
+    out.position = params.view_proj * vec4<f32>(light.position + out.offset, 0.0, 1.0);
+    out.color = light.color;
+    out.radius = light.radius;
+    out.source_radius = light.source_radius;
+    out.shadow_row = light.shadow_row;
+    return out;
+}
+
+// Occluder distance at `angle`, linearly interpolated between bins
+fn occluder_distance(row: i32, angle: f32) -> f32 {
+    let bin = (angle + PI) / (2.0 * PI) * f32(SHADOW_RESOLUTION) - 0.5;
+    let base = floor(bin);
+    let i0 = u32(i32(base) + i32(SHADOW_RESOLUTION)) % SHADOW_RESOLUTION;
+    let i1 = (i0 + 1u) % SHADOW_RESOLUTION;
+    let offset = u32(row) * SHADOW_RESOLUTION;
+    return mix(shadow_map[offset + i0], shadow_map[offset + i1], bin - base);
+}
+
+@fragment
+fn fs_light(in: VertexOutput) -> @location(0) vec4<f32> {
+    let distance = length(in.offset);
+    if distance >= in.radius {
+        discard;
+    }
+    let falloff = 1.0 - distance / in.radius;
+    var visibility = 1.0;
+    if in.shadow_row >= 0 {
+        let angle = atan2(in.offset.y, in.offset.x);
+        // A light with a body casts a penumbra that widens behind the occluder
+        let blocker = max(occluder_distance(in.shadow_row, angle), 1e-3);
+        var spread = 0.0;
+        if distance > blocker {
+            spread = min(in.source_radius * (distance - blocker) / (blocker * max(distance, 1e-3)), 0.3);
+        }
+        var lit = 0.0;
+        for (var i = 0; i < TAPS; i++) {
+            let t = f32(i) / f32(TAPS - 1) * 2.0 - 1.0;
+            lit += step(distance, occluder_distance(in.shadow_row, angle + t * spread) + 0.01);
+        }
+        visibility = lit / f32(TAPS);
+    }
+    return vec4<f32>(in.color * falloff * falloff * visibility, 1.0);
+}
+
+// The composite pass has its own layout; the bindings just can't reuse the numbers above
+@group(0) @binding(2) var lightmap: texture_2d<f32>;
+@group(0) @binding(3) var lightmap_sampler: sampler;
+
+struct CompositeOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+}
+
+@vertex
+fn vs_composite(@builtin(vertex_index) index: u32) -> CompositeOutput {
+    let uv = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+    var out: CompositeOutput;
+    out.position = vec4<f32>(uv * vec2<f32>(2.0, -2.0) + vec2<f32>(-1.0, 1.0), 0.0, 1.0);
+    out.uv = uv;
+    return out;
+}
+
+@fragment
+fn fs_composite(in: CompositeOutput) -> @location(0) vec4<f32> {
+    return vec4<f32>(textureSample(lightmap, lightmap_sampler, in.uv).rgb, 1.0);
+}
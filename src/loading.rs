@@ -0,0 +1,318 @@
+// src/loading.rs
+use crate::color::Color;
+use crate::ecs::resources::WindowInfo;
+use crate::ecs::world::World;
+use crate::math::Rect;
+use crate::state::{GameState, StateStack};
+use crate::ui::{ScreenTransition, Tween, Ui, UiAnimation, UiValue, Widget, WidgetId, WidgetKind};
+use glam::Vec2;
+use std::f32::consts::TAU;
+use std::sync::mpsc::{self, TryRecvError};
+
+// Used to lay out the loading screen before the window has reported its size
+const FALLBACK_SCREEN_SIZE: Vec2 = Vec2::new(1280.0, 720.0);
+// Spinner turns per second
+const SPINNER_SPEED: f32 = 1.5;
+
+type Poll = Box<dyn FnMut(&mut World) -> Result<f32, String>>;
+
+// A piece of loading work the `Loader` polls once a frame until it reports 1.0 or fails
+pub struct LoadTask {
+    name: String,
+    // Share of the overall progress bar, relative to the other tasks loading with it
+    weight: f32,
+    poll: Poll,
+    progress: f32,
+}
+
+impl LoadTask {
+    // `poll` does a slice of work on the main thread and returns how far along it is,
+    // 0..=1, e.g. spawning a level's entities a chunk at a time
+    pub fn new(name: &str, poll: impl FnMut(&mut World) -> Result<f32, String> + 'static) -> Self {
+        Self { name: name.to_string(), weight: 1.0, poll: Box::new(poll), progress: 0.0 }
+    }
+
+    // Runs `work` on its own thread straight away and hands its result to `finish` on the
+    // main thread once it is ready, e.g. decoding a texture and then uploading it
+    pub fn background<T: Send + 'static>(
+        name: &str,
+        work: impl FnOnce() -> Result<T, String> + Send + 'static,
+        finish: impl FnOnce(T, &mut World) + 'static,
+    ) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        std::thread::spawn(move || {
+            // Nobody is waiting any more if the receiver is gone
+            let _ = sender.send(work());
+        });
+        let mut finish = Some(finish);
+        Self::new(name, move |world| match receiver.try_recv() {
+            Ok(result) => {
+                let value = result?;
+                if let Some(finish) = finish.take() {
+                    finish(value, world);
+                }
+                Ok(1.0)
+            }
+            Err(TryRecvError::Empty) => Ok(0.0),
+            Err(TryRecvError::Disconnected) => Err("Loading thread stopped without a result".to_string()),
+        })
+    }
+
+    // Reads `path` off the main thread
+    pub fn file(path: &str, finish: impl FnOnce(Vec<u8>, &mut World) + 'static) -> Self {
+        let owned = path.to_string();
+        Self::background(path, move || std::fs::read(&owned).map_err(|e| format!("Failed to read {}: {}", owned, e)), finish)
+    }
+
+    pub fn with_weight(mut self, weight: f32) -> Self {
+        self.weight = weight.max(0.0);
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+// Resource running load tasks from anywhere: scene preloads, assets a state requests on
+// entering, streaming. Progress covers every task added since the loader was last idle.
+#[derive(Default)]
+pub struct Loader {
+    tasks: Vec<LoadTask>,
+    // Of the current batch
+    total_weight: f32,
+    finished_weight: f32,
+    errors: Vec<String>,
+}
+
+impl Loader {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&mut self, task: LoadTask) {
+        if self.tasks.is_empty() {
+            self.total_weight = 0.0;
+            self.finished_weight = 0.0;
+        }
+        self.total_weight += task.weight;
+        self.tasks.push(task);
+    }
+
+    pub fn is_idle(&self) -> bool {
+        self.tasks.is_empty()
+    }
+
+    // 0..=1 over the current batch; 1 while idle
+    pub fn progress(&self) -> f32 {
+        if self.tasks.is_empty() || self.total_weight <= 0.0 {
+            return 1.0;
+        }
+        let running: f32 = self.tasks.iter().map(|task| task.progress * task.weight).sum();
+        ((self.finished_weight + running) / self.total_weight).clamp(0.0, 1.0)
+    }
+
+    // The oldest unfinished task, for a status line
+    pub fn current(&self) -> Option<&str> {
+        self.tasks.first().map(LoadTask::name)
+    }
+
+    pub fn pending(&self) -> usize {
+        self.tasks.len()
+    }
+
+    // Failed tasks, as "name: error"; they count as finished so loading still completes
+    pub fn errors(&self) -> &[String] {
+        &self.errors
+    }
+
+    pub fn clear_errors(&mut self) {
+        self.errors.clear();
+    }
+
+    fn poll(&mut self, world: &mut World) {
+        let mut index = 0;
+        while index < self.tasks.len() {
+            let task = &mut self.tasks[index];
+            match (task.poll)(world) {
+                Ok(progress) if progress >= 1.0 => {
+                    log::debug!("Loaded '{}'", task.name);
+                    self.finished_weight += task.weight;
+                    self.tasks.remove(index);
+                }
+                Ok(progress) => {
+                    task.progress = progress.max(0.0);
+                    index += 1;
+                }
+                Err(e) => {
+                    log::error!("Loading '{}' failed: {}", task.name, e);
+                    self.errors.push(format!("{}: {}", task.name, e));
+                    self.finished_weight += task.weight;
+                    self.tasks.remove(index);
+                }
+            }
+        }
+    }
+}
+
+// System polling every queued `LoadTask`; runs before `update_states` so the loading
+// screen sees this frame's progress
+pub fn update_loader(world: &mut World, _delta_time: f64) {
+    // Taken out so tasks can reach the world
+    let Some(mut loader) = world.resources.remove::<Loader>() else { return };
+    loader.poll(world);
+    // Tasks added while polling went to a new loader
+    if let Some(added) = world.resources.remove::<Loader>() {
+        for task in added.tasks {
+            loader.add(task);
+        }
+        loader.errors.extend(added.errors);
+    }
+    world.resources.insert(loader);
+}
+
+// Resource configuring the built-in loading screen
+#[derive(Debug, Clone, PartialEq)]
+pub struct LoadingScreen {
+    // Shown one at a time, moving on every `tip_interval` seconds
+    pub tips: Vec<String>,
+    pub tip_interval: f32,
+    // Stays up at least this long, so quick loads don't flash
+    pub min_duration: f32,
+    // From the loading screen into the next state
+    pub transition: ScreenTransition,
+    pub background: Color,
+    // Where the next load's tips start, so consecutive loads don't repeat the same one
+    next_tip: usize,
+}
+
+impl Default for LoadingScreen {
+    fn default() -> Self {
+        Self {
+            tips: Vec::new(),
+            tip_interval: 5.0,
+            min_duration: 0.5,
+            transition: ScreenTransition::fade(0.5),
+            background: Color::BLACK,
+            next_tip: 0,
+        }
+    }
+}
+
+struct LoadingWidgets {
+    all: Vec<WidgetId>,
+    tip: WidgetId,
+    spinner: WidgetId,
+}
+
+// Built-in state shown while a scene switch loads: a progress bar and status line bound to
+// the `Loader`, a spinner and rotating tips. Once the loader is idle it replaces itself
+// with the state it was loading for.
+pub struct LoadingState {
+    next: Option<Box<dyn GameState>>,
+    preload: Vec<LoadTask>,
+    elapsed: f32,
+    first_tip: usize,
+    widgets: Option<LoadingWidgets>,
+}
+
+impl LoadingState {
+    pub fn new(next: Box<dyn GameState>, preload: Vec<LoadTask>) -> Self {
+        Self { next: Some(next), preload, elapsed: 0.0, first_tip: 0, widgets: None }
+    }
+
+    fn build_ui(ui: &mut Ui, screen: Vec2, background: Color) -> LoadingWidgets {
+        let mut backdrop = Widget::panel(Rect::new(Vec2::ZERO, screen));
+        backdrop.color = background;
+        let backdrop = ui.add(backdrop);
+
+        let bar_size = Vec2::new(screen.x * 0.6, 12.0);
+        let bar_min = Vec2::new((screen.x - bar_size.x) * 0.5, screen.y * 0.8);
+        let bar = ui.add(Widget::bar(Rect::new(bar_min, bar_min + bar_size), 0.0));
+        ui.bind_resource::<Loader>(bar, |loader| UiValue::Value(loader.progress()));
+
+        let status_min = bar_min - Vec2::new(0.0, 32.0);
+        let status = ui.add(Widget::label(Rect::new(status_min, status_min + Vec2::new(bar_size.x, 24.0)), "Loading"));
+        ui.bind_resource::<Loader>(status, |loader| {
+            UiValue::Text(loader.current().map_or("Loading".to_string(), |name| format!("Loading {}", name)))
+        });
+
+        let tip_min = bar_min + Vec2::new(0.0, 28.0);
+        let tip = ui.add(Widget::label(Rect::new(tip_min, tip_min + Vec2::new(bar_size.x, 48.0)), ""));
+
+        let spinner_size = Vec2::splat(48.0);
+        let spinner_min = screen - spinner_size - Vec2::splat(32.0);
+        let spinner = ui.add(Widget::spinner(Rect::new(spinner_min, spinner_min + spinner_size)));
+
+        LoadingWidgets { all: vec![backdrop, bar, status, tip, spinner], tip, spinner }
+    }
+}
+
+impl GameState for LoadingState {
+    fn name(&self) -> &str {
+        "loading"
+    }
+
+    fn enter(&mut self, world: &mut World) {
+        let loader = world.resources.get_or_insert_with(Loader::new);
+        for task in self.preload.drain(..) {
+            loader.add(task);
+        }
+        let settings = world.resources.get_or_insert_with(LoadingScreen::default);
+        self.first_tip = settings.next_tip;
+        settings.next_tip = settings.next_tip.wrapping_add(1);
+        let background = settings.background;
+        let screen = world
+            .resources
+            .get::<WindowInfo>()
+            .map(|info| info.metrics.logical_size())
+            .filter(|size| size.min_element() > 1.0)
+            .unwrap_or(FALLBACK_SCREEN_SIZE);
+        let ui = world.resources.get_or_insert_with(Ui::new);
+        let widgets = Self::build_ui(ui, screen, background);
+        let tip = widgets.tip;
+        ui.animate(UiAnimation::new().then(Tween::fade_in(tip, 0.3)));
+        self.widgets = Some(widgets);
+    }
+
+    fn exit(&mut self, world: &mut World) {
+        let (Some(widgets), Some(ui)) = (self.widgets.take(), world.resources.get_mut::<Ui>()) else { return };
+        for id in widgets.all {
+            ui.remove(id);
+        }
+    }
+
+    fn update(&mut self, world: &mut World, delta_time: f64) {
+        self.elapsed += delta_time as f32;
+        let settings = world.resources.get_or_insert_with(LoadingScreen::default).clone();
+        if let (Some(widgets), Some(ui)) = (&self.widgets, world.resources.get_mut::<Ui>()) {
+            let tip = (!settings.tips.is_empty()).then(|| {
+                let shown = (self.elapsed / settings.tip_interval.max(0.1)) as usize;
+                settings.tips[(self.first_tip + shown) % settings.tips.len()].clone()
+            });
+            if let (Some(tip), Some(widget)) = (tip, ui.get_mut(widgets.tip)) {
+                if let WidgetKind::Label { text } = &mut widget.kind {
+                    *text = tip;
+                }
+            }
+            if let Some(WidgetKind::Spinner { angle }) = ui.get_mut(widgets.spinner).map(|widget| &mut widget.kind) {
+                *angle = (*angle + delta_time as f32 * SPINNER_SPEED * TAU) % TAU;
+            }
+        }
+        let idle = world.resources.get::<Loader>().is_none_or(Loader::is_idle);
+        if idle && self.elapsed >= settings.min_duration {
+            if let (Some(next), Some(stack)) = (self.next.take(), world.resources.get_mut::<StateStack>()) {
+                stack.replace_boxed(next, Some(settings.transition));
+            }
+        }
+    }
+}
+
+impl StateStack {
+    // Switches to `next` through the loading screen: `transition` covers the old state, the
+    // loading screen shows until `preload` and anything else on the `Loader` is done, then
+    // `LoadingScreen::transition` brings in `next`
+    pub fn switch_to(&mut self, next: impl GameState + 'static, preload: Vec<LoadTask>, transition: ScreenTransition) {
+        self.replace_with(LoadingState::new(Box::new(next), preload), transition);
+    }
+}
@@ -0,0 +1,207 @@
+// src/lod.rs
+use crate::indirect::{CullObject, DrawIndexedArgs};
+use crate::mesh::{GpuMesh, Mesh, MeshVertex};
+use glam::{Vec2, Vec3};
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LodLevel {
+    pub args: DrawIndexedArgs,
+    // Smallest screen size (fraction of the view height) this level is drawn at
+    pub min_screen_size: f32,
+}
+
+// Levels from most to least detailed. Near a threshold the current level gets a margin of
+// `hysteresis` (as a fraction of the threshold) so objects don't flip levels every frame.
+#[derive(Debug, Clone)]
+pub struct LodChain {
+    levels: Vec<LodLevel>,
+    hysteresis: f32,
+}
+
+impl LodChain {
+    pub fn new(mut levels: Vec<LodLevel>, hysteresis: f32) -> Result<Self, String> {
+        if levels.is_empty() {
+            return Err("LOD chain needs at least one level".to_string());
+        }
+        levels.sort_by(|a, b| b.min_screen_size.total_cmp(&a.min_screen_size));
+        Ok(Self { levels, hysteresis: hysteresis.clamp(0.0, 0.9) })
+    }
+
+    pub fn levels(&self) -> &[LodLevel] {
+        &self.levels
+    }
+
+    pub fn args(&self, level: usize) -> DrawIndexedArgs {
+        self.levels[level.min(self.levels.len() - 1)].args
+    }
+
+    // Level for an object covering `screen_size` of the view, given the level it used last frame
+    pub fn select(&self, screen_size: f32, current: usize) -> usize {
+        let last = self.levels.len() - 1;
+        let current = current.min(last);
+        let threshold = |i: usize| {
+            let base = self.levels[i].min_screen_size;
+            match i.cmp(&current) {
+                std::cmp::Ordering::Less => base * (1.0 + self.hysteresis),
+                std::cmp::Ordering::Equal => base * (1.0 - self.hysteresis),
+                std::cmp::Ordering::Greater => base,
+            }
+        };
+        (0..=last).find(|&i| screen_size >= threshold(i)).unwrap_or(last)
+    }
+}
+
+// Fraction of the view height covered by a bounding sphere under a perspective projection
+pub fn screen_size(center: Vec3, radius: f32, camera: Vec3, fov_y: f32) -> f32 {
+    let distance = center.distance(camera);
+    if distance <= radius {
+        return f32::INFINITY;
+    }
+    radius / (distance * (fov_y * 0.5).tan())
+}
+
+// Same for 2D bounds against the view rect used by `GpuCuller`
+pub fn screen_size_2d(bounds_min: Vec2, bounds_max: Vec2, view_min: Vec2, view_max: Vec2) -> f32 {
+    let view_height = (view_max.y - view_min.y).abs().max(f32::EPSILON);
+    (bounds_max - bounds_min).abs().max_element() / view_height
+}
+
+// Vertex clustering: snaps vertices to a `resolution`^3 grid over the mesh bounds, merges
+// each cell into one averaged vertex and drops triangles that collapse. Cheap enough to
+// run at import time; coarse levels lose sharp features first.
+pub fn decimate(mesh: &Mesh, resolution: u32) -> Mesh {
+    let Some(first) = mesh.vertices.first() else { return Mesh::default() };
+    let (min, max) = mesh.vertices.iter().fold((Vec3::from(first.position), Vec3::from(first.position)), |(min, max), v| {
+        (min.min(v.position.into()), max.max(v.position.into()))
+    });
+    let resolution = resolution.max(1);
+    let cell_size = ((max - min) / resolution as f32).max(Vec3::splat(f32::EPSILON));
+
+    let mut cells: HashMap<[u32; 3], u32> = HashMap::new();
+    // Running sums per cluster: position, normal, uv, count
+    let mut sums: Vec<(Vec3, Vec3, Vec2, f32)> = Vec::new();
+    let remap: Vec<u32> = mesh.vertices.iter().map(|v| {
+        let cell = ((Vec3::from(v.position) - min) / cell_size).as_uvec3().min(glam::UVec3::splat(resolution - 1));
+        let cluster = *cells.entry(cell.to_array()).or_insert_with(|| {
+            sums.push((Vec3::ZERO, Vec3::ZERO, Vec2::ZERO, 0.0));
+            sums.len() as u32 - 1
+        });
+        let sum = &mut sums[cluster as usize];
+        sum.0 += Vec3::from(v.position);
+        sum.1 += Vec3::from(v.normal);
+        sum.2 += Vec2::from(v.uv);
+        sum.3 += 1.0;
+        cluster
+    }).collect();
+
+    let vertices = sums.into_iter().map(|(position, normal, uv, count)| MeshVertex {
+        position: (position / count).into(),
+        normal: normal.normalize_or_zero().into(),
+        uv: (uv / count).into(),
+    }).collect();
+    let indices = mesh.indices.chunks_exact(3)
+        .map(|tri| [remap[tri[0] as usize], remap[tri[1] as usize], remap[tri[2] as usize]])
+        .filter(|[a, b, c]| a != b && b != c && a != c)
+        .flatten()
+        .collect();
+    Mesh { vertices, indices }
+}
+
+// All levels of a mesh packed into one vertex/index buffer pair, so any mix of levels can
+// go through a single `IndirectDrawList`.
+pub struct LodMesh {
+    pub mesh: GpuMesh,
+    pub chain: LodChain,
+}
+
+impl LodMesh {
+    // `levels` pairs each mesh with its `min_screen_size`, most detailed first
+    pub fn upload(device: &wgpu::Device, label: &str, levels: &[(Mesh, f32)], hysteresis: f32) -> Result<Self, String> {
+        let mut packed = Mesh::default();
+        let mut lods = Vec::with_capacity(levels.len());
+        for (mesh, min_screen_size) in levels {
+            lods.push(LodLevel {
+                args: DrawIndexedArgs {
+                    index_count: mesh.indices.len() as u32,
+                    instance_count: 1,
+                    first_index: packed.indices.len() as u32,
+                    base_vertex: packed.vertices.len() as i32,
+                    first_instance: 0,
+                },
+                min_screen_size: *min_screen_size,
+            });
+            packed.vertices.extend_from_slice(&mesh.vertices);
+            packed.indices.extend_from_slice(&mesh.indices);
+        }
+        let chain = LodChain::new(lods, hysteresis)?;
+        Ok(Self { mesh: packed.upload(device, label), chain })
+    }
+
+    // Builds the chain from one source mesh: each extra level halves the clustering
+    // resolution, starting at `resolution`, and halves the screen size threshold.
+    pub fn generate(
+        device: &wgpu::Device,
+        label: &str,
+        source: &Mesh,
+        level_count: u32,
+        resolution: u32,
+        hysteresis: f32,
+    ) -> Result<Self, String> {
+        let mut levels = vec![(source.clone(), 0.25)];
+        for i in 1..level_count {
+            levels.push((decimate(source, (resolution >> (i - 1)).max(1)), 0.25 / (1 << i) as f32));
+        }
+        // The coarsest level catches everything smaller
+        if let Some(last) = levels.last_mut() {
+            last.1 = 0.0;
+        }
+        Self::upload(device, label, &levels, hysteresis)
+    }
+}
+
+// Remembers the level each object used last frame, which the hysteresis needs
+#[derive(Debug, Default)]
+pub struct LodSelector {
+    current: Vec<usize>,
+}
+
+impl LodSelector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn level(&self, object: usize) -> usize {
+        self.current.get(object).copied().unwrap_or(0)
+    }
+
+    pub fn select(&mut self, object: usize, chain: &LodChain, screen_size: f32) -> usize {
+        if object >= self.current.len() {
+            self.current.resize(object + 1, 0);
+        }
+        let level = chain.select(screen_size, self.current[object]);
+        self.current[object] = level;
+        level
+    }
+
+    // Picks the level for a 2D object and wraps it for `GpuCuller::cull`
+    #[allow(clippy::too_many_arguments)]
+    pub fn cull_object(
+        &mut self,
+        object: usize,
+        chain: &LodChain,
+        bounds_min: Vec2,
+        bounds_max: Vec2,
+        view_min: Vec2,
+        view_max: Vec2,
+        first_instance: u32,
+    ) -> CullObject {
+        let level = self.select(object, chain, screen_size_2d(bounds_min, bounds_max, view_min, view_max));
+        CullObject::new(bounds_min, bounds_max, chain.args(level).with_instances(first_instance, 1))
+    }
+
+    // Drops per-object state, e.g. when the object list is rebuilt in a different order
+    pub fn reset(&mut self) {
+        self.current.clear();
+    }
+}
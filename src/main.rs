@@ -1,18 +1,50 @@
 // src/main.rs
-mod window;
-mod renderer;
-mod game_loop;
-mod input;
-mod scene;
-mod app;
-
 use winit::event_loop::{EventLoop, ControlFlow};
-use app::VellumApp;
+use vellum::app::VellumApp;
+use vellum::bench::{self, BenchScript};
 
 fn main() {
     env_logger::init();
+    let args: Vec<String> = std::env::args().collect();
+    let bench_script = match args.iter().position(|arg| arg == "--bench") {
+        Some(i) => {
+            let Some(path) = args.get(i + 1) else {
+                eprintln!("Usage: {} --bench <scene.ron>", args[0]);
+                std::process::exit(2);
+            };
+            match BenchScript::load(path) {
+                Ok(script) => Some(script),
+                Err(e) => {
+                    eprintln!("{}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
+        None => None,
+    };
+
+    if let Some(script) = bench_script.as_ref().filter(|script| script.headless) {
+        if let Err(e) = bench::run_headless(script.clone()) {
+            eprintln!("Bench failed: {}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+
     let event_loop = EventLoop::new().expect("Failed to create event loop");
+    // The app's frame pacer switches this to `WaitUntil` its next frame
     event_loop.set_control_flow(ControlFlow::Poll);
-    let mut app = VellumApp::new();
+    #[allow(unused_mut)]
+    let mut app = match bench_script {
+        Some(script) => VellumApp::with_bench(script),
+        None => VellumApp::new(),
+    };
+    #[cfg(feature = "hot-reload")]
+    if let Some(path) = args.iter().position(|arg| arg == "--game-lib").and_then(|i| args.get(i + 1)) {
+        if let Err(e) = app.load_game_library(path) {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    }
     let _ = event_loop.run_app(&mut app);
-}
\ No newline at end of file
+}
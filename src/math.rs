@@ -0,0 +1,194 @@
+// src/math.rs
+// Engine maths on top of glam. Gameplay and engine APIs take these types; plain arrays
+// are kept only for GPU vertex/uniform layouts and RON data files.
+pub use crate::color::Color;
+pub use glam::{Mat3, Mat4, Quat, Vec2, Vec3, Vec4};
+
+// Axis-aligned rectangle in world units, +Y up
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Rect {
+    pub min: Vec2,
+    pub max: Vec2,
+}
+
+impl Rect {
+    pub fn new(min: Vec2, max: Vec2) -> Self {
+        Self { min: min.min(max), max: min.max(max) }
+    }
+
+    pub fn from_center_size(center: Vec2, size: Vec2) -> Self {
+        let half = size.abs() * 0.5;
+        Self { min: center - half, max: center + half }
+    }
+
+    // Smallest rectangle holding every point; `None` for no points
+    pub fn from_points(points: impl IntoIterator<Item = Vec2>) -> Option<Self> {
+        let mut points = points.into_iter();
+        let first = points.next()?;
+        Some(points.fold(Self { min: first, max: first }, |r, p| Self { min: r.min.min(p), max: r.max.max(p) }))
+    }
+
+    pub fn size(&self) -> Vec2 {
+        self.max - self.min
+    }
+
+    pub fn width(&self) -> f32 {
+        self.max.x - self.min.x
+    }
+
+    pub fn height(&self) -> f32 {
+        self.max.y - self.min.y
+    }
+
+    pub fn center(&self) -> Vec2 {
+        (self.min + self.max) * 0.5
+    }
+
+    pub fn contains(&self, point: Vec2) -> bool {
+        point.cmpge(self.min).all() && point.cmple(self.max).all()
+    }
+
+    // Touching edges count
+    pub fn overlaps(&self, other: &Rect) -> bool {
+        self.min.x <= other.max.x && self.max.x >= other.min.x && self.min.y <= other.max.y && self.max.y >= other.min.y
+    }
+
+    pub fn union(&self, other: &Rect) -> Rect {
+        Rect { min: self.min.min(other.min), max: self.max.max(other.max) }
+    }
+
+    pub fn intersection(&self, other: &Rect) -> Option<Rect> {
+        let rect = Rect { min: self.min.max(other.min), max: self.max.min(other.max) };
+        rect.min.cmple(rect.max).all().then_some(rect)
+    }
+
+    // Grown by `amount` on every side; negative shrinks
+    pub fn expand(&self, amount: f32) -> Rect {
+        Rect { min: self.min - Vec2::splat(amount), max: self.max + Vec2::splat(amount) }
+    }
+
+    pub fn clamp(&self, point: Vec2) -> Vec2 {
+        point.clamp(self.min, self.max)
+    }
+}
+
+// Position, rotation (radians, counter-clockwise) and scale of a 2D object
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Transform2D {
+    pub translation: Vec2,
+    pub rotation: f32,
+    pub scale: Vec2,
+}
+
+impl Default for Transform2D {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+impl Transform2D {
+    pub const IDENTITY: Self = Self { translation: Vec2::ZERO, rotation: 0.0, scale: Vec2::ONE };
+
+    pub fn from_translation(translation: Vec2) -> Self {
+        Self { translation, ..Self::IDENTITY }
+    }
+
+    pub fn matrix(&self) -> Mat3 {
+        Mat3::from_scale_angle_translation(self.scale, self.rotation, self.translation)
+    }
+
+    // For uploading alongside 3D transforms; z is left alone
+    pub fn to_mat4(&self) -> Mat4 {
+        Mat4::from_translation(self.translation.extend(0.0))
+            * Mat4::from_rotation_z(self.rotation)
+            * Mat4::from_scale(self.scale.extend(1.0))
+    }
+
+    pub fn transform_point(&self, point: Vec2) -> Vec2 {
+        self.translation + self.transform_vector(point)
+    }
+
+    // Rotated and scaled, not moved
+    pub fn transform_vector(&self, vector: Vec2) -> Vec2 {
+        Vec2::from_angle(self.rotation).rotate(vector * self.scale)
+    }
+
+    pub fn inverse_transform_point(&self, point: Vec2) -> Vec2 {
+        Vec2::from_angle(-self.rotation).rotate(point - self.translation) / self.scale
+    }
+
+    // `child` placed in this transform's space, e.g. parent * local. Exact for uniform
+    // scale; non-uniform scale under rotation would need shear, which isn't kept.
+    pub fn mul_transform(&self, child: &Transform2D) -> Transform2D {
+        Transform2D {
+            translation: self.transform_point(child.translation),
+            rotation: self.rotation + child.rotation,
+            scale: self.scale * child.scale,
+        }
+    }
+}
+
+// Position, rotation and scale of a 3D object
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Transform3D {
+    pub translation: Vec3,
+    pub rotation: Quat,
+    pub scale: Vec3,
+}
+
+impl Default for Transform3D {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+impl Transform3D {
+    pub const IDENTITY: Self = Self { translation: Vec3::ZERO, rotation: Quat::IDENTITY, scale: Vec3::ONE };
+
+    pub fn from_translation(translation: Vec3) -> Self {
+        Self { translation, ..Self::IDENTITY }
+    }
+
+    // Facing `target` (-Z forward, like cameras), with `up` as a hint
+    pub fn looking_at(translation: Vec3, target: Vec3, up: Vec3) -> Self {
+        let rotation = Quat::from_mat4(&Mat4::look_at_rh(translation, target, up).inverse());
+        Self { translation, rotation, scale: Vec3::ONE }
+    }
+
+    pub fn matrix(&self) -> Mat4 {
+        Mat4::from_scale_rotation_translation(self.scale, self.rotation, self.translation)
+    }
+
+    pub fn transform_point(&self, point: Vec3) -> Vec3 {
+        self.translation + self.rotation * (point * self.scale)
+    }
+
+    pub fn transform_vector(&self, vector: Vec3) -> Vec3 {
+        self.rotation * (vector * self.scale)
+    }
+
+    pub fn inverse_transform_point(&self, point: Vec3) -> Vec3 {
+        (self.rotation.inverse() * (point - self.translation)) / self.scale
+    }
+
+    pub fn forward(&self) -> Vec3 {
+        self.rotation * Vec3::NEG_Z
+    }
+
+    pub fn right(&self) -> Vec3 {
+        self.rotation * Vec3::X
+    }
+
+    pub fn up(&self) -> Vec3 {
+        self.rotation * Vec3::Y
+    }
+
+    // `child` placed in this transform's space; the same shear caveat as `Transform2D`
+    pub fn mul_transform(&self, child: &Transform3D) -> Transform3D {
+        Transform3D {
+            translation: self.transform_point(child.translation),
+            rotation: self.rotation * child.rotation,
+            scale: self.scale * child.scale,
+        }
+    }
+}
@@ -0,0 +1,247 @@
+// src/mesh.rs
+use glam::{Vec2, Vec3};
+use std::f32::consts::{PI, TAU};
+use wgpu::util::DeviceExt;
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct MeshVertex {
+    pub position: [f32; 3],
+    pub normal: [f32; 3],
+    pub uv: [f32; 2],
+}
+
+impl MeshVertex {
+    const ATTRIBUTES: [wgpu::VertexAttribute; 3] =
+        wgpu::vertex_attr_array![0 => Float32x3, 1 => Float32x3, 2 => Float32x2];
+
+    pub fn layout() -> wgpu::VertexBufferLayout<'static> {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<MeshVertex>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &Self::ATTRIBUTES,
+        }
+    }
+}
+
+// CPU-side mesh asset: indexed triangle list, counter-clockwise front faces
+#[derive(Debug, Clone, Default)]
+pub struct Mesh {
+    pub vertices: Vec<MeshVertex>,
+    pub indices: Vec<u32>,
+}
+
+impl Mesh {
+    // Smooth normals from face normals weighted by triangle area
+    pub fn recompute_normals(&mut self) {
+        let mut normals = vec![Vec3::ZERO; self.vertices.len()];
+        for tri in self.indices.chunks_exact(3) {
+            let [a, b, c] = [tri[0], tri[1], tri[2]].map(|i| Vec3::from(self.vertices[i as usize].position));
+            let face = (b - a).cross(c - a);
+            for &i in tri {
+                normals[i as usize] += face;
+            }
+        }
+        for (vertex, normal) in self.vertices.iter_mut().zip(normals) {
+            vertex.normal = normal.normalize_or_zero().into();
+        }
+    }
+
+    pub fn upload(&self, device: &wgpu::Device, label: &str) -> GpuMesh {
+        let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some(label),
+            contents: bytemuck::cast_slice(&self.vertices),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+        let index_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some(label),
+            contents: bytemuck::cast_slice(&self.indices),
+            usage: wgpu::BufferUsages::INDEX,
+        });
+        GpuMesh {
+            vertex_buffer,
+            index_buffer,
+            index_count: self.indices.len() as u32,
+        }
+    }
+}
+
+pub struct GpuMesh {
+    pub vertex_buffer: wgpu::Buffer,
+    pub index_buffer: wgpu::Buffer,
+    pub index_count: u32,
+}
+
+impl GpuMesh {
+    pub fn draw(&self, render_pass: &mut wgpu::RenderPass<'_>) {
+        render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+        render_pass.set_index_buffer(self.index_buffer.slice(..), wgpu::IndexFormat::Uint32);
+        render_pass.draw_indexed(0..self.index_count, 0, 0..1);
+    }
+}
+
+// Builds meshes at runtime, either from scratch or starting from a primitive
+#[derive(Debug, Clone, Default)]
+pub struct MeshBuilder {
+    mesh: Mesh,
+}
+
+impl MeshBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn vertex(&mut self, position: Vec3, normal: Vec3, uv: Vec2) -> u32 {
+        self.mesh.vertices.push(MeshVertex {
+            position: position.into(),
+            normal: normal.into(),
+            uv: uv.into(),
+        });
+        self.mesh.vertices.len() as u32 - 1
+    }
+
+    pub fn triangle(&mut self, a: u32, b: u32, c: u32) -> &mut Self {
+        self.mesh.indices.extend_from_slice(&[a, b, c]);
+        self
+    }
+
+    // Corners in counter-clockwise order
+    pub fn quad_indices(&mut self, a: u32, b: u32, c: u32, d: u32) -> &mut Self {
+        self.triangle(a, b, c).triangle(a, c, d)
+    }
+
+    // Appends another mesh, e.g. to merge primitives into one draw
+    pub fn append(&mut self, other: &Mesh) -> &mut Self {
+        let base = self.mesh.vertices.len() as u32;
+        self.mesh.vertices.extend_from_slice(&other.vertices);
+        self.mesh.indices.extend(other.indices.iter().map(|i| i + base));
+        self
+    }
+
+    pub fn build(self) -> Mesh {
+        self.mesh
+    }
+
+    // Rows of `columns + 1` vertices, connected into quads between consecutive rows
+    fn connect_rows(&mut self, base: u32, rows: u32, columns: u32) {
+        for r in 0..rows {
+            for c in 0..columns {
+                let a = base + r * (columns + 1) + c;
+                let b = a + columns + 1;
+                self.triangle(a, a + 1, b).triangle(a + 1, b + 1, b);
+            }
+        }
+    }
+
+    // Quad in the XY plane facing +Z, centred on the origin
+    pub fn quad(width: f32, height: f32) -> Self {
+        let mut builder = Self::new();
+        let (hw, hh) = (width * 0.5, height * 0.5);
+        let a = builder.vertex(Vec3::new(-hw, -hh, 0.0), Vec3::Z, Vec2::new(0.0, 1.0));
+        let b = builder.vertex(Vec3::new(hw, -hh, 0.0), Vec3::Z, Vec2::new(1.0, 1.0));
+        let c = builder.vertex(Vec3::new(hw, hh, 0.0), Vec3::Z, Vec2::new(1.0, 0.0));
+        let d = builder.vertex(Vec3::new(-hw, hh, 0.0), Vec3::Z, Vec2::new(0.0, 0.0));
+        builder.quad_indices(a, b, c, d);
+        builder
+    }
+
+    // Triangle fan in the XY plane facing +Z
+    pub fn circle(radius: f32, segments: u32) -> Self {
+        let mut builder = Self::new();
+        let segments = segments.max(3);
+        let center = builder.vertex(Vec3::ZERO, Vec3::Z, Vec2::splat(0.5));
+        for i in 0..=segments {
+            let dir = Vec2::from_angle(TAU * i as f32 / segments as f32);
+            builder.vertex((dir * radius).extend(0.0), Vec3::Z, Vec2::new(0.5 + dir.x * 0.5, 0.5 - dir.y * 0.5));
+        }
+        for i in 0..segments {
+            builder.triangle(center, center + 1 + i, center + 2 + i);
+        }
+        builder
+    }
+
+    // Axis-aligned box with separate vertices per face for hard normals
+    pub fn cube(size: Vec3) -> Self {
+        let mut builder = Self::new();
+        let half = size * 0.5;
+        // (normal, u, v) with u x v = normal so faces wind counter-clockwise from outside
+        let faces = [
+            (Vec3::X, Vec3::NEG_Z, Vec3::Y),
+            (Vec3::NEG_X, Vec3::Z, Vec3::Y),
+            (Vec3::Y, Vec3::X, Vec3::NEG_Z),
+            (Vec3::NEG_Y, Vec3::X, Vec3::Z),
+            (Vec3::Z, Vec3::X, Vec3::Y),
+            (Vec3::NEG_Z, Vec3::NEG_X, Vec3::Y),
+        ];
+        for (normal, u, v) in faces {
+            let corners = [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)].map(|(su, sv)| {
+                let position = (normal + u * su + v * sv) * half;
+                builder.vertex(position, normal, Vec2::new((su + 1.0) * 0.5, (1.0 - sv) * 0.5))
+            });
+            builder.quad_indices(corners[0], corners[1], corners[2], corners[3]);
+        }
+        builder
+    }
+
+    // UV sphere with `sectors` around the Y axis and `stacks` from pole to pole
+    pub fn sphere(radius: f32, sectors: u32, stacks: u32) -> Self {
+        let mut builder = Self::new();
+        let (sectors, stacks) = (sectors.max(3), stacks.max(2));
+        for i in 0..=stacks {
+            let phi = PI * i as f32 / stacks as f32;
+            for j in 0..=sectors {
+                let theta = TAU * j as f32 / sectors as f32;
+                let normal = Vec3::new(phi.sin() * theta.cos(), phi.cos(), phi.sin() * theta.sin());
+                builder.vertex(normal * radius, normal, Vec2::new(j as f32 / sectors as f32, i as f32 / stacks as f32));
+            }
+        }
+        builder.connect_rows(0, stacks, sectors);
+        builder
+    }
+
+    // Capsule along the Y axis; `height` is the length of the cylindrical middle
+    pub fn capsule(radius: f32, height: f32, sectors: u32, rings: u32) -> Self {
+        let mut builder = Self::new();
+        let (sectors, rings) = (sectors.max(3), rings.max(1));
+        let total = height + 2.0 * radius;
+        // Each hemisphere gets its own equator row; the gap between them is the cylinder
+        for row in 0..=(2 * rings + 1) {
+            let (phi, offset) = if row <= rings {
+                (PI * 0.5 * row as f32 / rings as f32, height * 0.5)
+            } else {
+                (PI * 0.5 * (1.0 + (row - rings - 1) as f32 / rings as f32), -height * 0.5)
+            };
+            for j in 0..=sectors {
+                let theta = TAU * j as f32 / sectors as f32;
+                let normal = Vec3::new(phi.sin() * theta.cos(), phi.cos(), phi.sin() * theta.sin());
+                let position = normal * radius + Vec3::Y * offset;
+                let v = (total * 0.5 - position.y) / total;
+                builder.vertex(position, normal, Vec2::new(j as f32 / sectors as f32, v));
+            }
+        }
+        builder.connect_rows(0, 2 * rings + 1, sectors);
+        builder
+    }
+
+    // Subdivided plane in the XZ plane facing +Y, e.g. for terrain or water
+    pub fn plane_grid(width: f32, depth: f32, columns: u32, rows: u32) -> Self {
+        let mut builder = Self::new();
+        let (columns, rows) = (columns.max(1), rows.max(1));
+        for r in 0..=rows {
+            for c in 0..=columns {
+                let uv = Vec2::new(c as f32 / columns as f32, r as f32 / rows as f32);
+                let position = Vec3::new((uv.x - 0.5) * width, 0.0, (uv.y - 0.5) * depth);
+                builder.vertex(position, Vec3::Y, uv);
+            }
+        }
+        // Rows run towards +Z here, so flip the winding used by `connect_rows`
+        for r in 0..rows {
+            for c in 0..columns {
+                let a = r * (columns + 1) + c;
+                let b = a + columns + 1;
+                builder.triangle(a, b, a + 1).triangle(a + 1, b, b + 1);
+            }
+        }
+        builder
+    }
+}
@@ -0,0 +1,94 @@
+// src/net/http.rs
+use std::io::{Read, Write};
+use std::net::{SocketAddr, TcpStream, ToSocketAddrs};
+use std::time::Duration;
+
+// A plain-HTTP server the engine talks to with small blocking requests, e.g. the lobby
+// service or a telemetry collector. Run requests off the main thread.
+#[derive(Debug, Clone)]
+pub struct HttpEndpoint {
+    // Names the service in errors, e.g. "matchmaking"
+    service: &'static str,
+    host: String,
+    addr: SocketAddr,
+    base_path: String,
+    pub timeout: Duration,
+}
+
+impl HttpEndpoint {
+    // `url` looks like "http://lobby.example.com:8080/api"
+    pub fn new(service: &'static str, url: &str) -> Result<Self, String> {
+        let rest = url
+            .strip_prefix("http://")
+            .ok_or_else(|| format!("Unsupported {} URL (only http:// is supported): {}", service, url))?;
+        let (authority, base_path) = match rest.find('/') {
+            Some(i) => (&rest[..i], rest[i..].trim_end_matches('/')),
+            None => (rest, ""),
+        };
+        let host_port = if authority.contains(':') { authority.to_string() } else { format!("{}:80", authority) };
+        let addr = host_port
+            .to_socket_addrs()
+            .map_err(|e| format!("Failed to resolve {}: {}", authority, e))?
+            .next()
+            .ok_or_else(|| format!("No address found for {}", authority))?;
+        Ok(Self {
+            service,
+            host: authority.to_string(),
+            addr,
+            base_path: base_path.to_string(),
+            timeout: Duration::from_secs(5),
+        })
+    }
+
+    // JSON in and out; `path` is relative to the URL's path
+    pub fn send(&self, method: &str, path: &str, body: Option<&str>) -> Result<String, String> {
+        let mut stream = TcpStream::connect_timeout(&self.addr, self.timeout)
+            .map_err(|e| format!("Failed to connect to {} server: {}", self.service, e))?;
+        stream.set_read_timeout(Some(self.timeout)).map_err(|e| e.to_string())?;
+        stream.set_write_timeout(Some(self.timeout)).map_err(|e| e.to_string())?;
+
+        let body = body.unwrap_or("");
+        let request = format!(
+            "{} {}{} HTTP/1.1\r\nHost: {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            method, self.base_path, path, self.host, body.len(), body
+        );
+        stream.write_all(request.as_bytes()).map_err(|e| format!("Failed to send request: {}", e))?;
+
+        let mut raw = Vec::new();
+        stream.read_to_end(&mut raw).map_err(|e| format!("Failed to read response: {}", e))?;
+        self.parse_response(&raw)
+    }
+
+    fn parse_response(&self, raw: &[u8]) -> Result<String, String> {
+        let split = raw.windows(4).position(|w| w == b"\r\n\r\n").ok_or("Malformed HTTP response")?;
+        let head = String::from_utf8_lossy(&raw[..split]);
+        let mut body = &raw[split + 4..];
+        let mut lines = head.lines();
+        let status: u16 = lines
+            .next()
+            .and_then(|line| line.split_whitespace().nth(1))
+            .and_then(|code| code.parse().ok())
+            .ok_or("Malformed HTTP status line")?;
+
+        for line in lines {
+            let Some((name, value)) = line.split_once(':') else { continue };
+            match name.trim().to_ascii_lowercase().as_str() {
+                "transfer-encoding" if value.trim().eq_ignore_ascii_case("chunked") => {
+                    return Err("Chunked responses are not supported".to_string());
+                }
+                "content-length" => {
+                    if let Ok(len) = value.trim().parse::<usize>() {
+                        body = &body[..len.min(body.len())];
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let body = String::from_utf8_lossy(body).into_owned();
+        if !(200..300).contains(&status) {
+            return Err(format!("The {} server returned {}: {}", self.service, status, body.trim()));
+        }
+        Ok(body)
+    }
+}
@@ -0,0 +1,81 @@
+// src/net/matchmaking.rs
+use super::http::HttpEndpoint;
+use serde::{Deserialize, Serialize};
+use std::net::SocketAddr;
+use std::time::Duration;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Lobby {
+    pub id: String,
+    pub name: String,
+    pub players: u32,
+    pub max_players: u32,
+}
+
+// Addresses a peer can be reached on; the public one comes from STUN (see `nat`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PeerInfo {
+    pub player_name: String,
+    pub local_addr: Option<SocketAddr>,
+    pub public_addr: Option<SocketAddr>,
+}
+
+impl PeerInfo {
+    // Candidates to try when punching through, public first
+    pub fn candidates(&self) -> Vec<SocketAddr> {
+        self.public_addr.iter().chain(self.local_addr.iter()).copied().collect()
+    }
+}
+
+#[derive(Serialize)]
+struct CreateLobbyRequest<'a> {
+    name: &'a str,
+    max_players: u32,
+}
+
+// Blocking client for a small REST lobby service. Plain HTTP only; run it off the
+// main thread so the game loop doesn't hitch while waiting on the server.
+pub struct MatchmakingClient {
+    server: HttpEndpoint,
+}
+
+impl MatchmakingClient {
+    // `server` looks like "http://lobby.example.com:8080/api"
+    pub fn new(server: &str) -> Result<Self, String> {
+        Ok(Self { server: HttpEndpoint::new("matchmaking", server)? })
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.server.timeout = timeout;
+        self
+    }
+
+    pub fn list_lobbies(&self) -> Result<Vec<Lobby>, String> {
+        self.request("GET", "/lobbies", None)
+    }
+
+    pub fn create_lobby(&self, name: &str, max_players: u32) -> Result<Lobby, String> {
+        let body = serde_json::to_string(&CreateLobbyRequest { name, max_players }).map_err(|e| e.to_string())?;
+        self.request("POST", "/lobbies", Some(&body))
+    }
+
+    // Registers us in the lobby and returns everyone already in it
+    pub fn join_lobby(&self, lobby_id: &str, me: &PeerInfo) -> Result<Vec<PeerInfo>, String> {
+        let body = serde_json::to_string(me).map_err(|e| e.to_string())?;
+        self.request("POST", &format!("/lobbies/{}/join", lobby_id), Some(&body))
+    }
+
+    pub fn peers(&self, lobby_id: &str) -> Result<Vec<PeerInfo>, String> {
+        self.request("GET", &format!("/lobbies/{}/peers", lobby_id), None)
+    }
+
+    pub fn leave_lobby(&self, lobby_id: &str, player_name: &str) -> Result<(), String> {
+        let body = serde_json::json!({ "player_name": player_name }).to_string();
+        self.server.send("POST", &format!("/lobbies/{}/leave", lobby_id), Some(&body)).map(|_| ())
+    }
+
+    fn request<T: for<'de> Deserialize<'de>>(&self, method: &str, path: &str, body: Option<&str>) -> Result<T, String> {
+        let response = self.server.send(method, path, body)?;
+        serde_json::from_str(&response).map_err(|e| format!("Invalid response from {}: {}", path, e))
+    }
+}
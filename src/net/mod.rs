@@ -0,0 +1,5 @@
+// src/net/mod.rs
+pub mod rollback;
+pub mod matchmaking;
+pub mod http;
+pub mod nat;
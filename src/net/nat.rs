@@ -0,0 +1,171 @@
+// src/net/nat.rs
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, ToSocketAddrs, UdpSocket};
+use std::time::{Duration, Instant};
+
+const STUN_BINDING_REQUEST: u16 = 0x0001;
+const STUN_BINDING_RESPONSE: u16 = 0x0101;
+const STUN_MAGIC_COOKIE: u32 = 0x2112_A442;
+const ATTR_MAPPED_ADDRESS: u16 = 0x0001;
+const ATTR_XOR_MAPPED_ADDRESS: u16 = 0x0020;
+
+const PUNCH_MAGIC: &[u8] = b"VELLUM_PUNCH";
+
+// Asks a STUN server which address our UDP socket appears as from outside the NAT.
+// Use the same socket for the game traffic afterwards so the mapping stays valid.
+pub fn discover_public_addr(socket: &UdpSocket, stun_server: &str, timeout: Duration) -> Result<SocketAddr, String> {
+    let server = stun_server
+        .to_socket_addrs()
+        .map_err(|e| format!("Failed to resolve STUN server {}: {}", stun_server, e))?
+        .next()
+        .ok_or_else(|| format!("No address found for STUN server {}", stun_server))?;
+
+    let transaction_id = transaction_id();
+    let mut request = Vec::with_capacity(20);
+    request.extend_from_slice(&STUN_BINDING_REQUEST.to_be_bytes());
+    request.extend_from_slice(&0u16.to_be_bytes());
+    request.extend_from_slice(&STUN_MAGIC_COOKIE.to_be_bytes());
+    request.extend_from_slice(&transaction_id);
+
+    let previous_timeout = socket.read_timeout().map_err(|e| e.to_string())?;
+    let result = (|| {
+        socket.send_to(&request, server).map_err(|e| format!("Failed to send STUN request: {}", e))?;
+        socket.set_read_timeout(Some(timeout)).map_err(|e| e.to_string())?;
+        let deadline = Instant::now() + timeout;
+        let mut buf = [0u8; 512];
+        while Instant::now() < deadline {
+            let (len, from) = match socket.recv_from(&mut buf) {
+                Ok(received) => received,
+                Err(e) => return Err(format!("No STUN response: {}", e)),
+            };
+            if from != server {
+                continue;
+            }
+            if let Some(addr) = parse_binding_response(&buf[..len], &transaction_id) {
+                return Ok(addr);
+            }
+        }
+        Err("Timed out waiting for STUN response".to_string())
+    })();
+    socket.set_read_timeout(previous_timeout).map_err(|e| e.to_string())?;
+    result
+}
+
+fn transaction_id() -> [u8; 12] {
+    // Only needs to be unique among our own in-flight requests
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or_default();
+    let mut id = [0u8; 12];
+    id.copy_from_slice(&nanos.to_le_bytes()[..12]);
+    id
+}
+
+fn parse_binding_response(packet: &[u8], transaction_id: &[u8; 12]) -> Option<SocketAddr> {
+    if packet.len() < 20 {
+        return None;
+    }
+    let message_type = u16::from_be_bytes([packet[0], packet[1]]);
+    let length = u16::from_be_bytes([packet[2], packet[3]]) as usize;
+    if message_type != STUN_BINDING_RESPONSE || &packet[8..20] != transaction_id || packet.len() < 20 + length {
+        return None;
+    }
+
+    let mut mapped = None;
+    let mut offset = 20;
+    while offset + 4 <= 20 + length {
+        let attr_type = u16::from_be_bytes([packet[offset], packet[offset + 1]]);
+        let attr_len = u16::from_be_bytes([packet[offset + 2], packet[offset + 3]]) as usize;
+        let value = packet.get(offset + 4..offset + 4 + attr_len)?;
+        match attr_type {
+            ATTR_XOR_MAPPED_ADDRESS => return parse_address(value, Some(&packet[4..20])),
+            ATTR_MAPPED_ADDRESS => mapped = parse_address(value, None),
+            _ => {}
+        }
+        // Attributes are padded to 4 bytes
+        offset += 4 + attr_len.div_ceil(4) * 4;
+    }
+    mapped
+}
+
+// `xor_key` is the magic cookie followed by the transaction id, for XOR-MAPPED-ADDRESS
+fn parse_address(value: &[u8], xor_key: Option<&[u8]>) -> Option<SocketAddr> {
+    if value.len() < 8 {
+        return None;
+    }
+    let family = value[1];
+    let mut port = u16::from_be_bytes([value[2], value[3]]);
+    if let Some(key) = xor_key {
+        port ^= u16::from_be_bytes([key[0], key[1]]);
+    }
+    let ip = match family {
+        0x01 => {
+            let mut octets = [value[4], value[5], value[6], value[7]];
+            if let Some(key) = xor_key {
+                octets.iter_mut().zip(key).for_each(|(b, k)| *b ^= k);
+            }
+            IpAddr::V4(Ipv4Addr::from(octets))
+        }
+        0x02 if value.len() >= 20 => {
+            let mut octets = [0u8; 16];
+            octets.copy_from_slice(&value[4..20]);
+            if let Some(key) = xor_key {
+                octets.iter_mut().zip(key).for_each(|(b, k)| *b ^= k);
+            }
+            IpAddr::V6(Ipv6Addr::from(octets))
+        }
+        _ => return None,
+    };
+    Some(SocketAddr::new(ip, port))
+}
+
+// UDP hole punching: both peers fire probes at each other's candidates at the same
+// time (coordinated through the lobby) until one gets through in each direction.
+pub struct HolePunch {
+    pub session_token: u64,
+    pub interval: Duration,
+    pub timeout: Duration,
+}
+
+impl HolePunch {
+    pub fn new(session_token: u64) -> Self {
+        Self {
+            session_token,
+            interval: Duration::from_millis(100),
+            timeout: Duration::from_secs(5),
+        }
+    }
+
+    // Returns the candidate address that answered; use it for all further traffic
+    pub fn run(&self, socket: &UdpSocket, candidates: &[SocketAddr]) -> Result<SocketAddr, String> {
+        if candidates.is_empty() {
+            return Err("No peer candidates to punch through to".to_string());
+        }
+        let mut probe = PUNCH_MAGIC.to_vec();
+        probe.extend_from_slice(&self.session_token.to_be_bytes());
+
+        let previous_timeout = socket.read_timeout().map_err(|e| e.to_string())?;
+        socket.set_read_timeout(Some(self.interval)).map_err(|e| e.to_string())?;
+        let deadline = Instant::now() + self.timeout;
+        let mut buf = [0u8; 64];
+        let mut result = Err("Timed out punching through to peer".to_string());
+
+        while Instant::now() < deadline {
+            for candidate in candidates {
+                // Probes to unreachable candidates are expected to fail
+                let _ = socket.send_to(&probe, candidate);
+            }
+            match socket.recv_from(&mut buf) {
+                Ok((len, from)) if buf[..len] == probe[..] => {
+                    // Answer once more so the peer sees our mapping even if it started later
+                    let _ = socket.send_to(&probe, from);
+                    result = Ok(from);
+                    break;
+                }
+                _ => {}
+            }
+        }
+        socket.set_read_timeout(previous_timeout).map_err(|e| e.to_string())?;
+        result
+    }
+}
@@ -0,0 +1,179 @@
+// src/net/rollback.rs
+use std::collections::HashMap;
+
+// Anything driven by the fixed-step loop that can be snapshotted and replayed.
+// `advance` must be deterministic: same state + same inputs = same result.
+pub trait Simulation {
+    type State: Clone;
+    type Input: Clone + PartialEq + Default;
+
+    fn save_state(&self) -> Self::State;
+    fn load_state(&mut self, state: &Self::State);
+    fn advance(&mut self, inputs: &[Self::Input]);
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AdvanceResult {
+    // Simulated one new tick, after re-simulating `resimulated` older ticks.
+    Advanced { resimulated: u32 },
+    // Too far ahead of the last confirmed remote input; wait for the peers.
+    Stalled,
+}
+
+struct PlayerInputs<I> {
+    confirmed: HashMap<u32, I>,
+    last_confirmed: Option<I>,
+    // Every frame below this one has a confirmed input
+    confirmed_until: u32,
+}
+
+impl<I: Clone + Default> PlayerInputs<I> {
+    fn new(input_delay: u32) -> Self {
+        // The first `input_delay` frames can't carry real input from anyone
+        let confirmed = (0..input_delay).map(|frame| (frame, I::default())).collect();
+        Self {
+            confirmed,
+            last_confirmed: None,
+            confirmed_until: input_delay,
+        }
+    }
+
+    fn confirm(&mut self, frame: u32, input: I) {
+        self.confirmed.insert(frame, input);
+        while let Some(input) = self.confirmed.get(&self.confirmed_until) {
+            self.last_confirmed = Some(input.clone());
+            self.confirmed_until += 1;
+        }
+    }
+
+    fn input_for(&self, frame: u32) -> I {
+        match self.confirmed.get(&frame) {
+            Some(input) => input.clone(),
+            // Prediction: assume the player keeps doing what they last did
+            None => self.last_confirmed.clone().unwrap_or_default(),
+        }
+    }
+
+    fn discard_before(&mut self, frame: u32) {
+        self.confirmed.retain(|&f, _| f >= frame);
+    }
+}
+
+pub struct RollbackSession<S: Simulation> {
+    num_players: usize,
+    input_delay: u32,
+    max_prediction: u32,
+    current_frame: u32,
+    players: Vec<PlayerInputs<S::Input>>,
+    // Inputs actually fed to `advance`, used to detect mispredictions
+    used_inputs: HashMap<u32, Vec<S::Input>>,
+    // State saved at the start of each frame still inside the prediction window
+    snapshots: HashMap<u32, S::State>,
+    first_incorrect_frame: Option<u32>,
+}
+
+impl<S: Simulation> RollbackSession<S> {
+    pub fn new(num_players: usize, input_delay: u32, max_prediction: u32) -> Self {
+        Self {
+            num_players,
+            input_delay,
+            max_prediction: max_prediction.max(1),
+            current_frame: 0,
+            players: (0..num_players).map(|_| PlayerInputs::new(input_delay)).collect(),
+            used_inputs: HashMap::new(),
+            snapshots: HashMap::new(),
+            first_incorrect_frame: None,
+        }
+    }
+
+    pub fn current_frame(&self) -> u32 {
+        self.current_frame
+    }
+
+    pub fn input_delay(&self) -> u32 {
+        self.input_delay
+    }
+
+    // Latest frame for which every player's input is known
+    pub fn confirmed_frame(&self) -> Option<u32> {
+        self.players.iter().map(|p| p.confirmed_until).min().and_then(|f| f.checked_sub(1))
+    }
+
+    // Queues local input sampled this tick; it takes effect `input_delay` frames later.
+    // Returns the frame it was scheduled for, which is what gets sent to the peers.
+    pub fn add_local_input(&mut self, player: usize, input: S::Input) -> Result<u32, String> {
+        let frame = self.current_frame + self.input_delay;
+        self.add_input(player, frame, input)?;
+        Ok(frame)
+    }
+
+    pub fn add_remote_input(&mut self, player: usize, frame: u32, input: S::Input) -> Result<(), String> {
+        self.add_input(player, frame, input)
+    }
+
+    fn add_input(&mut self, player: usize, frame: u32, input: S::Input) -> Result<(), String> {
+        if player >= self.num_players {
+            return Err(format!("Player {} out of range ({} players)", player, self.num_players));
+        }
+        if frame < self.players[player].confirmed_until {
+            // Duplicate of something we already have (e.g. a resent packet)
+            return Ok(());
+        }
+        if frame < self.current_frame {
+            if !self.snapshots.contains_key(&frame) {
+                return Err(format!("Input for frame {} arrived after its snapshot was discarded", frame));
+            }
+            let mispredicted = self.used_inputs.get(&frame).is_some_and(|used| used[player] != input);
+            if mispredicted {
+                self.first_incorrect_frame = Some(self.first_incorrect_frame.map_or(frame, |f| f.min(frame)));
+            }
+        }
+        self.players[player].confirm(frame, input);
+        Ok(())
+    }
+
+    // Runs one fixed tick. Call once per update reported by `GameLoop::tick`.
+    pub fn advance_frame(&mut self, sim: &mut S) -> AdvanceResult {
+        let resimulated = self.rollback(sim);
+
+        let confirmed_until = self.players.iter().map(|p| p.confirmed_until).min().unwrap_or(self.current_frame);
+        if self.current_frame >= confirmed_until + self.max_prediction {
+            return AdvanceResult::Stalled;
+        }
+
+        self.simulate_frame(sim, self.current_frame);
+        self.current_frame += 1;
+        self.discard_confirmed(confirmed_until);
+        AdvanceResult::Advanced { resimulated }
+    }
+
+    fn rollback(&mut self, sim: &mut S) -> u32 {
+        let Some(frame) = self.first_incorrect_frame.take() else { return 0 };
+        let Some(state) = self.snapshots.get(&frame) else {
+            log::error!("Missing snapshot for frame {}, cannot roll back", frame);
+            return 0;
+        };
+        sim.load_state(state);
+        for f in frame..self.current_frame {
+            self.simulate_frame(sim, f);
+        }
+        self.current_frame - frame
+    }
+
+    fn simulate_frame(&mut self, sim: &mut S, frame: u32) {
+        self.snapshots.insert(frame, sim.save_state());
+        let inputs: Vec<S::Input> = self.players.iter().map(|p| p.input_for(frame)).collect();
+        sim.advance(&inputs);
+        self.used_inputs.insert(frame, inputs);
+    }
+
+    // Frames before the oldest unconfirmed one can never be rolled back to again
+    fn discard_confirmed(&mut self, confirmed_until: u32) {
+        let keep_from = confirmed_until.min(self.current_frame);
+        self.snapshots.retain(|&f, _| f >= keep_from);
+        self.used_inputs.retain(|&f, _| f >= keep_from);
+        for player in &mut self.players {
+            player.discard_before(keep_from);
+        }
+    }
+}
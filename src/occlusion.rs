@@ -0,0 +1,271 @@
+// src/occlusion.rs
+use crate::resource_registry::{ResourceRegistry, Tracked};
+use crate::upload::{DynamicBuffer, UploadBelt};
+use glam::{Mat4, Vec3};
+use std::sync::{Arc, Mutex};
+
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct QueryBounds {
+    min: [f32; 3],
+    max: [f32; 3],
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ReadbackState {
+    Idle,
+    Mapping,
+    Ready,
+}
+
+const QUERY_SIZE: u64 = std::mem::size_of::<u64>() as u64;
+
+// Bounding-box occlusion queries. Each frame the boxes are drawn depth-tested (but
+// not written) after the occluders, and the sample counts come back a frame or two
+// later. Objects stay visible until a result says otherwise, so nothing pops out while
+// results are in flight; a box that contains the camera gets near-clipped, so callers
+// should treat nearby objects as always visible.
+pub struct OcclusionQueries {
+    capacity: u32,
+    query_set: wgpu::QuerySet,
+    bounds: DynamicBuffer,
+    params_buffer: Tracked<wgpu::Buffer>,
+    bind_group: wgpu::BindGroup,
+    pipeline: wgpu::RenderPipeline,
+    resolve_buffer: Tracked<wgpu::Buffer>,
+    readback_buffer: Tracked<wgpu::Buffer>,
+    state: Arc<Mutex<ReadbackState>>,
+    // Queries drawn this frame, and how many the pending readback holds
+    query_count: u32,
+    resolved_count: u32,
+    visible: Vec<bool>,
+}
+
+impl OcclusionQueries {
+    pub fn new(
+        device: &wgpu::Device,
+        resources: &ResourceRegistry,
+        color_format: wgpu::TextureFormat,
+        depth_format: wgpu::TextureFormat,
+        capacity: u32,
+    ) -> Result<Self, String> {
+        if capacity == 0 {
+            return Err("Occlusion query capacity must be at least 1".to_string());
+        }
+        let query_set = device.create_query_set(&wgpu::QuerySetDescriptor {
+            label: Some("occlusion queries"),
+            ty: wgpu::QueryType::Occlusion,
+            count: capacity,
+        });
+        let results_size = capacity as u64 * QUERY_SIZE;
+        let resolve_buffer = resources.create_buffer(device, &wgpu::BufferDescriptor {
+            label: Some("occlusion resolve"),
+            size: results_size,
+            usage: wgpu::BufferUsages::QUERY_RESOLVE | wgpu::BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        }, "occlusion");
+        let readback_buffer = resources.create_buffer(device, &wgpu::BufferDescriptor {
+            label: Some("occlusion readback"),
+            size: results_size,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        }, "occlusion");
+        let params_buffer = resources.create_buffer(device, &wgpu::BufferDescriptor {
+            label: Some("occlusion params"),
+            size: std::mem::size_of::<Mat4>() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        }, "occlusion");
+
+        let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("occlusion"),
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::VERTEX,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+        });
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("occlusion"),
+            layout: &layout,
+            entries: &[wgpu::BindGroupEntry { binding: 0, resource: params_buffer.as_entire_binding() }],
+        });
+
+        let shader = device.create_shader_module(wgpu::include_wgsl!("occlusion.wgsl"));
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("occlusion"),
+            bind_group_layouts: &[&layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("occlusion"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: Some("vs_main"),
+                buffers: &[wgpu::VertexBufferLayout {
+                    array_stride: std::mem::size_of::<QueryBounds>() as wgpu::BufferAddress,
+                    step_mode: wgpu::VertexStepMode::Instance,
+                    attributes: &wgpu::vertex_attr_array![0 => Float32x3, 1 => Float32x3],
+                }],
+                compilation_options: Default::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: Some("fs_main"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: color_format,
+                    blend: None,
+                    write_mask: wgpu::ColorWrites::empty(),
+                })],
+                compilation_options: Default::default(),
+            }),
+            // Both faces, so a box is still tested when the camera sits inside it
+            primitive: wgpu::PrimitiveState { cull_mode: None, ..Default::default() },
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: depth_format,
+                depth_write_enabled: false,
+                depth_compare: wgpu::CompareFunction::LessEqual,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        });
+
+        Ok(Self {
+            capacity,
+            query_set,
+            bounds: DynamicBuffer::new(
+                device,
+                resources,
+                "occlusion bounds",
+                wgpu::BufferUsages::VERTEX,
+                std::mem::size_of::<QueryBounds>() as u64 * capacity as u64,
+            ),
+            params_buffer,
+            bind_group,
+            pipeline,
+            resolve_buffer,
+            readback_buffer,
+            state: Arc::new(Mutex::new(ReadbackState::Idle)),
+            query_count: 0,
+            resolved_count: 0,
+            visible: Vec::new(),
+        })
+    }
+
+    pub fn capacity(&self) -> u32 {
+        self.capacity
+    }
+
+    // Pass as `occlusion_query_set` of the render pass that calls `draw_queries`
+    pub fn query_set(&self) -> &wgpu::QuerySet {
+        &self.query_set
+    }
+
+    // Uploads this frame's boxes; objects past `capacity` are never queried (always visible)
+    pub fn prepare(
+        &mut self,
+        device: &wgpu::Device,
+        resources: &ResourceRegistry,
+        encoder: &mut wgpu::CommandEncoder,
+        belt: &mut UploadBelt,
+        view_proj: Mat4,
+        bounds: &[(Vec3, Vec3)],
+    ) {
+        self.fetch_results();
+        // Results for the previous batch are still coming back; skip this frame's queries
+        if *self.lock_state() != ReadbackState::Idle {
+            self.query_count = 0;
+            return;
+        }
+        let bounds: Vec<QueryBounds> = bounds
+            .iter()
+            .take(self.capacity as usize)
+            .map(|(min, max)| QueryBounds { min: (*min).into(), max: (*max).into() })
+            .collect();
+        self.query_count = bounds.len() as u32;
+        if bounds.is_empty() {
+            return;
+        }
+        belt.write(device, encoder, &self.params_buffer, 0, bytemuck::bytes_of(&view_proj.to_cols_array()));
+        self.bounds.upload(device, resources, encoder, belt, bytemuck::cast_slice(&bounds));
+    }
+
+    // Call inside the pass, after the occluders have filled the depth buffer
+    pub fn draw_queries(&self, render_pass: &mut wgpu::RenderPass<'_>) {
+        if self.query_count == 0 {
+            return;
+        }
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(0, &self.bind_group, &[]);
+        render_pass.set_vertex_buffer(0, self.bounds.buffer().slice(..));
+        for index in 0..self.query_count {
+            render_pass.begin_occlusion_query(index);
+            render_pass.draw(0..36, index..index + 1);
+            render_pass.end_occlusion_query();
+        }
+    }
+
+    // Call after the pass ends, before submitting
+    pub fn resolve(&mut self, encoder: &mut wgpu::CommandEncoder) {
+        if self.query_count == 0 {
+            return;
+        }
+        let size = self.query_count as u64 * QUERY_SIZE;
+        encoder.resolve_query_set(&self.query_set, 0..self.query_count, &self.resolve_buffer, 0);
+        encoder.copy_buffer_to_buffer(&self.resolve_buffer, 0, &self.readback_buffer, 0, size);
+        self.resolved_count = self.query_count;
+    }
+
+    // Call after submitting; the results land once the device is polled
+    pub fn after_submit(&mut self) {
+        if std::mem::take(&mut self.query_count) == 0 {
+            return;
+        }
+        *self.lock_state() = ReadbackState::Mapping;
+        let state = self.state.clone();
+        let size = self.resolved_count as u64 * QUERY_SIZE;
+        self.readback_buffer.slice(..size).map_async(wgpu::MapMode::Read, move |result| {
+            let mut state = state.lock().unwrap_or_else(|e| e.into_inner());
+            *state = match result {
+                Ok(()) => ReadbackState::Ready,
+                Err(e) => {
+                    log::warn!("Occlusion readback failed: {}", e);
+                    ReadbackState::Idle
+                }
+            };
+        });
+    }
+
+    // Last known result for the object at `index`; true until a query says otherwise
+    pub fn is_visible(&self, index: usize) -> bool {
+        self.visible.get(index).copied().unwrap_or(true)
+    }
+
+    fn fetch_results(&mut self) {
+        if *self.lock_state() != ReadbackState::Ready {
+            return;
+        }
+        let size = self.resolved_count as u64 * QUERY_SIZE;
+        {
+            let data = self.readback_buffer.slice(..size).get_mapped_range();
+            let samples: &[u64] = bytemuck::cast_slice(&data);
+            self.visible.clear();
+            self.visible.extend(samples.iter().map(|&count| count > 0));
+        }
+        self.readback_buffer.unmap();
+        *self.lock_state() = ReadbackState::Idle;
+    }
+
+    fn lock_state(&self) -> std::sync::MutexGuard<'_, ReadbackState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
@@ -0,0 +1,34 @@
+// Bounding-box proxies for occlusion queries: depth-tested against the occluders, never written
+
+struct Params {
+    view_proj: mat4x4<f32>,
+}
+
+@group(0) @binding(0) var<uniform> params: Params;
+
+// Two triangles per face; each index picks a corner by its x/y/z bits
+const CUBE_INDICES = array<u32, 36>(
+    0u, 1u, 3u, 0u, 3u, 2u,
+    4u, 6u, 7u, 4u, 7u, 5u,
+    0u, 4u, 5u, 0u, 5u, 1u,
+    2u, 3u, 7u, 2u, 7u, 6u,
+    0u, 2u, 6u, 0u, 6u, 4u,
+    1u, 5u, 7u, 1u, 7u, 3u,
+);
+
+@vertex
+fn vs_main(
+    @builtin(vertex_index) vertex_index: u32,
+    @location(0) bounds_min: vec3<f32>,
+    @location(1) bounds_max: vec3<f32>,
+) -> @builtin(position) vec4<f32> {
+    let corner = CUBE_INDICES[vertex_index];
+    let select_max = vec3<bool>((corner & 1u) != 0u, (corner & 2u) != 0u, (corner & 4u) != 0u);
+    let position = select(bounds_min, bounds_max, select_max);
+    return params.view_proj * vec4<f32>(position, 1.0);
+}
+
+@fragment
+fn fs_main() -> @location(0) vec4<f32> {
+    return vec4<f32>(0.0);
+}
@@ -0,0 +1,324 @@
+// src/outline.rs
+use crate::bind_cache::{BindGroupCache, BindingKey};
+use crate::mesh::MeshVertex;
+use crate::resource_registry::{ResourceRegistry, Tracked};
+use crate::upload::UploadBelt;
+use glam::{Mat4, Vec4};
+
+pub const SELECTION_COLOR: Vec4 = Vec4::new(1.0, 0.6, 0.1, 1.0);
+pub const HOVER_COLOR: Vec4 = Vec4::new(0.4, 0.8, 1.0, 1.0);
+const MASK_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8Unorm;
+// Pixel coordinates, which need full f32 precision past 2048 pixels
+const SEED_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rg32Float;
+const MAX_THICKNESS: f32 = 64.0;
+// One uniform slot per jump-flood step, at the minimum uniform offset alignment
+const PARAMS_STRIDE: u64 = 256;
+const PARAMS_SLOTS: u64 = 8;
+
+// Per-instance model matrix and outline colour at vertex slot 1 of the mask pass,
+// locations 3..=7
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct OutlineInstance {
+    pub model: [f32; 16],
+    pub color: [f32; 4],
+}
+
+impl OutlineInstance {
+    const ATTRIBUTES: [wgpu::VertexAttribute; 5] =
+        wgpu::vertex_attr_array![3 => Float32x4, 4 => Float32x4, 5 => Float32x4, 6 => Float32x4, 7 => Float32x4];
+
+    pub fn new(model: Mat4, color: Vec4) -> Self {
+        Self { model: model.to_cols_array(), color: color.into() }
+    }
+
+    pub fn layout() -> wgpu::VertexBufferLayout<'static> {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<OutlineInstance>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Instance,
+            attributes: &Self::ATTRIBUTES,
+        }
+    }
+}
+
+// Mirrors `JumpFloodParams` in outline.wgsl
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct JumpFloodParams {
+    step: i32,
+    thickness: f32,
+    _pad: [u32; 2],
+}
+
+struct Target {
+    _texture: Tracked<wgpu::Texture>,
+    view: wgpu::TextureView,
+}
+
+impl Target {
+    fn new(device: &wgpu::Device, resources: &ResourceRegistry, label: &str, format: wgpu::TextureFormat, size: (u32, u32)) -> Self {
+        let texture = resources.create_texture(device, &wgpu::TextureDescriptor {
+            label: Some(label),
+            size: wgpu::Extent3d { width: size.0, height: size.1, depth_or_array_layers: 1 },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        }, "outline");
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        Self { _texture: texture, view }
+    }
+}
+
+// Selection and hover outlines via jump flooding: cost depends on the thickness only
+// logarithmically, and thick outlines stay round at corners.
+pub struct OutlineRenderer {
+    // Outline width in pixels, up to 64
+    pub thickness: f32,
+    size: (u32, u32),
+    mask: Target,
+    seeds: [Target; 2],
+    mask_params: Tracked<wgpu::Buffer>,
+    mask_bind_group: wgpu::BindGroup,
+    mask_pipeline: wgpu::RenderPipeline,
+    flood_params: Tracked<wgpu::Buffer>,
+    flood_layout: wgpu::BindGroupLayout,
+    init_pipeline: wgpu::RenderPipeline,
+    step_pipeline: wgpu::RenderPipeline,
+    composite_pipeline: wgpu::RenderPipeline,
+}
+
+impl OutlineRenderer {
+    pub fn new(
+        device: &wgpu::Device,
+        resources: &ResourceRegistry,
+        color_format: wgpu::TextureFormat,
+        width: u32,
+        height: u32,
+    ) -> Self {
+        let uniform_entry = |visibility| wgpu::BindGroupLayoutEntry {
+            binding: 0,
+            visibility,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Uniform,
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        };
+        let texture_entry = |binding| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Texture {
+                sample_type: wgpu::TextureSampleType::Float { filterable: false },
+                view_dimension: wgpu::TextureViewDimension::D2,
+                multisampled: false,
+            },
+            count: None,
+        };
+        let mask_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("outline mask"),
+            entries: &[uniform_entry(wgpu::ShaderStages::VERTEX)],
+        });
+        let flood_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("outline flood"),
+            entries: &[uniform_entry(wgpu::ShaderStages::FRAGMENT), texture_entry(1), texture_entry(2)],
+        });
+
+        let mask_params = resources.create_buffer(device, &wgpu::BufferDescriptor {
+            label: Some("outline mask params"),
+            size: std::mem::size_of::<Mat4>() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        }, "outline");
+        let mask_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("outline mask"),
+            layout: &mask_layout,
+            entries: &[wgpu::BindGroupEntry { binding: 0, resource: mask_params.as_entire_binding() }],
+        });
+        let flood_params = resources.create_buffer(device, &wgpu::BufferDescriptor {
+            label: Some("outline flood params"),
+            size: PARAMS_STRIDE * PARAMS_SLOTS,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        }, "outline");
+
+        let pipeline = |label, shader: &wgpu::ShaderModule, layout: &wgpu::BindGroupLayout, vertex_entry, buffers: &[wgpu::VertexBufferLayout], fragment_entry, format, blend| {
+            let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some(label),
+                bind_group_layouts: &[layout],
+                push_constant_ranges: &[],
+            });
+            device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some(label),
+                layout: Some(&pipeline_layout),
+                vertex: wgpu::VertexState {
+                    module: shader,
+                    entry_point: Some(vertex_entry),
+                    buffers,
+                    compilation_options: Default::default(),
+                },
+                fragment: Some(wgpu::FragmentState {
+                    module: shader,
+                    entry_point: Some(fragment_entry),
+                    targets: &[Some(wgpu::ColorTargetState { format, blend, write_mask: wgpu::ColorWrites::ALL })],
+                    compilation_options: Default::default(),
+                }),
+                primitive: wgpu::PrimitiveState::default(),
+                depth_stencil: None,
+                multisample: wgpu::MultisampleState::default(),
+                multiview: None,
+                cache: None,
+            })
+        };
+        let mask_shader = device.create_shader_module(wgpu::include_wgsl!("outline_mask.wgsl"));
+        let flood_shader = device.create_shader_module(wgpu::include_wgsl!("outline.wgsl"));
+        let mask_buffers = [MeshVertex::layout(), OutlineInstance::layout()];
+        let mask_pipeline = pipeline("outline mask", &mask_shader, &mask_layout, "vs_main", &mask_buffers, "fs_main", MASK_FORMAT, None);
+        let init_pipeline = pipeline("outline seed", &flood_shader, &flood_layout, "vs_fullscreen", &[], "fs_init", SEED_FORMAT, None);
+        let step_pipeline = pipeline("outline flood", &flood_shader, &flood_layout, "vs_fullscreen", &[], "fs_step", SEED_FORMAT, None);
+        let composite_pipeline = pipeline(
+            "outline composite",
+            &flood_shader,
+            &flood_layout,
+            "vs_fullscreen",
+            &[],
+            "fs_composite",
+            color_format,
+            Some(wgpu::BlendState::ALPHA_BLENDING),
+        );
+
+        let size = (width.max(1), height.max(1));
+        Self {
+            thickness: 3.0,
+            size,
+            mask: Target::new(device, resources, "outline mask", MASK_FORMAT, size),
+            seeds: [
+                Target::new(device, resources, "outline seeds", SEED_FORMAT, size),
+                Target::new(device, resources, "outline seeds", SEED_FORMAT, size),
+            ],
+            mask_params,
+            mask_bind_group,
+            mask_pipeline,
+            flood_params,
+            flood_layout,
+            init_pipeline,
+            step_pipeline,
+            composite_pipeline,
+        }
+    }
+
+    pub fn resize(&mut self, device: &wgpu::Device, resources: &ResourceRegistry, width: u32, height: u32) {
+        let size = (width.max(1), height.max(1));
+        if size == self.size {
+            return;
+        }
+        self.size = size;
+        self.mask = Target::new(device, resources, "outline mask", MASK_FORMAT, size);
+        self.seeds = [
+            Target::new(device, resources, "outline seeds", SEED_FORMAT, size),
+            Target::new(device, resources, "outline seeds", SEED_FORMAT, size),
+        ];
+    }
+
+    // Clears the mask and lets `draw_selected` fill it. The pass has the mask pipeline and
+    // camera set; bind mesh vertices at slot 0 and `OutlineInstance`s at slot 1.
+    pub fn render_mask<F>(
+        &self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        belt: &mut UploadBelt,
+        view_proj: Mat4,
+        draw_selected: F,
+    ) where
+        F: FnOnce(&mut wgpu::RenderPass<'_>),
+    {
+        belt.write(device, encoder, &self.mask_params, 0, bytemuck::bytes_of(&view_proj.to_cols_array()));
+        let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("outline mask"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: &self.mask.view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                    store: wgpu::StoreOp::Store,
+                },
+                depth_slice: None,
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        pass.set_pipeline(&self.mask_pipeline);
+        pass.set_bind_group(0, &self.mask_bind_group, &[]);
+        draw_selected(&mut pass);
+    }
+
+    // Floods the mask and blends the outlines over `target`
+    pub fn render(
+        &self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        belt: &mut UploadBelt,
+        bind_groups: &mut BindGroupCache,
+        target: &wgpu::TextureView,
+    ) {
+        let thickness = self.thickness.clamp(1.0, MAX_THICKNESS);
+        // Steps halve from the smallest power of two covering the thickness down to 1
+        let mut steps = Vec::new();
+        let mut step = (thickness.ceil() as u32).next_power_of_two() as i32;
+        while step >= 1 {
+            steps.push(step);
+            step /= 2;
+        }
+        // Slot 0 serves the seed and composite passes, the rest one flood step each
+        for (slot, step) in std::iter::once(0).chain(steps.iter().copied()).enumerate() {
+            let params = JumpFloodParams { step, thickness, _pad: [0; 2] };
+            belt.write(device, encoder, &self.flood_params, slot as u64 * PARAMS_STRIDE, bytemuck::bytes_of(&params));
+        }
+
+        let bind_group = |bind_groups: &mut BindGroupCache, slot: u64, seeds: &wgpu::TextureView| {
+            bind_groups.get_or_create(device, "outline flood", &self.flood_layout, &[
+                (0, BindingKey::Buffer {
+                    buffer: (*self.flood_params).clone(),
+                    offset: slot * PARAMS_STRIDE,
+                    size: Some(std::mem::size_of::<JumpFloodParams>() as u64),
+                }),
+                (1, BindingKey::TextureView(seeds.clone())),
+                (2, BindingKey::TextureView(self.mask.view.clone())),
+            ])
+        };
+        let fullscreen = |encoder: &mut wgpu::CommandEncoder, label, pipeline, bind_group: &wgpu::BindGroup, view, load| {
+            let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some(label),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view,
+                    resolve_target: None,
+                    ops: wgpu::Operations { load, store: wgpu::StoreOp::Store },
+                    depth_slice: None,
+                })],
+                depth_stencil_attachment: None,
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+            pass.set_pipeline(pipeline);
+            pass.set_bind_group(0, bind_group, &[]);
+            pass.draw(0..3, 0..1);
+        };
+
+        let clear = wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT);
+        // The seed pass doesn't read binding 1; the other seed target stands in for it
+        let init = bind_group(bind_groups, 0, &self.seeds[1].view);
+        fullscreen(encoder, "outline seed", &self.init_pipeline, &init, &self.seeds[0].view, clear);
+        let mut current = 0;
+        for slot in 1..=steps.len() as u64 {
+            let flood = bind_group(bind_groups, slot, &self.seeds[current].view);
+            fullscreen(encoder, "outline flood", &self.step_pipeline, &flood, &self.seeds[1 - current].view, clear);
+            current = 1 - current;
+        }
+        let composite = bind_group(bind_groups, 0, &self.seeds[current].view);
+        fullscreen(encoder, "outline composite", &self.composite_pipeline, &composite, target, wgpu::LoadOp::Load);
+    }
+}
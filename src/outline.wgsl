@@ -0,0 +1,72 @@
+// Jump-flood outlines: seeds every masked pixel with its own position, spreads the
+// nearest seed outwards in halving steps, then draws pixels within `thickness` of a
+// seed in that seed's mask colour. All passes draw one fullscreen triangle.
+
+struct JumpFloodParams {
+    step: i32,
+    thickness: f32,
+}
+
+@group(0) @binding(0) var<uniform> params: JumpFloodParams;
+@group(0) @binding(1) var seed_texture: texture_2d<f32>;
+@group(0) @binding(2) var mask_texture: texture_2d<f32>;
+
+@vertex
+fn vs_fullscreen(@builtin(vertex_index) index: u32) -> @builtin(position) vec4<f32> {
+    let uv = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+    return vec4<f32>(uv * 2.0 - 1.0, 0.0, 1.0);
+}
+
+// Pixels without a seed hold a negative coordinate
+@fragment
+fn fs_init(@builtin(position) frag_coord: vec4<f32>) -> @location(0) vec2<f32> {
+    let pixel = vec2<i32>(frag_coord.xy);
+    if (textureLoad(mask_texture, pixel, 0).a > 0.0) {
+        return vec2<f32>(pixel);
+    }
+    return vec2<f32>(-1.0);
+}
+
+@fragment
+fn fs_step(@builtin(position) frag_coord: vec4<f32>) -> @location(0) vec2<f32> {
+    let pixel = vec2<i32>(frag_coord.xy);
+    let size = vec2<i32>(textureDimensions(seed_texture));
+    var best = vec2<f32>(-1.0);
+    var best_distance = 1e30;
+    for (var y = -1; y <= 1; y++) {
+        for (var x = -1; x <= 1; x++) {
+            let tap = pixel + vec2<i32>(x, y) * params.step;
+            if (any(tap < vec2<i32>(0)) || any(tap >= size)) {
+                continue;
+            }
+            let seed = textureLoad(seed_texture, tap, 0).xy;
+            if (seed.x < 0.0) {
+                continue;
+            }
+            let offset = seed - vec2<f32>(pixel);
+            let distance = dot(offset, offset);
+            if (distance < best_distance) {
+                best_distance = distance;
+                best = seed;
+            }
+        }
+    }
+    return best;
+}
+
+@fragment
+fn fs_composite(@builtin(position) frag_coord: vec4<f32>) -> @location(0) vec4<f32> {
+    let pixel = vec2<i32>(frag_coord.xy);
+    let seed = textureLoad(seed_texture, pixel, 0).xy;
+    // Only outside the silhouette, so the selected object itself isn't tinted
+    if (seed.x < 0.0 || textureLoad(mask_texture, pixel, 0).a > 0.0) {
+        discard;
+    }
+    let distance = length(seed - vec2<f32>(pixel));
+    if (distance > params.thickness + 1.0) {
+        discard;
+    }
+    let color = textureLoad(mask_texture, vec2<i32>(seed), 0).rgb;
+    // One pixel of falloff keeps the outer edge smooth
+    return vec4<f32>(color, clamp(params.thickness + 1.0 - distance, 0.0, 1.0));
+}
@@ -0,0 +1,34 @@
+// Outline mask: selected meshes drawn flat in their outline colour, no depth test, so
+// outlines stay visible through occluders
+
+struct MaskParams {
+    view_proj: mat4x4<f32>,
+}
+
+@group(0) @binding(0) var<uniform> params: MaskParams;
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) color: vec4<f32>,
+}
+
+@vertex
+fn vs_main(
+    @location(0) position: vec3<f32>,
+    @location(3) model_0: vec4<f32>,
+    @location(4) model_1: vec4<f32>,
+    @location(5) model_2: vec4<f32>,
+    @location(6) model_3: vec4<f32>,
+    @location(7) color: vec4<f32>,
+) -> VertexOutput {
+    let model = mat4x4<f32>(model_0, model_1, model_2, model_3);
+    var out: VertexOutput;
+    out.position = params.view_proj * model * vec4<f32>(position, 1.0);
+    out.color = color;
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    return vec4<f32>(in.color.rgb, 1.0);
+}
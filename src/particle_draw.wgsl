@@ -0,0 +1,55 @@
+// Instanced particle rendering straight from the simulation buffers
+
+struct Particle {
+    position: vec2<f32>,
+    velocity: vec2<f32>,
+    color: vec4<f32>,
+    age: f32,
+    lifetime: f32,
+    size: f32,
+    _pad: f32,
+}
+
+struct DrawParams {
+    // Offset of the list that survived the last simulation step inside `indices`
+    alive_offset: u32,
+    _pad0: u32,
+    _pad1: u32,
+    _pad2: u32,
+}
+
+@group(0) @binding(0) var<storage, read> particles: array<Particle>;
+@group(0) @binding(1) var<storage, read> indices: array<u32>;
+@group(0) @binding(2) var<uniform> draw_params: DrawParams;
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) color: vec4<f32>,
+    @location(1) local: vec2<f32>,
+}
+
+@vertex
+fn vs_main(@builtin(vertex_index) vertex_index: u32, @builtin(instance_index) instance_index: u32) -> VertexOutput {
+    var corners = array<vec2<f32>, 6>(
+        vec2<f32>(-1.0, -1.0),
+        vec2<f32>(1.0, -1.0),
+        vec2<f32>(1.0, 1.0),
+        vec2<f32>(-1.0, -1.0),
+        vec2<f32>(1.0, 1.0),
+        vec2<f32>(-1.0, 1.0)
+    );
+    let p = particles[indices[draw_params.alive_offset + instance_index]];
+    let corner = corners[vertex_index];
+    var out: VertexOutput;
+    out.position = vec4<f32>(p.position + corner * p.size, 0.0, 1.0);
+    out.color = p.color;
+    out.local = corner;
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    // Soft round particles
+    let falloff = clamp(1.0 - length(in.local), 0.0, 1.0);
+    return vec4<f32>(in.color.rgb, in.color.a * falloff);
+}
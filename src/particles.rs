@@ -0,0 +1,352 @@
+// src/particles.rs
+use crate::resource_registry::{ResourceRegistry, Tracked};
+use crate::upload::UploadBelt;
+use glam::Vec2;
+use wgpu::util::DeviceExt;
+
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct GpuParticle {
+    position: [f32; 2],
+    velocity: [f32; 2],
+    color: [f32; 4],
+    age: f32,
+    lifetime: f32,
+    size: f32,
+    _pad: f32,
+}
+
+// Mirrors `SimParams` in particles.wgsl
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct SimParams {
+    emitter_position: [f32; 2],
+    gravity: [f32; 2],
+    start_color: [f32; 4],
+    end_color: [f32; 4],
+    direction: [f32; 2],
+    speed: f32,
+    spread: f32,
+    lifetime: f32,
+    size: f32,
+    dt: f32,
+    emit_count: u32,
+    seed: u32,
+    capacity: u32,
+    alive_in_offset: u32,
+    alive_out_offset: u32,
+    extent: [f32; 2],
+    _pad: [f32; 2],
+}
+
+// Byte offsets of the indirect arguments inside the control buffer (see `Control`)
+pub const ALIVE_COUNT_OFFSET: u64 = 36;
+const DRAW_ARGS_OFFSET: u64 = 32;
+const DISPATCH_ARGS_OFFSET: u64 = 48;
+const CONTROL_SIZE: usize = 16;
+const WORKGROUP_SIZE: u32 = 64;
+
+#[derive(Debug, Clone, Copy)]
+pub struct EmitterSettings {
+    pub position: Vec2,
+    // Half-size of the box particles spawn in; zero emits from a point
+    pub extent: Vec2,
+    pub direction: Vec2,
+    pub speed: f32,
+    // Cone angle in radians around `direction`
+    pub spread: f32,
+    pub lifetime: f32,
+    pub size: f32,
+    // Particles per second; use `burst` for one-off emission
+    pub rate: f32,
+    pub gravity: Vec2,
+    pub start_color: [f32; 4],
+    pub end_color: [f32; 4],
+}
+
+impl Default for EmitterSettings {
+    fn default() -> Self {
+        Self {
+            position: Vec2::ZERO,
+            extent: Vec2::ZERO,
+            direction: Vec2::Y,
+            speed: 0.5,
+            spread: 0.5,
+            lifetime: 2.0,
+            size: 0.01,
+            rate: 1000.0,
+            gravity: Vec2::new(0.0, -0.5),
+            start_color: [1.0, 0.8, 0.2, 1.0],
+            end_color: [1.0, 0.1, 0.0, 0.0],
+        }
+    }
+}
+
+// Particle system simulated entirely on the GPU: state lives in storage buffers,
+// compute kernels emit/update/compact it, and drawing uses the GPU-written alive count.
+pub struct GpuParticles {
+    pub emitter: EmitterSettings,
+    capacity: u32,
+    params_buffer: Tracked<wgpu::Buffer>,
+    control_buffer: Tracked<wgpu::Buffer>,
+    _particle_buffer: Tracked<wgpu::Buffer>,
+    _index_buffer: Tracked<wgpu::Buffer>,
+    _draw_params: [wgpu::Buffer; 2],
+    sim_bind_group: wgpu::BindGroup,
+    draw_bind_groups: [wgpu::BindGroup; 2],
+    begin_pipeline: wgpu::ComputePipeline,
+    emit_pipeline: wgpu::ComputePipeline,
+    update_pipeline: wgpu::ComputePipeline,
+    finish_pipeline: wgpu::ComputePipeline,
+    render_pipeline: wgpu::RenderPipeline,
+    // Which alive list is the in list this frame
+    parity: usize,
+    emit_accumulator: f32,
+    pending_burst: u32,
+    frame: u32,
+}
+
+impl GpuParticles {
+    pub fn new(
+        device: &wgpu::Device,
+        resources: &ResourceRegistry,
+        target_format: wgpu::TextureFormat,
+        capacity: u32,
+    ) -> Result<Self, String> {
+        if capacity == 0 {
+            return Err("Particle capacity must be at least 1".to_string());
+        }
+        let storage = wgpu::BufferUsages::STORAGE;
+        let particle_buffer = resources.create_buffer(device, &wgpu::BufferDescriptor {
+            label: Some("particles"),
+            size: capacity as u64 * std::mem::size_of::<GpuParticle>() as u64,
+            usage: storage,
+            mapped_at_creation: false,
+        }, "particles");
+
+        // Every particle starts out dead
+        let mut indices: Vec<u32> = (0..capacity).collect();
+        indices.resize(capacity as usize * 3, 0);
+        let index_buffer = resources.create_buffer_init(device, &wgpu::util::BufferInitDescriptor {
+            label: Some("particle indices"),
+            contents: bytemuck::cast_slice(&indices),
+            usage: storage,
+        }, "particles");
+
+        let mut control = [0u32; CONTROL_SIZE];
+        control[2] = capacity;
+        control[8] = 6;
+        control[13] = 1;
+        control[14] = 1;
+        let control_buffer = resources.create_buffer_init(device, &wgpu::util::BufferInitDescriptor {
+            label: Some("particle control"),
+            contents: bytemuck::cast_slice(&control),
+            usage: storage | wgpu::BufferUsages::INDIRECT | wgpu::BufferUsages::COPY_SRC,
+        }, "particles");
+
+        let params_buffer = resources.create_buffer(device, &wgpu::BufferDescriptor {
+            label: Some("particle params"),
+            size: std::mem::size_of::<SimParams>() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        }, "particles");
+
+        let sim_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("particle sim"),
+            entries: &[
+                layout_entry(0, wgpu::ShaderStages::COMPUTE, wgpu::BufferBindingType::Uniform),
+                layout_entry(1, wgpu::ShaderStages::COMPUTE, wgpu::BufferBindingType::Storage { read_only: false }),
+                layout_entry(2, wgpu::ShaderStages::COMPUTE, wgpu::BufferBindingType::Storage { read_only: false }),
+                layout_entry(3, wgpu::ShaderStages::COMPUTE, wgpu::BufferBindingType::Storage { read_only: false }),
+            ],
+        });
+        let sim_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("particle sim"),
+            layout: &sim_layout,
+            entries: &[
+                wgpu::BindGroupEntry { binding: 0, resource: params_buffer.as_entire_binding() },
+                wgpu::BindGroupEntry { binding: 1, resource: particle_buffer.as_entire_binding() },
+                wgpu::BindGroupEntry { binding: 2, resource: control_buffer.as_entire_binding() },
+                wgpu::BindGroupEntry { binding: 3, resource: index_buffer.as_entire_binding() },
+            ],
+        });
+
+        let sim_shader = device.create_shader_module(wgpu::include_wgsl!("particles.wgsl"));
+        let sim_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("particle sim"),
+            bind_group_layouts: &[&sim_layout],
+            push_constant_ranges: &[],
+        });
+        let compute_pipeline = |entry_point: &str| {
+            device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+                label: Some(entry_point),
+                layout: Some(&sim_pipeline_layout),
+                module: &sim_shader,
+                entry_point: Some(entry_point),
+                compilation_options: Default::default(),
+                cache: None,
+            })
+        };
+
+        let draw_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("particle draw"),
+            entries: &[
+                layout_entry(0, wgpu::ShaderStages::VERTEX, wgpu::BufferBindingType::Storage { read_only: true }),
+                layout_entry(1, wgpu::ShaderStages::VERTEX, wgpu::BufferBindingType::Storage { read_only: true }),
+                layout_entry(2, wgpu::ShaderStages::VERTEX, wgpu::BufferBindingType::Uniform),
+            ],
+        });
+        // One per alive list, so the offsets never need re-uploading
+        let draw_params = [1u32, 2u32].map(|list| {
+            device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("particle draw params"),
+                contents: bytemuck::cast_slice(&[list * capacity, 0, 0, 0]),
+                usage: wgpu::BufferUsages::UNIFORM,
+            })
+        });
+        let draw_bind_groups = [0, 1].map(|list| {
+            device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("particle draw"),
+                layout: &draw_layout,
+                entries: &[
+                    wgpu::BindGroupEntry { binding: 0, resource: particle_buffer.as_entire_binding() },
+                    wgpu::BindGroupEntry { binding: 1, resource: index_buffer.as_entire_binding() },
+                    wgpu::BindGroupEntry { binding: 2, resource: draw_params[list].as_entire_binding() },
+                ],
+            })
+        });
+
+        let draw_shader = device.create_shader_module(wgpu::include_wgsl!("particle_draw.wgsl"));
+        let draw_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("particle draw"),
+            bind_group_layouts: &[&draw_layout],
+            push_constant_ranges: &[],
+        });
+        let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("particle draw"),
+            layout: Some(&draw_pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &draw_shader,
+                entry_point: Some("vs_main"),
+                buffers: &[],
+                compilation_options: Default::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &draw_shader,
+                entry_point: Some("fs_main"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: target_format,
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: Default::default(),
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        });
+
+        Ok(Self {
+            emitter: EmitterSettings::default(),
+            capacity,
+            begin_pipeline: compute_pipeline("begin_frame"),
+            emit_pipeline: compute_pipeline("emit"),
+            update_pipeline: compute_pipeline("update"),
+            finish_pipeline: compute_pipeline("finish_frame"),
+            render_pipeline,
+            params_buffer,
+            control_buffer,
+            _particle_buffer: particle_buffer,
+            _index_buffer: index_buffer,
+            _draw_params: draw_params,
+            sim_bind_group,
+            draw_bind_groups,
+            parity: 0,
+            emit_accumulator: 0.0,
+            pending_burst: 0,
+            frame: 0,
+        })
+    }
+
+    pub fn capacity(&self) -> u32 {
+        self.capacity
+    }
+
+    // Counters and indirect args (see `Control` in particles.wgsl); the alive count is
+    // the u32 at `ALIVE_COUNT_OFFSET`, readable after copying out for stats
+    pub fn control_buffer(&self) -> &wgpu::Buffer {
+        &self.control_buffer
+    }
+
+    // Emits `count` extra particles on the next update
+    pub fn burst(&mut self, count: u32) {
+        self.pending_burst = self.pending_burst.saturating_add(count);
+    }
+
+    // Records this frame's simulation into `encoder`
+    pub fn update(&mut self, device: &wgpu::Device, encoder: &mut wgpu::CommandEncoder, belt: &mut UploadBelt, dt: f32) {
+        self.emit_accumulator += self.emitter.rate * dt;
+        let emit_count = self.emit_accumulator as u32 + std::mem::take(&mut self.pending_burst);
+        self.emit_accumulator = self.emit_accumulator.fract();
+        self.frame = self.frame.wrapping_add(1);
+
+        let alive_offsets = [self.capacity, self.capacity * 2];
+        let params = SimParams {
+            emitter_position: self.emitter.position.into(),
+            gravity: self.emitter.gravity.into(),
+            start_color: self.emitter.start_color,
+            end_color: self.emitter.end_color,
+            direction: self.emitter.direction.into(),
+            speed: self.emitter.speed,
+            spread: self.emitter.spread,
+            lifetime: self.emitter.lifetime,
+            size: self.emitter.size,
+            dt,
+            emit_count: emit_count.min(self.capacity),
+            seed: self.frame,
+            capacity: self.capacity,
+            alive_in_offset: alive_offsets[self.parity],
+            alive_out_offset: alive_offsets[1 - self.parity],
+            extent: self.emitter.extent.into(),
+            _pad: [0.0; 2],
+        };
+        belt.write(device, encoder, &self.params_buffer, 0, bytemuck::bytes_of(&params));
+
+        {
+            let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                label: Some("particle sim"),
+                timestamp_writes: None,
+            });
+            pass.set_bind_group(0, &self.sim_bind_group, &[]);
+            pass.set_pipeline(&self.begin_pipeline);
+            pass.dispatch_workgroups(1, 1, 1);
+            if params.emit_count > 0 {
+                pass.set_pipeline(&self.emit_pipeline);
+                pass.dispatch_workgroups(params.emit_count.div_ceil(WORKGROUP_SIZE), 1, 1);
+            }
+            pass.set_pipeline(&self.update_pipeline);
+            pass.dispatch_workgroups_indirect(&self.control_buffer, DISPATCH_ARGS_OFFSET);
+            pass.set_pipeline(&self.finish_pipeline);
+            pass.dispatch_workgroups(1, 1, 1);
+        }
+        self.parity = 1 - self.parity;
+    }
+
+    pub fn draw(&self, render_pass: &mut wgpu::RenderPass<'_>) {
+        render_pass.set_pipeline(&self.render_pipeline);
+        // After `update` flips the parity, the in list is the one that was just written
+        render_pass.set_bind_group(0, &self.draw_bind_groups[self.parity], &[]);
+        render_pass.draw_indirect(&self.control_buffer, DRAW_ARGS_OFFSET);
+    }
+}
+
+fn layout_entry(binding: u32, visibility: wgpu::ShaderStages, ty: wgpu::BufferBindingType) -> wgpu::BindGroupLayoutEntry {
+    wgpu::BindGroupLayoutEntry {
+        binding,
+        visibility,
+        ty: wgpu::BindingType::Buffer { ty, has_dynamic_offset: false, min_binding_size: None },
+        count: None,
+    }
+}
@@ -0,0 +1,150 @@
+// GPU particle simulation: begin_frame -> emit -> update (compacts the alive list) -> finish_frame
+
+struct Particle {
+    position: vec2<f32>,
+    velocity: vec2<f32>,
+    color: vec4<f32>,
+    age: f32,
+    lifetime: f32,
+    size: f32,
+    _pad: f32,
+}
+
+struct SimParams {
+    emitter_position: vec2<f32>,
+    gravity: vec2<f32>,
+    start_color: vec4<f32>,
+    end_color: vec4<f32>,
+    direction: vec2<f32>,
+    speed: f32,
+    spread: f32,
+    lifetime: f32,
+    size: f32,
+    dt: f32,
+    emit_count: u32,
+    seed: u32,
+    capacity: u32,
+    // Offsets of this frame's alive lists inside `indices`; they swap every frame
+    alive_in_offset: u32,
+    alive_out_offset: u32,
+    // Half-size of the box particles spawn in, around `emitter_position`
+    extent: vec2<f32>,
+    _pad: vec2<f32>,
+}
+
+// Counters plus the indirect arguments they feed, in one buffer
+struct Control {
+    alive_in: atomic<u32>,
+    alive_out: atomic<u32>,
+    dead: atomic<u32>,
+    emit_count: atomic<u32>,
+    dead_base: atomic<u32>,
+    _pad0: u32,
+    _pad1: u32,
+    _pad2: u32,
+    // DrawIndirectArgs
+    draw_vertex_count: u32,
+    draw_instance_count: u32,
+    draw_first_vertex: u32,
+    draw_first_instance: u32,
+    // DispatchIndirectArgs for next frame's update
+    dispatch_x: u32,
+    dispatch_y: u32,
+    dispatch_z: u32,
+    _pad3: u32,
+}
+
+@group(0) @binding(0) var<uniform> params: SimParams;
+@group(0) @binding(1) var<storage, read_write> particles: array<Particle>;
+@group(0) @binding(2) var<storage, read_write> control: Control;
+// [dead list | alive list A | alive list B], `capacity` entries each
+@group(0) @binding(3) var<storage, read_write> indices: array<u32>;
+
+const WORKGROUP_SIZE: u32 = 64u;
+
+fn hash(value: u32) -> u32 {
+    var x = value;
+    x = x ^ (x >> 16u);
+    x = x * 0x7feb352du;
+    x = x ^ (x >> 15u);
+    x = x * 0x846ca68bu;
+    x = x ^ (x >> 16u);
+    return x;
+}
+
+// Uniform float in [0, 1)
+fn random(seed: u32) -> f32 {
+    return f32(hash(seed) >> 8u) / 16777216.0;
+}
+
+// Decide how many particles can be emitted and reserve them from the top of the dead list
+@compute @workgroup_size(1)
+fn begin_frame() {
+    let dead = atomicLoad(&control.dead);
+    let emit = min(params.emit_count, dead);
+    atomicStore(&control.emit_count, emit);
+    atomicStore(&control.dead, dead - emit);
+    atomicStore(&control.dead_base, dead - emit);
+}
+
+// New particles go straight to the out list and start simulating next frame
+@compute @workgroup_size(64)
+fn emit(@builtin(global_invocation_id) id: vec3<u32>) {
+    let i = id.x;
+    if i >= atomicLoad(&control.emit_count) {
+        return;
+    }
+    let index = indices[atomicLoad(&control.dead_base) + i];
+    let seed = params.seed * 1973u + i * 9277u;
+
+    let base_angle = atan2(params.direction.y, params.direction.x);
+    let angle = base_angle + (random(seed) - 0.5) * params.spread;
+    let speed = params.speed * (0.5 + random(seed + 1u) * 0.5);
+
+    var p: Particle;
+    let offset = vec2<f32>(random(seed + 3u), random(seed + 4u)) * 2.0 - 1.0;
+    p.position = params.emitter_position + offset * params.extent;
+    p.velocity = vec2<f32>(cos(angle), sin(angle)) * speed;
+    p.color = params.start_color;
+    p.age = 0.0;
+    p.lifetime = params.lifetime * (0.75 + random(seed + 2u) * 0.5);
+    p.size = params.size;
+    particles[index] = p;
+
+    indices[params.alive_out_offset + atomicAdd(&control.alive_out, 1u)] = index;
+}
+
+@compute @workgroup_size(64)
+fn update(@builtin(global_invocation_id) id: vec3<u32>) {
+    let i = id.x;
+    if i >= atomicLoad(&control.alive_in) {
+        return;
+    }
+    let index = indices[params.alive_in_offset + i];
+    var p = particles[index];
+    p.age = p.age + params.dt;
+    if p.age >= p.lifetime {
+        indices[atomicAdd(&control.dead, 1u)] = index;
+        return;
+    }
+    p.velocity = p.velocity + params.gravity * params.dt;
+    p.position = p.position + p.velocity * params.dt;
+    p.color = mix(params.start_color, params.end_color, p.age / p.lifetime);
+    particles[index] = p;
+    indices[params.alive_out_offset + atomicAdd(&control.alive_out, 1u)] = index;
+}
+
+// Publish the survivors to the indirect draw and next frame's update dispatch
+@compute @workgroup_size(1)
+fn finish_frame() {
+    let alive = atomicLoad(&control.alive_out);
+    control.draw_vertex_count = 6u;
+    control.draw_instance_count = alive;
+    control.draw_first_vertex = 0u;
+    control.draw_first_instance = 0u;
+    control.dispatch_x = (alive + WORKGROUP_SIZE - 1u) / WORKGROUP_SIZE;
+    control.dispatch_y = 1u;
+    control.dispatch_z = 1u;
+    atomicStore(&control.alive_in, alive);
+    atomicStore(&control.alive_out, 0u);
+}
@@ -0,0 +1,199 @@
+// src/physics/character.rs
+use super::collision::{Collider, ColliderId, CollisionWorld, SweepHit};
+use super::layers::CollisionFilter;
+use super::shape::Shape;
+use glam::Vec2;
+
+// Sweeps per move; each hit uses one to slide along the surface
+const MAX_SLIDES: usize = 4;
+const DEPENETRATION_PASSES: usize = 4;
+
+#[derive(Debug, Clone, Default)]
+pub struct MoveResult {
+    // Motion actually applied
+    pub moved: Vec2,
+    pub grounded: bool,
+    pub ground_normal: Option<Vec2>,
+    pub hit_ceiling: bool,
+    pub hit_wall: bool,
+    // Every surface touched this move, in order
+    pub hits: Vec<SweepHit>,
+}
+
+// Kinematic body moved by game code rather than forces. `move_and_slide` stops at
+// obstacles and slides along them, walks up slopes up to `max_slope_degrees` and steps
+// up ledges up to `step_offset`, and sticks to the ground going down slopes and stairs.
+// +Y is up unless `up` says otherwise.
+#[derive(Debug, Clone)]
+pub struct CharacterController {
+    // Box or capsule; capsules slide over small ledges more smoothly
+    pub shape: Shape,
+    pub position: Vec2,
+    pub up: Vec2,
+    pub max_slope_degrees: f32,
+    pub step_offset: f32,
+    // How far below the feet the ground is still followed when walking downhill
+    pub snap_distance: f32,
+    // Gap kept to surfaces so the next sweep doesn't start touching them
+    pub skin: f32,
+    // The character's own collider in the world, if it has one; never collided with
+    pub collider: Option<ColliderId>,
+    // What the character bumps into; sensors never block it
+    pub filter: CollisionFilter,
+    grounded: bool,
+    ground_normal: Option<Vec2>,
+}
+
+impl CharacterController {
+    pub fn new(shape: Shape, position: Vec2) -> Self {
+        Self {
+            shape,
+            position,
+            up: Vec2::Y,
+            max_slope_degrees: 50.0,
+            step_offset: 0.25,
+            snap_distance: 0.2,
+            skin: 0.01,
+            collider: None,
+            filter: CollisionFilter::ALL,
+            grounded: false,
+            ground_normal: None,
+        }
+    }
+
+    pub fn is_grounded(&self) -> bool {
+        self.grounded
+    }
+
+    pub fn ground_normal(&self) -> Option<Vec2> {
+        self.ground_normal
+    }
+
+    fn is_walkable(&self, normal: Vec2) -> bool {
+        normal.dot(self.up) >= self.max_slope_degrees.to_radians().cos()
+    }
+
+    fn blocks(&self, id: ColliderId, collider: &Collider) -> bool {
+        Some(id) != self.collider && !collider.sensor && self.filter.interacts(&collider.filter)
+    }
+
+    fn sweep(&self, world: &CollisionWorld, from: Vec2, motion: Vec2) -> Option<SweepHit> {
+        world.sweep(&self.shape, from, motion, |id, collider| self.blocks(id, collider))
+    }
+
+    // Moves as far as possible along `motion`, keeping `skin` from whatever is hit
+    fn advance(&self, world: &CollisionWorld, from: Vec2, motion: Vec2) -> (Vec2, Option<SweepHit>) {
+        let length = motion.length();
+        if length < 1e-6 {
+            return (from, None);
+        }
+        match self.sweep(world, from, motion) {
+            Some(hit) => {
+                let travelled = (length * hit.fraction - self.skin).max(0.0);
+                (from + motion / length * travelled, Some(hit))
+            }
+            None => (from + motion, None),
+        }
+    }
+
+    // Pushes the character out of anything it was placed or moved into
+    fn depenetrate(&mut self, world: &CollisionWorld) {
+        for _ in 0..DEPENETRATION_PASSES {
+            let contacts = world.contacts(&self.shape, self.position, |id, collider| self.blocks(id, collider));
+            let Some(deepest) = contacts.iter().max_by(|a, b| a.depth.total_cmp(&b.depth)) else { break };
+            self.position += deepest.normal * (deepest.depth + self.skin);
+        }
+    }
+
+    // Tries to climb a ledge: up by `step_offset`, across, then back down onto walkable ground
+    fn try_step(&self, world: &CollisionWorld, from: Vec2, motion: Vec2) -> Option<(Vec2, Vec2)> {
+        let lateral = motion - self.up * motion.dot(self.up);
+        if self.step_offset <= 0.0 || lateral.length_squared() < 1e-8 {
+            return None;
+        }
+        let (raised, _) = self.advance(world, from, self.up * self.step_offset);
+        let (across, blocked) = self.advance(world, raised, lateral);
+        if blocked.is_some_and(|hit| !self.is_walkable(hit.normal)) && across.distance_squared(raised) < 1e-8 {
+            return None;
+        }
+        let drop = raised.distance(from) + self.skin * 2.0;
+        let (landed, ground) = self.advance(world, across, -self.up * drop);
+        let ground = ground.filter(|hit| self.is_walkable(hit.normal))?;
+        Some((landed, ground.normal))
+    }
+
+    pub fn move_and_slide(&mut self, world: &CollisionWorld, motion: Vec2) -> MoveResult {
+        let start = self.position;
+        let mut result = MoveResult::default();
+        self.depenetrate(world);
+
+        let was_grounded = self.grounded;
+        self.grounded = false;
+        self.ground_normal = None;
+        let falling = motion.dot(self.up) <= 0.0;
+        let mut remaining = motion;
+        for _ in 0..MAX_SLIDES {
+            if remaining.length_squared() < 1e-10 {
+                break;
+            }
+            let (position, hit) = self.advance(world, self.position, remaining);
+            let travelled = position - self.position;
+            self.position = position;
+            let Some(hit) = hit else { break };
+            result.hits.push(hit);
+            remaining -= travelled;
+
+            if self.is_walkable(hit.normal) {
+                self.grounded = true;
+                self.ground_normal = Some(hit.normal);
+                // Gravity pressing into the ground shouldn't slide the character downhill
+                let down = remaining.dot(self.up).min(0.0);
+                remaining -= self.up * down;
+            } else if hit.normal.dot(self.up) < -0.1 {
+                result.hit_ceiling = true;
+            } else {
+                if (was_grounded || self.grounded) && falling {
+                    if let Some((stepped, normal)) = self.try_step(world, self.position, remaining) {
+                        self.position = stepped;
+                        self.grounded = true;
+                        self.ground_normal = Some(normal);
+                        break;
+                    }
+                }
+                result.hit_wall = true;
+            }
+            let mut slid = remaining - hit.normal * remaining.dot(hit.normal);
+            // Sliding along a steep wall must not carry the character up it
+            if !self.is_walkable(hit.normal) && motion.dot(self.up) <= 0.0 {
+                slid -= self.up * slid.dot(self.up).max(0.0);
+            }
+            remaining = slid;
+        }
+
+        // Follow the ground down slopes and stairs instead of flying off them
+        if was_grounded && !self.grounded && falling && self.snap_distance > 0.0 {
+            let probe = -self.up * (self.snap_distance + self.skin);
+            if let (position, Some(hit)) = self.advance(world, self.position, probe) {
+                if self.is_walkable(hit.normal) {
+                    self.position = position;
+                    self.grounded = true;
+                    self.ground_normal = Some(hit.normal);
+                }
+            }
+        }
+        // Resting exactly on the ground: a zero-length move still counts as grounded
+        if !self.grounded && falling {
+            if let Some(hit) = self.sweep(world, self.position, -self.up * self.skin * 2.0) {
+                if self.is_walkable(hit.normal) {
+                    self.grounded = true;
+                    self.ground_normal = Some(hit.normal);
+                }
+            }
+        }
+
+        result.moved = self.position - start;
+        result.grounded = self.grounded;
+        result.ground_normal = self.ground_normal;
+        result
+    }
+}
@@ -0,0 +1,216 @@
+// src/physics/collision.rs
+use super::layers::CollisionFilter;
+use super::shape::{shape_penetration, sweep_shapes, Aabb, Shape};
+use glam::Vec2;
+use std::collections::HashMap;
+
+const DEFAULT_CELL_SIZE: f32 = 4.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ColliderId(u32);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Collider {
+    pub shape: Shape,
+    pub position: Vec2,
+    // Caller's handle for whatever owns the collider, e.g. `Entity::index`
+    pub user_data: u64,
+    pub filter: CollisionFilter,
+    // Detects overlaps but never blocks movement, e.g. pickups and trigger zones
+    pub sensor: bool,
+}
+
+impl Collider {
+    pub fn new(shape: Shape, position: Vec2) -> Self {
+        Self { shape, position, user_data: 0, filter: CollisionFilter::ALL, sensor: false }
+    }
+
+    pub fn with_user_data(mut self, user_data: u64) -> Self {
+        self.user_data = user_data;
+        self
+    }
+
+    pub fn with_filter(mut self, filter: CollisionFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    pub fn sensor(mut self) -> Self {
+        self.sensor = true;
+        self
+    }
+}
+
+// First contact of a swept shape
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SweepHit {
+    pub collider: ColliderId,
+    // Fraction of the motion travelled before touching, 0..=1
+    pub fraction: f32,
+    // Surface normal of the collider that was hit, pointing at the moving shape
+    pub normal: Vec2,
+    // Contact point on the collider's surface
+    pub point: Vec2,
+}
+
+// Overlap with a collider, and how to get out of it
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Contact {
+    pub collider: ColliderId,
+    pub normal: Vec2,
+    pub depth: f32,
+}
+
+// Colliders in a uniform grid broad-phase. Moving a collider re-bins it, so it suits
+// mostly static level geometry plus a handful of movers.
+pub struct CollisionWorld {
+    colliders: Vec<Option<Collider>>,
+    free: Vec<u32>,
+    cell_size: f32,
+    cells: HashMap<(i32, i32), Vec<ColliderId>>,
+}
+
+impl Default for CollisionWorld {
+    fn default() -> Self {
+        Self::new(DEFAULT_CELL_SIZE)
+    }
+}
+
+impl CollisionWorld {
+    // `cell_size` around the size of a typical collider works well
+    pub fn new(cell_size: f32) -> Self {
+        Self { colliders: Vec::new(), free: Vec::new(), cell_size: cell_size.max(0.01), cells: HashMap::new() }
+    }
+
+    fn cell_range(&self, aabb: &Aabb) -> ((i32, i32), (i32, i32)) {
+        let min = (aabb.min / self.cell_size).floor();
+        let max = (aabb.max / self.cell_size).floor();
+        ((min.x as i32, min.y as i32), (max.x as i32, max.y as i32))
+    }
+
+    fn bin(&mut self, id: ColliderId, collider: &Collider, insert: bool) {
+        let ((x0, y0), (x1, y1)) = self.cell_range(&collider.shape.aabb(collider.position));
+        for y in y0..=y1 {
+            for x in x0..=x1 {
+                if insert {
+                    self.cells.entry((x, y)).or_default().push(id);
+                } else if let Some(cell) = self.cells.get_mut(&(x, y)) {
+                    cell.retain(|&c| c != id);
+                    if cell.is_empty() {
+                        self.cells.remove(&(x, y));
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn insert(&mut self, collider: Collider) -> ColliderId {
+        let id = match self.free.pop() {
+            Some(index) => ColliderId(index),
+            None => {
+                self.colliders.push(None);
+                ColliderId(self.colliders.len() as u32 - 1)
+            }
+        };
+        self.bin(id, &collider, true);
+        self.colliders[id.0 as usize] = Some(collider);
+        id
+    }
+
+    pub fn remove(&mut self, id: ColliderId) -> Option<Collider> {
+        let collider = self.colliders.get_mut(id.0 as usize)?.take()?;
+        self.bin(id, &collider, false);
+        self.free.push(id.0);
+        Some(collider)
+    }
+
+    pub fn get(&self, id: ColliderId) -> Option<&Collider> {
+        self.colliders.get(id.0 as usize)?.as_ref()
+    }
+
+    pub fn set_position(&mut self, id: ColliderId, position: Vec2) -> bool {
+        let Some(mut collider) = self.get(id).copied() else { return false };
+        self.bin(id, &collider, false);
+        collider.position = position;
+        self.bin(id, &collider, true);
+        self.colliders[id.0 as usize] = Some(collider);
+        true
+    }
+
+    pub fn cell_size(&self) -> f32 {
+        self.cell_size
+    }
+
+    pub(crate) fn cell(&self, cell: (i32, i32)) -> &[ColliderId] {
+        self.cells.get(&cell).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    pub fn len(&self) -> usize {
+        self.colliders.len() - self.free.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (ColliderId, &Collider)> {
+        self.colliders.iter().enumerate().filter_map(|(i, c)| c.as_ref().map(|c| (ColliderId(i as u32), c)))
+    }
+
+    // Colliders whose bounds touch `aabb`, each once, in id order
+    pub fn query_aabb(&self, aabb: &Aabb) -> Vec<ColliderId> {
+        let ((x0, y0), (x1, y1)) = self.cell_range(aabb);
+        let mut found = Vec::new();
+        // Huge queries would walk millions of empty cells; scanning every collider is cheaper
+        if (x1 - x0 + 1) as i64 * (y1 - y0 + 1) as i64 > self.cells.len() as i64 * 4 {
+            found.extend(self.iter().filter(|(_, c)| c.shape.aabb(c.position).overlaps(aabb)).map(|(id, _)| id));
+            return found;
+        }
+        for y in y0..=y1 {
+            for x in x0..=x1 {
+                if let Some(cell) = self.cells.get(&(x, y)) {
+                    found.extend(cell.iter().copied().filter(|&id| self.colliders[id.0 as usize].is_some_and(|c| c.shape.aabb(c.position).overlaps(aabb))));
+                }
+            }
+        }
+        found.sort_unstable();
+        found.dedup();
+        found
+    }
+
+    // Sweeps `shape` from `position` along `motion`; the earliest hit among colliders that
+    // pass `filter`
+    pub fn sweep(&self, shape: &Shape, position: Vec2, motion: Vec2, filter: impl Fn(ColliderId, &Collider) -> bool) -> Option<SweepHit> {
+        let bounds = shape.aabb(position).union(&shape.aabb(position + motion));
+        let mut best: Option<SweepHit> = None;
+        for id in self.query_aabb(&bounds) {
+            let collider = self.colliders[id.0 as usize].as_ref().expect("query returns live colliders");
+            if !filter(id, collider) {
+                continue;
+            }
+            let Some((fraction, normal)) = sweep_shapes(shape, &collider.shape, position - collider.position, motion) else {
+                continue;
+            };
+            if best.is_none_or(|b| fraction < b.fraction) {
+                let point = position + motion * fraction + shape.support(-normal);
+                best = Some(SweepHit { collider: id, fraction, normal, point });
+            }
+        }
+        best
+    }
+
+    // Every collider `shape` at `position` overlaps, with the way out of each
+    pub fn contacts(&self, shape: &Shape, position: Vec2, filter: impl Fn(ColliderId, &Collider) -> bool) -> Vec<Contact> {
+        self.query_aabb(&shape.aabb(position))
+            .into_iter()
+            .filter_map(|id| {
+                let collider = self.colliders[id.0 as usize].as_ref()?;
+                if !filter(id, collider) {
+                    return None;
+                }
+                let (normal, depth) = shape_penetration(shape, &collider.shape, position - collider.position)?;
+                Some(Contact { collider: id, normal, depth })
+            })
+            .collect()
+    }
+}
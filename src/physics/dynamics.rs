@@ -0,0 +1,249 @@
+// src/physics/dynamics.rs
+use super::collision::{ColliderId, CollisionWorld};
+use super::joints::{Joint, JointId};
+use glam::Vec2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct BodyId(pub(crate) u32);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RigidBody {
+    pub position: Vec2,
+    // Radians, counter-clockwise
+    pub angle: f32,
+    pub velocity: Vec2,
+    pub angular_velocity: f32,
+    // Zero for static and kinematic bodies, which joints can't move
+    pub inverse_mass: f32,
+    pub inverse_inertia: f32,
+    pub gravity_scale: f32,
+    // Follows the body after each step, see `PhysicsWorld::sync_colliders`
+    pub collider: Option<ColliderId>,
+    pub(crate) previous_position: Vec2,
+    pub(crate) previous_angle: f32,
+}
+
+impl RigidBody {
+    // Mass and inertia of a solid `width` x `height` box
+    pub fn dynamic_box(position: Vec2, width: f32, height: f32, mass: f32) -> Self {
+        let inertia = mass * (width * width + height * height) / 12.0;
+        Self::new(position, 1.0 / mass.max(1e-6), 1.0 / inertia.max(1e-6))
+    }
+
+    pub fn dynamic_circle(position: Vec2, radius: f32, mass: f32) -> Self {
+        let inertia = 0.5 * mass * radius * radius;
+        Self::new(position, 1.0 / mass.max(1e-6), 1.0 / inertia.max(1e-6))
+    }
+
+    // Never moves on its own; anchors joints to the world
+    pub fn fixed(position: Vec2) -> Self {
+        Self::new(position, 0.0, 0.0)
+    }
+
+    fn new(position: Vec2, inverse_mass: f32, inverse_inertia: f32) -> Self {
+        Self {
+            position,
+            angle: 0.0,
+            velocity: Vec2::ZERO,
+            angular_velocity: 0.0,
+            inverse_mass,
+            inverse_inertia,
+            gravity_scale: 1.0,
+            collider: None,
+            previous_position: position,
+            previous_angle: 0.0,
+        }
+    }
+
+    // Body-local point to world space
+    pub fn world_point(&self, local: Vec2) -> Vec2 {
+        self.position + Vec2::from_angle(self.angle).rotate(local)
+    }
+
+    pub fn local_point(&self, world: Vec2) -> Vec2 {
+        Vec2::from_angle(-self.angle).rotate(world - self.position)
+    }
+
+    pub fn is_dynamic(&self) -> bool {
+        self.inverse_mass > 0.0 || self.inverse_inertia > 0.0
+    }
+
+    pub fn apply_impulse(&mut self, impulse: Vec2, world_point: Vec2) {
+        self.velocity += impulse * self.inverse_mass;
+        self.angular_velocity += (world_point - self.position).perp_dot(impulse) * self.inverse_inertia;
+    }
+}
+
+// Rigid bodies connected by joints, stepped with substepped position-based dynamics
+// (XPBD): stiff joints stay stiff at any mass ratio without tuning iteration counts.
+// Bodies don't collide with each other or the level yet; joints are the only constraints.
+pub struct PhysicsWorld {
+    pub gravity: Vec2,
+    // More substeps mean stiffer, more accurate joints for proportionally more work
+    pub substeps: u32,
+    bodies: Vec<Option<RigidBody>>,
+    free_bodies: Vec<u32>,
+    pub(crate) joints: Vec<Option<Joint>>,
+    free_joints: Vec<u32>,
+    broken: Vec<JointId>,
+}
+
+impl Default for PhysicsWorld {
+    fn default() -> Self {
+        Self {
+            gravity: Vec2::new(0.0, -9.81),
+            substeps: 8,
+            bodies: Vec::new(),
+            free_bodies: Vec::new(),
+            joints: Vec::new(),
+            free_joints: Vec::new(),
+            broken: Vec::new(),
+        }
+    }
+}
+
+impl PhysicsWorld {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_body(&mut self, mut body: RigidBody) -> BodyId {
+        body.previous_position = body.position;
+        body.previous_angle = body.angle;
+        match self.free_bodies.pop() {
+            Some(index) => {
+                self.bodies[index as usize] = Some(body);
+                BodyId(index)
+            }
+            None => {
+                self.bodies.push(Some(body));
+                BodyId(self.bodies.len() as u32 - 1)
+            }
+        }
+    }
+
+    // Also removes every joint attached to the body
+    pub fn remove_body(&mut self, id: BodyId) -> Option<RigidBody> {
+        let body = self.bodies.get_mut(id.0 as usize)?.take()?;
+        self.free_bodies.push(id.0);
+        let attached: Vec<JointId> = self.joints().filter(|(_, j)| j.body_a == id || j.body_b == id).map(|(jid, _)| jid).collect();
+        for joint in attached {
+            self.remove_joint(joint);
+        }
+        Some(body)
+    }
+
+    pub fn body(&self, id: BodyId) -> Option<&RigidBody> {
+        self.bodies.get(id.0 as usize)?.as_ref()
+    }
+
+    pub fn body_mut(&mut self, id: BodyId) -> Option<&mut RigidBody> {
+        self.bodies.get_mut(id.0 as usize)?.as_mut()
+    }
+
+    pub fn bodies(&self) -> impl Iterator<Item = (BodyId, &RigidBody)> {
+        self.bodies.iter().enumerate().filter_map(|(i, b)| b.as_ref().map(|b| (BodyId(i as u32), b)))
+    }
+
+    // Fails if either body doesn't exist or both are the same body
+    pub fn add_joint(&mut self, joint: Joint) -> Result<JointId, String> {
+        if joint.body_a == joint.body_b {
+            return Err("A joint needs two different bodies".to_string());
+        }
+        if self.body(joint.body_a).is_none() || self.body(joint.body_b).is_none() {
+            return Err("Joint refers to a body that doesn't exist".to_string());
+        }
+        Ok(match self.free_joints.pop() {
+            Some(index) => {
+                self.joints[index as usize] = Some(joint);
+                JointId(index)
+            }
+            None => {
+                self.joints.push(Some(joint));
+                JointId(self.joints.len() as u32 - 1)
+            }
+        })
+    }
+
+    pub fn remove_joint(&mut self, id: JointId) -> Option<Joint> {
+        let joint = self.joints.get_mut(id.0 as usize)?.take()?;
+        self.free_joints.push(id.0);
+        Some(joint)
+    }
+
+    pub fn joint(&self, id: JointId) -> Option<&Joint> {
+        self.joints.get(id.0 as usize)?.as_ref()
+    }
+
+    pub fn joint_mut(&mut self, id: JointId) -> Option<&mut Joint> {
+        self.joints.get_mut(id.0 as usize)?.as_mut()
+    }
+
+    pub fn joints(&self) -> impl Iterator<Item = (JointId, &Joint)> {
+        self.joints.iter().enumerate().filter_map(|(i, j)| j.as_ref().map(|j| (JointId(i as u32), j)))
+    }
+
+    // Joints that exceeded their break force since the last call; already removed
+    pub fn take_broken(&mut self) -> Vec<JointId> {
+        std::mem::take(&mut self.broken)
+    }
+
+    // Removes a joint that exceeded its break force and reports it to `take_broken`
+    pub(crate) fn break_joint(&mut self, index: usize) {
+        if self.joints.get_mut(index).and_then(Option::take).is_some() {
+            self.free_joints.push(index as u32);
+            self.broken.push(JointId(index as u32));
+        }
+    }
+
+    // Two bodies at once, for solving a joint between them
+    pub(crate) fn pair_mut(&mut self, a: BodyId, b: BodyId) -> Option<(&mut RigidBody, &mut RigidBody)> {
+        let (a, b) = (a.0 as usize, b.0 as usize);
+        if a == b || a >= self.bodies.len() || b >= self.bodies.len() {
+            return None;
+        }
+        let (first, second) = self.bodies.split_at_mut(a.max(b));
+        let (low, high) = (first[a.min(b)].as_mut()?, second[0].as_mut()?);
+        Some(if a < b { (low, high) } else { (high, low) })
+    }
+
+    pub fn step(&mut self, delta_time: f32) {
+        let substeps = self.substeps.max(1);
+        let h = delta_time / substeps as f32;
+        if h <= 0.0 {
+            return;
+        }
+        for _ in 0..substeps {
+            self.apply_spring_forces(h);
+            for body in self.bodies.iter_mut().flatten().filter(|b| b.is_dynamic()) {
+                if body.inverse_mass > 0.0 {
+                    body.velocity += self.gravity * body.gravity_scale * h;
+                }
+                body.previous_position = body.position;
+                body.previous_angle = body.angle;
+                body.position += body.velocity * h;
+                body.angle += body.angular_velocity * h;
+            }
+            for index in 0..self.joints.len() {
+                let Some(force) = self.solve_joint(index, h) else { continue };
+                let limit = self.joints[index].as_ref().and_then(|j| j.break_force);
+                if limit.is_some_and(|limit| force > limit) {
+                    self.break_joint(index);
+                }
+            }
+            for body in self.bodies.iter_mut().flatten().filter(|b| b.is_dynamic()) {
+                body.velocity = (body.position - body.previous_position) / h;
+                body.angular_velocity = (body.angle - body.previous_angle) / h;
+            }
+        }
+    }
+
+    // Moves each body's collider to the body
+    pub fn sync_colliders(&self, collision: &mut CollisionWorld) {
+        for body in self.bodies.iter().flatten() {
+            if let Some(collider) = body.collider {
+                collision.set_position(collider, body.position);
+            }
+        }
+    }
+}
@@ -0,0 +1,301 @@
+// src/physics/joints.rs
+use super::dynamics::{BodyId, PhysicsWorld, RigidBody};
+use crate::ecs::world::{Added, ChangeCursor, Entity, World};
+use glam::Vec2;
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct JointId(pub(crate) u32);
+
+// Anchors are body-local points
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum JointKind {
+    // Pins the anchors together; the bodies rotate freely about the pin
+    Revolute { anchor_a: Vec2, anchor_b: Vec2 },
+    // B slides along `axis` (local to A) without rotating relative to A, optionally
+    // between `limits` measured from anchor to anchor
+    Prismatic { anchor_a: Vec2, anchor_b: Vec2, axis: Vec2, limits: Option<(f32, f32)> },
+    // Keeps the anchors between `min` and `max` apart; min 0 makes a rope segment
+    Distance { anchor_a: Vec2, anchor_b: Vec2, min: f32, max: f32 },
+    // Pulls towards `rest_length` with `stiffness` N/m, losing energy with `damping` N·s/m
+    Spring { anchor_a: Vec2, anchor_b: Vec2, rest_length: f32, stiffness: f32, damping: f32 },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Joint {
+    pub body_a: BodyId,
+    pub body_b: BodyId,
+    pub kind: JointKind,
+    // Newtons; the joint is removed when it has to push harder than this
+    pub break_force: Option<f32>,
+    // 0 is rigid; higher values make positional joints give like a stiff spring (m/N)
+    pub compliance: f32,
+    // Relative angle B - A at creation, held by prismatic joints
+    pub(crate) reference_angle: f32,
+}
+
+impl Joint {
+    pub fn new(body_a: BodyId, body_b: BodyId, kind: JointKind) -> Self {
+        Self { body_a, body_b, kind, break_force: None, compliance: 0.0, reference_angle: 0.0 }
+    }
+
+    pub fn with_break_force(mut self, force: f32) -> Self {
+        self.break_force = Some(force);
+        self
+    }
+
+    pub fn with_compliance(mut self, compliance: f32) -> Self {
+        self.compliance = compliance.max(0.0);
+        self
+    }
+
+    fn anchors(&self) -> (Vec2, Vec2) {
+        match self.kind {
+            JointKind::Revolute { anchor_a, anchor_b }
+            | JointKind::Prismatic { anchor_a, anchor_b, .. }
+            | JointKind::Distance { anchor_a, anchor_b, .. }
+            | JointKind::Spring { anchor_a, anchor_b, .. } => (anchor_a, anchor_b),
+        }
+    }
+}
+
+// Generalized inverse mass of a body for a correction along `direction` at arm `r`
+fn weight(body: &RigidBody, r: Vec2, direction: Vec2) -> f32 {
+    let arm = r.perp_dot(direction);
+    body.inverse_mass + body.inverse_inertia * arm * arm
+}
+
+// XPBD positional correction reducing `error` along unit `direction` (from A's anchor to
+// B's). Returns the Lagrange multiplier applied.
+#[allow(clippy::too_many_arguments)]
+fn correct_position(a: &mut RigidBody, b: &mut RigidBody, r_a: Vec2, r_b: Vec2, direction: Vec2, error: f32, compliance: f32, h: f32) -> f32 {
+    let w = weight(a, r_a, direction) + weight(b, r_b, direction);
+    let alpha = compliance / (h * h);
+    if w + alpha <= 0.0 {
+        return 0.0;
+    }
+    let lambda = -error / (w + alpha);
+    let p = direction * lambda;
+    a.position -= p * a.inverse_mass;
+    a.angle -= r_a.perp_dot(p) * a.inverse_inertia;
+    b.position += p * b.inverse_mass;
+    b.angle += r_b.perp_dot(p) * b.inverse_inertia;
+    lambda
+}
+
+fn correct_angle(a: &mut RigidBody, b: &mut RigidBody, error: f32, compliance: f32, h: f32) -> f32 {
+    let w = a.inverse_inertia + b.inverse_inertia;
+    let alpha = compliance / (h * h);
+    if w + alpha <= 0.0 {
+        return 0.0;
+    }
+    let lambda = -error / (w + alpha);
+    a.angle -= lambda * a.inverse_inertia;
+    b.angle += lambda * b.inverse_inertia;
+    lambda
+}
+
+impl PhysicsWorld {
+    // Like `add_joint`, but anchored where the bodies are now: prismatic joints keep the
+    // current relative angle
+    pub fn connect(&mut self, mut joint: Joint) -> Result<JointId, String> {
+        if let (Some(a), Some(b)) = (self.body(joint.body_a), self.body(joint.body_b)) {
+            joint.reference_angle = b.angle - a.angle;
+        }
+        self.add_joint(joint)
+    }
+
+    // Springs act as forces on velocity before the positional solve. One pulled or pushed
+    // harder than its `break_force` snaps instead, like the joints in `step`.
+    pub(crate) fn apply_spring_forces(&mut self, h: f32) {
+        for index in 0..self.joints.len() {
+            let Some(joint) = self.joints[index] else { continue };
+            let JointKind::Spring { anchor_a, anchor_b, rest_length, stiffness, damping } = joint.kind else { continue };
+            let Some((a, b)) = self.pair_mut(joint.body_a, joint.body_b) else { continue };
+            let (pa, pb) = (a.world_point(anchor_a), b.world_point(anchor_b));
+            let offset = pb - pa;
+            let length = offset.length();
+            if length < 1e-6 {
+                continue;
+            }
+            let direction = offset / length;
+            let velocity_b = b.velocity + (pb - b.position).perp() * b.angular_velocity;
+            let velocity_a = a.velocity + (pa - a.position).perp() * a.angular_velocity;
+            let closing = (velocity_b - velocity_a).dot(direction);
+            let force = stiffness * (length - rest_length) + damping * closing;
+            if joint.break_force.is_some_and(|limit| force.abs() > limit) {
+                self.break_joint(index);
+                continue;
+            }
+            a.apply_impulse(direction * force * h, pa);
+            b.apply_impulse(-direction * force * h, pb);
+        }
+    }
+
+    // Solves one joint for one substep; the force it took, for breaking. Springs break in
+    // `apply_spring_forces` instead.
+    pub(crate) fn solve_joint(&mut self, index: usize, h: f32) -> Option<f32> {
+        let joint = self.joints.get(index).copied().flatten()?;
+        if matches!(joint.kind, JointKind::Spring { .. }) {
+            return None;
+        }
+        let (a, b) = self.pair_mut(joint.body_a, joint.body_b)?;
+        let (anchor_a, anchor_b) = joint.anchors();
+        let (pa, pb) = (a.world_point(anchor_a), b.world_point(anchor_b));
+        let (r_a, r_b) = (pa - a.position, pb - b.position);
+        let offset = pb - pa;
+        let length = offset.length();
+        let mut lambda = 0.0f32;
+        match joint.kind {
+            JointKind::Revolute { .. } => {
+                if length > 1e-6 {
+                    lambda = correct_position(a, b, r_a, r_b, offset / length, length, joint.compliance, h);
+                }
+            }
+            JointKind::Distance { min, max, .. } => {
+                let target = length.clamp(min, max.max(min));
+                if length > 1e-6 && length != target {
+                    lambda = correct_position(a, b, r_a, r_b, offset / length, length - target, joint.compliance, h);
+                }
+            }
+            JointKind::Prismatic { axis, limits, .. } => {
+                let angle_error = b.angle - a.angle - joint.reference_angle;
+                let angular = correct_angle(a, b, angle_error, joint.compliance, h);
+                let axis = Vec2::from_angle(a.angle).rotate(axis.normalize_or_zero());
+                let (pa, pb) = (a.world_point(anchor_a), b.world_point(anchor_b));
+                let (r_a, r_b) = (pa - a.position, pb - b.position);
+                let offset = pb - pa;
+                let normal = axis.perp();
+                let lateral = correct_position(a, b, r_a, r_b, normal, offset.dot(normal), joint.compliance, h);
+                lambda = lateral.hypot(angular);
+                if let Some((lower, upper)) = limits {
+                    let along = offset.dot(axis);
+                    let target = along.clamp(lower, upper.max(lower));
+                    if along != target {
+                        let (pa, pb) = (a.world_point(anchor_a), b.world_point(anchor_b));
+                        let limit = correct_position(a, b, pa - a.position, pb - b.position, axis, along - target, joint.compliance, h);
+                        lambda = lambda.hypot(limit);
+                    }
+                }
+            }
+            JointKind::Spring { .. } => {}
+        }
+        Some(lambda.abs() / (h * h))
+    }
+}
+
+// Bodies and joints making up a rope or chain
+#[derive(Debug, Clone, Default)]
+pub struct Rope {
+    pub bodies: Vec<BodyId>,
+    pub joints: Vec<JointId>,
+}
+
+// Hangs `segments` small bodies from `start`'s local `start_anchor` towards `end` (another
+// body and local anchor) or straight down. Rope segments can bunch up; chains (`rigid`)
+// keep every link at full length.
+#[allow(clippy::too_many_arguments)]
+pub fn rope(
+    physics: &mut PhysicsWorld,
+    start: BodyId,
+    start_anchor: Vec2,
+    end: Option<(BodyId, Vec2)>,
+    segments: u32,
+    segment_length: f32,
+    segment_mass: f32,
+    rigid: bool,
+) -> Result<Rope, String> {
+    let origin = physics.body(start).ok_or("Rope start body doesn't exist")?.world_point(start_anchor);
+    let direction = match end {
+        Some((body, anchor)) => {
+            let target = physics.body(body).ok_or("Rope end body doesn't exist")?.world_point(anchor);
+            (target - origin).normalize_or(Vec2::NEG_Y)
+        }
+        None => Vec2::NEG_Y,
+    };
+    let min = if rigid { segment_length } else { 0.0 };
+    let radius = segment_length * 0.25;
+    let mut rope = Rope::default();
+    let (mut previous, mut previous_anchor) = (start, start_anchor);
+    for i in 0..segments.max(1) {
+        let position = origin + direction * segment_length * (i as f32 + 1.0);
+        let body = physics.add_body(RigidBody::dynamic_circle(position, radius, segment_mass));
+        let kind = JointKind::Distance { anchor_a: previous_anchor, anchor_b: Vec2::ZERO, min, max: segment_length };
+        rope.joints.push(physics.add_joint(Joint::new(previous, body, kind))?);
+        rope.bodies.push(body);
+        (previous, previous_anchor) = (body, Vec2::ZERO);
+    }
+    if let Some((body, anchor)) = end {
+        let kind = JointKind::Distance { anchor_a: Vec2::ZERO, anchor_b: anchor, min, max: segment_length };
+        rope.joints.push(physics.add_joint(Joint::new(previous, body, kind))?);
+    }
+    Ok(rope)
+}
+
+// ECS side: an entity's body in the `PhysicsWorld` resource
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Body(pub BodyId);
+
+// Asks for a joint from this entity's body to `other`'s. `sync_joints` creates it; the
+// bodies' ids in `joint` are filled in from the entities.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct JointComponent {
+    pub other: Entity,
+    pub kind: JointKind,
+    pub break_force: Option<f32>,
+}
+
+// Set by `sync_joints` once the joint exists
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct JointHandle(pub JointId);
+
+// `sync_joints` state: which entity owns each joint, so joints of despawned entities can
+// be cleaned up
+#[derive(Debug, Default)]
+pub struct JointOwners {
+    owners: HashMap<JointId, Entity>,
+    cursor: ChangeCursor,
+}
+
+// System keeping the `PhysicsWorld` resource's joints in step with `JointComponent`s:
+// creates new ones, and drops components of joints that broke or whose entity is gone
+pub fn sync_joints(world: &mut World, _delta_time: f64) {
+    let Some(mut physics) = world.resources.remove::<PhysicsWorld>() else { return };
+    let mut state = world.resources.remove::<JointOwners>().unwrap_or_default();
+    let since = state.cursor.since(world);
+    let owners = &mut state.owners;
+
+    let requested: Vec<(Entity, JointComponent)> =
+        world.query_filtered::<JointComponent, Added<JointComponent>>(since).map(|(e, j)| (e, *j)).collect();
+    for (entity, request) in requested {
+        let (Some(a), Some(b)) = (world.get::<Body>(entity), world.get::<Body>(request.other)) else {
+            log::warn!("Joint on {:?} needs a Body on both entities when it is added", entity);
+            continue;
+        };
+        let joint = Joint { break_force: request.break_force, ..Joint::new(a.0, b.0, request.kind) };
+        match physics.connect(joint) {
+            Ok(id) => {
+                world.insert(entity, JointHandle(id));
+                owners.insert(id, entity);
+            }
+            Err(e) => log::warn!("Joint on {:?} not created: {}", entity, e),
+        }
+    }
+
+    for id in physics.take_broken() {
+        if let Some(entity) = owners.remove(&id) {
+            world.remove::<JointHandle>(entity);
+            world.remove::<JointComponent>(entity);
+        }
+    }
+    owners.retain(|&id, &mut entity| {
+        let alive = world.is_alive(entity) && world.get::<JointHandle>(entity) == Some(&JointHandle(id));
+        if !alive {
+            physics.remove_joint(id);
+        }
+        alive
+    });
+
+    world.resources.insert(physics);
+    world.resources.insert(state);
+}
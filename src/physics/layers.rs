@@ -0,0 +1,121 @@
+// src/physics/layers.rs
+use serde::Deserialize;
+
+pub const MAX_LAYERS: usize = 32;
+
+// Which colliders may touch. Two colliders interact when each one's `mask` includes the
+// other's `layers`, unless a shared non-zero `group` overrides it: positive groups always
+// interact, negative groups never do (e.g. a shooter and its bullets).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CollisionFilter {
+    // Layers this collider is on, one bit each
+    pub layers: u32,
+    // Layers this collider interacts with
+    pub mask: u32,
+    pub group: i32,
+}
+
+impl Default for CollisionFilter {
+    fn default() -> Self {
+        Self::ALL
+    }
+}
+
+impl CollisionFilter {
+    // On the first layer, interacting with everything
+    pub const ALL: CollisionFilter = CollisionFilter { layers: 1, mask: u32::MAX, group: 0 };
+
+    pub fn new(layers: u32, mask: u32) -> Self {
+        Self { layers, mask, group: 0 }
+    }
+
+    pub fn with_group(mut self, group: i32) -> Self {
+        self.group = group;
+        self
+    }
+
+    pub fn interacts(&self, other: &CollisionFilter) -> bool {
+        if self.group != 0 && self.group == other.group {
+            return self.group > 0;
+        }
+        self.mask & other.layers != 0 && other.mask & self.layers != 0
+    }
+}
+
+// Named layers and which pairs interact, loaded from RON, e.g.
+// `(layers: ["world", "player", "enemy", "bullet"], ignore: [("bullet", "bullet")])`
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct LayerConfig {
+    pub layers: Vec<String>,
+    // Pairs that never interact; every other pair does
+    pub ignore: Vec<(String, String)>,
+    // Overrides for layers that only interact with the listed ones, e.g. pickup triggers
+    // that only notice the player
+    pub only: Vec<(String, Vec<String>)>,
+}
+
+impl LayerConfig {
+    pub fn from_ron(source: &str) -> Result<Self, String> {
+        let config: Self = ron::from_str(source).map_err(|e| format!("Failed to parse layer config: {}", e))?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    pub fn load(path: &str) -> Result<Self, String> {
+        let source = std::fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+        Self::from_ron(&source)
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if self.layers.len() > MAX_LAYERS {
+            return Err(format!("{} collision layers, at most {} are supported", self.layers.len(), MAX_LAYERS));
+        }
+        for (i, name) in self.layers.iter().enumerate() {
+            if self.layers[..i].contains(name) {
+                return Err(format!("Collision layer '{}' is defined twice", name));
+            }
+        }
+        let names = self.ignore.iter().flat_map(|(a, b)| [a, b]).chain(self.only.iter().flat_map(|(a, list)| std::iter::once(a).chain(list)));
+        for name in names {
+            self.bit(name)?;
+        }
+        Ok(())
+    }
+
+    fn bit(&self, name: &str) -> Result<u32, String> {
+        self.layers
+            .iter()
+            .position(|layer| layer == name)
+            .map(|i| 1 << i)
+            .ok_or_else(|| format!("Unknown collision layer '{}'", name))
+    }
+
+    // Layers `name` interacts with. Interaction is symmetric, so restricting one side via
+    // `only` removes that layer from everyone else's mask too.
+    fn mask(&self, name: &str) -> Result<u32, String> {
+        self.bit(name)?;
+        let mut mask = (0..self.layers.len()).fold(0u32, |mask, i| mask | (1 << i));
+        for (a, b) in &self.ignore {
+            if a == name {
+                mask &= !self.bit(b)?;
+            }
+            if b == name {
+                mask &= !self.bit(a)?;
+            }
+        }
+        for (layer, allowed) in &self.only {
+            if layer == name {
+                mask &= allowed.iter().try_fold(0u32, |mask, n| self.bit(n).map(|bit| mask | bit))?;
+            } else if !allowed.iter().any(|n| n == name) {
+                mask &= !self.bit(layer)?;
+            }
+        }
+        Ok(mask)
+    }
+
+    // Filter for a collider on layer `name`
+    pub fn filter(&self, name: &str) -> Result<CollisionFilter, String> {
+        Ok(CollisionFilter::new(self.bit(name)?, self.mask(name)?))
+    }
+}
@@ -0,0 +1,8 @@
+// src/physics/mod.rs
+pub mod character;
+pub mod collision;
+pub mod dynamics;
+pub mod joints;
+pub mod layers;
+pub mod query;
+pub mod shape;
@@ -0,0 +1,164 @@
+// src/physics/query.rs
+use super::collision::{Collider, ColliderId, CollisionWorld};
+use super::layers::CollisionFilter;
+use super::shape::{sweep_shapes, Shape};
+use glam::Vec2;
+
+// Rays are swept points
+const RAY: Shape = Shape::Circle { radius: 0.0 };
+
+// Which colliders a query may hit
+#[derive(Debug, Clone)]
+pub struct QueryFilter {
+    // Tested against each collider's filter like a collider of its own, so a bullet's ray
+    // can use the bullet's filter
+    pub layers: CollisionFilter,
+    pub include_sensors: bool,
+    // E.g. the shooter's own collider
+    pub exclude: Vec<ColliderId>,
+    // Only colliders whose `user_data` passes; for filtering by entity kind
+    pub user_data: Option<fn(u64) -> bool>,
+}
+
+impl Default for QueryFilter {
+    fn default() -> Self {
+        Self { layers: CollisionFilter::ALL, include_sensors: false, exclude: Vec::new(), user_data: None }
+    }
+}
+
+impl QueryFilter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_layers(mut self, layers: CollisionFilter) -> Self {
+        self.layers = layers;
+        self
+    }
+
+    pub fn with_sensors(mut self) -> Self {
+        self.include_sensors = true;
+        self
+    }
+
+    pub fn excluding(mut self, id: ColliderId) -> Self {
+        self.exclude.push(id);
+        self
+    }
+
+    pub fn allows(&self, id: ColliderId, collider: &Collider) -> bool {
+        (self.include_sensors || !collider.sensor)
+            && self.layers.interacts(&collider.filter)
+            && !self.exclude.contains(&id)
+            && self.user_data.is_none_or(|accept| accept(collider.user_data))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RayHit {
+    pub collider: ColliderId,
+    // The collider's `user_data`, usually the entity it belongs to
+    pub user_data: u64,
+    // For shape casts, where the cast shape touches the collider
+    pub point: Vec2,
+    // Surface normal at `point`, facing back along the cast
+    pub normal: Vec2,
+    // Along the normalized direction; 0 when starting inside a collider
+    pub distance: f32,
+}
+
+impl CollisionWorld {
+    // First collider along the ray, within a finite `max_distance`. Walks the broad-phase grid cell
+    // by cell and stops at the first cell that can't hold anything closer, so long rays
+    // over big levels stay cheap.
+    pub fn raycast(&self, origin: Vec2, direction: Vec2, max_distance: f32, filter: &QueryFilter) -> Option<RayHit> {
+        let direction = direction.normalize_or_zero();
+        if direction == Vec2::ZERO || max_distance <= 0.0 {
+            return None;
+        }
+        let cell_size = self.cell_size();
+        let mut cell = (origin / cell_size).floor();
+        let step = Vec2::new(direction.x.signum(), direction.y.signum());
+        // Distance along the ray to the next cell boundary on each axis, and between boundaries
+        let next_boundary = |axis: usize| {
+            if direction[axis] == 0.0 {
+                return f32::INFINITY;
+            }
+            let edge = (cell[axis] + if direction[axis] > 0.0 { 1.0 } else { 0.0 }) * cell_size;
+            (edge - origin[axis]) / direction[axis]
+        };
+        let mut t_max = Vec2::new(next_boundary(0), next_boundary(1));
+        let t_delta = Vec2::new(cell_size / direction.x.abs(), cell_size / direction.y.abs());
+
+        let mut best: Option<RayHit> = None;
+        let mut tested = Vec::new();
+        let mut cell_entry = 0.0;
+        while cell_entry <= max_distance && best.is_none_or(|hit| hit.distance > cell_entry) {
+            for &id in self.cell((cell.x as i32, cell.y as i32)) {
+                if tested.contains(&id) {
+                    continue;
+                }
+                tested.push(id);
+                let Some(collider) = self.get(id) else { continue };
+                if !filter.allows(id, collider) {
+                    continue;
+                }
+                let motion = direction * max_distance;
+                let Some((fraction, normal)) = sweep_shapes(&RAY, &collider.shape, origin - collider.position, motion) else {
+                    continue;
+                };
+                let distance = fraction * max_distance;
+                if best.is_none_or(|hit| distance < hit.distance) {
+                    let point = origin + direction * distance;
+                    best = Some(RayHit { collider: id, user_data: collider.user_data, point, normal, distance });
+                }
+            }
+            if t_max.x < t_max.y {
+                cell_entry = t_max.x;
+                t_max.x += t_delta.x;
+                cell.x += step.x;
+            } else {
+                cell_entry = t_max.y;
+                t_max.y += t_delta.y;
+                cell.y += step.y;
+            }
+        }
+        best
+    }
+
+    // Every collider along the ray, nearest first
+    pub fn raycast_all(&self, origin: Vec2, direction: Vec2, max_distance: f32, filter: &QueryFilter) -> Vec<RayHit> {
+        let mut filter = filter.clone();
+        let mut hits = Vec::new();
+        while let Some(hit) = self.raycast(origin, direction, max_distance, &filter) {
+            filter.exclude.push(hit.collider);
+            hits.push(hit);
+        }
+        hits
+    }
+
+    // Moves `shape` from `origin` along `direction` and reports the first collider it
+    // touches, e.g. for thick bullets, melee sweeps and ground probes
+    pub fn shape_cast(
+        &self,
+        shape: &Shape,
+        origin: Vec2,
+        direction: Vec2,
+        max_distance: f32,
+        filter: &QueryFilter,
+    ) -> Option<RayHit> {
+        let direction = direction.normalize_or_zero();
+        if direction == Vec2::ZERO || max_distance <= 0.0 {
+            return None;
+        }
+        let hit = self.sweep(shape, origin, direction * max_distance, |id, collider| filter.allows(id, collider))?;
+        let user_data = self.get(hit.collider)?.user_data;
+        Some(RayHit { collider: hit.collider, user_data, point: hit.point, normal: hit.normal, distance: hit.fraction * max_distance })
+    }
+
+    // Colliders overlapping `shape` at `position`, e.g. for explosions, or trigger checks
+    // with `include_sensors`
+    pub fn overlap(&self, shape: &Shape, position: Vec2, filter: &QueryFilter) -> Vec<ColliderId> {
+        self.contacts(shape, position, |id, collider| filter.allows(id, collider)).into_iter().map(|c| c.collider).collect()
+    }
+}
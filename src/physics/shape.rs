@@ -0,0 +1,378 @@
+// src/physics/shape.rs
+use crate::math::{Rect, Vec2};
+
+// Most corners a `ConvexPolygon` can have
+pub const MAX_POLYGON_POINTS: usize = 8;
+
+// Collision shapes. Boxes, circles and capsules are axis-aligned rounded rectangles (inner
+// half extents plus a radius), which keeps tests between them down to one closed-form
+// case: the Minkowski sum of two rounded rectangles is another rounded rectangle. Pairs
+// with a polygon take the general case, a rounded convex hull.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Shape {
+    Box { half_extents: Vec2 },
+    Circle { radius: f32 },
+    // Upright: the straight part runs from -half_height to +half_height on Y
+    Capsule { half_height: f32, radius: f32 },
+    // E.g. slope tiles and terrain surfaces
+    Polygon(ConvexPolygon),
+}
+
+// Convex outline around the collider's position, counter-clockwise
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ConvexPolygon {
+    points: [Vec2; MAX_POLYGON_POINTS],
+    len: u8,
+}
+
+impl ConvexPolygon {
+    // `outline` in either winding; collinear and repeated points are dropped
+    pub fn new(outline: &[Vec2]) -> Result<Self, String> {
+        let mut hull = outline.to_vec();
+        convex_hull(&mut hull);
+        if hull.len() < 3 {
+            return Err("Polygon needs three corners that aren't on one line".to_string());
+        }
+        if hull.len() > MAX_POLYGON_POINTS {
+            return Err(format!("Polygon has {} corners, at most {} are supported", hull.len(), MAX_POLYGON_POINTS));
+        }
+        // A concave outline covers less than its hull
+        if area(&hull) > area(outline).abs() * 1.001 + 1e-6 {
+            return Err("Polygon is concave".to_string());
+        }
+        let mut points = [Vec2::ZERO; MAX_POLYGON_POINTS];
+        points[..hull.len()].copy_from_slice(&hull);
+        Ok(Self { points, len: hull.len() as u8 })
+    }
+
+    pub fn points(&self) -> &[Vec2] {
+        &self.points[..self.len as usize]
+    }
+}
+
+impl Shape {
+    pub fn rect(width: f32, height: f32) -> Self {
+        Shape::Box { half_extents: Vec2::new(width, height) * 0.5 }
+    }
+
+    // Total height includes both caps
+    pub fn capsule(height: f32, radius: f32) -> Self {
+        Shape::Capsule { half_height: (height * 0.5 - radius).max(0.0), radius }
+    }
+
+    // Right triangle filling a `width` x `height` cell, centred on it, with the slope
+    // rising towards +X, or towards -X for a negative `width`
+    pub fn slope(width: f32, height: f32) -> Self {
+        let half = Vec2::new(width.abs(), height) * 0.5;
+        let low = Vec2::new(-half.x, -half.y) * Vec2::new(width.signum(), 1.0);
+        let high = Vec2::new(half.x, half.y) * Vec2::new(width.signum(), 1.0);
+        let corner = Vec2::new(high.x, low.y);
+        Shape::Polygon(ConvexPolygon::new(&[low, corner, high]).expect("slope corners form a triangle"))
+    }
+
+    pub fn polygon(outline: &[Vec2]) -> Result<Self, String> {
+        Ok(Shape::Polygon(ConvexPolygon::new(outline)?))
+    }
+
+    // (inner half extents, corner radius); a polygon gives its bounds about the position
+    pub fn rounded_rect(&self) -> (Vec2, f32) {
+        match *self {
+            Shape::Box { half_extents } => (half_extents.abs(), 0.0),
+            Shape::Circle { radius } => (Vec2::ZERO, radius.abs()),
+            Shape::Capsule { half_height, radius } => (Vec2::new(0.0, half_height.abs()), radius.abs()),
+            Shape::Polygon(polygon) => (polygon.points().iter().fold(Vec2::ZERO, |half, p| half.max(p.abs())), 0.0),
+        }
+    }
+
+    pub fn half_size(&self) -> Vec2 {
+        let (half, radius) = self.rounded_rect();
+        half + Vec2::splat(radius)
+    }
+
+    pub fn aabb(&self, position: Vec2) -> Aabb {
+        if let Shape::Polygon(polygon) = self {
+            let min = polygon.points().iter().fold(Vec2::splat(f32::MAX), |m, p| m.min(*p));
+            let max = polygon.points().iter().fold(Vec2::splat(f32::MIN), |m, p| m.max(*p));
+            return Aabb { min: position + min, max: position + max };
+        }
+        let half = self.half_size();
+        Aabb { min: position - half, max: position + half }
+    }
+
+    // Farthest point of the shape, placed at the origin, in `direction`
+    pub fn support(&self, direction: Vec2) -> Vec2 {
+        if let Shape::Polygon(polygon) = self {
+            return polygon.points().iter().copied().max_by(|a, b| a.dot(direction).total_cmp(&b.dot(direction))).unwrap_or(Vec2::ZERO);
+        }
+        let (half, radius) = self.rounded_rect();
+        let sign = Vec2::select(direction.cmpeq(Vec2::ZERO), Vec2::ZERO, direction.signum());
+        half * sign + direction.normalize_or_zero() * radius
+    }
+
+    // The points whose rounded hull is the shape, and the rounding radius
+    fn core(&self) -> (Vec<Vec2>, f32) {
+        if let Shape::Polygon(polygon) = self {
+            return (polygon.points().to_vec(), 0.0);
+        }
+        let (half, radius) = self.rounded_rect();
+        (vec![half, Vec2::new(-half.x, half.y), -half, Vec2::new(half.x, -half.y)], radius)
+    }
+}
+
+// Positions of `moving` relative to `fixed` where the two overlap: a rounded convex hull,
+// as (counter-clockwise hull, radius), or the closed-form rounded rectangle when neither
+// is a polygon
+enum Difference {
+    RoundedRect(Vec2, f32),
+    RoundedHull(Vec<Vec2>, f32),
+}
+
+fn difference(moving: &Shape, fixed: &Shape) -> Difference {
+    if !matches!(moving, Shape::Polygon(_)) && !matches!(fixed, Shape::Polygon(_)) {
+        let (half_a, radius_a) = moving.rounded_rect();
+        let (half_b, radius_b) = fixed.rounded_rect();
+        return Difference::RoundedRect(half_a + half_b, radius_a + radius_b);
+    }
+    let (a, radius_a) = moving.core();
+    let (b, radius_b) = fixed.core();
+    let mut hull: Vec<Vec2> = b.iter().flat_map(|&b| a.iter().map(move |&a| b - a)).collect();
+    convex_hull(&mut hull);
+    Difference::RoundedHull(hull, radius_a + radius_b)
+}
+
+// First time in 0..=1 `moving`, `offset` from `fixed` and moving by `motion`, touches
+// `fixed`, with `fixed`'s surface normal there. Starting inside, like `ray_rounded_rect`.
+pub(crate) fn sweep_shapes(moving: &Shape, fixed: &Shape, offset: Vec2, motion: Vec2) -> Option<(f32, Vec2)> {
+    match difference(moving, fixed) {
+        Difference::RoundedRect(half, radius) => ray_rounded_rect(offset, motion, half, radius),
+        Difference::RoundedHull(hull, radius) => ray_rounded_hull(offset, motion, &hull, radius),
+    }
+}
+
+// How far `moving`, `offset` from `fixed`, overlaps it, and the direction out
+pub(crate) fn shape_penetration(moving: &Shape, fixed: &Shape, offset: Vec2) -> Option<(Vec2, f32)> {
+    match difference(moving, fixed) {
+        Difference::RoundedRect(half, radius) => rounded_rect_penetration(offset, half, radius),
+        Difference::RoundedHull(hull, radius) => rounded_hull_penetration(offset, &hull, radius),
+    }
+}
+
+// Replaces `points` with their convex hull, counter-clockwise without collinear points.
+// One or two distinct points stay as they are.
+fn convex_hull(points: &mut Vec<Vec2>) {
+    points.sort_by(|a, b| a.x.total_cmp(&b.x).then(a.y.total_cmp(&b.y)));
+    points.dedup_by(|a, b| a.distance_squared(*b) < 1e-12);
+    if points.len() < 3 {
+        return;
+    }
+    let turns_left = |hull: &[Vec2], p: Vec2| {
+        let (a, b) = (hull[hull.len() - 2], hull[hull.len() - 1]);
+        (b - a).perp_dot(p - b) > 1e-9
+    };
+    let mut hull: Vec<Vec2> = Vec::with_capacity(points.len() + 1);
+    for pass in [points.as_slice(), &points.iter().rev().copied().collect::<Vec<_>>()] {
+        let start = hull.len();
+        for &p in pass {
+            while hull.len() >= start + 2 && !turns_left(&hull, p) {
+                hull.pop();
+            }
+            hull.push(p);
+        }
+        // Each chain ends where the other starts
+        hull.pop();
+    }
+    *points = hull;
+}
+
+// Signed, positive counter-clockwise
+fn area(outline: &[Vec2]) -> f32 {
+    let count = outline.len();
+    (0..count).map(|i| outline[i].perp_dot(outline[(i + 1) % count])).sum::<f32>() * 0.5
+}
+
+// The hull's edges with their outward normals; a two-point hull has both sides
+fn hull_edges(hull: &[Vec2]) -> impl Iterator<Item = (Vec2, Vec2, Vec2)> + '_ {
+    let count = if hull.len() >= 2 { hull.len() } else { 0 };
+    (0..count).filter_map(move |i| {
+        let (a, b) = (hull[i], hull[(i + 1) % count]);
+        let normal = Vec2::new(b.y - a.y, a.x - b.x).normalize_or_zero();
+        (normal != Vec2::ZERO).then_some((a, b, normal))
+    })
+}
+
+fn closest_on_segment(point: Vec2, a: Vec2, b: Vec2) -> Vec2 {
+    let edge = b - a;
+    let t = ((point - a).dot(edge) / edge.length_squared().max(1e-12)).clamp(0.0, 1.0);
+    a + edge * t
+}
+
+// How far `point` is inside a convex hull grown by `radius`, and the direction out
+fn rounded_hull_penetration(point: Vec2, hull: &[Vec2], radius: f32) -> Option<(Vec2, f32)> {
+    let &first = hull.first()?;
+    // Edge the point is least inside of
+    let nearest_edge = hull_edges(hull).map(|(a, _, normal)| ((point - a).dot(normal), normal)).max_by(|a, b| a.0.total_cmp(&b.0));
+    let inside = hull.len() >= 3 && nearest_edge.is_some_and(|(distance, _)| distance <= 0.0);
+    if inside {
+        let (distance, normal) = nearest_edge?;
+        return Some((normal, radius - distance));
+    }
+    let closest = hull_edges(hull)
+        .map(|(a, b, _)| closest_on_segment(point, a, b))
+        .min_by(|a, b| a.distance_squared(point).total_cmp(&b.distance_squared(point)))
+        .unwrap_or(first);
+    let offset = point - closest;
+    let distance = offset.length();
+    if distance >= radius {
+        return None;
+    }
+    let normal = if distance > 0.0 { offset / distance } else { nearest_edge.map_or(Vec2::Y, |(_, normal)| normal) };
+    Some((normal, radius - distance))
+}
+
+// `ray_rounded_rect` for a convex hull grown by `radius`: the ray enters it through an
+// edge pushed out by `radius` or through a rounded corner
+fn ray_rounded_hull(origin: Vec2, motion: Vec2, hull: &[Vec2], radius: f32) -> Option<(f32, Vec2)> {
+    if let Some((normal, _)) = rounded_hull_penetration(origin, hull, radius) {
+        return (motion.dot(normal) < 0.0).then_some((0.0, normal));
+    }
+    let mut best: Option<(f32, Vec2)> = None;
+    let mut consider = |hit: Option<(f32, Vec2)>| {
+        if let Some(hit) = hit {
+            if best.is_none_or(|b| hit.0 < b.0) {
+                best = Some(hit);
+            }
+        }
+    };
+    for (a, b, normal) in hull_edges(hull) {
+        let approach = motion.dot(normal);
+        if approach >= 0.0 {
+            continue;
+        }
+        let t = (radius - (origin - a).dot(normal)) / approach;
+        let along = (origin + motion * t - a).dot(b - a) / (b - a).length_squared();
+        consider(((0.0..=1.0).contains(&t) && (0.0..=1.0).contains(&along)).then_some((t, normal)));
+    }
+    if radius > 0.0 {
+        for &corner in hull {
+            consider(ray_circle(origin, motion, corner, radius));
+        }
+    }
+    best
+}
+
+// Collision bounds are plain engine rectangles
+pub type Aabb = Rect;
+
+// Merges the set cells of a row-major `width` x `height` grid into as few rectangles as
+// possible, greedily: runs along each row, grown upwards while the next row matches.
+// Clears `cells` as it goes. Returns (x, y, width, height) in cells.
+pub(crate) fn merge_cells(width: usize, height: usize, cells: &mut [bool]) -> Vec<(usize, usize, usize, usize)> {
+    let mut rects = Vec::new();
+    for y in 0..height {
+        let mut x = 0;
+        while x < width {
+            if !cells[y * width + x] {
+                x += 1;
+                continue;
+            }
+            let start = x;
+            while x < width && cells[y * width + x] {
+                x += 1;
+            }
+            let mut rows = 1;
+            while y + rows < height && (start..x).all(|i| cells[(y + rows) * width + i]) {
+                rows += 1;
+            }
+            for row in y..y + rows {
+                cells[row * width + start..row * width + x].fill(false);
+            }
+            rects.push((start, y, x - start, rows));
+        }
+    }
+    rects
+}
+
+// How far `point` is inside a rounded rectangle at the origin, and the direction out
+pub(crate) fn rounded_rect_penetration(point: Vec2, half: Vec2, radius: f32) -> Option<(Vec2, f32)> {
+    let closest = point.clamp(-half, half);
+    let offset = point - closest;
+    let distance = offset.length();
+    if distance > 0.0 {
+        return (distance < radius).then(|| (offset / distance, radius - distance));
+    }
+    // Inside the inner box: leave through the nearest edge
+    let to_edge = half - point.abs();
+    if to_edge.x < to_edge.y {
+        Some((Vec2::new(point.x.signum(), 0.0), to_edge.x + radius))
+    } else {
+        Some((Vec2::new(0.0, point.y.signum()), to_edge.y + radius))
+    }
+}
+
+// First time in 0..=1 the ray `origin + motion * t` enters an origin-centred box
+fn ray_box(origin: Vec2, motion: Vec2, half: Vec2) -> Option<(f32, Vec2)> {
+    let mut t_enter = f32::NEG_INFINITY;
+    let mut t_exit = f32::INFINITY;
+    let mut normal = Vec2::ZERO;
+    for axis in 0..2 {
+        let (o, m, h) = (origin[axis], motion[axis], half[axis]);
+        if m.abs() < 1e-12 {
+            if o.abs() > h {
+                return None;
+            }
+            continue;
+        }
+        let (t0, t1) = ((-h - o) / m, (h - o) / m);
+        let (near, far) = if t0 < t1 { (t0, t1) } else { (t1, t0) };
+        if near > t_enter {
+            t_enter = near;
+            normal = Vec2::ZERO;
+            normal[axis] = -m.signum();
+        }
+        t_exit = t_exit.min(far);
+    }
+    (t_enter <= t_exit && (0.0..=1.0).contains(&t_enter)).then_some((t_enter, normal))
+}
+
+fn ray_circle(origin: Vec2, motion: Vec2, center: Vec2, radius: f32) -> Option<(f32, Vec2)> {
+    let to_origin = origin - center;
+    let a = motion.length_squared();
+    if a < 1e-12 {
+        return None;
+    }
+    let b = to_origin.dot(motion);
+    let c = to_origin.length_squared() - radius * radius;
+    let discriminant = b * b - a * c;
+    if discriminant < 0.0 {
+        return None;
+    }
+    let t = (-b - discriminant.sqrt()) / a;
+    (0.0..=1.0).contains(&t).then(|| (t, (origin + motion * t - center).normalize_or_zero()))
+}
+
+// First time in 0..=1 the ray `origin + motion * t` enters a rounded rectangle at the
+// origin, with the surface normal there. A ray starting inside hits at 0 if it moves
+// deeper, and misses if it is on its way out.
+pub(crate) fn ray_rounded_rect(origin: Vec2, motion: Vec2, half: Vec2, radius: f32) -> Option<(f32, Vec2)> {
+    if let Some((normal, _)) = rounded_rect_penetration(origin, half, radius) {
+        return (motion.dot(normal) < 0.0).then_some((0.0, normal));
+    }
+    // The rounded rectangle is the union of two boxes and four corner circles; the ray
+    // enters it where it first enters any of them
+    let mut best: Option<(f32, Vec2)> = None;
+    let mut consider = |hit: Option<(f32, Vec2)>| {
+        if let Some(hit) = hit {
+            if best.is_none_or(|b| hit.0 < b.0) {
+                best = Some(hit);
+            }
+        }
+    };
+    consider(ray_box(origin, motion, half + Vec2::new(radius, 0.0)));
+    consider(ray_box(origin, motion, half + Vec2::new(0.0, radius)));
+    if radius > 0.0 {
+        for corner in [half, Vec2::new(-half.x, half.y), -half, Vec2::new(half.x, -half.y)] {
+            consider(ray_circle(origin, motion, corner, radius));
+        }
+    }
+    best
+}
@@ -0,0 +1,253 @@
+// src/physics3d/collision.rs
+use super::convert::{from_rotation, from_vector, to_isometry, to_point};
+use super::dynamics::{BodyId, PhysicsWorld};
+use crate::mesh::Mesh;
+use crate::physics::layers::CollisionFilter;
+use glam::{Quat, Vec3};
+use rapier3d::prelude::{
+    ActiveHooks, ColliderBuilder, ColliderHandle, Group, InteractionGroups, PairFilterContext, PhysicsHooks, Point,
+    SharedShape, SolverFlags,
+};
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ColliderId(pub(crate) ColliderHandle);
+
+// Collision shape; cheap to clone, since the geometry is shared
+#[derive(Debug, Clone)]
+pub struct Shape(pub(crate) SharedShape);
+
+impl Shape {
+    // `size` is the full width, height and depth
+    pub fn cuboid(size: Vec3) -> Self {
+        let half = size.abs() * 0.5;
+        Shape(SharedShape::cuboid(half.x, half.y, half.z))
+    }
+
+    pub fn sphere(radius: f32) -> Self {
+        Shape(SharedShape::ball(radius))
+    }
+
+    // Upright; total height includes both caps
+    pub fn capsule(height: f32, radius: f32) -> Self {
+        Shape(SharedShape::capsule_y((height * 0.5 - radius).max(0.0), radius))
+    }
+
+    pub fn convex_hull(points: &[Vec3]) -> Result<Self, String> {
+        let points: Vec<Point<f32>> = points.iter().map(|&point| to_point(point)).collect();
+        SharedShape::convex_hull(&points)
+            .map(Shape)
+            .ok_or_else(|| "Convex hull needs four points that aren't on one plane".to_string())
+    }
+
+    // `mesh`'s exact triangles, for fixed level geometry. Triangle meshes have no volume, so
+    // dynamic bodies get neither mass nor inertia from them; use `convex_decomposition` there.
+    pub fn trimesh(mesh: &Mesh) -> Result<Self, String> {
+        let (vertices, indices) = mesh_buffers(mesh)?;
+        SharedShape::trimesh(vertices, indices).map(Shape).map_err(|e| format!("Invalid collision mesh: {:?}", e))
+    }
+
+    // Convex pieces approximating `mesh`, for concave props on dynamic bodies. Slow on big
+    // meshes, so decompose while loading and clone the shape for each copy of the prop.
+    pub fn convex_decomposition(mesh: &Mesh) -> Result<Self, String> {
+        let (vertices, indices) = mesh_buffers(mesh)?;
+        let shape = SharedShape::convex_decomposition(&vertices, &indices);
+        if shape.as_compound().is_none_or(|compound| compound.shapes().is_empty()) {
+            return Err("Convex decomposition found no solid parts".to_string());
+        }
+        Ok(Shape(shape))
+    }
+}
+
+type MeshBuffers = (Vec<Point<f32>>, Vec<[u32; 3]>);
+
+// `mesh`'s positions and triangles, checked so rapier doesn't panic on them
+fn mesh_buffers(mesh: &Mesh) -> Result<MeshBuffers, String> {
+    if mesh.indices.is_empty() || !mesh.indices.len().is_multiple_of(3) {
+        return Err(format!("Collision mesh has {} indices, not whole triangles", mesh.indices.len()));
+    }
+    if let Some(&index) = mesh.indices.iter().find(|&&index| index as usize >= mesh.vertices.len()) {
+        return Err(format!("Collision mesh index {} is past its {} vertices", index, mesh.vertices.len()));
+    }
+    let vertices = mesh.vertices.iter().map(|vertex| to_point(Vec3::from(vertex.position))).collect();
+    let indices = mesh.indices.chunks_exact(3).map(|triangle| [triangle[0], triangle[1], triangle[2]]).collect();
+    Ok((vertices, indices))
+}
+
+#[derive(Debug, Clone)]
+pub struct Collider {
+    pub shape: Shape,
+    // Relative to the body it's attached to, or to the world without one
+    pub position: Vec3,
+    pub rotation: Quat,
+    // Caller's handle for whatever owns the collider, e.g. `Entity::index`
+    pub user_data: u64,
+    pub filter: CollisionFilter,
+    // Detects overlaps but never blocks movement, e.g. pickups and trigger zones
+    pub sensor: bool,
+    // The body's mass and inertia come from its colliders' volume times density
+    pub density: f32,
+    pub friction: f32,
+    pub restitution: f32,
+}
+
+impl Collider {
+    pub fn new(shape: Shape, position: Vec3) -> Self {
+        Self {
+            shape,
+            position,
+            rotation: Quat::IDENTITY,
+            user_data: 0,
+            filter: CollisionFilter::ALL,
+            sensor: false,
+            density: 1.0,
+            friction: 0.5,
+            restitution: 0.0,
+        }
+    }
+
+    pub fn with_rotation(mut self, rotation: Quat) -> Self {
+        self.rotation = rotation;
+        self
+    }
+
+    pub fn with_user_data(mut self, user_data: u64) -> Self {
+        self.user_data = user_data;
+        self
+    }
+
+    pub fn with_filter(mut self, filter: CollisionFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    pub fn sensor(mut self) -> Self {
+        self.sensor = true;
+        self
+    }
+
+    pub fn with_density(mut self, density: f32) -> Self {
+        self.density = density.max(0.0);
+        self
+    }
+
+    pub fn with_friction(mut self, friction: f32) -> Self {
+        self.friction = friction.max(0.0);
+        self
+    }
+
+    pub fn with_restitution(mut self, restitution: f32) -> Self {
+        self.restitution = restitution.max(0.0);
+        self
+    }
+}
+
+// Rapier only knows layers and masks, so colliders with a `CollisionFilter::group` let every
+// pair through rapier's own test and decide here instead
+pub(crate) struct FilterHooks<'a> {
+    pub(crate) filters: &'a HashMap<ColliderHandle, CollisionFilter>,
+}
+
+impl FilterHooks<'_> {
+    fn interacts(&self, context: &PairFilterContext) -> bool {
+        match (self.filters.get(&context.collider1), self.filters.get(&context.collider2)) {
+            (Some(a), Some(b)) => a.interacts(b),
+            _ => true,
+        }
+    }
+}
+
+impl PhysicsHooks for FilterHooks<'_> {
+    fn filter_contact_pair(&self, context: &PairFilterContext) -> Option<SolverFlags> {
+        self.interacts(context).then_some(SolverFlags::COMPUTE_IMPULSES)
+    }
+
+    fn filter_intersection_pair(&self, context: &PairFilterContext) -> bool {
+        self.interacts(context)
+    }
+}
+
+impl PhysicsWorld {
+    // Attaches `collider` to `body`, or leaves it fixed in the world without one. Fails if
+    // `body` doesn't exist.
+    pub fn add_collider(&mut self, collider: Collider, body: Option<BodyId>) -> Result<ColliderId, String> {
+        if body.is_some_and(|body| self.bodies.get(body.0).is_none()) {
+            return Err("Collider refers to a body that doesn't exist".to_string());
+        }
+        let filter = collider.filter;
+        let (groups, hooks) = if filter.group == 0 {
+            let groups =
+                InteractionGroups::new(Group::from_bits_retain(filter.layers), Group::from_bits_retain(filter.mask));
+            (groups, ActiveHooks::empty())
+        } else {
+            (InteractionGroups::all(), ActiveHooks::FILTER_CONTACT_PAIRS | ActiveHooks::FILTER_INTERSECTION_PAIR)
+        };
+        let built = ColliderBuilder::new(collider.shape.0)
+            .position(to_isometry(collider.position, collider.rotation))
+            .user_data(collider.user_data as u128)
+            .collision_groups(groups)
+            .active_hooks(hooks)
+            .sensor(collider.sensor)
+            .density(collider.density)
+            .friction(collider.friction)
+            .restitution(collider.restitution)
+            .build();
+        let handle = match body {
+            Some(body) => self.colliders.insert_with_parent(built, body.0, &mut self.bodies),
+            None => self.colliders.insert(built),
+        };
+        self.filters.insert(handle, filter);
+        Ok(ColliderId(handle))
+    }
+
+    pub fn remove_collider(&mut self, id: ColliderId) -> Option<Collider> {
+        let collider = self.collider(id)?;
+        self.colliders.remove(id.0, &mut self.islands, &mut self.bodies, true);
+        self.filters.remove(&id.0);
+        Some(collider)
+    }
+
+    pub fn collider(&self, id: ColliderId) -> Option<Collider> {
+        let collider = self.colliders.get(id.0)?;
+        // Attached colliders report their pose on the body
+        let pose = collider.position_wrt_parent().unwrap_or(collider.position());
+        Some(Collider {
+            shape: Shape(collider.shared_shape().clone()),
+            position: from_vector(&pose.translation.vector),
+            rotation: from_rotation(&pose.rotation),
+            user_data: collider.user_data as u64,
+            filter: self.filters.get(&id.0).copied().unwrap_or_default(),
+            sensor: collider.is_sensor(),
+            density: collider.density(),
+            friction: collider.friction(),
+            restitution: collider.restitution(),
+        })
+    }
+
+    pub fn colliders(&self) -> impl Iterator<Item = (ColliderId, Collider)> + '_ {
+        self.colliders.iter().filter_map(|(handle, _)| Some((ColliderId(handle), self.collider(ColliderId(handle))?)))
+    }
+
+    // The body `id` is attached to, if any
+    pub fn collider_body(&self, id: ColliderId) -> Option<BodyId> {
+        self.colliders.get(id.0)?.parent().map(BodyId)
+    }
+
+    // Moves the collider on its body, or in the world without one
+    pub fn set_collider_position(&mut self, id: ColliderId, position: Vec3, rotation: Quat) -> bool {
+        let Some(collider) = self.colliders.get_mut(id.0) else { return false };
+        let pose = to_isometry(position, rotation);
+        if collider.parent().is_some() {
+            collider.set_position_wrt_parent(pose);
+        } else {
+            collider.set_position(pose);
+        }
+        true
+    }
+
+    // Where the collider is in the world, as of the last `step`
+    pub fn collider_pose(&self, id: ColliderId) -> Option<(Vec3, Quat)> {
+        let pose = self.colliders.get(id.0)?.position();
+        Some((from_vector(&pose.translation.vector), from_rotation(&pose.rotation)))
+    }
+}
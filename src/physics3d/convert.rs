@@ -0,0 +1,33 @@
+// src/physics3d/convert.rs
+// glam to rapier's nalgebra types and back
+use glam::{Quat, Vec3};
+use rapier3d::na::{Quaternion, UnitQuaternion};
+use rapier3d::prelude::{Isometry, Point, Vector};
+
+pub(crate) fn to_vector(v: Vec3) -> Vector<f32> {
+    Vector::new(v.x, v.y, v.z)
+}
+
+pub(crate) fn to_point(v: Vec3) -> Point<f32> {
+    Point::new(v.x, v.y, v.z)
+}
+
+pub(crate) fn to_rotation(q: Quat) -> UnitQuaternion<f32> {
+    UnitQuaternion::new_normalize(Quaternion::new(q.w, q.x, q.y, q.z))
+}
+
+pub(crate) fn to_isometry(position: Vec3, rotation: Quat) -> Isometry<f32> {
+    Isometry::from_parts(to_vector(position).into(), to_rotation(rotation))
+}
+
+pub(crate) fn from_vector(v: &Vector<f32>) -> Vec3 {
+    Vec3::new(v.x, v.y, v.z)
+}
+
+pub(crate) fn from_point(p: &Point<f32>) -> Vec3 {
+    Vec3::new(p.x, p.y, p.z)
+}
+
+pub(crate) fn from_rotation(q: &UnitQuaternion<f32>) -> Quat {
+    Quat::from_xyzw(q.i, q.j, q.k, q.w)
+}
@@ -0,0 +1,107 @@
+// src/physics3d/debug.rs
+use super::collision::{ColliderId, Shape};
+use super::convert::{from_point, from_rotation, from_vector};
+use super::dynamics::PhysicsWorld;
+use crate::debug_view::DebugDraw;
+use crate::mesh::{GpuMesh, Mesh, MeshBuilder};
+use glam::{Mat4, Vec2, Vec3};
+use rapier3d::prelude::{Isometry, Point, TypedShape};
+use std::collections::HashMap;
+
+const DEBUG_SECTORS: u32 = 16;
+const DEBUG_RINGS: u32 = 4;
+
+impl Shape {
+    // Triangles covering the shape, in its own space; flat-shaded apart from spheres and
+    // capsules. Shapes rapier builds itself and meshes can't show come out empty.
+    pub fn to_mesh(&self) -> Mesh {
+        shape_mesh(self.0.as_typed_shape())
+    }
+}
+
+fn shape_mesh(shape: TypedShape) -> Mesh {
+    match shape {
+        TypedShape::Cuboid(cuboid) => MeshBuilder::cube(from_vector(&cuboid.half_extents) * 2.0).build(),
+        TypedShape::Ball(ball) => MeshBuilder::sphere(ball.radius, DEBUG_SECTORS, DEBUG_RINGS * 2).build(),
+        TypedShape::Capsule(capsule) => {
+            MeshBuilder::capsule(capsule.radius, capsule.height(), DEBUG_SECTORS, DEBUG_RINGS).build()
+        }
+        TypedShape::ConvexPolyhedron(convex) => {
+            let (vertices, indices) = convex.to_trimesh();
+            faceted(&vertices, &indices)
+        }
+        TypedShape::TriMesh(trimesh) => faceted(trimesh.vertices(), trimesh.indices()),
+        TypedShape::Compound(compound) => {
+            let mut builder = MeshBuilder::new();
+            for (pose, part) in compound.shapes() {
+                builder.append(&transformed(shape_mesh(part.as_typed_shape()), pose));
+            }
+            builder.build()
+        }
+        _ => Mesh::default(),
+    }
+}
+
+// Separate vertices per triangle, so each face gets its own normal
+fn faceted(vertices: &[Point<f32>], indices: &[[u32; 3]]) -> Mesh {
+    let mut builder = MeshBuilder::new();
+    for triangle in indices {
+        let [a, b, c] = triangle.map(|index| from_point(&vertices[index as usize]));
+        let normal = (b - a).cross(c - a).normalize_or_zero();
+        let corners = [a, b, c].map(|corner| builder.vertex(corner, normal, Vec2::ZERO));
+        builder.triangle(corners[0], corners[1], corners[2]);
+    }
+    builder.build()
+}
+
+fn transformed(mut mesh: Mesh, pose: &Isometry<f32>) -> Mesh {
+    let transform = pose_matrix(pose);
+    for vertex in &mut mesh.vertices {
+        vertex.position = transform.transform_point3(Vec3::from(vertex.position)).into();
+        vertex.normal = transform.transform_vector3(Vec3::from(vertex.normal)).into();
+    }
+    mesh
+}
+
+fn pose_matrix(pose: &Isometry<f32>) -> Mat4 {
+    Mat4::from_rotation_translation(from_rotation(&pose.rotation), from_vector(&pose.translation.vector))
+}
+
+// Every collider's shape on the GPU, for drawing the physics world with `DebugViewPass`,
+// e.g. in wireframe. Shapes are uploaded once per collider.
+#[derive(Default)]
+pub struct PhysicsDebugMeshes {
+    meshes: HashMap<ColliderId, GpuMesh>,
+}
+
+impl PhysicsDebugMeshes {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Uploads colliders added since the last call and drops removed ones
+    pub fn prepare(&mut self, device: &wgpu::Device, world: &PhysicsWorld) {
+        self.meshes.retain(|id, _| world.colliders.get(id.0).is_some());
+        for (handle, collider) in world.colliders.iter() {
+            let id = ColliderId(handle);
+            if self.meshes.contains_key(&id) {
+                continue;
+            }
+            let mesh = shape_mesh(collider.shape().as_typed_shape());
+            if !mesh.indices.is_empty() {
+                self.meshes.insert(id, mesh.upload(device, "physics debug"));
+            }
+        }
+    }
+
+    // A draw per collider at its pose from the last `step`, for `DebugViewPass::render`
+    pub fn draws(&self, world: &PhysicsWorld) -> Vec<DebugDraw<'_>> {
+        self.meshes
+            .iter()
+            .filter_map(|(id, mesh)| {
+                let collider = world.colliders.get(id.0)?;
+                Some(DebugDraw { mesh, transform: pose_matrix(collider.position()) })
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,266 @@
+// src/physics3d/dynamics.rs
+use super::collision::FilterHooks;
+use super::convert::{from_rotation, from_vector, to_isometry, to_point, to_vector};
+use crate::physics::layers::CollisionFilter;
+use glam::{Quat, Vec3};
+use rapier3d::prelude::{
+    CCDSolver, ColliderHandle, ColliderSet, DefaultBroadPhase, ImpulseJointSet, IntegrationParameters, IslandManager,
+    MultibodyJointSet, NarrowPhase, PhysicsPipeline, QueryPipeline, RigidBodyBuilder, RigidBodyHandle, RigidBodySet,
+    RigidBodyType,
+};
+use std::collections::HashMap;
+use std::num::NonZeroUsize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BodyId(pub(crate) RigidBodyHandle);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BodyKind {
+    Dynamic,
+    // Never moves on its own, e.g. level geometry and joint anchors
+    Fixed,
+    // Moved by setting its position; pushes dynamic bodies without being pushed back,
+    // e.g. lifts and doors
+    Kinematic,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RigidBody {
+    pub kind: BodyKind,
+    pub position: Vec3,
+    pub rotation: Quat,
+    pub velocity: Vec3,
+    // Radians per second about each axis
+    pub angular_velocity: Vec3,
+    pub gravity_scale: f32,
+    pub linear_damping: f32,
+    pub angular_damping: f32,
+    // Continuous collision detection, so fast bodies don't tunnel through thin colliders
+    pub ccd: bool,
+    // Caller's handle for whatever owns the body, e.g. `Entity::index`
+    pub user_data: u64,
+}
+
+impl RigidBody {
+    // Mass and inertia come from the colliders added to it
+    pub fn dynamic(position: Vec3) -> Self {
+        Self::new(BodyKind::Dynamic, position)
+    }
+
+    pub fn fixed(position: Vec3) -> Self {
+        Self::new(BodyKind::Fixed, position)
+    }
+
+    pub fn kinematic(position: Vec3) -> Self {
+        Self::new(BodyKind::Kinematic, position)
+    }
+
+    fn new(kind: BodyKind, position: Vec3) -> Self {
+        Self {
+            kind,
+            position,
+            rotation: Quat::IDENTITY,
+            velocity: Vec3::ZERO,
+            angular_velocity: Vec3::ZERO,
+            gravity_scale: 1.0,
+            linear_damping: 0.0,
+            angular_damping: 0.0,
+            ccd: false,
+            user_data: 0,
+        }
+    }
+
+    pub fn with_rotation(mut self, rotation: Quat) -> Self {
+        self.rotation = rotation;
+        self
+    }
+
+    pub fn with_user_data(mut self, user_data: u64) -> Self {
+        self.user_data = user_data;
+        self
+    }
+
+    // Body-local point to world space
+    pub fn world_point(&self, local: Vec3) -> Vec3 {
+        self.position + self.rotation * local
+    }
+
+    pub fn local_point(&self, world: Vec3) -> Vec3 {
+        self.rotation.inverse() * (world - self.position)
+    }
+
+    pub fn is_dynamic(&self) -> bool {
+        self.kind == BodyKind::Dynamic
+    }
+}
+
+fn body_type(kind: BodyKind) -> RigidBodyType {
+    match kind {
+        BodyKind::Dynamic => RigidBodyType::Dynamic,
+        BodyKind::Fixed => RigidBodyType::Fixed,
+        BodyKind::Kinematic => RigidBodyType::KinematicPositionBased,
+    }
+}
+
+// Rigid bodies and their colliders, simulated by rapier. Bodies collide with each other and
+// with colliders that have no body, which stay where they're put. Collider filters work as
+// in the 2D `physics` module.
+pub struct PhysicsWorld {
+    pub gravity: Vec3,
+    // More solver iterations mean stiffer contacts and stacks for proportionally more work
+    pub solver_iterations: u32,
+    pub(crate) bodies: RigidBodySet,
+    pub(crate) colliders: ColliderSet,
+    // Every collider's filter, for `FilterHooks` and queries
+    pub(crate) filters: HashMap<ColliderHandle, CollisionFilter>,
+    pub(crate) islands: IslandManager,
+    pub(crate) queries: QueryPipeline,
+    pipeline: PhysicsPipeline,
+    integration: IntegrationParameters,
+    broad_phase: DefaultBroadPhase,
+    narrow_phase: NarrowPhase,
+    impulse_joints: ImpulseJointSet,
+    multibody_joints: MultibodyJointSet,
+    ccd: CCDSolver,
+}
+
+impl Default for PhysicsWorld {
+    fn default() -> Self {
+        Self {
+            gravity: Vec3::new(0.0, -9.81, 0.0),
+            solver_iterations: 4,
+            bodies: RigidBodySet::new(),
+            colliders: ColliderSet::new(),
+            filters: HashMap::new(),
+            islands: IslandManager::new(),
+            queries: QueryPipeline::new(),
+            pipeline: PhysicsPipeline::new(),
+            integration: IntegrationParameters::default(),
+            broad_phase: DefaultBroadPhase::new(),
+            narrow_phase: NarrowPhase::new(),
+            impulse_joints: ImpulseJointSet::new(),
+            multibody_joints: MultibodyJointSet::new(),
+            ccd: CCDSolver::new(),
+        }
+    }
+}
+
+impl PhysicsWorld {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_body(&mut self, body: RigidBody) -> BodyId {
+        let built = RigidBodyBuilder::new(body_type(body.kind))
+            .position(to_isometry(body.position, body.rotation))
+            .linvel(to_vector(body.velocity))
+            .angvel(to_vector(body.angular_velocity))
+            .gravity_scale(body.gravity_scale)
+            .linear_damping(body.linear_damping)
+            .angular_damping(body.angular_damping)
+            .ccd_enabled(body.ccd)
+            .user_data(body.user_data as u128)
+            .build();
+        BodyId(self.bodies.insert(built))
+    }
+
+    // Also removes every collider attached to the body
+    pub fn remove_body(&mut self, id: BodyId) -> Option<RigidBody> {
+        let body = self.body(id)?;
+        for collider in self.bodies.get(id.0)?.colliders() {
+            self.filters.remove(collider);
+        }
+        self.bodies.remove(
+            id.0,
+            &mut self.islands,
+            &mut self.colliders,
+            &mut self.impulse_joints,
+            &mut self.multibody_joints,
+            true,
+        );
+        Some(body)
+    }
+
+    pub fn body(&self, id: BodyId) -> Option<RigidBody> {
+        let body = self.bodies.get(id.0)?;
+        let kind = match body.body_type() {
+            RigidBodyType::Dynamic => BodyKind::Dynamic,
+            RigidBodyType::Fixed => BodyKind::Fixed,
+            RigidBodyType::KinematicPositionBased | RigidBodyType::KinematicVelocityBased => BodyKind::Kinematic,
+        };
+        Some(RigidBody {
+            kind,
+            position: from_vector(body.translation()),
+            rotation: from_rotation(body.rotation()),
+            velocity: from_vector(body.linvel()),
+            angular_velocity: from_vector(body.angvel()),
+            gravity_scale: body.gravity_scale(),
+            linear_damping: body.linear_damping(),
+            angular_damping: body.angular_damping(),
+            ccd: body.is_ccd_enabled(),
+            user_data: body.user_data as u64,
+        })
+    }
+
+    // Replaces the body's state, e.g. after editing what `body` returned. Kinematic bodies
+    // move to the new position over the next `step`, pushing what's in the way.
+    pub fn set_body(&mut self, id: BodyId, body: RigidBody) -> bool {
+        let Some(target) = self.bodies.get_mut(id.0) else { return false };
+        target.set_body_type(body_type(body.kind), true);
+        let pose = to_isometry(body.position, body.rotation);
+        if body.kind == BodyKind::Kinematic {
+            target.set_next_kinematic_position(pose);
+        } else {
+            target.set_position(pose, true);
+        }
+        target.set_linvel(to_vector(body.velocity), true);
+        target.set_angvel(to_vector(body.angular_velocity), true);
+        target.set_gravity_scale(body.gravity_scale, true);
+        target.set_linear_damping(body.linear_damping);
+        target.set_angular_damping(body.angular_damping);
+        target.enable_ccd(body.ccd);
+        target.user_data = body.user_data as u128;
+        true
+    }
+
+    pub fn bodies(&self) -> impl Iterator<Item = (BodyId, RigidBody)> + '_ {
+        self.bodies.iter().filter_map(|(handle, _)| Some((BodyId(handle), self.body(BodyId(handle))?)))
+    }
+
+    // Changes the body's velocity right away; does nothing to fixed and kinematic bodies
+    pub fn apply_impulse(&mut self, id: BodyId, impulse: Vec3, world_point: Vec3) {
+        if let Some(body) = self.bodies.get_mut(id.0) {
+            body.apply_impulse_at_point(to_vector(impulse), to_point(world_point), true);
+        }
+    }
+
+    pub fn step(&mut self, delta_time: f32) {
+        if delta_time <= 0.0 {
+            return;
+        }
+        self.integration.dt = delta_time;
+        self.integration.num_solver_iterations =
+            NonZeroUsize::new(self.solver_iterations as usize).unwrap_or(NonZeroUsize::MIN);
+        let hooks = FilterHooks { filters: &self.filters };
+        self.pipeline.step(
+            &to_vector(self.gravity),
+            &self.integration,
+            &mut self.islands,
+            &mut self.broad_phase,
+            &mut self.narrow_phase,
+            &mut self.bodies,
+            &mut self.colliders,
+            &mut self.impulse_joints,
+            &mut self.multibody_joints,
+            &mut self.ccd,
+            Some(&mut self.queries),
+            &hooks,
+            &(),
+        );
+    }
+
+    // Brings queries up to date with colliders added, removed or moved since the last `step`
+    pub fn update_queries(&mut self) {
+        self.queries.update(&self.colliders);
+    }
+}
@@ -0,0 +1,7 @@
+// src/physics3d/mod.rs
+// 3D counterpart of `physics`, backed by rapier; built with the `rapier3d` feature
+pub mod collision;
+mod convert;
+pub mod debug;
+pub mod dynamics;
+pub mod query;
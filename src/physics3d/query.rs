@@ -0,0 +1,171 @@
+// src/physics3d/query.rs
+use super::collision::{ColliderId, Shape};
+use super::convert::{from_point, from_vector, to_isometry, to_point, to_vector};
+use super::dynamics::PhysicsWorld;
+use crate::physics::layers::CollisionFilter;
+use glam::{Quat, Vec3};
+use rapier3d::parry::query::ShapeCastOptions;
+use rapier3d::prelude::{Collider, ColliderHandle, QueryFilter as RapierFilter, Ray};
+
+// Which colliders a query may hit
+#[derive(Debug, Clone)]
+pub struct QueryFilter {
+    // Tested against each collider's filter like a collider of its own, so a bullet's ray
+    // can use the bullet's filter
+    pub layers: CollisionFilter,
+    pub include_sensors: bool,
+    // E.g. the shooter's own collider
+    pub exclude: Vec<ColliderId>,
+    // Only colliders whose `user_data` passes; for filtering by entity kind
+    pub user_data: Option<fn(u64) -> bool>,
+}
+
+impl Default for QueryFilter {
+    fn default() -> Self {
+        Self { layers: CollisionFilter::ALL, include_sensors: false, exclude: Vec::new(), user_data: None }
+    }
+}
+
+impl QueryFilter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_layers(mut self, layers: CollisionFilter) -> Self {
+        self.layers = layers;
+        self
+    }
+
+    pub fn with_sensors(mut self) -> Self {
+        self.include_sensors = true;
+        self
+    }
+
+    pub fn excluding(mut self, id: ColliderId) -> Self {
+        self.exclude.push(id);
+        self
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RayHit {
+    pub collider: ColliderId,
+    // The collider's `user_data`, usually the entity it belongs to
+    pub user_data: u64,
+    // For shape casts, where the cast shape touches the collider
+    pub point: Vec3,
+    // Surface normal at `point`, facing back along the cast
+    pub normal: Vec3,
+    // Along the normalized direction; 0 when starting inside a collider
+    pub distance: f32,
+}
+
+// Queries see colliders as of the last `step` or `update_queries`
+impl PhysicsWorld {
+    fn allows(&self, filter: &QueryFilter, handle: ColliderHandle, collider: &Collider) -> bool {
+        (filter.include_sensors || !collider.is_sensor())
+            && self.filters.get(&handle).is_none_or(|layers| filter.layers.interacts(layers))
+            && !filter.exclude.contains(&ColliderId(handle))
+            && filter.user_data.is_none_or(|accept| accept(collider.user_data as u64))
+    }
+
+    fn hit(&self, handle: ColliderHandle, point: Vec3, normal: Vec3, distance: f32) -> RayHit {
+        let user_data = self.colliders.get(handle).map_or(0, |collider| collider.user_data as u64);
+        RayHit { collider: ColliderId(handle), user_data, point, normal, distance }
+    }
+
+    // First collider along the ray, within `max_distance`
+    pub fn raycast(&self, origin: Vec3, direction: Vec3, max_distance: f32, filter: &QueryFilter) -> Option<RayHit> {
+        let direction = direction.normalize_or_zero();
+        if direction == Vec3::ZERO || max_distance <= 0.0 {
+            return None;
+        }
+        let ray = Ray::new(to_point(origin), to_vector(direction));
+        let predicate = |handle: ColliderHandle, collider: &Collider| self.allows(filter, handle, collider);
+        let query = RapierFilter::new().predicate(&predicate);
+        let (handle, hit) =
+            self.queries.cast_ray_and_get_normal(&self.bodies, &self.colliders, &ray, max_distance, true, query)?;
+        let distance = hit.time_of_impact;
+        Some(self.hit(handle, origin + direction * distance, from_vector(&hit.normal), distance))
+    }
+
+    // Every collider along the ray, nearest first
+    pub fn raycast_all(&self, origin: Vec3, direction: Vec3, max_distance: f32, filter: &QueryFilter) -> Vec<RayHit> {
+        let direction = direction.normalize_or_zero();
+        if direction == Vec3::ZERO || max_distance <= 0.0 {
+            return Vec::new();
+        }
+        let ray = Ray::new(to_point(origin), to_vector(direction));
+        let predicate = |handle: ColliderHandle, collider: &Collider| self.allows(filter, handle, collider);
+        let query = RapierFilter::new().predicate(&predicate);
+        let mut hits = Vec::new();
+        self.queries.intersections_with_ray(
+            &self.bodies,
+            &self.colliders,
+            &ray,
+            max_distance,
+            true,
+            query,
+            |handle, hit| {
+                let distance = hit.time_of_impact;
+                hits.push(self.hit(handle, origin + direction * distance, from_vector(&hit.normal), distance));
+                true
+            },
+        );
+        hits.sort_by(|a, b| a.distance.total_cmp(&b.distance));
+        hits
+    }
+
+    // Moves `shape`, turned by `rotation`, from `origin` along `direction` and reports the
+    // first collider it touches, e.g. for thick bullets, melee sweeps and ground probes
+    pub fn shape_cast(
+        &self,
+        shape: &Shape,
+        origin: Vec3,
+        rotation: Quat,
+        direction: Vec3,
+        max_distance: f32,
+        filter: &QueryFilter,
+    ) -> Option<RayHit> {
+        let direction = direction.normalize_or_zero();
+        if direction == Vec3::ZERO || max_distance <= 0.0 {
+            return None;
+        }
+        let predicate = |handle: ColliderHandle, collider: &Collider| self.allows(filter, handle, collider);
+        let query = RapierFilter::new().predicate(&predicate);
+        let options = ShapeCastOptions {
+            compute_impact_geometry_on_penetration: true,
+            ..ShapeCastOptions::with_max_time_of_impact(max_distance)
+        };
+        let (handle, hit) = self.queries.cast_shape(
+            &self.bodies,
+            &self.colliders,
+            &to_isometry(origin, rotation),
+            &to_vector(direction),
+            shape.0.as_ref(),
+            options,
+            query,
+        )?;
+        Some(self.hit(handle, from_point(&hit.witness1), from_vector(&hit.normal1), hit.time_of_impact))
+    }
+
+    // Colliders overlapping `shape` at `position`, e.g. for explosions, or trigger checks
+    // with `include_sensors`
+    pub fn overlap(&self, shape: &Shape, position: Vec3, rotation: Quat, filter: &QueryFilter) -> Vec<ColliderId> {
+        let predicate = |handle: ColliderHandle, collider: &Collider| self.allows(filter, handle, collider);
+        let query = RapierFilter::new().predicate(&predicate);
+        let mut found = Vec::new();
+        self.queries.intersections_with_shape(
+            &self.bodies,
+            &self.colliders,
+            &to_isometry(position, rotation),
+            shape.0.as_ref(),
+            query,
+            |handle| {
+                found.push(ColliderId(handle));
+                true
+            },
+        );
+        found
+    }
+}
@@ -0,0 +1,224 @@
+// src/picking.rs
+use crate::bind_cache::BindGroupCache;
+use crate::ecs::world::Entity;
+use crate::gpu_buffer::{GpuStruct, UniformBuffer};
+use crate::mesh::{GpuMesh, MeshVertex};
+use crate::readback::{ReadbackData, ReadbackId, Readbacks};
+use crate::resource_registry::{ResourceRegistry, Tracked};
+use crate::upload::UploadBelt;
+use glam::Mat4;
+
+// (entity index + 1, generation) per pixel; zero where nothing was drawn
+pub const PICK_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rg32Uint;
+const PICK_DEPTH_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth32Float;
+
+// One mesh drawn into the ID buffer
+pub struct PickDraw<'a> {
+    pub entity: Entity,
+    pub mesh: &'a GpuMesh,
+    pub transform: Mat4,
+}
+
+// Mirrors `PickObject` in picking.wgsl
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct PickObject {
+    clip_from_local: [f32; 16],
+    id: [u32; 2],
+}
+
+impl GpuStruct for PickObject {}
+
+fn decode(texel: &[u8]) -> Option<Entity> {
+    let index = u32::from_ne_bytes(texel.get(0..4)?.try_into().ok()?);
+    let generation = u32::from_ne_bytes(texel.get(4..8)?.try_into().ok()?);
+    (index != 0).then(|| Entity::from_raw(index - 1, generation))
+}
+
+// Exact per-pixel picking, e.g. for an editor: `render` draws each entity's mesh with its
+// id into an offscreen integer target and copies back the texel under the cursor, so thin,
+// concave or transparent shapes pick exactly as drawn. The answer arrives a frame or two
+// later through `poll`; the target only needs to match the view's size and projection.
+pub struct PickingPass {
+    target: Tracked<wgpu::Texture>,
+    depth: Tracked<wgpu::Texture>,
+    objects: UniformBuffer<PickObject>,
+    layout: wgpu::BindGroupLayout,
+    pipeline: wgpu::RenderPipeline,
+    max_objects: u32,
+    pending: Option<ReadbackId>,
+}
+
+impl PickingPass {
+    // `max_objects` pickable draws per frame; `width` and `height` in physical pixels
+    pub fn new(device: &wgpu::Device, resources: &ResourceRegistry, width: u32, height: u32, max_objects: u32) -> Self {
+        // Dynamic offsets need more than one slot
+        let max_objects = max_objects.max(2);
+        let objects = UniformBuffer::new(device, resources, "pick objects", max_objects);
+        let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("picking"),
+            entries: &[objects.layout_entry(0, wgpu::ShaderStages::VERTEX_FRAGMENT)],
+        });
+        let shader = device.create_shader_module(wgpu::include_wgsl!("picking.wgsl"));
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("picking"),
+            bind_group_layouts: &[&layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("picking"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: Some("vs_main"),
+                buffers: &[MeshVertex::layout()],
+                compilation_options: Default::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: Some("fs_main"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: PICK_FORMAT,
+                    blend: None,
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: Default::default(),
+            }),
+            // No culling: back faces of open meshes are still part of the object
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: PICK_DEPTH_FORMAT,
+                depth_write_enabled: true,
+                depth_compare: wgpu::CompareFunction::Less,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        });
+        let (target, depth) = Self::create_targets(device, resources, width, height);
+        Self { target, depth, objects, layout, pipeline, max_objects, pending: None }
+    }
+
+    fn create_targets(
+        device: &wgpu::Device,
+        resources: &ResourceRegistry,
+        width: u32,
+        height: u32,
+    ) -> (Tracked<wgpu::Texture>, Tracked<wgpu::Texture>) {
+        let texture = |label, format, usage| {
+            resources.create_texture(device, &wgpu::TextureDescriptor {
+                label: Some(label),
+                size: wgpu::Extent3d { width: width.max(1), height: height.max(1), depth_or_array_layers: 1 },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format,
+                usage,
+                view_formats: &[],
+            }, "picking")
+        };
+        (
+            texture("pick ids", PICK_FORMAT, wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC),
+            texture("pick depth", PICK_DEPTH_FORMAT, wgpu::TextureUsages::RENDER_ATTACHMENT),
+        )
+    }
+
+    pub fn resize(&mut self, device: &wgpu::Device, resources: &ResourceRegistry, width: u32, height: u32) {
+        if (self.target.width(), self.target.height()) != (width.max(1), height.max(1)) {
+            (self.target, self.depth) = Self::create_targets(device, resources, width, height);
+        }
+    }
+
+    // The ID buffer itself, e.g. to outline the hovered object in a shader
+    pub fn target(&self) -> &wgpu::Texture {
+        &self.target
+    }
+
+    // Draws `draws` into the ID buffer and, with a cursor position in physical pixels,
+    // requests the id under it. Submit `encoder` before the renderer's frame so the
+    // readback is mapped with it. Draws past `max_objects` are skipped.
+    #[allow(clippy::too_many_arguments)]
+    pub fn render(
+        &mut self,
+        device: &wgpu::Device,
+        resources: &ResourceRegistry,
+        encoder: &mut wgpu::CommandEncoder,
+        belt: &mut UploadBelt,
+        bind_groups: &mut BindGroupCache,
+        readbacks: &mut Readbacks,
+        view_proj: Mat4,
+        draws: &[PickDraw<'_>],
+        cursor: Option<(u32, u32)>,
+    ) {
+        if draws.len() > self.max_objects as usize {
+            log::warn!("Picking {} of {} draws; raise max_objects", self.max_objects, draws.len());
+        }
+        self.objects.begin_frame();
+        let mut offsets = Vec::with_capacity(draws.len());
+        for draw in draws.iter().take(self.max_objects as usize) {
+            let object = PickObject {
+                clip_from_local: (view_proj * draw.transform).to_cols_array(),
+                id: [draw.entity.index() + 1, draw.entity.generation()],
+            };
+            if let Ok(offset) = self.objects.push(device, encoder, belt, &object) {
+                offsets.push(offset);
+            }
+        }
+
+        let view = self.target.create_view(&wgpu::TextureViewDescriptor::default());
+        let depth_view = self.depth.create_view(&wgpu::TextureViewDescriptor::default());
+        {
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("picking"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &view,
+                    resolve_target: None,
+                    ops: wgpu::Operations { load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT), store: wgpu::StoreOp::Store },
+                    depth_slice: None,
+                })],
+                depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                    view: &depth_view,
+                    depth_ops: Some(wgpu::Operations { load: wgpu::LoadOp::Clear(1.0), store: wgpu::StoreOp::Discard }),
+                    stencil_ops: None,
+                }),
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+            if !offsets.is_empty() {
+                let bind_group = self.objects.bind_group(device, bind_groups, &self.layout);
+                render_pass.set_pipeline(&self.pipeline);
+                for (draw, offset) in draws.iter().zip(&offsets) {
+                    render_pass.set_bind_group(0, &bind_group, &[*offset]);
+                    draw.mesh.draw(&mut render_pass);
+                }
+            }
+        }
+
+        let Some((x, y)) = cursor else { return };
+        if x >= self.target.width() || y >= self.target.height() {
+            return;
+        }
+        match readbacks.read_texture(device, resources, encoder, &self.target, (x, y), (1, 1)) {
+            // A newer request replaces one still in flight; its result is just never taken
+            Ok(id) => self.pending = Some(id),
+            Err(e) => log::warn!("Pick readback failed: {}", e),
+        }
+    }
+
+    // `Some` once the last requested pick has arrived: the entity under the cursor, or
+    // `None` over empty space. Check `World::is_alive`, as it may have been despawned since.
+    pub fn poll(&mut self, readbacks: &mut Readbacks) -> Option<Option<Entity>> {
+        let result = readbacks.take(self.pending?)?;
+        self.pending = None;
+        match result {
+            Ok(ReadbackData::Image(image)) => Some(image.texel(0, 0).and_then(decode)),
+            Ok(ReadbackData::Buffer(_)) => Some(None),
+            Err(e) => {
+                log::warn!("{}", e);
+                Some(None)
+            }
+        }
+    }
+}
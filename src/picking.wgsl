@@ -0,0 +1,19 @@
+// Entity ID buffer for picking: every fragment of a mesh writes its entity's id
+
+struct PickObject {
+    clip_from_local: mat4x4<f32>,
+    // Entity index + 1 (zero means nothing) and generation
+    id: vec2<u32>,
+}
+
+@group(0) @binding(0) var<uniform> object: PickObject;
+
+@vertex
+fn vs_main(@location(0) position: vec3<f32>) -> @builtin(position) vec4<f32> {
+    return object.clip_from_local * vec4<f32>(position, 1.0);
+}
+
+@fragment
+fn fs_main() -> @location(0) vec2<u32> {
+    return object.id;
+}
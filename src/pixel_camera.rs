@@ -0,0 +1,145 @@
+// src/pixel_camera.rs
+use crate::bind_cache::{BindGroupCache, SamplerCache};
+use crate::clip::ClipRect;
+use crate::resolution::{RenderScale, ResolutionScaler, UpscaleFilter};
+use crate::resource_registry::ResourceRegistry;
+use crate::upload::UploadBelt;
+use glam::{Mat4, Vec2};
+
+// Largest whole-number scale of `virtual_size` that fits the window, and the centred
+// window rect it covers. Never below 1; windows smaller than the virtual size squeeze it.
+pub fn integer_letterbox(virtual_size: (u32, u32), window_size: (u32, u32)) -> (u32, ClipRect) {
+    let scale = (window_size.0 / virtual_size.0.max(1)).min(window_size.1 / virtual_size.1.max(1)).max(1);
+    let (width, height) = (virtual_size.0 * scale, virtual_size.1 * scale);
+    let x = window_size.0.saturating_sub(width) / 2;
+    let y = window_size.1.saturating_sub(height) / 2;
+    (scale, ClipRect::new(x, y, width.min(window_size.0), height.min(window_size.1)))
+}
+
+// For sprite textures in pixel-art mode; filtering would blur texels together
+pub fn nearest_sampler(device: &wgpu::Device, samplers: &mut SamplerCache) -> wgpu::Sampler {
+    samplers.get(device, &wgpu::SamplerDescriptor {
+        label: Some("pixel art"),
+        mag_filter: wgpu::FilterMode::Nearest,
+        min_filter: wgpu::FilterMode::Nearest,
+        ..Default::default()
+    })
+}
+
+// 2D camera over a fixed virtual resolution. One world unit is one virtual pixel at zoom 1,
+// and the camera only ever sits on whole screen pixels, so scrolling doesn't shimmer.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PixelCamera {
+    // World position at the centre of the screen, +Y up
+    pub position: Vec2,
+    // Whole-number magnification; each world pixel covers zoom x zoom virtual pixels
+    pub zoom: u32,
+    virtual_size: (u32, u32),
+}
+
+impl PixelCamera {
+    pub fn new(virtual_width: u32, virtual_height: u32) -> Self {
+        Self { position: Vec2::ZERO, zoom: 1, virtual_size: (virtual_width.max(1), virtual_height.max(1)) }
+    }
+
+    pub fn virtual_size(&self) -> (u32, u32) {
+        self.virtual_size
+    }
+
+    // `position` rounded to the nearest virtual pixel
+    pub fn snapped_position(&self) -> Vec2 {
+        let zoom = self.zoom.max(1) as f32;
+        (self.position * zoom).round() / zoom
+    }
+
+    pub fn view_proj(&self) -> Mat4 {
+        let zoom = self.zoom.max(1) as f32;
+        let half = Vec2::new(self.virtual_size.0 as f32, self.virtual_size.1 as f32) / (2.0 * zoom);
+        let center = self.snapped_position();
+        // Odd virtual sizes would centre pixels on the screen's middle line; shifting half a
+        // pixel keeps texel edges on pixel edges
+        let offset = Vec2::new((self.virtual_size.0 % 2) as f32, (self.virtual_size.1 % 2) as f32) / (2.0 * zoom);
+        let min = center - half + offset;
+        let max = center + half + offset;
+        Mat4::orthographic_rh(min.x, max.x, min.y, max.y, -1000.0, 1000.0)
+    }
+
+    // Virtual pixel (top-left origin) to world position
+    pub fn virtual_to_world(&self, pixel: Vec2) -> Vec2 {
+        let size = Vec2::new(self.virtual_size.0 as f32, self.virtual_size.1 as f32);
+        let ndc = Vec2::new(pixel.x / size.x * 2.0 - 1.0, 1.0 - pixel.y / size.y * 2.0);
+        self.view_proj().inverse().project_point3(ndc.extend(0.0)).truncate()
+    }
+}
+
+// Pixel-art presentation: the scene renders at the camera's virtual resolution, then is
+// scaled up by a whole number with nearest filtering and letterboxed into the window.
+pub struct PixelPerfectRenderer {
+    pub camera: PixelCamera,
+    scaler: ResolutionScaler,
+    window_size: (u32, u32),
+}
+
+impl PixelPerfectRenderer {
+    pub fn new(
+        device: &wgpu::Device,
+        resources: &ResourceRegistry,
+        output_format: wgpu::TextureFormat,
+        virtual_width: u32,
+        virtual_height: u32,
+        window_width: u32,
+        window_height: u32,
+    ) -> Self {
+        let camera = PixelCamera::new(virtual_width, virtual_height);
+        let (width, height) = camera.virtual_size();
+        let mut scaler = ResolutionScaler::new(
+            device,
+            resources,
+            output_format,
+            output_format,
+            window_width,
+            window_height,
+            RenderScale::Fixed(width, height),
+        );
+        scaler.filter = UpscaleFilter::Nearest;
+        Self { camera, scaler, window_size: (window_width.max(1), window_height.max(1)) }
+    }
+
+    pub fn resize(&mut self, device: &wgpu::Device, resources: &ResourceRegistry, window_width: u32, window_height: u32) {
+        self.window_size = (window_width.max(1), window_height.max(1));
+        self.scaler.resize(device, resources, window_width, window_height);
+    }
+
+    // Render the scene here with `camera.view_proj()`
+    pub fn view(&self) -> &wgpu::TextureView {
+        self.scaler.view()
+    }
+
+    // Current integer scale and the window rect the game occupies
+    pub fn letterbox(&self) -> (u32, ClipRect) {
+        integer_letterbox(self.camera.virtual_size(), self.window_size)
+    }
+
+    // Window pixel (e.g. the cursor) to world position; `None` over the black bars
+    pub fn window_to_world(&self, window_pixel: Vec2) -> Option<Vec2> {
+        let (scale, rect) = self.letterbox();
+        if window_pixel.x < 0.0 || window_pixel.y < 0.0 || !rect.contains(window_pixel.x as u32, window_pixel.y as u32) {
+            return None;
+        }
+        let virtual_pixel = (window_pixel - Vec2::new(rect.x as f32, rect.y as f32)) / scale as f32;
+        Some(self.camera.virtual_to_world(virtual_pixel))
+    }
+
+    pub fn present(
+        &self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        belt: &mut UploadBelt,
+        bind_groups: &mut BindGroupCache,
+        samplers: &mut SamplerCache,
+        output: &wgpu::TextureView,
+    ) {
+        let (_, rect) = self.letterbox();
+        self.scaler.upscale_into(device, encoder, belt, bind_groups, samplers, output, Some(rect));
+    }
+}
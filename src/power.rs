@@ -0,0 +1,131 @@
+// src/power.rs
+use std::time::{Duration, Instant};
+
+// How often the power source is re-read in `PowerMode::Auto`
+const POWER_CHECK_INTERVAL: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PowerSource {
+    Mains,
+    Battery,
+    // Not reported on this platform
+    Unknown,
+}
+
+impl PowerSource {
+    // Reads /sys/class/power_supply on Linux; elsewhere always `Unknown`
+    pub fn detect() -> PowerSource {
+        let Ok(entries) = std::fs::read_dir("/sys/class/power_supply") else {
+            return PowerSource::Unknown;
+        };
+        let read = |path: std::path::PathBuf| std::fs::read_to_string(path).map(|s| s.trim().to_string()).unwrap_or_default();
+        let mut source = PowerSource::Unknown;
+        for entry in entries.flatten() {
+            let path = entry.path();
+            match read(path.join("type")).as_str() {
+                "Mains" if read(path.join("online")) == "1" => return PowerSource::Mains,
+                "Battery" if read(path.join("status")) == "Discharging" => source = PowerSource::Battery,
+                "Battery" if source == PowerSource::Unknown => source = PowerSource::Mains,
+                _ => {}
+            }
+        }
+        source
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PowerMode {
+    Performance,
+    // Half rate, and sleeps while idle; for games that can freeze without input, e.g.
+    // menus or turn-based play
+    LowPower,
+    // Half rate while running on battery; never sleeps, so the simulation keeps running
+    Auto,
+}
+
+// Decides when the app may trade smoothness for battery life. In low power, frames run at
+// half the refresh rate. `LowPower` mode also sleeps the event loop with
+// `ControlFlow::Wait` once nothing has happened for `idle_after`, until input or
+// `request_frames` wakes it. The app keeps it in `world.resources`.
+pub struct PowerManager {
+    pub mode: PowerMode,
+    pub idle_after: Duration,
+    source: PowerSource,
+    last_check: Option<Instant>,
+    last_activity: Instant,
+    // Game code keeping the loop awake, e.g. for a running animation
+    awake_until: Option<Instant>,
+}
+
+impl PowerManager {
+    pub fn new(mode: PowerMode) -> Self {
+        Self {
+            mode,
+            idle_after: Duration::from_secs(2),
+            source: PowerSource::Unknown,
+            last_check: None,
+            last_activity: Instant::now(),
+            awake_until: None,
+        }
+    }
+
+    // Re-reads the power source now and then; cheap to call every frame
+    pub fn poll(&mut self, now: Instant) {
+        if self.mode != PowerMode::Auto {
+            return;
+        }
+        if self.last_check.is_none_or(|last| now.duration_since(last) >= POWER_CHECK_INTERVAL) {
+            let source = PowerSource::detect();
+            if source != self.source {
+                log::info!("Power source: {:?}", source);
+            }
+            self.source = source;
+            self.last_check = Some(now);
+        }
+    }
+
+    pub fn source(&self) -> PowerSource {
+        self.source
+    }
+
+    pub fn is_low_power(&self) -> bool {
+        match self.mode {
+            PowerMode::Performance => false,
+            PowerMode::LowPower => true,
+            PowerMode::Auto => self.source == PowerSource::Battery,
+        }
+    }
+
+    // Input, window changes or anything else that needs a redraw
+    pub fn notify_activity(&mut self, now: Instant) {
+        self.last_activity = now;
+    }
+
+    // Keeps frames coming for `duration` even without input
+    pub fn request_frames(&mut self, now: Instant, duration: Duration) {
+        let until = now + duration;
+        self.awake_until = Some(self.awake_until.map_or(until, |current| current.max(until)));
+    }
+
+    // Only ever true in `PowerMode::LowPower`
+    pub fn is_idle(&self, now: Instant) -> bool {
+        self.mode == PowerMode::LowPower
+            && now.duration_since(self.last_activity) >= self.idle_after
+            && self.awake_until.is_none_or(|until| now >= until)
+    }
+
+    // For adapter selection at startup
+    pub fn adapter_preference(&self) -> wgpu::PowerPreference {
+        if self.is_low_power() {
+            wgpu::PowerPreference::LowPower
+        } else {
+            wgpu::PowerPreference::HighPerformance
+        }
+    }
+}
+
+impl Default for PowerManager {
+    fn default() -> Self {
+        Self::new(PowerMode::Auto)
+    }
+}
@@ -0,0 +1,215 @@
+// src/procgen/dungeon.rs
+use super::{Rng, Spawn};
+use crate::tilemap::{TileLayer, EMPTY_TILE};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DungeonSettings {
+    // Size in tiles
+    pub width: u32,
+    pub height: u32,
+    // Room sides, in tiles
+    pub min_room: u32,
+    pub max_room: u32,
+    // How many times the map is split; up to 2^depth rooms
+    pub max_depth: u32,
+    pub corridor_width: u32,
+    // Enemies per room other than the start room, min..=max
+    pub enemies_per_room: (u32, u32),
+}
+
+impl Default for DungeonSettings {
+    fn default() -> Self {
+        Self { width: 64, height: 48, min_room: 5, max_room: 12, max_depth: 4, corridor_width: 1, enemies_per_room: (0, 3) }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Room {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Room {
+    pub fn center(&self) -> (u32, u32) {
+        (self.x + self.width / 2, self.y + self.height / 2)
+    }
+
+    pub fn contains(&self, x: u32, y: u32) -> bool {
+        x >= self.x && y >= self.y && x < self.x + self.width && y < self.y + self.height
+    }
+}
+
+// A rooms-and-corridors level from binary space partitioning: the map is split
+// recursively, each leaf gets a room, and sibling subtrees are joined by a corridor, so
+// every room is reachable. Spawns: one "player" in the first room, an "exit" in the room
+// farthest from it, and "enemy" entries in the others.
+#[derive(Debug, Clone)]
+pub struct Dungeon {
+    pub width: u32,
+    pub height: u32,
+    // Row-major, row 0 at the bottom like `TileLayer`
+    floor: Vec<bool>,
+    pub rooms: Vec<Room>,
+    pub spawns: Vec<Spawn>,
+}
+
+#[derive(Clone, Copy)]
+struct Leaf {
+    x: u32,
+    y: u32,
+    width: u32,
+    height: u32,
+}
+
+impl Dungeon {
+    pub fn generate(settings: &DungeonSettings, seed: u64) -> Result<Self, String> {
+        let min_room = settings.min_room.max(3);
+        if settings.max_room < min_room {
+            return Err(format!("Dungeon max_room {} is smaller than min_room {}", settings.max_room, min_room));
+        }
+        // Rooms keep a one-tile wall inside their leaf
+        if settings.width < min_room + 2 || settings.height < min_room + 2 {
+            return Err(format!("A {}x{} dungeon can't fit a {}-tile room", settings.width, settings.height, min_room));
+        }
+        let mut dungeon = Self {
+            width: settings.width,
+            height: settings.height,
+            floor: vec![false; (settings.width * settings.height) as usize],
+            rooms: Vec::new(),
+            spawns: Vec::new(),
+        };
+        let mut rng = Rng::new(seed);
+        let root = Leaf { x: 0, y: 0, width: settings.width, height: settings.height };
+        dungeon.split(root, settings.max_depth, settings, &mut rng);
+        dungeon.place_spawns(settings, &mut rng);
+        Ok(dungeon)
+    }
+
+    // Builds the subtree's rooms, connects its halves, and returns the rooms it holds
+    fn split(&mut self, leaf: Leaf, depth: u32, settings: &DungeonSettings, rng: &mut Rng) -> Vec<usize> {
+        let min_leaf = settings.min_room.max(3) + 2;
+        let can_split_x = leaf.width >= min_leaf * 2;
+        let can_split_y = leaf.height >= min_leaf * 2;
+        if depth == 0 || !(can_split_x || can_split_y) {
+            return vec![self.add_room(leaf, settings, rng)];
+        }
+        // Split across the longer side, so leaves stay roughly square
+        let vertical = if can_split_x && can_split_y {
+            leaf.width > leaf.height || (leaf.width == leaf.height && rng.chance(0.5))
+        } else {
+            can_split_x
+        };
+        let (a, b) = if vertical {
+            let at = rng.range_u32(min_leaf, leaf.width - min_leaf + 1);
+            (Leaf { width: at, ..leaf }, Leaf { x: leaf.x + at, width: leaf.width - at, ..leaf })
+        } else {
+            let at = rng.range_u32(min_leaf, leaf.height - min_leaf + 1);
+            (Leaf { height: at, ..leaf }, Leaf { y: leaf.y + at, height: leaf.height - at, ..leaf })
+        };
+        let left = self.split(a, depth - 1, settings, rng);
+        let right = self.split(b, depth - 1, settings, rng);
+        // Join the closest pair of rooms across the split
+        let closest = left
+            .iter()
+            .flat_map(|&l| right.iter().map(move |&r| (l, r)))
+            .min_by_key(|&(l, r)| {
+                let (a, b) = (self.rooms[l].center(), self.rooms[r].center());
+                a.0.abs_diff(b.0) + a.1.abs_diff(b.1)
+            });
+        if let Some((l, r)) = closest {
+            self.corridor(self.rooms[l].center(), self.rooms[r].center(), settings.corridor_width, rng);
+        }
+        [left, right].concat()
+    }
+
+    fn add_room(&mut self, leaf: Leaf, settings: &DungeonSettings, rng: &mut Rng) -> usize {
+        let max_w = settings.max_room.min(leaf.width - 2);
+        let max_h = settings.max_room.min(leaf.height - 2);
+        let min_room = settings.min_room.max(3);
+        let width = rng.range_u32(min_room.min(max_w), max_w + 1);
+        let height = rng.range_u32(min_room.min(max_h), max_h + 1);
+        let x = leaf.x + 1 + rng.range_u32(0, leaf.width - 1 - width);
+        let y = leaf.y + 1 + rng.range_u32(0, leaf.height - 1 - height);
+        let room = Room { x, y, width, height };
+        self.carve(room.x, room.y, room.x + room.width, room.y + room.height);
+        self.rooms.push(room);
+        self.rooms.len() - 1
+    }
+
+    // Carves `min..max`, clamped to leave the outer border solid
+    fn carve(&mut self, x0: u32, y0: u32, x1: u32, y1: u32) {
+        for y in y0.max(1)..y1.min(self.height - 1) {
+            for x in x0.max(1)..x1.min(self.width - 1) {
+                self.floor[(y * self.width + x) as usize] = true;
+            }
+        }
+    }
+
+    // An L-shaped corridor, turning at random either way
+    fn corridor(&mut self, from: (u32, u32), to: (u32, u32), width: u32, rng: &mut Rng) {
+        let width = width.max(1);
+        let corner = if rng.chance(0.5) { (to.0, from.1) } else { (from.0, to.1) };
+        for (a, b) in [(from, corner), (corner, to)] {
+            let (x0, x1) = (a.0.min(b.0), a.0.max(b.0));
+            let (y0, y1) = (a.1.min(b.1), a.1.max(b.1));
+            self.carve(x0, y0, x1 + width, y1 + width);
+        }
+    }
+
+    fn place_spawns(&mut self, settings: &DungeonSettings, rng: &mut Rng) {
+        let Some(start) = self.rooms.first().copied() else { return };
+        self.spawns.push(Spawn { kind: "player".to_string(), cell: start.center() });
+        let c = start.center();
+        let exit = (1..self.rooms.len()).max_by_key(|&i| {
+            let r = self.rooms[i].center();
+            r.0.abs_diff(c.0) + r.1.abs_diff(c.1)
+        });
+        if let Some(exit) = exit {
+            self.spawns.push(Spawn { kind: "exit".to_string(), cell: self.rooms[exit].center() });
+        }
+        let (min, max) = settings.enemies_per_room;
+        for room in self.rooms.iter().skip(1) {
+            for _ in 0..rng.range_u32(min, max.max(min) + 1) {
+                let cell = (rng.range_u32(room.x, room.x + room.width), rng.range_u32(room.y, room.y + room.height));
+                self.spawns.push(Spawn { kind: "enemy".to_string(), cell });
+            }
+        }
+    }
+
+    pub fn is_floor(&self, x: i64, y: i64) -> bool {
+        x >= 0 && y >= 0 && x < self.width as i64 && y < self.height as i64 && self.floor[(y as u32 * self.width + x as u32) as usize]
+    }
+
+    // Solid cells that border the floor, including diagonally
+    pub fn is_wall(&self, x: i64, y: i64) -> bool {
+        !self.is_floor(x, y) && (-1..=1).any(|dy| (-1..=1).any(|dx| self.is_floor(x + dx, y + dy)))
+    }
+
+    // Row-major `true` for every cell that blocks, for `Grid::from_collision`
+    pub fn solid_mask(&self) -> Vec<bool> {
+        self.floor.iter().map(|&floor| !floor).collect()
+    }
+
+    // Walls bordering the floor, marked for collision; cells far from any floor stay empty
+    pub fn wall_layer(&self, name: &str, wall_tile: u32) -> TileLayer {
+        let mut layer = self.layer(name, |x, y| if self.is_wall(x, y) { wall_tile } else { EMPTY_TILE });
+        layer.collision = true;
+        layer
+    }
+
+    pub fn floor_layer(&self, name: &str, floor_tile: u32) -> TileLayer {
+        self.layer(name, |x, y| if self.is_floor(x, y) { floor_tile } else { EMPTY_TILE })
+    }
+
+    fn layer(&self, name: &str, tile: impl Fn(i64, i64) -> u32) -> TileLayer {
+        let mut layer = TileLayer::new(name, self.width, self.height);
+        for y in 0..self.height {
+            for x in 0..self.width {
+                layer.set(x, y, tile(x as i64, y as i64));
+            }
+        }
+        layer
+    }
+}
@@ -0,0 +1,77 @@
+// src/procgen/mod.rs
+pub mod dungeon;
+pub mod noise;
+pub mod poisson;
+pub mod wfc;
+
+use glam::Vec2;
+
+// Small seedable generator, so the same seed always builds the same level. Xorshift64*,
+// seeded through SplitMix64 so nearby seeds still give unrelated sequences.
+#[derive(Debug, Clone)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        let mut z = seed.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        Self { state: (z ^ (z >> 31)).max(1) }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        self.state.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    pub fn next_u32(&mut self) -> u32 {
+        (self.next_u64() >> 32) as u32
+    }
+
+    // Uniform in 0..1
+    pub fn next_f32(&mut self) -> f32 {
+        (self.next_u32() >> 8) as f32 / (1u32 << 24) as f32
+    }
+
+    // Uniform in `min..max`; `min` when the range is empty
+    pub fn range_u32(&mut self, min: u32, max: u32) -> u32 {
+        if max <= min {
+            return min;
+        }
+        min + (self.next_u64() % (max - min) as u64) as u32
+    }
+
+    pub fn range_f32(&mut self, min: f32, max: f32) -> f32 {
+        min + (max - min) * self.next_f32()
+    }
+
+    pub fn chance(&mut self, probability: f32) -> bool {
+        self.next_f32() < probability
+    }
+
+    // Uniform inside the rectangle `min..max`
+    pub fn point_in(&mut self, min: Vec2, max: Vec2) -> Vec2 {
+        Vec2::new(self.range_f32(min.x, max.x), self.range_f32(min.y, max.y))
+    }
+
+    pub fn pick<'a, T>(&mut self, items: &'a [T]) -> Option<&'a T> {
+        (!items.is_empty()).then(|| &items[self.range_u32(0, items.len() as u32) as usize])
+    }
+
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            items.swap(i, self.range_u32(0, i as u32 + 1) as usize);
+        }
+    }
+}
+
+// Something a generator wants placed, e.g. the player start or an enemy, at a tile
+#[derive(Debug, Clone, PartialEq)]
+pub struct Spawn {
+    pub kind: String,
+    pub cell: (u32, u32),
+}
@@ -0,0 +1,138 @@
+// src/procgen/noise.rs
+use super::Rng;
+use glam::Vec2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NoiseKind {
+    Perlin,
+    Simplex,
+}
+
+// Fractal settings for `Noise::fbm`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Fractal {
+    pub kind: NoiseKind,
+    pub octaves: u32,
+    // Scale of the first octave: features per world unit
+    pub frequency: f32,
+    // Frequency multiplier per octave
+    pub lacunarity: f32,
+    // Amplitude multiplier per octave
+    pub gain: f32,
+}
+
+impl Default for Fractal {
+    fn default() -> Self {
+        Self { kind: NoiseKind::Simplex, octaves: 5, frequency: 1.0, lacunarity: 2.0, gain: 0.5 }
+    }
+}
+
+// Twelve directions, evenly spread so no axis is favoured
+const GRADIENTS: [Vec2; 12] = [
+    Vec2::new(1.0, 0.0),
+    Vec2::new(0.866, 0.5),
+    Vec2::new(0.5, 0.866),
+    Vec2::new(0.0, 1.0),
+    Vec2::new(-0.5, 0.866),
+    Vec2::new(-0.866, 0.5),
+    Vec2::new(-1.0, 0.0),
+    Vec2::new(-0.866, -0.5),
+    Vec2::new(-0.5, -0.866),
+    Vec2::new(0.0, -1.0),
+    Vec2::new(0.5, -0.866),
+    Vec2::new(0.866, -0.5),
+];
+
+// Gradient noise over a permutation table shuffled by the seed. Output is roughly -1..=1.
+#[derive(Debug, Clone)]
+pub struct Noise {
+    perm: [u8; 512],
+}
+
+impl Noise {
+    pub fn new(seed: u64) -> Self {
+        let mut table: [u8; 256] = std::array::from_fn(|i| i as u8);
+        Rng::new(seed).shuffle(&mut table);
+        Self { perm: std::array::from_fn(|i| table[i & 255]) }
+    }
+
+    fn gradient(&self, x: i32, y: i32) -> Vec2 {
+        let hash = self.perm[(self.perm[(x & 255) as usize] as usize + (y & 255) as usize) & 511];
+        GRADIENTS[hash as usize % GRADIENTS.len()]
+    }
+
+    // Classic gradient noise on a square lattice, with the quintic fade
+    pub fn perlin(&self, p: Vec2) -> f32 {
+        let cell = p.floor();
+        let (x, y) = (cell.x as i32, cell.y as i32);
+        let f = p - cell;
+        let fade = f * f * f * (f * (f * 6.0 - 15.0) + 10.0);
+        let corner = |dx: i32, dy: i32| self.gradient(x + dx, y + dy).dot(f - Vec2::new(dx as f32, dy as f32));
+        let bottom = corner(0, 0) + (corner(1, 0) - corner(0, 0)) * fade.x;
+        let top = corner(0, 1) + (corner(1, 1) - corner(0, 1)) * fade.x;
+        // The lattice peaks at about ±0.7
+        (bottom + (top - bottom) * fade.y) * 1.42
+    }
+
+    // Simplex noise: fewer corners per sample than Perlin and no grid-aligned artefacts
+    pub fn simplex(&self, p: Vec2) -> f32 {
+        const SKEW: f32 = 0.366_025_42; // (sqrt(3) - 1) / 2
+        const UNSKEW: f32 = 0.211_324_87; // (3 - sqrt(3)) / 6
+        let s = (p.x + p.y) * SKEW;
+        let cell = (p + Vec2::splat(s)).floor();
+        let t = (cell.x + cell.y) * UNSKEW;
+        let d0 = p - (cell - Vec2::splat(t));
+        let step = if d0.x > d0.y { Vec2::X } else { Vec2::Y };
+        let d1 = d0 - step + Vec2::splat(UNSKEW);
+        let d2 = d0 - Vec2::ONE + Vec2::splat(2.0 * UNSKEW);
+        let (x, y) = (cell.x as i32, cell.y as i32);
+        let contribution = |d: Vec2, gx: i32, gy: i32| {
+            let falloff = 0.5 - d.length_squared();
+            if falloff <= 0.0 {
+                return 0.0;
+            }
+            let falloff = falloff * falloff;
+            falloff * falloff * self.gradient(gx, gy).dot(d)
+        };
+        let sum = contribution(d0, x, y)
+            + contribution(d1, x + step.x as i32, y + step.y as i32)
+            + contribution(d2, x + 1, y + 1);
+        // Scaled so the output spans about -1..=1 for unit gradients
+        sum * 99.0
+    }
+
+    pub fn sample(&self, kind: NoiseKind, p: Vec2) -> f32 {
+        match kind {
+            NoiseKind::Perlin => self.perlin(p),
+            NoiseKind::Simplex => self.simplex(p),
+        }
+    }
+
+    // Fractal Brownian motion: octaves summed and normalised back to about -1..=1
+    pub fn fbm(&self, p: Vec2, fractal: &Fractal) -> f32 {
+        let (mut sum, mut amplitude, mut total, mut frequency) = (0.0, 1.0, 0.0, fractal.frequency);
+        for octave in 0..fractal.octaves.max(1) {
+            // Offset each octave so their lattices don't line up at the origin
+            let offset = Vec2::splat(octave as f32 * 17.13);
+            sum += self.sample(fractal.kind, p * frequency + offset) * amplitude;
+            total += amplitude;
+            amplitude *= fractal.gain;
+            frequency *= fractal.lacunarity;
+        }
+        sum / total
+    }
+
+    // Sharp crests where the noise crosses zero, for mountain ridges; 0..=1
+    pub fn ridged(&self, p: Vec2, fractal: &Fractal) -> f32 {
+        let (mut sum, mut amplitude, mut total, mut frequency) = (0.0, 1.0, 0.0, fractal.frequency);
+        for octave in 0..fractal.octaves.max(1) {
+            let offset = Vec2::splat(octave as f32 * 17.13);
+            let ridge = 1.0 - self.sample(fractal.kind, p * frequency + offset).abs();
+            sum += ridge * ridge * amplitude;
+            total += amplitude;
+            amplitude *= fractal.gain;
+            frequency *= fractal.lacunarity;
+        }
+        sum / total
+    }
+}
@@ -0,0 +1,83 @@
+// src/procgen/poisson.rs
+use super::Rng;
+use glam::Vec2;
+
+// Candidates tried around each point before it's retired
+const DEFAULT_ATTEMPTS: u32 = 30;
+
+// Evenly spread random points in `min..max`, none closer than `radius` to another
+// (Bridson's algorithm). Good for scattering trees, rocks or pickups without clumps.
+pub fn poisson_disk(rng: &mut Rng, min: Vec2, max: Vec2, radius: f32) -> Vec<Vec2> {
+    poisson_disk_with(rng, min, max, radius, DEFAULT_ATTEMPTS, |_| true)
+}
+
+// As `poisson_disk`, keeping only points `accept` allows, e.g. those on walkable ground.
+// More `attempts` packs the points more tightly.
+pub fn poisson_disk_with(
+    rng: &mut Rng,
+    min: Vec2,
+    max: Vec2,
+    radius: f32,
+    attempts: u32,
+    accept: impl Fn(Vec2) -> bool,
+) -> Vec<Vec2> {
+    let size = max - min;
+    if radius <= 0.0 || size.x <= 0.0 || size.y <= 0.0 {
+        return Vec::new();
+    }
+    // A cell this size holds at most one point
+    let cell_size = radius / std::f32::consts::SQRT_2;
+    let (columns, rows) = ((size.x / cell_size).ceil() as usize, (size.y / cell_size).ceil() as usize);
+    let mut grid: Vec<Option<u32>> = vec![None; columns * rows];
+    let cell_of = |p: Vec2| {
+        let c = ((p - min) / cell_size).floor();
+        ((c.x as usize).min(columns - 1), (c.y as usize).min(rows - 1))
+    };
+
+    let mut points: Vec<Vec2> = Vec::new();
+    let mut active: Vec<u32> = Vec::new();
+    let add = |p: Vec2, grid: &mut [Option<u32>], points: &mut Vec<Vec2>, active: &mut Vec<u32>| {
+        let (cx, cy) = cell_of(p);
+        grid[cy * columns + cx] = Some(points.len() as u32);
+        active.push(points.len() as u32);
+        points.push(p);
+    };
+
+    // Seed from a random accepted point, giving up if the area seems to reject everything
+    for _ in 0..attempts.max(1) * 4 {
+        let p = rng.point_in(min, max);
+        if accept(p) {
+            add(p, &mut grid, &mut points, &mut active);
+            break;
+        }
+    }
+
+    while !active.is_empty() {
+        let slot = rng.range_u32(0, active.len() as u32) as usize;
+        let center = points[active[slot] as usize];
+        let mut found = false;
+        for _ in 0..attempts.max(1) {
+            // Uniform in the annulus radius..2 * radius
+            let angle = rng.range_f32(0.0, std::f32::consts::TAU);
+            let distance = radius * (1.0 + 3.0 * rng.next_f32()).sqrt();
+            let candidate = center + Vec2::from_angle(angle) * distance;
+            if candidate.cmplt(min).any() || candidate.cmpge(max).any() || !accept(candidate) {
+                continue;
+            }
+            let (cx, cy) = cell_of(candidate);
+            let near = (cy.saturating_sub(2)..(cy + 3).min(rows))
+                .flat_map(|y| (cx.saturating_sub(2)..(cx + 3).min(columns)).map(move |x| (x, y)))
+                .filter_map(|(x, y)| grid[y * columns + x])
+                .any(|other| points[other as usize].distance_squared(candidate) < radius * radius);
+            if !near {
+                add(candidate, &mut grid, &mut points, &mut active);
+                found = true;
+                break;
+            }
+        }
+        if !found {
+            active.swap_remove(slot);
+        }
+    }
+    points
+}
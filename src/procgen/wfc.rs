@@ -0,0 +1,255 @@
+// src/procgen/wfc.rs
+use super::Rng;
+use crate::tilemap::TileLayer;
+use serde::Deserialize;
+
+// Sides in `sockets` order: north, east, south, west (y up)
+const OFFSETS: [(i64, i64); 4] = [(0, 1), (1, 0), (0, -1), (-1, 0)];
+
+fn opposite(side: usize) -> usize {
+    (side + 2) % 4
+}
+
+// A tile and the edge labels it exposes. Two tiles may sit side by side when the touching
+// sockets match, e.g. a road tile's "road" east socket only fits a "road" west socket.
+#[derive(Debug, Clone, Deserialize)]
+pub struct WfcTile {
+    pub tile: u32,
+    // North, east, south, west
+    pub sockets: Vec<String>,
+    // Relative frequency
+    #[serde(default = "default_weight")]
+    pub weight: f32,
+}
+
+fn default_weight() -> f32 {
+    1.0
+}
+
+// Tiles for Wave Function Collapse, loaded from RON, e.g.
+// `(tiles: [(tile: 1, sockets: ["grass", "grass", "grass", "grass"], weight: 4.0), ...])`
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct WfcTileset {
+    pub tiles: Vec<WfcTile>,
+    // Attempts before giving up on a contradiction
+    pub max_attempts: u32,
+}
+
+impl WfcTileset {
+    pub fn from_ron(source: &str) -> Result<Self, String> {
+        let tileset: Self = ron::from_str(source).map_err(|e| format!("Failed to parse WFC tileset: {}", e))?;
+        tileset.validate()?;
+        Ok(tileset)
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if self.tiles.is_empty() {
+            return Err("WFC tileset has no tiles".to_string());
+        }
+        for tile in &self.tiles {
+            if tile.sockets.len() != 4 {
+                return Err(format!("WFC tile {} needs 4 sockets (north, east, south, west), has {}", tile.tile, tile.sockets.len()));
+            }
+            if !tile.weight.is_finite() || tile.weight <= 0.0 {
+                return Err(format!("WFC tile {} needs a positive weight", tile.tile));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn load(path: &str) -> Result<Self, String> {
+        let source = std::fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+        Self::from_ron(&source)
+    }
+}
+
+// Set of candidate tiles for one cell, one bit per tileset index
+#[derive(Clone, PartialEq)]
+struct Domain(Vec<u64>);
+
+impl Domain {
+    fn full(count: usize) -> Self {
+        let mut words = vec![u64::MAX; count.div_ceil(64)];
+        if !count.is_multiple_of(64) {
+            *words.last_mut().expect("at least one tile") = (1u64 << (count % 64)) - 1;
+        }
+        Domain(words)
+    }
+
+    fn empty(count: usize) -> Self {
+        Domain(vec![0; count.div_ceil(64)])
+    }
+
+    fn single(count: usize, tile: usize) -> Self {
+        let mut domain = Self::empty(count);
+        domain.insert(tile);
+        domain
+    }
+
+    fn insert(&mut self, tile: usize) {
+        self.0[tile / 64] |= 1 << (tile % 64);
+    }
+
+    fn len(&self) -> u32 {
+        self.0.iter().map(|w| w.count_ones()).sum()
+    }
+
+    fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        self.0.iter().enumerate().flat_map(|(i, &word)| (0..64).filter(move |b| word & (1 << b) != 0).map(move |b| i * 64 + b))
+    }
+
+    // Keeps only tiles also in `other`; true if anything was removed
+    fn intersect(&mut self, other: &Domain) -> bool {
+        let mut changed = false;
+        for (word, mask) in self.0.iter_mut().zip(&other.0) {
+            let kept = *word & mask;
+            changed |= kept != *word;
+            *word = kept;
+        }
+        changed
+    }
+}
+
+// Wave Function Collapse on a grid: every cell starts able to be any tile, the most
+// constrained cell is collapsed to a weighted random choice, and the choice is propagated
+// to the neighbours' candidates. A contradiction restarts with the next seed.
+pub struct Wfc<'a> {
+    tileset: &'a WfcTileset,
+    width: u32,
+    height: u32,
+    // compatible[side][tile]: tiles allowed on that side of `tile`
+    compatible: [Vec<Domain>; 4],
+    fixed: Vec<(u32, u32, usize)>,
+}
+
+impl<'a> Wfc<'a> {
+    pub fn new(tileset: &'a WfcTileset, width: u32, height: u32) -> Result<Self, String> {
+        tileset.validate()?;
+        if width == 0 || height == 0 {
+            return Err("WFC output needs at least one cell".to_string());
+        }
+        let count = tileset.tiles.len();
+        let compatible = std::array::from_fn(|side| {
+            (0..count)
+                .map(|a| {
+                    let mut domain = Domain::empty(count);
+                    for (b, other) in tileset.tiles.iter().enumerate() {
+                        if tileset.tiles[a].sockets[side] == other.sockets[opposite(side)] {
+                            domain.insert(b);
+                        }
+                    }
+                    domain
+                })
+                .collect()
+        });
+        Ok(Self { tileset, width, height, compatible, fixed: Vec::new() })
+    }
+
+    // Forces a cell before solving, e.g. an entrance on the border
+    pub fn fix(&mut self, x: u32, y: u32, tile: u32) -> Result<&mut Self, String> {
+        let index = self
+            .tileset
+            .tiles
+            .iter()
+            .position(|t| t.tile == tile)
+            .ok_or_else(|| format!("Tile {} is not in the WFC tileset", tile))?;
+        if x >= self.width || y >= self.height {
+            return Err(format!("WFC cell ({}, {}) is outside the {}x{} output", x, y, self.width, self.height));
+        }
+        self.fixed.push((x, y, index));
+        Ok(self)
+    }
+
+    // Solves into a tile layer named `name`
+    pub fn generate(&self, name: &str, seed: u64) -> Result<TileLayer, String> {
+        let attempts = self.tileset.max_attempts.max(1);
+        for attempt in 0..attempts {
+            let mut rng = Rng::new(seed.wrapping_add(attempt as u64));
+            if let Some(cells) = self.solve(&mut rng) {
+                let mut layer = TileLayer::new(name, self.width, self.height);
+                for (i, tile) in cells.into_iter().enumerate() {
+                    layer.set(i as u32 % self.width, i as u32 / self.width, self.tileset.tiles[tile].tile);
+                }
+                return Ok(layer);
+            }
+        }
+        Err(format!("WFC hit a contradiction in all {} attempts; the sockets may be too restrictive", attempts))
+    }
+
+    fn solve(&self, rng: &mut Rng) -> Option<Vec<usize>> {
+        let count = self.tileset.tiles.len();
+        let mut cells = vec![Domain::full(count); (self.width * self.height) as usize];
+        let mut pending = Vec::new();
+        for &(x, y, tile) in &self.fixed {
+            let index = (y * self.width + x) as usize;
+            cells[index].intersect(&Domain::single(count, tile));
+            pending.push(index);
+        }
+        self.propagate(&mut cells, &mut pending)?;
+
+        loop {
+            // Lowest entropy, with a little noise to break ties randomly
+            let next = cells
+                .iter()
+                .enumerate()
+                .filter(|(_, d)| d.len() > 1)
+                .map(|(i, d)| (i, self.entropy(d) + rng.next_f32() * 1e-3))
+                .min_by(|a, b| a.1.total_cmp(&b.1));
+            let Some((index, _)) = next else { break };
+            let total: f32 = cells[index].iter().map(|t| self.tileset.tiles[t].weight).sum();
+            let mut roll = rng.next_f32() * total;
+            let candidates: Vec<usize> = cells[index].iter().collect();
+            let mut choice = *candidates.last().expect("cell has candidates");
+            for &tile in &candidates {
+                roll -= self.tileset.tiles[tile].weight;
+                if roll <= 0.0 {
+                    choice = tile;
+                    break;
+                }
+            }
+            cells[index] = Domain::single(count, choice);
+            pending.push(index);
+            self.propagate(&mut cells, &mut pending)?;
+        }
+        cells.iter().map(|d| d.iter().next()).collect()
+    }
+
+    fn entropy(&self, domain: &Domain) -> f32 {
+        let (mut sum, mut sum_log) = (0.0f32, 0.0f32);
+        for tile in domain.iter() {
+            let w = self.tileset.tiles[tile].weight;
+            sum += w;
+            sum_log += w * w.ln();
+        }
+        sum.ln() - sum_log / sum
+    }
+
+    // Narrows neighbours until nothing changes; `None` when a cell runs out of tiles
+    fn propagate(&self, cells: &mut [Domain], pending: &mut Vec<usize>) -> Option<()> {
+        let count = self.tileset.tiles.len();
+        while let Some(index) = pending.pop() {
+            let (x, y) = ((index as u32 % self.width) as i64, (index as u32 / self.width) as i64);
+            for (side, (dx, dy)) in OFFSETS.iter().enumerate() {
+                let (nx, ny) = (x + dx, y + dy);
+                if nx < 0 || ny < 0 || nx >= self.width as i64 || ny >= self.height as i64 {
+                    continue;
+                }
+                let mut allowed = Domain::empty(count);
+                for tile in cells[index].iter() {
+                    for (word, mask) in allowed.0.iter_mut().zip(&self.compatible[side][tile].0) {
+                        *word |= mask;
+                    }
+                }
+                let neighbor = (ny as u32 * self.width + nx as u32) as usize;
+                if cells[neighbor].intersect(&allowed) {
+                    if cells[neighbor].len() == 0 {
+                        return None;
+                    }
+                    pending.push(neighbor);
+                }
+            }
+        }
+        Some(())
+    }
+}
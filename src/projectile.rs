@@ -0,0 +1,191 @@
+// src/projectile.rs
+use crate::physics::collision::CollisionWorld;
+use crate::physics::query::{QueryFilter, RayHit};
+use crate::physics::shape::Shape;
+use glam::Vec2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ProjectileId {
+    index: u32,
+    generation: u32,
+}
+
+#[derive(Debug, Clone)]
+pub struct Projectile {
+    pub position: Vec2,
+    pub velocity: Vec2,
+    // 0 casts a ray; anything larger sweeps a circle, for fat bullets and fireballs
+    pub radius: f32,
+    // Multiplies the system's gravity: 0 for bullets, 1 for grenades and arrows
+    pub gravity_scale: f32,
+    // Seconds before the projectile expires without hitting anything
+    pub lifetime: f32,
+    // Usually the shooter's layers, excluding the shooter's own collider
+    pub filter: QueryFilter,
+    // Caller's handle, e.g. the weapon or the damage it deals
+    pub user_data: u64,
+}
+
+impl Projectile {
+    pub fn new(position: Vec2, velocity: Vec2, lifetime: f32) -> Self {
+        Self { position, velocity, radius: 0.0, gravity_scale: 0.0, lifetime, filter: QueryFilter::default(), user_data: 0 }
+    }
+
+    pub fn with_radius(mut self, radius: f32) -> Self {
+        self.radius = radius;
+        self
+    }
+
+    pub fn with_gravity(mut self, gravity_scale: f32) -> Self {
+        self.gravity_scale = gravity_scale;
+        self
+    }
+
+    pub fn with_filter(mut self, filter: QueryFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    pub fn with_user_data(mut self, user_data: u64) -> Self {
+        self.user_data = user_data;
+        self
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ProjectileEvent {
+    // Hit a collider; the projectile is gone and `position` is where it stopped
+    Impact { projectile: ProjectileId, user_data: u64, position: Vec2, velocity: Vec2, hit: RayHit },
+    // Ran out of lifetime mid-air, e.g. for grenades that go off on a timer
+    Expired { projectile: ProjectileId, user_data: u64, position: Vec2 },
+}
+
+struct Slot {
+    generation: u32,
+    projectile: Option<Projectile>,
+}
+
+// Every live projectile, stepped against a `CollisionWorld`. Each tick sweeps the path
+// travelled since the last one, so fast projectiles can't pass through thin walls.
+// Slots of finished projectiles are reused, so steady fire doesn't allocate.
+pub struct ProjectileSystem {
+    pub gravity: Vec2,
+    slots: Vec<Slot>,
+    free: Vec<u32>,
+    live: usize,
+    events: Vec<ProjectileEvent>,
+}
+
+impl ProjectileSystem {
+    pub fn new(gravity: Vec2) -> Self {
+        Self { gravity, slots: Vec::new(), free: Vec::new(), live: 0, events: Vec::new() }
+    }
+
+    // Allocates `capacity` slots up front, e.g. the most bullets a level ever has in flight
+    pub fn with_capacity(gravity: Vec2, capacity: usize) -> Self {
+        let mut system = Self::new(gravity);
+        system.slots.extend((0..capacity).map(|_| Slot { generation: 0, projectile: None }));
+        // Popped from the back, so low slots fill first
+        system.free.extend((0..capacity as u32).rev());
+        system.events.reserve(capacity);
+        system
+    }
+
+    pub fn spawn(&mut self, projectile: Projectile) -> ProjectileId {
+        self.live += 1;
+        if let Some(index) = self.free.pop() {
+            let slot = &mut self.slots[index as usize];
+            slot.projectile = Some(projectile);
+            return ProjectileId { index, generation: slot.generation };
+        }
+        self.slots.push(Slot { generation: 0, projectile: Some(projectile) });
+        ProjectileId { index: self.slots.len() as u32 - 1, generation: 0 }
+    }
+
+    // Removes a projectile without an event; false if it had already finished
+    pub fn despawn(&mut self, id: ProjectileId) -> bool {
+        self.release(id).is_some()
+    }
+
+    pub fn get(&self, id: ProjectileId) -> Option<&Projectile> {
+        self.slots.get(id.index as usize).filter(|s| s.generation == id.generation)?.projectile.as_ref()
+    }
+
+    pub fn get_mut(&mut self, id: ProjectileId) -> Option<&mut Projectile> {
+        self.slots.get_mut(id.index as usize).filter(|s| s.generation == id.generation)?.projectile.as_mut()
+    }
+
+    pub fn len(&self) -> usize {
+        self.live
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.live == 0
+    }
+
+    // Live projectiles, e.g. for drawing them
+    pub fn iter(&self) -> impl Iterator<Item = (ProjectileId, &Projectile)> {
+        self.slots.iter().enumerate().filter_map(|(index, slot)| {
+            Some((ProjectileId { index: index as u32, generation: slot.generation }, slot.projectile.as_ref()?))
+        })
+    }
+
+    pub fn clear(&mut self) {
+        for index in 0..self.slots.len() as u32 {
+            let generation = self.slots[index as usize].generation;
+            self.release(ProjectileId { index, generation });
+        }
+    }
+
+    fn release(&mut self, id: ProjectileId) -> Option<Projectile> {
+        let slot = self.slots.get_mut(id.index as usize).filter(|s| s.generation == id.generation)?;
+        let projectile = slot.projectile.take()?;
+        slot.generation = slot.generation.wrapping_add(1);
+        self.free.push(id.index);
+        self.live -= 1;
+        Some(projectile)
+    }
+
+    // Moves every projectile by `delta_time`, queueing an event for each one that hits
+    // something or expires. Call from the fixed update, after colliders have moved.
+    pub fn step(&mut self, collisions: &CollisionWorld, delta_time: f32) {
+        for index in 0..self.slots.len() {
+            let generation = self.slots[index].generation;
+            let Some(projectile) = self.slots[index].projectile.as_mut() else { continue };
+            let id = ProjectileId { index: index as u32, generation };
+
+            let acceleration = self.gravity * projectile.gravity_scale;
+            let motion = projectile.velocity * delta_time + acceleration * (0.5 * delta_time * delta_time);
+            let distance = motion.length();
+            let hit = if projectile.radius > 0.0 {
+                let shape = Shape::Circle { radius: projectile.radius };
+                collisions.shape_cast(&shape, projectile.position, motion, distance, &projectile.filter)
+            } else {
+                collisions.raycast(projectile.position, motion, distance, &projectile.filter)
+            };
+            if let Some(hit) = hit {
+                let position = projectile.position + motion / distance * hit.distance;
+                let fraction = hit.distance / distance;
+                let velocity = projectile.velocity + acceleration * delta_time * fraction;
+                let user_data = projectile.user_data;
+                self.release(id);
+                self.events.push(ProjectileEvent::Impact { projectile: id, user_data, position, velocity, hit });
+                continue;
+            }
+
+            projectile.position += motion;
+            projectile.velocity += acceleration * delta_time;
+            projectile.lifetime -= delta_time;
+            if projectile.lifetime <= 0.0 {
+                let (user_data, position) = (projectile.user_data, projectile.position);
+                self.release(id);
+                self.events.push(ProjectileEvent::Expired { projectile: id, user_data, position });
+            }
+        }
+    }
+
+    // Impacts and expiries since the last call, in the order they happened
+    pub fn drain_events(&mut self) -> impl Iterator<Item = ProjectileEvent> + '_ {
+        self.events.drain(..)
+    }
+}
@@ -0,0 +1,239 @@
+// src/readback.rs
+use crate::regression::Image;
+use crate::resource_registry::{ResourceRegistry, Tracked};
+use std::sync::{Arc, Mutex};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ReadbackId(u64);
+
+// Texels copied out of a texture region, rows tightly packed
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReadbackImage {
+    pub width: u32,
+    pub height: u32,
+    pub format: wgpu::TextureFormat,
+    pub data: Vec<u8>,
+}
+
+impl ReadbackImage {
+    pub fn texel_size(&self) -> usize {
+        self.format.block_copy_size(None).unwrap_or(4) as usize
+    }
+
+    // Raw bytes of one texel, e.g. an entity id in an R32Uint target
+    pub fn texel(&self, x: u32, y: u32) -> Option<&[u8]> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        let size = self.texel_size();
+        let start = (y as usize * self.width as usize + x as usize) * size;
+        self.data.get(start..start + size)
+    }
+
+    // 8-bit RGBA or BGRA surfaces as RGBA8, e.g. for `Image::write_pam`
+    pub fn to_image(&self) -> Option<Image> {
+        use wgpu::TextureFormat::*;
+        let pixels = match self.format {
+            Rgba8Unorm | Rgba8UnormSrgb => self.data.clone(),
+            Bgra8Unorm | Bgra8UnormSrgb => self.data.chunks_exact(4).flat_map(|bgra| [bgra[2], bgra[1], bgra[0], bgra[3]]).collect(),
+            _ => return None,
+        };
+        Some(Image { width: self.width, height: self.height, pixels })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ReadbackData {
+    Buffer(Vec<u8>),
+    Image(ReadbackImage),
+}
+
+enum Layout {
+    Buffer { size: u64 },
+    Image { width: u32, height: u32, format: wgpu::TextureFormat, row: u32, padded_row: u32 },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum MapState {
+    // The copy is recorded but its commands may not be submitted yet
+    Recorded,
+    Mapping,
+    Ready,
+    Failed(String),
+}
+
+struct Pending {
+    id: ReadbackId,
+    buffer: Tracked<wgpu::Buffer>,
+    layout: Layout,
+    state: Arc<Mutex<MapState>>,
+}
+
+// Copies buffers and texture regions back to the CPU without stalling: `read_*` records the
+// copy, `after_submit` starts mapping once the commands are submitted and `collect`, called
+// every frame after polling the device, picks up whatever has arrived. The renderer does
+// both around its own submit, so copies recorded into its frame (or submitted before it)
+// need nothing else. Results usually arrive one or two frames later.
+#[derive(Default)]
+pub struct Readbacks {
+    pending: Vec<Pending>,
+    finished: Vec<(ReadbackId, Result<ReadbackData, String>)>,
+    next_id: u64,
+}
+
+impl Readbacks {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn staging(&mut self, device: &wgpu::Device, resources: &ResourceRegistry, size: u64) -> (ReadbackId, Tracked<wgpu::Buffer>) {
+        self.next_id += 1;
+        let buffer = resources.create_buffer(device, &wgpu::BufferDescriptor {
+            label: Some("readback"),
+            size,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        }, "readback");
+        (ReadbackId(self.next_id), buffer)
+    }
+
+    fn push(&mut self, id: ReadbackId, buffer: Tracked<wgpu::Buffer>, layout: Layout) -> ReadbackId {
+        self.pending.push(Pending { id, buffer, layout, state: Arc::new(Mutex::new(MapState::Recorded)) });
+        id
+    }
+
+    // `source` needs COPY_SRC; `offset` and `size` multiples of 4, e.g. resolved query results
+    pub fn read_buffer(
+        &mut self,
+        device: &wgpu::Device,
+        resources: &ResourceRegistry,
+        encoder: &mut wgpu::CommandEncoder,
+        source: &wgpu::Buffer,
+        offset: u64,
+        size: u64,
+    ) -> Result<ReadbackId, String> {
+        if size == 0 || !offset.is_multiple_of(wgpu::COPY_BUFFER_ALIGNMENT) || !size.is_multiple_of(wgpu::COPY_BUFFER_ALIGNMENT) {
+            return Err(format!("Buffer readback of {} bytes at {} isn't 4-byte aligned", size, offset));
+        }
+        let (id, buffer) = self.staging(device, resources, size);
+        encoder.copy_buffer_to_buffer(source, offset, &buffer, 0, size);
+        Ok(self.push(id, buffer, Layout::Buffer { size }))
+    }
+
+    // `origin` and `size` in texels of mip 0; `texture` needs COPY_SRC and an uncompressed
+    // colour format, e.g. one pixel of an ID buffer or a whole frame for a screenshot
+    pub fn read_texture(
+        &mut self,
+        device: &wgpu::Device,
+        resources: &ResourceRegistry,
+        encoder: &mut wgpu::CommandEncoder,
+        texture: &wgpu::Texture,
+        origin: (u32, u32),
+        size: (u32, u32),
+    ) -> Result<ReadbackId, String> {
+        let format = texture.format();
+        if !texture.usage().contains(wgpu::TextureUsages::COPY_SRC) {
+            return Err("Texture readback needs COPY_SRC usage".to_string());
+        }
+        let texel_size = match format.block_copy_size(None) {
+            Some(texel_size) if format.block_dimensions() == (1, 1) => texel_size,
+            _ => return Err(format!("Can't read back {:?} textures", format)),
+        };
+        let (x, y) = origin;
+        let (width, height) = size;
+        if width == 0 || height == 0 || x + width > texture.width() || y + height > texture.height() {
+            return Err(format!("Readback region {}x{} at ({}, {}) is outside the texture", width, height, x, y));
+        }
+        let row = width * texel_size;
+        let padded_row = row.div_ceil(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT) * wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+        let (id, buffer) = self.staging(device, resources, padded_row as u64 * height as u64);
+        encoder.copy_texture_to_buffer(
+            wgpu::TexelCopyTextureInfo {
+                texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d { x, y, z: 0 },
+                aspect: wgpu::TextureAspect::All,
+            },
+            wgpu::TexelCopyBufferInfo {
+                buffer: &buffer,
+                layout: wgpu::TexelCopyBufferLayout { offset: 0, bytes_per_row: Some(padded_row), rows_per_image: Some(height) },
+            },
+            wgpu::Extent3d { width, height, depth_or_array_layers: 1 },
+        );
+        Ok(self.push(id, buffer, Layout::Image { width, height, format, row, padded_row }))
+    }
+
+    // Call after submitting the commands that hold the copies
+    pub fn after_submit(&mut self) {
+        for pending in &self.pending {
+            {
+                // Released before `map_async`, whose callback may run right away on error
+                let mut state = lock(&pending.state);
+                if *state != MapState::Recorded {
+                    continue;
+                }
+                *state = MapState::Mapping;
+            }
+            let callback_state = pending.state.clone();
+            pending.buffer.slice(..).map_async(wgpu::MapMode::Read, move |result| {
+                *lock(&callback_state) = match result {
+                    Ok(()) => MapState::Ready,
+                    Err(e) => MapState::Failed(e.to_string()),
+                };
+            });
+        }
+    }
+
+    // Call after `device.poll`; moves arrived results to `take`/`drain`
+    pub fn collect(&mut self) {
+        let mut index = 0;
+        while index < self.pending.len() {
+            let state = lock(&self.pending[index].state).clone();
+            let result = match state {
+                MapState::Recorded | MapState::Mapping => {
+                    index += 1;
+                    continue;
+                }
+                MapState::Failed(e) => Err(format!("Readback failed: {}", e)),
+                MapState::Ready => Ok(read_mapped(&self.pending[index])),
+            };
+            let pending = self.pending.swap_remove(index);
+            if result.is_ok() {
+                pending.buffer.unmap();
+            }
+            self.finished.push((pending.id, result));
+        }
+    }
+
+    pub fn is_pending(&self, id: ReadbackId) -> bool {
+        self.pending.iter().any(|pending| pending.id == id)
+    }
+
+    // The result for `id` once it arrived; each result is handed out once
+    pub fn take(&mut self, id: ReadbackId) -> Option<Result<ReadbackData, String>> {
+        let index = self.finished.iter().position(|(finished, _)| *finished == id)?;
+        Some(self.finished.swap_remove(index).1)
+    }
+
+    pub fn drain(&mut self) -> Vec<(ReadbackId, Result<ReadbackData, String>)> {
+        std::mem::take(&mut self.finished)
+    }
+}
+
+fn lock(state: &Mutex<MapState>) -> std::sync::MutexGuard<'_, MapState> {
+    state.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn read_mapped(pending: &Pending) -> ReadbackData {
+    let mapped = pending.buffer.slice(..).get_mapped_range();
+    match pending.layout {
+        Layout::Buffer { size } => ReadbackData::Buffer(mapped[..size as usize].to_vec()),
+        Layout::Image { width, height, format, row, padded_row } => {
+            let mut data = Vec::with_capacity(row as usize * height as usize);
+            for line in mapped.chunks_exact(padded_row as usize) {
+                data.extend_from_slice(&line[..row as usize]);
+            }
+            ReadbackData::Image(ReadbackImage { width, height, format, data })
+        }
+    }
+}
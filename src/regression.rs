@@ -0,0 +1,316 @@
+// src/regression.rs
+use crate::bind_cache::{BindGroupCache, SamplerCache};
+use crate::resource_registry::ResourceRegistry;
+use crate::upload::UploadBelt;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+// Linear 8-bit, so results don't depend on the platform's sRGB encode
+pub const REFERENCE_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8Unorm;
+// Set to rewrite golden images from the current output instead of comparing
+pub const UPDATE_GOLDEN_ENV: &str = "VELLUM_UPDATE_GOLDEN";
+const READBACK_TIMEOUT: Duration = Duration::from_secs(10);
+
+// Tightly packed RGBA8 pixels
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Image {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>,
+}
+
+impl Image {
+    // Netpbm PAM (RGB_ALPHA): lossless, dependency-free, and most image viewers open it
+    pub fn write_pam(&self, path: &Path) -> Result<(), String> {
+        let mut data = format!(
+            "P7\nWIDTH {}\nHEIGHT {}\nDEPTH 4\nMAXVAL 255\nTUPLTYPE RGB_ALPHA\nENDHDR\n",
+            self.width, self.height
+        )
+        .into_bytes();
+        data.extend_from_slice(&self.pixels);
+        std::fs::write(path, data).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+    }
+
+    pub fn read_pam(path: &Path) -> Result<Image, String> {
+        let data = std::fs::read(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        const END: &[u8] = b"ENDHDR\n";
+        let header_end = data
+            .windows(END.len())
+            .position(|window| window == END)
+            .ok_or_else(|| format!("{} is not a PAM image", path.display()))?;
+        let header = String::from_utf8_lossy(&data[..header_end]);
+        if !header.starts_with("P7") {
+            return Err(format!("{} is not a PAM image", path.display()));
+        }
+        let field = |name: &str| {
+            header
+                .lines()
+                .find_map(|line| line.strip_prefix(name).map(str::trim))
+                .and_then(|value| value.parse::<u32>().ok())
+                .ok_or_else(|| format!("{}: missing {}", path.display(), name))
+        };
+        let (width, height) = (field("WIDTH")?, field("HEIGHT")?);
+        if field("DEPTH")? != 4 || field("MAXVAL")? != 255 {
+            return Err(format!("{}: only 8-bit RGBA PAM images are supported", path.display()));
+        }
+        let pixels = data[header_end + END.len()..].to_vec();
+        if pixels.len() != width as usize * height as usize * 4 {
+            return Err(format!("{}: expected {}x{} pixels", path.display(), width, height));
+        }
+        Ok(Image { width, height, pixels })
+    }
+
+    // Red where pixels differ beyond `tolerance`, a dimmed grey copy of `self` elsewhere
+    pub fn diff_image(&self, other: &Image, tolerance: &Tolerance) -> Image {
+        let pixels = self
+            .pixels
+            .chunks_exact(4)
+            .zip(other.pixels.chunks_exact(4))
+            .flat_map(|(a, b)| {
+                if channel_difference(a, b) > tolerance.channel {
+                    [255, 0, 0, 255]
+                } else {
+                    let grey = ((a[0] as u32 + a[1] as u32 + a[2] as u32) / 12) as u8;
+                    [grey, grey, grey, 255]
+                }
+            })
+            .collect();
+        Image { width: self.width, height: self.height, pixels }
+    }
+}
+
+fn channel_difference(a: &[u8], b: &[u8]) -> u8 {
+    a.iter().zip(b).map(|(x, y)| x.abs_diff(*y)).max().unwrap_or(0)
+}
+
+// GPU drivers round differently, so a little disagreement is expected
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Tolerance {
+    // Largest per-channel difference that still counts as matching
+    pub channel: u8,
+    // Fraction of pixels allowed to exceed `channel`
+    pub max_differing: f32,
+}
+
+impl Default for Tolerance {
+    fn default() -> Self {
+        Self { channel: 2, max_differing: 0.001 }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Comparison {
+    pub differing_pixels: u64,
+    pub total_pixels: u64,
+    pub max_channel_difference: u8,
+    pub passed: bool,
+}
+
+pub fn compare(actual: &Image, expected: &Image, tolerance: &Tolerance) -> Comparison {
+    let total_pixels = actual.width as u64 * actual.height as u64;
+    if (actual.width, actual.height) != (expected.width, expected.height) {
+        return Comparison { differing_pixels: total_pixels, total_pixels, max_channel_difference: 255, passed: false };
+    }
+    let mut differing_pixels = 0;
+    let mut max_channel_difference = 0;
+    for (a, b) in actual.pixels.chunks_exact(4).zip(expected.pixels.chunks_exact(4)) {
+        let difference = channel_difference(a, b);
+        max_channel_difference = max_channel_difference.max(difference);
+        if difference > tolerance.channel {
+            differing_pixels += 1;
+        }
+    }
+    let passed = differing_pixels as f64 <= tolerance.max_differing as f64 * total_pixels as f64;
+    Comparison { differing_pixels, total_pixels, max_channel_difference, passed }
+}
+
+// Engine state a reference scene renders with
+pub struct RenderContext<'a> {
+    pub device: &'a wgpu::Device,
+    pub queue: &'a wgpu::Queue,
+    pub resources: &'a ResourceRegistry,
+    pub belt: &'a mut UploadBelt,
+    pub bind_groups: &'a mut BindGroupCache,
+    pub samplers: &'a mut SamplerCache,
+}
+
+pub trait ReferenceScene {
+    // Golden file name, without extension
+    fn name(&self) -> &str;
+
+    fn size(&self) -> (u32, u32) {
+        (256, 256)
+    }
+
+    // Draws one frame into `target`, a cleared `REFERENCE_FORMAT` texture of `size`.
+    // Must be deterministic: fixed camera, fixed time step, no randomness.
+    fn render(&mut self, context: &mut RenderContext<'_>, encoder: &mut wgpu::CommandEncoder, target: &wgpu::TextureView);
+}
+
+// Renders reference scenes offscreen and checks them against golden images in
+// `golden_dir`. A missing golden image is written from the first run. On a mismatch the
+// actual output and a diff image are written next to it for inspection.
+pub struct RegressionHarness {
+    golden_dir: PathBuf,
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+    resources: ResourceRegistry,
+    belt: UploadBelt,
+    bind_groups: BindGroupCache,
+    samplers: SamplerCache,
+}
+
+impl RegressionHarness {
+    // Fails without any adapter; CI machines without a GPU can use a software one such
+    // as lavapipe or WARP
+    pub fn new(golden_dir: impl Into<PathBuf>) -> Result<Self, String> {
+        let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor {
+            backends: wgpu::Backends::all(),
+            ..Default::default()
+        });
+        let adapter = pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions {
+            power_preference: wgpu::PowerPreference::LowPower,
+            compatible_surface: None,
+            force_fallback_adapter: false,
+        }))
+        .map_err(|e| format!("No adapter for headless rendering: {}", e))?;
+        let info = adapter.get_info();
+        log::info!("Regression harness using {} ({:?})", info.name, info.backend);
+        let (device, queue) = pollster::block_on(adapter.request_device(&wgpu::DeviceDescriptor {
+            label: Some("regression harness"),
+            required_features: wgpu::Features::empty(),
+            required_limits: wgpu::Limits::downlevel_defaults(),
+            memory_hints: wgpu::MemoryHints::default(),
+            experimental_features: wgpu::ExperimentalFeatures::default(),
+            trace: wgpu::Trace::Off,
+        }))
+        .map_err(|e| format!("Failed to request device: {}", e))?;
+
+        Ok(Self {
+            golden_dir: golden_dir.into(),
+            device,
+            queue,
+            resources: ResourceRegistry::new(),
+            belt: UploadBelt::new(64 * 1024),
+            bind_groups: BindGroupCache::new(256),
+            samplers: SamplerCache::new(),
+        })
+    }
+
+    pub fn render(&mut self, scene: &mut dyn ReferenceScene) -> Result<Image, String> {
+        let (width, height) = scene.size();
+        let size = wgpu::Extent3d { width: width.max(1), height: height.max(1), depth_or_array_layers: 1 };
+        let texture = self.device.create_texture(&wgpu::TextureDescriptor {
+            label: Some(scene.name()),
+            size,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: REFERENCE_FORMAT,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let unpadded_row = size.width * 4;
+        let padded_row = unpadded_row.div_ceil(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT) * wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+        let readback = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("regression readback"),
+            size: padded_row as u64 * size.height as u64,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        let mut encoder = self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("regression harness"),
+        });
+        encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("regression clear"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: &view,
+                resolve_target: None,
+                ops: wgpu::Operations { load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT), store: wgpu::StoreOp::Store },
+                depth_slice: None,
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        let mut context = RenderContext {
+            device: &self.device,
+            queue: &self.queue,
+            resources: &self.resources,
+            belt: &mut self.belt,
+            bind_groups: &mut self.bind_groups,
+            samplers: &mut self.samplers,
+        };
+        scene.render(&mut context, &mut encoder, &view);
+        encoder.copy_texture_to_buffer(
+            wgpu::TexelCopyTextureInfo {
+                texture: &texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            wgpu::TexelCopyBufferInfo {
+                buffer: &readback,
+                layout: wgpu::TexelCopyBufferLayout {
+                    offset: 0,
+                    bytes_per_row: Some(padded_row),
+                    rows_per_image: Some(size.height),
+                },
+            },
+            size,
+        );
+        self.belt.finish();
+        self.queue.submit(std::iter::once(encoder.finish()));
+        self.belt.recall();
+
+        readback.slice(..).map_async(wgpu::MapMode::Read, |_| {});
+        self.device
+            .poll(wgpu::PollType::Wait { submission_index: None, timeout: Some(READBACK_TIMEOUT) })
+            .map_err(|e| format!("Waiting for '{}' failed: {}", scene.name(), e))?;
+        let mut pixels = Vec::with_capacity((unpadded_row * size.height) as usize);
+        {
+            let data = readback.slice(..).get_mapped_range();
+            for row in data.chunks_exact(padded_row as usize) {
+                pixels.extend_from_slice(&row[..unpadded_row as usize]);
+            }
+        }
+        readback.unmap();
+        Ok(Image { width: size.width, height: size.height, pixels })
+    }
+
+    // Renders `scene` and compares it with its golden image; `Err` describes the mismatch
+    pub fn check(&mut self, scene: &mut dyn ReferenceScene, tolerance: &Tolerance) -> Result<Comparison, String> {
+        let actual = self.render(scene)?;
+        let golden = self.golden_dir.join(format!("{}.pam", scene.name()));
+        if std::env::var_os(UPDATE_GOLDEN_ENV).is_some() || !golden.exists() {
+            std::fs::create_dir_all(&self.golden_dir)
+                .map_err(|e| format!("Failed to create {}: {}", self.golden_dir.display(), e))?;
+            actual.write_pam(&golden)?;
+            log::info!("Wrote golden image {}", golden.display());
+            let total_pixels = actual.width as u64 * actual.height as u64;
+            return Ok(Comparison { differing_pixels: 0, total_pixels, max_channel_difference: 0, passed: true });
+        }
+
+        let expected = Image::read_pam(&golden)?;
+        let comparison = compare(&actual, &expected, tolerance);
+        if comparison.passed {
+            return Ok(comparison);
+        }
+        let actual_path = self.golden_dir.join(format!("{}.actual.pam", scene.name()));
+        actual.write_pam(&actual_path)?;
+        if (actual.width, actual.height) == (expected.width, expected.height) {
+            actual.diff_image(&expected, tolerance).write_pam(&self.golden_dir.join(format!("{}.diff.pam", scene.name())))?;
+        }
+        Err(format!(
+            "'{}' differs from {}: {} of {} pixels off by up to {} (output in {})",
+            scene.name(),
+            golden.display(),
+            comparison.differing_pixels,
+            comparison.total_pixels,
+            comparison.max_channel_difference,
+            actual_path.display()
+        ))
+    }
+}
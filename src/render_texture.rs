@@ -0,0 +1,270 @@
+// src/render_texture.rs
+use crate::atmosphere::Atmosphere;
+use crate::camera::ClearMode;
+use crate::resource_registry::{ResourceRegistry, Tracked};
+use glam::{Mat4, Vec3};
+use std::f32::consts::PI;
+
+pub const RENDER_TEXTURE_DEPTH_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth32Float;
+
+// Defaults to identity matrices, so positions pass through as clip space
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct ViewCamera {
+    pub view: Mat4,
+    pub projection: Mat4,
+}
+
+impl ViewCamera {
+    pub fn perspective(eye: Vec3, target: Vec3, fov_y: f32, aspect: f32, near: f32, far: f32) -> Self {
+        Self {
+            view: Mat4::look_at_rh(eye, target, Vec3::Y),
+            projection: Mat4::perspective_rh(fov_y, aspect, near, far),
+        }
+    }
+
+    // Looking straight down on `center`, covering `extent` world units on each side; the
+    // usual minimap camera. +Z is down on the map.
+    pub fn top_down(center: Vec3, extent: f32, height: f32) -> Self {
+        Self {
+            view: Mat4::look_at_rh(center + Vec3::Y * height, center, Vec3::NEG_Z),
+            projection: Mat4::orthographic_rh(-extent, extent, -extent, extent, 0.0, height * 2.0),
+        }
+    }
+
+    pub fn view_proj(&self) -> Mat4 {
+        self.projection * self.view
+    }
+
+    pub fn position(&self) -> Vec3 {
+        self.view.inverse().w_axis.truncate()
+    }
+}
+
+// `view` reflected through the plane at `point` with `normal`. Mirroring flips winding,
+// so the pass rendering with it needs its cull mode inverted.
+pub fn mirror_view(view: Mat4, point: Vec3, normal: Vec3) -> Mat4 {
+    let n = normal.normalize();
+    let d = -n.dot(point);
+    let reflection = Mat4::from_cols(
+        (1.0 - 2.0 * n.x * n.x, -2.0 * n.x * n.y, -2.0 * n.x * n.z, 0.0).into(),
+        (-2.0 * n.y * n.x, 1.0 - 2.0 * n.y * n.y, -2.0 * n.y * n.z, 0.0).into(),
+        (-2.0 * n.z * n.x, -2.0 * n.z * n.y, 1.0 - 2.0 * n.z * n.z, 0.0).into(),
+        (-2.0 * d * n.x, -2.0 * d * n.y, -2.0 * d * n.z, 1.0).into(),
+    );
+    view * reflection
+}
+
+// What the viewer sees through a portal: `view` moved from the `entrance` portal's frame to
+// the `exit` portal's. Both transforms face out of their portal along local +Z, so the
+// viewer comes out of the exit facing away from it.
+pub fn portal_view(view: Mat4, entrance: Mat4, exit: Mat4) -> Mat4 {
+    view * entrance * Mat4::from_rotation_y(PI) * exit.inverse()
+}
+
+// How often a render texture is redrawn; security cameras and minimaps rarely need every frame
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UpdateRate {
+    EveryFrame,
+    // Once every n frames
+    Interval(u32),
+    // Only after `RenderTextures::request_update`
+    Manual,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RenderTextureDesc {
+    pub width: u32,
+    pub height: u32,
+    pub format: wgpu::TextureFormat,
+    // Adds a `RENDER_TEXTURE_DEPTH_FORMAT` attachment for 3D views
+    pub depth: bool,
+    pub clear: ClearMode,
+    pub update_rate: UpdateRate,
+}
+
+impl Default for RenderTextureDesc {
+    fn default() -> Self {
+        Self {
+            width: 512,
+            height: 512,
+            format: wgpu::TextureFormat::Rgba8UnormSrgb,
+            depth: true,
+            clear: ClearMode::default(),
+            update_rate: UpdateRate::EveryFrame,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RenderTextureId(u32);
+
+// A secondary camera and the texture it renders into. `view` can be bound like any other
+// texture, so it works as a material input for screens, portals and HUD minimaps.
+pub struct RenderTexture {
+    pub camera: ViewCamera,
+    pub enabled: bool,
+    desc: RenderTextureDesc,
+    _color: Tracked<wgpu::Texture>,
+    view: wgpu::TextureView,
+    _depth: Option<Tracked<wgpu::Texture>>,
+    depth_view: Option<wgpu::TextureView>,
+    frames_since_update: u32,
+    update_requested: bool,
+}
+
+impl RenderTexture {
+    pub fn desc(&self) -> &RenderTextureDesc {
+        &self.desc
+    }
+
+    pub fn view(&self) -> &wgpu::TextureView {
+        &self.view
+    }
+
+    pub fn aspect(&self) -> f32 {
+        self.desc.width as f32 / self.desc.height as f32
+    }
+
+    fn is_due(&self) -> bool {
+        self.enabled
+            && (self.update_requested
+                || match self.desc.update_rate {
+                    UpdateRate::EveryFrame => true,
+                    UpdateRate::Interval(n) => self.frames_since_update + 1 >= n.max(1),
+                    UpdateRate::Manual => false,
+                })
+    }
+}
+
+// Registry of game-owned render textures. Once per frame, before the main view, `render`
+// begins a cleared pass for each one that is due and hands it to the caller to draw the
+// scene with that texture's camera.
+pub struct RenderTextures {
+    entries: Vec<(RenderTextureId, RenderTexture)>,
+    next_id: u32,
+}
+
+impl RenderTextures {
+    pub fn new() -> Self {
+        Self { entries: Vec::new(), next_id: 0 }
+    }
+
+    pub fn create(
+        &mut self,
+        device: &wgpu::Device,
+        resources: &ResourceRegistry,
+        label: &str,
+        desc: RenderTextureDesc,
+        camera: ViewCamera,
+    ) -> RenderTextureId {
+        let desc = RenderTextureDesc { width: desc.width.max(1), height: desc.height.max(1), ..desc };
+        let texture = |format, usage| resources.create_texture(device, &wgpu::TextureDescriptor {
+            label: Some(label),
+            size: wgpu::Extent3d { width: desc.width, height: desc.height, depth_or_array_layers: 1 },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage,
+            view_formats: &[],
+        }, "render_texture");
+        let color = texture(desc.format, wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING);
+        let depth = desc.depth.then(|| texture(RENDER_TEXTURE_DEPTH_FORMAT, wgpu::TextureUsages::RENDER_ATTACHMENT));
+
+        let id = RenderTextureId(self.next_id);
+        self.next_id += 1;
+        self.entries.push((id, RenderTexture {
+            camera,
+            enabled: true,
+            desc,
+            view: color.create_view(&wgpu::TextureViewDescriptor::default()),
+            _color: color,
+            depth_view: depth.as_ref().map(|depth| depth.create_view(&wgpu::TextureViewDescriptor::default())),
+            _depth: depth,
+            frames_since_update: 0,
+            // Render once up front so the texture never shows uninitialized contents
+            update_requested: true,
+        }));
+        id
+    }
+
+    pub fn remove(&mut self, id: RenderTextureId) -> bool {
+        let count = self.entries.len();
+        self.entries.retain(|(existing, _)| *existing != id);
+        self.entries.len() != count
+    }
+
+    pub fn get(&self, id: RenderTextureId) -> Option<&RenderTexture> {
+        self.entries.iter().find(|(existing, _)| *existing == id).map(|(_, target)| target)
+    }
+
+    pub fn get_mut(&mut self, id: RenderTextureId) -> Option<&mut RenderTexture> {
+        self.entries.iter_mut().find(|(existing, _)| *existing == id).map(|(_, target)| target)
+    }
+
+    // Shorthand for binding the texture in a material
+    pub fn view(&self, id: RenderTextureId) -> Option<&wgpu::TextureView> {
+        self.get(id).map(RenderTexture::view)
+    }
+
+    // Redraws the texture on the next `render`, whatever its update rate
+    pub fn request_update(&mut self, id: RenderTextureId) {
+        if let Some(target) = self.get_mut(id) {
+            target.update_requested = true;
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    // Calls `draw` with a pass started per the texture's `clear` and its camera, for each
+    // texture due this frame. `atmosphere` gives the fog colour for `ClearMode::Skybox`.
+    pub fn render<F>(&mut self, encoder: &mut wgpu::CommandEncoder, atmosphere: &Atmosphere, mut draw: F)
+    where
+        F: FnMut(&mut wgpu::RenderPass<'_>, RenderTextureId, &ViewCamera),
+    {
+        for (id, target) in &mut self.entries {
+            if !target.is_due() {
+                target.frames_since_update = target.frames_since_update.saturating_add(1);
+                continue;
+            }
+            target.frames_since_update = 0;
+            target.update_requested = false;
+
+            let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("render texture"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &target.view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: target.desc.clear.load_op(atmosphere),
+                        store: wgpu::StoreOp::Store,
+                    },
+                    depth_slice: None,
+                })],
+                depth_stencil_attachment: target.depth_view.as_ref().map(|view| wgpu::RenderPassDepthStencilAttachment {
+                    view,
+                    depth_ops: Some(wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(1.0),
+                        store: wgpu::StoreOp::Discard,
+                    }),
+                    stencil_ops: None,
+                }),
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+            draw(&mut pass, *id, &target.camera);
+        }
+    }
+}
+
+impl Default for RenderTextures {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -1,8 +1,44 @@
 // src/renderer.rs
-use wgpu::{Device, Instance, Queue, Surface, SurfaceConfiguration, RenderPipeline};
+use wgpu::{Device, Queue, Surface, SurfaceConfiguration, RenderPipeline};
 use winit::window::Window;
 use std::sync::Arc;
+use glam::Vec2;
 use crate::scene::Scene;
+use crate::resource_registry::{ResourceRegistry, ResourceStats, Tracked};
+use crate::upload::{UploadBelt, FRAMES_IN_FLIGHT};
+use crate::frames::FramesInFlight;
+use crate::readback::{ReadbackData, ReadbackId, Readbacks};
+use crate::regression::Image;
+use crate::bind_cache::{BindGroupCache, SamplerCache};
+use crate::particles::GpuParticles;
+use crate::environment::EnvironmentController;
+use crate::indirect::IndirectMode;
+use crate::window::DisplayMetrics;
+use crate::camera::ClearMode;
+use crate::capabilities::GpuCapabilities;
+use crate::gpu_fallback::{acquire_gpu, GpuSelection, GpuTier};
+use crate::gpu_validation::GpuValidation;
+use crate::frame_capture::FrameCapture;
+use crate::shader_errors::{compile_shader, ShaderDiagnostic, ShaderErrorLog};
+use crate::gpu_buffer::{GpuStruct, UniformBuffer};
+use crate::render_texture::ViewCamera;
+use crate::ui::{Ui, UiPass};
+use crate::debug_view::{DebugDraw, DebugViewPass};
+use crate::occlusion::OcclusionQueries;
+use crate::atmosphere::{AtmosphereRenderer, FOG_GROUP, FOG_WGSL};
+use crate::screen_effect::ScreenEffectChain;
+use crate::resolution::{RenderScale, ResolutionScaler, UpscaleFilter};
+use crate::render_texture::RENDER_TEXTURE_DEPTH_FORMAT;
+use glam::Mat4;
+
+// Mirrors `CameraParams` in shader.wgsl
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct CameraParams {
+    view_proj: [f32; 16],
+}
+
+impl GpuStruct for CameraParams {}
 
 pub struct Renderer {
     pub device: Option<Device>,
@@ -11,8 +47,70 @@ pub struct Renderer {
     pub config: Option<SurfaceConfiguration>,
     pub render_pipeline: Option<RenderPipeline>,
     pub scene: Scene,
+    // View for the main pass; the default identity draws scene positions as clip space
+    pub camera: ViewCamera,
+    pub resources: ResourceRegistry,
+    // The current frame's belt; `frames` swaps in the next slot's at the start of `render`
+    pub upload_belt: UploadBelt,
+    pub frames: FramesInFlight,
+    pub bind_groups: BindGroupCache,
+    pub samplers: SamplerCache,
+    pub particles: Option<GpuParticles>,
+    // Drives `scene.atmosphere` and shows rain or snow through `particles`
+    pub environment: Option<EnvironmentController>,
+    pub indirect_mode: IndirectMode,
+    // Fallback tier and adapter picked by `initialize`
+    pub gpu: Option<GpuSelection>,
+    // What that device supports; query before enabling optional paths
+    pub capabilities: GpuCapabilities,
+    pub display: DisplayMetrics,
+    // User preference on top of the OS scale factor, e.g. from an accessibility menu
+    pub ui_scale: f32,
+    // Adapter choice for `initialize`
+    pub power_preference: wgpu::PowerPreference,
+    // Render target when initialized headless, in place of the surface
+    pub offscreen: Option<Tracked<wgpu::Texture>>,
+    // Draw calls issued by the last `render`
+    pub draw_calls: u32,
+    // How the main pass starts; `Skybox` draws `scene.atmosphere`'s sky behind the entities
+    pub clear: ClearMode,
+    // Copies back to the CPU; collected once per frame in `render`
+    pub readbacks: Readbacks,
+    // Developer error scopes around frame work; set before `initialize` for backend
+    // validation layers too
+    pub validation: GpuValidation,
+    // Graphics debugger captures, see `request_frame_capture`
+    pub capture: FrameCapture,
+    // Compile errors from engine shaders, for the debug overlay or console
+    pub shader_errors: ShaderErrorLog,
+    // Widgets over the scene, fed by `queue_ui`; e.g. `set_font` on it for text
+    pub ui: Option<UiPass>,
+    // Replaces the main pass while its view isn't `Off`, under particles and UI; the app
+    // binds F3 to `debug_view.cycle`
+    pub debug_view: Option<DebugViewPass>,
+    // Culls scene entities hidden behind others or off screen; see `enable_occlusion`
+    pub occlusion: Option<OcclusionQueries>,
+    // Fullscreen effects from the finished frame, UI included, to the window; `add` e.g. a
+    // damage vignette to it. Passes the frame through unchanged while empty.
+    pub screen_effects: Option<ScreenEffectChain>,
+    screenshot_requested: bool,
+    screenshot: Option<ReadbackId>,
+    camera_params: Option<(UniformBuffer<CameraParams>, wgpu::BindGroupLayout)>,
+    // `scene.atmosphere` on the GPU, for the main shader's fog and the sky
+    atmosphere: Option<AtmosphereRenderer>,
+    // What the frame draws into before `screen_effects` carries it to the output
+    scene_target: Option<Tracked<wgpu::Texture>>,
+    // Internal target the scene renders into instead while a render scale is set
+    resolution: Option<ResolutionScaler>,
+    // The main pass's depth buffer, at `render_size`
+    depth: Option<Tracked<wgpu::Texture>>,
 }
 
+const HEADLESS_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8UnormSrgb;
+// Shared with render textures, so passes built for either accept both
+const DEPTH_FORMAT: wgpu::TextureFormat = RENDER_TEXTURE_DEPTH_FORMAT;
+const UPLOAD_CHUNK_SIZE: wgpu::BufferAddress = 64 * 1024;
+
 impl Renderer {
     pub fn new() -> Self {
         Self {
@@ -22,191 +120,595 @@ impl Renderer {
             config: None,
             render_pipeline: None,
             scene: Scene::new(),
+            camera: ViewCamera::default(),
+            resources: ResourceRegistry::new(),
+            upload_belt: UploadBelt::new(UPLOAD_CHUNK_SIZE),
+            frames: FramesInFlight::new(UPLOAD_CHUNK_SIZE),
+            bind_groups: BindGroupCache::new(256),
+            samplers: SamplerCache::new(),
+            particles: None,
+            environment: None,
+            indirect_mode: IndirectMode::Direct,
+            gpu: None,
+            capabilities: GpuCapabilities::default(),
+            display: DisplayMetrics::default(),
+            ui_scale: 1.0,
+            power_preference: wgpu::PowerPreference::LowPower,
+            offscreen: None,
+            draw_calls: 0,
+            clear: ClearMode::default(),
+            readbacks: Readbacks::new(),
+            validation: GpuValidation::from_env(),
+            capture: FrameCapture::new(),
+            shader_errors: ShaderErrorLog::new(),
+            ui: None,
+            debug_view: None,
+            occlusion: None,
+            screen_effects: None,
+            screenshot_requested: false,
+            screenshot: None,
+            camera_params: None,
+            atmosphere: None,
+            scene_target: None,
+            resolution: None,
+            depth: None,
         }
     }
 
+    // Falls back through `acquire_gpu`'s chain instead of failing on the first device
+    // error; `gpu` reports the tier it settled on
     pub async fn initialize(&mut self, window: Arc<Window>) -> Result<(), String> {
-        // FIXED: Added & to borrow the descriptor
-        let instance = Instance::new(&wgpu::InstanceDescriptor {
-            backends: wgpu::Backends::all(),
-            ..Default::default()
-        });
-
-        log::info!("Enumerating adapters:");
-        for adapter in instance.enumerate_adapters(wgpu::Backends::all()) {
-            let info = adapter.get_info();
-            log::info!("  - {} ({:?})", info.name, info.backend);
-        }
-
-        let surface = instance.create_surface(window.clone()).map_err(|e| format!("Failed to create surface: {}", e))?;
-
-        // FIXED: request_adapter now returns Result instead of Option
-        let adapter = match instance
-            .request_adapter(&wgpu::RequestAdapterOptions {
-                power_preference: wgpu::PowerPreference::LowPower,
-                compatible_surface: Some(&surface),
-                force_fallback_adapter: false,
-            })
-            .await
-        {
-            Ok(adapter) => adapter,
-            Err(_) => {
-                log::warn!("No adapter found with surface compatibility, trying without...");
-                instance
-                    .request_adapter(&wgpu::RequestAdapterOptions {
-                        power_preference: wgpu::PowerPreference::LowPower,
-                        compatible_surface: None,
-                        force_fallback_adapter: true,
-                    })
-                    .await
-                    .map_err(|_| "Failed to find any suitable GPU adapter.")?
-            }
-        };
-
-        let info = adapter.get_info();
-        log::info!("Using adapter: {} ({:?})", info.name, info.backend);
-
-        let (device, queue) = adapter
-            .request_device(&wgpu::DeviceDescriptor {
-                label: None,
-                required_features: wgpu::Features::empty(),
-                required_limits: wgpu::Limits::downlevel_defaults(),
-                // FIXED: Added missing fields for wgpu 27.0
-                memory_hints: wgpu::MemoryHints::default(),
-                experimental_features: wgpu::ExperimentalFeatures::default(),
-                trace: wgpu::Trace::Off,
-            })
-            .await
-            .map_err(|e| format!("Failed to request device: {}", e))?;
+        let gpu = acquire_gpu(Some(&window), self.power_preference, self.validation.instance_flags()).await?;
+        let surface = gpu.surface.ok_or("GPU was acquired without a surface")?;
+        let adapter = gpu.adapter;
+        let (device, queue) = (gpu.device, gpu.queue);
+        self.select_gpu(&adapter, &device, gpu.selection);
+        self.display = DisplayMetrics::from_window(&window);
 
         let surface_caps = surface.get_capabilities(&adapter);
         let surface_format = surface_caps.formats[0];
+        let (width, height) = self.capabilities.clamp_extent(window.inner_size().width, window.inner_size().height);
         let config = SurfaceConfiguration {
-            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            // COPY_SRC where offered, for screenshots
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | (surface_caps.usages & wgpu::TextureUsages::COPY_SRC),
             format: surface_format,
-            width: window.inner_size().width,
-            height: window.inner_size().height,
+            width,
+            height,
             present_mode: wgpu::PresentMode::Fifo,
             alpha_mode: surface_caps.alpha_modes[0],
             view_formats: vec![],
-            desired_maximum_frame_latency: 2,
+            desired_maximum_frame_latency: FRAMES_IN_FLIGHT as u32,
         };
         surface.configure(&device, &config);
 
-        let shader = device.create_shader_module(wgpu::include_wgsl!("shader.wgsl"));
+        self.finish_initialize(device, queue, Some(surface), config).await;
+        Ok(())
+    }
+
+    // No window: frames render into `offscreen`, e.g. for benchmarks on machines without
+    // a display
+    pub async fn initialize_headless(&mut self, width: u32, height: u32) -> Result<(), String> {
+        let gpu = acquire_gpu(None, self.power_preference, self.validation.instance_flags()).await?;
+        let (device, queue) = (gpu.device, gpu.queue);
+        self.select_gpu(&gpu.adapter, &device, gpu.selection);
+        self.display = DisplayMetrics::new(width.max(1), height.max(1), 1.0);
+        let (width, height) = self.capabilities.clamp_extent(width, height);
+
+        let config = SurfaceConfiguration {
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            format: HEADLESS_FORMAT,
+            width,
+            height,
+            present_mode: wgpu::PresentMode::Fifo,
+            alpha_mode: wgpu::CompositeAlphaMode::Opaque,
+            view_formats: vec![],
+            desired_maximum_frame_latency: FRAMES_IN_FLIGHT as u32,
+        };
+        self.offscreen = Some(self.create_offscreen(&device, &config));
+        self.finish_initialize(device, queue, None, config).await;
+        Ok(())
+    }
+
+    fn create_offscreen(&self, device: &Device, config: &SurfaceConfiguration) -> Tracked<wgpu::Texture> {
+        self.resources.create_texture(device, &wgpu::TextureDescriptor {
+            label: Some("headless target"),
+            size: wgpu::Extent3d { width: config.width, height: config.height, depth_or_array_layers: 1 },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: config.format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        }, "renderer")
+    }
+
+    fn create_scene_target(&self, device: &Device, config: &SurfaceConfiguration) -> Tracked<wgpu::Texture> {
+        self.resources.create_texture(device, &wgpu::TextureDescriptor {
+            label: Some("scene colour"),
+            size: wgpu::Extent3d { width: config.width, height: config.height, depth_or_array_layers: 1 },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: config.format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        }, "renderer")
+    }
+
+    fn create_depth(&self, device: &Device, (width, height): (u32, u32)) -> Tracked<wgpu::Texture> {
+        self.resources.create_texture(device, &wgpu::TextureDescriptor {
+            label: Some("main depth"),
+            size: wgpu::Extent3d { width, height, depth_or_array_layers: 1 },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: DEPTH_FORMAT,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
+        }, "renderer")
+    }
+
+    fn select_gpu(&mut self, adapter: &wgpu::Adapter, device: &Device, selection: GpuSelection) {
+        self.capabilities = GpuCapabilities::new(adapter, device);
+        self.indirect_mode = self.capabilities.indirect_mode();
+        log::info!(
+            "Indirect draw mode: {:?}, compute: {}, bindless: {}, max texture size: {}",
+            self.indirect_mode,
+            self.capabilities.compute,
+            self.capabilities.bindless_textures,
+            self.capabilities.max_texture_size
+        );
+        self.gpu = Some(selection);
+    }
+
+    async fn finish_initialize(&mut self, device: Device, queue: Queue, surface: Option<Surface<'static>>, config: SurfaceConfiguration) {
+        let camera_params = UniformBuffer::new(&device, &self.resources, "camera params", 1);
+        let camera_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("camera params"),
+            entries: &[camera_params.layout_entry(0, wgpu::ShaderStages::VERTEX)],
+        });
+        let atmosphere = AtmosphereRenderer::new(&device, &self.resources, config.format, Some(DEPTH_FORMAT));
+        // shader.wgsl calls `apply_fog`, so it compiles after the fog helpers
+        let fog_lines = FOG_WGSL.matches('\n').count() as u32 + 1;
+        let shader = compile_shader(&device, "shader.wgsl", &format!("{}\n{}", FOG_WGSL, include_str!("shader.wgsl")))
+            .map_err(|diagnostics| diagnostics.into_iter().map(|d| d.skip_prelude("fog.wgsl", fog_lines)).collect());
+        let pipelines = match self.shader_errors.track("shader.wgsl", shader) {
+            Some(shader) => self.create_main_pipeline(&device, &shader, config.format, &[&camera_layout, atmosphere.layout()]).await,
+            None => {
+                self.fall_back_to_clear("main shader failed to compile");
+                None
+            }
+        };
+
+        self.scene.initialize_buffer(&device, &self.resources);
+        self.scene_target = Some(self.create_scene_target(&device, &config));
+        self.depth = Some(self.create_depth(&device, (config.width, config.height)));
+        self.screen_effects = Some(ScreenEffectChain::new(&device, &self.resources, config.format, config.width, config.height));
+        self.ui = Some(UiPass::new(&device, &queue, &self.resources, &mut self.samplers, &self.capabilities, config.format));
+        self.debug_view = Some(DebugViewPass::new(&device, &self.resources, &self.capabilities, config.format, config.width, config.height, 16));
+
+        self.device = Some(device);
+        self.queue = Some(queue);
+        self.surface = surface;
+        self.config = Some(config);
+        self.render_pipeline = pipelines;
+        self.camera_params = Some((camera_params, camera_layout));
+        self.atmosphere = Some(atmosphere);
+    }
+
+    fn fall_back_to_clear(&mut self, reason: &str) {
+        log::error!("Main pipeline failed to build, falling back to clear-only rendering: {}", reason);
+        if let Some(gpu) = &mut self.gpu {
+            gpu.failures.push(format!("{}: {}", gpu.tier, reason));
+            gpu.tier = GpuTier::ClearOnly;
+        }
+    }
+
+    async fn create_main_pipeline(
+        &mut self,
+        device: &Device,
+        shader: &wgpu::ShaderModule,
+        format: wgpu::TextureFormat,
+        bind_group_layouts: &[&wgpu::BindGroupLayout],
+    ) -> Option<RenderPipeline> {
+        // Broken drivers sometimes reject valid shaders; catch that instead of panicking
+        device.push_error_scope(wgpu::ErrorFilter::Validation);
         let render_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
-            label: None,
-            bind_group_layouts: &[],
+            label: Some("main"),
+            bind_group_layouts,
             push_constant_ranges: &[],
         });
 
-        let vertex_buffer_layout = wgpu::VertexBufferLayout {
-            array_stride: std::mem::size_of::<crate::scene::Vertex>() as wgpu::BufferAddress,
-            step_mode: wgpu::VertexStepMode::Vertex,
-            attributes: &[
-                wgpu::VertexAttribute {
-                    format: wgpu::VertexFormat::Float32x2,
-                    offset: 0,
-                    shader_location: 0,
-                },
-            ],
-        };
-
         let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-            label: None,
+            label: Some("main"),
             layout: Some(&render_pipeline_layout),
             vertex: wgpu::VertexState {
-                module: &shader,
+                module: shader,
                 // FIXED: entry_point now expects Option<&str>
                 entry_point: Some("vs_main"),
-                buffers: &[vertex_buffer_layout],
+                buffers: &[crate::scene::SceneVertex::layout()],
                 compilation_options: Default::default(),
             },
             fragment: Some(wgpu::FragmentState {
-                module: &shader,
+                module: shader,
                 // FIXED: entry_point now expects Option<&str>
                 entry_point: Some("fs_main"),
                 targets: &[Some(wgpu::ColorTargetState {
-                    format: surface_format,
+                    format,
                     blend: None,
                     write_mask: wgpu::ColorWrites::ALL,
                 })],
                 compilation_options: Default::default(),
             }),
             primitive: wgpu::PrimitiveState::default(),
-            depth_stencil: None,
+            // Equal depths pass, so flat 2D entities still layer in draw order
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: DEPTH_FORMAT,
+                depth_write_enabled: true,
+                depth_compare: wgpu::CompareFunction::LessEqual,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
             multisample: wgpu::MultisampleState::default(),
             multiview: None,
             // FIXED: Added missing cache field
             cache: None,
         });
 
-        self.scene.initialize_buffer(&device);
+        match device.pop_error_scope().await {
+            None => Some(render_pipeline),
+            Some(e) => {
+                self.shader_errors.report("shader.wgsl", vec![ShaderDiagnostic::from_error("shader.wgsl", &e)]);
+                self.fall_back_to_clear(&e.to_string());
+                None
+            }
+        }
+    }
+
+    pub fn enable_particles(&mut self, capacity: u32) -> Result<(), String> {
+        let (Some(device), Some(config)) = (&self.device, &self.config) else {
+            return Err("Renderer is not initialized".to_string());
+        };
+        if !self.capabilities.compute_particles() {
+            return Err("GPU particles need compute shaders, vertex storage buffers and indirect draws, which this device doesn't support".to_string());
+        }
+        self.particles = Some(GpuParticles::new(device, &self.resources, config.format, capacity)?);
+        Ok(())
+    }
 
-        self.device = Some(device);
-        self.queue = Some(queue);
-        self.surface = Some(surface);
-        self.config = Some(config);
-        self.render_pipeline = Some(render_pipeline);
+    // Tests the first `capacity` scene entities' bounding boxes against the main pass's depth
+    // each frame, after the entities last seen visible have drawn. Entities whose box covered
+    // no pixels are skipped until a later result, a frame or two behind, shows them again.
+    pub fn enable_occlusion(&mut self, capacity: u32) -> Result<(), String> {
+        let (Some(device), Some(config)) = (&self.device, &self.config) else {
+            return Err("Renderer is not initialized".to_string());
+        };
+        self.occlusion = Some(OcclusionQueries::new(device, &self.resources, config.format, DEPTH_FORMAT, capacity)?);
         Ok(())
     }
 
-    pub fn render(&mut self) {
-        let Some(surface) = &self.surface else { return };
+    // Renders the scene at `scale` of the output and upscales it with `filter`, e.g. 0.5 on
+    // weak GPUs or a fixed size for pixel art; `None` goes back to full size. Particles and
+    // UI always draw at full size.
+    pub fn set_render_scale(&mut self, scale: Option<RenderScale>, filter: UpscaleFilter) -> Result<(), String> {
+        let (Some(device), Some(config)) = (&self.device, &self.config) else {
+            return Err("Renderer is not initialized".to_string());
+        };
+        match (scale, &mut self.resolution) {
+            (None, _) => self.resolution = None,
+            (Some(scale), Some(resolution)) => resolution.set_scale(device, &self.resources, scale),
+            (Some(scale), None) => {
+                let (format, width, height) = (config.format, config.width, config.height);
+                self.resolution = Some(ResolutionScaler::new(device, &self.resources, format, format, width, height, scale));
+            }
+        }
+        if let Some(resolution) = &mut self.resolution {
+            resolution.filter = filter;
+        }
+        self.resize_render_targets();
+        Ok(())
+    }
+
+    pub fn render_scale(&self) -> Option<RenderScale> {
+        self.resolution.as_ref().map(ResolutionScaler::scale)
+    }
+
+    // Size the scene renders at, to build projections from; the output size unless a render
+    // scale is set
+    pub fn render_size(&self) -> (u32, u32) {
+        match (&self.resolution, &self.config) {
+            (Some(resolution), _) => resolution.internal_size(),
+            (None, Some(config)) => (config.width, config.height),
+            (None, None) => (1, 1),
+        }
+    }
+
+    // Depth and debug view follow `render_size`
+    fn resize_render_targets(&mut self) {
+        let size = self.render_size();
+        let Some(device) = &self.device else { return };
+        if self.depth.as_ref().is_none_or(|depth| (depth.width(), depth.height()) != size) {
+            self.depth = Some(self.create_depth(device, size));
+        }
+        if let Some(debug_view) = &mut self.debug_view {
+            debug_view.resize(device, &self.resources, size.0, size.1);
+        }
+    }
+
+    pub fn render(&mut self, delta_time: f64) {
         let Some(device) = &self.device else { return };
         let Some(queue) = &self.queue else { return };
         let Some(config) = &self.config else { return };
-        let Some(render_pipeline) = &self.render_pipeline else { return };
-        let Some(vertex_buffer) = self.scene.vertex_buffer() else { return };
-
-        let output = match surface.get_current_texture() {
-            Ok(output) => output,
-            Err(wgpu::SurfaceError::Lost) => {
-                surface.configure(device, config);
-                return;
-            }
-            Err(e) => {
-                log::error!("Surface error: {}", e);
-                return;
-            }
-        };
+        // Blocks only when the GPU is `FRAMES_IN_FLIGHT` frames behind
+        self.frames.begin(device, &mut self.upload_belt);
 
-        let view = output.texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let output = match &self.surface {
+            Some(surface) => match surface.get_current_texture() {
+                Ok(output) => Some(output),
+                Err(wgpu::SurfaceError::Lost) => {
+                    surface.configure(device, config);
+                    return;
+                }
+                Err(e) => {
+                    log::error!("Surface error: {}", e);
+                    return;
+                }
+            },
+            None => None,
+        };
+        let output_view = match (&output, &self.offscreen) {
+            (Some(output), _) => output.texture.create_view(&wgpu::TextureViewDescriptor::default()),
+            (None, Some(offscreen)) => offscreen.create_view(&wgpu::TextureViewDescriptor::default()),
+            (None, None) => return,
+        };
+        let Some(view) = self.scene_target.as_ref().map(|target| target.create_view(&wgpu::TextureViewDescriptor::default())) else {
+            return;
+        };
+        self.capture.begin_frame(device);
+        // Command errors surface at `finish`, so this scope runs until then; the inner one
+        // catches device errors from the uploads themselves
+        self.validation.push(device);
         let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
-            label: None,
+            label: Some("frame"),
         });
+        if let Some(environment) = &mut self.environment {
+            environment.update(delta_time as f32);
+            environment.apply(&mut self.scene.atmosphere, self.particles.as_mut());
+        }
+        self.validation.push(device);
+        self.scene.upload(device, &self.resources, &mut encoder, &mut self.upload_belt);
+        if let Some((camera_params, _)) = &mut self.camera_params {
+            let params = CameraParams { view_proj: self.camera.view_proj().to_cols_array() };
+            camera_params.set(device, &mut encoder, &mut self.upload_belt, &params);
+        }
+        if let Some(atmosphere) = &mut self.atmosphere {
+            let (view_proj, position) = (self.camera.view_proj(), self.camera.position());
+            atmosphere.update(device, &mut encoder, &mut self.upload_belt, &self.scene.atmosphere, position, view_proj);
+        }
+        if let Some(particles) = &mut self.particles {
+            particles.update(device, &mut encoder, &mut self.upload_belt, delta_time as f32);
+        }
+        if let Some(ui) = &mut self.ui {
+            ui.prepare(device, &self.resources, &mut encoder, &mut self.upload_belt);
+        }
+        if let Some(occlusion) = &mut self.occlusion {
+            let bounds: Vec<_> = self.scene.entity_bounds().collect();
+            occlusion.prepare(device, &self.resources, &mut encoder, &mut self.upload_belt, self.camera.view_proj(), &bounds);
+        }
+        self.validation.pop(device, "frame uploads");
 
-        {
+        // The scene goes straight into `view` at full size, or through the internal target
+        let scene_view = self.resolution.as_ref().map_or(&view, ResolutionScaler::view);
+        if let Some(debug_view) = self.debug_view.as_mut().filter(|debug_view| debug_view.is_active()) {
+            let mesh = self.scene.upload_debug_mesh(device, &self.resources, &mut encoder, &mut self.upload_belt);
+            let draws = [DebugDraw { mesh: &mesh, transform: Mat4::IDENTITY }];
+            let view_proj = self.camera.view_proj();
+            self.draw_calls = debug_view.render(device, &mut encoder, &mut self.upload_belt, &mut self.bind_groups, scene_view, view_proj, &draws);
+        } else {
+            let depth_view = self.depth.as_ref().map(|depth| depth.create_view(&wgpu::TextureViewDescriptor::default()));
             let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                label: None,
+                label: Some("main"),
                 color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                    view: &view,
+                    view: scene_view,
                     resolve_target: None,
                     ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                        load: self.clear.load_op(&self.scene.atmosphere),
                         store: wgpu::StoreOp::Store,
                     },
                     // FIXED: Added missing depth_slice field
                     depth_slice: None,
                 })],
-                depth_stencil_attachment: None,
+                depth_stencil_attachment: depth_view.as_ref().map(|view| wgpu::RenderPassDepthStencilAttachment {
+                    view,
+                    depth_ops: Some(wgpu::Operations { load: wgpu::LoadOp::Clear(1.0), store: wgpu::StoreOp::Discard }),
+                    stencil_ops: None,
+                }),
                 timestamp_writes: None,
-                occlusion_query_set: None,
+                occlusion_query_set: self.occlusion.as_ref().map(OcclusionQueries::query_set),
             });
-            render_pass.set_pipeline(render_pipeline);
-            render_pass.set_vertex_buffer(0, vertex_buffer.slice(..));
-            render_pass.draw(0..self.scene.vertex_count(), 0..1);
+            self.draw_calls = 0;
+            if let Some(atmosphere) = &self.atmosphere {
+                self.clear.draw_sky(&mut render_pass, atmosphere);
+            }
+            // Clear-only tier: the pass still clears so the window isn't left with garbage
+            if let (Some(render_pipeline), Some(vertex_buffer), Some((camera_params, camera_layout)), Some(atmosphere)) =
+                (&self.render_pipeline, self.scene.vertex_buffer(), &self.camera_params, &self.atmosphere)
+            {
+                render_pass.set_bind_group(0, &camera_params.bind_group(device, &mut self.bind_groups, camera_layout), &[]);
+                render_pass.set_bind_group(FOG_GROUP, atmosphere.bind_group(), &[]);
+                render_pass.set_vertex_buffer(0, vertex_buffer.slice(..));
+                render_pass.set_pipeline(render_pipeline);
+                match &self.occlusion {
+                    // The entities seen last time are the occluders; every box is then tested
+                    // against them, including those of entities skipped here
+                    Some(occlusion) => {
+                        for (index, range) in self.scene.entity_ranges().enumerate() {
+                            if occlusion.is_visible(index) {
+                                render_pass.draw(range, 0..1);
+                                self.draw_calls += 1;
+                            }
+                        }
+                        occlusion.draw_queries(&mut render_pass);
+                    }
+                    None => {
+                        render_pass.draw(0..self.scene.vertex_count(), 0..1);
+                        self.draw_calls = 1;
+                    }
+                }
+            }
+            drop(render_pass);
+            if let Some(occlusion) = &mut self.occlusion {
+                occlusion.resolve(&mut encoder);
+            }
         }
 
-        queue.submit(std::iter::once(encoder.finish()));
-        output.present();
+        if let Some(resolution) = &self.resolution {
+            resolution.upscale(device, &mut encoder, &mut self.upload_belt, &mut self.bind_groups, &mut self.samplers, &view);
+        }
+
+        // Over the main pass or the debug view alike, so the HUD stays up under F3, and before
+        // `screen_effects` so filters and transitions cover the UI too
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("overlay"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: &view,
+                resolve_target: None,
+                ops: wgpu::Operations { load: wgpu::LoadOp::Load, store: wgpu::StoreOp::Store },
+                depth_slice: None,
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        if let Some(particles) = self.particles.as_ref().filter(|_| self.render_pipeline.is_some()) {
+            particles.draw(&mut render_pass);
+            self.draw_calls += 1;
+        }
+        // Over everything, and on the clear-only tier too
+        if let Some(ui) = &self.ui {
+            self.draw_calls += ui.draw_calls();
+            ui.draw(device, &mut self.bind_groups, &mut render_pass);
+        }
+        drop(render_pass);
+        if let Some(screen_effects) = &mut self.screen_effects {
+            screen_effects.render(
+                device,
+                &mut encoder,
+                &mut self.upload_belt,
+                &mut self.bind_groups,
+                &mut self.samplers,
+                delta_time as f32,
+                &view,
+                &output_view,
+            );
+        }
+
+        let target = output.as_ref().map(|output| &output.texture).or(self.offscreen.as_deref());
+        if let Some(target) = target.filter(|_| std::mem::take(&mut self.screenshot_requested)) {
+            let size = (target.width(), target.height());
+            match self.readbacks.read_texture(device, &self.resources, &mut encoder, target, (0, 0), size) {
+                Ok(id) => self.screenshot = Some(id),
+                Err(e) => log::warn!("Screenshot failed: {}", e),
+            }
+        }
+
+        self.upload_belt.finish();
+        let commands = encoder.finish();
+        self.validation.pop(device, "frame commands");
+        self.validation.push(device);
+        let submission = queue.submit(std::iter::once(commands));
+        self.validation.pop(device, "frame submit");
+        self.frames.end(submission);
+        self.upload_belt.recall();
+        // Lets finished staging chunks map again for reuse
+        if let Err(e) = device.poll(wgpu::PollType::Poll) {
+            log::warn!("Device poll failed: {}", e);
+        }
+        self.readbacks.after_submit();
+        if let Some(occlusion) = &mut self.occlusion {
+            occlusion.after_submit();
+        }
+        self.readbacks.collect();
+        if let Some(output) = output {
+            output.present();
+        }
+        self.capture.end_frame(device);
+    }
+
+    // Queues `ui`'s widgets for the next `render`, laid out in logical pixels divided by
+    // `ui_scale`
+    pub fn queue_ui(&mut self, ui: &Ui) {
+        let scale = self.ui_scale_factor();
+        let size = self.config.as_ref().map_or(Vec2::ONE, |config| Vec2::new(config.width as f32, config.height as f32));
+        if let Some(pass) = &mut self.ui {
+            pass.queue(ui, size / scale.max(f32::EPSILON));
+        }
+    }
+
+    // Captures the next frame in RenderDoc (or Xcode) when the game runs under it
+    pub fn request_frame_capture(&mut self) {
+        self.capture.request();
+    }
+
+    // Copies the next rendered frame; poll `take_screenshot` for it over the following frames
+    pub fn request_screenshot(&mut self) {
+        self.screenshot_requested = true;
     }
 
+    // The last requested screenshot once it has arrived, as RGBA8
+    pub fn take_screenshot(&mut self) -> Option<Result<Image, String>> {
+        let result = self.readbacks.take(self.screenshot?)?;
+        self.screenshot = None;
+        Some(match result {
+            Ok(ReadbackData::Image(image)) => image.to_image().ok_or_else(|| format!("Can't convert {:?} screenshots", image.format)),
+            Ok(ReadbackData::Buffer(_)) => Err("Screenshot readback returned a buffer".to_string()),
+            Err(e) => Err(e),
+        })
+    }
+
+    pub fn resource_stats(&self) -> ResourceStats {
+        self.resources.stats()
+    }
+
+    // Logical pixels to physical ones for UI and text: the OS scale factor times `ui_scale`
+    pub fn ui_scale_factor(&self) -> f32 {
+        self.display.scale_factor as f32 * self.ui_scale
+    }
+
+    // Moving to a monitor with a different DPI; winit follows up with a resize
+    pub fn set_scale_factor(&mut self, scale_factor: f64) {
+        self.display = DisplayMetrics::new(self.display.physical_width, self.display.physical_height, scale_factor);
+    }
+
+    // `width` and `height` in physical pixels
     pub fn resize(&mut self, width: u32, height: u32) {
+        self.display = DisplayMetrics::new(width.max(1), height.max(1), self.display.scale_factor);
+        let (width, height) = self.capabilities.clamp_extent(width, height);
         if let (Some(surface), Some(device), Some(config)) = (&self.surface, &self.device, &mut self.config) {
-            config.width = width.max(1);
-            config.height = height.max(1);
+            config.width = width;
+            config.height = height;
             surface.configure(device, config);
+        } else if let (Some(device), Some(config), Some(_)) = (&self.device, &mut self.config, &self.offscreen) {
+            config.width = width;
+            config.height = height;
+            let config = config.clone();
+            self.offscreen = Some(self.create_offscreen(device, &config));
         }
+        if let (Some(device), Some(config)) = (&self.device, &self.config) {
+            self.scene_target = Some(self.create_scene_target(device, config));
+            if let Some(screen_effects) = &mut self.screen_effects {
+                screen_effects.resize(device, &self.resources, config.width, config.height);
+            }
+            if let Some(resolution) = &mut self.resolution {
+                resolution.resize(device, &self.resources, config.width, config.height);
+            }
+        }
+        self.resize_render_targets();
     }
-}
\ No newline at end of file
+}
+
+impl Default for Renderer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,288 @@
+// src/resolution.rs
+use crate::bind_cache::{BindGroupCache, BindingKey, SamplerCache};
+use crate::clip::ClipRect;
+use crate::resource_registry::{ResourceRegistry, Tracked};
+use crate::upload::UploadBelt;
+
+// Mirrors `UpscaleParams` in upscale.wgsl
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct UpscaleParams {
+    input_size: [f32; 2],
+    filter_mode: u32,
+    sharpness: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UpscaleFilter {
+    // Hard pixel edges for pixel art
+    Nearest,
+    Linear,
+    // Linear plus contrast-adaptive sharpening; `sharpness` in 0..1. Meant for LDR input.
+    Sharpened { sharpness: f32 },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RenderScale {
+    // Fraction of the output size per axis, e.g. 0.5 renders a quarter of the pixels
+    Factor(f32),
+    // Fixed game resolution, whatever the window size
+    Fixed(u32, u32),
+}
+
+impl RenderScale {
+    pub fn internal_size(&self, output_width: u32, output_height: u32) -> (u32, u32) {
+        match *self {
+            RenderScale::Factor(factor) => {
+                let factor = factor.clamp(0.1, 4.0);
+                (
+                    ((output_width as f32 * factor).round() as u32).max(1),
+                    ((output_height as f32 * factor).round() as u32).max(1),
+                )
+            }
+            RenderScale::Fixed(width, height) => (width.max(1), height.max(1)),
+        }
+    }
+}
+
+// An internal render target decoupled from the window size. Render the scene into
+// `view`, then `upscale` it to the swapchain. Keeps the target as long as the internal
+// size doesn't change, so window resizes under `Fixed` cost nothing.
+pub struct ResolutionScaler {
+    pub filter: UpscaleFilter,
+    scale: RenderScale,
+    format: wgpu::TextureFormat,
+    output_size: (u32, u32),
+    internal_size: (u32, u32),
+    _target: Tracked<wgpu::Texture>,
+    view: wgpu::TextureView,
+    params_buffer: Tracked<wgpu::Buffer>,
+    layout: wgpu::BindGroupLayout,
+    pipeline: wgpu::RenderPipeline,
+}
+
+impl ResolutionScaler {
+    // `format` is the internal target's; `output_format` the texture `upscale` draws into
+    pub fn new(
+        device: &wgpu::Device,
+        resources: &ResourceRegistry,
+        format: wgpu::TextureFormat,
+        output_format: wgpu::TextureFormat,
+        output_width: u32,
+        output_height: u32,
+        scale: RenderScale,
+    ) -> Self {
+        let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("upscale"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+        });
+        let shader = device.create_shader_module(wgpu::include_wgsl!("upscale.wgsl"));
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("upscale"),
+            bind_group_layouts: &[&layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("upscale"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: Some("vs_fullscreen"),
+                buffers: &[],
+                compilation_options: Default::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: Some("fs_upscale"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: output_format,
+                    blend: None,
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: Default::default(),
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        });
+        let params_buffer = resources.create_buffer(device, &wgpu::BufferDescriptor {
+            label: Some("upscale params"),
+            size: std::mem::size_of::<UpscaleParams>() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        }, "resolution");
+
+        let output_size = (output_width.max(1), output_height.max(1));
+        let internal_size = scale.internal_size(output_size.0, output_size.1);
+        let (target, view) = Self::create_target(device, resources, format, internal_size);
+        Self {
+            filter: UpscaleFilter::Linear,
+            scale,
+            format,
+            output_size,
+            internal_size,
+            _target: target,
+            view,
+            params_buffer,
+            layout,
+            pipeline,
+        }
+    }
+
+    fn create_target(
+        device: &wgpu::Device,
+        resources: &ResourceRegistry,
+        format: wgpu::TextureFormat,
+        size: (u32, u32),
+    ) -> (Tracked<wgpu::Texture>, wgpu::TextureView) {
+        let texture = resources.create_texture(device, &wgpu::TextureDescriptor {
+            label: Some("internal resolution"),
+            size: wgpu::Extent3d { width: size.0, height: size.1, depth_or_array_layers: 1 },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        }, "resolution");
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        (texture, view)
+    }
+
+    fn recreate(&mut self, device: &wgpu::Device, resources: &ResourceRegistry) {
+        let internal_size = self.scale.internal_size(self.output_size.0, self.output_size.1);
+        if internal_size == self.internal_size {
+            return;
+        }
+        self.internal_size = internal_size;
+        (self._target, self.view) = Self::create_target(device, resources, self.format, internal_size);
+    }
+
+    // Call on window resize
+    pub fn resize(&mut self, device: &wgpu::Device, resources: &ResourceRegistry, output_width: u32, output_height: u32) {
+        self.output_size = (output_width.max(1), output_height.max(1));
+        self.recreate(device, resources);
+    }
+
+    // E.g. from a graphics menu or dynamic resolution; the target is recreated if needed
+    pub fn set_scale(&mut self, device: &wgpu::Device, resources: &ResourceRegistry, scale: RenderScale) {
+        self.scale = scale;
+        self.recreate(device, resources);
+    }
+
+    pub fn scale(&self) -> RenderScale {
+        self.scale
+    }
+
+    // Size to render the scene at, and to build its projection and viewport from
+    pub fn internal_size(&self) -> (u32, u32) {
+        self.internal_size
+    }
+
+    pub fn view(&self) -> &wgpu::TextureView {
+        &self.view
+    }
+
+    // Stretches the internal target over all of `output`
+    pub fn upscale(
+        &self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        belt: &mut UploadBelt,
+        bind_groups: &mut BindGroupCache,
+        samplers: &mut SamplerCache,
+        output: &wgpu::TextureView,
+    ) {
+        self.upscale_into(device, encoder, belt, bind_groups, samplers, output, None);
+    }
+
+    // Draws into `viewport` of `output` only and clears the rest to black, for letterboxing
+    #[allow(clippy::too_many_arguments)]
+    pub fn upscale_into(
+        &self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        belt: &mut UploadBelt,
+        bind_groups: &mut BindGroupCache,
+        samplers: &mut SamplerCache,
+        output: &wgpu::TextureView,
+        viewport: Option<ClipRect>,
+    ) {
+        let (filter_mode, sharpness) = match self.filter {
+            UpscaleFilter::Nearest => (0, 0.0),
+            UpscaleFilter::Linear => (1, 0.0),
+            UpscaleFilter::Sharpened { sharpness } => (2, sharpness.clamp(0.0, 1.0)),
+        };
+        let params = UpscaleParams {
+            input_size: [self.internal_size.0 as f32, self.internal_size.1 as f32],
+            filter_mode,
+            sharpness,
+        };
+        belt.write(device, encoder, &self.params_buffer, 0, bytemuck::bytes_of(&params));
+
+        let sampler = samplers.get(device, &wgpu::SamplerDescriptor {
+            label: Some("upscale"),
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+        let bind_group = bind_groups.get_or_create(device, "upscale", &self.layout, &[
+            (0, BindingKey::buffer(&self.params_buffer)),
+            (1, BindingKey::TextureView(self.view.clone())),
+            (2, BindingKey::Sampler(sampler)),
+        ]);
+        let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("upscale"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: output,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                    store: wgpu::StoreOp::Store,
+                },
+                depth_slice: None,
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        if let Some(viewport) = viewport {
+            if viewport.is_empty() {
+                return;
+            }
+            pass.set_viewport(viewport.x as f32, viewport.y as f32, viewport.width as f32, viewport.height as f32, 0.0, 1.0);
+        }
+        pass.set_pipeline(&self.pipeline);
+        pass.set_bind_group(0, &bind_group, &[]);
+        pass.draw(0..3, 0..1);
+    }
+}
@@ -0,0 +1,210 @@
+// src/resource_registry.rs
+use std::collections::HashMap;
+use std::fmt;
+use std::ops::Deref;
+use std::sync::{Arc, Mutex};
+use wgpu::util::DeviceExt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum ResourceCategory {
+    VertexBuffer,
+    IndexBuffer,
+    UniformBuffer,
+    StorageBuffer,
+    StagingBuffer,
+    Texture,
+    RenderTarget,
+    Other,
+}
+
+impl ResourceCategory {
+    pub fn from_buffer_usage(usage: wgpu::BufferUsages) -> Self {
+        if usage.contains(wgpu::BufferUsages::VERTEX) {
+            Self::VertexBuffer
+        } else if usage.contains(wgpu::BufferUsages::INDEX) {
+            Self::IndexBuffer
+        } else if usage.contains(wgpu::BufferUsages::UNIFORM) {
+            Self::UniformBuffer
+        } else if usage.contains(wgpu::BufferUsages::STORAGE) {
+            Self::StorageBuffer
+        } else if usage.intersects(wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::MAP_WRITE) {
+            Self::StagingBuffer
+        } else {
+            Self::Other
+        }
+    }
+
+    pub fn from_texture_usage(usage: wgpu::TextureUsages) -> Self {
+        if usage.contains(wgpu::TextureUsages::RENDER_ATTACHMENT) {
+            Self::RenderTarget
+        } else {
+            Self::Texture
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ResourceRecord {
+    pub label: String,
+    pub owner: &'static str,
+    pub category: ResourceCategory,
+    pub size: u64,
+}
+
+#[derive(Default)]
+struct RegistryState {
+    live: HashMap<u64, ResourceRecord>,
+    next_id: u64,
+    live_bytes: u64,
+    peak_bytes: u64,
+    created_this_frame: u32,
+    created_total: u64,
+}
+
+// Records every buffer/texture created through it. Cloning shares the same registry.
+#[derive(Clone, Default)]
+pub struct ResourceRegistry {
+    state: Arc<Mutex<RegistryState>>,
+}
+
+// A GPU resource whose record is removed from the registry when it's dropped
+pub struct Tracked<T> {
+    resource: T,
+    id: u64,
+    state: Arc<Mutex<RegistryState>>,
+}
+
+impl<T> Deref for Tracked<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.resource
+    }
+}
+
+impl<T> Drop for Tracked<T> {
+    fn drop(&mut self) {
+        if let Ok(mut state) = self.state.lock() {
+            if let Some(record) = state.live.remove(&self.id) {
+                state.live_bytes -= record.size;
+            }
+        }
+    }
+}
+
+impl ResourceRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn track<T>(&self, resource: T, label: &str, owner: &'static str, category: ResourceCategory, size: u64) -> Tracked<T> {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let id = state.next_id;
+        state.next_id += 1;
+        state.live.insert(id, ResourceRecord { label: label.to_string(), owner, category, size });
+        state.live_bytes += size;
+        state.peak_bytes = state.peak_bytes.max(state.live_bytes);
+        state.created_this_frame += 1;
+        state.created_total += 1;
+        Tracked { resource, id, state: self.state.clone() }
+    }
+
+    pub fn create_buffer(&self, device: &wgpu::Device, desc: &wgpu::BufferDescriptor, owner: &'static str) -> Tracked<wgpu::Buffer> {
+        let buffer = device.create_buffer(desc);
+        let category = ResourceCategory::from_buffer_usage(desc.usage);
+        self.track(buffer, desc.label.unwrap_or("unlabelled"), owner, category, desc.size)
+    }
+
+    pub fn create_buffer_init(
+        &self,
+        device: &wgpu::Device,
+        desc: &wgpu::util::BufferInitDescriptor,
+        owner: &'static str,
+    ) -> Tracked<wgpu::Buffer> {
+        let buffer = device.create_buffer_init(desc);
+        let category = ResourceCategory::from_buffer_usage(desc.usage);
+        let size = buffer.size();
+        self.track(buffer, desc.label.unwrap_or("unlabelled"), owner, category, size)
+    }
+
+    pub fn create_texture(&self, device: &wgpu::Device, desc: &wgpu::TextureDescriptor, owner: &'static str) -> Tracked<wgpu::Texture> {
+        let texture = device.create_texture(desc);
+        let category = ResourceCategory::from_texture_usage(desc.usage);
+        let size = crate::texture::byte_size(&texture);
+        self.track(texture, desc.label.unwrap_or("unlabelled"), owner, category, size)
+    }
+
+    // Resets the per-frame creation counter; call once at the start of each frame
+    pub fn begin_frame(&self) {
+        self.state.lock().unwrap_or_else(|e| e.into_inner()).created_this_frame = 0;
+    }
+
+    pub fn stats(&self) -> ResourceStats {
+        let state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let mut by_category: HashMap<ResourceCategory, (u32, u64)> = HashMap::new();
+        let mut by_owner: HashMap<&'static str, (u32, u64)> = HashMap::new();
+        for record in state.live.values() {
+            let entry = by_category.entry(record.category).or_default();
+            entry.0 += 1;
+            entry.1 += record.size;
+            let entry = by_owner.entry(record.owner).or_default();
+            entry.0 += 1;
+            entry.1 += record.size;
+        }
+        let mut by_category: Vec<_> = by_category.into_iter().map(|(c, (n, b))| (c, n, b)).collect();
+        by_category.sort_by_key(|&(c, _, _)| c);
+        let mut by_owner: Vec<_> = by_owner.into_iter().map(|(o, (n, b))| (o, n, b)).collect();
+        by_owner.sort_by_key(|&(_, _, b)| std::cmp::Reverse(b));
+        ResourceStats {
+            live_count: state.live.len() as u32,
+            live_bytes: state.live_bytes,
+            peak_bytes: state.peak_bytes,
+            created_this_frame: state.created_this_frame,
+            created_total: state.created_total,
+            by_category,
+            by_owner,
+        }
+    }
+
+    pub fn records(&self) -> Vec<ResourceRecord> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner()).live.values().cloned().collect()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ResourceStats {
+    pub live_count: u32,
+    pub live_bytes: u64,
+    pub peak_bytes: u64,
+    // Non-zero every frame means something is recreating resources per frame
+    pub created_this_frame: u32,
+    pub created_total: u64,
+    // (category, count, bytes)
+    pub by_category: Vec<(ResourceCategory, u32, u64)>,
+    // (owner, count, bytes), largest first
+    pub by_owner: Vec<(&'static str, u32, u64)>,
+}
+
+fn kib(bytes: u64) -> f64 {
+    bytes as f64 / 1024.0
+}
+
+impl fmt::Display for ResourceStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "GPU resources: {} live, {:.1} KiB (peak {:.1} KiB), {} created this frame",
+            self.live_count,
+            kib(self.live_bytes),
+            kib(self.peak_bytes),
+            self.created_this_frame
+        )?;
+        for (category, count, bytes) in &self.by_category {
+            writeln!(f, "  {:?}: {} ({:.1} KiB)", category, count, kib(*bytes))?;
+        }
+        for (owner, count, bytes) in &self.by_owner {
+            writeln!(f, "  [{}] {} ({:.1} KiB)", owner, count, kib(*bytes))?;
+        }
+        Ok(())
+    }
+}
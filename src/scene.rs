@@ -1,68 +1,253 @@
 // src/scene.rs
-use wgpu::util::DeviceExt;
+use crate::atmosphere::Atmosphere;
+use crate::math::{Color, Vec2, Vec3};
+use crate::mesh::{GpuMesh, Mesh, MeshVertex};
+use crate::resource_registry::ResourceRegistry;
+use crate::upload::{DynamicBuffer, UploadBelt};
 
 #[derive(Clone, Copy)]
 pub struct Vertex {
-    position: [f32; 2],
+    position: Vec2,
+}
+
+// What the scene uploads: entity vertices in world space with their entity's tint and flash
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct SceneVertex {
+    pub position: [f32; 2],
+    pub tint: [f32; 4],
+    // rgb flash colour, a how strongly it replaces the tinted colour
+    pub flash: [f32; 4],
+}
+
+impl SceneVertex {
+    const ATTRIBUTES: [wgpu::VertexAttribute; 3] =
+        wgpu::vertex_attr_array![0 => Float32x2, 1 => Float32x4, 2 => Float32x4];
+
+    pub fn layout() -> wgpu::VertexBufferLayout<'static> {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<SceneVertex>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &Self::ATTRIBUTES,
+        }
+    }
+}
+
+// A flash that fades from full strength to nothing over `duration` seconds
+#[derive(Clone, Copy)]
+struct Flash {
+    color: Color,
+    duration: f32,
+    remaining: f32,
+}
+
+impl Flash {
+    fn strength(&self) -> f32 {
+        if self.duration > 0.0 { (self.remaining / self.duration).clamp(0.0, 1.0) } else { 0.0 }
+    }
 }
 
 #[derive(Clone)]
 pub struct Entity {
     vertices: Vec<Vertex>,
-    position: [f32; 2],
+    position: Vec2,
+    // Multiplies the entity's colour, e.g. for team colours
+    tint: Color,
+    flash: Option<Flash>,
+}
+
+// Simulation state of a scene, without any GPU resources
+#[derive(Clone)]
+pub struct SceneSnapshot {
+    entities: Vec<Entity>,
 }
 
 pub struct Scene {
     entities: Vec<Entity>,
-    vertex_buffer: Option<wgpu::Buffer>,
+    vertex_buffer: Option<DynamicBuffer>,
+    // Entities changed since the last upload
+    dirty: bool,
+    // `to_mesh` on the GPU for the debug views, and whether entities moved since its upload
+    debug_mesh: Option<(DynamicBuffer, DynamicBuffer)>,
+    debug_mesh_dirty: bool,
+    // Fog and sky; animate it for day/night cycles
+    pub atmosphere: Atmosphere,
 }
 
 impl Scene {
     pub fn new() -> Self {
         let triangle = Entity {
             vertices: vec![
-                Vertex { position: [0.0, 0.5] },
-                Vertex { position: [-0.5, -0.5] },
-                Vertex { position: [0.5, -0.5] },
+                Vertex { position: Vec2::new(0.0, 0.5) },
+                Vertex { position: Vec2::new(-0.5, -0.5) },
+                Vertex { position: Vec2::new(0.5, -0.5) },
             ],
-            position: [0.0, 0.0],
+            position: Vec2::ZERO,
+            tint: Color::WHITE,
+            flash: None,
         };
         Self {
             entities: vec![triangle],
             vertex_buffer: None,
+            dirty: true,
+            debug_mesh: None,
+            debug_mesh_dirty: true,
+            atmosphere: Atmosphere::default(),
         }
     }
 
-    pub fn initialize_buffer(&mut self, device: &wgpu::Device) {
-        let vertices: Vec<Vertex> = self.entities.iter()
+    pub fn initialize_buffer(&mut self, device: &wgpu::Device, resources: &ResourceRegistry) {
+        let capacity = (self.vertex_count() as usize * std::mem::size_of::<SceneVertex>()) as u64;
+        self.vertex_buffer = Some(DynamicBuffer::new(device, resources, "scene vertices", wgpu::BufferUsages::VERTEX, capacity));
+        self.dirty = true;
+    }
+
+    // Streams changed vertex data through the upload belt as part of `encoder`
+    pub fn upload(
+        &mut self,
+        device: &wgpu::Device,
+        resources: &ResourceRegistry,
+        encoder: &mut wgpu::CommandEncoder,
+        belt: &mut UploadBelt,
+    ) {
+        let Some(vertex_buffer) = &mut self.vertex_buffer else { return };
+        if !self.dirty {
+            return;
+        }
+        let vertices: Vec<SceneVertex> = self.entities.iter()
             .flat_map(|entity| {
-                entity.vertices.iter().map(move |v| Vertex {
-                    position: [v.position[0] + entity.position[0], v.position[1] + entity.position[1]]
+                let flash = entity.flash.map_or([0.0; 4], |f| f.color.with_alpha(f.strength()).to_array());
+                entity.vertices.iter().map(move |v| SceneVertex {
+                    position: (v.position + entity.position).into(),
+                    tint: entity.tint.to_array(),
+                    flash,
                 })
             })
             .collect();
-        
-        self.vertex_buffer = Some(device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-            label: None,
-            contents: bytemuck::cast_slice(&vertices),
-            usage: wgpu::BufferUsages::VERTEX,
-        }));
+        vertex_buffer.upload(device, resources, encoder, belt, bytemuck::cast_slice(&vertices));
+        self.dirty = false;
+    }
+
+    // `to_mesh` for the debug views, re-uploaded into the same buffers only when entities
+    // moved since the last call
+    pub fn upload_debug_mesh(
+        &mut self,
+        device: &wgpu::Device,
+        resources: &ResourceRegistry,
+        encoder: &mut wgpu::CommandEncoder,
+        belt: &mut UploadBelt,
+    ) -> GpuMesh {
+        let index_count = self.vertex_count();
+        let (vertices, indices) = self.debug_mesh.get_or_insert_with(|| {
+            self.debug_mesh_dirty = true;
+            (
+                DynamicBuffer::new(device, resources, "debug view scene vertices", wgpu::BufferUsages::VERTEX, 0),
+                DynamicBuffer::new(device, resources, "debug view scene indices", wgpu::BufferUsages::INDEX, 0),
+            )
+        });
+        if std::mem::take(&mut self.debug_mesh_dirty) {
+            let mesh = Self::entities_mesh(&self.entities);
+            vertices.upload(device, resources, encoder, belt, bytemuck::cast_slice(&mesh.vertices));
+            indices.upload(device, resources, encoder, belt, bytemuck::cast_slice(&mesh.indices));
+        }
+        GpuMesh {
+            vertex_buffer: vertices.buffer().clone(),
+            index_buffer: indices.buffer().clone(),
+            index_count,
+        }
+    }
+
+    fn mark_dirty(&mut self) {
+        self.dirty = true;
+        self.debug_mesh_dirty = true;
     }
 
     pub fn vertex_buffer(&self) -> Option<&wgpu::Buffer> {
-        self.vertex_buffer.as_ref()
+        self.vertex_buffer.as_ref().map(|b| b.buffer())
     }
 
     pub fn vertex_count(&self) -> u32 {
         self.entities.iter().map(|e| e.vertices.len() as u32).sum()
     }
 
+    // Each entity's vertices in the vertex buffer, in entity order
+    pub fn entity_ranges(&self) -> impl Iterator<Item = std::ops::Range<u32>> + '_ {
+        self.entities.iter().scan(0, |start, entity| {
+            let range = *start..*start + entity.vertices.len() as u32;
+            *start = range.end;
+            Some(range)
+        })
+    }
+
+    // Each entity's world-space bounding box, flat at z = 0, in entity order
+    pub fn entity_bounds(&self) -> impl Iterator<Item = (Vec3, Vec3)> + '_ {
+        self.entities.iter().map(|entity| {
+            let (min, max) = entity.vertices.iter().fold((Vec2::splat(f32::MAX), Vec2::splat(f32::MIN)), |(min, max), v| {
+                (min.min(v.position), max.max(v.position))
+            });
+            ((min + entity.position).extend(0.0), (max + entity.position).extend(0.0))
+        })
+    }
+
+    // The entities as one flat mesh at z = 0 facing +Z, with UVs from world position; what
+    // the debug views draw in place of the scene
+    pub fn to_mesh(&self) -> Mesh {
+        Self::entities_mesh(&self.entities)
+    }
+
+    fn entities_mesh(entities: &[Entity]) -> Mesh {
+        let vertices: Vec<MeshVertex> = entities.iter()
+            .flat_map(|entity| entity.vertices.iter().map(move |v| {
+                let position = v.position + entity.position;
+                MeshVertex { position: [position.x, position.y, 0.0], normal: [0.0, 0.0, 1.0], uv: position.into() }
+            }))
+            .collect();
+        Mesh { indices: (0..vertices.len() as u32).collect(), vertices }
+    }
+
+    // False if there is no entity at `index`
+    pub fn set_tint(&mut self, index: usize, tint: Color) -> bool {
+        let Some(entity) = self.entities.get_mut(index) else { return false };
+        entity.tint = tint;
+        self.dirty = true;
+        true
+    }
+
+    // Starts a hit flash that fades out over `duration` seconds, replacing any running one
+    pub fn flash(&mut self, index: usize, color: Color, duration: f32) -> bool {
+        let Some(entity) = self.entities.get_mut(index) else { return false };
+        entity.flash = Some(Flash { color, duration, remaining: duration });
+        self.dirty = true;
+        true
+    }
+
+    pub fn snapshot(&self) -> SceneSnapshot {
+        SceneSnapshot { entities: self.entities.clone() }
+    }
+
+    pub fn restore(&mut self, snapshot: &SceneSnapshot) {
+        self.entities = snapshot.entities.clone();
+        self.mark_dirty();
+    }
+
     pub fn update(&mut self, delta_time: f64) {
+        for entity in &mut self.entities {
+            let Some(flash) = &mut entity.flash else { continue };
+            flash.remaining -= delta_time as f32;
+            if flash.remaining <= 0.0 {
+                entity.flash = None;
+            }
+            self.dirty = true;
+        }
         if !self.entities.is_empty() {
-            self.entities[0].position[0] += (delta_time * 0.5) as f32; // Move at 0.5 units/sec
+            self.entities[0].position.x += (delta_time * 0.5) as f32; // Move at 0.5 units/sec
+            self.mark_dirty();
         }
     }
 }
 
-unsafe impl bytemuck::Pod for Vertex {}
-unsafe impl bytemuck::Zeroable for Vertex {}
\ No newline at end of file
+impl Default for Scene {
+    fn default() -> Self {
+        Self::new()
+    }
+}
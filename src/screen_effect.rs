@@ -0,0 +1,347 @@
+// src/screen_effect.rs
+use crate::bind_cache::{BindGroupCache, BindingKey, SamplerCache};
+use crate::resource_registry::{ResourceRegistry, Tracked};
+use crate::shader_errors::{compile_shader, ShaderDiagnostic, ShaderErrorLog};
+use crate::upload::UploadBelt;
+use glam::Vec4;
+
+// Prepended to every effect's source; see the file for what it declares
+pub const SCREEN_EFFECT_PRELUDE: &str = include_str!("screen_effect.wgsl");
+// Number of vec4 parameters each effect gets in `effect.params`
+pub const MAX_EFFECT_PARAMS: usize = 4;
+
+const PASSTHROUGH_WGSL: &str = "
+@fragment
+fn fs_main(in: EffectVertex) -> @location(0) vec4<f32> {
+    return sample_source(in.uv);
+}
+";
+
+// Mirrors `EffectUniforms` in screen_effect.wgsl
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct EffectUniforms {
+    resolution: [f32; 2],
+    time: f32,
+    delta_time: f32,
+    params: [[f32; 4]; MAX_EFFECT_PARAMS],
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct EffectId(u32);
+
+// One user fullscreen pass. `params` is uploaded every frame, so game code can animate
+// it directly, e.g. a damage flash fading out.
+pub struct ScreenEffect {
+    pub enabled: bool,
+    pub params: [Vec4; MAX_EFFECT_PARAMS],
+    name: String,
+    order: i32,
+    uniforms: Tracked<wgpu::Buffer>,
+    pipeline: wgpu::RenderPipeline,
+}
+
+impl ScreenEffect {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn order(&self) -> i32 {
+        self.order
+    }
+}
+
+struct Target {
+    _texture: Tracked<wgpu::Texture>,
+    view: wgpu::TextureView,
+}
+
+// An ordered list of user effects between an input and an output texture. Effects run by
+// ascending `order`, so a chain can leave gaps for effects registered later; make one
+// chain per insertion point (e.g. HDR before tonemapping, LDR after it).
+pub struct ScreenEffectChain {
+    // Compile errors of effects passed to `add`, by effect name, for the debug overlay
+    pub shader_errors: ShaderErrorLog,
+    format: wgpu::TextureFormat,
+    size: (u32, u32),
+    time: f32,
+    targets: [Target; 2],
+    layout: wgpu::BindGroupLayout,
+    pipeline_layout: wgpu::PipelineLayout,
+    effects: Vec<(EffectId, ScreenEffect)>,
+    next_id: u32,
+    // Used when no effect is enabled, so `output` is always written
+    passthrough: ScreenEffect,
+}
+
+impl ScreenEffectChain {
+    pub fn new(
+        device: &wgpu::Device,
+        resources: &ResourceRegistry,
+        format: wgpu::TextureFormat,
+        width: u32,
+        height: u32,
+    ) -> Self {
+        let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("screen effect"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+        });
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("screen effect"),
+            bind_group_layouts: &[&layout],
+            push_constant_ranges: &[],
+        });
+        let passthrough = Self::compile(device, resources, &pipeline_layout, format, "passthrough", 0, PASSTHROUGH_WGSL)
+            .expect("built-in passthrough effect should compile");
+        let size = (width.max(1), height.max(1));
+
+        Self {
+            shader_errors: ShaderErrorLog::new(),
+            format,
+            size,
+            time: 0.0,
+            targets: Self::create_targets(device, resources, format, size),
+            layout,
+            pipeline_layout,
+            effects: Vec::new(),
+            next_id: 0,
+            passthrough,
+        }
+    }
+
+    fn create_targets(
+        device: &wgpu::Device,
+        resources: &ResourceRegistry,
+        format: wgpu::TextureFormat,
+        size: (u32, u32),
+    ) -> [Target; 2] {
+        [0, 1].map(|_| {
+            let texture = resources.create_texture(device, &wgpu::TextureDescriptor {
+                label: Some("screen effect target"),
+                size: wgpu::Extent3d { width: size.0, height: size.1, depth_or_array_layers: 1 },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format,
+                usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+                view_formats: &[],
+            }, "screen_effect");
+            let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+            Target { _texture: texture, view }
+        })
+    }
+
+    // Validation errors are caught in error scopes so a broken user shader is reported, with
+    // lines counted from the start of `source`, instead of bringing down the device
+    fn compile(
+        device: &wgpu::Device,
+        resources: &ResourceRegistry,
+        pipeline_layout: &wgpu::PipelineLayout,
+        format: wgpu::TextureFormat,
+        name: &str,
+        order: i32,
+        source: &str,
+    ) -> Result<ScreenEffect, Vec<ShaderDiagnostic>> {
+        let prelude_lines = SCREEN_EFFECT_PRELUDE.matches('\n').count() as u32 + 1;
+        let shader = compile_shader(device, name, &format!("{}\n{}", SCREEN_EFFECT_PRELUDE, source)).map_err(|diagnostics| {
+            diagnostics.into_iter().map(|d| d.skip_prelude("screen_effect.wgsl", prelude_lines)).collect::<Vec<_>>()
+        })?;
+        device.push_error_scope(wgpu::ErrorFilter::Validation);
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some(name),
+            layout: Some(pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: Some("vs_fullscreen"),
+                buffers: &[],
+                compilation_options: Default::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: Some("fs_main"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format,
+                    blend: None,
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: Default::default(),
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        });
+        if let Some(error) = pollster::block_on(device.pop_error_scope()) {
+            return Err(vec![ShaderDiagnostic::from_error(name, &error)]);
+        }
+
+        let uniforms = resources.create_buffer(device, &wgpu::BufferDescriptor {
+            label: Some(name),
+            size: std::mem::size_of::<EffectUniforms>() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        }, "screen_effect");
+        Ok(ScreenEffect {
+            enabled: true,
+            params: [Vec4::ZERO; MAX_EFFECT_PARAMS],
+            name: name.to_string(),
+            order,
+            uniforms,
+            pipeline,
+        })
+    }
+
+    pub fn resize(&mut self, device: &wgpu::Device, resources: &ResourceRegistry, width: u32, height: u32) {
+        let size = (width.max(1), height.max(1));
+        if size == self.size {
+            return;
+        }
+        self.size = size;
+        self.targets = Self::create_targets(device, resources, self.format, size);
+    }
+
+    // Compiles `source` after `SCREEN_EFFECT_PRELUDE`; it must define `fs_main`. Effects
+    // with equal `order` run in the order they were added. Errors are also kept in
+    // `shader_errors` until the effect compiles.
+    pub fn add(
+        &mut self,
+        device: &wgpu::Device,
+        resources: &ResourceRegistry,
+        name: &str,
+        order: i32,
+        source: &str,
+    ) -> Result<EffectId, String> {
+        let effect = match Self::compile(device, resources, &self.pipeline_layout, self.format, name, order, source) {
+            Ok(effect) => {
+                self.shader_errors.clear(name);
+                effect
+            }
+            Err(diagnostics) => {
+                let message = diagnostics.iter().map(ToString::to_string).collect::<Vec<_>>().join("\n");
+                self.shader_errors.report(name, diagnostics);
+                return Err(format!("Screen effect '{}' failed to compile:\n{}", name, message));
+            }
+        };
+        let id = EffectId(self.next_id);
+        self.next_id += 1;
+        let index = self.effects.partition_point(|(_, existing)| existing.order <= order);
+        self.effects.insert(index, (id, effect));
+        Ok(id)
+    }
+
+    pub fn remove(&mut self, id: EffectId) -> bool {
+        let count = self.effects.len();
+        self.effects.retain(|(existing, _)| *existing != id);
+        self.effects.len() != count
+    }
+
+    pub fn get(&self, id: EffectId) -> Option<&ScreenEffect> {
+        self.effects.iter().find(|(existing, _)| *existing == id).map(|(_, effect)| effect)
+    }
+
+    pub fn get_mut(&mut self, id: EffectId) -> Option<&mut ScreenEffect> {
+        self.effects.iter_mut().find(|(existing, _)| *existing == id).map(|(_, effect)| effect)
+    }
+
+    pub fn find(&self, name: &str) -> Option<EffectId> {
+        self.effects.iter().find(|(_, effect)| effect.name == name).map(|(id, _)| *id)
+    }
+
+    // In the order they run
+    pub fn effects(&self) -> impl Iterator<Item = (EffectId, &ScreenEffect)> {
+        self.effects.iter().map(|(id, effect)| (*id, effect))
+    }
+
+    // Runs every enabled effect from `input` into `output`, which must be a different
+    // texture of the chain's format and size
+    #[allow(clippy::too_many_arguments)]
+    pub fn render(
+        &mut self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        belt: &mut UploadBelt,
+        bind_groups: &mut BindGroupCache,
+        samplers: &mut SamplerCache,
+        delta_time: f32,
+        input: &wgpu::TextureView,
+        output: &wgpu::TextureView,
+    ) {
+        self.time += delta_time;
+        let mut passes: Vec<&ScreenEffect> =
+            self.effects.iter().map(|(_, effect)| effect).filter(|effect| effect.enabled).collect();
+        if passes.is_empty() {
+            passes.push(&self.passthrough);
+        }
+
+        let sampler = samplers.get(device, &wgpu::SamplerDescriptor {
+            label: Some("screen effect"),
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+        let mut source = input;
+        for (index, effect) in passes.iter().enumerate() {
+            let uniforms = EffectUniforms {
+                resolution: [self.size.0 as f32, self.size.1 as f32],
+                time: self.time,
+                delta_time,
+                params: effect.params.map(Into::into),
+            };
+            belt.write(device, encoder, &effect.uniforms, 0, bytemuck::bytes_of(&uniforms));
+
+            let target = if index + 1 == passes.len() { output } else { &self.targets[index % 2].view };
+            let bind_group = bind_groups.get_or_create(device, "screen effect", &self.layout, &[
+                (0, BindingKey::buffer(&effect.uniforms)),
+                (1, BindingKey::TextureView(source.clone())),
+                (2, BindingKey::Sampler(sampler.clone())),
+            ]);
+            let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some(&effect.name),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: target,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                        store: wgpu::StoreOp::Store,
+                    },
+                    depth_slice: None,
+                })],
+                depth_stencil_attachment: None,
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+            pass.set_pipeline(&effect.pipeline);
+            pass.set_bind_group(0, &bind_group, &[]);
+            pass.draw(0..3, 0..1);
+            source = target;
+        }
+    }
+}
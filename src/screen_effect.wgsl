@@ -0,0 +1,34 @@
+// Screen effect prelude, prepended to every user effect. The effect defines
+// `fs_main(in: EffectVertex) -> @location(0) vec4<f32>` and reads the previous
+// pass through `sample_source`; `effect.params` holds its own values.
+
+struct EffectUniforms {
+    resolution: vec2<f32>,
+    // Seconds since the chain was created
+    time: f32,
+    delta_time: f32,
+    params: array<vec4<f32>, 4>,
+}
+
+@group(0) @binding(0) var<uniform> effect: EffectUniforms;
+@group(0) @binding(1) var source_texture: texture_2d<f32>;
+@group(0) @binding(2) var source_sampler: sampler;
+
+struct EffectVertex {
+    @builtin(position) position: vec4<f32>,
+    // (0, 0) at the top left
+    @location(0) uv: vec2<f32>,
+}
+
+@vertex
+fn vs_fullscreen(@builtin(vertex_index) index: u32) -> EffectVertex {
+    let uv = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+    var out: EffectVertex;
+    out.position = vec4<f32>(uv.x * 2.0 - 1.0, 1.0 - uv.y * 2.0, 0.0, 1.0);
+    out.uv = uv;
+    return out;
+}
+
+fn sample_source(uv: vec2<f32>) -> vec4<f32> {
+    return textureSampleLevel(source_texture, source_sampler, uv, 0.0);
+}
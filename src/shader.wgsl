@@ -1,16 +1,38 @@
+// Compiled after fog.wgsl, which binds `atmosphere` at group 1 and defines `apply_fog`
+
+struct CameraParams {
+    view_proj: mat4x4<f32>,
+}
+
+@group(0) @binding(0) var<uniform> camera: CameraParams;
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) tint: vec4<f32>,
+    @location(1) flash: vec4<f32>,
+    @location(2) world_position: vec3<f32>,
+}
+
 // Vertex shader
 @vertex
-fn vs_main(@builtin(vertex_index) in_vertex_index: u32) -> @builtin(position) vec4<f32> {
-    var pos = array<vec2<f32>, 3>(
-        vec2<f32>(0.0, 0.5),
-        vec2<f32>(-0.5, -0.5),
-        vec2<f32>(0.5, -0.5)
-    );
-    return vec4<f32>(pos[in_vertex_index], 0.0, 1.0);
+fn vs_main(
+    @location(0) position: vec2<f32>,
+    @location(1) tint: vec4<f32>,
+    @location(2) flash: vec4<f32>,
+) -> VertexOutput {
+    var out: VertexOutput;
+    out.world_position = vec3<f32>(position, 0.0);
+    out.position = camera.view_proj * vec4<f32>(out.world_position, 1.0);
+    out.tint = tint;
+    out.flash = flash;
+    return out;
 }
 
 // Fragment shader
 @fragment
-fn fs_main() -> @location(0) vec4<f32> {
-    return vec4<f32>(1.0, 0.0, 0.0, 1.0); // Red color
-}
\ No newline at end of file
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let color = vec4<f32>(1.0, 0.0, 0.0, 1.0) * in.tint; // Red color
+    // Flash strength in alpha; blends towards the flash colour for hit effects
+    let flashed = mix(color.rgb, in.flash.rgb, in.flash.a);
+    return vec4<f32>(apply_fog(flashed, in.world_position), color.a);
+}
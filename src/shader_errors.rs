@@ -0,0 +1,151 @@
+// src/shader_errors.rs
+
+// One shader compile or pipeline error. `line` and `column` are 1-based; 0 when the error
+// has no source location, e.g. a missing entry point reported at pipeline creation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShaderDiagnostic {
+    pub file: String,
+    pub line: u32,
+    pub column: u32,
+    pub message: String,
+}
+
+impl ShaderDiagnostic {
+    pub fn new(file: &str, message: impl Into<String>) -> Self {
+        Self { file: file.to_string(), line: 0, column: 0, message: message.into() }
+    }
+
+    // For errors caught in an error scope around pipeline creation
+    pub fn from_error(file: &str, error: &wgpu::Error) -> Self {
+        Self::new(file, error.to_string())
+    }
+
+    // Moves the location back by `lines`, for sources compiled after a prelude. Errors
+    // inside the prelude are attributed to `prelude` instead.
+    pub fn skip_prelude(mut self, prelude: &str, lines: u32) -> Self {
+        if self.line > lines {
+            self.line -= lines;
+        } else if self.line > 0 {
+            self.file = prelude.to_string();
+        }
+        self
+    }
+}
+
+impl std::fmt::Display for ShaderDiagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.line > 0 {
+            write!(f, "{}:{}:{}: {}", self.file, self.line, self.column, self.message)
+        } else {
+            write!(f, "{}: {}", self.file, self.message)
+        }
+    }
+}
+
+// Compiles WGSL in a validation error scope, so a broken shader comes back as diagnostics
+// instead of a device error. Pipelines built from the module can still fail; wrap them in
+// their own scope and report with `ShaderDiagnostic::from_error`.
+pub fn compile_shader(device: &wgpu::Device, file: &str, source: &str) -> Result<wgpu::ShaderModule, Vec<ShaderDiagnostic>> {
+    device.push_error_scope(wgpu::ErrorFilter::Validation);
+    let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+        label: Some(file),
+        source: wgpu::ShaderSource::Wgsl(source.into()),
+    });
+    let info = pollster::block_on(module.get_compilation_info());
+    let error = pollster::block_on(device.pop_error_scope());
+    let mut diagnostics: Vec<ShaderDiagnostic> = info
+        .messages
+        .into_iter()
+        .filter(|message| message.message_type == wgpu::CompilationMessageType::Error)
+        .map(|message| {
+            let (line, column) = message.location.map_or((0, 0), |location| (location.line_number, location.line_position));
+            ShaderDiagnostic { file: file.to_string(), line, column, message: summary(&message.message) }
+        })
+        .collect();
+    match error {
+        None if diagnostics.is_empty() => Ok(module),
+        Some(error) if diagnostics.is_empty() => {
+            diagnostics.push(ShaderDiagnostic::from_error(file, &error));
+            Err(diagnostics)
+        }
+        _ => Err(diagnostics),
+    }
+}
+
+// naga renders the source snippet under its message; the overlay only wants the cause, e.g.
+// "unknown type: `Foo`" out of "Shader 'x' parsing error: unknown type: `Foo`\n  ┌─ ..."
+fn summary(message: &str) -> String {
+    let first = message.lines().map(str::trim).find(|line| !line.is_empty()).unwrap_or(message);
+    let cause = first.split_once(" error: ").map_or(first, |(_, cause)| cause);
+    cause.to_string()
+}
+
+// Current shader errors per source, for the debug overlay or console to show. A successful
+// recompile clears a source's entries, so after a hot reload the list shows only what is
+// still broken. Sources are keyed by the name they were reported under, which may differ
+// from the diagnostics' files, e.g. an effect whose error is in a shared prelude.
+#[derive(Debug, Clone, Default)]
+pub struct ShaderErrorLog {
+    diagnostics: Vec<(String, ShaderDiagnostic)>,
+    // Bumped on every change, so an overlay only rebuilds its text when needed
+    revision: u64,
+}
+
+impl ShaderErrorLog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Replaces `source`'s entries with `diagnostics` and logs them
+    pub fn report(&mut self, source: &str, diagnostics: Vec<ShaderDiagnostic>) {
+        for diagnostic in &diagnostics {
+            log::error!("Shader error: {}", diagnostic);
+        }
+        self.diagnostics.retain(|(key, _)| key != source);
+        self.diagnostics.extend(diagnostics.into_iter().map(|diagnostic| (source.to_string(), diagnostic)));
+        self.revision += 1;
+    }
+
+    pub fn clear(&mut self, source: &str) {
+        let before = self.diagnostics.len();
+        self.diagnostics.retain(|(key, _)| key != source);
+        if self.diagnostics.len() != before {
+            self.revision += 1;
+        }
+    }
+
+    // Reports or clears `source` from a `compile_shader` result, passing the module through
+    pub fn track(&mut self, source: &str, result: Result<wgpu::ShaderModule, Vec<ShaderDiagnostic>>) -> Option<wgpu::ShaderModule> {
+        match result {
+            Ok(module) => {
+                self.clear(source);
+                Some(module)
+            }
+            Err(diagnostics) => {
+                self.report(source, diagnostics);
+                None
+            }
+        }
+    }
+
+    pub fn diagnostics(&self) -> impl Iterator<Item = &ShaderDiagnostic> {
+        self.diagnostics.iter().map(|(_, diagnostic)| diagnostic)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.diagnostics.is_empty()
+    }
+
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    // Plain-text listing for overlays and consoles, one `file:line:column: message` per error
+    pub fn report_text(&self) -> String {
+        let mut out = String::new();
+        for diagnostic in self.diagnostics() {
+            out += &format!("{}\n", diagnostic);
+        }
+        out
+    }
+}
@@ -0,0 +1,399 @@
+// src/shadow.rs
+use crate::mesh::MeshVertex;
+use crate::resource_registry::{ResourceRegistry, Tracked};
+use crate::upload::UploadBelt;
+use glam::{Mat4, Vec3, Vec4};
+
+// Prepend to a fragment shader to get `shadow_factor` and `shadow_debug_tint`
+pub const SHADOW_SAMPLING_WGSL: &str = include_str!("shadow_sampling.wgsl");
+// Bind group index the sampling helpers expect
+pub const SHADOW_GROUP: u32 = 2;
+pub const MAX_CASCADES: usize = 4;
+pub const SHADOW_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth32Float;
+
+// Per-instance model matrix at vertex slot 1 of the caster pass, locations 3..=6
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct ShadowInstance {
+    pub model: [f32; 16],
+}
+
+impl ShadowInstance {
+    const ATTRIBUTES: [wgpu::VertexAttribute; 4] =
+        wgpu::vertex_attr_array![3 => Float32x4, 4 => Float32x4, 5 => Float32x4, 6 => Float32x4];
+
+    pub fn new(model: Mat4) -> Self {
+        Self { model: model.to_cols_array() }
+    }
+
+    pub fn layout() -> wgpu::VertexBufferLayout<'static> {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<ShadowInstance>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Instance,
+            attributes: &Self::ATTRIBUTES,
+        }
+    }
+}
+
+// Mirrors `ShadowParams` in shadow_sampling.wgsl
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct ShadowParams {
+    view_proj: [[f32; 16]; MAX_CASCADES],
+    splits: [f32; MAX_CASCADES],
+    cascade_count: u32,
+    depth_bias: f32,
+    texel_size: f32,
+    debug: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CascadeConfig {
+    pub cascade_count: u32,
+    // Width and height of each cascade layer in texels
+    pub resolution: u32,
+    // Shadows end here even if the camera sees further
+    pub max_distance: f32,
+    // Blend between uniform (0.0) and logarithmic (1.0) split distances
+    pub split_lambda: f32,
+    // How far behind each cascade casters are still captured, in world units
+    pub caster_margin: f32,
+    pub depth_bias: f32,
+}
+
+impl Default for CascadeConfig {
+    fn default() -> Self {
+        Self {
+            cascade_count: 4,
+            resolution: 2048,
+            max_distance: 200.0,
+            split_lambda: 0.75,
+            caster_margin: 50.0,
+            depth_bias: 0.0015,
+        }
+    }
+}
+
+// Perspective camera the cascades are fit to
+#[derive(Debug, Clone, Copy)]
+pub struct ShadowCamera {
+    pub view: Mat4,
+    pub fov_y: f32,
+    pub aspect: f32,
+    pub near: f32,
+    pub far: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Cascade {
+    pub view_proj: Mat4,
+    // View depth where this cascade hands over to the next
+    pub split_far: f32,
+    // World-space size of one shadow texel
+    pub texel_world_size: f32,
+}
+
+// Practical split scheme: interpolates between logarithmic and uniform distances
+pub fn cascade_splits(near: f32, far: f32, count: u32, lambda: f32) -> Vec<f32> {
+    (1..=count)
+        .map(|i| {
+            let t = i as f32 / count as f32;
+            let log = near * (far / near).powf(t);
+            let uniform = near + (far - near) * t;
+            lambda * log + (1.0 - lambda) * uniform
+        })
+        .collect()
+}
+
+// Fits one orthographic light projection per split. Each cascade is bounded by a sphere
+// so its size doesn't change as the camera rotates, and its origin is snapped to whole
+// texels so shadow edges don't shimmer as the camera moves.
+pub fn fit_cascades(camera: &ShadowCamera, light_direction: Vec3, config: &CascadeConfig) -> Vec<Cascade> {
+    let count = config.cascade_count.clamp(1, MAX_CASCADES as u32);
+    let far = camera.far.min(config.max_distance);
+    let direction = light_direction.normalize_or(Vec3::NEG_Y);
+    let up = if direction.y.abs() > 0.99 { Vec3::Z } else { Vec3::Y };
+    let inv_view = camera.view.inverse();
+    let tan_half = (camera.fov_y * 0.5).tan();
+    let resolution = config.resolution.max(1) as f32;
+
+    let mut split_near = camera.near;
+    cascade_splits(camera.near, far, count, config.split_lambda)
+        .into_iter()
+        .map(|split_far| {
+            let corners: Vec<Vec3> = [split_near, split_far]
+                .into_iter()
+                .flat_map(|depth| {
+                    let (half_h, half_w) = (depth * tan_half, depth * tan_half * camera.aspect);
+                    [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)]
+                        .map(|(x, y)| inv_view.transform_point3(Vec3::new(x * half_w, y * half_h, -depth)))
+                })
+                .collect();
+            split_near = split_far;
+
+            let center = corners.iter().copied().sum::<Vec3>() / corners.len() as f32;
+            let radius = corners.iter().map(|c| c.distance(center)).fold(0.0, f32::max);
+            // Quantized so floating-point noise can't change the texel size frame to frame
+            let radius = (radius * 16.0).ceil() / 16.0;
+
+            let eye = center - direction * (radius + config.caster_margin);
+            let view = Mat4::look_at_rh(eye, center, up);
+            let mut projection = Mat4::orthographic_rh(-radius, radius, -radius, radius, 0.0, 2.0 * radius + config.caster_margin);
+            let origin = (projection * view).transform_point3(Vec3::ZERO) * resolution * 0.5;
+            let snap = (origin.round() - origin) * 2.0 / resolution;
+            projection.w_axis += Vec4::new(snap.x, snap.y, 0.0, 0.0);
+
+            Cascade { view_proj: projection * view, split_far, texel_world_size: 2.0 * radius / resolution }
+        })
+        .collect()
+}
+
+// Directional light shadows split into up to four cascades, rendered into layers of one
+// depth texture array and sampled with hardware PCF.
+pub struct CascadedShadowMaps {
+    pub config: CascadeConfig,
+    pub debug_cascades: bool,
+    _texture: Tracked<wgpu::Texture>,
+    layer_views: Vec<wgpu::TextureView>,
+    cascade_buffers: Vec<Tracked<wgpu::Buffer>>,
+    cascade_bind_groups: Vec<wgpu::BindGroup>,
+    params_buffer: Tracked<wgpu::Buffer>,
+    sampling_layout: wgpu::BindGroupLayout,
+    sampling_bind_group: wgpu::BindGroup,
+    pipeline: wgpu::RenderPipeline,
+    cascades: Vec<Cascade>,
+}
+
+impl CascadedShadowMaps {
+    pub fn new(device: &wgpu::Device, resources: &ResourceRegistry, config: CascadeConfig) -> Result<Self, String> {
+        if config.cascade_count == 0 || config.cascade_count as usize > MAX_CASCADES {
+            return Err(format!("Cascade count must be between 1 and {}", MAX_CASCADES));
+        }
+        if config.resolution == 0 {
+            return Err("Shadow map resolution must be at least 1".to_string());
+        }
+        let texture = resources.create_texture(device, &wgpu::TextureDescriptor {
+            label: Some("shadow cascades"),
+            size: wgpu::Extent3d {
+                width: config.resolution,
+                height: config.resolution,
+                depth_or_array_layers: config.cascade_count,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: SHADOW_FORMAT,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        }, "shadows");
+        let layer_views = (0..config.cascade_count)
+            .map(|layer| texture.create_view(&wgpu::TextureViewDescriptor {
+                label: Some("shadow cascade"),
+                dimension: Some(wgpu::TextureViewDimension::D2),
+                base_array_layer: layer,
+                array_layer_count: Some(1),
+                ..Default::default()
+            }))
+            .collect();
+        let array_view = texture.create_view(&wgpu::TextureViewDescriptor {
+            label: Some("shadow cascades"),
+            dimension: Some(wgpu::TextureViewDimension::D2Array),
+            ..Default::default()
+        });
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("shadow"),
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            compare: Some(wgpu::CompareFunction::LessEqual),
+            ..Default::default()
+        });
+
+        let cascade_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("shadow cascade"),
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::VERTEX,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+        });
+        let cascade_buffers: Vec<_> = (0..config.cascade_count)
+            .map(|_| resources.create_buffer(device, &wgpu::BufferDescriptor {
+                label: Some("shadow cascade"),
+                size: std::mem::size_of::<Mat4>() as u64,
+                usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            }, "shadows"))
+            .collect();
+        let cascade_bind_groups = cascade_buffers
+            .iter()
+            .map(|buffer| device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("shadow cascade"),
+                layout: &cascade_layout,
+                entries: &[wgpu::BindGroupEntry { binding: 0, resource: buffer.as_entire_binding() }],
+            }))
+            .collect();
+
+        let params_buffer = resources.create_buffer(device, &wgpu::BufferDescriptor {
+            label: Some("shadow params"),
+            size: std::mem::size_of::<ShadowParams>() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        }, "shadows");
+        let sampling_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("shadow sampling"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Depth,
+                        view_dimension: wgpu::TextureViewDimension::D2Array,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Comparison),
+                    count: None,
+                },
+            ],
+        });
+        let sampling_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("shadow sampling"),
+            layout: &sampling_layout,
+            entries: &[
+                wgpu::BindGroupEntry { binding: 0, resource: params_buffer.as_entire_binding() },
+                wgpu::BindGroupEntry { binding: 1, resource: wgpu::BindingResource::TextureView(&array_view) },
+                wgpu::BindGroupEntry { binding: 2, resource: wgpu::BindingResource::Sampler(&sampler) },
+            ],
+        });
+
+        let shader = device.create_shader_module(wgpu::include_wgsl!("shadow.wgsl"));
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("shadow casters"),
+            bind_group_layouts: &[&cascade_layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("shadow casters"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: Some("vs_main"),
+                buffers: &[MeshVertex::layout(), ShadowInstance::layout()],
+                compilation_options: Default::default(),
+            },
+            fragment: None,
+            // Front faces culled so the bias only has to cover back-face acne
+            primitive: wgpu::PrimitiveState { cull_mode: Some(wgpu::Face::Front), ..Default::default() },
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: SHADOW_FORMAT,
+                depth_write_enabled: true,
+                depth_compare: wgpu::CompareFunction::LessEqual,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState { constant: 2, slope_scale: 2.0, clamp: 0.0 },
+            }),
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        });
+
+        Ok(Self {
+            config,
+            debug_cascades: false,
+            _texture: texture,
+            layer_views,
+            cascade_buffers,
+            cascade_bind_groups,
+            params_buffer,
+            sampling_layout,
+            sampling_bind_group,
+            pipeline,
+            cascades: Vec::new(),
+        })
+    }
+
+    // For the lit pipeline layout, at `SHADOW_GROUP`
+    pub fn sampling_layout(&self) -> &wgpu::BindGroupLayout {
+        &self.sampling_layout
+    }
+
+    pub fn sampling_bind_group(&self) -> &wgpu::BindGroup {
+        &self.sampling_bind_group
+    }
+
+    // Cascades fit by the last `update`, e.g. for drawing their bounds in a debug overlay
+    pub fn cascades(&self) -> &[Cascade] {
+        &self.cascades
+    }
+
+    // Refits the cascades to `camera` and writes their matrices as part of `encoder`
+    pub fn update(
+        &mut self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        belt: &mut UploadBelt,
+        camera: &ShadowCamera,
+        light_direction: Vec3,
+    ) {
+        self.cascades = fit_cascades(camera, light_direction, &self.config);
+        let mut params = ShadowParams {
+            view_proj: [Mat4::IDENTITY.to_cols_array(); MAX_CASCADES],
+            splits: [0.0; MAX_CASCADES],
+            cascade_count: self.cascades.len() as u32,
+            depth_bias: self.config.depth_bias,
+            texel_size: 1.0 / self.config.resolution as f32,
+            debug: self.debug_cascades as u32,
+        };
+        for (i, (cascade, buffer)) in self.cascades.iter().zip(&self.cascade_buffers).enumerate() {
+            params.view_proj[i] = cascade.view_proj.to_cols_array();
+            params.splits[i] = cascade.split_far;
+            belt.write(device, encoder, buffer, 0, bytemuck::bytes_of(&params.view_proj[i]));
+        }
+        belt.write(device, encoder, &self.params_buffer, 0, bytemuck::bytes_of(&params));
+    }
+
+    // One depth pass per cascade. `draw_casters` gets the pass with the caster pipeline and
+    // cascade bind group set, and binds mesh vertices at slot 0 and `ShadowInstance`s at slot 1.
+    pub fn render<F>(&self, encoder: &mut wgpu::CommandEncoder, mut draw_casters: F)
+    where
+        F: FnMut(&mut wgpu::RenderPass<'_>, usize),
+    {
+        for (index, (view, bind_group)) in self.layer_views.iter().zip(&self.cascade_bind_groups).enumerate() {
+            let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("shadow cascade"),
+                color_attachments: &[],
+                depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                    view,
+                    depth_ops: Some(wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(1.0),
+                        store: wgpu::StoreOp::Store,
+                    }),
+                    stencil_ops: None,
+                }),
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+            pass.set_pipeline(&self.pipeline);
+            pass.set_bind_group(0, bind_group, &[]);
+            draw_casters(&mut pass, index);
+        }
+    }
+}
@@ -0,0 +1,19 @@
+// Depth-only caster pass for one shadow cascade; no fragment stage
+
+struct Cascade {
+    view_proj: mat4x4<f32>,
+}
+
+@group(0) @binding(0) var<uniform> cascade: Cascade;
+
+@vertex
+fn vs_main(
+    @location(0) position: vec3<f32>,
+    @location(3) model_0: vec4<f32>,
+    @location(4) model_1: vec4<f32>,
+    @location(5) model_2: vec4<f32>,
+    @location(6) model_3: vec4<f32>,
+) -> @builtin(position) vec4<f32> {
+    let model = mat4x4<f32>(model_0, model_1, model_2, model_3);
+    return cascade.view_proj * model * vec4<f32>(position, 1.0);
+}
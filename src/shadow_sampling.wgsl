@@ -0,0 +1,60 @@
+// Cascaded shadow lookups; prepend to a fragment shader (see `SHADOW_SAMPLING_WGSL`)
+// and bind `CascadedShadowMaps::sampling_bind_group` at group 2
+
+struct ShadowParams {
+    view_proj: array<mat4x4<f32>, 4>,
+    splits: vec4<f32>,
+    cascade_count: u32,
+    depth_bias: f32,
+    texel_size: f32,
+    debug: u32,
+}
+
+@group(2) @binding(0) var<uniform> shadow_params: ShadowParams;
+@group(2) @binding(1) var shadow_map: texture_depth_2d_array;
+@group(2) @binding(2) var shadow_sampler: sampler_comparison;
+
+// First cascade whose far split lies beyond `view_depth`
+fn shadow_cascade(view_depth: f32) -> u32 {
+    var cascade = 0u;
+    for (var i = 0u; i + 1u < shadow_params.cascade_count; i++) {
+        if (view_depth > shadow_params.splits[i]) {
+            cascade = i + 1u;
+        }
+    }
+    return cascade;
+}
+
+// 1.0 when lit, 0.0 when fully shadowed; 3x3 PCF over the comparison sampler
+fn shadow_factor(world_position: vec3<f32>, view_depth: f32) -> f32 {
+    if (view_depth > shadow_params.splits[shadow_params.cascade_count - 1u]) {
+        return 1.0;
+    }
+    let cascade = shadow_cascade(view_depth);
+    let clip = shadow_params.view_proj[cascade] * vec4<f32>(world_position, 1.0);
+    let ndc = clip.xyz / clip.w;
+    let uv = vec2<f32>(ndc.x * 0.5 + 0.5, 0.5 - ndc.y * 0.5);
+    let depth = ndc.z - shadow_params.depth_bias;
+    var lit = 0.0;
+    for (var y = -1; y <= 1; y++) {
+        for (var x = -1; x <= 1; x++) {
+            let offset = vec2<f32>(f32(x), f32(y)) * shadow_params.texel_size;
+            lit += textureSampleCompareLevel(shadow_map, shadow_sampler, uv + offset, cascade, depth);
+        }
+    }
+    return lit / 9.0;
+}
+
+// Tints `color` by cascade when debug visualization is on: red, green, blue, yellow
+fn shadow_debug_tint(color: vec3<f32>, view_depth: f32) -> vec3<f32> {
+    if (shadow_params.debug == 0u) {
+        return color;
+    }
+    let tints = array<vec3<f32>, 4>(
+        vec3<f32>(1.0, 0.3, 0.3),
+        vec3<f32>(0.3, 1.0, 0.3),
+        vec3<f32>(0.3, 0.3, 1.0),
+        vec3<f32>(1.0, 1.0, 0.3),
+    );
+    return color * tints[shadow_cascade(view_depth)];
+}
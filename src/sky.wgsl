@@ -0,0 +1,62 @@
+// Analytic sky: single scattering with Rayleigh and Mie phase functions, air mass
+// growing towards the horizon, and a sun disk. Draws one fullscreen triangle at the far plane.
+
+struct AtmosphereParams {
+    inv_view_proj: mat4x4<f32>,
+    camera_position: vec3<f32>,
+    fog_density: f32,
+    fog_color: vec3<f32>,
+    fog_start: f32,
+    sun_direction: vec3<f32>,
+    height_falloff: f32,
+    rayleigh: vec3<f32>,
+    fog_base_height: f32,
+    mie: f32,
+    mie_g: f32,
+    sun_intensity: f32,
+    sun_scatter: f32,
+}
+
+@group(0) @binding(0) var<uniform> atmosphere: AtmosphereParams;
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) ndc: vec2<f32>,
+}
+
+@vertex
+fn vs_sky(@builtin(vertex_index) index: u32) -> VertexOutput {
+    let uv = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+    var out: VertexOutput;
+    out.ndc = uv * 2.0 - 1.0;
+    out.position = vec4<f32>(out.ndc, 1.0, 1.0);
+    return out;
+}
+
+const PI: f32 = 3.14159265;
+
+fn air_mass(height: f32) -> f32 {
+    return 1.0 / (max(height, 0.0) + 0.15);
+}
+
+@fragment
+fn fs_sky(in: VertexOutput) -> @location(0) vec4<f32> {
+    let far = atmosphere.inv_view_proj * vec4<f32>(in.ndc, 1.0, 1.0);
+    let direction = normalize(far.xyz / far.w - atmosphere.camera_position);
+    let sun = normalize(atmosphere.sun_direction);
+    let mu = dot(direction, sun);
+
+    let rayleigh_phase = 3.0 / (16.0 * PI) * (1.0 + mu * mu);
+    let g = atmosphere.mie_g;
+    let mie_phase = (1.0 - g * g) / (4.0 * PI * pow(1.0 + g * g - 2.0 * g * mu, 1.5));
+    let extinction = atmosphere.rayleigh + vec3<f32>(atmosphere.mie);
+
+    let sun_light = atmosphere.sun_intensity * exp(-extinction * air_mass(sun.y));
+    let scattering = atmosphere.rayleigh * rayleigh_phase + vec3<f32>(atmosphere.mie * mie_phase);
+    var color = sun_light * scattering / extinction * (1.0 - exp(-extinction * air_mass(direction.y)));
+    color += sun_light * smoothstep(0.9995, 0.9998, mu);
+
+    // Below the horizon the sky fades into the fog colour instead of a hard ground line
+    let below = smoothstep(0.0, -0.1, direction.y);
+    return vec4<f32>(mix(color, atmosphere.fog_color, below), 1.0);
+}
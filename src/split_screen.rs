@@ -0,0 +1,245 @@
+// src/split_screen.rs
+use crate::atmosphere::Atmosphere;
+use crate::bind_cache::BindGroupCache;
+use crate::camera::ClearMode;
+use crate::indirect::{CullObject, GpuCuller, IndirectDrawList, IndirectMode};
+use crate::render_texture::ViewCamera;
+use crate::resource_registry::ResourceRegistry;
+use crate::upload::UploadBelt;
+use glam::{Vec2, Vec3};
+
+// Part of the render target as fractions of its size, (0, 0) at the top left
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ViewportRect {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+impl ViewportRect {
+    pub const FULL: ViewportRect = ViewportRect { x: 0.0, y: 0.0, width: 1.0, height: 1.0 };
+
+    pub fn new(x: f32, y: f32, width: f32, height: f32) -> Self {
+        Self { x, y, width, height }
+    }
+
+    // (x, y, width, height) in pixels of a `target_width` x `target_height` target. Edges
+    // are rounded, so neighbouring viewports share them without gaps or overlap.
+    pub fn to_pixels(&self, target_width: u32, target_height: u32) -> (u32, u32, u32, u32) {
+        let (w, h) = (target_width as f32, target_height as f32);
+        let left = (self.x * w).round().clamp(0.0, w) as u32;
+        let top = (self.y * h).round().clamp(0.0, h) as u32;
+        let right = ((self.x + self.width) * w).round().clamp(0.0, w) as u32;
+        let bottom = ((self.y + self.height) * h).round().clamp(0.0, h) as u32;
+        (left, top, right.saturating_sub(left), bottom.saturating_sub(top))
+    }
+
+    // For the view's projection matrix
+    pub fn aspect(&self, target_width: u32, target_height: u32) -> f32 {
+        let (_, _, width, height) = self.to_pixels(target_width, target_height);
+        width.max(1) as f32 / height.max(1) as f32
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SplitDirection {
+    // Two players side by side
+    Vertical,
+    // Two players one above the other
+    Horizontal,
+}
+
+// The usual co-op layouts: full screen, halves split along `direction`, three players with
+// player one across the top, and quadrants. Beyond four it falls back to a grid.
+pub fn split_layout(count: usize, direction: SplitDirection) -> Vec<ViewportRect> {
+    match count {
+        0 => Vec::new(),
+        1 => vec![ViewportRect::FULL],
+        2 => match direction {
+            SplitDirection::Vertical => vec![ViewportRect::new(0.0, 0.0, 0.5, 1.0), ViewportRect::new(0.5, 0.0, 0.5, 1.0)],
+            SplitDirection::Horizontal => vec![ViewportRect::new(0.0, 0.0, 1.0, 0.5), ViewportRect::new(0.0, 0.5, 1.0, 0.5)],
+        },
+        3 => vec![
+            ViewportRect::new(0.0, 0.0, 1.0, 0.5),
+            ViewportRect::new(0.0, 0.5, 0.5, 0.5),
+            ViewportRect::new(0.5, 0.5, 0.5, 0.5),
+        ],
+        _ => {
+            let columns = (count as f32).sqrt().ceil() as usize;
+            let rows = count.div_ceil(columns);
+            let (width, height) = (1.0 / columns as f32, 1.0 / rows as f32);
+            (0..count)
+                .map(|i| ViewportRect::new((i % columns) as f32 * width, (i / columns) as f32 * height, width, height))
+                .collect()
+        }
+    }
+}
+
+// XY bounds of everything the camera can see, for `GpuCuller`'s view rect
+pub fn visible_rect(camera: &ViewCamera) -> (Vec2, Vec2) {
+    let inv_view_proj = camera.view_proj().inverse();
+    let mut min = Vec2::splat(f32::MAX);
+    let mut max = Vec2::splat(f32::MIN);
+    for corner in 0..8 {
+        let ndc = Vec3::new(
+            if corner & 1 == 0 { -1.0 } else { 1.0 },
+            if corner & 2 == 0 { -1.0 } else { 1.0 },
+            if corner & 4 == 0 { 0.0 } else { 1.0 },
+        );
+        let world = inv_view_proj.project_point3(ndc).truncate();
+        min = min.min(world);
+        max = max.max(world);
+    }
+    (min, max)
+}
+
+// One player's camera, where it lands on screen and what survived culling for it
+pub struct PlayerView {
+    pub camera: ViewCamera,
+    pub viewport: ViewportRect,
+    pub enabled: bool,
+    draws: IndirectDrawList,
+}
+
+impl PlayerView {
+    pub fn draws(&self) -> &IndirectDrawList {
+        &self.draws
+    }
+}
+
+// Renders N cameras into one target in a single frame. Each view gets its own culled draw
+// list, so objects only one player can see cost nothing in the other viewports.
+pub struct SplitScreen {
+    pub direction: SplitDirection,
+    // Applies to the whole target, before any view draws
+    pub clear: ClearMode,
+    views: Vec<PlayerView>,
+    // Only in `MultiDraw` mode; direct draws skip culling and may lack compute
+    culler: Option<GpuCuller>,
+    mode: IndirectMode,
+}
+
+impl SplitScreen {
+    pub fn new(device: &wgpu::Device, resources: &ResourceRegistry, mode: IndirectMode) -> Self {
+        Self {
+            direction: SplitDirection::Vertical,
+            clear: ClearMode::default(),
+            views: Vec::new(),
+            culler: (mode == IndirectMode::MultiDraw).then(|| GpuCuller::new(device, resources)),
+            mode,
+        }
+    }
+
+    // Adds a player and re-lays out every view; returns the new view's index
+    pub fn add_view(&mut self, device: &wgpu::Device, resources: &ResourceRegistry, camera: ViewCamera) -> usize {
+        self.views.push(PlayerView {
+            camera,
+            viewport: ViewportRect::FULL,
+            enabled: true,
+            draws: IndirectDrawList::new(device, resources, "split screen draws", self.mode),
+        });
+        self.relayout();
+        self.views.len() - 1
+    }
+
+    // Later views move down one index
+    pub fn remove_view(&mut self, index: usize) {
+        if index < self.views.len() {
+            self.views.remove(index);
+            self.relayout();
+        }
+    }
+
+    // Resets every viewport to `split_layout`, e.g. after changing `direction`. Viewports
+    // set by hand afterwards are kept until the next call.
+    pub fn relayout(&mut self) {
+        let layout = split_layout(self.views.len(), self.direction);
+        for (view, viewport) in self.views.iter_mut().zip(layout) {
+            view.viewport = viewport;
+        }
+    }
+
+    pub fn views(&self) -> &[PlayerView] {
+        &self.views
+    }
+
+    pub fn view_mut(&mut self, index: usize) -> Option<&mut PlayerView> {
+        self.views.get_mut(index)
+    }
+
+    pub fn len(&self) -> usize {
+        self.views.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.views.is_empty()
+    }
+
+    // Culls `objects` once per enabled view against that view's camera
+    #[allow(clippy::too_many_arguments)]
+    pub fn cull(
+        &mut self,
+        device: &wgpu::Device,
+        resources: &ResourceRegistry,
+        encoder: &mut wgpu::CommandEncoder,
+        belt: &mut UploadBelt,
+        bind_groups: &mut BindGroupCache,
+        objects: &[CullObject],
+    ) {
+        let Some(culler) = &mut self.culler else {
+            // Direct mode: every object drawn, as `GpuCuller::cull` does
+            for view in self.views.iter_mut().filter(|view| view.enabled) {
+                view.draws.clear();
+                for object in objects {
+                    view.draws.push(object.args);
+                }
+            }
+            return;
+        };
+        for view in self.views.iter_mut().filter(|view| view.enabled) {
+            let (view_min, view_max) = visible_rect(&view.camera);
+            culler.cull(device, resources, encoder, belt, bind_groups, view_min, view_max, objects, &mut view.draws);
+        }
+    }
+
+    // Begins one pass over the whole target, then for each enabled view restricts drawing
+    // to its viewport and calls `draw` with its index, camera and culled draws
+    pub fn render<F>(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        target: &wgpu::TextureView,
+        target_size: (u32, u32),
+        depth: Option<&wgpu::TextureView>,
+        atmosphere: &Atmosphere,
+        mut draw: F,
+    ) where
+        F: FnMut(&mut wgpu::RenderPass<'_>, usize, &PlayerView),
+    {
+        let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("split screen"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: target,
+                resolve_target: None,
+                ops: wgpu::Operations { load: self.clear.load_op(atmosphere), store: wgpu::StoreOp::Store },
+                depth_slice: None,
+            })],
+            depth_stencil_attachment: depth.map(|view| wgpu::RenderPassDepthStencilAttachment {
+                view,
+                depth_ops: Some(wgpu::Operations { load: wgpu::LoadOp::Clear(1.0), store: wgpu::StoreOp::Discard }),
+                stencil_ops: None,
+            }),
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        for (index, view) in self.views.iter().enumerate().filter(|(_, view)| view.enabled) {
+            let (x, y, width, height) = view.viewport.to_pixels(target_size.0, target_size.1);
+            if width == 0 || height == 0 {
+                continue;
+            }
+            pass.set_viewport(x as f32, y as f32, width as f32, height as f32, 0.0, 1.0);
+            pass.set_scissor_rect(x, y, width, height);
+            draw(&mut pass, index, view);
+        }
+    }
+}
@@ -0,0 +1,327 @@
+// src/sprite_batch.rs
+use crate::bind_cache::{BindGroupCache, BindingKey, SamplerCache};
+use crate::capabilities::GpuCapabilities;
+use crate::color::Color;
+use crate::gpu_buffer::{GpuStruct, UniformBuffer};
+use crate::resource_registry::ResourceRegistry;
+use crate::upload::{DynamicBuffer, UploadBelt};
+use glam::{Mat4, Vec2};
+use std::num::NonZeroU32;
+use std::ops::Range;
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct SpriteVertex {
+    pub position: [f32; 2],
+    pub uv: [f32; 2],
+    pub color: [f32; 4],
+    // Slot in the bindless texture array; unused on the per-texture path
+    pub texture: u32,
+}
+
+impl SpriteVertex {
+    const ATTRIBUTES: [wgpu::VertexAttribute; 4] =
+        wgpu::vertex_attr_array![0 => Float32x2, 1 => Float32x2, 2 => Float32x4, 3 => Uint32];
+
+    pub fn layout() -> wgpu::VertexBufferLayout<'static> {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<SpriteVertex>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &Self::ATTRIBUTES,
+        }
+    }
+}
+
+// How a batch binds textures: all at once through a binding array where the device has
+// bindless support, otherwise one bind group and draw call per run of equal textures
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MaterialPath {
+    Bindless { capacity: u32 },
+    PerTexture,
+}
+
+// Plenty for a 2D game's atlases; keeps the bind group small on drivers with huge limits
+const MAX_BINDLESS_TEXTURES: u32 = 1024;
+
+impl MaterialPath {
+    pub fn select(capabilities: &GpuCapabilities) -> Self {
+        let capacity = capabilities.limits.max_binding_array_elements_per_shader_stage.min(MAX_BINDLESS_TEXTURES);
+        if capabilities.bindless_textures && capacity > 0 {
+            Self::Bindless { capacity }
+        } else {
+            Self::PerTexture
+        }
+    }
+}
+
+// Returned by `SpriteBatch::add_texture`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct TextureSlot(u32);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Sprite {
+    pub texture: TextureSlot,
+    // World-space centre and size
+    pub position: Vec2,
+    pub size: Vec2,
+    // Radians, counter-clockwise
+    pub rotation: f32,
+    // Top-left and bottom-right UVs, e.g. a frame in an atlas
+    pub uv: [Vec2; 2],
+    pub color: Color,
+    // Drawn in ascending order; equal layers keep submission order
+    pub layer: i32,
+}
+
+impl Sprite {
+    pub fn new(texture: TextureSlot, position: Vec2, size: Vec2) -> Self {
+        Self {
+            texture,
+            position,
+            size,
+            rotation: 0.0,
+            uv: [Vec2::ZERO, Vec2::ONE],
+            color: Color::WHITE,
+            layer: 0,
+        }
+    }
+
+    fn build(&self, vertices: &mut Vec<SpriteVertex>) {
+        let (sin, cos) = self.rotation.sin_cos();
+        let half = self.size * 0.5;
+        let corner = |x: f32, y: f32, uv: Vec2| {
+            let local = Vec2::new(x * half.x, y * half.y);
+            let position = self.position + Vec2::new(local.x * cos - local.y * sin, local.x * sin + local.y * cos);
+            SpriteVertex { position: position.into(), uv: uv.into(), color: self.color.to_array(), texture: self.texture.0 }
+        };
+        let [uv_min, uv_max] = self.uv;
+        let top_left = corner(-1.0, 1.0, uv_min);
+        let top_right = corner(1.0, 1.0, Vec2::new(uv_max.x, uv_min.y));
+        let bottom_left = corner(-1.0, -1.0, Vec2::new(uv_min.x, uv_max.y));
+        let bottom_right = corner(1.0, -1.0, uv_max);
+        vertices.extend_from_slice(&[top_left, bottom_left, top_right, top_right, bottom_left, bottom_right]);
+    }
+}
+
+// Mirrors `SpriteParams` in sprite.wgsl and sprite_bindless.wgsl
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct SpriteParams {
+    view_proj: [f32; 16],
+}
+
+impl GpuStruct for SpriteParams {}
+
+// Alpha-blended textured quads. On the bindless path every sprite goes out in one draw
+// call whatever its texture; on the per-texture path consecutive sprites (after sorting
+// by layer) that share a texture share a draw. The path is picked from the device's
+// capabilities, so game code is the same either way.
+pub struct SpriteBatch {
+    path: MaterialPath,
+    textures: Vec<wgpu::TextureView>,
+    sampler: wgpu::Sampler,
+    sprites: Vec<Sprite>,
+    scratch: Vec<SpriteVertex>,
+    vertices: DynamicBuffer,
+    // Vertex range and texture of each draw call built by `prepare`
+    runs: Vec<(Range<u32>, TextureSlot)>,
+    params: UniformBuffer<SpriteParams>,
+    params_layout: wgpu::BindGroupLayout,
+    texture_layout: wgpu::BindGroupLayout,
+    // Bindless only; rebuilt after textures are added
+    texture_array: Option<wgpu::BindGroup>,
+    pipeline: wgpu::RenderPipeline,
+}
+
+impl SpriteBatch {
+    pub fn new(
+        device: &wgpu::Device,
+        resources: &ResourceRegistry,
+        samplers: &mut SamplerCache,
+        capabilities: &GpuCapabilities,
+        color_format: wgpu::TextureFormat,
+    ) -> Self {
+        let path = MaterialPath::select(capabilities);
+        let params = UniformBuffer::new(device, resources, "sprite params", 1);
+        let params_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("sprite params"),
+            entries: &[params.layout_entry(0, wgpu::ShaderStages::VERTEX)],
+        });
+        let texture_count = match path {
+            MaterialPath::Bindless { capacity } => NonZeroU32::new(capacity),
+            MaterialPath::PerTexture => None,
+        };
+        let texture_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("sprite textures"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: texture_count,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+        });
+        // The binding array only compiles on bindless devices, so each path has its own shader
+        let shader = match path {
+            MaterialPath::Bindless { .. } => device.create_shader_module(wgpu::include_wgsl!("sprite_bindless.wgsl")),
+            MaterialPath::PerTexture => device.create_shader_module(wgpu::include_wgsl!("sprite.wgsl")),
+        };
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("sprites"),
+            bind_group_layouts: &[&params_layout, &texture_layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("sprites"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: Some("vs_main"),
+                buffers: &[SpriteVertex::layout()],
+                compilation_options: Default::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: Some("fs_main"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: color_format,
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: Default::default(),
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        });
+        let sampler = samplers.get(device, &wgpu::SamplerDescriptor {
+            label: Some("sprites"),
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+        let vertices = DynamicBuffer::new(device, resources, "sprite vertices", wgpu::BufferUsages::VERTEX, 64 * 1024);
+        log::info!("Sprite material path: {:?}", path);
+        Self {
+            path,
+            textures: Vec::new(),
+            sampler,
+            sprites: Vec::new(),
+            scratch: Vec::new(),
+            vertices,
+            runs: Vec::new(),
+            params,
+            params_layout,
+            texture_layout,
+            texture_array: None,
+            pipeline,
+        }
+    }
+
+    pub fn path(&self) -> MaterialPath {
+        self.path
+    }
+
+    // Register each texture once, e.g. at load; fails only when the bindless array is full
+    pub fn add_texture(&mut self, view: wgpu::TextureView) -> Result<TextureSlot, String> {
+        if let MaterialPath::Bindless { capacity } = self.path {
+            if self.textures.len() as u32 >= capacity {
+                return Err(format!("Sprite texture array is full ({} textures)", capacity));
+            }
+        }
+        self.textures.push(view);
+        self.texture_array = None;
+        Ok(TextureSlot(self.textures.len() as u32 - 1))
+    }
+
+    // Queues a sprite for the next `prepare`
+    pub fn push(&mut self, sprite: Sprite) {
+        self.sprites.push(sprite);
+    }
+
+    // Builds this frame's vertices and draw runs from the queued sprites, then clears the queue
+    pub fn prepare(
+        &mut self,
+        device: &wgpu::Device,
+        resources: &ResourceRegistry,
+        encoder: &mut wgpu::CommandEncoder,
+        belt: &mut UploadBelt,
+        view_proj: Mat4,
+    ) {
+        self.sprites.retain(|sprite| (sprite.texture.0 as usize) < self.textures.len());
+        self.sprites.sort_by_key(|sprite| sprite.layer);
+        self.scratch.clear();
+        self.runs.clear();
+        for sprite in self.sprites.drain(..) {
+            let start = self.scratch.len() as u32;
+            sprite.build(&mut self.scratch);
+            let end = self.scratch.len() as u32;
+            match self.runs.last_mut() {
+                // Bindless draws everything in one run; the texture comes from each vertex
+                Some((range, texture)) if matches!(self.path, MaterialPath::Bindless { .. }) || *texture == sprite.texture => {
+                    range.end = end;
+                }
+                _ => self.runs.push((start..end, sprite.texture)),
+            }
+        }
+        if !self.scratch.is_empty() {
+            self.vertices.upload(device, resources, encoder, belt, bytemuck::cast_slice(&self.scratch));
+        }
+        self.params.set(device, encoder, belt, &SpriteParams { view_proj: view_proj.to_cols_array() });
+
+        if matches!(self.path, MaterialPath::Bindless { .. }) && self.texture_array.is_none() && !self.textures.is_empty() {
+            let views: Vec<&wgpu::TextureView> = self.textures.iter().collect();
+            // Fewer views than the layout's count is fine with PARTIALLY_BOUND_BINDING_ARRAY
+            self.texture_array = Some(device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("sprite texture array"),
+                layout: &self.texture_layout,
+                entries: &[
+                    wgpu::BindGroupEntry { binding: 0, resource: wgpu::BindingResource::TextureViewArray(&views) },
+                    wgpu::BindGroupEntry { binding: 1, resource: wgpu::BindingResource::Sampler(&self.sampler) },
+                ],
+            }));
+        }
+    }
+
+    // Draw calls the next `draw` will issue
+    pub fn draw_calls(&self) -> u32 {
+        self.runs.len() as u32
+    }
+
+    pub fn draw(&self, device: &wgpu::Device, bind_groups: &mut BindGroupCache, render_pass: &mut wgpu::RenderPass<'_>) {
+        if self.runs.is_empty() {
+            return;
+        }
+        let params = self.params.bind_group(device, bind_groups, &self.params_layout);
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(0, &params, &[]);
+        render_pass.set_vertex_buffer(0, self.vertices.buffer().slice(..));
+        for (range, texture) in &self.runs {
+            match (&self.path, &self.texture_array) {
+                (MaterialPath::Bindless { .. }, Some(texture_array)) => render_pass.set_bind_group(1, texture_array, &[]),
+                (MaterialPath::Bindless { .. }, None) => return,
+                (MaterialPath::PerTexture, _) => {
+                    let bind_group = bind_groups.get_or_create(device, "sprite texture", &self.texture_layout, &[
+                        (0, BindingKey::TextureView(self.textures[texture.0 as usize].clone())),
+                        (1, BindingKey::Sampler(self.sampler.clone())),
+                    ]);
+                    render_pass.set_bind_group(1, &bind_group, &[]);
+                }
+            }
+            render_pass.draw(range.clone(), 0..1);
+        }
+    }
+}
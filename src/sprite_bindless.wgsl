@@ -0,0 +1,38 @@
+// Sprites on the bindless path: every texture is bound at once and each vertex carries
+// its slot, so one draw call covers sprites with different textures
+
+struct SpriteParams {
+    view_proj: mat4x4<f32>,
+}
+
+@group(0) @binding(0) var<uniform> params: SpriteParams;
+@group(1) @binding(0) var sprite_textures: binding_array<texture_2d<f32>>;
+@group(1) @binding(1) var sprite_sampler: sampler;
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+    @location(1) color: vec4<f32>,
+    @location(2) @interpolate(flat) texture: u32,
+}
+
+@vertex
+fn vs_main(
+    @location(0) position: vec2<f32>,
+    @location(1) uv: vec2<f32>,
+    @location(2) color: vec4<f32>,
+    @location(3) texture: u32,
+) -> VertexOutput {
+    var out: VertexOutput;
+    out.position = params.view_proj * vec4<f32>(position, 0.0, 1.0);
+    out.uv = uv;
+    out.color = color;
+    out.texture = texture;
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    // Neighbouring fragments can read different textures
+    return textureSample(sprite_textures[in.texture], sprite_sampler, in.uv) * in.color;
+}
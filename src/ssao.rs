@@ -0,0 +1,293 @@
+// src/ssao.rs
+use crate::bind_cache::{BindGroupCache, BindingKey};
+use crate::resource_registry::{ResourceRegistry, Tracked};
+use crate::upload::UploadBelt;
+use glam::{Mat4, Vec3};
+
+pub const AO_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::R8Unorm;
+const MAX_SAMPLES: usize = 64;
+
+// Mirrors `SsaoParams` in ssao.wgsl
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct SsaoParams {
+    projection: [f32; 16],
+    inv_projection: [f32; 16],
+    kernel: [[f32; 4]; MAX_SAMPLES],
+    screen_size: [f32; 2],
+    radius: f32,
+    bias: f32,
+    intensity: f32,
+    sample_count: u32,
+    blur_radius: i32,
+    _pad: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SsaoQuality {
+    Low,
+    Medium,
+    High,
+    Ultra,
+}
+
+impl SsaoQuality {
+    pub fn sample_count(self) -> u32 {
+        match self {
+            Self::Low => 8,
+            Self::Medium => 16,
+            Self::High => 32,
+            Self::Ultra => 64,
+        }
+    }
+
+    pub fn blur_radius(self) -> i32 {
+        match self {
+            Self::Low => 1,
+            Self::Medium | Self::High => 2,
+            Self::Ultra => 3,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SsaoSettings {
+    pub quality: SsaoQuality,
+    // View-space radius of the sampled hemisphere
+    pub radius: f32,
+    // Depth offset that keeps flat surfaces from occluding themselves
+    pub bias: f32,
+    pub intensity: f32,
+}
+
+impl Default for SsaoSettings {
+    fn default() -> Self {
+        Self { quality: SsaoQuality::Medium, radius: 0.5, bias: 0.025, intensity: 1.0 }
+    }
+}
+
+// Hemisphere around +Z, denser near the origin so close occluders weigh more
+fn sample_kernel(count: usize) -> [[f32; 4]; MAX_SAMPLES] {
+    let mut state = 0x9e37_79b9_u32;
+    let mut next = || {
+        state ^= state << 13;
+        state ^= state >> 17;
+        state ^= state << 5;
+        state as f32 / u32::MAX as f32
+    };
+    let mut kernel = [[0.0; 4]; MAX_SAMPLES];
+    for (i, slot) in kernel.iter_mut().enumerate().take(count) {
+        let direction = Vec3::new(next() * 2.0 - 1.0, next() * 2.0 - 1.0, next()).normalize_or(Vec3::Z);
+        let t = i as f32 / count as f32;
+        let scale = 0.1 + 0.9 * t * t;
+        *slot = (direction * next() * scale).extend(0.0).into();
+    }
+    kernel
+}
+
+// Ambient occlusion from a depth buffer and view-space normals, blurred into one R8
+// texture. Lighting multiplies its ambient term by `output_view`; the built-in 2D pass has
+// neither input, so this is for games' own 3D pipelines.
+pub struct SsaoPass {
+    pub settings: SsaoSettings,
+    size: (u32, u32),
+    raw: Tracked<wgpu::Texture>,
+    raw_view: wgpu::TextureView,
+    output: Tracked<wgpu::Texture>,
+    output_view: wgpu::TextureView,
+    params_buffer: Tracked<wgpu::Buffer>,
+    layout: wgpu::BindGroupLayout,
+    ao_pipeline: wgpu::RenderPipeline,
+    blur_pipeline: wgpu::RenderPipeline,
+}
+
+impl SsaoPass {
+    pub fn new(device: &wgpu::Device, resources: &ResourceRegistry, width: u32, height: u32, settings: SsaoSettings) -> Self {
+        let texture_entry = |binding, sample_type| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Texture {
+                sample_type,
+                view_dimension: wgpu::TextureViewDimension::D2,
+                multisampled: false,
+            },
+            count: None,
+        };
+        let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("ssao"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                texture_entry(1, wgpu::TextureSampleType::Depth),
+                texture_entry(2, wgpu::TextureSampleType::Float { filterable: false }),
+                texture_entry(3, wgpu::TextureSampleType::Float { filterable: false }),
+            ],
+        });
+        let shader = device.create_shader_module(wgpu::include_wgsl!("ssao.wgsl"));
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("ssao"),
+            bind_group_layouts: &[&layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = |label, entry_point| device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some(label),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: Some("vs_fullscreen"),
+                buffers: &[],
+                compilation_options: Default::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: Some(entry_point),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: AO_FORMAT,
+                    blend: None,
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: Default::default(),
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        });
+        let ao_pipeline = pipeline("ssao", "fs_ao");
+        let blur_pipeline = pipeline("ssao blur", "fs_blur");
+
+        let params_buffer = resources.create_buffer(device, &wgpu::BufferDescriptor {
+            label: Some("ssao params"),
+            size: std::mem::size_of::<SsaoParams>() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        }, "ssao");
+        let (raw, raw_view) = Self::create_target(device, resources, "ssao raw", width, height);
+        let (output, output_view) = Self::create_target(device, resources, "ssao", width, height);
+
+        Self {
+            settings,
+            size: (width.max(1), height.max(1)),
+            raw,
+            raw_view,
+            output,
+            output_view,
+            params_buffer,
+            layout,
+            ao_pipeline,
+            blur_pipeline,
+        }
+    }
+
+    fn create_target(
+        device: &wgpu::Device,
+        resources: &ResourceRegistry,
+        label: &str,
+        width: u32,
+        height: u32,
+    ) -> (Tracked<wgpu::Texture>, wgpu::TextureView) {
+        let texture = resources.create_texture(device, &wgpu::TextureDescriptor {
+            label: Some(label),
+            size: wgpu::Extent3d { width: width.max(1), height: height.max(1), depth_or_array_layers: 1 },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: AO_FORMAT,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        }, "ssao");
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        (texture, view)
+    }
+
+    // Must match the depth and normal inputs; call alongside the renderer's resize
+    pub fn resize(&mut self, device: &wgpu::Device, resources: &ResourceRegistry, width: u32, height: u32) {
+        let size = (width.max(1), height.max(1));
+        if size == self.size {
+            return;
+        }
+        self.size = size;
+        (self.raw, self.raw_view) = Self::create_target(device, resources, "ssao raw", size.0, size.1);
+        (self.output, self.output_view) = Self::create_target(device, resources, "ssao", size.0, size.1);
+    }
+
+    // Blurred occlusion, 1.0 where unoccluded
+    pub fn output_view(&self) -> &wgpu::TextureView {
+        &self.output_view
+    }
+
+    pub fn output_texture(&self) -> &wgpu::Texture {
+        &self.output
+    }
+
+    // `depth` is the scene depth buffer and `normals` holds view-space normals in xyz,
+    // both at the size given to `new`/`resize`. `projection` must be the one used for depth.
+    #[allow(clippy::too_many_arguments)]
+    pub fn render(
+        &self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        belt: &mut UploadBelt,
+        bind_groups: &mut BindGroupCache,
+        depth: &wgpu::TextureView,
+        normals: &wgpu::TextureView,
+        projection: Mat4,
+    ) {
+        let sample_count = self.settings.quality.sample_count();
+        let params = SsaoParams {
+            projection: projection.to_cols_array(),
+            inv_projection: projection.inverse().to_cols_array(),
+            kernel: sample_kernel(sample_count as usize),
+            screen_size: [self.size.0 as f32, self.size.1 as f32],
+            radius: self.settings.radius,
+            bias: self.settings.bias,
+            intensity: self.settings.intensity,
+            sample_count,
+            blur_radius: self.settings.quality.blur_radius(),
+            _pad: 0,
+        };
+        belt.write(device, encoder, &self.params_buffer, 0, bytemuck::bytes_of(&params));
+
+        // Binding 3 is only read by the blur; the AO pass gets the output there so it
+        // never samples the texture it renders to
+        let passes = [
+            ("ssao", &self.ao_pipeline, &self.output_view, &self.raw_view),
+            ("ssao blur", &self.blur_pipeline, &self.raw_view, &self.output_view),
+        ];
+        for (label, pipeline, input, target) in passes {
+            let bind_group = bind_groups.get_or_create(device, label, &self.layout, &[
+                (0, BindingKey::buffer(&self.params_buffer)),
+                (1, BindingKey::TextureView(depth.clone())),
+                (2, BindingKey::TextureView(normals.clone())),
+                (3, BindingKey::TextureView(input.clone())),
+            ]);
+            let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some(label),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: target,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::WHITE),
+                        store: wgpu::StoreOp::Store,
+                    },
+                    depth_slice: None,
+                })],
+                depth_stencil_attachment: None,
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+            pass.set_pipeline(pipeline);
+            pass.set_bind_group(0, &bind_group, &[]);
+            pass.draw(0..3, 0..1);
+        }
+    }
+}
@@ -0,0 +1,91 @@
+// Screen-space ambient occlusion: hemisphere sampling against the depth buffer, then a
+// depth-aware blur. Both passes draw one fullscreen triangle.
+
+struct SsaoParams {
+    projection: mat4x4<f32>,
+    inv_projection: mat4x4<f32>,
+    kernel: array<vec4<f32>, 64>,
+    screen_size: vec2<f32>,
+    radius: f32,
+    bias: f32,
+    intensity: f32,
+    sample_count: u32,
+    blur_radius: i32,
+}
+
+@group(0) @binding(0) var<uniform> params: SsaoParams;
+@group(0) @binding(1) var depth_texture: texture_depth_2d;
+@group(0) @binding(2) var normal_texture: texture_2d<f32>;
+@group(0) @binding(3) var ao_texture: texture_2d<f32>;
+
+@vertex
+fn vs_fullscreen(@builtin(vertex_index) index: u32) -> @builtin(position) vec4<f32> {
+    let uv = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+    return vec4<f32>(uv * 2.0 - 1.0, 0.0, 1.0);
+}
+
+fn view_position(pixel: vec2<i32>) -> vec3<f32> {
+    let depth = textureLoad(depth_texture, pixel, 0);
+    let uv = (vec2<f32>(pixel) + 0.5) / params.screen_size;
+    let ndc = vec4<f32>(uv.x * 2.0 - 1.0, 1.0 - uv.y * 2.0, depth, 1.0);
+    let view = params.inv_projection * ndc;
+    return view.xyz / view.w;
+}
+
+fn hash(pixel: vec2<u32>) -> f32 {
+    var h = pixel.x * 1973u + pixel.y * 9277u;
+    h = (h ^ (h >> 15u)) * 0x2c1b3c6du;
+    h = (h ^ (h >> 12u)) * 0x297a2d39u;
+    return f32(h ^ (h >> 15u)) / 4294967295.0;
+}
+
+@fragment
+fn fs_ao(@builtin(position) frag_coord: vec4<f32>) -> @location(0) vec4<f32> {
+    let pixel = vec2<i32>(frag_coord.xy);
+    if (textureLoad(depth_texture, pixel, 0) >= 1.0) {
+        return vec4<f32>(1.0);
+    }
+    let position = view_position(pixel);
+    let normal = normalize(textureLoad(normal_texture, pixel, 0).xyz);
+
+    // Per-pixel rotation of the kernel around the normal; the blur hides the noise
+    let angle = hash(vec2<u32>(pixel)) * 6.2831853;
+    let random = vec3<f32>(cos(angle), sin(angle), 0.0);
+    let tangent = normalize(random - normal * dot(random, normal) + vec3<f32>(1e-4, 0.0, 0.0));
+    let bitangent = cross(normal, tangent);
+    let tbn = mat3x3<f32>(tangent, bitangent, normal);
+
+    var occlusion = 0.0;
+    let max_pixel = vec2<i32>(params.screen_size) - 1;
+    for (var i = 0u; i < params.sample_count; i++) {
+        let sample = position + tbn * params.kernel[i].xyz * params.radius;
+        let clip = params.projection * vec4<f32>(sample, 1.0);
+        let ndc = clip.xy / clip.w;
+        let uv = vec2<f32>(ndc.x * 0.5 + 0.5, 0.5 - ndc.y * 0.5);
+        let scene = view_position(clamp(vec2<i32>(uv * params.screen_size), vec2<i32>(0), max_pixel));
+        // Fades out occluders far outside the radius so silhouettes don't darken the background
+        let range = smoothstep(0.0, 1.0, params.radius / abs(position.z - scene.z));
+        occlusion += select(0.0, 1.0, scene.z >= sample.z + params.bias) * range;
+    }
+    let ao = clamp(1.0 - occlusion / f32(max(params.sample_count, 1u)) * params.intensity, 0.0, 1.0);
+    return vec4<f32>(ao);
+}
+
+@fragment
+fn fs_blur(@builtin(position) frag_coord: vec4<f32>) -> @location(0) vec4<f32> {
+    let pixel = vec2<i32>(frag_coord.xy);
+    let max_pixel = vec2<i32>(params.screen_size) - 1;
+    let center_depth = view_position(pixel).z;
+    var total = 0.0;
+    var weight = 0.0;
+    for (var y = -params.blur_radius; y <= params.blur_radius; y++) {
+        for (var x = -params.blur_radius; x <= params.blur_radius; x++) {
+            let tap = clamp(pixel + vec2<i32>(x, y), vec2<i32>(0), max_pixel);
+            // Taps across a depth edge would bleed occlusion onto the other surface
+            let w = 1.0 / (1.0 + abs(view_position(tap).z - center_depth) * 8.0);
+            total += textureLoad(ao_texture, tap, 0).r * w;
+            weight += w;
+        }
+    }
+    return vec4<f32>(total / weight);
+}
@@ -0,0 +1,287 @@
+// src/ssr.rs
+use crate::bind_cache::{BindGroupCache, BindingKey, SamplerCache};
+use crate::resource_registry::{ResourceRegistry, Tracked};
+use crate::upload::UploadBelt;
+use glam::Mat4;
+
+pub const REFLECTION_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba16Float;
+
+// Mirrors `SsrParams` in ssr.wgsl
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct SsrParams {
+    projection: [f32; 16],
+    inv_projection: [f32; 16],
+    inv_view: [f32; 16],
+    screen_size: [f32; 2],
+    max_distance: f32,
+    thickness: f32,
+    max_steps: u32,
+    max_blur_radius: f32,
+    _pad: [u32; 2],
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SsrSettings {
+    // View-space length of the marched ray
+    pub max_distance: f32,
+    // How far behind the depth buffer a ray may be and still count as a hit
+    pub thickness: f32,
+    pub max_steps: u32,
+    // Blur radius in pixels at roughness 1.0
+    pub max_blur_radius: f32,
+}
+
+impl Default for SsrSettings {
+    fn default() -> Self {
+        Self { max_distance: 20.0, thickness: 0.3, max_steps: 64, max_blur_radius: 8.0 }
+    }
+}
+
+// Reflections for the PBR pipeline. Reads the lit scene colour, depth and a normal target
+// (view-space normal in xyz, roughness in w); `output_view` holds the reflected radiance,
+// environment-lit where rays left the screen, for lighting to weight by Fresnel.
+pub struct SsrPass {
+    pub settings: SsrSettings,
+    size: (u32, u32),
+    trace: Tracked<wgpu::Texture>,
+    trace_view: wgpu::TextureView,
+    output: Tracked<wgpu::Texture>,
+    output_view: wgpu::TextureView,
+    _black_environment: Tracked<wgpu::Texture>,
+    black_environment_view: wgpu::TextureView,
+    environment: Option<wgpu::TextureView>,
+    params_buffer: Tracked<wgpu::Buffer>,
+    layout: wgpu::BindGroupLayout,
+    trace_pipeline: wgpu::RenderPipeline,
+    blur_pipeline: wgpu::RenderPipeline,
+}
+
+impl SsrPass {
+    pub fn new(device: &wgpu::Device, resources: &ResourceRegistry, width: u32, height: u32) -> Self {
+        let texture_entry = |binding, sample_type, view_dimension| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Texture { sample_type, view_dimension, multisampled: false },
+            count: None,
+        };
+        let unfiltered = wgpu::TextureSampleType::Float { filterable: false };
+        let d2 = wgpu::TextureViewDimension::D2;
+        let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("ssr"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                texture_entry(1, wgpu::TextureSampleType::Depth, d2),
+                texture_entry(2, unfiltered, d2),
+                texture_entry(3, unfiltered, d2),
+                texture_entry(4, wgpu::TextureSampleType::Float { filterable: true }, wgpu::TextureViewDimension::Cube),
+                wgpu::BindGroupLayoutEntry {
+                    binding: 5,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+                texture_entry(6, unfiltered, d2),
+            ],
+        });
+        let shader = device.create_shader_module(wgpu::include_wgsl!("ssr.wgsl"));
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("ssr"),
+            bind_group_layouts: &[&layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = |label, entry_point| device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some(label),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: Some("vs_fullscreen"),
+                buffers: &[],
+                compilation_options: Default::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: Some(entry_point),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: REFLECTION_FORMAT,
+                    blend: None,
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: Default::default(),
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        });
+        let trace_pipeline = pipeline("ssr trace", "fs_trace");
+        let blur_pipeline = pipeline("ssr blur", "fs_blur");
+
+        // Stand-in until `set_environment` is called: misses reflect black
+        let black_environment = resources.create_texture(device, &wgpu::TextureDescriptor {
+            label: Some("ssr black environment"),
+            size: wgpu::Extent3d { width: 1, height: 1, depth_or_array_layers: 6 },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgba8Unorm,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        }, "ssr");
+        let black_environment_view = black_environment.create_view(&wgpu::TextureViewDescriptor {
+            dimension: Some(wgpu::TextureViewDimension::Cube),
+            ..Default::default()
+        });
+
+        let params_buffer = resources.create_buffer(device, &wgpu::BufferDescriptor {
+            label: Some("ssr params"),
+            size: std::mem::size_of::<SsrParams>() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        }, "ssr");
+        let size = (width.max(1), height.max(1));
+        let (trace, trace_view) = Self::create_target(device, resources, "ssr trace", size);
+        let (output, output_view) = Self::create_target(device, resources, "ssr", size);
+
+        Self {
+            settings: SsrSettings::default(),
+            size,
+            trace,
+            trace_view,
+            output,
+            output_view,
+            _black_environment: black_environment,
+            black_environment_view,
+            environment: None,
+            params_buffer,
+            layout,
+            trace_pipeline,
+            blur_pipeline,
+        }
+    }
+
+    fn create_target(
+        device: &wgpu::Device,
+        resources: &ResourceRegistry,
+        label: &str,
+        size: (u32, u32),
+    ) -> (Tracked<wgpu::Texture>, wgpu::TextureView) {
+        let texture = resources.create_texture(device, &wgpu::TextureDescriptor {
+            label: Some(label),
+            size: wgpu::Extent3d { width: size.0, height: size.1, depth_or_array_layers: 1 },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: REFLECTION_FORMAT,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        }, "ssr");
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        (texture, view)
+    }
+
+    pub fn resize(&mut self, device: &wgpu::Device, resources: &ResourceRegistry, width: u32, height: u32) {
+        let size = (width.max(1), height.max(1));
+        if size == self.size {
+            return;
+        }
+        self.size = size;
+        (self.trace, self.trace_view) = Self::create_target(device, resources, "ssr trace", size);
+        (self.output, self.output_view) = Self::create_target(device, resources, "ssr", size);
+    }
+
+    // Cube view sampled where rays miss; its mip chain is used as a roughness blur
+    pub fn set_environment(&mut self, environment: Option<wgpu::TextureView>) {
+        self.environment = environment;
+    }
+
+    pub fn output_view(&self) -> &wgpu::TextureView {
+        &self.output_view
+    }
+
+    pub fn output_texture(&self) -> &wgpu::Texture {
+        &self.output
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn render(
+        &self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        belt: &mut UploadBelt,
+        bind_groups: &mut BindGroupCache,
+        samplers: &mut SamplerCache,
+        color: &wgpu::TextureView,
+        depth: &wgpu::TextureView,
+        normals: &wgpu::TextureView,
+        view: Mat4,
+        projection: Mat4,
+    ) {
+        let params = SsrParams {
+            projection: projection.to_cols_array(),
+            inv_projection: projection.inverse().to_cols_array(),
+            inv_view: view.inverse().to_cols_array(),
+            screen_size: [self.size.0 as f32, self.size.1 as f32],
+            max_distance: self.settings.max_distance,
+            thickness: self.settings.thickness,
+            max_steps: self.settings.max_steps,
+            max_blur_radius: self.settings.max_blur_radius,
+            _pad: [0; 2],
+        };
+        belt.write(device, encoder, &self.params_buffer, 0, bytemuck::bytes_of(&params));
+
+        let sampler = samplers.get(device, &wgpu::SamplerDescriptor {
+            label: Some("ssr environment"),
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            mipmap_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+        let environment = self.environment.as_ref().unwrap_or(&self.black_environment_view);
+        // Binding 6 is only read by the blur; the trace pass gets the output there so it
+        // never samples the texture it renders to
+        let passes = [
+            ("ssr trace", &self.trace_pipeline, &self.output_view, &self.trace_view),
+            ("ssr blur", &self.blur_pipeline, &self.trace_view, &self.output_view),
+        ];
+        for (label, pipeline, input, target) in passes {
+            let bind_group = bind_groups.get_or_create(device, label, &self.layout, &[
+                (0, BindingKey::buffer(&self.params_buffer)),
+                (1, BindingKey::TextureView(depth.clone())),
+                (2, BindingKey::TextureView(normals.clone())),
+                (3, BindingKey::TextureView(color.clone())),
+                (4, BindingKey::TextureView(environment.clone())),
+                (5, BindingKey::Sampler(sampler.clone())),
+                (6, BindingKey::TextureView(input.clone())),
+            ]);
+            let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some(label),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: target,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                        store: wgpu::StoreOp::Store,
+                    },
+                    depth_slice: None,
+                })],
+                depth_stencil_attachment: None,
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+            pass.set_pipeline(pipeline);
+            pass.set_bind_group(0, &bind_group, &[]);
+            pass.draw(0..3, 0..1);
+        }
+    }
+}
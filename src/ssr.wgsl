@@ -0,0 +1,110 @@
+// Screen-space reflections: marches the reflected view ray against the depth buffer and
+// falls back to the environment cubemap where it misses, then blurs by roughness.
+// Both passes draw one fullscreen triangle.
+
+struct SsrParams {
+    projection: mat4x4<f32>,
+    inv_projection: mat4x4<f32>,
+    inv_view: mat4x4<f32>,
+    screen_size: vec2<f32>,
+    max_distance: f32,
+    thickness: f32,
+    max_steps: u32,
+    max_blur_radius: f32,
+}
+
+@group(0) @binding(0) var<uniform> params: SsrParams;
+@group(0) @binding(1) var depth_texture: texture_depth_2d;
+@group(0) @binding(2) var normal_texture: texture_2d<f32>;
+@group(0) @binding(3) var color_texture: texture_2d<f32>;
+@group(0) @binding(4) var environment: texture_cube<f32>;
+@group(0) @binding(5) var environment_sampler: sampler;
+@group(0) @binding(6) var trace_texture: texture_2d<f32>;
+
+@vertex
+fn vs_fullscreen(@builtin(vertex_index) index: u32) -> @builtin(position) vec4<f32> {
+    let uv = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+    return vec4<f32>(uv * 2.0 - 1.0, 0.0, 1.0);
+}
+
+fn view_position(pixel: vec2<i32>) -> vec3<f32> {
+    let depth = textureLoad(depth_texture, pixel, 0);
+    let uv = (vec2<f32>(pixel) + 0.5) / params.screen_size;
+    let view = params.inv_projection * vec4<f32>(uv.x * 2.0 - 1.0, 1.0 - uv.y * 2.0, depth, 1.0);
+    return view.xyz / view.w;
+}
+
+fn sample_environment(view_direction: vec3<f32>, roughness: f32) -> vec3<f32> {
+    let world = (params.inv_view * vec4<f32>(view_direction, 0.0)).xyz;
+    let max_lod = f32(textureNumLevels(environment) - 1u);
+    return textureSampleLevel(environment, environment_sampler, world, roughness * max_lod).rgb;
+}
+
+// Normal texture: view-space normal in xyz, roughness in w
+@fragment
+fn fs_trace(@builtin(position) frag_coord: vec4<f32>) -> @location(0) vec4<f32> {
+    let pixel = vec2<i32>(frag_coord.xy);
+    if (textureLoad(depth_texture, pixel, 0) >= 1.0) {
+        return vec4<f32>(0.0);
+    }
+    let surface = textureLoad(normal_texture, pixel, 0);
+    let normal = normalize(surface.xyz);
+    let roughness = clamp(surface.w, 0.0, 1.0);
+    let origin = view_position(pixel);
+    let direction = reflect(normalize(origin), normal);
+    let fallback = sample_environment(direction, roughness);
+
+    let max_pixel = vec2<i32>(params.screen_size) - 1;
+    let step_length = params.max_distance / f32(max(params.max_steps, 1u));
+    var hit_color = vec3<f32>(0.0);
+    var confidence = 0.0;
+    for (var i = 1u; i <= params.max_steps; i++) {
+        let point = origin + direction * step_length * f32(i);
+        let clip = params.projection * vec4<f32>(point, 1.0);
+        if (clip.w <= 0.0) {
+            break;
+        }
+        let ndc = clip.xy / clip.w;
+        if (any(abs(ndc) > vec2<f32>(1.0))) {
+            break;
+        }
+        let uv = vec2<f32>(ndc.x * 0.5 + 0.5, 0.5 - ndc.y * 0.5);
+        let sample_pixel = clamp(vec2<i32>(uv * params.screen_size), vec2<i32>(0), max_pixel);
+        let scene_z = view_position(sample_pixel).z;
+        let behind = scene_z - point.z;
+        if (behind > 0.0 && behind < params.thickness) {
+            hit_color = textureLoad(color_texture, sample_pixel, 0).rgb;
+            // Fade hits near the screen border and at the end of the ray, where the
+            // screen-space data runs out
+            let edge = clamp((1.0 - max(abs(ndc.x), abs(ndc.y))) * 8.0, 0.0, 1.0);
+            let distance = 1.0 - f32(i) / f32(params.max_steps);
+            confidence = edge * distance * (1.0 - roughness);
+            break;
+        }
+    }
+    return vec4<f32>(mix(fallback, hit_color, confidence), roughness);
+}
+
+// Wider blur for rougher surfaces approximates the spread of a glossy lobe
+@fragment
+fn fs_blur(@builtin(position) frag_coord: vec4<f32>) -> @location(0) vec4<f32> {
+    let pixel = vec2<i32>(frag_coord.xy);
+    let max_pixel = vec2<i32>(params.screen_size) - 1;
+    let center = textureLoad(trace_texture, pixel, 0);
+    let radius = center.a * params.max_blur_radius;
+    if (radius < 0.5) {
+        return center;
+    }
+    var total = vec3<f32>(0.0);
+    var weight = 0.0;
+    for (var y = -2; y <= 2; y++) {
+        for (var x = -2; x <= 2; x++) {
+            let offset = vec2<f32>(f32(x), f32(y)) * radius * 0.5;
+            let tap = clamp(pixel + vec2<i32>(offset), vec2<i32>(0), max_pixel);
+            let w = exp(-f32(x * x + y * y) * 0.25);
+            total += textureLoad(trace_texture, tap, 0).rgb * w;
+            weight += w;
+        }
+    }
+    return vec4<f32>(total / weight, center.a);
+}
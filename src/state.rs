@@ -0,0 +1,219 @@
+// src/state.rs
+use crate::ecs::world::World;
+use crate::resource_registry::ResourceRegistry;
+use crate::screen_effect::{EffectId, ScreenEffect, ScreenEffectChain};
+use crate::ui::transition::{ScreenTransition, TRANSITION_EFFECT, TRANSITION_EFFECT_ORDER, TRANSITION_EFFECT_WGSL};
+use std::collections::VecDeque;
+
+// A screen of the game, e.g. the title menu, gameplay or a pause menu, kept on the
+// `StateStack`. Every hook gets the world, so states set up and tear down their own UI
+// and entities.
+pub trait GameState {
+    fn name(&self) -> &str;
+
+    fn enter(&mut self, _world: &mut World) {}
+
+    fn exit(&mut self, _world: &mut World) {}
+
+    // Another state was pushed over this one
+    fn pause(&mut self, _world: &mut World) {}
+
+    // The state over this one was popped
+    fn resume(&mut self, _world: &mut World) {}
+
+    // Only the top state updates, once per frame
+    fn update(&mut self, _world: &mut World, _delta_time: f64) {}
+}
+
+enum StateChange {
+    Push(Box<dyn GameState>),
+    Pop,
+    Replace(Box<dyn GameState>),
+}
+
+impl StateChange {
+    fn apply(self, states: &mut Vec<Box<dyn GameState>>, world: &mut World) {
+        match self {
+            StateChange::Push(mut state) => {
+                if let Some(top) = states.last_mut() {
+                    top.pause(world);
+                }
+                log::debug!("Entering state '{}'", state.name());
+                state.enter(world);
+                states.push(state);
+            }
+            StateChange::Pop => {
+                if let Some(mut state) = states.pop() {
+                    log::debug!("Leaving state '{}'", state.name());
+                    state.exit(world);
+                }
+                if let Some(top) = states.last_mut() {
+                    top.resume(world);
+                }
+            }
+            StateChange::Replace(mut state) => {
+                if let Some(mut old) = states.pop() {
+                    log::debug!("Leaving state '{}'", old.name());
+                    old.exit(world);
+                }
+                log::debug!("Entering state '{}'", state.name());
+                state.enter(world);
+                states.push(state);
+            }
+        }
+    }
+}
+
+struct Pending {
+    change: StateChange,
+    transition: Option<ScreenTransition>,
+}
+
+struct ActiveTransition {
+    transition: ScreenTransition,
+    elapsed: f32,
+    // Applied at the midpoint, while the screen is covered
+    change: Option<StateChange>,
+}
+
+// Resource holding the game's states, topmost active. Changes are queued and applied by
+// `update_states`, one at a time; one made with a transition waits for the screen to be
+// covered, and later changes wait for the transition to finish.
+#[derive(Default)]
+pub struct StateStack {
+    states: Vec<Box<dyn GameState>>,
+    pending: VecDeque<Pending>,
+    transition: Option<ActiveTransition>,
+}
+
+impl StateStack {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, state: impl GameState + 'static) {
+        self.queue(StateChange::Push(Box::new(state)), None);
+    }
+
+    pub fn push_with(&mut self, state: impl GameState + 'static, transition: ScreenTransition) {
+        self.queue(StateChange::Push(Box::new(state)), Some(transition));
+    }
+
+    pub fn pop(&mut self) {
+        self.queue(StateChange::Pop, None);
+    }
+
+    pub fn pop_with(&mut self, transition: ScreenTransition) {
+        self.queue(StateChange::Pop, Some(transition));
+    }
+
+    // Swaps the top state without resuming the one under it
+    pub fn replace(&mut self, state: impl GameState + 'static) {
+        self.queue(StateChange::Replace(Box::new(state)), None);
+    }
+
+    pub fn replace_with(&mut self, state: impl GameState + 'static, transition: ScreenTransition) {
+        self.queue(StateChange::Replace(Box::new(state)), Some(transition));
+    }
+
+    // `replace` for a state already boxed, e.g. one held by another state
+    pub fn replace_boxed(&mut self, state: Box<dyn GameState>, transition: Option<ScreenTransition>) {
+        self.queue(StateChange::Replace(state), transition);
+    }
+
+    fn queue(&mut self, change: StateChange, transition: Option<ScreenTransition>) {
+        self.pending.push_back(Pending { change, transition });
+    }
+
+    // Bottom first. Empty while `update_states` runs the states' hooks, which have the
+    // stack lent out.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.states.iter().map(|state| state.name())
+    }
+
+    pub fn top(&self) -> Option<&str> {
+        self.states.last().map(|state| state.name())
+    }
+
+    pub fn len(&self) -> usize {
+        self.states.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.states.is_empty()
+    }
+
+    pub fn has_pending(&self) -> bool {
+        !self.pending.is_empty()
+    }
+
+    pub fn is_transitioning(&self) -> bool {
+        self.transition.is_some()
+    }
+
+    // Shows the running transition in `effect`, compiled from `TRANSITION_EFFECT_WGSL`, and
+    // disables it otherwise; call once a frame
+    pub fn apply_transition(&self, effect: &mut ScreenEffect) {
+        effect.enabled = self.transition.is_some();
+        if let Some(active) = &self.transition {
+            effect.params = active.transition.effect_params(active.elapsed);
+        }
+    }
+
+    // Adds the transition effect to `chain` the first time, then `apply_transition`s it
+    pub fn apply_transition_effect(
+        &self,
+        chain: &mut ScreenEffectChain,
+        device: &wgpu::Device,
+        resources: &ResourceRegistry,
+    ) -> Result<EffectId, String> {
+        let id = match chain.find(TRANSITION_EFFECT) {
+            Some(id) => id,
+            None => chain.add(device, resources, TRANSITION_EFFECT, TRANSITION_EFFECT_ORDER, TRANSITION_EFFECT_WGSL)?,
+        };
+        if let Some(effect) = chain.get_mut(id) {
+            self.apply_transition(effect);
+        }
+        Ok(id)
+    }
+
+    // Moves the transition on by `step` and returns the next change that is due
+    fn next_change(&mut self, step: &mut f32) -> Option<StateChange> {
+        loop {
+            if let Some(active) = &mut self.transition {
+                active.elapsed += std::mem::take(step);
+                if active.elapsed >= active.transition.duration * 0.5 {
+                    if let Some(change) = active.change.take() {
+                        return Some(change);
+                    }
+                }
+                if active.elapsed < active.transition.duration {
+                    return None;
+                }
+                self.transition = None;
+            }
+            let pending = self.pending.pop_front()?;
+            match pending.transition {
+                Some(transition) if transition.duration > 0.0 => {
+                    self.transition = Some(ActiveTransition { transition, elapsed: 0.0, change: Some(pending.change) });
+                }
+                _ => return Some(pending.change),
+            }
+        }
+    }
+}
+
+// System applying queued state changes and updating the top state
+pub fn update_states(world: &mut World, delta_time: f64) {
+    let Some(stack) = world.resources.get_mut::<StateStack>() else { return };
+    // Taken out so hooks can reach the world, including the stack to queue changes
+    let mut states = std::mem::take(&mut stack.states);
+    let mut step = delta_time as f32;
+    while let Some(change) = world.resources.get_mut::<StateStack>().and_then(|stack| stack.next_change(&mut step)) {
+        change.apply(&mut states, world);
+    }
+    if let Some(top) = states.last_mut() {
+        top.update(world, delta_time);
+    }
+    world.resources.get_or_insert_with(StateStack::new).states = states;
+}
@@ -0,0 +1,328 @@
+// src/steam.rs
+use crate::ecs::world::World;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::PathBuf;
+
+// Platform services through Steamworks when the `steam` feature is on. `Steam::offline` is
+// the stub used without the feature or when Steam isn't running: achievements and rich
+// presence are kept in memory only, so game code calls the same methods either way.
+pub struct Steam {
+    app_id: u32,
+    #[cfg(feature = "steam")]
+    api: Option<native::SteamApi>,
+    achievements: BTreeSet<String>,
+    rich_presence: BTreeMap<String, String>,
+    // Achievements changed since the last `StoreStats`
+    stats_dirty: bool,
+}
+
+impl Steam {
+    // Connects to the running Steam client. Fails if the feature is off, the Steamworks
+    // library can't be found next to the executable, or Steam isn't running; fall back to
+    // `offline` then.
+    pub fn init(app_id: u32) -> Result<Self, String> {
+        #[cfg(feature = "steam")]
+        {
+            let api = native::SteamApi::load(app_id)?;
+            let mut steam = Self::offline(app_id);
+            steam.api = Some(api);
+            Ok(steam)
+        }
+        #[cfg(not(feature = "steam"))]
+        Err(format!("Steam support for app {} is not built in (enable the `steam` feature)", app_id))
+    }
+
+    pub fn offline(app_id: u32) -> Self {
+        Self {
+            app_id,
+            #[cfg(feature = "steam")]
+            api: None,
+            achievements: BTreeSet::new(),
+            rich_presence: BTreeMap::new(),
+            stats_dirty: false,
+        }
+    }
+
+    // `init` or `offline` as available, logging why Steam is unavailable
+    pub fn init_or_offline(app_id: u32) -> Self {
+        Self::init(app_id).unwrap_or_else(|e| {
+            log::info!("Running without Steam: {}", e);
+            Self::offline(app_id)
+        })
+    }
+
+    pub fn app_id(&self) -> u32 {
+        self.app_id
+    }
+
+    pub fn is_running(&self) -> bool {
+        #[cfg(feature = "steam")]
+        return self.api.is_some();
+        #[cfg(not(feature = "steam"))]
+        false
+    }
+
+    // The signed-in user's SteamID64
+    pub fn user_id(&self) -> Option<u64> {
+        #[cfg(feature = "steam")]
+        return self.api.as_ref().map(native::SteamApi::user_id).filter(|id| *id != 0);
+        #[cfg(not(feature = "steam"))]
+        None
+    }
+
+    // `name` is the API name set up in the Steamworks partner site. Stored with the next
+    // `run_callbacks`, so unlocking several at once sends one update.
+    pub fn unlock_achievement(&mut self, name: &str) -> Result<(), String> {
+        #[cfg(feature = "steam")]
+        if let Some(api) = &self.api {
+            api.set_achievement(name)?;
+        }
+        if self.achievements.insert(name.to_string()) {
+            self.stats_dirty = true;
+        }
+        Ok(())
+    }
+
+    // For testing; players can't re-lock achievements
+    pub fn clear_achievement(&mut self, name: &str) -> Result<(), String> {
+        #[cfg(feature = "steam")]
+        if let Some(api) = &self.api {
+            api.clear_achievement(name)?;
+        }
+        self.achievements.remove(name);
+        self.stats_dirty = true;
+        Ok(())
+    }
+
+    pub fn is_achieved(&self, name: &str) -> bool {
+        #[cfg(feature = "steam")]
+        if let Some(achieved) = self.api.as_ref().and_then(|api| api.achievement(name)) {
+            return achieved;
+        }
+        self.achievements.contains(name)
+    }
+
+    // Shown on the friends list. Steam displays "steam_display" through the game's
+    // localization tokens; other keys are free-form, e.g. "status" or "steam_player_group".
+    pub fn set_rich_presence(&mut self, key: &str, value: &str) -> Result<(), String> {
+        #[cfg(feature = "steam")]
+        if let Some(api) = &self.api {
+            api.set_rich_presence(key, value)?;
+        }
+        self.rich_presence.insert(key.to_string(), value.to_string());
+        Ok(())
+    }
+
+    pub fn clear_rich_presence(&mut self) {
+        #[cfg(feature = "steam")]
+        if let Some(api) = &self.api {
+            api.clear_rich_presence();
+        }
+        self.rich_presence.clear();
+    }
+
+    pub fn rich_presence(&self, key: &str) -> Option<&str> {
+        self.rich_presence.get(key).map(String::as_str)
+    }
+
+    // Where saves go so Steam Auto-Cloud syncs them: the user's data directory, then
+    // `game`, then the SteamID (or "local" offline) so players sharing a machine don't
+    // overwrite each other. Point the Auto-Cloud root at the `game` directory.
+    pub fn cloud_save_dir(&self, game: &str) -> PathBuf {
+        let user = self.user_id().map_or("local".to_string(), |id| id.to_string());
+        user_data_dir().join(game).join(user)
+    }
+
+    // Pumps Steam's callbacks and stores pending achievements; call every frame
+    pub fn run_callbacks(&mut self) {
+        #[cfg(feature = "steam")]
+        if let Some(api) = &self.api {
+            api.run_callbacks();
+            if self.stats_dirty && !api.store_stats() {
+                log::warn!("Steam refused to store stats; retrying next frame");
+                return;
+            }
+        }
+        self.stats_dirty = false;
+    }
+}
+
+// Per-user application data: %APPDATA% on Windows, Application Support on macOS and
+// $XDG_DATA_HOME (or ~/.local/share) elsewhere
+fn user_data_dir() -> PathBuf {
+    let home = || std::env::var_os("HOME").map(PathBuf::from).unwrap_or_default();
+    if cfg!(windows) {
+        std::env::var_os("APPDATA").map(PathBuf::from).unwrap_or_else(home)
+    } else if cfg!(target_os = "macos") {
+        home().join("Library/Application Support")
+    } else {
+        std::env::var_os("XDG_DATA_HOME").map(PathBuf::from).unwrap_or_else(|| home().join(".local/share"))
+    }
+}
+
+// Run in `PreUpdate`; does nothing without a `Steam` resource
+pub fn run_steam_callbacks(world: &mut World, _delta_time: f64) {
+    if let Some(steam) = world.resources.get_mut::<Steam>() {
+        steam.run_callbacks();
+    }
+}
+
+// Steamworks' flat C API, loaded at runtime so building needs no SDK. Ship the SDK's
+// redistributable library next to the executable.
+#[cfg(feature = "steam")]
+mod native {
+    use std::ffi::{c_char, c_void, CString};
+
+    #[cfg(windows)]
+    const LIBRARY: &str = "steam_api64.dll";
+    #[cfg(target_os = "macos")]
+    const LIBRARY: &str = "libsteam_api.dylib";
+    #[cfg(all(not(windows), not(target_os = "macos")))]
+    const LIBRARY: &str = "libsteam_api.so";
+
+    // Interface accessors are versioned; newest first, covering SDK 1.53 onwards
+    const USER_STATS: [&[u8]; 2] = [b"SteamAPI_SteamUserStats_v013\0", b"SteamAPI_SteamUserStats_v012\0"];
+    const FRIENDS: [&[u8]; 1] = [b"SteamAPI_SteamFriends_v017\0"];
+    const USER: [&[u8]; 2] = [b"SteamAPI_SteamUser_v023\0", b"SteamAPI_SteamUser_v021\0"];
+
+    type Accessor = unsafe extern "C" fn() -> *mut c_void;
+
+    pub struct SteamApi {
+        user_stats: *mut c_void,
+        friends: *mut c_void,
+        user: *mut c_void,
+        run_callbacks: unsafe extern "C" fn(),
+        shutdown: unsafe extern "C" fn(),
+        set_achievement: unsafe extern "C" fn(*mut c_void, *const c_char) -> bool,
+        clear_achievement: unsafe extern "C" fn(*mut c_void, *const c_char) -> bool,
+        get_achievement: unsafe extern "C" fn(*mut c_void, *const c_char, *mut bool) -> bool,
+        store_stats: unsafe extern "C" fn(*mut c_void) -> bool,
+        set_rich_presence: unsafe extern "C" fn(*mut c_void, *const c_char, *const c_char) -> bool,
+        clear_rich_presence: unsafe extern "C" fn(*mut c_void),
+        get_steam_id: unsafe extern "C" fn(*mut c_void) -> u64,
+        // Dropped last; the function pointers point into it
+        _library: libloading::Library,
+    }
+
+    fn c_string(text: &str) -> Result<CString, String> {
+        CString::new(text).map_err(|_| format!("'{}' contains a NUL byte", text.escape_debug()))
+    }
+
+    impl SteamApi {
+        pub fn load(app_id: u32) -> Result<Self, String> {
+            // Lets init find the app without a steam_appid.txt during development
+            std::env::set_var("SteamAppId", app_id.to_string());
+            // Loading runs the library's initializers; it must be Valve's redistributable
+            let library = unsafe { libloading::Library::new(LIBRARY) }.map_err(|e| format!("Failed to load {}: {}", LIBRARY, e))?;
+            unsafe {
+                macro_rules! symbol {
+                    ($name:literal) => {
+                        *library.get($name).map_err(|e| format!("{} has no {}: {}", LIBRARY, String::from_utf8_lossy(&$name[..$name.len() - 1]), e))?
+                    };
+                }
+                let accessor = |names: &[&[u8]]| -> Result<*mut c_void, String> {
+                    let get = names.iter().find_map(|name| library.get::<Accessor>(name).ok().map(|symbol| *symbol));
+                    let pointer = get.map(|get| get()).unwrap_or(std::ptr::null_mut());
+                    if pointer.is_null() {
+                        return Err(format!("{} has no usable {}", LIBRARY, String::from_utf8_lossy(names[0]).trim_end_matches('\0')));
+                    }
+                    Ok(pointer)
+                };
+
+                // SDK 1.59 replaced `SteamAPI_Init` with `SteamAPI_InitFlat`, which explains failures
+                let init_flat: Result<libloading::Symbol<unsafe extern "C" fn(*mut [c_char; 1024]) -> i32>, _> = library.get(b"SteamAPI_InitFlat\0");
+                if let Ok(init_flat) = init_flat {
+                    let mut message = [0 as c_char; 1024];
+                    if init_flat(&mut message) != 0 {
+                        let message = std::ffi::CStr::from_ptr(message.as_ptr()).to_string_lossy().into_owned();
+                        return Err(format!("Steam failed to initialize: {}", message));
+                    }
+                } else {
+                    let init: unsafe extern "C" fn() -> bool = symbol!(b"SteamAPI_Init\0");
+                    if !init() {
+                        return Err("Steam failed to initialize (is the client running?)".to_string());
+                    }
+                }
+
+                let shutdown: unsafe extern "C" fn() = symbol!(b"SteamAPI_Shutdown\0");
+                let interfaces = (accessor(&USER_STATS), accessor(&FRIENDS), accessor(&USER));
+                let (user_stats, friends, user) = match interfaces {
+                    (Ok(user_stats), Ok(friends), Ok(user)) => (user_stats, friends, user),
+                    (Err(e), _, _) | (_, Err(e), _) | (_, _, Err(e)) => {
+                        shutdown();
+                        return Err(e);
+                    }
+                };
+                Ok(Self {
+                    user_stats,
+                    friends,
+                    user,
+                    run_callbacks: symbol!(b"SteamAPI_RunCallbacks\0"),
+                    shutdown,
+                    set_achievement: symbol!(b"SteamAPI_ISteamUserStats_SetAchievement\0"),
+                    clear_achievement: symbol!(b"SteamAPI_ISteamUserStats_ClearAchievement\0"),
+                    get_achievement: symbol!(b"SteamAPI_ISteamUserStats_GetAchievement\0"),
+                    store_stats: symbol!(b"SteamAPI_ISteamUserStats_StoreStats\0"),
+                    set_rich_presence: symbol!(b"SteamAPI_ISteamFriends_SetRichPresence\0"),
+                    clear_rich_presence: symbol!(b"SteamAPI_ISteamFriends_ClearRichPresence\0"),
+                    get_steam_id: symbol!(b"SteamAPI_ISteamUser_GetSteamID\0"),
+                    _library: library,
+                })
+            }
+        }
+
+        pub fn run_callbacks(&self) {
+            unsafe { (self.run_callbacks)() }
+        }
+
+        pub fn user_id(&self) -> u64 {
+            unsafe { (self.get_steam_id)(self.user) }
+        }
+
+        pub fn set_achievement(&self, name: &str) -> Result<(), String> {
+            let name_c = c_string(name)?;
+            if !unsafe { (self.set_achievement)(self.user_stats, name_c.as_ptr()) } {
+                return Err(format!("Steam has no achievement '{}'", name));
+            }
+            Ok(())
+        }
+
+        pub fn clear_achievement(&self, name: &str) -> Result<(), String> {
+            let name_c = c_string(name)?;
+            if !unsafe { (self.clear_achievement)(self.user_stats, name_c.as_ptr()) } {
+                return Err(format!("Steam has no achievement '{}'", name));
+            }
+            Ok(())
+        }
+
+        // `None` if Steam doesn't know the achievement or hasn't loaded stats yet
+        pub fn achievement(&self, name: &str) -> Option<bool> {
+            let name = c_string(name).ok()?;
+            let mut achieved = false;
+            unsafe { (self.get_achievement)(self.user_stats, name.as_ptr(), &mut achieved) }.then_some(achieved)
+        }
+
+        pub fn store_stats(&self) -> bool {
+            unsafe { (self.store_stats)(self.user_stats) }
+        }
+
+        pub fn set_rich_presence(&self, key: &str, value: &str) -> Result<(), String> {
+            let (key_c, value_c) = (c_string(key)?, c_string(value)?);
+            // Fails for over-long values or too many keys
+            if !unsafe { (self.set_rich_presence)(self.friends, key_c.as_ptr(), value_c.as_ptr()) } {
+                return Err(format!("Steam rejected rich presence {} = '{}'", key, value));
+            }
+            Ok(())
+        }
+
+        pub fn clear_rich_presence(&self) {
+            unsafe { (self.clear_rich_presence)(self.friends) }
+        }
+    }
+
+    impl Drop for SteamApi {
+        fn drop(&mut self) {
+            unsafe { (self.shutdown)() }
+        }
+    }
+}
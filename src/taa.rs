@@ -0,0 +1,335 @@
+// src/taa.rs
+use crate::bind_cache::{BindGroupCache, BindingKey, SamplerCache};
+use crate::resource_registry::{ResourceRegistry, Tracked};
+use crate::upload::UploadBelt;
+use glam::{Mat4, Vec2, Vec3};
+
+// Prepend to entity shaders that write into `TaaPass::velocity_view`
+pub const MOTION_VECTOR_WGSL: &str = include_str!("taa_motion.wgsl");
+pub const VELOCITY_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rg16Float;
+// Length of the jitter pattern before it repeats
+const JITTER_PHASES: u32 = 8;
+
+// Mirrors `TaaParams` in taa.wgsl and taa_velocity.wgsl
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct TaaParams {
+    inv_view_proj: [f32; 16],
+    prev_view_proj: [f32; 16],
+    jitter: [f32; 2],
+    screen_size: [f32; 2],
+    blend: f32,
+    reset: u32,
+    _pad: [u32; 2],
+}
+
+fn halton(mut index: u32, base: u32) -> f32 {
+    let (mut result, mut fraction) = (0.0, 1.0);
+    while index > 0 {
+        fraction /= base as f32;
+        result += fraction * (index % base) as f32;
+        index /= base;
+    }
+    result
+}
+
+// Sub-pixel offset in pixels, in [-0.5, 0.5), for `frame`
+pub fn jitter_offset(frame: u32) -> Vec2 {
+    let index = frame % JITTER_PHASES + 1;
+    Vec2::new(halton(index, 2), halton(index, 3)) - 0.5
+}
+
+struct Target {
+    texture: Tracked<wgpu::Texture>,
+    view: wgpu::TextureView,
+}
+
+impl Target {
+    fn new(device: &wgpu::Device, resources: &ResourceRegistry, label: &str, format: wgpu::TextureFormat, size: (u32, u32)) -> Self {
+        let texture = resources.create_texture(device, &wgpu::TextureDescriptor {
+            label: Some(label),
+            size: wgpu::Extent3d { width: size.0, height: size.1, depth_or_array_layers: 1 },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        }, "taa");
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        Self { texture, view }
+    }
+}
+
+// Temporal anti-aliasing for the 3D path. Per frame:
+// 1. draw the scene with `jittered_projection`,
+// 2. `render_velocity` fills camera motion from depth,
+// 3. moving entities draw their own motion (see `MOTION_VECTOR_WGSL`) into `velocity_view`,
+// 4. `resolve` blends with history; `output_view` holds the anti-aliased frame.
+pub struct TaaPass {
+    // Weight of the current frame; lower is smoother but slower to react
+    pub blend: f32,
+    color_format: wgpu::TextureFormat,
+    size: (u32, u32),
+    velocity: Target,
+    history: [Target; 2],
+    // Which history target `resolve` writes this frame
+    current: usize,
+    frame: u32,
+    prev_view_proj: Option<Mat4>,
+    params_buffer: Tracked<wgpu::Buffer>,
+    velocity_layout: wgpu::BindGroupLayout,
+    resolve_layout: wgpu::BindGroupLayout,
+    velocity_pipeline: wgpu::RenderPipeline,
+    resolve_pipeline: wgpu::RenderPipeline,
+}
+
+impl TaaPass {
+    pub fn new(
+        device: &wgpu::Device,
+        resources: &ResourceRegistry,
+        color_format: wgpu::TextureFormat,
+        width: u32,
+        height: u32,
+    ) -> Self {
+        let uniform_entry = wgpu::BindGroupLayoutEntry {
+            binding: 0,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Uniform,
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        };
+        let texture_entry = |binding, sample_type| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Texture {
+                sample_type,
+                view_dimension: wgpu::TextureViewDimension::D2,
+                multisampled: false,
+            },
+            count: None,
+        };
+        let velocity_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("taa velocity"),
+            entries: &[uniform_entry, texture_entry(1, wgpu::TextureSampleType::Depth)],
+        });
+        let resolve_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("taa resolve"),
+            entries: &[
+                uniform_entry,
+                texture_entry(1, wgpu::TextureSampleType::Float { filterable: false }),
+                texture_entry(2, wgpu::TextureSampleType::Float { filterable: false }),
+                texture_entry(3, wgpu::TextureSampleType::Float { filterable: true }),
+                wgpu::BindGroupLayoutEntry {
+                    binding: 4,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+        });
+
+        let pipeline = |label, shader: &wgpu::ShaderModule, layout: &wgpu::BindGroupLayout, entry_point, format| {
+            let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some(label),
+                bind_group_layouts: &[layout],
+                push_constant_ranges: &[],
+            });
+            device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some(label),
+                layout: Some(&pipeline_layout),
+                vertex: wgpu::VertexState {
+                    module: shader,
+                    entry_point: Some("vs_fullscreen"),
+                    buffers: &[],
+                    compilation_options: Default::default(),
+                },
+                fragment: Some(wgpu::FragmentState {
+                    module: shader,
+                    entry_point: Some(entry_point),
+                    targets: &[Some(wgpu::ColorTargetState {
+                        format,
+                        blend: None,
+                        write_mask: wgpu::ColorWrites::ALL,
+                    })],
+                    compilation_options: Default::default(),
+                }),
+                primitive: wgpu::PrimitiveState::default(),
+                depth_stencil: None,
+                multisample: wgpu::MultisampleState::default(),
+                multiview: None,
+                cache: None,
+            })
+        };
+        let velocity_shader = device.create_shader_module(wgpu::include_wgsl!("taa_velocity.wgsl"));
+        let resolve_shader = device.create_shader_module(wgpu::include_wgsl!("taa.wgsl"));
+        let velocity_pipeline = pipeline("taa velocity", &velocity_shader, &velocity_layout, "fs_velocity", VELOCITY_FORMAT);
+        let resolve_pipeline = pipeline("taa resolve", &resolve_shader, &resolve_layout, "fs_resolve", color_format);
+
+        let params_buffer = resources.create_buffer(device, &wgpu::BufferDescriptor {
+            label: Some("taa params"),
+            size: std::mem::size_of::<TaaParams>() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        }, "taa");
+
+        let size = (width.max(1), height.max(1));
+        Self {
+            blend: 0.1,
+            color_format,
+            size,
+            velocity: Target::new(device, resources, "taa velocity", VELOCITY_FORMAT, size),
+            history: [
+                Target::new(device, resources, "taa history", color_format, size),
+                Target::new(device, resources, "taa history", color_format, size),
+            ],
+            current: 0,
+            frame: 0,
+            prev_view_proj: None,
+            params_buffer,
+            velocity_layout,
+            resolve_layout,
+            velocity_pipeline,
+            resolve_pipeline,
+        }
+    }
+
+    pub fn resize(&mut self, device: &wgpu::Device, resources: &ResourceRegistry, width: u32, height: u32) {
+        let size = (width.max(1), height.max(1));
+        if size == self.size {
+            return;
+        }
+        self.size = size;
+        self.velocity = Target::new(device, resources, "taa velocity", VELOCITY_FORMAT, size);
+        self.history = [
+            Target::new(device, resources, "taa history", self.color_format, size),
+            Target::new(device, resources, "taa history", self.color_format, size),
+        ];
+        self.reset();
+    }
+
+    // Drops history, e.g. on camera cuts where reprojection would smear the old view
+    pub fn reset(&mut self) {
+        self.prev_view_proj = None;
+    }
+
+    // This frame's jitter in pixels
+    pub fn jitter(&self) -> Vec2 {
+        jitter_offset(self.frame)
+    }
+
+    // Offsets `projection` by this frame's sub-pixel jitter; draw the scene with this one
+    pub fn jittered_projection(&self, projection: Mat4) -> Mat4 {
+        let offset = self.jitter() * 2.0 / Vec2::new(self.size.0 as f32, self.size.1 as f32);
+        Mat4::from_translation(Vec3::new(offset.x, -offset.y, 0.0)) * projection
+    }
+
+    pub fn velocity_view(&self) -> &wgpu::TextureView {
+        &self.velocity.view
+    }
+
+    // Anti-aliased result of the last `resolve`
+    pub fn output_view(&self) -> &wgpu::TextureView {
+        &self.history[self.current].view
+    }
+
+    pub fn output_texture(&self) -> &wgpu::Texture {
+        &self.history[self.current].texture
+    }
+
+    // `view_proj` is this frame's unjittered camera matrix; `depth` the scene depth buffer
+    pub fn render_velocity(
+        &mut self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        belt: &mut UploadBelt,
+        bind_groups: &mut BindGroupCache,
+        view_proj: Mat4,
+        depth: &wgpu::TextureView,
+    ) {
+        let params = TaaParams {
+            inv_view_proj: view_proj.inverse().to_cols_array(),
+            prev_view_proj: self.prev_view_proj.unwrap_or(view_proj).to_cols_array(),
+            jitter: self.jitter().into(),
+            screen_size: [self.size.0 as f32, self.size.1 as f32],
+            blend: self.blend.clamp(0.0, 1.0),
+            reset: self.prev_view_proj.is_none() as u32,
+            _pad: [0; 2],
+        };
+        belt.write(device, encoder, &self.params_buffer, 0, bytemuck::bytes_of(&params));
+        self.prev_view_proj = Some(view_proj);
+
+        let bind_group = bind_groups.get_or_create(device, "taa velocity", &self.velocity_layout, &[
+            (0, BindingKey::buffer(&self.params_buffer)),
+            (1, BindingKey::TextureView(depth.clone())),
+        ]);
+        let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("taa velocity"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: &self.velocity.view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                    store: wgpu::StoreOp::Store,
+                },
+                depth_slice: None,
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        pass.set_pipeline(&self.velocity_pipeline);
+        pass.set_bind_group(0, &bind_group, &[]);
+        pass.draw(0..3, 0..1);
+    }
+
+    // Blends the jittered `color` into history and advances the jitter sequence
+    pub fn resolve(
+        &mut self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        bind_groups: &mut BindGroupCache,
+        samplers: &mut SamplerCache,
+        color: &wgpu::TextureView,
+    ) {
+        let previous = self.current;
+        self.current = 1 - self.current;
+        let sampler = samplers.get(device, &wgpu::SamplerDescriptor {
+            label: Some("taa history"),
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+        let bind_group = bind_groups.get_or_create(device, "taa resolve", &self.resolve_layout, &[
+            (0, BindingKey::buffer(&self.params_buffer)),
+            (1, BindingKey::TextureView(color.clone())),
+            (2, BindingKey::TextureView(self.velocity.view.clone())),
+            (3, BindingKey::TextureView(self.history[previous].view.clone())),
+            (4, BindingKey::Sampler(sampler)),
+        ]);
+        {
+            let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("taa resolve"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &self.history[self.current].view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                        store: wgpu::StoreOp::Store,
+                    },
+                    depth_slice: None,
+                })],
+                depth_stencil_attachment: None,
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+            pass.set_pipeline(&self.resolve_pipeline);
+            pass.set_bind_group(0, &bind_group, &[]);
+            pass.draw(0..3, 0..1);
+        }
+        self.frame = self.frame.wrapping_add(1);
+    }
+}
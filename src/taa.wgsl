@@ -0,0 +1,49 @@
+// TAA resolve: reprojects last frame's history along the motion vectors and blends in
+// the current jittered frame, clamping history to the local colour range to reject ghosts
+
+struct TaaParams {
+    inv_view_proj: mat4x4<f32>,
+    prev_view_proj: mat4x4<f32>,
+    jitter: vec2<f32>,
+    screen_size: vec2<f32>,
+    blend: f32,
+    reset: u32,
+}
+
+@group(0) @binding(0) var<uniform> params: TaaParams;
+@group(0) @binding(1) var color_texture: texture_2d<f32>;
+@group(0) @binding(2) var velocity_texture: texture_2d<f32>;
+@group(0) @binding(3) var history_texture: texture_2d<f32>;
+@group(0) @binding(4) var history_sampler: sampler;
+
+@vertex
+fn vs_fullscreen(@builtin(vertex_index) index: u32) -> @builtin(position) vec4<f32> {
+    let uv = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+    return vec4<f32>(uv * 2.0 - 1.0, 0.0, 1.0);
+}
+
+@fragment
+fn fs_resolve(@builtin(position) frag_coord: vec4<f32>) -> @location(0) vec4<f32> {
+    let pixel = vec2<i32>(frag_coord.xy);
+    let max_pixel = vec2<i32>(params.screen_size) - 1;
+    let current = textureLoad(color_texture, pixel, 0);
+
+    var low = current.rgb;
+    var high = current.rgb;
+    for (var y = -1; y <= 1; y++) {
+        for (var x = -1; x <= 1; x++) {
+            let tap = textureLoad(color_texture, clamp(pixel + vec2<i32>(x, y), vec2<i32>(0), max_pixel), 0).rgb;
+            low = min(low, tap);
+            high = max(high, tap);
+        }
+    }
+
+    let uv = frag_coord.xy / params.screen_size;
+    let prev_uv = uv - textureLoad(velocity_texture, pixel, 0).xy;
+    let off_screen = any(prev_uv < vec2<f32>(0.0)) || any(prev_uv > vec2<f32>(1.0));
+    if (params.reset != 0u || off_screen) {
+        return current;
+    }
+    let history = textureSampleLevel(history_texture, history_sampler, prev_uv, 0.0).rgb;
+    return vec4<f32>(mix(clamp(history, low, high), current.rgb, params.blend), current.a);
+}
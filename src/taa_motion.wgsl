@@ -0,0 +1,9 @@
+// Motion vector helper for shaders that draw moving entities into the TAA velocity
+// target; see `MOTION_VECTOR_WGSL`
+
+// Screen-space motion in UV units from this frame's and last frame's unjittered clip positions
+fn motion_vector(clip: vec4<f32>, prev_clip: vec4<f32>) -> vec2<f32> {
+    let ndc = clip.xy / clip.w;
+    let prev_ndc = prev_clip.xy / prev_clip.w;
+    return (ndc - prev_ndc) * vec2<f32>(0.5, -0.5);
+}
@@ -0,0 +1,30 @@
+// Camera motion vectors from the depth buffer: where each pixel's surface was on screen
+// last frame, for geometry that didn't move on its own
+
+struct TaaParams {
+    inv_view_proj: mat4x4<f32>,
+    prev_view_proj: mat4x4<f32>,
+    jitter: vec2<f32>,
+    screen_size: vec2<f32>,
+    blend: f32,
+    reset: u32,
+}
+
+@group(0) @binding(0) var<uniform> params: TaaParams;
+@group(0) @binding(1) var depth_texture: texture_depth_2d;
+
+@vertex
+fn vs_fullscreen(@builtin(vertex_index) index: u32) -> @builtin(position) vec4<f32> {
+    let uv = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+    return vec4<f32>(uv * 2.0 - 1.0, 0.0, 1.0);
+}
+
+@fragment
+fn fs_velocity(@builtin(position) frag_coord: vec4<f32>) -> @location(0) vec2<f32> {
+    let depth = textureLoad(depth_texture, vec2<i32>(frag_coord.xy), 0);
+    let uv = frag_coord.xy / params.screen_size;
+    let world = params.inv_view_proj * vec4<f32>(uv.x * 2.0 - 1.0, 1.0 - uv.y * 2.0, depth, 1.0);
+    let previous = params.prev_view_proj * (world / world.w);
+    let prev_uv = vec2<f32>(previous.x / previous.w * 0.5 + 0.5, 0.5 - previous.y / previous.w * 0.5);
+    return uv - prev_uv;
+}
@@ -0,0 +1,321 @@
+// src/telemetry.rs
+use crate::ecs::world::World;
+use crate::net::http::HttpEndpoint;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TelemetryEvent {
+    pub name: String,
+    // Unix milliseconds
+    pub timestamp: u64,
+    pub session: String,
+    pub fields: BTreeMap<String, Value>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TelemetryConfig {
+    // Batches are POSTed here as `{"events": [...]}`; plain http:// only
+    pub endpoint: String,
+    // Sent once this many events are waiting
+    pub batch_size: usize,
+    // And at least this often
+    pub flush_interval: Duration,
+    // Batches that couldn't be sent wait here for the next successful post
+    pub spool_dir: PathBuf,
+    // Oldest spooled batches are dropped past this
+    pub max_spool_bytes: u64,
+    // Summarize frame times into a "performance" event every flush
+    pub frame_stats: bool,
+}
+
+impl TelemetryConfig {
+    pub fn new(endpoint: &str, spool_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            endpoint: endpoint.to_string(),
+            batch_size: 50,
+            flush_interval: Duration::from_secs(30),
+            spool_dir: spool_dir.into(),
+            max_spool_bytes: 4 * 1024 * 1024,
+            frame_stats: true,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct Batch<'a> {
+    events: &'a [TelemetryEvent],
+}
+
+enum Upload {
+    Batch(Vec<TelemetryEvent>),
+    Shutdown,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct FrameStats {
+    count: u32,
+    total: f64,
+    min: f64,
+    max: f64,
+}
+
+// Opt-in gameplay and performance telemetry. Nothing is recorded, kept or sent until the
+// player opts in; `opt_out` stops the upload thread and deletes everything not yet sent,
+// including the spool. Events are batched and posted on a background thread; batches that
+// fail (e.g. offline) are spooled to disk and retried after the next successful post.
+pub struct Telemetry {
+    config: TelemetryConfig,
+    session: String,
+    pending: Vec<TelemetryEvent>,
+    // Seconds since the last flush
+    since_flush: f64,
+    frames: FrameStats,
+    uploader: Option<(Sender<Upload>, JoinHandle<()>)>,
+    // Set by `opt_out`; the upload thread checks it before every request
+    opted_out: Arc<AtomicBool>,
+}
+
+fn unix_millis() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |since| since.as_millis() as u64)
+}
+
+impl Telemetry {
+    // Starts opted out
+    pub fn new(config: TelemetryConfig) -> Self {
+        // Unique enough to group one run's events without identifying the player
+        let session = format!("{:x}-{:x}", unix_millis(), std::process::id());
+        Self {
+            config,
+            session,
+            pending: Vec::new(),
+            since_flush: 0.0,
+            frames: FrameStats::default(),
+            uploader: None,
+            opted_out: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.uploader.is_some()
+    }
+
+    pub fn session(&self) -> &str {
+        &self.session
+    }
+
+    // Call only after the player agreed, e.g. from a consent prompt whose answer the game
+    // saves. The endpoint is resolved on the upload thread, so this never blocks on DNS;
+    // while it can't be reached, batches are spooled.
+    pub fn opt_in(&mut self) {
+        if self.uploader.is_some() {
+            return;
+        }
+        let endpoint = LazyEndpoint { url: self.config.endpoint.clone(), resolved: None };
+        let (sender, receiver) = mpsc::channel();
+        let spool = Spool { dir: self.config.spool_dir.clone(), max_bytes: self.config.max_spool_bytes };
+        self.opted_out = Arc::new(AtomicBool::new(false));
+        let opted_out = self.opted_out.clone();
+        let thread = std::thread::spawn(move || upload(endpoint, spool, receiver, &opted_out));
+        self.uploader = Some((sender, thread));
+        self.since_flush = 0.0;
+    }
+
+    // Takes effect at once: nothing recorded before is sent and the spool is deleted
+    pub fn opt_out(&mut self) {
+        self.pending.clear();
+        self.frames = FrameStats::default();
+        self.opted_out.store(true, Ordering::SeqCst);
+        if let Some((sender, thread)) = self.uploader.take() {
+            // Batches already queued are dropped, not sent
+            let _ = sender.send(Upload::Shutdown);
+            let _ = thread.join();
+        }
+        // After the thread stopped, so it can't spool again; also covers earlier runs
+        Spool { dir: self.config.spool_dir.clone(), max_bytes: 0 }.purge();
+    }
+
+    // `telemetry.record("level_complete", [("level", json!(3)), ("seconds", json!(124.5))])`;
+    // dropped unless opted in
+    pub fn record<'a>(&mut self, name: &str, fields: impl IntoIterator<Item = (&'a str, Value)>) {
+        if !self.is_enabled() {
+            return;
+        }
+        self.pending.push(TelemetryEvent {
+            name: name.to_string(),
+            timestamp: unix_millis(),
+            session: self.session.clone(),
+            fields: fields.into_iter().map(|(key, value)| (key.to_string(), value)).collect(),
+        });
+        if self.pending.len() >= self.config.batch_size {
+            self.flush();
+        }
+    }
+
+    // Call once per frame; samples the frame time and flushes on the interval
+    pub fn update(&mut self, delta_time: f64) {
+        if !self.is_enabled() {
+            return;
+        }
+        if self.config.frame_stats && delta_time > 0.0 {
+            let frames = &mut self.frames;
+            let ms = delta_time * 1000.0;
+            (frames.min, frames.max) = if frames.count == 0 { (ms, ms) } else { (frames.min.min(ms), frames.max.max(ms)) };
+            frames.count += 1;
+            frames.total += ms;
+        }
+        self.since_flush += delta_time;
+        if self.since_flush >= self.config.flush_interval.as_secs_f64() {
+            self.flush();
+        }
+    }
+
+    // Hands everything waiting to the upload thread now
+    pub fn flush(&mut self) {
+        self.since_flush = 0.0;
+        let frames = std::mem::take(&mut self.frames);
+        if frames.count > 0 {
+            // `record` could flush again and recurse; push directly
+            self.pending.push(TelemetryEvent {
+                name: "performance".to_string(),
+                timestamp: unix_millis(),
+                session: self.session.clone(),
+                fields: BTreeMap::from([
+                    ("frames".to_string(), Value::from(frames.count)),
+                    ("frame_ms_avg".to_string(), Value::from(frames.total / frames.count as f64)),
+                    ("frame_ms_min".to_string(), Value::from(frames.min)),
+                    ("frame_ms_max".to_string(), Value::from(frames.max)),
+                ]),
+            });
+        }
+        let Some((sender, _)) = &self.uploader else { return };
+        if !self.pending.is_empty() {
+            let _ = sender.send(Upload::Batch(std::mem::take(&mut self.pending)));
+        }
+    }
+}
+
+impl Drop for Telemetry {
+    // Sends or spools what is left; waits at most for one request timeout per batch
+    fn drop(&mut self) {
+        self.flush();
+        if let Some((sender, thread)) = self.uploader.take() {
+            let _ = sender.send(Upload::Shutdown);
+            let _ = thread.join();
+        }
+    }
+}
+
+struct Spool {
+    dir: PathBuf,
+    max_bytes: u64,
+}
+
+impl Spool {
+    // Oldest first; names sort by time
+    fn files(&self) -> Vec<(PathBuf, u64)> {
+        let Ok(entries) = std::fs::read_dir(&self.dir) else { return Vec::new() };
+        let mut files: Vec<(PathBuf, u64)> = entries
+            .filter_map(Result::ok)
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|extension| extension == "json"))
+            .filter_map(|path| std::fs::metadata(&path).ok().map(|meta| (path, meta.len())))
+            .collect();
+        files.sort();
+        files
+    }
+
+    fn write(&self, body: &str) -> Result<(), String> {
+        std::fs::create_dir_all(&self.dir).map_err(|e| format!("Failed to create {}: {}", self.dir.display(), e))?;
+        let path = self.dir.join(format!("telemetry-{:016}-{}.json", unix_millis(), std::process::id()));
+        std::fs::write(&path, body).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+        let mut files = self.files();
+        let mut total: u64 = files.iter().map(|(_, size)| size).sum();
+        for (oldest, size) in files.drain(..) {
+            if total <= self.max_bytes {
+                break;
+            }
+            let _ = std::fs::remove_file(&oldest);
+            total -= size;
+        }
+        Ok(())
+    }
+
+    fn purge(&self) {
+        for (path, _) in self.files() {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+// The collector's URL, resolved on first use and again after a failed lookup, e.g. when
+// the game started offline
+struct LazyEndpoint {
+    url: String,
+    resolved: Option<HttpEndpoint>,
+}
+
+impl LazyEndpoint {
+    fn post(&mut self, body: &str) -> Result<(), String> {
+        let endpoint = match &mut self.resolved {
+            Some(endpoint) => endpoint,
+            resolved => resolved.insert(HttpEndpoint::new("telemetry", &self.url)?),
+        };
+        endpoint.send("POST", "", Some(body)).map(|_| ())
+    }
+}
+
+// Retries spooled batches, oldest first, stopping at the first failure
+fn drain_spool(endpoint: &mut LazyEndpoint, spool: &Spool, opted_out: &AtomicBool) {
+    for (path, _) in spool.files() {
+        if opted_out.load(Ordering::SeqCst) {
+            return;
+        }
+        let Ok(body) = std::fs::read_to_string(&path) else { continue };
+        if endpoint.post(&body).is_err() {
+            return;
+        }
+        let _ = std::fs::remove_file(&path);
+    }
+}
+
+fn upload(mut endpoint: LazyEndpoint, spool: Spool, receiver: Receiver<Upload>, opted_out: &AtomicBool) {
+    // Anything left from an earlier run goes first, if the collector is reachable
+    drain_spool(&mut endpoint, &spool, opted_out);
+    for message in receiver {
+        let Upload::Batch(events) = message else { return };
+        if opted_out.load(Ordering::SeqCst) {
+            return;
+        }
+        let body = match serde_json::to_string(&Batch { events: &events }) {
+            Ok(body) => body,
+            Err(e) => {
+                log::warn!("Dropping telemetry batch: {}", e);
+                continue;
+            }
+        };
+        match endpoint.post(&body) {
+            Ok(()) => drain_spool(&mut endpoint, &spool, opted_out),
+            Err(e) => {
+                log::debug!("Telemetry offline, spooling {} events: {}", events.len(), e);
+                if let Err(e) = spool.write(&body) {
+                    log::warn!("{}", e);
+                }
+            }
+        }
+    }
+}
+
+// Run in `PostUpdate`; does nothing without a `Telemetry` resource
+pub fn update_telemetry(world: &mut World, delta_time: f64) {
+    if let Some(telemetry) = world.resources.get_mut::<Telemetry>() {
+        telemetry.update(delta_time);
+    }
+}
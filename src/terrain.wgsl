@@ -0,0 +1,57 @@
+// Heightmap terrain: four tiled layers blended by a splat map stretched over the whole
+// terrain, lit by one directional sun plus an ambient term
+
+struct TerrainParams {
+    view_proj: mat4x4<f32>,
+    sun_direction: vec3<f32>,
+    // Layer repeats across the whole terrain
+    tiling: f32,
+    sun_color: vec3<f32>,
+    _pad0: f32,
+    ambient: vec3<f32>,
+    _pad1: f32,
+}
+
+@group(0) @binding(0) var<uniform> params: TerrainParams;
+@group(0) @binding(1) var splat_map: texture_2d<f32>;
+@group(0) @binding(2) var layers: texture_2d_array<f32>;
+@group(0) @binding(3) var clamp_sampler: sampler;
+@group(0) @binding(4) var wrap_sampler: sampler;
+
+struct VertexInput {
+    @location(0) position: vec3<f32>,
+    @location(1) normal: vec3<f32>,
+    @location(2) uv: vec2<f32>,
+}
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) normal: vec3<f32>,
+    @location(1) uv: vec2<f32>,
+}
+
+@vertex
+fn vs_main(in: VertexInput) -> VertexOutput {
+    var out: VertexOutput;
+    out.position = params.view_proj * vec4<f32>(in.position, 1.0);
+    out.normal = in.normal;
+    out.uv = in.uv;
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let splat = textureSample(splat_map, clamp_sampler, in.uv);
+    // Renormalise so bilinear filtering between texels can't darken the blend
+    let weights = splat / max(splat.r + splat.g + splat.b + splat.a, 1e-4);
+    let tiled = in.uv * params.tiling;
+    let albedo = textureSample(layers, wrap_sampler, tiled, 0).rgb * weights.r
+        + textureSample(layers, wrap_sampler, tiled, 1).rgb * weights.g
+        + textureSample(layers, wrap_sampler, tiled, 2).rgb * weights.b
+        + textureSample(layers, wrap_sampler, tiled, 3).rgb * weights.a;
+
+    let normal = normalize(in.normal);
+    let diffuse = max(dot(normal, normalize(params.sun_direction)), 0.0);
+    let light = params.sun_color * diffuse + params.ambient;
+    return vec4<f32>(albedo * light, 1.0);
+}
@@ -0,0 +1,142 @@
+// src/terrain/heightfield.rs
+use super::Heightmap;
+use glam::{Vec2, Vec3};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TerrainHit {
+    pub distance: f32,
+    pub point: Vec3,
+    pub normal: Vec3,
+}
+
+// A heightmap placed in the world, for collision and ground queries. Heights follow the
+// same triangles as the most detailed terrain mesh, so objects rest exactly on what's drawn.
+#[derive(Debug, Clone)]
+pub struct Heightfield {
+    pub heightmap: Heightmap,
+    // Corner at the first sample, at height 0.0
+    pub origin: Vec3,
+    // Extent in XZ
+    pub size: Vec2,
+    // World height of a sample of 1.0
+    pub height_scale: f32,
+}
+
+impl Heightfield {
+    pub fn new(heightmap: Heightmap, origin: Vec3, size: Vec2, height_scale: f32) -> Self {
+        Self { heightmap, origin, size, height_scale }
+    }
+
+    // World units between neighbouring samples along X and Z
+    pub fn cell_size(&self) -> Vec2 {
+        let (width, depth) = self.heightmap.size();
+        self.size / Vec2::new((width - 1).max(1) as f32, (depth - 1).max(1) as f32)
+    }
+
+    // World position of sample (x, z)
+    pub fn sample_position(&self, x: u32, z: u32) -> Vec3 {
+        let cell = self.cell_size();
+        let h = self.heightmap.get(x as i64, z as i64);
+        self.origin + Vec3::new(x as f32 * cell.x, h * self.height_scale, z as f32 * cell.y)
+    }
+
+    // Smooth normal at sample (x, z) from central differences
+    pub fn sample_normal(&self, x: u32, z: u32) -> Vec3 {
+        let (x, z) = (x as i64, z as i64);
+        let cell = self.cell_size();
+        let map = &self.heightmap;
+        let dx = (map.get(x + 1, z) - map.get(x - 1, z)) * self.height_scale / (2.0 * cell.x);
+        let dz = (map.get(x, z + 1) - map.get(x, z - 1)) * self.height_scale / (2.0 * cell.y);
+        Vec3::new(-dx, 1.0, -dz).normalize()
+    }
+
+    pub fn contains(&self, x: f32, z: f32) -> bool {
+        let local = Vec2::new(x - self.origin.x, z - self.origin.z);
+        local.cmpge(Vec2::ZERO).all() && local.cmple(self.size).all()
+    }
+
+    // Cell and position within it for a world XZ, clamped to the field
+    fn locate(&self, x: f32, z: f32) -> (i64, i64, f32, f32) {
+        let (width, depth) = self.heightmap.size();
+        let cell = self.cell_size();
+        let gx = ((x - self.origin.x) / cell.x).clamp(0.0, (width - 1) as f32);
+        let gz = ((z - self.origin.z) / cell.y).clamp(0.0, (depth - 1) as f32);
+        let (cx, cz) = (gx.floor().min(width.saturating_sub(2) as f32), gz.floor().min(depth.saturating_sub(2) as f32));
+        (cx as i64, cz as i64, gx - cx, gz - cz)
+    }
+
+    // Ground height under (x, z), or `None` off the edge
+    pub fn height_at(&self, x: f32, z: f32) -> Option<f32> {
+        self.contains(x, z).then(|| self.height_clamped(x, z))
+    }
+
+    // As `height_at`, extending the edge heights outwards
+    pub fn height_clamped(&self, x: f32, z: f32) -> f32 {
+        let (cx, cz, fx, fz) = self.locate(x, z);
+        let map = &self.heightmap;
+        let h = |dx, dz| map.get(cx + dx, cz + dz);
+        // Cells are split along the (1, 0)-(0, 1) diagonal, like the mesh
+        let height = if fx + fz <= 1.0 {
+            h(0, 0) + (h(1, 0) - h(0, 0)) * fx + (h(0, 1) - h(0, 0)) * fz
+        } else {
+            h(1, 1) + (h(0, 1) - h(1, 1)) * (1.0 - fx) + (h(1, 0) - h(1, 1)) * (1.0 - fz)
+        };
+        self.origin.y + height * self.height_scale
+    }
+
+    // Face normal of the triangle under (x, z)
+    pub fn normal_at(&self, x: f32, z: f32) -> Option<Vec3> {
+        if !self.contains(x, z) {
+            return None;
+        }
+        let (cx, cz, fx, fz) = self.locate(x, z);
+        let cell = self.cell_size();
+        let map = &self.heightmap;
+        let h = |dx, dz| map.get(cx + dx, cz + dz) * self.height_scale;
+        let (dx, dz) = if fx + fz <= 1.0 {
+            (h(1, 0) - h(0, 0), h(0, 1) - h(0, 0))
+        } else {
+            (h(1, 1) - h(0, 1), h(1, 1) - h(1, 0))
+        };
+        Some(Vec3::new(-dx / cell.x, 1.0, -dz / cell.y).normalize())
+    }
+
+    // First point where the ray meets the ground within `max_distance`
+    pub fn raycast(&self, origin: Vec3, direction: Vec3, max_distance: f32) -> Option<TerrainHit> {
+        let direction = direction.try_normalize()?;
+        let above = |t: f32| {
+            let p = origin + direction * t;
+            p.y - self.height_clamped(p.x, p.z)
+        };
+        // March in half-cell steps for a sign change, then bisect
+        let step = self.cell_size().min_element() * 0.5;
+        if above(0.0) < 0.0 {
+            return None;
+        }
+        let mut t0 = 0.0;
+        while t0 < max_distance {
+            let t1 = (t0 + step).min(max_distance);
+            if above(t1) <= 0.0 {
+                let (mut lo, mut hi) = (t0, t1);
+                for _ in 0..16 {
+                    let mid = (lo + hi) * 0.5;
+                    if above(mid) > 0.0 { lo = mid } else { hi = mid }
+                }
+                let point = origin + direction * hi;
+                let normal = self.normal_at(point.x, point.z)?;
+                return Some(TerrainHit { distance: hi, point, normal });
+            }
+            t0 = t1;
+        }
+        None
+    }
+
+    // Push-out for a sphere sunk into the ground: the direction to move it and how far
+    pub fn sphere_contact(&self, center: Vec3, radius: f32) -> Option<(Vec3, f32)> {
+        let normal = self.normal_at(center.x, center.z)?;
+        let ground = self.height_clamped(center.x, center.z);
+        // Distance from the ground plane under the centre, along its normal
+        let distance = (center.y - ground) * normal.y;
+        (distance < radius).then_some((normal, radius - distance))
+    }
+}
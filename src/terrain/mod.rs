@@ -0,0 +1,314 @@
+// src/terrain/mod.rs
+mod heightfield;
+mod render;
+
+pub use heightfield::{Heightfield, TerrainHit};
+pub use render::{TerrainRenderer, SPLAT_LAYERS};
+
+use crate::indirect::DrawIndexedArgs;
+use crate::lod::{screen_size, LodChain, LodLevel, LodSelector};
+use crate::mesh::{Mesh, MeshVertex};
+use glam::{Vec2, Vec3};
+
+// Grid of height samples, row-major with row 0 at the terrain's -Z edge. Values are
+// normally 0..=1 and scaled by `TerrainSettings::height_scale`.
+#[derive(Debug, Clone)]
+pub struct Heightmap {
+    width: u32,
+    depth: u32,
+    heights: Vec<f32>,
+}
+
+impl Heightmap {
+    pub fn new(width: u32, depth: u32) -> Result<Self, String> {
+        Self::from_fn(width, depth, |_, _| 0.0)
+    }
+
+    pub fn from_fn(width: u32, depth: u32, height: impl Fn(u32, u32) -> f32) -> Result<Self, String> {
+        if width < 2 || depth < 2 {
+            return Err(format!("A heightmap needs at least 2x2 samples, got {}x{}", width, depth));
+        }
+        let heights = (0..depth).flat_map(|z| (0..width).map(move |x| (x, z))).map(|(x, z)| height(x, z)).collect();
+        Ok(Self { width, depth, heights })
+    }
+
+    // Headerless 16-bit little-endian samples (`.r16` / `.raw`), as exported by most
+    // terrain tools
+    pub fn from_r16(bytes: &[u8], width: u32, depth: u32) -> Result<Self, String> {
+        let expected = width as usize * depth as usize * 2;
+        if bytes.len() != expected {
+            return Err(format!("Expected {} bytes for a {}x{} R16 heightmap, got {}", expected, width, depth, bytes.len()));
+        }
+        let heights = bytes.chunks_exact(2).map(|b| u16::from_le_bytes([b[0], b[1]]) as f32 / u16::MAX as f32).collect();
+        Self::from_heights(width, depth, heights)
+    }
+
+    // Headerless 8-bit samples
+    pub fn from_r8(bytes: &[u8], width: u32, depth: u32) -> Result<Self, String> {
+        let expected = width as usize * depth as usize;
+        if bytes.len() != expected {
+            return Err(format!("Expected {} bytes for a {}x{} R8 heightmap, got {}", expected, width, depth, bytes.len()));
+        }
+        Self::from_heights(width, depth, bytes.iter().map(|&b| b as f32 / 255.0).collect())
+    }
+
+    fn from_heights(width: u32, depth: u32, heights: Vec<f32>) -> Result<Self, String> {
+        if width < 2 || depth < 2 {
+            return Err(format!("A heightmap needs at least 2x2 samples, got {}x{}", width, depth));
+        }
+        Ok(Self { width, depth, heights })
+    }
+
+    pub fn load_r16(path: &str, width: u32, depth: u32) -> Result<Self, String> {
+        let bytes = std::fs::read(path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+        Self::from_r16(&bytes, width, depth)
+    }
+
+    pub fn size(&self) -> (u32, u32) {
+        (self.width, self.depth)
+    }
+
+    // Sample at (x, z), clamped to the edges
+    pub fn get(&self, x: i64, z: i64) -> f32 {
+        let x = x.clamp(0, self.width as i64 - 1) as usize;
+        let z = z.clamp(0, self.depth as i64 - 1) as usize;
+        self.heights[z * self.width as usize + x]
+    }
+
+    pub fn set(&mut self, x: u32, z: u32, height: f32) {
+        if x < self.width && z < self.depth {
+            self.heights[(z * self.width + x) as usize] = height;
+        }
+    }
+}
+
+// Where a splat layer shows: weight is 1.0 inside both ranges, fading to 0.0 over
+// `falloff` outside them. Slope is 0.0 for flat ground and 1.0 for a vertical cliff.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SplatRule {
+    pub layer: usize,
+    // World heights
+    pub height: (f32, f32),
+    pub slope: (f32, f32),
+    pub falloff: f32,
+}
+
+impl SplatRule {
+    // Grass low and flat, rock on slopes, snow up high and dirt everywhere else, for
+    // terrain `height_scale` units tall
+    pub fn defaults(height_scale: f32) -> Vec<SplatRule> {
+        vec![
+            SplatRule { layer: 0, height: (f32::MIN, height_scale * 0.6), slope: (0.0, 0.25), falloff: 0.1 },
+            SplatRule { layer: 1, height: (f32::MIN, f32::MAX), slope: (0.35, 1.0), falloff: 0.1 },
+            SplatRule { layer: 2, height: (height_scale * 0.75, f32::MAX), slope: (0.0, 0.45), falloff: height_scale * 0.05 },
+            SplatRule { layer: 3, height: (f32::MIN, f32::MAX), slope: (0.0, 1.0), falloff: 0.0 },
+        ]
+    }
+
+    fn weight(&self, height: f32, slope: f32) -> f32 {
+        let fade = |value: f32, (min, max): (f32, f32), falloff: f32| {
+            let outside = (min - value).max(value - max).max(0.0);
+            if falloff > 0.0 { (1.0 - outside / falloff).max(0.0) } else if outside > 0.0 { 0.0 } else { 1.0 }
+        };
+        fade(height, self.height, self.falloff) * fade(slope, self.slope, self.falloff.min(0.1))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TerrainSettings {
+    // Corner at the first heightmap sample, and the extent in XZ
+    pub origin: Vec3,
+    pub size: Vec2,
+    pub height_scale: f32,
+    // Cells per chunk side at full detail
+    pub chunk_cells: u32,
+    // Each level halves the resolution of the one before
+    pub lod_levels: u32,
+    // Screen size (fraction of view height) a chunk needs for full detail
+    pub detail: f32,
+    pub hysteresis: f32,
+    // How far chunk edges hang down, hiding cracks between levels
+    pub skirt_depth: f32,
+}
+
+impl Default for TerrainSettings {
+    fn default() -> Self {
+        Self {
+            origin: Vec3::ZERO,
+            size: Vec2::splat(256.0),
+            height_scale: 40.0,
+            chunk_cells: 32,
+            lod_levels: 4,
+            detail: 0.8,
+            hysteresis: 0.1,
+            skirt_depth: 2.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TerrainChunk {
+    pub min: Vec3,
+    pub max: Vec3,
+    pub chain: LodChain,
+}
+
+impl TerrainChunk {
+    pub fn center(&self) -> Vec3 {
+        (self.min + self.max) * 0.5
+    }
+
+    pub fn radius(&self) -> f32 {
+        (self.max - self.min).length() * 0.5
+    }
+}
+
+// Heightmap terrain split into square chunks, each with a mesh per level of detail, all
+// packed into one `Mesh` so `TerrainRenderer` draws any mix of levels from one buffer pair.
+// Chunks have skirts instead of stitching, so neighbours at different levels don't crack.
+pub struct Terrain {
+    pub settings: TerrainSettings,
+    pub heightfield: Heightfield,
+    pub chunks: Vec<TerrainChunk>,
+    pub mesh: Mesh,
+}
+
+impl Terrain {
+    pub fn new(heightmap: Heightmap, settings: TerrainSettings) -> Result<Self, String> {
+        if settings.size.cmple(Vec2::ZERO).any() {
+            return Err("Terrain size must be positive".to_string());
+        }
+        let heightfield = Heightfield::new(heightmap, settings.origin, settings.size, settings.height_scale);
+        let mut terrain = Self { settings, heightfield, chunks: Vec::new(), mesh: Mesh::default() };
+        terrain.build()?;
+        Ok(terrain)
+    }
+
+    // Regenerates every chunk, e.g. after editing `heightfield.heightmap`
+    pub fn build(&mut self) -> Result<(), String> {
+        let (width, depth) = self.heightfield.heightmap.size();
+        let chunk_cells = self.settings.chunk_cells.max(1);
+        // No level coarser than one quad per chunk
+        let levels = self.settings.lod_levels.clamp(1, chunk_cells.ilog2() + 1);
+        self.chunks.clear();
+        self.mesh = Mesh::default();
+        for z0 in (0..depth - 1).step_by(chunk_cells as usize) {
+            for x0 in (0..width - 1).step_by(chunk_cells as usize) {
+                let (x1, z1) = ((x0 + chunk_cells).min(width - 1), (z0 + chunk_cells).min(depth - 1));
+                let mut lods = Vec::with_capacity(levels as usize);
+                let (mut min, mut max) = (Vec3::splat(f32::MAX), Vec3::splat(f32::MIN));
+                for level in 0..levels {
+                    let first_index = self.mesh.indices.len() as u32;
+                    let base_vertex = self.mesh.vertices.len() as i32;
+                    let chunk = self.chunk_mesh((x0, z0), (x1, z1), 1 << level);
+                    for v in &chunk.vertices {
+                        min = min.min(Vec3::from(v.position));
+                        max = max.max(Vec3::from(v.position));
+                    }
+                    lods.push(LodLevel {
+                        args: DrawIndexedArgs {
+                            index_count: chunk.indices.len() as u32,
+                            instance_count: 1,
+                            first_index,
+                            base_vertex,
+                            first_instance: 0,
+                        },
+                        min_screen_size: if level + 1 == levels { 0.0 } else { self.settings.detail / (1 << level) as f32 },
+                    });
+                    self.mesh.vertices.extend(chunk.vertices);
+                    self.mesh.indices.extend(chunk.indices);
+                }
+                let chain = LodChain::new(lods, self.settings.hysteresis)?;
+                self.chunks.push(TerrainChunk { min, max, chain });
+            }
+        }
+        Ok(())
+    }
+
+    // Grid over samples `min..=max` every `step` samples, plus a skirt around the edge
+    fn chunk_mesh(&self, min: (u32, u32), max: (u32, u32), step: u32) -> Mesh {
+        let axis = |from: u32, to: u32| {
+            let mut samples: Vec<u32> = (from..to).step_by(step as usize).collect();
+            samples.push(to);
+            samples
+        };
+        let (xs, zs) = (axis(min.0, max.0), axis(min.1, max.1));
+        let (width, depth) = self.heightfield.heightmap.size();
+        let field = &self.heightfield;
+        let vertex = |x: u32, z: u32, drop: f32| MeshVertex {
+            position: (field.sample_position(x, z) - Vec3::Y * drop).into(),
+            normal: field.sample_normal(x, z).into(),
+            uv: [x as f32 / (width - 1) as f32, z as f32 / (depth - 1) as f32],
+        };
+        let mut mesh = Mesh::default();
+        let columns = xs.len() as u32;
+        for &z in &zs {
+            for &x in &xs {
+                mesh.vertices.push(vertex(x, z, 0.0));
+            }
+        }
+        // Rows run towards +Z, so (a, b, a + 1) faces up
+        for r in 0..zs.len() as u32 - 1 {
+            for c in 0..columns - 1 {
+                let a = r * columns + c;
+                let b = a + columns;
+                mesh.indices.extend_from_slice(&[a, b, a + 1, a + 1, b, b + 1]);
+            }
+        }
+        // Walk the border and hang a strip of quads below it
+        let rows = zs.len() as u32;
+        let border: Vec<u32> = (0..columns)
+            .chain((1..rows).map(|r| r * columns + columns - 1))
+            .chain((0..columns - 1).rev().map(|c| (rows - 1) * columns + c))
+            .chain((1..rows - 1).rev().map(|r| r * columns))
+            .collect();
+        let skirt_base = mesh.vertices.len() as u32;
+        for &i in &border {
+            let (x, z) = (xs[(i % columns) as usize], zs[(i / columns) as usize]);
+            mesh.vertices.push(vertex(x, z, self.settings.skirt_depth));
+        }
+        let count = border.len() as u32;
+        for k in 0..count {
+            let (top_a, top_b) = (border[k as usize], border[((k + 1) % count) as usize]);
+            let (low_a, low_b) = (skirt_base + k, skirt_base + (k + 1) % count);
+            mesh.indices.extend_from_slice(&[top_a, low_a, top_b, top_b, low_a, low_b]);
+        }
+        mesh
+    }
+
+    // Draw arguments for every chunk at the level its on-screen size calls for
+    pub fn select_lods(&self, selector: &mut LodSelector, camera: Vec3, fov_y: f32) -> Vec<DrawIndexedArgs> {
+        self.chunks
+            .iter()
+            .enumerate()
+            .map(|(i, chunk)| {
+                let level = selector.select(i, &chunk.chain, screen_size(chunk.center(), chunk.radius(), camera, fov_y));
+                chunk.chain.args(level)
+            })
+            .collect()
+    }
+
+    // RGBA8 layer weights per heightmap sample from height and slope, for
+    // `TerrainRenderer::set_splat`. Samples no rule covers fall back to layer 0.
+    pub fn generate_splat(&self, rules: &[SplatRule]) -> Vec<[u8; 4]> {
+        let (width, depth) = self.heightfield.heightmap.size();
+        let mut splat = Vec::with_capacity((width * depth) as usize);
+        for z in 0..depth {
+            for x in 0..width {
+                let height = self.heightfield.sample_position(x, z).y;
+                let slope = 1.0 - self.heightfield.sample_normal(x, z).y;
+                let mut weights = [0.0f32; SPLAT_LAYERS];
+                // Rules claim what's left after the ones before them, so order is priority
+                let mut remaining = 1.0;
+                for rule in rules.iter().filter(|r| r.layer < SPLAT_LAYERS) {
+                    let w = rule.weight(height, slope) * remaining;
+                    weights[rule.layer] += w;
+                    remaining -= w;
+                }
+                weights[0] += remaining;
+                splat.push(weights.map(|w| (w.clamp(0.0, 1.0) * 255.0).round() as u8));
+            }
+        }
+        splat
+    }
+}
@@ -0,0 +1,279 @@
+// src/terrain/render.rs
+use super::Terrain;
+use crate::bind_cache::{BindGroupCache, BindingKey, SamplerCache};
+use crate::indirect::DrawIndexedArgs;
+use crate::lod::LodSelector;
+use crate::mesh::{GpuMesh, MeshVertex};
+use crate::resource_registry::{ResourceRegistry, Tracked};
+use crate::upload::UploadBelt;
+use glam::{Mat4, Vec3};
+
+// Texture layers the splat map blends, one per RGBA channel
+pub const SPLAT_LAYERS: usize = 4;
+
+// Mirrors `TerrainParams` in terrain.wgsl
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct TerrainParams {
+    view_proj: [f32; 16],
+    sun_direction: [f32; 3],
+    tiling: f32,
+    sun_color: [f32; 3],
+    _pad0: f32,
+    ambient: [f32; 3],
+    _pad1: f32,
+}
+
+// Placeholder layer colours until `set_layers`: grass, rock, snow, dirt
+const DEFAULT_LAYER_COLORS: [[u8; 4]; SPLAT_LAYERS] = [[86, 125, 70, 255], [120, 115, 110, 255], [240, 240, 245, 255], [130, 100, 70, 255]];
+
+// Draws a `Terrain`: each frame `prepare` picks every chunk's level of detail and uploads
+// the camera and lighting, then `draw` issues one indexed draw per chunk.
+pub struct TerrainRenderer {
+    // Layer repeats across the whole terrain
+    pub tiling: f32,
+    mesh: GpuMesh,
+    splat: Tracked<wgpu::Texture>,
+    splat_view: wgpu::TextureView,
+    _default_layers: Tracked<wgpu::Texture>,
+    default_layers_view: wgpu::TextureView,
+    layers: Option<wgpu::TextureView>,
+    params_buffer: Tracked<wgpu::Buffer>,
+    layout: wgpu::BindGroupLayout,
+    pipeline: wgpu::RenderPipeline,
+    selector: LodSelector,
+    draws: Vec<DrawIndexedArgs>,
+}
+
+impl TerrainRenderer {
+    // Uploads the terrain's mesh and a splat map from `SplatRule::defaults`.
+    // `depth_format` must match the pass the terrain is drawn in.
+    pub fn new(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        resources: &ResourceRegistry,
+        terrain: &Terrain,
+        color_format: wgpu::TextureFormat,
+        depth_format: Option<wgpu::TextureFormat>,
+    ) -> Self {
+        let texture_entry = |binding, view_dimension| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Texture {
+                sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                view_dimension,
+                multisampled: false,
+            },
+            count: None,
+        };
+        let sampler_entry = |binding| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+            count: None,
+        };
+        let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("terrain"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                texture_entry(1, wgpu::TextureViewDimension::D2),
+                texture_entry(2, wgpu::TextureViewDimension::D2Array),
+                sampler_entry(3),
+                sampler_entry(4),
+            ],
+        });
+        let shader = device.create_shader_module(wgpu::include_wgsl!("../terrain.wgsl"));
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("terrain"),
+            bind_group_layouts: &[&layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("terrain"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: Some("vs_main"),
+                buffers: &[MeshVertex::layout()],
+                compilation_options: Default::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: Some("fs_main"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: color_format,
+                    blend: None,
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: Default::default(),
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: depth_format.map(|format| wgpu::DepthStencilState {
+                format,
+                depth_write_enabled: true,
+                depth_compare: wgpu::CompareFunction::LessEqual,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        });
+
+        let (width, depth) = terrain.heightfield.heightmap.size();
+        let splat = resources.create_texture(device, &wgpu::TextureDescriptor {
+            label: Some("terrain splat"),
+            size: wgpu::Extent3d { width, height: depth, depth_or_array_layers: 1 },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgba8Unorm,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        }, "terrain");
+        let default_layers = resources.create_texture(device, &wgpu::TextureDescriptor {
+            label: Some("terrain default layers"),
+            size: wgpu::Extent3d { width: 1, height: 1, depth_or_array_layers: SPLAT_LAYERS as u32 },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgba8UnormSrgb,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        }, "terrain");
+        queue.write_texture(
+            default_layers.as_image_copy(),
+            DEFAULT_LAYER_COLORS.as_flattened(),
+            wgpu::TexelCopyBufferLayout { offset: 0, bytes_per_row: Some(4), rows_per_image: Some(1) },
+            wgpu::Extent3d { width: 1, height: 1, depth_or_array_layers: SPLAT_LAYERS as u32 },
+        );
+        let array_view = wgpu::TextureViewDescriptor {
+            dimension: Some(wgpu::TextureViewDimension::D2Array),
+            ..Default::default()
+        };
+
+        let params_buffer = resources.create_buffer(device, &wgpu::BufferDescriptor {
+            label: Some("terrain params"),
+            size: std::mem::size_of::<TerrainParams>() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        }, "terrain");
+
+        let renderer = Self {
+            tiling: 64.0,
+            mesh: terrain.mesh.upload(device, "terrain"),
+            splat_view: splat.create_view(&wgpu::TextureViewDescriptor::default()),
+            splat,
+            default_layers_view: default_layers.create_view(&array_view),
+            _default_layers: default_layers,
+            layers: None,
+            params_buffer,
+            layout,
+            pipeline,
+            selector: LodSelector::new(),
+            draws: Vec::new(),
+        };
+        let rules = super::SplatRule::defaults(terrain.settings.height_scale);
+        renderer.set_splat(queue, &terrain.generate_splat(&rules));
+        renderer
+    }
+
+    // Layer weights per heightmap sample, e.g. from `Terrain::generate_splat` or painted
+    pub fn set_splat(&self, queue: &wgpu::Queue, weights: &[[u8; 4]]) {
+        let size = self.splat.size();
+        if weights.len() != (size.width * size.height) as usize {
+            log::warn!("Splat map has {} texels, expected {}x{}", weights.len(), size.width, size.height);
+            return;
+        }
+        queue.write_texture(
+            self.splat.as_image_copy(),
+            weights.as_flattened(),
+            wgpu::TexelCopyBufferLayout { offset: 0, bytes_per_row: Some(size.width * 4), rows_per_image: None },
+            size,
+        );
+    }
+
+    // A `D2Array` view with `SPLAT_LAYERS` layers, matched to the splat channels in order
+    pub fn set_layers(&mut self, layers: Option<wgpu::TextureView>) {
+        self.layers = layers;
+    }
+
+    // Picks levels of detail for `camera` and uploads the frame's parameters. `sun_direction`
+    // points towards the sun and `sun_color` includes its intensity, e.g. from an
+    // `EnvironmentController`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn prepare(
+        &mut self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        belt: &mut UploadBelt,
+        terrain: &Terrain,
+        view_proj: Mat4,
+        camera_position: Vec3,
+        fov_y: f32,
+        sun_direction: Vec3,
+        sun_color: Vec3,
+        ambient: Vec3,
+    ) {
+        self.draws = terrain.select_lods(&mut self.selector, camera_position, fov_y);
+        let params = TerrainParams {
+            view_proj: view_proj.to_cols_array(),
+            sun_direction: sun_direction.normalize_or(Vec3::Y).into(),
+            tiling: self.tiling,
+            sun_color: sun_color.into(),
+            _pad0: 0.0,
+            ambient: ambient.into(),
+            _pad1: 0.0,
+        };
+        belt.write(device, encoder, &self.params_buffer, 0, bytemuck::bytes_of(&params));
+    }
+
+    // Total triangles drawn at the levels picked by the last `prepare`
+    pub fn triangle_count(&self) -> u32 {
+        self.draws.iter().map(|d| d.index_count / 3).sum()
+    }
+
+    pub fn draw(
+        &self,
+        device: &wgpu::Device,
+        bind_groups: &mut BindGroupCache,
+        samplers: &mut SamplerCache,
+        render_pass: &mut wgpu::RenderPass<'_>,
+    ) {
+        let sampler = |samplers: &mut SamplerCache, label, address_mode| samplers.get(device, &wgpu::SamplerDescriptor {
+            label: Some(label),
+            address_mode_u: address_mode,
+            address_mode_v: address_mode,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            mipmap_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+        let clamp = sampler(samplers, "terrain clamp", wgpu::AddressMode::ClampToEdge);
+        let wrap = sampler(samplers, "terrain wrap", wgpu::AddressMode::Repeat);
+        let layers = self.layers.as_ref().unwrap_or(&self.default_layers_view);
+        let bind_group = bind_groups.get_or_create(device, "terrain", &self.layout, &[
+            (0, BindingKey::buffer(&self.params_buffer)),
+            (1, BindingKey::TextureView(self.splat_view.clone())),
+            (2, BindingKey::TextureView(layers.clone())),
+            (3, BindingKey::Sampler(clamp)),
+            (4, BindingKey::Sampler(wrap)),
+        ]);
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(0, &bind_group, &[]);
+        render_pass.set_vertex_buffer(0, self.mesh.vertex_buffer.slice(..));
+        render_pass.set_index_buffer(self.mesh.index_buffer.slice(..), wgpu::IndexFormat::Uint32);
+        for args in &self.draws {
+            render_pass.draw_indexed(args.first_index..args.first_index + args.index_count, args.base_vertex, 0..1);
+        }
+    }
+}
@@ -0,0 +1,365 @@
+// src/terrain2d.rs
+use crate::physics::collision::{Collider, ColliderId, CollisionWorld};
+use crate::physics::layers::CollisionFilter;
+use crate::physics::shape::{merge_cells, Shape};
+use crate::scene::SceneVertex;
+use crate::math::{Color, Vec2};
+
+// Cells per chunk side; carving re-meshes and re-collides whole chunks
+pub const DEFAULT_CHUNK_CELLS: u32 = 16;
+
+// Triangles covering the solid part of one chunk, in world space
+#[derive(Debug, Clone, Default)]
+pub struct ChunkMesh {
+    pub vertices: Vec<Vec2>,
+    pub indices: Vec<u32>,
+}
+
+impl ChunkMesh {
+    pub fn is_empty(&self) -> bool {
+        self.indices.is_empty()
+    }
+
+    // Unindexed triangle list for the scene pipeline
+    pub fn scene_vertices(&self, tint: Color) -> Vec<SceneVertex> {
+        let tint = tint.to_array();
+        self.indices
+            .iter()
+            .map(|&i| SceneVertex { position: self.vertices[i as usize].into(), tint, flash: [0.0; 4] })
+            .collect()
+    }
+}
+
+#[derive(Default)]
+struct Chunk {
+    mesh: ChunkMesh,
+    colliders: Vec<ColliderId>,
+    mesh_dirty: bool,
+    collision_dirty: bool,
+}
+
+// Terrain stored as a density per grid point: above zero is solid, and the surface is the
+// zero crossing. Carving and filling edit the densities and mark the chunks they touch;
+// `remesh` rebuilds those chunks' outlines with marching squares, and `sync_colliders`
+// replaces their boxes and surface polygons in a `CollisionWorld`.
+pub struct DestructibleTerrain {
+    // Bottom-left corner of the grid in world space
+    origin: Vec2,
+    cell_size: f32,
+    // Cells across and up; the grid has one more point each way
+    width: u32,
+    height: u32,
+    chunk_cells: u32,
+    density: Vec<f32>,
+    chunks: Vec<Chunk>,
+    chunks_x: u32,
+    pub filter: CollisionFilter,
+    // Copied to every terrain collider, so hits can be traced back to the terrain
+    pub user_data: u64,
+}
+
+impl DestructibleTerrain {
+    // Empty terrain of `width` x `height` cells
+    pub fn new(origin: Vec2, cell_size: f32, width: u32, height: u32) -> Result<Self, String> {
+        Self::with_chunk_cells(origin, cell_size, width, height, DEFAULT_CHUNK_CELLS)
+    }
+
+    pub fn with_chunk_cells(origin: Vec2, cell_size: f32, width: u32, height: u32, chunk_cells: u32) -> Result<Self, String> {
+        if width == 0 || height == 0 {
+            return Err("Terrain needs at least one cell each way".to_string());
+        }
+        if !cell_size.is_finite() || cell_size <= 0.0 {
+            return Err("Terrain cell size must be positive".to_string());
+        }
+        let chunk_cells = chunk_cells.max(1);
+        let chunks_x = width.div_ceil(chunk_cells);
+        let chunks_y = height.div_ceil(chunk_cells);
+        Ok(Self {
+            origin,
+            cell_size,
+            width,
+            height,
+            chunk_cells,
+            density: vec![-1.0; ((width + 1) * (height + 1)) as usize],
+            chunks: (0..chunks_x * chunks_y).map(|_| Chunk::default()).collect(),
+            chunks_x,
+            filter: CollisionFilter::ALL,
+            user_data: 0,
+        })
+    }
+
+    // Terrain from a solid/empty bitmap, row-major from the bottom row; one cell per pixel
+    pub fn from_bitmap(origin: Vec2, cell_size: f32, width: u32, height: u32, solid: &[bool]) -> Result<Self, String> {
+        if solid.len() != (width * height) as usize {
+            return Err(format!("Terrain bitmap has {} pixels, expected {}x{}", solid.len(), width, height));
+        }
+        let mut terrain = Self::new(origin, cell_size, width, height)?;
+        // A point is solid when any pixel touching it is, so single pixels survive
+        for y in 0..=height {
+            for x in 0..=width {
+                let touching = (y.saturating_sub(1)..(y + 1).min(height))
+                    .flat_map(|py| (x.saturating_sub(1)..(x + 1).min(width)).map(move |px| (px, py)))
+                    .any(|(px, py)| solid[(py * width + px) as usize]);
+                terrain.density[(y * (width + 1) + x) as usize] = if touching { 1.0 } else { -1.0 };
+            }
+        }
+        terrain.mark_all();
+        Ok(terrain)
+    }
+
+    // Fills every grid point from `density(world_position)`, e.g. `|p| ground_height(p.x) - p.y`
+    pub fn fill_with(&mut self, density: impl Fn(Vec2) -> f32) {
+        for y in 0..=self.height {
+            for x in 0..=self.width {
+                let index = self.point_index(x, y);
+                self.density[index] = density(self.point_position(x, y));
+            }
+        }
+        self.mark_all();
+    }
+
+    pub fn origin(&self) -> Vec2 {
+        self.origin
+    }
+
+    pub fn cell_size(&self) -> f32 {
+        self.cell_size
+    }
+
+    // (cells across, cells up)
+    pub fn size(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    pub fn chunk_count(&self) -> usize {
+        self.chunks.len()
+    }
+
+    fn point_index(&self, x: u32, y: u32) -> usize {
+        (y * (self.width + 1) + x) as usize
+    }
+
+    fn point_position(&self, x: u32, y: u32) -> Vec2 {
+        self.origin + Vec2::new(x as f32, y as f32) * self.cell_size
+    }
+
+    fn mark_all(&mut self) {
+        for chunk in &mut self.chunks {
+            chunk.mesh_dirty = true;
+            chunk.collision_dirty = true;
+        }
+    }
+
+    // Density at a world position, bilinear between grid points; empty outside the grid
+    pub fn density_at(&self, position: Vec2) -> f32 {
+        let local = (position - self.origin) / self.cell_size;
+        if local.x < 0.0 || local.y < 0.0 || local.x > self.width as f32 || local.y > self.height as f32 {
+            return -1.0;
+        }
+        let x0 = (local.x.floor() as u32).min(self.width - 1);
+        let y0 = (local.y.floor() as u32).min(self.height - 1);
+        let t = local - Vec2::new(x0 as f32, y0 as f32);
+        let d = |x, y| self.density[self.point_index(x, y)];
+        let bottom = d(x0, y0) + (d(x0 + 1, y0) - d(x0, y0)) * t.x;
+        let top = d(x0, y0 + 1) + (d(x0 + 1, y0 + 1) - d(x0, y0 + 1)) * t.x;
+        bottom + (top - bottom) * t.y
+    }
+
+    pub fn is_solid(&self, position: Vec2) -> bool {
+        self.density_at(position) > 0.0
+    }
+
+    // Applies `edit(current, world_position)` to every grid point within `radius` of
+    // `center`, marking the chunks around each point that changed. Returns how many changed.
+    fn edit_circle(&mut self, center: Vec2, radius: f32, edit: impl Fn(f32, Vec2) -> f32) -> usize {
+        let reach = radius + self.cell_size;
+        let min = ((center - self.origin - Vec2::splat(reach)) / self.cell_size).floor().max(Vec2::ZERO);
+        let max = ((center - self.origin + Vec2::splat(reach)) / self.cell_size).ceil();
+        let (x1, y1) = ((max.x.max(0.0) as u32).min(self.width), (max.y.max(0.0) as u32).min(self.height));
+        let mut changed = 0;
+        for y in (min.y as u32)..=y1 {
+            for x in (min.x as u32)..=x1 {
+                let index = self.point_index(x, y);
+                let old = self.density[index];
+                let new = edit(old, self.point_position(x, y)).clamp(-1.0, 1.0);
+                if new != old {
+                    self.density[index] = new;
+                    self.mark_point(x, y);
+                    changed += 1;
+                }
+            }
+        }
+        changed
+    }
+
+    // A point is a corner of up to four cells, which may sit in different chunks
+    fn mark_point(&mut self, x: u32, y: u32) {
+        for cy in [y.saturating_sub(1), y.min(self.height - 1)] {
+            for cx in [x.saturating_sub(1), x.min(self.width - 1)] {
+                let chunk = (cy / self.chunk_cells * self.chunks_x + cx / self.chunk_cells) as usize;
+                self.chunks[chunk].mesh_dirty = true;
+                self.chunks[chunk].collision_dirty = true;
+            }
+        }
+    }
+
+    // Removes a disc, e.g. an explosion. The densities become the distance to the disc's
+    // edge in cells, so the new surface follows the circle smoothly.
+    pub fn carve_circle(&mut self, center: Vec2, radius: f32) -> usize {
+        let cell_size = self.cell_size;
+        self.edit_circle(center, radius, |old, p| old.min((p.distance(center) - radius) / cell_size))
+    }
+
+    // Adds a disc of material, e.g. dirt thrown back in
+    pub fn fill_circle(&mut self, center: Vec2, radius: f32) -> usize {
+        let cell_size = self.cell_size;
+        self.edit_circle(center, radius, |old, p| old.max((radius - p.distance(center)) / cell_size))
+    }
+
+    // Digs a capsule-shaped tunnel from `from` to `to`
+    pub fn carve_line(&mut self, from: Vec2, to: Vec2, radius: f32) -> usize {
+        let steps = (from.distance(to) / (radius * 0.5).max(self.cell_size)).ceil().max(1.0) as u32;
+        (0..=steps).map(|i| self.carve_circle(from.lerp(to, i as f32 / steps as f32), radius)).sum()
+    }
+
+    // Chunks whose mesh changed since the last `remesh`
+    pub fn dirty_chunks(&self) -> Vec<usize> {
+        (0..self.chunks.len()).filter(|&i| self.chunks[i].mesh_dirty).collect()
+    }
+
+    // Re-meshes the changed chunks and returns them, so their GPU copies can be replaced
+    pub fn remesh(&mut self) -> Vec<usize> {
+        let dirty = self.dirty_chunks();
+        for &chunk in &dirty {
+            self.chunks[chunk].mesh = self.build_mesh(chunk);
+            self.chunks[chunk].mesh_dirty = false;
+        }
+        dirty
+    }
+
+    pub fn chunk_mesh(&self, chunk: usize) -> Option<&ChunkMesh> {
+        self.chunks.get(chunk).map(|c| &c.mesh)
+    }
+
+    // (first cell, one past the last cell) of a chunk
+    fn chunk_cells(&self, chunk: usize) -> ((u32, u32), (u32, u32)) {
+        let (cx, cy) = (chunk as u32 % self.chunks_x, chunk as u32 / self.chunks_x);
+        let (x0, y0) = (cx * self.chunk_cells, cy * self.chunk_cells);
+        ((x0, y0), ((x0 + self.chunk_cells).min(self.width), (y0 + self.chunk_cells).min(self.height)))
+    }
+
+    // Marching squares. Each cell's solid part is bounded by its solid corners and the
+    // edge crossings between them; all of those lie on the cell's border, in order, so the
+    // polygon is convex and a fan triangulates it. Saddle cells join their two corners.
+    fn build_mesh(&self, chunk: usize) -> ChunkMesh {
+        let ((x0, y0), (x1, y1)) = self.chunk_cells(chunk);
+        let mut mesh = ChunkMesh::default();
+        let mut polygon: Vec<Vec2> = Vec::with_capacity(8);
+        for y in y0..y1 {
+            for x in x0..x1 {
+                if !self.cell_polygon(x, y, &mut polygon) {
+                    continue;
+                }
+                let base = mesh.vertices.len() as u32;
+                mesh.vertices.extend_from_slice(&polygon);
+                for i in 1..polygon.len() as u32 - 1 {
+                    mesh.indices.extend_from_slice(&[base, base + i, base + i + 1]);
+                }
+            }
+        }
+        mesh
+    }
+
+    // The solid part of a cell, as its marching squares polygon; false when it has none
+    fn cell_polygon(&self, x: u32, y: u32, polygon: &mut Vec<Vec2>) -> bool {
+        let corners = [(x, y), (x + 1, y), (x + 1, y + 1), (x, y + 1)];
+        let values = corners.map(|(cx, cy)| self.density[self.point_index(cx, cy)]);
+        if values.iter().all(|&v| v <= 0.0) {
+            return false;
+        }
+        let positions = corners.map(|(cx, cy)| self.point_position(cx, cy));
+        polygon.clear();
+        for k in 0..4 {
+            let next = (k + 1) % 4;
+            if values[k] > 0.0 {
+                polygon.push(positions[k]);
+            }
+            if (values[k] > 0.0) != (values[next] > 0.0) {
+                let t = values[k] / (values[k] - values[next]);
+                polygon.push(positions[k].lerp(positions[next], t));
+            }
+        }
+        true
+    }
+
+    // Fully solid cells are merged into boxes; surface cells collide as their mesh polygon,
+    // so slopes stay slopes. Returns (position, shape) pairs.
+    fn build_shapes(&self, chunk: usize) -> Vec<(Vec2, Shape)> {
+        let ((x0, y0), (x1, y1)) = self.chunk_cells(chunk);
+        let (w, h) = ((x1 - x0) as usize, (y1 - y0) as usize);
+        let mut solid = vec![false; w * h];
+        let mut shapes = Vec::new();
+        let mut polygon = Vec::with_capacity(8);
+        for y in 0..h {
+            for x in 0..w {
+                let (cx, cy) = (x0 + x as u32, y0 + y as u32);
+                solid[y * w + x] = [(cx, cy), (cx + 1, cy), (cx + 1, cy + 1), (cx, cy + 1)]
+                    .iter()
+                    .all(|&(px, py)| self.density[self.point_index(px, py)] > 0.0);
+                if solid[y * w + x] || !self.cell_polygon(cx, cy, &mut polygon) {
+                    continue;
+                }
+                let center = polygon.iter().sum::<Vec2>() / polygon.len() as f32;
+                let outline: Vec<Vec2> = polygon.iter().map(|p| *p - center).collect();
+                // Slivers too thin to form a polygon are skipped
+                if let Ok(shape) = Shape::polygon(&outline) {
+                    shapes.push((center, shape));
+                }
+            }
+        }
+        for (x, y, rw, rh) in merge_cells(w, h, &mut solid) {
+            let min = self.point_position(x0 + x as u32, y0 + y as u32);
+            let max = self.point_position(x0 + (x + rw) as u32, y0 + (y + rh) as u32);
+            shapes.push(((min + max) * 0.5, Shape::rect(max.x - min.x, max.y - min.y)));
+        }
+        shapes
+    }
+
+    // Replaces the colliders of every chunk changed since the last sync
+    pub fn sync_colliders(&mut self, world: &mut CollisionWorld) {
+        for chunk in 0..self.chunks.len() {
+            if !self.chunks[chunk].collision_dirty {
+                continue;
+            }
+            for id in self.chunks[chunk].colliders.drain(..) {
+                world.remove(id);
+            }
+            let colliders = self
+                .build_shapes(chunk)
+                .into_iter()
+                .map(|(position, shape)| {
+                    let collider = Collider::new(shape, position)
+                        .with_filter(self.filter)
+                        .with_user_data(self.user_data);
+                    world.insert(collider)
+                })
+                .collect();
+            self.chunks[chunk].colliders = colliders;
+            self.chunks[chunk].collision_dirty = false;
+        }
+    }
+
+    // Takes the terrain's colliders back out of `world`
+    pub fn remove_colliders(&mut self, world: &mut CollisionWorld) {
+        for chunk in &mut self.chunks {
+            for id in chunk.colliders.drain(..) {
+                world.remove(id);
+            }
+            chunk.collision_dirty = true;
+        }
+    }
+
+    pub fn collider_count(&self) -> usize {
+        self.chunks.iter().map(|c| c.colliders.len()).sum()
+    }
+}
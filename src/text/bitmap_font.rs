@@ -0,0 +1,175 @@
+// src/text/bitmap_font.rs
+use super::{Font, Glyph};
+use glam::Vec2;
+use std::collections::HashMap;
+
+// A font drawn ahead of time into texture pages: AngelCode BMFont output (from BMFont,
+// Hiero, msdf-bmfont and most bitmap font tools) or a grid of equal cells, as retro sprite
+// fonts usually come.
+#[derive(Debug, Clone, Default)]
+pub struct BitmapFont {
+    pub face: String,
+    pub line_height: f32,
+    pub base: f32,
+    // Every page shares this size
+    pub page_size: (u32, u32),
+    // Image files per page id, relative to the font file; empty for grid fonts
+    pub pages: Vec<String>,
+    glyphs: HashMap<char, Glyph>,
+    kerning: HashMap<(char, char), f32>,
+    // Drawn in place of characters the font lacks
+    pub fallback: Option<char>,
+}
+
+// `key=value` pairs of one BMFont text line; values may be quoted
+fn fields(line: &str) -> HashMap<&str, &str> {
+    let mut fields = HashMap::new();
+    let mut rest = line;
+    while let Some(eq) = rest.find('=') {
+        let key = rest[..eq].trim().rsplit(' ').next().unwrap_or_default();
+        rest = &rest[eq + 1..];
+        let value = if let Some(quoted) = rest.strip_prefix('"') {
+            let end = quoted.find('"').unwrap_or(quoted.len());
+            rest = quoted.get(end + 1..).unwrap_or_default();
+            &quoted[..end]
+        } else {
+            let end = rest.find(' ').unwrap_or(rest.len());
+            let value = &rest[..end];
+            rest = &rest[end..];
+            value
+        };
+        fields.insert(key, value);
+    }
+    fields
+}
+
+fn number<T: std::str::FromStr>(fields: &HashMap<&str, &str>, key: &str, line: usize) -> Result<T, String> {
+    let value = fields.get(key).ok_or_else(|| format!("BMFont line {}: missing {}", line, key))?;
+    value.parse().map_err(|_| format!("BMFont line {}: bad {} '{}'", line, key, value))
+}
+
+fn character(id: u32, line: usize) -> Result<char, String> {
+    char::from_u32(id).ok_or_else(|| format!("BMFont line {}: {} isn't a character", line, id))
+}
+
+impl BitmapFont {
+    // BMFont's text format (.fnt); the XML and binary variants aren't supported
+    pub fn parse_bmfont(source: &str) -> Result<Self, String> {
+        let mut font = Self { fallback: Some('?'), ..Self::default() };
+        for (index, line) in source.lines().enumerate() {
+            let line_number = index + 1;
+            let (tag, rest) = line.trim().split_once(' ').unwrap_or((line.trim(), ""));
+            let fields = fields(rest);
+            match tag {
+                "info" => font.face = fields.get("face").unwrap_or(&"").to_string(),
+                "common" => {
+                    font.line_height = number(&fields, "lineHeight", line_number)?;
+                    font.base = number(&fields, "base", line_number)?;
+                    font.page_size = (number(&fields, "scaleW", line_number)?, number(&fields, "scaleH", line_number)?);
+                }
+                "page" => {
+                    let id: usize = number(&fields, "id", line_number)?;
+                    if font.pages.len() <= id {
+                        font.pages.resize(id + 1, String::new());
+                    }
+                    font.pages[id] = fields.get("file").unwrap_or(&"").to_string();
+                }
+                "char" => {
+                    let c = character(number(&fields, "id", line_number)?, line_number)?;
+                    font.glyphs.insert(c, Glyph {
+                        page: number(&fields, "page", line_number)?,
+                        origin: (number(&fields, "x", line_number)?, number(&fields, "y", line_number)?),
+                        size: (number(&fields, "width", line_number)?, number(&fields, "height", line_number)?),
+                        offset: Vec2::new(number(&fields, "xoffset", line_number)?, number(&fields, "yoffset", line_number)?),
+                        advance: number(&fields, "xadvance", line_number)?,
+                    });
+                }
+                "kerning" => {
+                    let first = character(number(&fields, "first", line_number)?, line_number)?;
+                    let second = character(number(&fields, "second", line_number)?, line_number)?;
+                    font.kerning.insert((first, second), number(&fields, "amount", line_number)?);
+                }
+                _ => {}
+            }
+        }
+        if font.line_height <= 0.0 || font.page_size.0 == 0 || font.page_size.1 == 0 {
+            return Err("BMFont file has no valid common line".to_string());
+        }
+        Ok(font)
+    }
+
+    pub fn load(path: &str) -> Result<Self, String> {
+        let source = std::fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+        Self::parse_bmfont(&source).map_err(|e| format!("{}: {}", path, e))
+    }
+
+    // A sprite font laid out as equal `cell` sized cells, left to right and top to bottom
+    // from the image's top-left, holding the characters of `charset` in order, e.g.
+    // `" !\"#$%&'()*+,-./0123456789..."`. Every glyph fills its cell and advances by its width.
+    pub fn grid(image_size: (u32, u32), cell: (u32, u32), charset: &str) -> Result<Self, String> {
+        let (cell_width, cell_height) = cell;
+        if cell_width == 0 || cell_height == 0 {
+            return Err("Grid font cells can't be empty".to_string());
+        }
+        let columns = image_size.0 / cell_width;
+        let rows = image_size.1 / cell_height;
+        let count = charset.chars().count() as u32;
+        if count > columns * rows {
+            return Err(format!("{} characters don't fit a {}x{} grid", count, columns, rows));
+        }
+        let glyphs = charset
+            .chars()
+            .enumerate()
+            .map(|(index, c)| {
+                let index = index as u32;
+                (c, Glyph {
+                    page: 0,
+                    origin: ((index % columns) * cell_width, (index / columns) * cell_height),
+                    size: cell,
+                    offset: Vec2::ZERO,
+                    advance: cell_width as f32,
+                })
+            })
+            .collect();
+        Ok(Self {
+            face: String::new(),
+            line_height: cell_height as f32,
+            base: cell_height as f32,
+            page_size: image_size,
+            pages: Vec::new(),
+            glyphs,
+            kerning: HashMap::new(),
+            fallback: charset.contains('?').then_some('?'),
+        })
+    }
+
+    pub fn glyph_count(&self) -> usize {
+        self.glyphs.len()
+    }
+
+    pub fn set_kerning(&mut self, first: char, second: char, amount: f32) {
+        self.kerning.insert((first, second), amount);
+    }
+}
+
+impl Font for BitmapFont {
+    fn line_height(&self) -> f32 {
+        self.line_height
+    }
+
+    fn base(&self) -> f32 {
+        self.base
+    }
+
+    fn page_size(&self, _page: u32) -> (u32, u32) {
+        self.page_size
+    }
+
+    fn glyph(&self, c: char) -> Option<&Glyph> {
+        self.glyphs.get(&c).or_else(|| self.glyphs.get(&self.fallback?))
+    }
+
+    fn kerning(&self, first: char, second: char) -> f32 {
+        self.kerning.get(&(first, second)).copied().unwrap_or(0.0)
+    }
+}
@@ -0,0 +1,60 @@
+// src/text/icons.rs
+use glam::Vec2;
+use std::collections::HashMap;
+
+// An image drawn inline with text, e.g. a button prompt, a currency symbol or an emoji
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct InlineIcon {
+    // Index into the icon textures passed to `TextLayout::sprites`
+    pub texture: u32,
+    // Top-left and bottom-right UVs, e.g. a cell of an icon atlas
+    pub uv: [Vec2; 2],
+    // In font pixels; sits on the baseline and advances by its width plus `spacing`
+    pub size: Vec2,
+    pub spacing: f32,
+}
+
+// Named icons for `[icon=name]` markup. Characters can also stand for icons, which is how
+// emoji are drawn: bitmap fonts have no colour glyphs, so alias each emoji to an icon.
+#[derive(Debug, Clone, Default)]
+pub struct IconSet {
+    icons: Vec<(String, InlineIcon)>,
+    chars: HashMap<char, usize>,
+}
+
+impl IconSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Replaces an icon of the same name
+    pub fn add(&mut self, name: &str, icon: InlineIcon) {
+        match self.icons.iter_mut().find(|(existing, _)| existing == name) {
+            Some((_, existing)) => *existing = icon,
+            None => self.icons.push((name.to_string(), icon)),
+        }
+    }
+
+    // Draws `c` as the icon `name` wherever it appears in laid out text, e.g. '🙂'
+    pub fn alias(&mut self, c: char, name: &str) -> Result<(), String> {
+        let index = self.index(name).ok_or_else(|| format!("No icon named '{}'", name))?;
+        self.chars.insert(c, index);
+        Ok(())
+    }
+
+    pub(crate) fn index(&self, name: &str) -> Option<usize> {
+        self.icons.iter().position(|(existing, _)| existing == name)
+    }
+
+    pub(crate) fn index_of_char(&self, c: char) -> Option<usize> {
+        self.chars.get(&c).copied()
+    }
+
+    pub(crate) fn by_index(&self, index: usize) -> Option<&InlineIcon> {
+        self.icons.get(index).map(|(_, icon)| icon)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&InlineIcon> {
+        self.by_index(self.index(name)?)
+    }
+}
@@ -0,0 +1,311 @@
+// src/text/layout.rs
+use super::icons::IconSet;
+use super::markup::{parse_markup, RunContent};
+use super::Font;
+use crate::color::Color;
+use crate::math::Rect;
+use crate::sprite_batch::{Sprite, TextureSlot};
+use glam::Vec2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TextAlign {
+    #[default]
+    Left,
+    Center,
+    Right,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TextStyle {
+    // World units per font pixel
+    pub scale: f32,
+    pub color: Color,
+    // Wraps at word boundaries to fit, in world units; words longer than a line break
+    // between characters
+    pub max_width: Option<f32>,
+    // Within `max_width`, or the widest line without one
+    pub align: TextAlign,
+    // Multiplies the font's line height
+    pub line_spacing: f32,
+}
+
+impl Default for TextStyle {
+    fn default() -> Self {
+        Self { scale: 1.0, color: Color::WHITE, max_width: None, align: TextAlign::Left, line_spacing: 1.0 }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuadSource {
+    // Font texture page
+    Page(u32),
+    // `InlineIcon::texture`
+    Icon(u32),
+}
+
+// One glyph or icon placed by layout, relative to the layout's top-left corner (y up)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GlyphQuad {
+    pub source: QuadSource,
+    // Centre and size, as `Sprite` takes them
+    pub position: Vec2,
+    pub size: Vec2,
+    // Top-left and bottom-right UVs
+    pub uv: [Vec2; 2],
+    pub color: Color,
+}
+
+// A laid out line: `top` is relative to the layout's top (zero or below), `width` excludes
+// trailing spaces and `offset` is the alignment shift already applied to its quads
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TextLine {
+    pub top: f32,
+    pub height: f32,
+    pub width: f32,
+    pub offset: f32,
+}
+
+// Positioned glyphs and the bounds they take up
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TextLayout {
+    pub quads: Vec<GlyphQuad>,
+    pub lines: Vec<TextLine>,
+    // Widest line, or `max_width` when set, by total line height; in world units
+    pub size: Vec2,
+}
+
+impl TextLayout {
+    // The space taken with the top-left at `origin`, e.g. for a UI layout pass
+    pub fn bounds(&self, origin: Vec2) -> Rect {
+        Rect::new(origin - Vec2::new(0.0, self.size.y), origin + Vec2::new(self.size.x, 0.0))
+    }
+
+    // Sprites for `SpriteBatch::push` with the layout's top-left at `origin`. `pages` holds
+    // the texture slot of each font page and `icons` of each icon texture; quads whose
+    // texture has no slot are skipped.
+    pub fn sprites<'a>(
+        &'a self,
+        origin: Vec2,
+        pages: &'a [TextureSlot],
+        icons: &'a [TextureSlot],
+        layer: i32,
+    ) -> impl Iterator<Item = Sprite> + 'a {
+        self.quads.iter().filter_map(move |quad| {
+            let texture = match quad.source {
+                QuadSource::Page(page) => *pages.get(page as usize)?,
+                QuadSource::Icon(icon) => *icons.get(icon as usize)?,
+            };
+            let mut sprite = Sprite::new(texture, origin + quad.position, quad.size);
+            sprite.uv = quad.uv;
+            sprite.color = quad.color;
+            sprite.layer = layer;
+            Some(sprite)
+        })
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Kind {
+    Glyph(char),
+    Icon(usize),
+    Space(char),
+    Newline,
+}
+
+#[derive(Clone, Copy)]
+struct Item {
+    kind: Kind,
+    color: Color,
+    // Absolute: style scale times markup scale
+    scale: f32,
+}
+
+struct Placed {
+    item: usize,
+    x: f32,
+}
+
+#[derive(Default)]
+struct Line {
+    placed: Vec<Placed>,
+    width: f32,
+    // Largest item scale, which sets the line's height and baseline
+    scale: f32,
+    // Started by wrapping rather than a newline
+    wrapped: bool,
+}
+
+fn advance(font: &impl Font, icons: &IconSet, item: &Item) -> f32 {
+    match item.kind {
+        Kind::Glyph(c) | Kind::Space(c) => font.glyph(c).map_or(0.0, |glyph| glyph.advance) * item.scale,
+        Kind::Icon(index) => icons.by_index(index).map_or(0.0, |icon| icon.size.x + icon.spacing) * item.scale,
+        Kind::Newline => 0.0,
+    }
+}
+
+fn kerning(font: &impl Font, previous: Option<&Item>, item: &Item) -> f32 {
+    match (previous.map(|previous| previous.kind), item.kind) {
+        (Some(Kind::Glyph(first)), Kind::Glyph(second)) => font.kerning(first, second) * item.scale,
+        _ => 0.0,
+    }
+}
+
+// Plain text, laid out from the top-left corner with one line per `\n`. Works the same for
+// any `Font`, so switching between TTF, BMFont and grid fonts doesn't touch UI code.
+pub fn layout_text(font: &impl Font, text: &str, style: &TextStyle) -> TextLayout {
+    let items = text.chars().map(|c| item(c, &IconSet::default(), style.color, style.scale)).collect();
+    layout_items(font, &IconSet::default(), items, style)
+}
+
+// Text with `parse_markup` tags and inline icons, wrapped and aligned by `style`
+pub fn layout_rich_text(font: &impl Font, icons: &IconSet, text: &str, style: &TextStyle) -> TextLayout {
+    let mut items = Vec::new();
+    for run in parse_markup(text, style.color) {
+        let scale = style.scale * run.scale;
+        match run.content {
+            RunContent::Text(text) => items.extend(text.chars().map(|c| item(c, icons, run.color, scale))),
+            RunContent::Icon(name) => match icons.index(&name) {
+                Some(index) => items.push(Item { kind: Kind::Icon(index), color: run.color, scale }),
+                None => log::warn!("Text uses unknown icon '{}'", name),
+            },
+        }
+    }
+    layout_items(font, icons, items, style)
+}
+
+// Size of `text` laid out with `style`, without keeping the quads
+pub fn measure_rich_text(font: &impl Font, icons: &IconSet, text: &str, style: &TextStyle) -> Vec2 {
+    layout_rich_text(font, icons, text, style).size
+}
+
+fn item(c: char, icons: &IconSet, color: Color, scale: f32) -> Item {
+    let kind = match c {
+        '\n' => Kind::Newline,
+        c if c.is_whitespace() => Kind::Space(c),
+        c => icons.index_of_char(c).map_or(Kind::Glyph(c), Kind::Icon),
+    };
+    Item { kind, color, scale }
+}
+
+fn layout_items(font: &impl Font, icons: &IconSet, items: Vec<Item>, style: &TextStyle) -> TextLayout {
+    let max_width = style.max_width.unwrap_or(f32::INFINITY);
+    let mut lines = Vec::new();
+    let mut line = Line { scale: style.scale, ..Line::default() };
+    let mut cursor = 0.0;
+
+    let mut index = 0;
+    while index < items.len() {
+        match items[index].kind {
+            Kind::Newline => {
+                lines.push(std::mem::replace(&mut line, Line { scale: style.scale, ..Line::default() }));
+                cursor = 0.0;
+                index += 1;
+            }
+            Kind::Space(_) => {
+                // Spaces a wrap lands on are dropped
+                if !(line.wrapped && line.placed.is_empty()) {
+                    line.placed.push(Placed { item: index, x: cursor });
+                    cursor += advance(font, icons, &items[index]);
+                }
+                index += 1;
+            }
+            Kind::Glyph(_) | Kind::Icon(_) => {
+                let end = items[index..]
+                    .iter()
+                    .position(|item| matches!(item.kind, Kind::Space(_) | Kind::Newline))
+                    .map_or(items.len(), |length| index + length);
+                let word = &items[index..end];
+                let word_width: f32 = word
+                    .iter()
+                    .enumerate()
+                    .map(|(i, item)| advance(font, icons, item) + kerning(font, i.checked_sub(1).map(|p| &word[p]), item))
+                    .sum();
+                if line.width > 0.0 && cursor + word_width > max_width {
+                    lines.push(std::mem::replace(&mut line, Line { scale: style.scale, wrapped: true, ..Line::default() }));
+                    cursor = 0.0;
+                }
+                for (offset, item) in word.iter().enumerate() {
+                    cursor += kerning(font, offset.checked_sub(1).map(|p| &word[p]), item);
+                    let width = advance(font, icons, item);
+                    // Only words longer than a whole line get here
+                    if line.width > 0.0 && cursor + width > max_width {
+                        lines.push(std::mem::replace(&mut line, Line { scale: style.scale, wrapped: true, ..Line::default() }));
+                        cursor = 0.0;
+                    }
+                    line.placed.push(Placed { item: index + offset, x: cursor });
+                    cursor += width;
+                    line.width = cursor;
+                    line.scale = line.scale.max(item.scale);
+                }
+                index = end;
+            }
+        }
+    }
+    lines.push(line);
+
+    let widest = lines.iter().map(|line| line.width).fold(0.0, f32::max);
+    let align_width = style.max_width.unwrap_or(widest);
+    let mut layout = TextLayout::default();
+    let mut top = 0.0;
+    for line in &lines {
+        let height = font.line_height() * line.scale * style.line_spacing;
+        let base = font.base() * line.scale;
+        let offset = match style.align {
+            TextAlign::Left => 0.0,
+            TextAlign::Center => (align_width - line.width) * 0.5,
+            TextAlign::Right => align_width - line.width,
+        };
+        for placed in &line.placed {
+            let item = &items[placed.item];
+            let x = offset + placed.x;
+            if let Some(quad) = place(font, icons, item, x, top, base) {
+                layout.quads.push(quad);
+            }
+        }
+        layout.lines.push(TextLine { top, height, width: line.width, offset });
+        top -= height;
+    }
+    layout.size = Vec2::new(style.max_width.unwrap_or(widest), -top);
+    layout
+}
+
+// The quad for `item` at pen `x` on a line whose top is `top` and whose baseline is `base`
+// below it; none for spaces and glyphs without pixels
+fn place(font: &impl Font, icons: &IconSet, item: &Item, x: f32, top: f32, base: f32) -> Option<GlyphQuad> {
+    let scale = item.scale;
+    match item.kind {
+        Kind::Glyph(c) => {
+            let glyph = font.glyph(c)?;
+            if glyph.size.0 == 0 || glyph.size.1 == 0 {
+                return None;
+            }
+            let (page_width, page_height) = font.page_size(glyph.page);
+            let size = Vec2::new(glyph.size.0 as f32, glyph.size.1 as f32);
+            let uv_min = Vec2::new(glyph.origin.0 as f32 / page_width as f32, glyph.origin.1 as f32 / page_height as f32);
+            let uv_max = uv_min + size / Vec2::new(page_width as f32, page_height as f32);
+            // Smaller runs share the line's baseline; font offsets are y down from the top
+            let glyph_top = top - (base - font.base() * scale);
+            let top_left = Vec2::new(x, glyph_top) + Vec2::new(glyph.offset.x, -glyph.offset.y) * scale;
+            Some(GlyphQuad {
+                source: QuadSource::Page(glyph.page),
+                position: top_left + Vec2::new(size.x, -size.y) * scale * 0.5,
+                size: size * scale,
+                uv: [uv_min, uv_max],
+                color: item.color,
+            })
+        }
+        Kind::Icon(index) => {
+            let icon = icons.by_index(index)?;
+            let size = icon.size * scale;
+            let baseline = top - base;
+            Some(GlyphQuad {
+                source: QuadSource::Icon(icon.texture),
+                position: Vec2::new(x + size.x * 0.5, baseline + size.y * 0.5),
+                size,
+                uv: icon.uv,
+                color: item.color,
+            })
+        }
+        Kind::Space(_) | Kind::Newline => None,
+    }
+}
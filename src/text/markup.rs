@@ -0,0 +1,75 @@
+// src/text/markup.rs
+use crate::color::Color;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum RunContent {
+    Text(String),
+    // Name of an icon in the `IconSet` passed to layout
+    Icon(String),
+}
+
+// Text sharing one style, or an inline icon
+#[derive(Debug, Clone, PartialEq)]
+pub struct Run {
+    pub content: RunContent,
+    pub color: Color,
+    // Multiplies `TextStyle::scale`
+    pub scale: f32,
+}
+
+// Splits BBCode-style markup into runs:
+//   [color=#ff8800]text[/color]   sRGB hex colour, as `Color::from_hex` takes it
+//   [scale=1.5]text[/scale]       relative size
+//   [icon=coin]                   inline icon
+//   [[                            a literal '['
+// Tags nest. Anything that isn't a well-formed tag stays in the text as written, so stray
+// brackets in player names don't vanish.
+pub fn parse_markup(text: &str, color: Color) -> Vec<Run> {
+    let mut runs = Vec::new();
+    let mut colors = vec![color];
+    let mut scales = vec![1.0];
+    let mut current = String::new();
+    let mut rest = text;
+    while let Some(open) = rest.find('[') {
+        current.push_str(&rest[..open]);
+        rest = &rest[open..];
+        if let Some(after) = rest.strip_prefix("[[") {
+            current.push('[');
+            rest = after;
+            continue;
+        }
+        let Some(close) = rest.find(']') else { break };
+        let tag = &rest[1..close];
+        let (name, value) = tag.split_once('=').unwrap_or((tag, ""));
+        let style = (*colors.last().unwrap_or(&color), *scales.last().unwrap_or(&1.0));
+        let handled = match (name.trim(), value.trim()) {
+            ("color", value) => Color::from_hex(value).map(|c| colors.push(c)).is_ok(),
+            ("/color", "") if colors.len() > 1 => colors.pop().is_some(),
+            ("scale", value) => value.parse::<f32>().ok().filter(|s| *s > 0.0).map(|s| scales.push(s * style.1)).is_some(),
+            ("/scale", "") if scales.len() > 1 => scales.pop().is_some(),
+            ("icon", name) if !name.is_empty() => {
+                flush(&mut runs, &mut current, style);
+                runs.push(Run { content: RunContent::Icon(name.to_string()), color: style.0, scale: style.1 });
+                true
+            }
+            _ => false,
+        };
+        if handled {
+            // Text before the tag keeps the style it was written in
+            flush(&mut runs, &mut current, style);
+            rest = &rest[close + 1..];
+        } else {
+            current.push('[');
+            rest = &rest[1..];
+        }
+    }
+    current.push_str(rest);
+    flush(&mut runs, &mut current, (*colors.last().unwrap_or(&color), *scales.last().unwrap_or(&1.0)));
+    runs
+}
+
+fn flush(runs: &mut Vec<Run>, current: &mut String, (color, scale): (Color, f32)) {
+    if !current.is_empty() {
+        runs.push(Run { content: RunContent::Text(std::mem::take(current)), color, scale });
+    }
+}
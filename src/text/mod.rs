@@ -0,0 +1,45 @@
+// src/text/mod.rs
+pub mod bitmap_font;
+pub mod icons;
+pub mod layout;
+pub mod markup;
+
+pub use bitmap_font::BitmapFont;
+pub use icons::{IconSet, InlineIcon};
+pub use layout::{layout_rich_text, layout_text, measure_rich_text, GlyphQuad, QuadSource, TextAlign, TextLayout, TextLine, TextStyle};
+
+use glam::Vec2;
+
+// Where a glyph sits in its font's texture pages and how it is placed on a line, in font
+// pixels
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Glyph {
+    pub page: u32,
+    // Top-left texel and size in the page
+    pub origin: (u32, u32),
+    pub size: (u32, u32),
+    // From the pen position at the top of the line to the glyph's top-left corner, y down
+    pub offset: Vec2,
+    // How far the pen moves right afterwards
+    pub advance: f32,
+}
+
+// What text layout needs from a font, so bitmap, grid and rasterized TTF fonts share
+// `layout_text`. Glyph textures are pages the caller uploads, one texture per page.
+pub trait Font {
+    // Distance between baselines of consecutive lines, in font pixels
+    fn line_height(&self) -> f32;
+
+    // From the top of a line to its baseline
+    fn base(&self) -> f32;
+
+    // Size in texels of `page`, for turning glyph rects into UVs
+    fn page_size(&self, page: u32) -> (u32, u32);
+
+    fn glyph(&self, c: char) -> Option<&Glyph>;
+
+    // Extra advance between a pair of glyphs, usually negative
+    fn kerning(&self, _first: char, _second: char) -> f32 {
+        0.0
+    }
+}
@@ -0,0 +1,817 @@
+// src/texture/basis.rs
+// Basis Universal payloads in KTX2 files, decoded to RGBA8: ETC1S under BasisLZ
+// supercompression, and UASTC blocks. `encode` then turns the pixels into whatever block
+// format the GPU samples.
+
+// Colour models from the KTX2 data format descriptor
+pub const MODEL_ETC1S: u8 = 163;
+pub const MODEL_UASTC: u8 = 166;
+
+const GLOBAL_HEADER_SIZE: usize = 20;
+const IMAGE_DESC_SIZE: usize = 20;
+// Image flag for ETC1S video frames predicted from the one before
+const IS_P_FRAME: u32 = 2;
+// Most RGBA8 bytes one file may decode to; its header alone could otherwise ask for any amount
+const MAX_DECODED_SIZE: usize = 1 << 30;
+
+// Order the code length code sizes are sent in, most common first
+const CODE_LENGTH_ORDER: [usize; 21] = [17, 18, 19, 20, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15, 16];
+// Endpoint predictor symbol repeating the last one
+const REPEAT_PREDICTION: u32 = 256;
+// Selector history run symbol meaning the length follows as a variable-length number
+const LONG_RUN: u32 = 63;
+
+// ETC1 modifiers by intensity, in selector order
+const ETC1_MODIFIERS: [[i32; 4]; 8] = [
+    [-8, -2, 2, 8],
+    [-17, -5, 5, 17],
+    [-29, -9, 9, 29],
+    [-42, -13, 13, 42],
+    [-60, -18, 18, 60],
+    [-80, -24, 24, 80],
+    [-106, -33, 33, 106],
+    [-183, -47, 47, 183],
+];
+
+// Size of one mip level and how many images (layers, faces and depth slices) it holds
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LevelShape {
+    pub width: u32,
+    pub height: u32,
+    pub images: usize,
+}
+
+impl LevelShape {
+    pub fn image_size(&self) -> usize {
+        self.width as usize * self.height as usize * 4
+    }
+
+    fn blocks(&self) -> (usize, usize) {
+        (self.width.div_ceil(4) as usize, self.height.div_ceil(4) as usize)
+    }
+}
+
+// Byte offsets in an RGBA8 image of a 4x4 block's texels, skipping ones past the edge
+fn block_texels(shape: &LevelShape, block_x: usize, block_y: usize) -> impl Iterator<Item = (usize, usize)> {
+    let (width, height) = (shape.width as usize, shape.height as usize);
+    (0..16).filter_map(move |texel| {
+        let (x, y) = (block_x * 4 + texel % 4, block_y * 4 + texel / 4);
+        (x < width && y < height).then_some((texel, (y * width + x) * 4))
+    })
+}
+
+// Fails before anything is allocated if `shapes` decode to more than `MAX_DECODED_SIZE`
+fn check_decoded_size(shapes: &[LevelShape]) -> Result<(), String> {
+    let total = shapes.iter().try_fold(0usize, |total, shape| {
+        (shape.width as usize)
+            .checked_mul(shape.height as usize)?
+            .checked_mul(4)?
+            .checked_mul(shape.images)?
+            .checked_add(total)
+    });
+    match total {
+        Some(total) if total <= MAX_DECODED_SIZE => Ok(()),
+        _ => Err(format!("Basis texture would decode to more than {} bytes", MAX_DECODED_SIZE)),
+    }
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(data.get(offset..offset + 4)?.try_into().ok()?))
+}
+
+// Canonical Huffman code, as Deflate assigns them from code lengths
+#[derive(Default)]
+struct Huffman {
+    // Codes of each length, 1..=16
+    counts: [u16; 17],
+    // Ordered by code
+    symbols: Vec<u16>,
+}
+
+impl Huffman {
+    fn new(lengths: &[u8]) -> Result<Self, String> {
+        let mut counts = [0u16; 17];
+        for &length in lengths.iter().filter(|&&length| length > 0) {
+            counts[length as usize] += 1;
+        }
+        let mut unused = 1i32;
+        for &count in &counts[1..] {
+            unused = unused * 2 - count as i32;
+            if unused < 0 {
+                return Err("Oversubscribed Huffman code in Basis data".to_string());
+            }
+        }
+        let mut symbols: Vec<u16> = (0..lengths.len() as u16).filter(|&symbol| lengths[symbol as usize] > 0).collect();
+        symbols.sort_by_key(|&symbol| lengths[symbol as usize]);
+        Ok(Self { counts, symbols })
+    }
+}
+
+// LSB-first reader over a Basis bit stream
+struct BitReader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> BitReader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data, position: 0 }
+    }
+
+    // Past the end reads as zeros, like the reference transcoder
+    fn bits(&mut self, count: u32) -> u32 {
+        let mut value = 0;
+        for index in 0..count {
+            let byte = self.data.get(self.position / 8).copied().unwrap_or(0);
+            value |= (((byte >> (self.position % 8)) & 1) as u32) << index;
+            self.position += 1;
+        }
+        value
+    }
+
+    // Chunks of `chunk_bits`, each followed by a bit saying whether another comes
+    fn vlc(&mut self, chunk_bits: u32) -> Result<u32, String> {
+        let (mut value, mut shift) = (0u32, 0);
+        loop {
+            let chunk = self.bits(chunk_bits + 1);
+            value |= (chunk & ((1 << chunk_bits) - 1)) << shift;
+            shift += chunk_bits;
+            if chunk & (1 << chunk_bits) == 0 {
+                return Ok(value);
+            }
+            if shift >= 32 {
+                return Err("Variable-length number in Basis data is too long".to_string());
+            }
+        }
+    }
+
+    // Codes arrive most significant bit first
+    fn symbol(&mut self, code: &Huffman) -> Result<u32, String> {
+        let (mut value, mut first, mut index) = (0i32, 0i32, 0i32);
+        for &count in &code.counts[1..] {
+            value |= self.bits(1) as i32;
+            let count = count as i32;
+            if value - first < count {
+                return Ok(code.symbols[(index + value - first) as usize] as u32);
+            }
+            index += count;
+            first = (first + count) << 1;
+            value <<= 1;
+        }
+        Err("Invalid Huffman code in Basis data".to_string())
+    }
+
+    // Code lengths, themselves Huffman coded with zero runs and repeats, as in Deflate
+    fn huffman(&mut self) -> Result<Huffman, String> {
+        let total = self.bits(14) as usize;
+        if total == 0 {
+            return Ok(Huffman::default());
+        }
+        let sent = self.bits(5) as usize;
+        if sent == 0 || sent > CODE_LENGTH_ORDER.len() {
+            return Err("Invalid Huffman table in Basis data".to_string());
+        }
+        let mut length_lengths = [0u8; 21];
+        for &symbol in &CODE_LENGTH_ORDER[..sent] {
+            length_lengths[symbol] = self.bits(3) as u8;
+        }
+        let length_code = Huffman::new(&length_lengths)?;
+        let mut lengths = vec![0u8; total];
+        let mut index = 0;
+        while index < total {
+            match self.symbol(&length_code)? {
+                length @ 0..=16 => {
+                    lengths[index] = length as u8;
+                    index += 1;
+                }
+                17 => index += self.bits(3) as usize + 3,
+                18 => index += self.bits(7) as usize + 11,
+                symbol => {
+                    let repeat = if symbol == 19 { self.bits(2) + 3 } else { self.bits(7) + 7 } as usize;
+                    let previous = index.checked_sub(1).map_or(0, |last| lengths[last]);
+                    if previous == 0 || index + repeat > total {
+                        return Err("Invalid Huffman table in Basis data".to_string());
+                    }
+                    lengths[index..index + repeat].fill(previous);
+                    index += repeat;
+                }
+            }
+        }
+        if index != total {
+            return Err("Invalid Huffman table in Basis data".to_string());
+        }
+        Huffman::new(&lengths)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct Endpoint {
+    // 5 bits per channel
+    color: [u8; 3],
+    // Row of `ETC1_MODIFIERS`
+    intensity: u8,
+}
+
+// Recently used selectors, roughly most recent first, for the history symbols to index.
+// New ones go in the back half so a run of literals can't flush the front.
+struct SelectorHistory {
+    values: Vec<usize>,
+    rover: usize,
+}
+
+impl SelectorHistory {
+    fn new(size: usize) -> Self {
+        Self { values: vec![0; size], rover: size / 2 }
+    }
+
+    fn get(&self, index: usize) -> Result<usize, String> {
+        self.values.get(index).copied().ok_or_else(|| "ETC1S selector history index is out of range".to_string())
+    }
+
+    fn add(&mut self, value: usize) {
+        if self.values.is_empty() {
+            return;
+        }
+        self.values[self.rover] = value;
+        self.rover += 1;
+        if self.rover == self.values.len() {
+            self.rover = self.values.len() / 2;
+        }
+    }
+
+    // Moves a used entry halfway to the front
+    fn touch(&mut self, index: usize) {
+        if index > 0 {
+            self.values.swap(index / 2, index);
+        }
+    }
+}
+
+// Endpoint and selector codebooks shared by every ETC1S slice in a file, with the Huffman
+// codes the slices are compressed with
+struct Etc1sCodebook {
+    endpoints: Vec<Endpoint>,
+    // A byte per row, 2 bits per texel from the left
+    selectors: Vec<[u8; 4]>,
+    endpoint_prediction: Huffman,
+    endpoint_delta: Huffman,
+    selector: Huffman,
+    selector_run: Huffman,
+    history_size: usize,
+}
+
+impl Etc1sCodebook {
+    fn decode(
+        endpoint_count: usize,
+        endpoint_data: &[u8],
+        selector_count: usize,
+        selector_data: &[u8],
+        table_data: &[u8],
+    ) -> Result<Self, String> {
+        if endpoint_count == 0 || selector_count == 0 {
+            return Err("ETC1S data has an empty codebook".to_string());
+        }
+
+        // Endpoints are deltas from the previous one, each channel coded by how bright it was
+        let mut reader = BitReader::new(endpoint_data);
+        let color_deltas = [reader.huffman()?, reader.huffman()?, reader.huffman()?];
+        let intensity_delta = reader.huffman()?;
+        let grayscale = reader.bits(1) != 0;
+        let mut endpoints = Vec::with_capacity(endpoint_count);
+        let mut previous = Endpoint { color: [16; 3], intensity: 0 };
+        for _ in 0..endpoint_count {
+            previous.intensity = ((reader.symbol(&intensity_delta)? + previous.intensity as u32) & 7) as u8;
+            for channel in 0..if grayscale { 1 } else { 3 } {
+                let last = previous.color[channel];
+                let code = &color_deltas[if last <= 9 {
+                    0
+                } else if last <= 21 {
+                    1
+                } else {
+                    2
+                }];
+                previous.color[channel] = ((reader.symbol(code)? + last as u32) & 31) as u8;
+            }
+            if grayscale {
+                previous.color = [previous.color[0]; 3];
+            }
+            endpoints.push(previous);
+        }
+
+        // Selectors are raw, or each row XORed with the one of the previous selector
+        let mut reader = BitReader::new(selector_data);
+        if reader.bits(1) != 0 || reader.bits(1) != 0 {
+            return Err("ETC1S global selector codebooks aren't supported".to_string());
+        }
+        let mut selectors = Vec::with_capacity(selector_count);
+        if reader.bits(1) != 0 {
+            for _ in 0..selector_count {
+                selectors.push([0; 4].map(|_| reader.bits(8) as u8));
+            }
+        } else {
+            let delta = reader.huffman()?;
+            let mut previous = [0; 4].map(|_| reader.bits(8) as u8);
+            selectors.push(previous);
+            for _ in 1..selector_count {
+                for row in &mut previous {
+                    *row ^= reader.symbol(&delta)? as u8;
+                }
+                selectors.push(previous);
+            }
+        }
+
+        let mut reader = BitReader::new(table_data);
+        Ok(Self {
+            endpoints,
+            selectors,
+            endpoint_prediction: reader.huffman()?,
+            endpoint_delta: reader.huffman()?,
+            selector: reader.huffman()?,
+            selector_run: reader.huffman()?,
+            history_size: reader.bits(13) as usize,
+        })
+    }
+
+    // Writes a slice's colours into `pixels`, or only its green channel as alpha
+    fn decode_slice(&self, data: &[u8], shape: &LevelShape, pixels: &mut [u8], alpha: bool) -> Result<(), String> {
+        let (blocks_x, blocks_y) = shape.blocks();
+        let mut reader = BitReader::new(data);
+        let mut history = SelectorHistory::new(self.history_size);
+        let history_symbol = self.selectors.len();
+        let run_symbol = history_symbol + self.history_size;
+        // Endpoint indices of the row above, and prediction bits for the lower row of each
+        // 2x2 group of blocks
+        let mut above = vec![0; blocks_x];
+        let mut row = vec![0; blocks_x];
+        let mut lower_predictions = vec![0; blocks_x.div_ceil(2)];
+        let (mut previous_prediction, mut prediction_repeats) = (0, 0);
+        let mut previous_endpoint = 0;
+        let mut selector_repeats = 0;
+        for block_y in 0..blocks_y {
+            let mut predictions = 0;
+            for block_x in 0..blocks_x {
+                if block_x % 2 == 0 {
+                    predictions = if block_y % 2 == 0 {
+                        let bits = if prediction_repeats > 0 {
+                            prediction_repeats -= 1;
+                            previous_prediction
+                        } else {
+                            match reader.symbol(&self.endpoint_prediction)? {
+                                REPEAT_PREDICTION => {
+                                    prediction_repeats = reader.vlc(4)? + 2;
+                                    previous_prediction
+                                }
+                                bits => {
+                                    previous_prediction = bits;
+                                    bits
+                                }
+                            }
+                        };
+                        lower_predictions[block_x / 2] = bits >> 4;
+                        bits
+                    } else {
+                        lower_predictions[block_x / 2]
+                    };
+                }
+
+                // From the left, above, above left, or a delta from the previous block
+                let endpoint = match predictions & 3 {
+                    0 if block_x > 0 => previous_endpoint,
+                    1 if block_y > 0 => above[block_x],
+                    2 if block_x > 0 && block_y > 0 => above[block_x - 1],
+                    3 => (previous_endpoint + reader.symbol(&self.endpoint_delta)? as usize) % self.endpoints.len(),
+                    _ => return Err("Invalid ETC1S endpoint prediction".to_string()),
+                };
+                predictions >>= 2;
+                previous_endpoint = endpoint;
+                row[block_x] = endpoint;
+
+                // A literal, an entry of the history, or a run of the most recent entry
+                let selector = if selector_repeats > 0 {
+                    selector_repeats -= 1;
+                    history.get(0)?
+                } else {
+                    match reader.symbol(&self.selector)? as usize {
+                        symbol if symbol == run_symbol => {
+                            let run = reader.symbol(&self.selector_run)?;
+                            let count = if run == LONG_RUN { reader.vlc(7)? + 3 } else { run + 3 };
+                            selector_repeats = count as usize - 1;
+                            history.get(0)?
+                        }
+                        symbol if symbol > run_symbol => return Err("Invalid ETC1S selector symbol".to_string()),
+                        symbol if symbol >= history_symbol => {
+                            let selector = history.get(symbol - history_symbol)?;
+                            history.touch(symbol - history_symbol);
+                            selector
+                        }
+                        symbol => {
+                            history.add(symbol);
+                            symbol
+                        }
+                    }
+                };
+
+                let endpoint = self.endpoints[endpoint];
+                let rows = self.selectors.get(selector).ok_or("ETC1S selector index is out of range")?;
+                let modifiers = ETC1_MODIFIERS[endpoint.intensity as usize];
+                for (texel, offset) in block_texels(shape, block_x, block_y) {
+                    let modifier = modifiers[(rows[texel / 4] >> (texel % 4 * 2)) as usize & 3];
+                    let channel = |color: u8| ((((color << 3) | (color >> 2)) as i32) + modifier).clamp(0, 255) as u8;
+                    if alpha {
+                        pixels[offset + 3] = channel(endpoint.color[1]);
+                    } else {
+                        let [r, g, b] = endpoint.color.map(channel);
+                        pixels[offset..offset + 4].copy_from_slice(&[r, g, b, 255]);
+                    }
+                }
+            }
+            std::mem::swap(&mut above, &mut row);
+        }
+        Ok(())
+    }
+}
+
+// ETC1S levels to RGBA8. `global` is the file's supercompression global data: the
+// codebooks, Huffman tables and where each image's slices lie in its level.
+pub fn decode_etc1s(global: &[u8], levels: &[Vec<u8>], shapes: &[LevelShape]) -> Result<Vec<Vec<u8>>, String> {
+    check_decoded_size(shapes)?;
+    let truncated = || "KTX2 Basis global data is truncated".to_string();
+    let field = |offset: usize| read_u32(global, offset).ok_or_else(truncated);
+    let endpoint_count = (field(0)? & 0xFFFF) as usize;
+    let selector_count = (field(0)? >> 16) as usize;
+    let image_count: usize = shapes.iter().map(|shape| shape.images).sum();
+    let mut offset = GLOBAL_HEADER_SIZE + image_count * IMAGE_DESC_SIZE;
+    let mut sections = [&[][..]; 4];
+    for (index, section) in sections.iter_mut().enumerate() {
+        let length = field(4 + index * 4)? as usize;
+        *section = global.get(offset..offset + length).ok_or_else(truncated)?;
+        offset += length;
+    }
+    let [endpoint_data, selector_data, table_data, _] = sections;
+    let codebook = Etc1sCodebook::decode(endpoint_count, endpoint_data, selector_count, selector_data, table_data)?;
+
+    let mut image = 0;
+    let mut decoded = Vec::with_capacity(levels.len());
+    for (level, (data, shape)) in levels.iter().zip(shapes).enumerate() {
+        let mut pixels = vec![0; shape.image_size() * shape.images];
+        for output in pixels.chunks_exact_mut(shape.image_size()) {
+            let desc = GLOBAL_HEADER_SIZE + image * IMAGE_DESC_SIZE;
+            image += 1;
+            let [flags, rgb_offset, rgb_length, alpha_offset, alpha_length] =
+                [0, 4, 8, 12, 16].map(|offset| read_u32(global, desc + offset).unwrap_or(0) as usize);
+            if flags as u32 & IS_P_FRAME != 0 {
+                return Err("ETC1S video frames aren't supported".to_string());
+            }
+            let slice = |offset: usize, length: usize| {
+                data.get(offset..offset + length)
+                    .ok_or_else(|| format!("ETC1S slice in KTX2 level {} is out of bounds", level))
+            };
+            codebook.decode_slice(slice(rgb_offset, rgb_length)?, shape, output, false)?;
+            if alpha_length > 0 {
+                codebook.decode_slice(slice(alpha_offset, alpha_length)?, shape, output, true)?;
+            }
+        }
+        decoded.push(pixels);
+    }
+    Ok(decoded)
+}
+
+struct UastcMode {
+    // Prefix code in the block's low bits
+    code: u8,
+    code_bits: u32,
+    // Transcoding hints for other formats, skipped
+    hint_bits: u32,
+    subsets: usize,
+    planes: usize,
+    // 2 for luminance and alpha, 3 for RGB, 4 for RGBA
+    components: usize,
+    // Row of `ISE_RANGES`
+    endpoint_range: usize,
+    weight_bits: u32,
+}
+
+#[allow(clippy::too_many_arguments)]
+const fn mode(
+    code: u8,
+    code_bits: u32,
+    hint_bits: u32,
+    subsets: usize,
+    planes: usize,
+    components: usize,
+    endpoint_range: usize,
+    weight_bits: u32,
+) -> UastcMode {
+    UastcMode { code, code_bits, hint_bits, subsets, planes, components, endpoint_range, weight_bits }
+}
+
+const UASTC_MODES: [UastcMode; 19] = [
+    mode(0x01, 4, 15, 1, 1, 3, 19, 4),
+    mode(0x35, 6, 15, 1, 1, 3, 20, 2),
+    mode(0x1D, 5, 15, 2, 1, 3, 8, 3),
+    mode(0x03, 5, 15, 3, 1, 3, 7, 2),
+    mode(0x13, 5, 15, 2, 1, 3, 12, 2),
+    mode(0x0B, 5, 15, 1, 1, 3, 20, 3),
+    mode(0x1B, 5, 15, 1, 2, 3, 18, 2),
+    mode(0x07, 5, 15, 2, 1, 3, 12, 2),
+    // Solid colour
+    mode(0x17, 5, 0, 0, 0, 4, 0, 0),
+    mode(0x0F, 5, 23, 2, 1, 4, 8, 2),
+    mode(0x02, 3, 17, 1, 1, 4, 13, 4),
+    mode(0x00, 2, 17, 1, 2, 4, 13, 2),
+    mode(0x06, 3, 17, 1, 1, 4, 19, 3),
+    mode(0x1F, 5, 23, 1, 2, 4, 20, 1),
+    mode(0x0D, 5, 23, 1, 1, 4, 20, 2),
+    mode(0x05, 7, 23, 1, 1, 2, 20, 4),
+    mode(0x15, 6, 23, 2, 1, 2, 20, 2),
+    mode(0x25, 6, 23, 1, 2, 2, 20, 2),
+    mode(0x09, 4, 15, 1, 1, 3, 11, 5),
+];
+const SOLID_MODE: usize = 8;
+
+// ASTC integer sequence encoding ranges as [bits, trits, quints]
+const ISE_RANGES: [[u32; 3]; 21] = [
+    [1, 0, 0],
+    [0, 1, 0],
+    [2, 0, 0],
+    [0, 0, 1],
+    [1, 1, 0],
+    [3, 0, 0],
+    [1, 0, 1],
+    [2, 1, 0],
+    [4, 0, 0],
+    [2, 0, 1],
+    [3, 1, 0],
+    [5, 0, 0],
+    [3, 0, 1],
+    [4, 1, 0],
+    [6, 0, 0],
+    [4, 0, 1],
+    [5, 1, 0],
+    [7, 0, 0],
+    [5, 0, 1],
+    [6, 1, 0],
+    [8, 0, 0],
+];
+
+// ASTC partition seeds of the patterns UASTC can use: the two-subset ones shared with
+// BC7, the three-subset ones, and the two-subset stand-ins mode 7 uses for BC7
+// three-subset patterns
+const TWO_SUBSET_SEEDS: [u16; 30] = [
+    28, 20, 16, 29, 91, 9, 107, 72, 149, 204, 50, 114, 496, 17, 78, 39, 252, 828, 43, 156, 116, 210, 476, 273, 684,
+    359, 246, 195, 694, 524,
+];
+const THREE_SUBSET_SEEDS: [u16; 11] = [260, 74, 32, 156, 183, 15, 745, 0, 335, 902, 254];
+const MODE_7_SEEDS: [u16; 19] =
+    [36, 48, 61, 137, 161, 183, 226, 281, 302, 307, 479, 495, 593, 594, 605, 799, 812, 988, 993];
+
+// Subset of each texel for every partition pattern, worked out once per decode
+struct Partitions {
+    two: Vec<[u8; 16]>,
+    three: Vec<[u8; 16]>,
+    mode_7: Vec<[u8; 16]>,
+}
+
+impl Partitions {
+    fn new() -> Self {
+        let patterns = |seeds: &[u16], subsets: u32| -> Vec<[u8; 16]> {
+            seeds
+                .iter()
+                .map(|&seed| std::array::from_fn(|texel| astc_partition(seed as u32, subsets, texel as u32)))
+                .collect()
+        };
+        Self {
+            two: patterns(&TWO_SUBSET_SEEDS, 2),
+            three: patterns(&THREE_SUBSET_SEEDS, 3),
+            mode_7: patterns(&MODE_7_SEEDS, 2),
+        }
+    }
+}
+
+// ASTC's partition hash for a texel of a 4x4 block
+fn astc_partition(seed: u32, subsets: u32, texel: u32) -> u8 {
+    // Small blocks sample the hash at twice the spacing
+    let (x, y) = ((texel % 4) << 1, (texel / 4) << 1);
+    let seed = seed + (subsets - 1) * 1024;
+    let mut random = seed;
+    random ^= random >> 15;
+    random = random.wrapping_sub(random << 17);
+    random = random.wrapping_add(random << 7);
+    random = random.wrapping_add(random << 4);
+    random ^= random >> 5;
+    random = random.wrapping_add(random << 16);
+    random ^= random >> 7;
+    random ^= random >> 3;
+    random ^= random << 6;
+    random ^= random >> 17;
+
+    // Only the x and y terms of the first three lines matter for 2D blocks of up to three subsets
+    let (shift_a, shift_b) = match (seed & 1 != 0, seed & 2 != 0) {
+        (true, small) => (if small { 4 } else { 5 }, if subsets == 3 { 6 } else { 5 }),
+        (false, small) => (if subsets == 3 { 6 } else { 5 }, if small { 4 } else { 5 }),
+    };
+    let seeds: [u32; 6] = std::array::from_fn(|index| {
+        let value = (random >> (index * 4)) & 0xF;
+        (value * value) >> if index % 2 == 0 { shift_a } else { shift_b }
+    });
+
+    let a = (seeds[0] * x + seeds[1] * y + (random >> 14)) & 0x3F;
+    let b = (seeds[2] * x + seeds[3] * y + (random >> 10)) & 0x3F;
+    let c = if subsets >= 3 { (seeds[4] * x + seeds[5] * y + (random >> 6)) & 0x3F } else { 0 };
+    if a >= b && a >= c {
+        0
+    } else if b >= c {
+        1
+    } else {
+        2
+    }
+}
+
+// Repeats `value`'s `bits` bits until they fill `to` bits
+fn replicate(value: u32, bits: u32, to: u32) -> u32 {
+    let (mut result, mut filled) = (0, 0);
+    while filled < to {
+        result = (result << bits) | value;
+        filled += bits;
+    }
+    result >> (filled - to)
+}
+
+// ASTC's endpoint unquantisation to 8 bits
+fn unquantize_endpoint(value: u32, range: usize) -> u8 {
+    let [bits, trits, _] = ISE_RANGES[range];
+    let low = value & ((1 << bits) - 1);
+    if ISE_RANGES[range][1..] == [0, 0] {
+        return replicate(low, bits, 8) as u8;
+    }
+    let digit = value >> bits;
+    let bit = |index: u32| (low >> index) & 1;
+    let (b, c) = match (trits > 0, bits) {
+        (true, 1) => (0, 204),
+        (true, 2) => (bit(1) * 0b100010110, 93),
+        (true, 3) => (bit(2) * 0b100001010 + bit(1) * 0b010000101, 44),
+        (true, 4) => (bit(3) * 0b100000100 + bit(2) * 0b010000010 + bit(1) * 0b001000001, 22),
+        (true, 5) => (bit(4) * 0b100000010 + bit(3) * 0b010000001 + bit(2) * 0b001000000 + bit(1) * 0b000100000, 11),
+        (true, _) => (
+            bit(5) * 0b100000001
+                + bit(4) * 0b010000000
+                + bit(3) * 0b001000000
+                + bit(2) * 0b000100000
+                + bit(1) * 0b000010000,
+            5,
+        ),
+        (false, 1) => (0, 113),
+        (false, 2) => (bit(1) * 0b100001100, 54),
+        (false, 3) => (bit(2) * 0b100000101 + bit(1) * 0b010000010, 26),
+        (false, 4) => (bit(3) * 0b100000010 + bit(2) * 0b010000001 + bit(1) * 0b001000000, 13),
+        (false, _) => (bit(4) * 0b100000001 + bit(3) * 0b010000000 + bit(2) * 0b001000000 + bit(1) * 0b000100000, 6),
+    };
+    let a = if low & 1 != 0 { 0x1FF } else { 0 };
+    let t = (digit * c + b) ^ a;
+    ((a & 0x80) | (t >> 2)) as u8
+}
+
+fn interpolate(low: u8, high: u8, weight: u32, srgb: bool) -> u8 {
+    let expand = |value: u8| if srgb { ((value as u32) << 8) | 0x80 } else { value as u32 * 257 };
+    (((expand(low) * (64 - weight) + expand(high) * weight + 32) >> 6) >> 8) as u8
+}
+
+// LSB-first reader over a 128-bit block
+struct BlockReader {
+    bits: u128,
+    position: u32,
+}
+
+impl BlockReader {
+    fn read(&mut self, count: u32) -> u32 {
+        let value = (self.bits >> self.position) as u32 & ((1u64 << count) - 1) as u32;
+        self.position += count;
+        value
+    }
+}
+
+fn decode_uastc_block(block: &[u8], partitions: &Partitions, srgb: bool) -> Result<[[u8; 4]; 16], String> {
+    let bits = u128::from_le_bytes(block.try_into().map_err(|_| "UASTC block is truncated")?);
+    let (index, mode) = UASTC_MODES
+        .iter()
+        .enumerate()
+        .find(|(_, mode)| bits as u8 & ((1 << mode.code_bits) - 1) == mode.code)
+        .ok_or("Invalid UASTC block mode")?;
+    let mut reader = BlockReader { bits, position: mode.code_bits };
+    if index == SOLID_MODE {
+        let color = [0; 4].map(|_| reader.read(8) as u8);
+        return Ok([color; 16]);
+    }
+    reader.position += mode.hint_bits;
+
+    let pattern = match index {
+        2 | 4 | 7 | 9 | 16 => reader.read(5),
+        3 => reader.read(4),
+        _ => 0,
+    } as usize;
+    let partition = match index {
+        2 | 4 | 9 | 16 => partitions.two.get(pattern),
+        3 => partitions.three.get(pattern),
+        7 => partitions.mode_7.get(pattern),
+        _ => Some(&[0; 16]),
+    }
+    .ok_or("Invalid UASTC partition pattern")?;
+    // Channel using the second plane of weights
+    let second_plane = match index {
+        6 | 11 | 13 => reader.read(2) as usize,
+        17 => 3,
+        _ => 4,
+    };
+
+    // Trit or quint digits come first, packed in bundles, then each value's low bits
+    let values = mode.components * 2 * mode.subsets;
+    let [bits_per_value, trits, quints] = ISE_RANGES[mode.endpoint_range];
+    let mut digits = [0u32; 18];
+    if trits > 0 || quints > 0 {
+        let (bundle, base) = if trits > 0 { (5, 3) } else { (3, 5) };
+        for (index, chunk) in digits[..values].chunks_mut(bundle).enumerate() {
+            let size = match (trits > 0, values - index * bundle) {
+                (true, 1) => 2,
+                (true, 2) => 4,
+                (true, 3) => 5,
+                (true, 4) => 7,
+                (true, _) => 8,
+                (false, 1) => 3,
+                (false, 2) => 5,
+                (false, _) => 7,
+            };
+            let mut packed = reader.read(size);
+            for digit in chunk {
+                *digit = packed % base;
+                packed /= base;
+            }
+        }
+    }
+    let mut endpoints = [0u8; 18];
+    for (endpoint, digit) in endpoints[..values].iter_mut().zip(digits) {
+        let value = (digit << bits_per_value) | reader.read(bits_per_value);
+        *endpoint = unquantize_endpoint(value, mode.endpoint_range);
+    }
+
+    // The first weight of each subset, or of each plane, drops its top bit
+    let mut weights = [0u32; 32];
+    for (index, weight) in weights[..16 * mode.planes].iter_mut().enumerate() {
+        let anchor = if mode.planes == 2 { index < 2 } else { !partition[..index].contains(&partition[index]) };
+        let value = reader.read(mode.weight_bits - anchor as u32);
+        let expanded = replicate(value, mode.weight_bits, 6);
+        *weight = if expanded > 32 { expanded + 1 } else { expanded };
+    }
+
+    let mut texels = [[0u8; 4]; 16];
+    for (texel, color) in texels.iter_mut().enumerate() {
+        let values = &endpoints[partition[texel] as usize * mode.components * 2..];
+        let (low, high) = match mode.components {
+            2 => ([values[0], values[0], values[0], values[2]], [values[1], values[1], values[1], values[3]]),
+            3 => ([values[0], values[2], values[4], 255], [values[1], values[3], values[5], 255]),
+            _ => ([values[0], values[2], values[4], values[6]], [values[1], values[3], values[5], values[7]]),
+        };
+        for channel in 0..4 {
+            let weight = match mode.planes {
+                2 => weights[texel * 2 + (channel == second_plane) as usize],
+                _ => weights[texel],
+            };
+            color[channel] = interpolate(low[channel], high[channel], weight, srgb);
+        }
+    }
+    Ok(texels)
+}
+
+// UASTC levels to RGBA8
+pub fn decode_uastc(levels: &[Vec<u8>], shapes: &[LevelShape], srgb: bool) -> Result<Vec<Vec<u8>>, String> {
+    check_decoded_size(shapes)?;
+    let partitions = Partitions::new();
+    let mut decoded = Vec::with_capacity(levels.len());
+    for (level, (data, shape)) in levels.iter().zip(shapes).enumerate() {
+        let (blocks_x, blocks_y) = shape.blocks();
+        let expected = blocks_x * blocks_y * 16 * shape.images;
+        if data.len() < expected {
+            return Err(format!(
+                "KTX2 level {} has {} bytes of UASTC blocks, expected {}",
+                level,
+                data.len(),
+                expected
+            ));
+        }
+        let mut pixels = vec![0; shape.image_size() * shape.images];
+        let mut blocks = data.chunks_exact(16);
+        for output in pixels.chunks_exact_mut(shape.image_size()) {
+            for block_y in 0..blocks_y {
+                for block_x in 0..blocks_x {
+                    let block = blocks.next().unwrap_or_default();
+                    let texels = decode_uastc_block(block, &partitions, srgb)
+                        .map_err(|e| format!("{} in KTX2 level {}", e, level))?;
+                    for (texel, offset) in block_texels(shape, block_x, block_y) {
+                        output[offset..offset + 4].copy_from_slice(&texels[texel]);
+                    }
+                }
+            }
+        }
+        decoded.push(pixels);
+    }
+    Ok(decoded)
+}
@@ -0,0 +1,359 @@
+// src/texture/encode.rs
+// Block compression of RGBA8 images for transcoded textures: BC7 mode 6, single-partition
+// ASTC 4x4 and ETC2 RGBA8. The encoders run while textures load, so they fit a line
+// through each block's colours and pick the nearest palette entries rather than search.
+use glam::Vec4;
+use wgpu::{AstcBlock, TextureFormat};
+
+// BC7 4-bit index weights, out of 64
+const BC7_WEIGHTS: [u32; 16] = [0, 4, 9, 13, 17, 21, 26, 30, 34, 38, 43, 47, 51, 55, 60, 64];
+// Unquantised ASTC weights, out of 64, for 2 and 3 bits
+const ASTC_WEIGHTS_2: [u32; 4] = [0, 21, 43, 64];
+const ASTC_WEIGHTS_3: [u32; 8] = [0, 9, 18, 27, 37, 46, 55, 64];
+// ASTC block modes for a 4x4 grid of single-plane weights with 2 and 3 bits
+const ASTC_MODE_WEIGHTS_2: u32 = 66;
+const ASTC_MODE_WEIGHTS_3: u32 = 83;
+// ASTC colour endpoint modes for direct RGB and RGBA
+const ASTC_CEM_RGB: u32 = 8;
+const ASTC_CEM_RGBA: u32 = 12;
+
+// ETC1 modifiers by table as (small, large); each is added or subtracted
+const ETC1_MODIFIERS: [[i32; 2]; 8] = [[2, 8], [5, 17], [9, 29], [13, 42], [18, 60], [24, 80], [33, 106], [47, 183]];
+// EAC alpha modifiers by table, before the multiplier
+const EAC_MODIFIERS: [[i32; 8]; 16] = [
+    [-3, -6, -9, -15, 2, 5, 8, 14],
+    [-3, -7, -10, -13, 2, 6, 9, 12],
+    [-2, -5, -8, -13, 1, 4, 7, 12],
+    [-2, -4, -6, -13, 1, 3, 5, 12],
+    [-3, -6, -8, -12, 2, 5, 7, 11],
+    [-3, -7, -9, -11, 2, 6, 8, 10],
+    [-4, -7, -8, -11, 3, 6, 7, 10],
+    [-3, -5, -8, -11, 2, 4, 7, 10],
+    [-2, -6, -8, -10, 1, 5, 7, 9],
+    [-2, -5, -8, -10, 1, 4, 7, 9],
+    [-2, -4, -8, -10, 1, 3, 7, 9],
+    [-2, -5, -7, -10, 1, 4, 6, 9],
+    [-3, -4, -7, -10, 2, 3, 6, 9],
+    [-1, -2, -3, -10, 0, 1, 2, 9],
+    [-4, -6, -8, -9, 3, 5, 7, 8],
+    [-3, -5, -7, -9, 2, 4, 6, 8],
+];
+// Row of `EAC_MODIFIERS` with a zero modifier, at `EAC_ZERO_INDEX`, for flat alpha
+const EAC_ZERO_TABLE: u64 = 13;
+const EAC_ZERO_INDEX: u64 = 4;
+
+type Block = [[u8; 4]; 16];
+
+// Encodes one `width` x `height` RGBA8 image as `format`, which may be RGBA8 itself
+pub fn encode(pixels: &[u8], width: u32, height: u32, format: TextureFormat) -> Result<Vec<u8>, String> {
+    let encode_block: fn(&Block) -> [u8; 16] = match format {
+        TextureFormat::Rgba8Unorm | TextureFormat::Rgba8UnormSrgb => return Ok(pixels.to_vec()),
+        TextureFormat::Bc7RgbaUnorm | TextureFormat::Bc7RgbaUnormSrgb => bc7_block,
+        TextureFormat::Astc { block: AstcBlock::B4x4, .. } => astc_block,
+        TextureFormat::Etc2Rgba8Unorm | TextureFormat::Etc2Rgba8UnormSrgb => etc2_block,
+        other => return Err(format!("Can't encode textures as {:?}", other)),
+    };
+    if pixels.len() < width as usize * height as usize * 4 {
+        return Err(format!("{}x{} image has only {} bytes", width, height, pixels.len()));
+    }
+    let mut encoded = Vec::with_capacity(width.div_ceil(4) as usize * height.div_ceil(4) as usize * 16);
+    for block_y in 0..height.div_ceil(4) {
+        for block_x in 0..width.div_ceil(4) {
+            encoded.extend_from_slice(&encode_block(&gather(pixels, width, height, block_x, block_y)));
+        }
+    }
+    Ok(encoded)
+}
+
+// A 4x4 block of texels in raster order, repeating the last row and column past the edges
+fn gather(pixels: &[u8], width: u32, height: u32, block_x: u32, block_y: u32) -> Block {
+    std::array::from_fn(|texel| {
+        let x = (block_x * 4 + texel as u32 % 4).min(width - 1);
+        let y = (block_y * 4 + texel as u32 / 4).min(height - 1);
+        let offset = (y * width + x) as usize * 4;
+        pixels[offset..offset + 4].try_into().unwrap()
+    })
+}
+
+fn to_vec4(texel: [u8; 4]) -> Vec4 {
+    Vec4::from_array(texel.map(|channel| channel as f32))
+}
+
+fn distance(a: [u8; 4], b: [u8; 4]) -> u32 {
+    a.iter().zip(&b).map(|(&a, &b)| (a as i32 - b as i32).pow(2) as u32).sum()
+}
+
+// Index of the palette entry nearest each texel
+fn nearest(block: &Block, palette: &[[u8; 4]]) -> [usize; 16] {
+    block.map(|texel| {
+        palette.iter().enumerate().min_by_key(|&(_, &entry)| distance(texel, entry)).map_or(0, |(index, _)| index)
+    })
+}
+
+// Ends of the block's colours along their main axis, found by power iteration on the
+// covariance
+fn principal_endpoints(block: &Block) -> (Vec4, Vec4) {
+    let colors = block.map(to_vec4);
+    let mean = colors.iter().sum::<Vec4>() / 16.0;
+    let mut covariance = [[0.0f32; 4]; 4];
+    for color in &colors {
+        let offset = *color - mean;
+        for (row, &value) in covariance.iter_mut().zip(offset.as_ref()) {
+            for (cell, &other) in row.iter_mut().zip(offset.as_ref()) {
+                *cell += value * other;
+            }
+        }
+    }
+    let covariance = glam::Mat4::from_cols_array_2d(&covariance);
+    let (min, max) =
+        colors.iter().fold((Vec4::splat(255.0), Vec4::ZERO), |(min, max), &color| (min.min(color), max.max(color)));
+    let mut axis = max - min;
+    for _ in 0..8 {
+        axis = (covariance * axis).normalize_or_zero();
+    }
+    if axis == Vec4::ZERO {
+        return (mean, mean);
+    }
+    let (low, high) = colors.iter().fold((f32::MAX, f32::MIN), |(low, high), &color| {
+        let along = (color - mean).dot(axis);
+        (low.min(along), high.max(along))
+    });
+    let clamp = |color: Vec4| color.clamp(Vec4::ZERO, Vec4::splat(255.0));
+    (clamp(mean + axis * low), clamp(mean + axis * high))
+}
+
+// LSB-first writer of a 128-bit block
+#[derive(Default)]
+struct BlockWriter {
+    bits: u128,
+    position: u32,
+}
+
+impl BlockWriter {
+    fn write(&mut self, value: u32, count: u32) {
+        self.bits |= ((value as u128) & ((1 << count) - 1)) << self.position;
+        self.position += count;
+    }
+}
+
+// Mode 6: one subset, RGBA endpoints of 7 bits plus a shared low bit each, 4-bit indices
+fn bc7_block(block: &Block) -> [u8; 16] {
+    let (low, high) = principal_endpoints(block);
+    // Best low bit for each endpoint
+    let quantize = |color: Vec4| {
+        (0..2u32)
+            .map(|bit| {
+                let bit_value = bit as f32;
+                let high_bits =
+                    color.to_array().map(|channel| ((channel - bit_value) / 2.0).round().clamp(0.0, 127.0) as u32);
+                let expanded = high_bits.map(|channel| (channel << 1 | bit) as u8);
+                (high_bits, bit, distance(expanded, color.to_array().map(|channel| channel.round() as u8)))
+            })
+            .min_by_key(|&(_, _, error)| error)
+            .unwrap()
+    };
+    let mut ends = [quantize(low), quantize(high)];
+    let expand = |(high_bits, bit, _): ([u32; 4], u32, u32)| high_bits.map(|channel| (channel << 1 | bit) as u8);
+    let palette = |ends: &[([u32; 4], u32, u32); 2]| -> [[u8; 4]; 16] {
+        let (first, second) = (expand(ends[0]), expand(ends[1]));
+        BC7_WEIGHTS.map(|weight| {
+            std::array::from_fn(|channel| {
+                ((first[channel] as u32 * (64 - weight) + second[channel] as u32 * weight + 32) >> 6) as u8
+            })
+        })
+    };
+    let mut indices = nearest(block, &palette(&ends));
+    // The first texel's index drops its top bit, so it has to be in the lower half
+    if indices[0] >= 8 {
+        ends.swap(0, 1);
+        indices = indices.map(|index| 15 - index);
+    }
+
+    let mut writer = BlockWriter::default();
+    writer.write(1 << 6, 7);
+    for channel in 0..4 {
+        writer.write(ends[0].0[channel], 7);
+        writer.write(ends[1].0[channel], 7);
+    }
+    writer.write(ends[0].1, 1);
+    writer.write(ends[1].1, 1);
+    for (texel, &index) in indices.iter().enumerate() {
+        writer.write(index as u32, if texel == 0 { 3 } else { 4 });
+    }
+    writer.bits.to_le_bytes()
+}
+
+// One partition with 8-bit endpoints: RGB with 3-bit weights for opaque blocks, RGBA with
+// 2-bit weights otherwise, which is what still fits
+fn astc_block(block: &Block) -> [u8; 16] {
+    let opaque = block.iter().all(|texel| texel[3] == 255);
+    let (weights, block_mode, cem, channels) = if opaque {
+        (&ASTC_WEIGHTS_3[..], ASTC_MODE_WEIGHTS_3, ASTC_CEM_RGB, 3)
+    } else {
+        (&ASTC_WEIGHTS_2[..], ASTC_MODE_WEIGHTS_2, ASTC_CEM_RGBA, 4)
+    };
+    let (low, high) = principal_endpoints(block);
+    let round = |color: Vec4| color.to_array().map(|channel| channel.round() as u8);
+    let (mut low, mut high) = (round(low), round(high));
+    if opaque {
+        (low[3], high[3]) = (255, 255);
+    }
+    // A darker second endpoint means blue contraction to the decoder, so keep it the brighter
+    let brightness = |color: [u8; 4]| color[..3].iter().map(|&channel| channel as u32).sum::<u32>();
+    if brightness(high) < brightness(low) {
+        (low, high) = (high, low);
+    }
+    let palette: Vec<[u8; 4]> = weights
+        .iter()
+        .map(|&weight| {
+            std::array::from_fn(|channel| {
+                let (low, high) = (low[channel] as u32 * 257, high[channel] as u32 * 257);
+                (((low * (64 - weight) + high * weight + 32) >> 6) >> 8) as u8
+            })
+        })
+        .collect();
+    let indices = nearest(block, &palette);
+
+    let mut writer = BlockWriter::default();
+    writer.write(block_mode, 11);
+    // One partition
+    writer.write(0, 2);
+    writer.write(cem, 4);
+    for channel in 0..channels {
+        writer.write(low[channel] as u32, 8);
+        writer.write(high[channel] as u32, 8);
+    }
+    // Weights fill the block from the top bit down
+    let weight_bits = weights.len().trailing_zeros();
+    for (texel, &index) in indices.iter().enumerate() {
+        for bit in 0..weight_bits {
+            let position = 127 - (texel as u32 * weight_bits + bit);
+            writer.bits |= (((index >> bit) & 1) as u128) << position;
+        }
+    }
+    writer.bits.to_le_bytes()
+}
+
+// EAC alpha then an ETC1 colour block, both big-endian
+fn etc2_block(block: &Block) -> [u8; 16] {
+    let mut encoded = [0; 16];
+    encoded[..8].copy_from_slice(&eac_alpha(block).to_be_bytes());
+    encoded[8..].copy_from_slice(&etc1_color(block).to_be_bytes());
+    encoded
+}
+
+// Texel `texel` of a raster-order block as ETC and EAC number them, column by column
+fn column_major(texel: usize) -> usize {
+    (texel % 4) * 4 + texel / 4
+}
+
+fn eac_alpha(block: &Block) -> u64 {
+    let alphas = block.map(|texel| texel[3] as i32);
+    let (min, max) = (*alphas.iter().min().unwrap(), *alphas.iter().max().unwrap());
+    if min == max {
+        let indices = (0..16).fold(0, |bits, texel| bits | EAC_ZERO_INDEX << (45 - 3 * texel));
+        return (min as u64) << 56 | 1 << 52 | EAC_ZERO_TABLE << 48 | indices;
+    }
+    let mut best = (u32::MAX, 0);
+    for (table, modifiers) in EAC_MODIFIERS.iter().enumerate() {
+        let (lowest, highest) = (modifiers[3], modifiers[7]);
+        let ideal = (max - min) as f32 / (highest - lowest) as f32;
+        for multiplier in [ideal.floor(), ideal.ceil()].map(|multiplier| (multiplier as i32).clamp(1, 15)) {
+            let base = (((min + max) - (lowest + highest) * multiplier) as f32 / 2.0).round().clamp(0.0, 255.0) as i32;
+            let mut error = 0;
+            let mut indices = 0u64;
+            for (texel, &alpha) in alphas.iter().enumerate() {
+                let (index, distance) = modifiers
+                    .iter()
+                    .map(|&modifier| ((base + modifier * multiplier).clamp(0, 255) - alpha).pow(2) as u32)
+                    .enumerate()
+                    .min_by_key(|&(_, distance)| distance)
+                    .unwrap();
+                error += distance;
+                indices |= (index as u64) << (45 - 3 * column_major(texel));
+            }
+            if error < best.0 {
+                best = (error, (base as u64) << 56 | (multiplier as u64) << 52 | (table as u64) << 48 | indices);
+            }
+        }
+    }
+    best.1
+}
+
+// Individual or differential mode, whichever the two halves' colours allow, with the
+// better of the side-by-side and stacked splits
+fn etc1_color(block: &Block) -> u64 {
+    let mut best = (u32::MAX, 0);
+    for flip in [false, true] {
+        let half_of = |texel: usize| if flip { texel / 8 } else { texel % 4 / 2 };
+        let averages: [Vec4; 2] = std::array::from_fn(|half| {
+            (0..16).filter(|&texel| half_of(texel) == half).map(|texel| to_vec4(block[texel])).sum::<Vec4>() / 8.0
+        });
+        let quantize = |color: Vec4, levels: f32| {
+            color.truncate().to_array().map(|channel| (channel * levels / 255.0).round() as i32)
+        };
+        let (fine, coarse) =
+            (averages.map(|average| quantize(average, 31.0)), averages.map(|average| quantize(average, 15.0)));
+        let deltas: [i32; 3] = std::array::from_fn(|channel| fine[1][channel] - fine[0][channel]);
+        let differential = deltas.iter().all(|delta| (-4..=3).contains(delta));
+        let bases = if differential {
+            fine.map(|color| color.map(|channel| (channel << 3) | (channel >> 2)))
+        } else {
+            coarse.map(|color| color.map(|channel| (channel << 4) | channel))
+        };
+
+        let mut error = 0;
+        let mut tables = [0u64; 2];
+        let mut indices = 0u64;
+        for half in 0..2 {
+            let (table_error, table, half_indices) = (0..8)
+                .map(|table| {
+                    let [small, large] = ETC1_MODIFIERS[table];
+                    let mut table_error = 0;
+                    let mut half_indices = 0u64;
+                    for texel in (0..16).filter(|&texel| half_of(texel) == half) {
+                        let (index, distance) = [small, large, -small, -large]
+                            .iter()
+                            .map(|&modifier| {
+                                let color = bases[half].map(|channel| (channel + modifier).clamp(0, 255) as u8);
+                                distance(
+                                    [color[0], color[1], color[2], 0],
+                                    [block[texel][0], block[texel][1], block[texel][2], 0],
+                                )
+                            })
+                            .enumerate()
+                            .min_by_key(|&(_, distance)| distance)
+                            .unwrap();
+                        table_error += distance;
+                        let position = column_major(texel);
+                        half_indices |= ((index as u64 >> 1) << (16 + position)) | ((index as u64 & 1) << position);
+                    }
+                    (table_error, table, half_indices)
+                })
+                .min_by_key(|&(table_error, _, _)| table_error)
+                .unwrap();
+            error += table_error;
+            tables[half] = table as u64;
+            indices |= half_indices;
+        }
+        if error >= best.0 {
+            continue;
+        }
+        let colors = if differential {
+            (0..3).fold(0u64, |bits, channel| {
+                let shift = 59 - channel * 8;
+                bits | (fine[0][channel] as u64) << shift | ((deltas[channel] & 7) as u64) << (shift - 3)
+            })
+        } else {
+            (0..3).fold(0u64, |bits, channel| {
+                let shift = 60 - channel * 8;
+                bits | (coarse[0][channel] as u64) << shift | (coarse[1][channel] as u64) << (shift - 4)
+            })
+        };
+        best = (
+            error,
+            colors | tables[0] << 37 | tables[1] << 34 | (differential as u64) << 33 | (flip as u64) << 32 | indices,
+        );
+    }
+    best.1
+}
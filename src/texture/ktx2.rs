@@ -0,0 +1,329 @@
+// src/texture/ktx2.rs
+use super::basis::{self, LevelShape};
+use super::{encode, Texture};
+use std::io::Read;
+use wgpu::{AstcBlock, AstcChannel, TextureFormat};
+
+const IDENTIFIER: [u8; 12] = [0xAB, 0x4B, 0x54, 0x58, 0x20, 0x32, 0x30, 0xBB, 0x0D, 0x0A, 0x1A, 0x0A];
+const HEADER_SIZE: usize = 80;
+
+const SUPERCOMPRESSION_NONE: u32 = 0;
+const SUPERCOMPRESSION_BASIS_LZ: u32 = 1;
+const SUPERCOMPRESSION_ZSTD: u32 = 2;
+// Zstd levels are decompressed into at most this much up front, however large their
+// header says they are, and never past `MAX_ZSTD_LEVEL_SIZE`
+const ZSTD_PREALLOCATION: usize = 16 << 20;
+const MAX_ZSTD_LEVEL_SIZE: u64 = 1 << 30;
+
+// Data format descriptor transfer function for sRGB
+const TRANSFER_SRGB: u8 = 2;
+
+#[derive(Debug, Clone, Copy)]
+pub struct Ktx2Header {
+    pub vk_format: u32,
+    pub width: u32,
+    pub height: u32,
+    pub depth: u32,
+    pub layers: u32,
+    pub faces: u32,
+    pub levels: u32,
+    pub supercompression: u32,
+}
+
+// Parsed KTX2 container with every mip level decompressed and ready for upload
+pub struct Ktx2Image {
+    pub header: Ktx2Header,
+    pub format: TextureFormat,
+    // Level 0 (largest) first
+    pub levels: Vec<Vec<u8>>,
+    // Basis Universal (ETC1S or UASTC) payload, decoded to RGBA8 in `levels`; `upload`
+    // encodes it in the best block format the device samples
+    pub basis: bool,
+}
+
+fn read_u32(data: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(data[offset..offset + 4].try_into().unwrap())
+}
+
+fn read_u64(data: &[u8], offset: usize) -> u64 {
+    u64::from_le_bytes(data[offset..offset + 8].try_into().unwrap())
+}
+
+impl Ktx2Image {
+    pub fn parse(data: &[u8]) -> Result<Self, String> {
+        if data.len() < HEADER_SIZE || data[..12] != IDENTIFIER {
+            return Err("Not a KTX2 file".to_string());
+        }
+        let header = Ktx2Header {
+            vk_format: read_u32(data, 12),
+            width: read_u32(data, 20).max(1),
+            height: read_u32(data, 24).max(1),
+            depth: read_u32(data, 28).max(1),
+            layers: read_u32(data, 32).max(1),
+            faces: read_u32(data, 36).max(1),
+            levels: read_u32(data, 40).max(1),
+            supercompression: read_u32(data, 44),
+        };
+
+        // Each level halves the largest dimension until it reaches 1
+        let max_levels = 32 - header.width.max(header.height).max(header.depth).leading_zeros();
+        if header.levels > max_levels {
+            return Err(format!(
+                "KTX2 file has {} mip levels, but {}x{}x{} has at most {}",
+                header.levels, header.width, header.height, header.depth, max_levels
+            ));
+        }
+
+        // Basis payloads have no vkFormat; the descriptor's colour model says which they are
+        let (model, transfer) = read_dfd(data).unwrap_or_default();
+        let basis_model = (header.vk_format == 0).then_some(model);
+        if header.supercompression == SUPERCOMPRESSION_BASIS_LZ && basis_model != Some(basis::MODEL_ETC1S) {
+            return Err("BasisLZ supercompression without ETC1S data".to_string());
+        }
+        let srgb = transfer == TRANSFER_SRGB;
+        let format = match basis_model {
+            Some(basis::MODEL_ETC1S | basis::MODEL_UASTC) if srgb => TextureFormat::Rgba8UnormSrgb,
+            Some(basis::MODEL_ETC1S | basis::MODEL_UASTC) => TextureFormat::Rgba8Unorm,
+            _ => vk_format_to_wgpu(header.vk_format)
+                .ok_or_else(|| format!("Unsupported KTX2 vkFormat {}", header.vk_format))?,
+        };
+
+        let level_index_end = HEADER_SIZE + header.levels as usize * 24;
+        if data.len() < level_index_end {
+            return Err("KTX2 level index is truncated".to_string());
+        }
+        let mut levels = Vec::with_capacity(header.levels as usize);
+        for level in 0..header.levels as usize {
+            let entry = HEADER_SIZE + level * 24;
+            let offset = read_u64(data, entry) as usize;
+            let length = read_u64(data, entry + 8) as usize;
+            let bytes = data
+                .get(offset..offset.saturating_add(length))
+                .ok_or_else(|| format!("KTX2 level {} is out of bounds", level))?;
+            levels.push(match header.supercompression {
+                // BasisLZ slices are decoded with the global data below
+                SUPERCOMPRESSION_NONE | SUPERCOMPRESSION_BASIS_LZ => bytes.to_vec(),
+                SUPERCOMPRESSION_ZSTD => {
+                    let size = read_u64(data, entry + 16);
+                    if size > MAX_ZSTD_LEVEL_SIZE {
+                        return Err(format!("KTX2 level {} decompresses to {} bytes, too many to load", level, size));
+                    }
+                    let mut decoded = Vec::with_capacity((size as usize).min(ZSTD_PREALLOCATION));
+                    ruzstd::decoding::StreamingDecoder::new(bytes)
+                        .map_err(|e| format!("Invalid Zstd data in KTX2 level {}: {}", level, e))?
+                        .take(size)
+                        .read_to_end(&mut decoded)
+                        .map_err(|e| format!("Failed to decompress KTX2 level {}: {}", level, e))?;
+                    decoded
+                }
+                other => return Err(format!("Unsupported KTX2 supercompression scheme {}", other)),
+            });
+        }
+
+        let shapes = level_shapes(&header);
+        let levels = match basis_model {
+            Some(basis::MODEL_ETC1S) => {
+                let offset = read_u64(data, 64) as usize;
+                let length = read_u64(data, 72) as usize;
+                let global = data
+                    .get(offset..offset.saturating_add(length))
+                    .ok_or("KTX2 supercompression global data is out of bounds")?;
+                basis::decode_etc1s(global, &levels, &shapes)?
+            }
+            Some(basis::MODEL_UASTC) => basis::decode_uastc(&levels, &shapes, srgb)?,
+            _ => levels,
+        };
+        Ok(Self { header, format, levels, basis: basis_model.is_some() })
+    }
+
+    // RGBA8 levels, such as a decoded Basis payload, re-encoded as `format`: BC7, ASTC 4x4,
+    // ETC2 RGBA8 or RGBA8
+    pub fn transcoded(&self, format: TextureFormat) -> Result<Self, String> {
+        if !matches!(self.format, TextureFormat::Rgba8Unorm | TextureFormat::Rgba8UnormSrgb) {
+            return Err(format!("Only RGBA8 textures can be transcoded, not {:?}", self.format));
+        }
+        let mut levels = Vec::with_capacity(self.levels.len());
+        for (bytes, shape) in self.levels.iter().zip(level_shapes(&self.header)) {
+            let mut encoded = Vec::new();
+            for image in bytes.chunks_exact(shape.image_size()).take(shape.images) {
+                encoded.extend(encode::encode(image, shape.width, shape.height, format)?);
+            }
+            levels.push(encoded);
+        }
+        Ok(Self { header: self.header, format, levels, basis: false })
+    }
+
+    pub fn upload(&self, device: &wgpu::Device, queue: &wgpu::Queue, label: &str) -> Result<Texture, String> {
+        if self.basis {
+            // Block formats need whole blocks at level 0, so odd sizes stay RGBA8
+            let whole_blocks = self.header.width.is_multiple_of(4) && self.header.height.is_multiple_of(4);
+            let format = if whole_blocks {
+                preferred_transcode_format(device.features(), self.format.is_srgb())
+            } else {
+                self.format
+            };
+            return self.transcoded(format)?.upload(device, queue, label);
+        }
+        let required = self.format.required_features();
+        if !device.features().contains(required) {
+            return Err(format!("{:?} needs {:?}, which this adapter doesn't support", self.format, required));
+        }
+
+        let header = &self.header;
+        let is_cube = header.faces == 6;
+        let size = wgpu::Extent3d {
+            width: header.width,
+            height: header.height,
+            depth_or_array_layers: if header.depth > 1 { header.depth } else { header.layers * header.faces },
+        };
+        let dimension = if header.depth > 1 { wgpu::TextureDimension::D3 } else { wgpu::TextureDimension::D2 };
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some(label),
+            size,
+            mip_level_count: header.levels,
+            sample_count: 1,
+            dimension,
+            format: self.format,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        });
+
+        let (block_width, block_height) = self.format.block_dimensions();
+        let block_size = self.format.block_copy_size(None).ok_or("KTX2 format has no copyable block size")?;
+        for (level, bytes) in self.levels.iter().enumerate() {
+            let level_size = size.mip_level_size(level as u32, dimension);
+            let blocks_x = level_size.width.div_ceil(block_width);
+            let blocks_y = level_size.height.div_ceil(block_height);
+            let expected = (blocks_x * blocks_y * block_size * level_size.depth_or_array_layers) as usize;
+            if bytes.len() < expected {
+                return Err(format!("KTX2 level {} has {} bytes, expected {}", level, bytes.len(), expected));
+            }
+            queue.write_texture(
+                wgpu::TexelCopyTextureInfo {
+                    texture: &texture,
+                    mip_level: level as u32,
+                    origin: wgpu::Origin3d::ZERO,
+                    aspect: wgpu::TextureAspect::All,
+                },
+                &bytes[..expected],
+                wgpu::TexelCopyBufferLayout {
+                    offset: 0,
+                    bytes_per_row: Some(blocks_x * block_size),
+                    rows_per_image: Some(blocks_y),
+                },
+                level_size.physical_size(self.format),
+            );
+        }
+
+        let view_dimension = match (dimension, is_cube, header.layers > 1) {
+            (wgpu::TextureDimension::D3, _, _) => wgpu::TextureViewDimension::D3,
+            (_, true, true) => wgpu::TextureViewDimension::CubeArray,
+            (_, true, false) => wgpu::TextureViewDimension::Cube,
+            (_, false, true) => wgpu::TextureViewDimension::D2Array,
+            _ => wgpu::TextureViewDimension::D2,
+        };
+        Ok(Texture::from_texture(texture, view_dimension))
+    }
+}
+
+pub fn load(device: &wgpu::Device, queue: &wgpu::Queue, path: &str) -> Result<Texture, String> {
+    let data = std::fs::read(path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+    let image = Ktx2Image::parse(&data).map_err(|e| format!("{}: {}", path, e))?;
+    image.upload(device, queue, path)
+}
+
+// Size and image count of each mip level, level 0 first
+fn level_shapes(header: &Ktx2Header) -> Vec<LevelShape> {
+    (0..header.levels)
+        .map(|level| LevelShape {
+            width: (header.width >> level).max(1),
+            height: (header.height >> level).max(1),
+            images: (header.layers as usize)
+                .saturating_mul(header.faces as usize)
+                .saturating_mul((header.depth >> level).max(1) as usize),
+        })
+        .collect()
+}
+
+// Colour model and transfer function from the basic block of the data format descriptor
+fn read_dfd(data: &[u8]) -> Option<(u8, u8)> {
+    let offset = read_u32(data, 48) as usize;
+    let block = data.get(offset.checked_add(4)?..offset.checked_add(16)?)?;
+    Some((block[8], block[10]))
+}
+
+// Best GPU format for Basis data on this device, as `Ktx2Image::upload` transcodes it
+pub fn preferred_transcode_format(features: wgpu::Features, srgb: bool) -> TextureFormat {
+    let astc_channel = if srgb { AstcChannel::UnormSrgb } else { AstcChannel::Unorm };
+    if features.contains(wgpu::Features::TEXTURE_COMPRESSION_BC) {
+        if srgb { TextureFormat::Bc7RgbaUnormSrgb } else { TextureFormat::Bc7RgbaUnorm }
+    } else if features.contains(wgpu::Features::TEXTURE_COMPRESSION_ASTC) {
+        TextureFormat::Astc { block: AstcBlock::B4x4, channel: astc_channel }
+    } else if features.contains(wgpu::Features::TEXTURE_COMPRESSION_ETC2) {
+        if srgb { TextureFormat::Etc2Rgba8UnormSrgb } else { TextureFormat::Etc2Rgba8Unorm }
+    } else if srgb {
+        TextureFormat::Rgba8UnormSrgb
+    } else {
+        TextureFormat::Rgba8Unorm
+    }
+}
+
+fn vk_format_to_wgpu(vk_format: u32) -> Option<TextureFormat> {
+    const ASTC_BLOCKS: [AstcBlock; 14] = [
+        AstcBlock::B4x4,
+        AstcBlock::B5x4,
+        AstcBlock::B5x5,
+        AstcBlock::B6x5,
+        AstcBlock::B6x6,
+        AstcBlock::B8x5,
+        AstcBlock::B8x6,
+        AstcBlock::B8x8,
+        AstcBlock::B10x5,
+        AstcBlock::B10x6,
+        AstcBlock::B10x8,
+        AstcBlock::B10x10,
+        AstcBlock::B12x10,
+        AstcBlock::B12x12,
+    ];
+    Some(match vk_format {
+        9 => TextureFormat::R8Unorm,
+        16 => TextureFormat::Rg8Unorm,
+        37 => TextureFormat::Rgba8Unorm,
+        43 => TextureFormat::Rgba8UnormSrgb,
+        44 => TextureFormat::Bgra8Unorm,
+        50 => TextureFormat::Bgra8UnormSrgb,
+        97 => TextureFormat::Rgba16Float,
+        109 => TextureFormat::Rgba32Float,
+        // BC1 RGB and RGBA share a block layout
+        131 | 133 => TextureFormat::Bc1RgbaUnorm,
+        132 | 134 => TextureFormat::Bc1RgbaUnormSrgb,
+        135 => TextureFormat::Bc2RgbaUnorm,
+        136 => TextureFormat::Bc2RgbaUnormSrgb,
+        137 => TextureFormat::Bc3RgbaUnorm,
+        138 => TextureFormat::Bc3RgbaUnormSrgb,
+        139 => TextureFormat::Bc4RUnorm,
+        140 => TextureFormat::Bc4RSnorm,
+        141 => TextureFormat::Bc5RgUnorm,
+        142 => TextureFormat::Bc5RgSnorm,
+        143 => TextureFormat::Bc6hRgbUfloat,
+        144 => TextureFormat::Bc6hRgbFloat,
+        145 => TextureFormat::Bc7RgbaUnorm,
+        146 => TextureFormat::Bc7RgbaUnormSrgb,
+        147 => TextureFormat::Etc2Rgb8Unorm,
+        148 => TextureFormat::Etc2Rgb8UnormSrgb,
+        149 => TextureFormat::Etc2Rgb8A1Unorm,
+        150 => TextureFormat::Etc2Rgb8A1UnormSrgb,
+        151 => TextureFormat::Etc2Rgba8Unorm,
+        152 => TextureFormat::Etc2Rgba8UnormSrgb,
+        153 => TextureFormat::EacR11Unorm,
+        154 => TextureFormat::EacR11Snorm,
+        155 => TextureFormat::EacRg11Unorm,
+        156 => TextureFormat::EacRg11Snorm,
+        157..=184 => {
+            let index = (vk_format - 157) as usize;
+            let channel = if index.is_multiple_of(2) { AstcChannel::Unorm } else { AstcChannel::UnormSrgb };
+            TextureFormat::Astc { block: ASTC_BLOCKS[index / 2], channel }
+        }
+        _ => return None,
+    })
+}
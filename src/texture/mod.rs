@@ -0,0 +1,54 @@
+// src/texture/mod.rs
+pub mod ktx2;
+pub mod video;
+pub mod basis;
+pub mod encode;
+
+pub struct Texture {
+    pub texture: wgpu::Texture,
+    pub view: wgpu::TextureView,
+    pub format: wgpu::TextureFormat,
+    pub size: wgpu::Extent3d,
+}
+
+impl Texture {
+    pub fn from_texture(texture: wgpu::Texture, view_dimension: wgpu::TextureViewDimension) -> Self {
+        let view = texture.create_view(&wgpu::TextureViewDescriptor {
+            dimension: Some(view_dimension),
+            ..Default::default()
+        });
+        Self {
+            format: texture.format(),
+            size: texture.size(),
+            texture,
+            view,
+        }
+    }
+
+    pub fn byte_size(&self) -> u64 {
+        byte_size(&self.texture)
+    }
+}
+
+// Bytes of GPU memory used by all mip levels and layers of a texture
+pub fn byte_size(texture: &wgpu::Texture) -> u64 {
+    let format = texture.format();
+    let (block_width, block_height) = format.block_dimensions();
+    let block_size = format.block_copy_size(None).unwrap_or(4) as u64;
+    (0..texture.mip_level_count())
+        .map(|level| {
+            let size = texture.size().mip_level_size(level, texture.dimension());
+            let blocks_x = size.width.div_ceil(block_width) as u64;
+            let blocks_y = size.height.div_ceil(block_height) as u64;
+            blocks_x * blocks_y * size.depth_or_array_layers as u64 * block_size * texture.sample_count() as u64
+        })
+        .sum()
+}
+
+// Compressed format families worth requesting when the adapter offers them
+pub fn compression_features(adapter_features: wgpu::Features) -> wgpu::Features {
+    adapter_features
+        & (wgpu::Features::TEXTURE_COMPRESSION_BC
+            | wgpu::Features::TEXTURE_COMPRESSION_ETC2
+            | wgpu::Features::TEXTURE_COMPRESSION_ASTC)
+}
@@ -0,0 +1,289 @@
+// src/texture/video.rs
+use crate::resource_registry::{ResourceRegistry, Tracked};
+use std::io::{ErrorKind, Read};
+use std::process::{Child, ChildStdout, Command, Stdio};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::thread::JoinHandle;
+
+// Decoded frames held ahead of playback; each is width * height * 4 bytes
+const BUFFERED_FRAMES: usize = 4;
+
+// Produces tightly packed RGBA8 frames. Runs on the player's decode thread.
+pub trait VideoDecoder: Send {
+    fn width(&self) -> u32;
+    fn height(&self) -> u32;
+    fn frame_rate(&self) -> f32;
+    // `None` at the end of the stream
+    fn next_frame(&mut self) -> Result<Option<Vec<u8>>, String>;
+    fn rewind(&mut self) -> Result<(), String>;
+}
+
+// Decodes through the system `ffmpeg` and `ffprobe` binaries, so any format they know plays
+// without linking a codec library. Both must be on PATH.
+pub struct FfmpegDecoder {
+    path: String,
+    width: u32,
+    height: u32,
+    frame_rate: f32,
+    child: Child,
+    stdout: ChildStdout,
+}
+
+impl FfmpegDecoder {
+    pub fn open(path: &str) -> Result<Self, String> {
+        let probe = Command::new("ffprobe")
+            .args(["-v", "error", "-select_streams", "v:0", "-show_entries", "stream=width,height,r_frame_rate", "-of", "csv=p=0"])
+            .arg(path)
+            .output()
+            .map_err(|e| format!("Failed to run ffprobe: {}", e))?;
+        if !probe.status.success() {
+            return Err(format!("ffprobe failed on {}: {}", path, String::from_utf8_lossy(&probe.stderr).trim()));
+        }
+        // e.g. "1920,1080,30000/1001"
+        let info = String::from_utf8_lossy(&probe.stdout);
+        let fields: Vec<&str> = info.trim().split(',').collect();
+        let [width, height, rate] = fields[..] else {
+            return Err(format!("{} has no video stream", path));
+        };
+        let parse = |field: &str| field.parse::<u32>().map_err(|_| format!("{}: bad stream info '{}'", path, info.trim()));
+        let (width, height) = (parse(width)?, parse(height)?);
+        let frame_rate = match rate.split_once('/') {
+            Some((numerator, denominator)) => parse(numerator)? as f32 / parse(denominator)?.max(1) as f32,
+            None => parse(rate)? as f32,
+        };
+        if width == 0 || height == 0 || frame_rate <= 0.0 {
+            return Err(format!("{}: bad stream info '{}'", path, info.trim()));
+        }
+
+        let (child, stdout) = Self::spawn(path)?;
+        Ok(Self { path: path.to_string(), width, height, frame_rate, child, stdout })
+    }
+
+    fn spawn(path: &str) -> Result<(Child, ChildStdout), String> {
+        let mut child = Command::new("ffmpeg")
+            .args(["-v", "error", "-i", path, "-map", "0:v:0", "-f", "rawvideo", "-pix_fmt", "rgba", "-"])
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|e| format!("Failed to run ffmpeg: {}", e))?;
+        let stdout = child.stdout.take().ok_or("ffmpeg has no stdout")?;
+        Ok((child, stdout))
+    }
+
+    fn stop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+impl VideoDecoder for FfmpegDecoder {
+    fn width(&self) -> u32 {
+        self.width
+    }
+
+    fn height(&self) -> u32 {
+        self.height
+    }
+
+    fn frame_rate(&self) -> f32 {
+        self.frame_rate
+    }
+
+    fn next_frame(&mut self) -> Result<Option<Vec<u8>>, String> {
+        let mut frame = vec![0; self.width as usize * self.height as usize * 4];
+        match self.stdout.read_exact(&mut frame) {
+            Ok(()) => Ok(Some(frame)),
+            Err(e) if e.kind() == ErrorKind::UnexpectedEof => Ok(None),
+            Err(e) => Err(format!("Failed to read frame from {}: {}", self.path, e)),
+        }
+    }
+
+    fn rewind(&mut self) -> Result<(), String> {
+        self.stop();
+        (self.child, self.stdout) = Self::spawn(&self.path)?;
+        Ok(())
+    }
+}
+
+impl Drop for FfmpegDecoder {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+enum DecodeEvent {
+    Frame(Vec<u8>),
+    Finished,
+    Failed(String),
+}
+
+// Streams a decoder into an Rgba8UnormSrgb texture for cutscenes and in-world screens.
+// Decoding happens on a background thread a few frames ahead; `update` shows whichever
+// frame matches the playback clock and skips frames when decoding falls behind.
+pub struct VideoPlayer {
+    pub paused: bool,
+    size: (u32, u32),
+    frame_rate: f32,
+    time: f32,
+    frames_shown: u64,
+    finished: bool,
+    error: Option<String>,
+    texture: Tracked<wgpu::Texture>,
+    view: wgpu::TextureView,
+    frames: Receiver<DecodeEvent>,
+    _decode_thread: JoinHandle<()>,
+}
+
+impl VideoPlayer {
+    // With `looping`, the decoder rewinds at the end of the stream and playback never finishes
+    pub fn new(
+        device: &wgpu::Device,
+        resources: &ResourceRegistry,
+        mut decoder: Box<dyn VideoDecoder>,
+        looping: bool,
+        label: &str,
+    ) -> Self {
+        let size = (decoder.width().max(1), decoder.height().max(1));
+        let frame_rate = decoder.frame_rate();
+        let texture = resources.create_texture(device, &wgpu::TextureDescriptor {
+            label: Some(label),
+            size: wgpu::Extent3d { width: size.0, height: size.1, depth_or_array_layers: 1 },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgba8UnormSrgb,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        }, "video");
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+        let (sender, frames) = mpsc::sync_channel(BUFFERED_FRAMES);
+        // Ends when the stream does, or when a send fails because the player was dropped
+        let decode_thread = std::thread::spawn(move || {
+            // An empty stream would otherwise rewind forever
+            let mut decoded_since_rewind = false;
+            loop {
+                let event = match decoder.next_frame() {
+                    Ok(Some(frame)) => {
+                        decoded_since_rewind = true;
+                        DecodeEvent::Frame(frame)
+                    }
+                    Ok(None) if looping && decoded_since_rewind => match decoder.rewind() {
+                        Ok(()) => {
+                            decoded_since_rewind = false;
+                            continue;
+                        }
+                        Err(e) => DecodeEvent::Failed(e),
+                    },
+                    Ok(None) => DecodeEvent::Finished,
+                    Err(e) => DecodeEvent::Failed(e),
+                };
+                let last = !matches!(event, DecodeEvent::Frame(_));
+                if sender.send(event).is_err() || last {
+                    break;
+                }
+            }
+        });
+
+        Self {
+            paused: false,
+            size,
+            frame_rate,
+            time: 0.0,
+            frames_shown: 0,
+            finished: false,
+            error: None,
+            texture,
+            view,
+            frames,
+            _decode_thread: decode_thread,
+        }
+    }
+
+    pub fn open(device: &wgpu::Device, resources: &ResourceRegistry, path: &str, looping: bool) -> Result<Self, String> {
+        let decoder = FfmpegDecoder::open(path)?;
+        Ok(Self::new(device, resources, Box::new(decoder), looping, path))
+    }
+
+    pub fn view(&self) -> &wgpu::TextureView {
+        &self.view
+    }
+
+    pub fn texture(&self) -> &wgpu::Texture {
+        &self.texture
+    }
+
+    pub fn size(&self) -> (u32, u32) {
+        self.size
+    }
+
+    // Seconds of video played so far
+    pub fn time(&self) -> f32 {
+        self.time
+    }
+
+    // True once the last frame was shown, or decoding failed
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+
+    // Advances the playback clock and uploads the frame due now, if it changed
+    pub fn update(&mut self, queue: &wgpu::Queue, delta_time: f32) {
+        if self.paused || self.finished {
+            return;
+        }
+        self.time += delta_time;
+        let due = (self.time * self.frame_rate) as u64 + 1;
+        let mut latest = None;
+        while self.frames_shown < due {
+            match self.frames.try_recv() {
+                Ok(DecodeEvent::Frame(frame)) => {
+                    latest = Some(frame);
+                    self.frames_shown += 1;
+                }
+                Ok(DecodeEvent::Finished) | Err(TryRecvError::Disconnected) => {
+                    self.finished = true;
+                    break;
+                }
+                Ok(DecodeEvent::Failed(e)) => {
+                    log::error!("Video playback stopped: {}", e);
+                    self.error = Some(e);
+                    self.finished = true;
+                    break;
+                }
+                // The decoder is behind; keep showing the last frame
+                Err(TryRecvError::Empty) => break,
+            }
+        }
+
+        let Some(frame) = latest else { return };
+        let expected = self.size.0 as usize * self.size.1 as usize * 4;
+        if frame.len() < expected {
+            let e = format!("Video frame has {} bytes, expected {}", frame.len(), expected);
+            log::error!("Video playback stopped: {}", e);
+            self.error = Some(e);
+            self.finished = true;
+            return;
+        }
+        queue.write_texture(
+            wgpu::TexelCopyTextureInfo {
+                texture: &self.texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            &frame[..expected],
+            wgpu::TexelCopyBufferLayout {
+                offset: 0,
+                bytes_per_row: Some(self.size.0 * 4),
+                rows_per_image: Some(self.size.1),
+            },
+            wgpu::Extent3d { width: self.size.0, height: self.size.1, depth_or_array_layers: 1 },
+        );
+    }
+}
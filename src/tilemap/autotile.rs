@@ -0,0 +1,148 @@
+// src/tilemap/autotile.rs
+use super::{TileLayer, EMPTY_TILE};
+use serde::Deserialize;
+
+// Neighbouring cells, y up
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub enum Neighbor {
+    N,
+    NE,
+    E,
+    SE,
+    S,
+    SW,
+    W,
+    NW,
+}
+
+impl Neighbor {
+    pub const ALL: [Neighbor; 8] =
+        [Neighbor::N, Neighbor::NE, Neighbor::E, Neighbor::SE, Neighbor::S, Neighbor::SW, Neighbor::W, Neighbor::NW];
+
+    pub fn bit(self) -> u8 {
+        1 << self as u8
+    }
+
+    pub fn offset(self) -> (i64, i64) {
+        match self {
+            Neighbor::N => (0, 1),
+            Neighbor::NE => (1, 1),
+            Neighbor::E => (1, 0),
+            Neighbor::SE => (1, -1),
+            Neighbor::S => (0, -1),
+            Neighbor::SW => (-1, -1),
+            Neighbor::W => (-1, 0),
+            Neighbor::NW => (-1, 1),
+        }
+    }
+}
+
+fn mask_of(neighbors: &[Neighbor]) -> u8 {
+    neighbors.iter().fold(0, |mask, n| mask | n.bit())
+}
+
+// Picks `tile` for a terrain cell when every `require` neighbour is terrain and no
+// `forbid` neighbour is. Neighbours in neither list don't matter.
+#[derive(Debug, Clone, Deserialize)]
+pub struct AutoTileRule {
+    pub tile: u32,
+    #[serde(default)]
+    pub require: Vec<Neighbor>,
+    #[serde(default)]
+    pub forbid: Vec<Neighbor>,
+}
+
+// Rule-based auto-tiling, loaded from RON, e.g. a grass top edge:
+// `(rules: [(tile: 2, require: [S, E, W], forbid: [N])], fallback: 1)`
+// Rules are tried in order and the first match wins, so list the most specific first.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct AutoTileRules {
+    pub rules: Vec<AutoTileRule>,
+    // Tile for terrain cells no rule matches
+    pub fallback: u32,
+    // Cells beyond the layer's edge count as terrain, so ground doesn't grow a border there
+    pub outside_is_terrain: bool,
+}
+
+impl AutoTileRules {
+    pub fn from_ron(source: &str) -> Result<Self, String> {
+        let rules: Self = ron::from_str(source).map_err(|e| format!("Failed to parse auto-tile rules: {}", e))?;
+        if let Some(rule) = rules.rules.iter().find(|r| mask_of(&r.require) & mask_of(&r.forbid) != 0) {
+            return Err(format!("Auto-tile rule for tile {} both requires and forbids a neighbour", rule.tile));
+        }
+        Ok(rules)
+    }
+
+    pub fn load(path: &str) -> Result<Self, String> {
+        let source = std::fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+        Self::from_ron(&source)
+    }
+
+    // The common 16-tile layout: tile `first + mask`, where mask has N = 1, E = 2, S = 4,
+    // W = 8 set for each side that continues the terrain
+    pub fn four_bit(first: u32) -> Self {
+        let sides = [Neighbor::N, Neighbor::E, Neighbor::S, Neighbor::W];
+        let rules = (0..16u32)
+            .map(|mask| {
+                let (require, forbid) = sides.iter().enumerate().partition::<Vec<_>, _>(|(i, _)| mask & (1 << i) != 0);
+                AutoTileRule {
+                    tile: first + mask,
+                    require: require.into_iter().map(|(_, &n)| n).collect(),
+                    forbid: forbid.into_iter().map(|(_, &n)| n).collect(),
+                }
+            })
+            .collect();
+        Self { rules, fallback: first, outside_is_terrain: false }
+    }
+
+    // Which neighbours of (x, y) are terrain, one `Neighbor::bit` each
+    pub fn neighbor_mask(&self, layer: &TileLayer, x: i64, y: i64, is_terrain: &impl Fn(u32) -> bool) -> u8 {
+        let (width, height) = layer.size();
+        Neighbor::ALL.iter().fold(0, |mask, &n| {
+            let (nx, ny) = (x + n.offset().0, y + n.offset().1);
+            let outside = nx < 0 || ny < 0 || nx >= width as i64 || ny >= height as i64;
+            let terrain = if outside { self.outside_is_terrain } else { is_terrain(layer.get(nx, ny)) };
+            if terrain { mask | n.bit() } else { mask }
+        })
+    }
+
+    pub fn pick(&self, mask: u8) -> u32 {
+        self.rules
+            .iter()
+            .find(|rule| mask & mask_of(&rule.require) == mask_of(&rule.require) && mask & mask_of(&rule.forbid) == 0)
+            .map_or(self.fallback, |rule| rule.tile)
+    }
+
+    // Re-tiles every terrain cell of `layer`. `is_terrain` must accept every tile the rules
+    // produce as well as whatever the terrain was painted with.
+    pub fn apply(&self, layer: &mut TileLayer, is_terrain: impl Fn(u32) -> bool) {
+        let (width, height) = layer.size();
+        self.apply_region(layer, (0, 0), (width, height), &is_terrain);
+    }
+
+    // Sets or clears terrain at (x, y) at runtime, e.g. digging, and re-tiles it and its
+    // neighbours. New terrain is painted with `fallback` first, so that must be non-empty.
+    pub fn set_terrain(&self, layer: &mut TileLayer, x: u32, y: u32, terrain: bool, is_terrain: impl Fn(u32) -> bool) {
+        layer.set(x, y, if terrain { self.fallback } else { EMPTY_TILE });
+        let min = (x.saturating_sub(1), y.saturating_sub(1));
+        self.apply_region(layer, min, (x + 2, y + 2), &is_terrain);
+    }
+
+    fn apply_region(&self, layer: &mut TileLayer, min: (u32, u32), max: (u32, u32), is_terrain: &impl Fn(u32) -> bool) {
+        let (width, height) = layer.size();
+        let (max_x, max_y) = (max.0.min(width), max.1.min(height));
+        // Decide every cell from the layer as it was, then write
+        let mut picked = Vec::new();
+        for y in min.1..max_y {
+            for x in min.0..max_x {
+                if is_terrain(layer.get(x as i64, y as i64)) {
+                    picked.push((x, y, self.pick(self.neighbor_mask(layer, x as i64, y as i64, is_terrain))));
+                }
+            }
+        }
+        for (x, y, tile) in picked {
+            layer.set(x, y, tile);
+        }
+    }
+}
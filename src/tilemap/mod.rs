@@ -0,0 +1,222 @@
+// src/tilemap/mod.rs
+pub mod autotile;
+pub mod tiled;
+
+use crate::physics::collision::{Collider, ColliderId, CollisionWorld};
+use crate::physics::layers::{CollisionFilter, LayerConfig};
+use crate::physics::shape::{merge_cells, Shape};
+use glam::Vec2;
+use std::collections::HashMap;
+
+// Tile id of an empty cell
+pub const EMPTY_TILE: u32 = 0;
+
+// A grid of tile ids, row-major with row 0 at the bottom (y up, like the world)
+#[derive(Debug, Clone)]
+pub struct TileLayer {
+    pub name: String,
+    width: u32,
+    height: u32,
+    tiles: Vec<u32>,
+    // Every non-empty tile is a solid box
+    pub collision: bool,
+    // Collision layer name from the `LayerConfig` passed to `build_colliders`
+    pub collision_layer: Option<String>,
+    pub visible: bool,
+}
+
+impl TileLayer {
+    pub fn new(name: &str, width: u32, height: u32) -> Self {
+        Self {
+            name: name.to_string(),
+            width,
+            height,
+            tiles: vec![EMPTY_TILE; (width * height) as usize],
+            collision: false,
+            collision_layer: None,
+            visible: true,
+        }
+    }
+
+    pub fn size(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    pub fn tiles(&self) -> &[u32] {
+        &self.tiles
+    }
+
+    // Empty outside the layer
+    pub fn get(&self, x: i64, y: i64) -> u32 {
+        if x < 0 || y < 0 || x >= self.width as i64 || y >= self.height as i64 {
+            return EMPTY_TILE;
+        }
+        self.tiles[(y as u32 * self.width + x as u32) as usize]
+    }
+
+    pub fn set(&mut self, x: u32, y: u32, tile: u32) {
+        if x < self.width && y < self.height {
+            self.tiles[(y * self.width + x) as usize] = tile;
+        }
+    }
+
+    // Row-major `true` for every tile `solid` accepts, e.g. for `Grid::from_collision`
+    pub fn mask(&self, solid: impl Fn(u32) -> bool) -> Vec<bool> {
+        self.tiles.iter().map(|&tile| solid(tile)).collect()
+    }
+}
+
+// A shape placed in the map: a spawn point, trigger or piece of level geometry
+#[derive(Debug, Clone)]
+pub struct MapObject {
+    pub name: String,
+    // Tiled's class (formerly type), e.g. "spawn" or "door"
+    pub class: String,
+    // Centre, in world space
+    pub position: Vec2,
+    // `None` for points
+    pub shape: Option<Shape>,
+    pub properties: HashMap<String, String>,
+}
+
+impl MapObject {
+    pub fn property(&self, name: &str) -> Option<&str> {
+        self.properties.get(name).map(String::as_str)
+    }
+
+    fn flag(&self, name: &str) -> bool {
+        self.property(name) == Some("true")
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ObjectLayer {
+    pub name: String,
+    pub objects: Vec<MapObject>,
+    // Every shaped object becomes a collider, not just those marked `collision`
+    pub collision: bool,
+    pub collision_layer: Option<String>,
+}
+
+// Collision shape of one tile id, relative to the tile's centre
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TileShape {
+    pub offset: Vec2,
+    pub shape: Shape,
+}
+
+// Tile layers, object layers and per-tile collision shapes. Row 0 of every layer sits at
+// `origin`, each tile is `tile_size` in world units.
+#[derive(Debug, Clone)]
+pub struct Tilemap {
+    pub width: u32,
+    pub height: u32,
+    pub tile_size: Vec2,
+    pub origin: Vec2,
+    pub layers: Vec<TileLayer>,
+    pub object_layers: Vec<ObjectLayer>,
+    // Shapes for tiles that aren't full boxes, e.g. slopes from `set_slope`
+    pub tile_shapes: HashMap<u32, Vec<TileShape>>,
+}
+
+impl Tilemap {
+    pub fn new(width: u32, height: u32, tile_size: Vec2) -> Self {
+        Self {
+            width,
+            height,
+            tile_size,
+            origin: Vec2::ZERO,
+            layers: Vec::new(),
+            object_layers: Vec::new(),
+            tile_shapes: HashMap::new(),
+        }
+    }
+
+    // Makes `tile` collide as a slope filling its cell, rising towards +X, or towards -X
+    // unless `rises_right`
+    pub fn set_slope(&mut self, tile: u32, rises_right: bool) {
+        let width = if rises_right { self.tile_size.x } else { -self.tile_size.x };
+        self.tile_shapes.insert(tile, vec![TileShape { offset: Vec2::ZERO, shape: Shape::slope(width, self.tile_size.y) }]);
+    }
+
+    pub fn layer(&self, name: &str) -> Option<&TileLayer> {
+        self.layers.iter().find(|layer| layer.name == name)
+    }
+
+    pub fn layer_mut(&mut self, name: &str) -> Option<&mut TileLayer> {
+        self.layers.iter_mut().find(|layer| layer.name == name)
+    }
+
+    pub fn object_layer(&self, name: &str) -> Option<&ObjectLayer> {
+        self.object_layers.iter().find(|layer| layer.name == name)
+    }
+
+    // Objects of one class across all object layers, e.g. every "spawn"
+    pub fn objects_of_class<'a>(&'a self, class: &'a str) -> impl Iterator<Item = &'a MapObject> + 'a {
+        self.object_layers.iter().flat_map(|layer| &layer.objects).filter(move |object| object.class == class)
+    }
+
+    pub fn tile_center(&self, x: u32, y: u32) -> Vec2 {
+        self.origin + (Vec2::new(x as f32, y as f32) + 0.5) * self.tile_size
+    }
+
+    pub fn world_to_tile(&self, point: Vec2) -> Option<(u32, u32)> {
+        let cell = ((point - self.origin) / self.tile_size).floor();
+        if cell.x < 0.0 || cell.y < 0.0 || cell.x >= self.width as f32 || cell.y >= self.height as f32 {
+            return None;
+        }
+        Some((cell.x as u32, cell.y as u32))
+    }
+
+    fn filter(layers: Option<&LayerConfig>, name: Option<&str>) -> Result<CollisionFilter, String> {
+        match (layers, name) {
+            (Some(layers), Some(name)) => layers.filter(name),
+            (None, Some(name)) => Err(format!("Map uses collision layer '{}' but no layer config was given", name)),
+            _ => Ok(CollisionFilter::ALL),
+        }
+    }
+
+    // Adds the map's collision to `world` and returns the new colliders:
+    // - tile layers marked `collision`: each non-empty tile, merged into as few boxes as
+    //   possible, except tiles with shapes in `tile_shapes`, which use those instead
+    // - tiles with `tile_shapes` on any other layer
+    // - objects with a shape on layers marked `collision`, or with a `collision` property;
+    //   a `sensor` property makes them triggers
+    // `collision_layer` names are looked up in `layers`.
+    pub fn build_colliders(&self, world: &mut CollisionWorld, layers: Option<&LayerConfig>) -> Result<Vec<ColliderId>, String> {
+        let mut colliders = Vec::new();
+        for layer in &self.layers {
+            let filter = Self::filter(layers, layer.collision_layer.as_deref())?;
+            let mut full = layer.mask(|tile| layer.collision && tile != EMPTY_TILE && !self.tile_shapes.contains_key(&tile));
+            for (x, y, w, h) in merge_cells(layer.width as usize, layer.height as usize, &mut full) {
+                let size = Vec2::new(w as f32, h as f32) * self.tile_size;
+                let min = self.origin + Vec2::new(x as f32, y as f32) * self.tile_size;
+                colliders.push(world.insert(Collider::new(Shape::rect(size.x, size.y), min + size * 0.5).with_filter(filter)));
+            }
+            for y in 0..layer.height {
+                for x in 0..layer.width {
+                    let Some(shapes) = self.tile_shapes.get(&layer.get(x as i64, y as i64)) else { continue };
+                    for shape in shapes {
+                        let position = self.tile_center(x, y) + shape.offset;
+                        colliders.push(world.insert(Collider::new(shape.shape, position).with_filter(filter)));
+                    }
+                }
+            }
+        }
+        for layer in &self.object_layers {
+            for object in &layer.objects {
+                let Some(shape) = object.shape else { continue };
+                if !layer.collision && !object.flag("collision") {
+                    continue;
+                }
+                let name = object.property("collision_layer").or(layer.collision_layer.as_deref());
+                let mut collider = Collider::new(shape, object.position).with_filter(Self::filter(layers, name)?);
+                if object.flag("sensor") {
+                    collider = collider.sensor();
+                }
+                colliders.push(world.insert(collider));
+            }
+        }
+        Ok(colliders)
+    }
+}
@@ -0,0 +1,323 @@
+// src/tilemap/tiled.rs
+use super::{MapObject, ObjectLayer, TileLayer, TileShape, Tilemap};
+use crate::physics::shape::Shape;
+use glam::Vec2;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::Path;
+
+// Flip and rotation flags Tiled stores in the top bits of a tile id
+const GID_FLAGS: u32 = 0xF000_0000;
+
+// The parts of Tiled's JSON map format (.tmj) the importer reads
+
+#[derive(Deserialize)]
+struct TiledProperty {
+    name: String,
+    value: serde_json::Value,
+}
+
+#[derive(Deserialize)]
+struct TiledPoint {
+    x: f32,
+    y: f32,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct TiledObject {
+    name: String,
+    // Called `type` before Tiled 1.9
+    #[serde(alias = "type")]
+    class: String,
+    x: f32,
+    y: f32,
+    width: f32,
+    height: f32,
+    rotation: f32,
+    ellipse: bool,
+    point: bool,
+    polygon: Option<Vec<TiledPoint>>,
+    polyline: Option<Vec<TiledPoint>>,
+    gid: Option<u32>,
+    properties: Vec<TiledProperty>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct TiledObjectGroup {
+    objects: Vec<TiledObject>,
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+enum TiledLayer {
+    #[serde(rename = "tilelayer")]
+    Tiles {
+        name: String,
+        width: u32,
+        height: u32,
+        data: serde_json::Value,
+        #[serde(default = "visible")]
+        visible: bool,
+        #[serde(default)]
+        properties: Vec<TiledProperty>,
+    },
+    #[serde(rename = "objectgroup")]
+    Objects {
+        name: String,
+        #[serde(default)]
+        objects: Vec<TiledObject>,
+        #[serde(default)]
+        properties: Vec<TiledProperty>,
+    },
+    Group {
+        #[serde(default)]
+        layers: Vec<TiledLayer>,
+    },
+    #[serde(rename = "imagelayer")]
+    Image {},
+}
+
+fn visible() -> bool {
+    true
+}
+
+#[derive(Deserialize)]
+struct TiledTile {
+    id: u32,
+    #[serde(default)]
+    objectgroup: Option<TiledObjectGroup>,
+    #[serde(default)]
+    properties: Vec<TiledProperty>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct TiledTileset {
+    firstgid: u32,
+    // External tileset file; its own JSON has no `firstgid`
+    source: Option<String>,
+    tiles: Vec<TiledTile>,
+}
+
+#[derive(Deserialize)]
+struct TiledMap {
+    width: u32,
+    height: u32,
+    tilewidth: f32,
+    tileheight: f32,
+    #[serde(default)]
+    infinite: bool,
+    #[serde(default)]
+    orientation: Option<String>,
+    #[serde(default)]
+    layers: Vec<TiledLayer>,
+    #[serde(default)]
+    tilesets: Vec<TiledTileset>,
+}
+
+fn properties(list: &[TiledProperty]) -> HashMap<String, String> {
+    list.iter()
+        .map(|p| {
+            let value = match &p.value {
+                serde_json::Value::String(s) => s.clone(),
+                other => other.to_string(),
+            };
+            (p.name.clone(), value)
+        })
+        .collect()
+}
+
+// Pixel space (y down from the map's top edge) to world space
+struct Converter {
+    scale: Vec2,
+    map_height: f32,
+}
+
+impl Converter {
+    fn point(&self, x: f32, y: f32) -> Vec2 {
+        Vec2::new(x * self.scale.x, (self.map_height - y) * self.scale.y)
+    }
+
+    // An ellipse becomes a circle when round, an upright capsule when tall, and its
+    // bounding box when wide, since capsules only stand upright
+    fn shape(&self, width: f32, height: f32, ellipse: bool) -> Shape {
+        let size = Vec2::new(width, height) * self.scale;
+        if !ellipse {
+            return Shape::rect(size.x, size.y);
+        }
+        if (size.x - size.y).abs() <= size.x.max(size.y) * 0.01 {
+            Shape::Circle { radius: size.x * 0.5 }
+        } else if size.y > size.x {
+            Shape::capsule(size.y, size.x * 0.5)
+        } else {
+            Shape::rect(size.x, size.y)
+        }
+    }
+
+    // (centre in pixels, shape in world units)
+    fn object(&self, object: &TiledObject) -> (Vec2, Option<Shape>) {
+        if object.rotation != 0.0 {
+            log::warn!("Tiled object '{}' is rotated; colliders ignore rotation", object.name);
+        }
+        if object.point {
+            return (Vec2::new(object.x, object.y), None);
+        }
+        if let Some(points) = object.polygon.as_ref().or(object.polyline.as_ref()) {
+            if points.is_empty() {
+                return (Vec2::new(object.x, object.y), None);
+            }
+            let min = points.iter().fold(Vec2::splat(f32::MAX), |m, p| m.min(Vec2::new(p.x, p.y)));
+            let max = points.iter().fold(Vec2::splat(f32::MIN), |m, p| m.max(Vec2::new(p.x, p.y)));
+            let center = (min + max) * 0.5;
+            // Convex polygons, e.g. slopes, collide as drawn; anything else as its bounds
+            if object.polygon.is_some() {
+                let outline: Vec<Vec2> = points.iter().map(|p| Vec2::new(p.x - center.x, center.y - p.y) * self.scale).collect();
+                match Shape::polygon(&outline) {
+                    Ok(shape) => return (Vec2::new(object.x, object.y) + center, Some(shape)),
+                    Err(e) => log::warn!("Tiled object '{}' can't collide as a polygon ({}); using its bounding box", object.name, e),
+                }
+            } else {
+                log::warn!("Tiled object '{}' is a polyline; using its bounding box", object.name);
+            }
+            let size = max - min;
+            return (Vec2::new(object.x, object.y) + center, Some(self.shape(size.x, size.y, false)));
+        }
+        // Tile objects are anchored at their bottom-left corner, everything else at top-left
+        let top = if object.gid.is_some() { object.y - object.height } else { object.y };
+        let center = Vec2::new(object.x + object.width * 0.5, top + object.height * 0.5);
+        if object.width <= 0.0 || object.height <= 0.0 {
+            return (center, None);
+        }
+        (center, Some(self.shape(object.width, object.height, object.ellipse)))
+    }
+}
+
+impl Tilemap {
+    // Imports a map saved in Tiled's JSON format, with `pixels_per_unit` map pixels per
+    // world unit. External tilesets can't be resolved without a path; use `load_tiled`.
+    pub fn from_tiled_json(source: &str, pixels_per_unit: f32) -> Result<Self, String> {
+        Self::parse_tiled(source, pixels_per_unit, |source| {
+            Err(format!("Tileset '{}' is external; load the map with Tilemap::load_tiled", source))
+        })
+    }
+
+    // Imports a Tiled JSON map from disk, along with any external JSON tilesets (.tsj)
+    pub fn load_tiled(path: &str, pixels_per_unit: f32) -> Result<Self, String> {
+        let source = std::fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+        let dir = Path::new(path).parent().unwrap_or(Path::new(""));
+        Self::parse_tiled(&source, pixels_per_unit, |tileset| {
+            let tileset_path = dir.join(tileset);
+            std::fs::read_to_string(&tileset_path).map_err(|e| format!("Failed to read {}: {}", tileset_path.display(), e))
+        })
+    }
+
+    fn parse_tiled(source: &str, pixels_per_unit: f32, read_tileset: impl Fn(&str) -> Result<String, String>) -> Result<Self, String> {
+        if !pixels_per_unit.is_finite() || pixels_per_unit <= 0.0 {
+            return Err("Pixels per unit must be positive".to_string());
+        }
+        let map: TiledMap = serde_json::from_str(source).map_err(|e| format!("Failed to parse Tiled map: {}", e))?;
+        if map.infinite {
+            return Err("Infinite Tiled maps are not supported; resize the map to a fixed size".to_string());
+        }
+        if map.orientation.as_deref().is_some_and(|o| o != "orthogonal") {
+            return Err(format!("Only orthogonal Tiled maps are supported, not {}", map.orientation.unwrap_or_default()));
+        }
+        let tile_pixels = Vec2::new(map.tilewidth, map.tileheight);
+        let mut tilemap = Tilemap::new(map.width, map.height, tile_pixels / pixels_per_unit);
+        let convert = Converter { scale: Vec2::splat(1.0 / pixels_per_unit), map_height: map.height as f32 * map.tileheight };
+
+        for tileset in &map.tilesets {
+            let tiles = match &tileset.source {
+                Some(file) => {
+                    if file.ends_with(".tsx") {
+                        return Err(format!("Tileset '{}' is XML; export it from Tiled as JSON (.tsj)", file));
+                    }
+                    let external: TiledTileset = serde_json::from_str(&read_tileset(file)?)
+                        .map_err(|e| format!("Failed to parse Tiled tileset {}: {}", file, e))?;
+                    external.tiles
+                }
+                None => Vec::new(),
+            };
+            for tile in tileset.tiles.iter().chain(&tiles) {
+                let gid = tileset.firstgid + tile.id;
+                let mut shapes = Vec::new();
+                for object in tile.objectgroup.iter().flat_map(|group| &group.objects) {
+                    let (center, Some(shape)) = convert.object(object) else { continue };
+                    let offset = Vec2::new(center.x - tile_pixels.x * 0.5, tile_pixels.y * 0.5 - center.y) / pixels_per_unit;
+                    shapes.push(TileShape { offset, shape });
+                }
+                if shapes.is_empty() {
+                    let tile_properties = properties(&tile.properties);
+                    let size = tilemap.tile_size;
+                    // A `slope` property names the way the tile rises
+                    let shape = match tile_properties.get("slope").map(String::as_str) {
+                        Some("right") => Some(Shape::slope(size.x, size.y)),
+                        Some("left") => Some(Shape::slope(-size.x, size.y)),
+                        Some(other) => {
+                            log::warn!("Tile {} has slope '{}'; expected 'left' or 'right'", gid, other);
+                            None
+                        }
+                        None => tile_properties.get("collision").is_some_and(|v| v == "true").then(|| Shape::rect(size.x, size.y)),
+                    };
+                    shapes.extend(shape.map(|shape| TileShape { offset: Vec2::ZERO, shape }));
+                }
+                if !shapes.is_empty() {
+                    tilemap.tile_shapes.insert(gid, shapes);
+                }
+            }
+        }
+
+        let mut pending: Vec<TiledLayer> = map.layers;
+        pending.reverse();
+        while let Some(layer) = pending.pop() {
+            match layer {
+                TiledLayer::Tiles { name, width, height, data, visible, properties: props } => {
+                    let data: Vec<u32> = serde_json::from_value(data)
+                        .map_err(|_| format!("Tile layer '{}' is compressed or encoded; save the map with CSV layer data", name))?;
+                    if data.len() != (width * height) as usize {
+                        return Err(format!("Tile layer '{}' has {} tiles, expected {}x{}", name, data.len(), width, height));
+                    }
+                    let props = properties(&props);
+                    let mut layer = TileLayer::new(&name, width, height);
+                    layer.visible = visible;
+                    layer.collision = props.get("collision").is_some_and(|v| v == "true");
+                    layer.collision_layer = props.get("collision_layer").cloned();
+                    // Tiled rows run top-down
+                    for (i, gid) in data.iter().enumerate() {
+                        let (x, row) = (i as u32 % width, i as u32 / width);
+                        layer.set(x, height - 1 - row, gid & !GID_FLAGS);
+                    }
+                    tilemap.layers.push(layer);
+                }
+                TiledLayer::Objects { name, objects, properties: props } => {
+                    let props = properties(&props);
+                    let objects = objects
+                        .iter()
+                        .map(|object| {
+                            let (center, shape) = convert.object(object);
+                            MapObject {
+                                name: object.name.clone(),
+                                class: object.class.clone(),
+                                position: convert.point(center.x, center.y),
+                                shape,
+                                properties: properties(&object.properties),
+                            }
+                        })
+                        .collect();
+                    tilemap.object_layers.push(ObjectLayer {
+                        name,
+                        objects,
+                        collision: props.get("collision").is_some_and(|v| v == "true"),
+                        collision_layer: props.get("collision_layer").cloned(),
+                    });
+                }
+                // Group children keep their place in the layer order
+                TiledLayer::Group { layers } => pending.extend(layers.into_iter().rev()),
+                TiledLayer::Image {} => {}
+            }
+        }
+        Ok(tilemap)
+    }
+}
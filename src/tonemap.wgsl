@@ -0,0 +1,56 @@
+// Maps the HDR frame to display range using the adapted luminance from auto-exposure
+
+struct HdrParams {
+    min_log_luminance: f32,
+    inv_log_luminance_range: f32,
+    log_luminance_range: f32,
+    delta_time: f32,
+    adaptation_rate: f32,
+    exposure_compensation: f32,
+    tonemapper: u32,
+    encode_srgb: u32,
+}
+
+struct Exposure {
+    luminance: f32,
+}
+
+@group(0) @binding(0) var<uniform> params: HdrParams;
+@group(0) @binding(1) var hdr_texture: texture_2d<f32>;
+@group(0) @binding(2) var<storage, read> exposure: Exposure;
+
+@vertex
+fn vs_fullscreen(@builtin(vertex_index) index: u32) -> @builtin(position) vec4<f32> {
+    let uv = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+    return vec4<f32>(uv * 2.0 - 1.0, 0.0, 1.0);
+}
+
+fn reinhard(color: vec3<f32>) -> vec3<f32> {
+    return color / (1.0 + color);
+}
+
+// Narkowicz's fit of the ACES filmic curve
+fn aces(color: vec3<f32>) -> vec3<f32> {
+    let a = color * (2.51 * color + 0.03);
+    let b = color * (2.43 * color + 0.59) + 0.14;
+    return clamp(a / b, vec3<f32>(0.0), vec3<f32>(1.0));
+}
+
+fn linear_to_srgb(color: vec3<f32>) -> vec3<f32> {
+    let low = color * 12.92;
+    let high = 1.055 * pow(color, vec3<f32>(1.0 / 2.4)) - 0.055;
+    return select(high, low, color <= vec3<f32>(0.0031308));
+}
+
+@fragment
+fn fs_tonemap(@builtin(position) frag_coord: vec4<f32>) -> @location(0) vec4<f32> {
+    let hdr = textureLoad(hdr_texture, vec2<i32>(frag_coord.xy), 0);
+    // Middle grey (0.18) at the adapted luminance, shifted by the compensation in stops
+    let scale = 0.18 / max(exposure.luminance, 1e-4) * exp2(params.exposure_compensation);
+    let exposed = hdr.rgb * scale;
+    var mapped = select(reinhard(exposed), aces(exposed), params.tonemapper == 1u);
+    if (params.encode_srgb != 0u) {
+        mapped = linear_to_srgb(mapped);
+    }
+    return vec4<f32>(mapped, 1.0);
+}
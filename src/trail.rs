@@ -0,0 +1,281 @@
+// src/trail.rs
+use crate::bind_cache::BindGroupCache;
+use crate::gpu_buffer::{GpuStruct, UniformBuffer};
+use crate::resource_registry::ResourceRegistry;
+use crate::upload::{DynamicBuffer, UploadBelt};
+use glam::{Mat4, Vec3, Vec4};
+use std::collections::VecDeque;
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct TrailVertex {
+    pub position: [f32; 3],
+    pub color: [f32; 4],
+}
+
+impl TrailVertex {
+    const ATTRIBUTES: [wgpu::VertexAttribute; 2] = wgpu::vertex_attr_array![0 => Float32x3, 1 => Float32x4];
+
+    pub fn layout() -> wgpu::VertexBufferLayout<'static> {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<TrailVertex>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &Self::ATTRIBUTES,
+        }
+    }
+}
+
+// Which way the ribbon's flat side faces
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TrailAlignment {
+    // Turns to face the camera, for projectiles and magic
+    View,
+    // Fixed normal: `Vec3::Z` for 2D scenes, `Vec3::Y` for skid marks on the ground, the
+    // swing plane for a sword slash
+    Normal(Vec3),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TrailSettings {
+    // Seconds each point lives; this sets the trail's length
+    pub lifetime: f32,
+    // Distance moved before a new point is laid down
+    pub min_distance: f32,
+    // Oldest points are dropped beyond this
+    pub max_points: usize,
+    // Width and colour from a point's birth to its death
+    pub width: (f32, f32),
+    pub color: ([f32; 4], [f32; 4]),
+    pub alignment: TrailAlignment,
+}
+
+impl Default for TrailSettings {
+    fn default() -> Self {
+        Self {
+            lifetime: 0.4,
+            min_distance: 0.05,
+            max_points: 64,
+            width: (0.2, 0.0),
+            color: ([1.0, 1.0, 1.0, 1.0], [1.0, 1.0, 1.0, 0.0]),
+            alignment: TrailAlignment::View,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct TrailPoint {
+    position: Vec3,
+    age: f32,
+}
+
+// Component recording the path of whatever it's attached to. Call `update` with the
+// owner's position every frame; the newest point tracks it exactly, and older ones fade
+// and narrow as they age. Clearing `emitting` lets the trail die away behind a stopped owner.
+#[derive(Debug, Clone)]
+pub struct TrailRenderer {
+    pub settings: TrailSettings,
+    pub emitting: bool,
+    // Oldest first; the last point follows the owner
+    points: VecDeque<TrailPoint>,
+}
+
+impl TrailRenderer {
+    pub fn new(settings: TrailSettings) -> Self {
+        Self { settings, emitting: true, points: VecDeque::new() }
+    }
+
+    pub fn update(&mut self, position: Vec3, delta_time: f32) {
+        let lifetime = self.settings.lifetime.max(f32::EPSILON);
+        for point in &mut self.points {
+            point.age += delta_time;
+        }
+        while self.points.front().is_some_and(|p| p.age >= lifetime) {
+            self.points.pop_front();
+        }
+        if !self.emitting {
+            return;
+        }
+        let len = self.points.len();
+        if len < 2 {
+            // An anchor and a head that follows the owner
+            self.points.clear();
+            self.points.push_back(TrailPoint { position, age: 0.0 });
+            self.points.push_back(TrailPoint { position, age: 0.0 });
+        } else {
+            // Commit the head once it has moved far enough from the point behind it
+            let previous = self.points[len - 2].position;
+            let head = self.points.back_mut().expect("trail has points");
+            head.position = position;
+            head.age = 0.0;
+            if previous.distance(position) >= self.settings.min_distance {
+                self.points.push_back(TrailPoint { position, age: 0.0 });
+            }
+        }
+        while self.points.len() > self.settings.max_points.max(2) {
+            self.points.pop_front();
+        }
+    }
+
+    // Drops the whole trail, e.g. when the owner teleports
+    pub fn clear(&mut self) {
+        self.points.clear();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.points.len() < 2
+    }
+
+    pub fn point_count(&self) -> usize {
+        self.points.len()
+    }
+
+    // Appends the ribbon as a triangle list; `camera` is only used by `TrailAlignment::View`
+    pub fn build(&self, camera: Vec3, vertices: &mut Vec<TrailVertex>) {
+        if self.is_empty() {
+            return;
+        }
+        let settings = &self.settings;
+        let lifetime = settings.lifetime.max(f32::EPSILON);
+        let count = self.points.len();
+        let mut previous_side = Vec3::ZERO;
+        let edges: Vec<(TrailVertex, TrailVertex)> = (0..count)
+            .map(|i| {
+                let point = self.points[i];
+                let (before, after) = (self.points[i.saturating_sub(1)].position, self.points[(i + 1).min(count - 1)].position);
+                let tangent = (after - before).normalize_or_zero();
+                let facing = match settings.alignment {
+                    TrailAlignment::View => camera - point.position,
+                    TrailAlignment::Normal(normal) => normal,
+                };
+                // Keep the last good side where the trail doubles back or hasn't moved
+                let side = tangent.cross(facing).try_normalize().unwrap_or(previous_side);
+                previous_side = side;
+                let t = (point.age / lifetime).clamp(0.0, 1.0);
+                let half_width = (settings.width.0 + (settings.width.1 - settings.width.0) * t) * 0.5;
+                let color = Vec4::from(settings.color.0).lerp(Vec4::from(settings.color.1), t).to_array();
+                (
+                    TrailVertex { position: (point.position - side * half_width).into(), color },
+                    TrailVertex { position: (point.position + side * half_width).into(), color },
+                )
+            })
+            .collect();
+        for pair in edges.windows(2) {
+            let ((a0, a1), (b0, b1)) = (pair[0], pair[1]);
+            vertices.extend_from_slice(&[a0, b0, a1, a1, b0, b1]);
+        }
+    }
+}
+
+// Mirrors `TrailParams` in trail.wgsl
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct TrailParams {
+    view_proj: [f32; 16],
+}
+
+impl GpuStruct for TrailParams {}
+
+// Draws any number of trails in one alpha-blended call. Depth is tested but not written,
+// so draw after opaque geometry.
+pub struct TrailBatch {
+    vertices: DynamicBuffer,
+    vertex_count: u32,
+    scratch: Vec<TrailVertex>,
+    params: UniformBuffer<TrailParams>,
+    layout: wgpu::BindGroupLayout,
+    pipeline: wgpu::RenderPipeline,
+}
+
+impl TrailBatch {
+    // `depth_format` must match the pass the trails are drawn in
+    pub fn new(
+        device: &wgpu::Device,
+        resources: &ResourceRegistry,
+        color_format: wgpu::TextureFormat,
+        depth_format: Option<wgpu::TextureFormat>,
+    ) -> Self {
+        let params = UniformBuffer::new(device, resources, "trail params", 1);
+        let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("trails"),
+            entries: &[params.layout_entry(0, wgpu::ShaderStages::VERTEX)],
+        });
+        let shader = device.create_shader_module(wgpu::include_wgsl!("trail.wgsl"));
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("trails"),
+            bind_group_layouts: &[&layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("trails"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: Some("vs_main"),
+                buffers: &[TrailVertex::layout()],
+                compilation_options: Default::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: Some("fs_main"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: color_format,
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: Default::default(),
+            }),
+            // Ribbons twist, so both faces show
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: depth_format.map(|format| wgpu::DepthStencilState {
+                format,
+                depth_write_enabled: false,
+                depth_compare: wgpu::CompareFunction::LessEqual,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        });
+        let vertices = DynamicBuffer::new(device, resources, "trail vertices", wgpu::BufferUsages::VERTEX, 64 * 1024);
+        Self { vertices, vertex_count: 0, scratch: Vec::new(), params, layout, pipeline }
+    }
+
+    // Rebuilds every trail's ribbon for this frame
+    #[allow(clippy::too_many_arguments)]
+    pub fn prepare<'a>(
+        &mut self,
+        device: &wgpu::Device,
+        resources: &ResourceRegistry,
+        encoder: &mut wgpu::CommandEncoder,
+        belt: &mut UploadBelt,
+        view_proj: Mat4,
+        camera_position: Vec3,
+        trails: impl IntoIterator<Item = &'a TrailRenderer>,
+    ) {
+        self.scratch.clear();
+        for trail in trails {
+            trail.build(camera_position, &mut self.scratch);
+        }
+        self.vertex_count = self.scratch.len() as u32;
+        if !self.scratch.is_empty() {
+            self.vertices.upload(device, resources, encoder, belt, bytemuck::cast_slice(&self.scratch));
+        }
+        self.params.set(device, encoder, belt, &TrailParams { view_proj: view_proj.to_cols_array() });
+    }
+
+    pub fn vertex_count(&self) -> u32 {
+        self.vertex_count
+    }
+
+    pub fn draw(&self, device: &wgpu::Device, bind_groups: &mut BindGroupCache, render_pass: &mut wgpu::RenderPass<'_>) {
+        if self.vertex_count == 0 {
+            return;
+        }
+        let bind_group = self.params.bind_group(device, bind_groups, &self.layout);
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(0, &bind_group, &[]);
+        render_pass.set_vertex_buffer(0, self.vertices.buffer().slice(..));
+        render_pass.draw(0..self.vertex_count, 0..1);
+    }
+}
@@ -0,0 +1,174 @@
+// src/ui/animation.rs
+use super::{Ui, Widget, WidgetId};
+use glam::Vec2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Easing {
+    Linear,
+    EaseIn,
+    EaseOut,
+    #[default]
+    EaseInOut,
+    // Overshoots a little before settling, for pop-in scales
+    BackOut,
+}
+
+impl Easing {
+    // Maps progress 0..=1 to eased progress; `BackOut` briefly exceeds 1
+    pub fn apply(self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Easing::Linear => t,
+            Easing::EaseIn => t * t * t,
+            Easing::EaseOut => 1.0 - (1.0 - t).powi(3),
+            Easing::EaseInOut => t * t * (3.0 - 2.0 * t),
+            Easing::BackOut => {
+                const OVERSHOOT: f32 = 1.70158;
+                let u = t - 1.0;
+                1.0 + u * u * ((OVERSHOOT + 1.0) * u + OVERSHOOT)
+            }
+        }
+    }
+}
+
+// A widget display property and the values it moves between
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UiProperty {
+    Opacity { from: f32, to: f32 },
+    // Pixels, y down
+    Offset { from: Vec2, to: Vec2 },
+    Scale { from: f32, to: f32 },
+}
+
+// One property of one widget changing over `duration` seconds
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Tween {
+    pub widget: WidgetId,
+    pub property: UiProperty,
+    pub duration: f32,
+    pub easing: Easing,
+}
+
+impl Tween {
+    pub fn new(widget: WidgetId, property: UiProperty, duration: f32) -> Self {
+        Self { widget, property, duration, easing: Easing::default() }
+    }
+
+    pub fn with_easing(mut self, easing: Easing) -> Self {
+        self.easing = easing;
+        self
+    }
+
+    pub fn fade_in(widget: WidgetId, duration: f32) -> Self {
+        Self::new(widget, UiProperty::Opacity { from: 0.0, to: 1.0 }, duration)
+    }
+
+    pub fn fade_out(widget: WidgetId, duration: f32) -> Self {
+        Self::new(widget, UiProperty::Opacity { from: 1.0, to: 0.0 }, duration)
+    }
+
+    // Moves in from `from` pixels away to where the widget's rect puts it
+    pub fn slide_in(widget: WidgetId, from: Vec2, duration: f32) -> Self {
+        Self::new(widget, UiProperty::Offset { from, to: Vec2::ZERO }, duration).with_easing(Easing::EaseOut)
+    }
+
+    pub fn slide_out(widget: WidgetId, to: Vec2, duration: f32) -> Self {
+        Self::new(widget, UiProperty::Offset { from: Vec2::ZERO, to }, duration).with_easing(Easing::EaseIn)
+    }
+
+    pub fn scale(widget: WidgetId, from: f32, to: f32, duration: f32) -> Self {
+        Self::new(widget, UiProperty::Scale { from, to }, duration)
+    }
+
+    pub(crate) fn apply(&self, widget: &mut Widget, elapsed: f32) {
+        let t = if self.duration > 0.0 { self.easing.apply(elapsed / self.duration) } else { 1.0 };
+        match self.property {
+            UiProperty::Opacity { from, to } => widget.opacity = (from + (to - from) * t).clamp(0.0, 1.0),
+            UiProperty::Offset { from, to } => widget.offset = from.lerp(to, t),
+            UiProperty::Scale { from, to } => widget.scale = from + (to - from) * t,
+        }
+    }
+}
+
+// Tweens in steps: everything in a step plays together and the next step starts once
+// the longest one ends, e.g.
+// `UiAnimation::new().then(Tween::fade_in(panel, 0.2)).with(Tween::slide_in(panel, offset, 0.2))
+//   .wait(0.1).then(Tween::scale(button, 0.8, 1.0, 0.15).with_easing(Easing::BackOut))`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct UiAnimation {
+    steps: Vec<Step>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+struct Step {
+    tweens: Vec<Tween>,
+    // At least this long, so a step can be a pause
+    duration: f32,
+}
+
+impl Step {
+    fn length(&self) -> f32 {
+        self.tweens.iter().map(|tween| tween.duration).fold(self.duration, f32::max)
+    }
+}
+
+impl UiAnimation {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Starts a new step with `tween`
+    pub fn then(mut self, tween: Tween) -> Self {
+        self.steps.push(Step { tweens: vec![tween], duration: 0.0 });
+        self
+    }
+
+    // Plays `tween` alongside the current step
+    pub fn with(mut self, tween: Tween) -> Self {
+        match self.steps.last_mut() {
+            Some(step) => step.tweens.push(tween),
+            None => return self.then(tween),
+        }
+        self
+    }
+
+    // A step doing nothing for `seconds`
+    pub fn wait(mut self, seconds: f32) -> Self {
+        self.steps.push(Step { tweens: Vec::new(), duration: seconds });
+        self
+    }
+
+    pub fn duration(&self) -> f32 {
+        self.steps.iter().map(Step::length).sum()
+    }
+
+    pub(crate) fn widgets(&self) -> impl Iterator<Item = WidgetId> + '_ {
+        self.steps.iter().flat_map(|step| step.tweens.iter().map(|tween| tween.widget))
+    }
+
+    // Sets every property this animation touches as it is `elapsed` seconds in. Steps not
+    // reached yet are left alone, so a widget keeps its own value until its tween starts.
+    pub(crate) fn sample(&self, ui: &mut Ui, elapsed: f32) {
+        let mut start = 0.0;
+        for step in &self.steps {
+            if elapsed < start {
+                break;
+            }
+            for tween in &step.tweens {
+                if let Some(widget) = ui.get_mut(tween.widget) {
+                    tween.apply(widget, elapsed - start);
+                }
+            }
+            start += step.length();
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct AnimationId(pub(crate) u32);
+
+pub(crate) struct Playing {
+    pub(crate) id: AnimationId,
+    pub(crate) animation: UiAnimation,
+    pub(crate) elapsed: f32,
+}
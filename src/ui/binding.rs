@@ -0,0 +1,155 @@
+// src/ui/binding.rs
+use super::{Ui, WidgetId};
+use crate::color::Color;
+use crate::ecs::world::{Entity, Tick, World};
+use std::any::TypeId;
+
+// What a binding writes into its widget
+#[derive(Debug, Clone, PartialEq)]
+pub enum UiValue {
+    // Label and button text
+    Text(String),
+    // Bar fill, 0..=1
+    Value(f32),
+    Visible(bool),
+    Enabled(bool),
+    Color(Color),
+}
+
+type Reader = Box<dyn FnMut(&World) -> Option<UiValue>>;
+type OnClick = Box<dyn FnMut(&mut World, &mut Ui)>;
+pub(crate) type ClearEvents = fn(&mut World);
+
+pub(crate) struct Binding {
+    pub(crate) widget: WidgetId,
+    read: Reader,
+    // Last value written, so unchanged values don't touch the widget
+    last: Option<UiValue>,
+}
+
+impl Binding {
+    pub(crate) fn poll(&mut self, world: &World) -> Option<UiValue> {
+        let value = (self.read)(world)?;
+        if self.last.as_ref() == Some(&value) {
+            return None;
+        }
+        self.last = Some(value.clone());
+        Some(value)
+    }
+}
+
+pub(crate) struct Callback {
+    pub(crate) widget: WidgetId,
+    pub(crate) run: OnClick,
+}
+
+// Resource queueing typed events from widget interaction, one per event type. Each
+// `update_ui` clears the last run's events and queues new ones, so every system sees an
+// event for one frame whatever stage it runs in.
+#[derive(Debug)]
+pub struct UiEvents<E> {
+    events: Vec<E>,
+}
+
+impl<E> Default for UiEvents<E> {
+    fn default() -> Self {
+        Self { events: Vec::new() }
+    }
+}
+
+impl<E> UiEvents<E> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, event: E) {
+        self.events.push(event);
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &E> {
+        self.events.iter()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    pub fn drain(&mut self) -> impl Iterator<Item = E> + '_ {
+        self.events.drain(..)
+    }
+
+    pub fn clear(&mut self) {
+        self.events.clear();
+    }
+}
+
+fn clear_events<E: 'static>(world: &mut World) {
+    if let Some(events) = world.resources.get_mut::<UiEvents<E>>() {
+        events.clear();
+    }
+}
+
+impl Ui {
+    // Keeps `widget` showing `map` of `entity`'s `T`, e.g. a bar bound to `Health::fraction`.
+    // `map` only runs when the component was added or changed since it last ran; nothing is
+    // written while the entity lacks a `T`.
+    pub fn bind_component<T: 'static>(
+        &mut self,
+        widget: WidgetId,
+        entity: Entity,
+        mut map: impl FnMut(&T) -> UiValue + 'static,
+    ) {
+        let mut seen: Option<Tick> = None;
+        self.bind(widget, move |world| {
+            let (_, changed) = world.ticks::<T>(entity)?;
+            if seen == Some(changed) {
+                return None;
+            }
+            seen = Some(changed);
+            Some(map(world.get::<T>(entity)?))
+        });
+    }
+
+    // Keeps `widget` showing `map` of resource `R`, e.g. a score label. Resources have no
+    // change ticks, so `map` runs every update and only differing values are written.
+    pub fn bind_resource<R: 'static>(&mut self, widget: WidgetId, mut map: impl FnMut(&R) -> UiValue + 'static) {
+        self.bind(widget, move |world| world.resources.get::<R>().map(&mut map));
+    }
+
+    // Any other source; `read` returns `None` to leave the widget as it is
+    pub fn bind(&mut self, widget: WidgetId, read: impl FnMut(&World) -> Option<UiValue> + 'static) {
+        self.bindings.push(Binding { widget, read: Box::new(read), last: None });
+    }
+
+    // Queues a copy of `event` in `UiEvents<E>` whenever `widget` is activated
+    pub fn on_click<E: Clone + 'static>(&mut self, widget: WidgetId, event: E) {
+        self.track_events::<E>();
+        self.on_click_with(widget, move |world, _| {
+            world.resources.get_or_insert_with(UiEvents::<E>::new).push(event.clone());
+        });
+    }
+
+    // Runs `callback` whenever `widget` is activated. The `Ui` resource is out of the world
+    // while callbacks run, so they get it alongside, e.g. to open another menu.
+    pub fn on_click_with(&mut self, widget: WidgetId, callback: impl FnMut(&mut World, &mut Ui) + 'static) {
+        self.callbacks.push(Callback { widget, run: Box::new(callback) });
+    }
+
+    // Has `update_ui` clear `UiEvents<E>` before queueing each run's events
+    pub fn track_events<E: 'static>(&mut self) {
+        if !self.event_types.iter().any(|(id, _)| *id == TypeId::of::<E>()) {
+            self.event_types.push((TypeId::of::<E>(), clear_events::<E>));
+        }
+    }
+
+    pub(crate) fn clear_events(&self, world: &mut World) {
+        for (_, clear) in &self.event_types {
+            clear(world);
+        }
+    }
+
+    // Drops bindings on `widget`, e.g. before rebinding it to another entity
+    pub fn unbind(&mut self, widget: WidgetId) {
+        self.bindings.retain(|binding| binding.widget != widget);
+    }
+}
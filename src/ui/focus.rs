@@ -0,0 +1,281 @@
+// src/ui/focus.rs
+use super::animation::{Easing, Tween, UiAnimation};
+use super::{Ui, WidgetId};
+use crate::ecs::world::World;
+use crate::input::actions::{ActionMap, ActionState, Actions, Binding};
+use crate::input::gamepad::GamepadButton;
+use glam::Vec2;
+use winit::keyboard::KeyCode;
+
+// Actions `navigate_ui` reads from the `Actions` resource
+pub const NAV_UP: &str = "ui_up";
+pub const NAV_DOWN: &str = "ui_down";
+pub const NAV_LEFT: &str = "ui_left";
+pub const NAV_RIGHT: &str = "ui_right";
+pub const NAV_CONFIRM: &str = "ui_confirm";
+pub const NAV_CANCEL: &str = "ui_cancel";
+
+// Arrow keys and the d-pad move, Enter, Space and the south button confirm, Escape and
+// the east button cancel. Actions the map already binds are left as they are, so
+// remapped controls survive.
+pub fn bind_navigation(map: &mut ActionMap) {
+    let defaults = [
+        (NAV_UP, [Binding::Key(KeyCode::ArrowUp), Binding::Gamepad(GamepadButton::DPadUp)].as_slice()),
+        (NAV_DOWN, &[Binding::Key(KeyCode::ArrowDown), Binding::Gamepad(GamepadButton::DPadDown)]),
+        (NAV_LEFT, &[Binding::Key(KeyCode::ArrowLeft), Binding::Gamepad(GamepadButton::DPadLeft)]),
+        (NAV_RIGHT, &[Binding::Key(KeyCode::ArrowRight), Binding::Gamepad(GamepadButton::DPadRight)]),
+        (
+            NAV_CONFIRM,
+            &[Binding::Key(KeyCode::Enter), Binding::Key(KeyCode::Space), Binding::Gamepad(GamepadButton::South)],
+        ),
+        (NAV_CANCEL, &[Binding::Key(KeyCode::Escape), Binding::Gamepad(GamepadButton::East)]),
+    ];
+    for (action, bindings) in defaults {
+        if map.bindings(action).is_empty() {
+            map.rebind(action, bindings.to_vec());
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NavDirection {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+impl NavDirection {
+    // In UI pixels, y down
+    fn vector(self) -> Vec2 {
+        match self {
+            NavDirection::Up => Vec2::NEG_Y,
+            NavDirection::Down => Vec2::Y,
+            NavDirection::Left => Vec2::NEG_X,
+            NavDirection::Right => Vec2::X,
+        }
+    }
+
+    fn is_horizontal(self) -> bool {
+        matches!(self, NavDirection::Left | NavDirection::Right)
+    }
+}
+
+// Which axes jump to the far side when nothing lies further in the pressed direction
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FocusWrap {
+    None,
+    Horizontal,
+    Vertical,
+    #[default]
+    Both,
+}
+
+impl FocusWrap {
+    fn allows(self, direction: NavDirection) -> bool {
+        match self {
+            FocusWrap::None => false,
+            FocusWrap::Horizontal => direction.is_horizontal(),
+            FocusWrap::Vertical => !direction.is_horizontal(),
+            FocusWrap::Both => true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Navigation {
+    pub wrap: FocusWrap,
+    // Holding a direction moves again after `repeat_delay` seconds, then every
+    // `repeat_interval`; zero turns repeating off
+    pub repeat_delay: f32,
+    pub repeat_interval: f32,
+}
+
+impl Default for Navigation {
+    fn default() -> Self {
+        Self { wrap: FocusWrap::default(), repeat_delay: 0.4, repeat_interval: 0.12 }
+    }
+}
+
+// How the focused widget stands out: it scales up by `scale` over `duration` and back down
+// when focus leaves. Renderers can also check `Widget::focused` to draw an outline.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FocusStyle {
+    pub scale: f32,
+    pub duration: f32,
+    pub easing: Easing,
+}
+
+impl Default for FocusStyle {
+    fn default() -> Self {
+        Self { scale: 1.1, duration: 0.1, easing: Easing::EaseOut }
+    }
+}
+
+// Queued in `UiEvents<UiCancel>` by the `update_ui` after the cancel action is pressed,
+// e.g. to close the menu on top
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UiCancel {
+    pub focused: Option<WidgetId>,
+}
+
+#[derive(Default)]
+pub(crate) struct Focus {
+    pub(crate) focused: Option<WidgetId>,
+    // Explicit moves that override the spatial search
+    neighbors: Vec<(WidgetId, NavDirection, WidgetId)>,
+    // Since the last `update_ui`
+    pub(crate) cancelled: Vec<UiCancel>,
+}
+
+impl Ui {
+    pub fn focused(&self) -> Option<WidgetId> {
+        self.focus.focused
+    }
+
+    // Visible, enabled and interactive
+    pub fn is_focusable(&self, id: WidgetId) -> bool {
+        self.get(id).is_some_and(|widget| widget.visible && widget.enabled && widget.is_interactive())
+    }
+
+    // Moves focus to `id`, or clears it with `None`; unfocusable widgets are ignored
+    pub fn set_focus(&mut self, id: Option<WidgetId>) {
+        if id.is_some_and(|id| !self.is_focusable(id)) || id == self.focus.focused {
+            return;
+        }
+        let style = self.focus_style;
+        if let Some(old) = self.focus.focused.take() {
+            if let Some(widget) = self.get_mut(old) {
+                widget.focused = false;
+                let from = widget.scale;
+                self.animate(UiAnimation::new().then(Tween::scale(old, from, 1.0, style.duration).with_easing(style.easing)));
+            }
+        }
+        if let Some(new) = id {
+            if let Some(widget) = self.get_mut(new) {
+                widget.focused = true;
+                let from = widget.scale;
+                self.animate(UiAnimation::new().then(Tween::scale(new, from, style.scale, style.duration).with_easing(style.easing)));
+            }
+        }
+        self.focus.focused = id;
+    }
+
+    // Pressing `direction` on `from` always goes to `to`, e.g. from the bottom of a list
+    // to a tab bar far off to the side
+    pub fn set_neighbor(&mut self, from: WidgetId, direction: NavDirection, to: WidgetId) {
+        self.focus.neighbors.retain(|(existing, d, _)| (*existing, *d) != (from, direction));
+        self.focus.neighbors.push((from, direction, to));
+    }
+
+    // Where `direction` leads from `from`: an explicit neighbour if set and focusable, else
+    // the nearest focusable widget that way, favouring ones in line. With nothing that way,
+    // wraps to the far side when `navigation.wrap` allows.
+    pub fn neighbor(&self, from: WidgetId, direction: NavDirection) -> Option<WidgetId> {
+        let explicit = self.focus.neighbors.iter().find(|(existing, d, _)| (*existing, *d) == (from, direction));
+        if let Some(&(_, _, to)) = explicit.filter(|(_, _, to)| self.is_focusable(*to)) {
+            return Some(to);
+        }
+        let origin = self.get(from)?.rect.center();
+        let axis = direction.vector();
+        let candidates: Vec<(WidgetId, f32, f32)> = self
+            .widgets()
+            .filter(|(id, _)| *id != from && self.is_focusable(*id))
+            .map(|(id, widget)| {
+                let delta = widget.rect.center() - origin;
+                let along = delta.dot(axis);
+                (id, along, (delta - axis * along).length())
+            })
+            .collect();
+        // Sideways distance counts double, so a widget straight ahead beats a closer one
+        // diagonally off
+        let score = |along: f32, across: f32| along + across * 2.0;
+        let ahead = candidates
+            .iter()
+            .filter(|(_, along, _)| *along > 0.5)
+            .min_by(|a, b| score(a.1, a.2).total_cmp(&score(b.1, b.2)));
+        if let Some(&(id, _, _)) = ahead {
+            return Some(id);
+        }
+        if !self.navigation.wrap.allows(direction) {
+            return None;
+        }
+        // Furthest back, still favouring ones in line
+        candidates
+            .iter()
+            .filter(|(_, along, _)| *along < -0.5)
+            .min_by(|a, b| score(a.1, a.2).total_cmp(&score(b.1, b.2)))
+            .map(|&(id, _, _)| id)
+    }
+
+    // Focuses the first focusable widget when nothing is, so the first press lands
+    // somewhere; returns whether focus moved
+    pub fn move_focus(&mut self, direction: NavDirection) -> bool {
+        let target = match self.focus.focused.filter(|id| self.is_focusable(*id)) {
+            Some(current) => self.neighbor(current, direction),
+            None => self.first_focusable(),
+        };
+        let moved = target.is_some() && target != self.focus.focused;
+        self.set_focus(target);
+        moved
+    }
+
+    pub fn activate_focused(&mut self) -> Option<WidgetId> {
+        let id = self.focus.focused.filter(|id| self.is_focusable(*id))?;
+        self.activate(id);
+        Some(id)
+    }
+
+    // Backs out as if the cancel action was pressed; queued in `UiEvents<UiCancel>` by the
+    // next `update_ui`
+    pub fn cancel(&mut self) {
+        let focused = self.focus.focused;
+        self.focus.cancelled.push(UiCancel { focused });
+    }
+
+    fn first_focusable(&self) -> Option<WidgetId> {
+        self.widgets().map(|(id, _)| id).find(|id| self.is_focusable(*id))
+    }
+
+    pub(crate) fn forget_focus(&mut self, id: WidgetId) {
+        if self.focus.focused == Some(id) {
+            self.focus.focused = None;
+        }
+        self.focus.neighbors.retain(|(from, _, to)| *from != id && *to != id);
+    }
+}
+
+// Whether a held action fires this tick: on the press, then after the delay at each interval
+fn repeats(state: ActionState, delta_time: f32, navigation: &Navigation) -> bool {
+    if state.just_pressed {
+        return true;
+    }
+    if !state.pressed || navigation.repeat_delay <= 0.0 || navigation.repeat_interval <= 0.0 {
+        return false;
+    }
+    // Both from tick counts, so consecutive ticks agree on where the boundaries fall
+    let now = state.held_ticks as f32 * delta_time - navigation.repeat_delay;
+    let before = state.held_ticks.saturating_sub(1) as f32 * delta_time - navigation.repeat_delay;
+    now >= 0.0 && (before < 0.0 || (now / navigation.repeat_interval).floor() > (before / navigation.repeat_interval).floor())
+}
+
+// System moving focus and confirming or cancelling from the `NAV_*` actions. Runs in
+// `FixedUpdate` after the actions update, so each press is seen exactly once.
+pub fn navigate_ui(world: &mut World, delta_time: f64) {
+    let Some(actions) = world.resources.get::<Actions>() else { return };
+    let states = [NAV_UP, NAV_DOWN, NAV_LEFT, NAV_RIGHT, NAV_CONFIRM, NAV_CANCEL].map(|action| actions.state(action));
+    let Some(ui) = world.resources.get_mut::<Ui>() else { return };
+    let navigation = ui.navigation;
+    let directions = [NavDirection::Up, NavDirection::Down, NavDirection::Left, NavDirection::Right];
+    for (direction, state) in directions.into_iter().zip(states) {
+        if repeats(state, delta_time as f32, &navigation) {
+            ui.move_focus(direction);
+        }
+    }
+    if states[4].just_pressed {
+        ui.activate_focused();
+    }
+    if states[5].just_pressed {
+        ui.cancel();
+    }
+}
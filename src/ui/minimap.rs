@@ -0,0 +1,225 @@
+// src/ui/minimap.rs
+use super::{Ui, Widget, WidgetId, WidgetKind};
+use crate::color::Color;
+use crate::ecs::world::{Entity, World};
+use crate::math::{Rect, Transform2D};
+use crate::render_texture::{RenderTextureId, ViewCamera};
+use glam::{Mat4, Vec2};
+
+// Distance of a 2D minimap camera above the z = 0 plane
+const CAMERA_HEIGHT: f32 = 100.0;
+
+// Component putting an entity on minimaps, at its `Transform2D` translation
+#[derive(Debug, Clone, PartialEq)]
+pub struct MapMarker {
+    // Name of an icon in the HUD's `IconSet`, e.g. "enemy" or "objective"
+    pub icon: String,
+    pub color: Color,
+    // Pixels on the map, whatever the zoom
+    pub size: f32,
+    // Turns with the entity, e.g. the player's arrow
+    pub rotates: bool,
+    // Stays at the map's edge while out of range, e.g. quest objectives
+    pub clamp_to_edge: bool,
+}
+
+impl MapMarker {
+    pub fn new(icon: &str, color: Color) -> Self {
+        Self { icon: icon.to_string(), color, size: 8.0, rotates: false, clamp_to_edge: false }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MinimapShape {
+    #[default]
+    Rect,
+    // Inscribed in the widget's rect
+    Circle,
+}
+
+// A marker as placed on the map by the last `update_ui`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Blip {
+    pub entity: Entity,
+    // Pixels from the widget's centre, y down, before the widget's display scale
+    pub offset: Vec2,
+    // Radians counter-clockwise on screen; zero unless the marker rotates
+    pub rotation: f32,
+    pub marker: MapMarker,
+    // Out of range and pinned to the edge
+    pub clamped: bool,
+}
+
+// The view a minimap widget shows. World space is y up and the map faces `rotation`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Minimap {
+    // World point at the widget's centre
+    pub center: Vec2,
+    // Entity the map centres on each update, using its `Transform2D`
+    pub follow: Option<Entity>,
+    // Turns the map with `follow` so its +Y points up, instead of keeping north up
+    pub rotate_with_follow: bool,
+    // Radians; the world direction `Vec2::from_angle(rotation).rotate(Vec2::Y)` points up
+    pub rotation: f32,
+    // World units from the centre to the nearest edge
+    pub range: f32,
+    pub min_range: f32,
+    pub max_range: f32,
+    pub shape: MinimapShape,
+    // Drawn under the markers; keep its camera at `view_camera` and its size small, with an
+    // `UpdateRate::Interval`, since a minimap doesn't need full resolution or every frame
+    pub background: Option<RenderTextureId>,
+    blips: Vec<Blip>,
+}
+
+impl Minimap {
+    pub fn new(range: f32) -> Self {
+        Self {
+            center: Vec2::ZERO,
+            follow: None,
+            rotate_with_follow: false,
+            rotation: 0.0,
+            range,
+            min_range: range * 0.25,
+            max_range: range * 4.0,
+            shape: MinimapShape::default(),
+            background: None,
+            blips: Vec::new(),
+        }
+    }
+
+    pub fn following(mut self, entity: Entity) -> Self {
+        self.follow = Some(entity);
+        self
+    }
+
+    // Above 1 zooms in, within `min_range..=max_range`
+    pub fn zoom(&mut self, factor: f32) {
+        if factor > 0.0 {
+            self.range = (self.range / factor).clamp(self.min_range, self.max_range);
+        }
+    }
+
+    pub fn blips(&self) -> &[Blip] {
+        &self.blips
+    }
+
+    // Pixels from the centre, y down, for a world point on a map whose nearest edge is
+    // `radius` pixels out
+    pub fn world_to_map(&self, point: Vec2, radius: f32) -> Vec2 {
+        let local = Vec2::from_angle(-self.rotation).rotate(point - self.center);
+        Vec2::new(local.x, -local.y) * (radius / self.range)
+    }
+
+    pub fn map_to_world(&self, offset: Vec2, radius: f32) -> Vec2 {
+        let local = Vec2::new(offset.x, -offset.y) * (self.range / radius);
+        self.center + Vec2::from_angle(self.rotation).rotate(local)
+    }
+
+    // Orthographic camera over the map's area for the `background` render texture, looking
+    // down -Z at a 2D scene
+    pub fn view_camera(&self, aspect: f32) -> ViewCamera {
+        let up = Vec2::from_angle(self.rotation).rotate(Vec2::Y);
+        let (half_width, half_height) =
+            if aspect >= 1.0 { (self.range * aspect, self.range) } else { (self.range, self.range / aspect) };
+        ViewCamera {
+            view: Mat4::look_at_rh(self.center.extend(CAMERA_HEIGHT), self.center.extend(0.0), up.extend(0.0)),
+            projection: Mat4::orthographic_rh(-half_width, half_width, -half_height, half_height, 0.0, CAMERA_HEIGHT * 2.0),
+        }
+    }
+
+    // Brings `offset` inside the map, or returns `None` if it is already inside
+    fn clamp(&self, offset: Vec2, half_size: Vec2) -> Option<Vec2> {
+        match self.shape {
+            MinimapShape::Rect => {
+                let clamped = offset.clamp(-half_size, half_size);
+                (clamped != offset).then_some(clamped)
+            }
+            MinimapShape::Circle => {
+                let radius = half_size.min_element();
+                (offset.length() > radius).then(|| offset.normalize_or_zero() * radius)
+            }
+        }
+    }
+
+    // Follows the target and places every marker for a widget of `size` pixels
+    fn update(&mut self, world: &World, size: Vec2) {
+        if let Some(target) = self.follow.and_then(|entity| world.get::<Transform2D>(entity)) {
+            self.center = target.translation;
+            if self.rotate_with_follow {
+                self.rotation = target.rotation;
+            }
+        }
+        let half_size = size * 0.5;
+        let radius = half_size.min_element();
+        self.blips.clear();
+        for (entity, marker) in world.query::<MapMarker>() {
+            let Some(transform) = world.get::<Transform2D>(entity) else { continue };
+            let mut offset = self.world_to_map(transform.translation, radius);
+            let mut clamped = false;
+            if let Some(edge) = self.clamp(offset, half_size) {
+                if !marker.clamp_to_edge {
+                    continue;
+                }
+                offset = edge;
+                clamped = true;
+            }
+            let rotation = if marker.rotates { transform.rotation - self.rotation } else { 0.0 };
+            self.blips.push(Blip { entity, offset, rotation, marker: marker.clone(), clamped });
+        }
+    }
+}
+
+// Queued in `UiEvents<MinimapPing>` when a minimap is clicked, or confirmed while focused
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MinimapPing {
+    pub widget: WidgetId,
+    // Where on the world the map was clicked; its centre without a click point
+    pub position: Vec2,
+}
+
+impl Widget {
+    pub fn minimap(rect: Rect, minimap: Minimap) -> Self {
+        Self::new(WidgetKind::Minimap(Box::new(minimap)), rect)
+    }
+
+    pub fn as_minimap(&self) -> Option<&Minimap> {
+        match &self.kind {
+            WidgetKind::Minimap(minimap) => Some(minimap),
+            _ => None,
+        }
+    }
+
+    pub fn as_minimap_mut(&mut self) -> Option<&mut Minimap> {
+        match &mut self.kind {
+            WidgetKind::Minimap(minimap) => Some(minimap),
+            _ => None,
+        }
+    }
+
+    // The ping for activating this widget at `point`, if it is a minimap and the point
+    // falls on the map
+    pub(crate) fn ping(&self, widget: WidgetId, point: Option<Vec2>) -> Option<MinimapPing> {
+        let minimap = self.as_minimap()?;
+        let Some(point) = point else { return Some(MinimapPing { widget, position: minimap.center }) };
+        let rect = self.display_rect();
+        let offset = point - rect.center();
+        if minimap.clamp(offset, rect.size() * 0.5).is_some() {
+            return None;
+        }
+        let radius = rect.size().min_element() * 0.5;
+        Some(MinimapPing { widget, position: minimap.map_to_world(offset, radius) })
+    }
+}
+
+impl Ui {
+    pub(crate) fn update_minimaps(&mut self, world: &World) {
+        for widget in self.widgets.iter_mut().flatten() {
+            let size = widget.rect.size();
+            if let WidgetKind::Minimap(minimap) = &mut widget.kind {
+                minimap.update(world, size);
+            }
+        }
+    }
+}
+
@@ -0,0 +1,284 @@
+// src/ui/mod.rs
+pub mod animation;
+pub mod binding;
+pub mod focus;
+pub mod minimap;
+pub mod pass;
+pub mod transition;
+
+pub use animation::{AnimationId, Easing, Tween, UiAnimation, UiProperty};
+pub use binding::{UiEvents, UiValue};
+pub use focus::{FocusStyle, FocusWrap, NavDirection, Navigation, UiCancel};
+pub use minimap::{Blip, MapMarker, Minimap, MinimapPing, MinimapShape};
+pub use pass::UiPass;
+pub use transition::{ScreenTransition, TransitionKind, WipeDirection, TRANSITION_EFFECT, TRANSITION_EFFECT_ORDER, TRANSITION_EFFECT_WGSL};
+
+use crate::color::Color;
+use crate::ecs::world::World;
+use crate::math::Rect;
+use animation::Playing;
+use binding::{Binding, Callback, ClearEvents};
+use focus::Focus;
+use glam::Vec2;
+use std::any::TypeId;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct WidgetId(u32);
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum WidgetKind {
+    Panel,
+    Label { text: String },
+    Button { text: String },
+    // Filled left to right, 0..=1, e.g. a health bar
+    Bar { value: f32 },
+    Minimap(Box<Minimap>),
+    // Busy indicator; whoever owns it turns `angle`, in radians
+    Spinner { angle: f32 },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Widget {
+    pub kind: WidgetKind,
+    // Screen pixels from the top-left, y down
+    pub rect: Rect,
+    pub color: Color,
+    pub visible: bool,
+    // Disabled widgets are drawn but ignore interaction
+    pub enabled: bool,
+    // Display-only, usually animated: multiplies `color`'s alpha
+    pub opacity: f32,
+    // Display-only: pixels moved from `rect`, and a scale about its centre
+    pub offset: Vec2,
+    pub scale: f32,
+    // Set by `Ui::set_focus`
+    pub focused: bool,
+}
+
+impl Widget {
+    pub fn new(kind: WidgetKind, rect: Rect) -> Self {
+        Self { kind, rect, color: Color::WHITE, visible: true, enabled: true, opacity: 1.0, offset: Vec2::ZERO, scale: 1.0, focused: false }
+    }
+
+    pub fn panel(rect: Rect) -> Self {
+        Self::new(WidgetKind::Panel, rect)
+    }
+
+    pub fn label(rect: Rect, text: &str) -> Self {
+        Self::new(WidgetKind::Label { text: text.to_string() }, rect)
+    }
+
+    pub fn button(rect: Rect, text: &str) -> Self {
+        Self::new(WidgetKind::Button { text: text.to_string() }, rect)
+    }
+
+    pub fn bar(rect: Rect, value: f32) -> Self {
+        Self::new(WidgetKind::Bar { value: value.clamp(0.0, 1.0) }, rect)
+    }
+
+    pub fn spinner(rect: Rect) -> Self {
+        Self::new(WidgetKind::Spinner { angle: 0.0 }, rect)
+    }
+
+    pub fn text(&self) -> Option<&str> {
+        match &self.kind {
+            WidgetKind::Label { text } | WidgetKind::Button { text } => Some(text),
+            _ => None,
+        }
+    }
+
+    pub fn is_interactive(&self) -> bool {
+        matches!(self.kind, WidgetKind::Button { .. } | WidgetKind::Minimap(_))
+    }
+
+    // Where the widget is drawn, after `offset` and `scale`
+    pub fn display_rect(&self) -> Rect {
+        Rect::from_center_size(self.rect.center() + self.offset, self.rect.size() * self.scale)
+    }
+
+    // `color` with `opacity` applied
+    pub fn display_color(&self) -> Color {
+        self.color.with_alpha(self.color.a * self.opacity)
+    }
+}
+
+// Retained screen-space widgets, kept as a world resource. Game code builds the widgets
+// once, binds them to the data they show and subscribes to what they do; `update_ui` then
+// keeps them in sync and delivers their events, so nothing needs copying by hand each frame.
+// Later widgets draw over earlier ones.
+pub struct Ui {
+    pub navigation: Navigation,
+    pub focus_style: FocusStyle,
+    // Ids index this and are never reused
+    widgets: Vec<Option<Widget>>,
+    bindings: Vec<Binding>,
+    callbacks: Vec<Callback>,
+    // Activated since the last `update_ui`, in order, with the click point if clicked
+    activated: Vec<(WidgetId, Option<Vec2>)>,
+    animations: Vec<Playing>,
+    next_animation: u32,
+    focus: Focus,
+    // `UiEvents` types cleared by each `update_ui`
+    event_types: Vec<(TypeId, ClearEvents)>,
+}
+
+impl Default for Ui {
+    fn default() -> Self {
+        let mut ui = Self {
+            navigation: Navigation::default(),
+            focus_style: FocusStyle::default(),
+            widgets: Vec::new(),
+            bindings: Vec::new(),
+            callbacks: Vec::new(),
+            activated: Vec::new(),
+            animations: Vec::new(),
+            next_animation: 0,
+            focus: Focus::default(),
+            event_types: Vec::new(),
+        };
+        ui.track_events::<MinimapPing>();
+        ui.track_events::<UiCancel>();
+        ui
+    }
+}
+
+impl Ui {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&mut self, widget: Widget) -> WidgetId {
+        self.widgets.push(Some(widget));
+        WidgetId(self.widgets.len() as u32 - 1)
+    }
+
+    // Drops the widget with its bindings, callbacks and focus
+    pub fn remove(&mut self, id: WidgetId) -> Option<Widget> {
+        self.forget_focus(id);
+        self.bindings.retain(|binding| binding.widget != id);
+        self.callbacks.retain(|callback| callback.widget != id);
+        self.widgets.get_mut(id.0 as usize)?.take()
+    }
+
+    pub fn get(&self, id: WidgetId) -> Option<&Widget> {
+        self.widgets.get(id.0 as usize)?.as_ref()
+    }
+
+    pub fn get_mut(&mut self, id: WidgetId) -> Option<&mut Widget> {
+        self.widgets.get_mut(id.0 as usize)?.as_mut()
+    }
+
+    // In draw order
+    pub fn widgets(&self) -> impl Iterator<Item = (WidgetId, &Widget)> {
+        self.widgets.iter().enumerate().filter_map(|(index, widget)| Some((WidgetId(index as u32), widget.as_ref()?)))
+    }
+
+    // Topmost visible, enabled, interactive widget under `point`
+    pub fn hit_test(&self, point: Vec2) -> Option<WidgetId> {
+        self.widgets()
+            .filter(|(_, widget)| widget.visible && widget.enabled && widget.is_interactive() && widget.display_rect().contains(point))
+            .last()
+            .map(|(id, _)| id)
+    }
+
+    // A click or tap at `point` in screen pixels; returns the widget it activated
+    pub fn click(&mut self, point: Vec2) -> Option<WidgetId> {
+        let id = self.hit_test(point)?;
+        self.activated.push((id, Some(point)));
+        Some(id)
+    }
+
+    // Presses `id` as if clicked, e.g. from a keyboard or gamepad confirm. Its callbacks
+    // run in the next `update_ui`.
+    pub fn activate(&mut self, id: WidgetId) {
+        if self.get(id).is_some_and(|widget| widget.visible && widget.enabled) {
+            self.activated.push((id, None));
+        }
+    }
+
+    // Plays `animation` from the next `update_ui`. Animations run side by side; when two
+    // touch the same property the one started later wins.
+    pub fn animate(&mut self, animation: UiAnimation) -> AnimationId {
+        let id = AnimationId(self.next_animation);
+        self.next_animation += 1;
+        self.animations.push(Playing { id, animation, elapsed: 0.0 });
+        id
+    }
+
+    // Leaves the widgets as they are now
+    pub fn stop_animation(&mut self, id: AnimationId) {
+        self.animations.retain(|playing| playing.id != id);
+    }
+
+    pub fn is_playing(&self, id: AnimationId) -> bool {
+        self.animations.iter().any(|playing| playing.id == id)
+    }
+
+    pub fn is_animating(&self, widget: WidgetId) -> bool {
+        self.animations.iter().any(|playing| playing.animation.widgets().any(|id| id == widget))
+    }
+
+    // Moves animations on by `delta_time` seconds; finished ones are left at their end
+    // values and dropped
+    pub(crate) fn advance_animations(&mut self, delta_time: f32) {
+        let mut animations = std::mem::take(&mut self.animations);
+        for playing in &mut animations {
+            playing.elapsed += delta_time;
+            playing.animation.sample(self, playing.elapsed);
+        }
+        animations.retain(|playing| playing.elapsed < playing.animation.duration());
+        self.animations = animations;
+    }
+
+    pub(crate) fn apply(&mut self, id: WidgetId, value: UiValue) {
+        let Some(widget) = self.get_mut(id) else { return };
+        match (value, &mut widget.kind) {
+            (UiValue::Text(new), WidgetKind::Label { text } | WidgetKind::Button { text }) => *text = new,
+            (UiValue::Value(new), WidgetKind::Bar { value }) => *value = new.clamp(0.0, 1.0),
+            (UiValue::Visible(visible), _) => widget.visible = visible,
+            (UiValue::Enabled(enabled), _) => widget.enabled = enabled,
+            (UiValue::Color(color), _) => widget.color = color,
+            (value, kind) => log::warn!("Can't bind {:?} to a {:?} widget", value, kind),
+        }
+    }
+}
+
+// System refreshing bound widgets and minimaps from the world, queueing events and running
+// callbacks for widgets activated since the last run, and playing animations
+pub fn update_ui(world: &mut World, delta_time: f64) {
+    // Taken out so bindings can read the world and callbacks write to it
+    let Some(mut ui) = world.resources.remove::<Ui>() else { return };
+    ui.clear_events(world);
+    let mut updates = Vec::new();
+    for binding in &mut ui.bindings {
+        if let Some(value) = binding.poll(world) {
+            updates.push((binding.widget, value));
+        }
+    }
+    for (widget, value) in updates {
+        ui.apply(widget, value);
+    }
+    ui.update_minimaps(world);
+    for cancel in std::mem::take(&mut ui.focus.cancelled) {
+        world.resources.get_or_insert_with(UiEvents::<UiCancel>::new).push(cancel);
+    }
+    // Taken out so callbacks can change the `Ui`; ones they add land in `ui.callbacks`
+    let mut callbacks = std::mem::take(&mut ui.callbacks);
+    for (id, point) in std::mem::take(&mut ui.activated) {
+        if let Some(ping) = ui.get(id).and_then(|widget| widget.ping(id, point)) {
+            world.resources.get_or_insert_with(UiEvents::<MinimapPing>::new).push(ping);
+        }
+        for callback in callbacks.iter_mut().filter(|callback| callback.widget == id) {
+            // An earlier callback may have removed the widget
+            if ui.get(id).is_none() {
+                break;
+            }
+            (callback.run)(world, &mut ui);
+        }
+    }
+    callbacks.retain(|callback| ui.get(callback.widget).is_some());
+    callbacks.append(&mut ui.callbacks);
+    ui.callbacks = callbacks;
+    ui.advance_animations(delta_time as f32);
+    world.resources.insert(ui);
+}
@@ -0,0 +1,213 @@
+// src/ui/pass.rs
+use super::minimap::Minimap;
+use super::{Ui, Widget, WidgetKind};
+use crate::bind_cache::{BindGroupCache, SamplerCache};
+use crate::capabilities::GpuCapabilities;
+use crate::color::Color;
+use crate::math::Rect;
+use crate::render_texture::RenderTextureId;
+use crate::resource_registry::{ResourceRegistry, Tracked};
+use crate::sprite_batch::{Sprite, SpriteBatch, TextureSlot};
+use crate::text::{layout_text, BitmapFont, IconSet, TextAlign, TextStyle};
+use crate::upload::UploadBelt;
+use glam::{Mat4, Vec2};
+use std::collections::HashMap;
+use std::f32::consts::TAU;
+
+// Unfilled part of a bar and a button's background, as a share of the widget's brightness
+const BACKGROUND_SHADE: f32 = 0.25;
+// Disabled widgets fade to this share of their alpha
+const DISABLED_ALPHA: f32 = 0.5;
+const SPINNER_DOTS: usize = 8;
+
+// Draws the `Ui` resource's widgets over the frame with a `SpriteBatch`. Widgets are laid
+// out in UI units, y down; the pass maps the target's size in those units onto the screen.
+pub struct UiPass {
+    batch: SpriteBatch,
+    // 1x1 white, tinted for solid quads
+    _white_texture: Tracked<wgpu::Texture>,
+    white: TextureSlot,
+    // With a texture slot per font page
+    font: Option<(BitmapFont, Vec<TextureSlot>)>,
+    // Minimap marker icons by `MapMarker::icon`, with a texture slot per icon texture
+    icons: Option<(IconSet, Vec<TextureSlot>)>,
+    // Minimap backgrounds
+    render_textures: HashMap<RenderTextureId, TextureSlot>,
+    // Target size in UI units, from the last `queue`
+    screen: Vec2,
+}
+
+impl UiPass {
+    pub fn new(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        resources: &ResourceRegistry,
+        samplers: &mut SamplerCache,
+        capabilities: &GpuCapabilities,
+        color_format: wgpu::TextureFormat,
+    ) -> Self {
+        let mut batch = SpriteBatch::new(device, resources, samplers, capabilities, color_format);
+        let white_texture = resources.create_texture(device, &wgpu::TextureDescriptor {
+            label: Some("ui white"),
+            size: wgpu::Extent3d { width: 1, height: 1, depth_or_array_layers: 1 },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgba8Unorm,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        }, "ui");
+        queue.write_texture(
+            white_texture.as_image_copy(),
+            &[255, 255, 255, 255],
+            wgpu::TexelCopyBufferLayout { offset: 0, bytes_per_row: Some(4), rows_per_image: None },
+            wgpu::Extent3d { width: 1, height: 1, depth_or_array_layers: 1 },
+        );
+        // The batch is empty, so there is always room for the first texture
+        let white = batch
+            .add_texture(white_texture.create_view(&wgpu::TextureViewDescriptor::default()))
+            .expect("empty sprite batch rejected a texture");
+        Self {
+            batch,
+            _white_texture: white_texture,
+            white,
+            font: None,
+            icons: None,
+            render_textures: HashMap::new(),
+            screen: Vec2::ONE,
+        }
+    }
+
+    // Labels and buttons show no text until a font is set; `pages` holds a view of each
+    // font page's texture
+    pub fn set_font(&mut self, font: BitmapFont, pages: Vec<wgpu::TextureView>) -> Result<(), String> {
+        let slots = pages.into_iter().map(|page| self.batch.add_texture(page)).collect::<Result<Vec<_>, _>>()?;
+        self.font = Some((font, slots));
+        Ok(())
+    }
+
+    // Minimap markers draw as their icon from `icons`, or as a square without one;
+    // `textures` holds a view of each icon texture
+    pub fn set_icons(&mut self, icons: IconSet, textures: Vec<wgpu::TextureView>) -> Result<(), String> {
+        let slots = textures.into_iter().map(|texture| self.batch.add_texture(texture)).collect::<Result<Vec<_>, _>>()?;
+        self.icons = Some((icons, slots));
+        Ok(())
+    }
+
+    // Lets minimaps show `id` as their `background`; register it again after resizing it
+    pub fn add_render_texture(&mut self, id: RenderTextureId, view: wgpu::TextureView) -> Result<(), String> {
+        let slot = self.batch.add_texture(view)?;
+        self.render_textures.insert(id, slot);
+        Ok(())
+    }
+
+    // Queues every visible widget for the next `prepare`, in draw order; `screen` is the
+    // target's size in UI units
+    pub fn queue(&mut self, ui: &Ui, screen: Vec2) {
+        self.screen = screen.max(Vec2::ONE);
+        for (_, widget) in ui.widgets().filter(|(_, widget)| widget.visible) {
+            self.queue_widget(widget);
+        }
+    }
+
+    fn queue_widget(&mut self, widget: &Widget) {
+        let rect = widget.display_rect();
+        let mut color = widget.display_color();
+        if !widget.enabled {
+            color.a *= DISABLED_ALPHA;
+        }
+        let background = color.scaled(BACKGROUND_SHADE);
+        match &widget.kind {
+            WidgetKind::Panel => self.quad(rect.center(), rect.size(), color),
+            WidgetKind::Label { text } => self.text(text, rect, widget.scale, color, TextAlign::Left),
+            WidgetKind::Button { text } => {
+                self.quad(rect.center(), rect.size(), background);
+                self.text(text, rect, widget.scale, color, TextAlign::Center);
+            }
+            WidgetKind::Bar { value } => {
+                self.quad(rect.center(), rect.size(), background);
+                let fill = Vec2::new(rect.width() * value, rect.height());
+                self.quad(rect.min + fill * 0.5, fill, color);
+            }
+            // A ring of dots fading behind the leading one
+            WidgetKind::Spinner { angle } => {
+                let radius = rect.size().min_element() * 0.5;
+                let dot = radius * 0.3;
+                for index in 0..SPINNER_DOTS {
+                    let trail = index as f32 / SPINNER_DOTS as f32;
+                    let center = rect.center() + Vec2::from_angle(angle - trail * TAU) * (radius - dot * 0.5);
+                    self.quad(center, Vec2::splat(dot), color.with_alpha(color.a * (1.0 - trail)));
+                }
+            }
+            WidgetKind::Minimap(minimap) => self.minimap(minimap, rect, widget.scale, color),
+        }
+    }
+
+    // The background, or a backdrop without one, then the blips from the last `update_ui`
+    fn minimap(&mut self, minimap: &Minimap, rect: Rect, scale: f32, color: Color) {
+        match minimap.background.and_then(|id| self.render_textures.get(&id)) {
+            Some(&texture) => {
+                let mut sprite = Sprite::new(texture, self.to_screen(rect.center()), rect.size());
+                sprite.color = Color::WHITE.with_alpha(color.a);
+                self.batch.push(sprite);
+            }
+            None => self.quad(rect.center(), rect.size(), color.scaled(BACKGROUND_SHADE)),
+        }
+        for blip in minimap.blips() {
+            let icon = self.icons.as_ref().and_then(|(icons, slots)| {
+                let icon = icons.get(&blip.marker.icon)?;
+                Some((*slots.get(icon.texture as usize)?, icon.uv))
+            });
+            let (texture, uv) = icon.unwrap_or((self.white, [Vec2::ZERO, Vec2::ONE]));
+            let center = rect.center() + blip.offset * scale;
+            let mut sprite = Sprite::new(texture, self.to_screen(center), Vec2::splat(blip.marker.size));
+            sprite.uv = uv;
+            sprite.rotation = blip.rotation;
+            sprite.color = blip.marker.color.with_alpha(blip.marker.color.a * color.a);
+            self.batch.push(sprite);
+        }
+    }
+
+    // UI units are y down and sprites y up
+    fn to_screen(&self, point: Vec2) -> Vec2 {
+        Vec2::new(point.x, self.screen.y - point.y)
+    }
+
+    fn quad(&mut self, center: Vec2, size: Vec2, color: Color) {
+        let mut sprite = Sprite::new(self.white, self.to_screen(center), size);
+        sprite.color = color;
+        self.batch.push(sprite);
+    }
+
+    // Wrapped to `rect`'s width and centred in it vertically
+    fn text(&mut self, text: &str, rect: Rect, scale: f32, color: Color, align: TextAlign) {
+        let Some((font, pages)) = &self.font else { return };
+        let style = TextStyle { scale, color, max_width: Some(rect.width()), align, line_spacing: 1.0 };
+        let layout = layout_text(font, text, &style);
+        let top = rect.min.y + ((rect.height() - layout.size.y) * 0.5).max(0.0);
+        let origin = self.to_screen(Vec2::new(rect.min.x, top));
+        for sprite in layout.sprites(origin, pages, &[], 0) {
+            self.batch.push(sprite);
+        }
+    }
+
+    // Uploads what `queue` gathered; call once per frame before the pass it draws in
+    pub fn prepare(
+        &mut self,
+        device: &wgpu::Device,
+        resources: &ResourceRegistry,
+        encoder: &mut wgpu::CommandEncoder,
+        belt: &mut UploadBelt,
+    ) {
+        let projection = Mat4::orthographic_rh(0.0, self.screen.x, 0.0, self.screen.y, -1.0, 1.0);
+        self.batch.prepare(device, resources, encoder, belt, projection);
+    }
+
+    pub fn draw_calls(&self) -> u32 {
+        self.batch.draw_calls()
+    }
+
+    pub fn draw(&self, device: &wgpu::Device, bind_groups: &mut BindGroupCache, render_pass: &mut wgpu::RenderPass<'_>) {
+        self.batch.draw(device, bind_groups, render_pass);
+    }
+}
@@ -0,0 +1,97 @@
+// src/ui/transition.rs
+use super::animation::Easing;
+use crate::color::Color;
+use crate::screen_effect::MAX_EFFECT_PARAMS;
+use glam::{Vec2, Vec4};
+
+// Screen effect source drawing transitions; add it to the last chain before present and
+// feed it `StateStack::apply_transition`
+pub const TRANSITION_EFFECT_WGSL: &str = include_str!("transition.wgsl");
+pub const TRANSITION_EFFECT: &str = "screen transition";
+// Over the usual effects, and under the colour vision filter so that sees it too
+pub const TRANSITION_EFFECT_ORDER: i32 = 900;
+
+// Width of a wipe's soft edge, as a fraction of the screen
+const WIPE_SOFTNESS: f32 = 0.02;
+
+// The way a wipe travels across the screen
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WipeDirection {
+    Left,
+    Right,
+    Up,
+    Down,
+}
+
+impl WipeDirection {
+    // In uv space, y down
+    fn vector(self) -> Vec2 {
+        match self {
+            WipeDirection::Left => Vec2::NEG_X,
+            WipeDirection::Right => Vec2::X,
+            WipeDirection::Up => Vec2::NEG_Y,
+            WipeDirection::Down => Vec2::Y,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransitionKind {
+    Fade,
+    Wipe(WipeDirection),
+}
+
+// Covers the screen over the first half of `duration` and uncovers it over the second.
+// The state stack swaps states at the midpoint, while nothing shows through.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ScreenTransition {
+    pub kind: TransitionKind,
+    // Linear, as `Color::to_array` gives it
+    pub color: Color,
+    // Seconds, both halves together
+    pub duration: f32,
+    pub easing: Easing,
+}
+
+impl ScreenTransition {
+    // Fade to black and back
+    pub fn fade(duration: f32) -> Self {
+        Self { kind: TransitionKind::Fade, color: Color::BLACK, duration, easing: Easing::EaseInOut }
+    }
+
+    pub fn wipe(direction: WipeDirection, duration: f32) -> Self {
+        Self { kind: TransitionKind::Wipe(direction), ..Self::fade(duration) }
+    }
+
+    pub fn with_color(mut self, color: Color) -> Self {
+        self.color = color;
+        self
+    }
+
+    // How much of the screen is covered `elapsed` seconds in, 0..=1
+    pub fn coverage(&self, elapsed: f32) -> f32 {
+        let half = self.duration * 0.5;
+        if half <= 0.0 {
+            return 0.0;
+        }
+        if elapsed < half {
+            self.easing.apply(elapsed / half)
+        } else {
+            1.0 - self.easing.apply((elapsed - half) / half)
+        }
+    }
+
+    // `ScreenEffect::params` for `TRANSITION_EFFECT_WGSL` at `elapsed` seconds
+    pub fn effect_params(&self, elapsed: f32) -> [Vec4; MAX_EFFECT_PARAMS] {
+        let (wipe, direction) = match self.kind {
+            TransitionKind::Fade => (0.0, Vec2::ZERO),
+            TransitionKind::Wipe(direction) => (1.0, direction.vector()),
+        };
+        let revealing = if elapsed >= self.duration * 0.5 { 1.0 } else { 0.0 };
+        let mut params = [Vec4::ZERO; MAX_EFFECT_PARAMS];
+        params[0] = Vec4::from(self.color);
+        params[1] = Vec4::new(self.coverage(elapsed), wipe, revealing, WIPE_SOFTNESS);
+        params[2] = direction.extend(0.0).extend(0.0);
+        params
+    }
+}
@@ -0,0 +1,27 @@
+// Screen transition, run as a screen effect after `SCREEN_EFFECT_PRELUDE`. Parameters
+// come from `ScreenTransition::effect_params`:
+//   params[0]  colour covering the screen, alpha scaling it
+//   params[1]  x: coverage 0..=1, y: 0 fade / 1 wipe, z: 1 while revealing, w: edge softness
+//   params[2]  xy: direction the wipe travels in, uv space
+
+@fragment
+fn fs_main(in: EffectVertex) -> @location(0) vec4<f32> {
+    let scene = sample_source(in.uv);
+    let color = effect.params[0];
+    let coverage = effect.params[1].x;
+    var amount = coverage;
+    if effect.params[1].y > 0.5 {
+        let softness = effect.params[1].w;
+        // 0 at the edge the wipe starts from, 1 at the far one
+        let along = dot(in.uv - 0.5, effect.params[2].xy) + 0.5;
+        if effect.params[1].z > 0.5 {
+            // The trailing edge follows in the same direction, uncovering what it passed
+            let edge = (1.0 - coverage) * (1.0 + 2.0 * softness) - softness;
+            amount = smoothstep(edge - softness, edge, along);
+        } else {
+            let edge = coverage * (1.0 + 2.0 * softness) - softness;
+            amount = 1.0 - smoothstep(edge - softness, edge, along);
+        }
+    }
+    return vec4<f32>(mix(scene.rgb, color.rgb, amount * color.a), scene.a);
+}
@@ -0,0 +1,134 @@
+// src/upload.rs
+use crate::resource_registry::{ResourceRegistry, Tracked};
+use wgpu::util::StagingBelt;
+
+// Frames the CPU may record ahead of the GPU; per-frame buffers keep this many copies
+pub const FRAMES_IN_FLIGHT: usize = 2;
+
+// Per-frame uploads go through reused, persistently recycled staging chunks instead
+// of allocating a fresh buffer for every write.
+pub struct UploadBelt {
+    belt: StagingBelt,
+    bytes_this_frame: u64,
+}
+
+impl UploadBelt {
+    pub fn new(chunk_size: wgpu::BufferAddress) -> Self {
+        Self {
+            belt: StagingBelt::new(chunk_size),
+            bytes_this_frame: 0,
+        }
+    }
+
+    // Copies `data` into `target` at `offset` as part of `encoder`. The write is padded
+    // to `COPY_BUFFER_ALIGNMENT`, so `target` must have room for the rounded-up size.
+    pub fn write(
+        &mut self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        target: &wgpu::Buffer,
+        offset: wgpu::BufferAddress,
+        data: &[u8],
+    ) {
+        let Some(size) = wgpu::BufferSize::new(aligned_size(data.len() as u64)) else { return };
+        let mut view = self.belt.write_buffer(encoder, target, offset, size, device);
+        view[..data.len()].copy_from_slice(data);
+        self.bytes_this_frame += size.get();
+    }
+
+    // Call after recording all writes and before submitting the encoders that use them
+    pub fn finish(&mut self) {
+        self.belt.finish();
+    }
+
+    // Call after submitting; chunks come back once the device is polled
+    pub fn recall(&mut self) -> u64 {
+        self.belt.recall();
+        std::mem::take(&mut self.bytes_this_frame)
+    }
+}
+
+fn aligned_size(size: u64) -> u64 {
+    wgpu::util::align_to(size, wgpu::COPY_BUFFER_ALIGNMENT)
+}
+
+// GPU buffer that keeps its allocation between frames and only grows when the data outgrows it
+pub struct DynamicBuffer {
+    buffer: Tracked<wgpu::Buffer>,
+    label: &'static str,
+    usage: wgpu::BufferUsages,
+    len: u64,
+}
+
+impl DynamicBuffer {
+    pub fn new(
+        device: &wgpu::Device,
+        resources: &ResourceRegistry,
+        label: &'static str,
+        usage: wgpu::BufferUsages,
+        capacity: u64,
+    ) -> Self {
+        let usage = usage | wgpu::BufferUsages::COPY_DST;
+        Self {
+            buffer: Self::allocate(device, resources, label, usage, capacity),
+            label,
+            usage,
+            len: 0,
+        }
+    }
+
+    fn allocate(
+        device: &wgpu::Device,
+        resources: &ResourceRegistry,
+        label: &'static str,
+        usage: wgpu::BufferUsages,
+        capacity: u64,
+    ) -> Tracked<wgpu::Buffer> {
+        resources.create_buffer(
+            device,
+            &wgpu::BufferDescriptor {
+                label: Some(label),
+                size: aligned_size(capacity.max(wgpu::COPY_BUFFER_ALIGNMENT)),
+                usage,
+                mapped_at_creation: false,
+            },
+            label,
+        )
+    }
+
+    pub fn buffer(&self) -> &wgpu::Buffer {
+        &self.buffer
+    }
+
+    // Bytes written by the last upload
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn capacity(&self) -> u64 {
+        self.buffer.size()
+    }
+
+    // Replaces the contents with `data`, growing to the next power of two if needed
+    pub fn upload(
+        &mut self,
+        device: &wgpu::Device,
+        resources: &ResourceRegistry,
+        encoder: &mut wgpu::CommandEncoder,
+        belt: &mut UploadBelt,
+        data: &[u8],
+    ) {
+        let needed = aligned_size(data.len() as u64);
+        if needed > self.capacity() {
+            let capacity = needed.next_power_of_two();
+            log::debug!("Growing {} buffer to {} bytes", self.label, capacity);
+            self.buffer = Self::allocate(device, resources, self.label, self.usage, capacity);
+        }
+        belt.write(device, encoder, &self.buffer, 0, data);
+        self.len = data.len() as u64;
+    }
+}
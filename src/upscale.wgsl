@@ -0,0 +1,63 @@
+// Upscale from the internal render resolution to the output: nearest for pixel art,
+// bilinear, or bilinear followed by contrast-adaptive sharpening to win back detail
+
+struct UpscaleParams {
+    input_size: vec2<f32>,
+    filter_mode: u32,
+    sharpness: f32,
+}
+
+@group(0) @binding(0) var<uniform> params: UpscaleParams;
+@group(0) @binding(1) var input_texture: texture_2d<f32>;
+@group(0) @binding(2) var input_sampler: sampler;
+
+struct FullscreenVertex {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+}
+
+@vertex
+fn vs_fullscreen(@builtin(vertex_index) index: u32) -> FullscreenVertex {
+    let uv = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+    var out: FullscreenVertex;
+    out.position = vec4<f32>(uv.x * 2.0 - 1.0, 1.0 - uv.y * 2.0, 0.0, 1.0);
+    out.uv = uv;
+    return out;
+}
+
+fn bilinear(uv: vec2<f32>) -> vec3<f32> {
+    return textureSampleLevel(input_texture, input_sampler, uv, 0.0).rgb;
+}
+
+// Sharpens less where the neighbourhood already has contrast, so edges don't ring.
+// Expects colour in 0..1; brighter HDR values pass through unsharpened.
+fn sharpen(uv: vec2<f32>) -> vec3<f32> {
+    let texel = 1.0 / params.input_size;
+    let c = bilinear(uv);
+    let n = bilinear(uv + vec2<f32>(0.0, -texel.y));
+    let s = bilinear(uv + vec2<f32>(0.0, texel.y));
+    let e = bilinear(uv + vec2<f32>(texel.x, 0.0));
+    let w = bilinear(uv + vec2<f32>(-texel.x, 0.0));
+    let low = min(c, min(min(n, s), min(e, w)));
+    let high = max(c, max(max(n, s), max(e, w)));
+    let amount = sqrt(clamp(min(low, 1.0 - high) / max(high, vec3<f32>(1e-5)), vec3<f32>(0.0), vec3<f32>(1.0)));
+    let weight = amount * (-1.0 / mix(8.0, 5.0, params.sharpness));
+    return (c + (n + s + e + w) * weight) / (1.0 + 4.0 * weight);
+}
+
+@fragment
+fn fs_upscale(in: FullscreenVertex) -> @location(0) vec4<f32> {
+    switch params.filter_mode {
+        case 0u: {
+            let max_pixel = vec2<i32>(params.input_size) - 1;
+            let pixel = min(vec2<i32>(in.uv * params.input_size), max_pixel);
+            return vec4<f32>(textureLoad(input_texture, pixel, 0).rgb, 1.0);
+        }
+        case 1u: {
+            return vec4<f32>(bilinear(in.uv), 1.0);
+        }
+        default: {
+            return vec4<f32>(sharpen(in.uv), 1.0);
+        }
+    }
+}
@@ -0,0 +1,327 @@
+// src/water.rs
+use crate::bind_cache::{BindGroupCache, BindingKey, SamplerCache};
+use crate::resource_registry::{ResourceRegistry, Tracked};
+use crate::upload::UploadBelt;
+use glam::{Mat4, Vec2, Vec3, Vec4};
+
+pub const WATER_DEPTH_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth32Float;
+
+// Mirrors `WaterParams` in water.wgsl
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct WaterParams {
+    view_proj: [f32; 16],
+    camera_position: [f32; 3],
+    time: f32,
+    water_color: [f32; 3],
+    distortion: f32,
+    height: f32,
+    size: f32,
+    wave_scale: f32,
+    wave_speed: f32,
+    screen_size: [f32; 2],
+    center: [f32; 2],
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WaterSettings {
+    // The water is a square of `size` at `height`, centred on `center` in XZ
+    pub height: f32,
+    pub size: f32,
+    pub center: Vec2,
+    // Tint applied to what's seen through the surface
+    pub color: Vec3,
+    // Screen-space UV offset per unit of normal tilt
+    pub distortion: f32,
+    // Normal map repeats per world unit, and how fast it scrolls
+    pub wave_scale: f32,
+    pub wave_speed: f32,
+}
+
+impl Default for WaterSettings {
+    fn default() -> Self {
+        Self {
+            height: 0.0,
+            size: 100.0,
+            center: Vec2::ZERO,
+            color: Vec3::new(0.4, 0.7, 0.8),
+            distortion: 0.02,
+            wave_scale: 0.1,
+            wave_speed: 0.03,
+        }
+    }
+}
+
+// Colour + depth target the reflected or refracted scene is rendered into
+pub struct OffscreenTarget {
+    _color: Tracked<wgpu::Texture>,
+    pub color_view: wgpu::TextureView,
+    _depth: Tracked<wgpu::Texture>,
+    pub depth_view: wgpu::TextureView,
+}
+
+impl OffscreenTarget {
+    fn new(device: &wgpu::Device, resources: &ResourceRegistry, label: &str, format: wgpu::TextureFormat, size: (u32, u32)) -> Self {
+        let texture = |format, usage| resources.create_texture(device, &wgpu::TextureDescriptor {
+            label: Some(label),
+            size: wgpu::Extent3d { width: size.0, height: size.1, depth_or_array_layers: 1 },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage,
+            view_formats: &[],
+        }, "water");
+        let color = texture(format, wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING);
+        let depth = texture(WATER_DEPTH_FORMAT, wgpu::TextureUsages::RENDER_ATTACHMENT);
+        Self {
+            color_view: color.create_view(&wgpu::TextureViewDescriptor::default()),
+            _color: color,
+            depth_view: depth.create_view(&wgpu::TextureViewDescriptor::default()),
+            _depth: depth,
+        }
+    }
+}
+
+// Planar reflection and refraction. Each frame: render the scene from `reflection_view`
+// into `reflection`, render it normally (without the water) into `refraction`, then draw
+// the surface in the main pass.
+pub struct WaterRenderer {
+    pub settings: WaterSettings,
+    color_format: wgpu::TextureFormat,
+    size: (u32, u32),
+    reflection: OffscreenTarget,
+    refraction: OffscreenTarget,
+    _flat_normal: Tracked<wgpu::Texture>,
+    flat_normal_view: wgpu::TextureView,
+    normal_map: Option<wgpu::TextureView>,
+    params_buffer: Tracked<wgpu::Buffer>,
+    layout: wgpu::BindGroupLayout,
+    pipeline: wgpu::RenderPipeline,
+}
+
+impl WaterRenderer {
+    // `depth_format` must match the main pass the surface is drawn in
+    pub fn new(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        resources: &ResourceRegistry,
+        color_format: wgpu::TextureFormat,
+        depth_format: Option<wgpu::TextureFormat>,
+        width: u32,
+        height: u32,
+    ) -> Self {
+        let texture_entry = |binding| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Texture {
+                sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                view_dimension: wgpu::TextureViewDimension::D2,
+                multisampled: false,
+            },
+            count: None,
+        };
+        let sampler_entry = |binding| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+            count: None,
+        };
+        let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("water"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                texture_entry(1),
+                texture_entry(2),
+                texture_entry(3),
+                sampler_entry(4),
+                sampler_entry(5),
+            ],
+        });
+        let shader = device.create_shader_module(wgpu::include_wgsl!("water.wgsl"));
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("water"),
+            bind_group_layouts: &[&layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("water"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: Some("vs_main"),
+                buffers: &[],
+                compilation_options: Default::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: Some("fs_main"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: color_format,
+                    blend: None,
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: Default::default(),
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: depth_format.map(|format| wgpu::DepthStencilState {
+                format,
+                depth_write_enabled: true,
+                depth_compare: wgpu::CompareFunction::LessEqual,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        });
+
+        // Straight-up normal, used until `set_normal_map` gives the surface waves
+        let flat_normal = resources.create_texture(device, &wgpu::TextureDescriptor {
+            label: Some("water flat normal"),
+            size: wgpu::Extent3d { width: 1, height: 1, depth_or_array_layers: 1 },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgba8Unorm,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        }, "water");
+        queue.write_texture(
+            flat_normal.as_image_copy(),
+            &[128, 128, 255, 255],
+            wgpu::TexelCopyBufferLayout { offset: 0, bytes_per_row: Some(4), rows_per_image: None },
+            wgpu::Extent3d { width: 1, height: 1, depth_or_array_layers: 1 },
+        );
+        let flat_normal_view = flat_normal.create_view(&wgpu::TextureViewDescriptor::default());
+
+        let params_buffer = resources.create_buffer(device, &wgpu::BufferDescriptor {
+            label: Some("water params"),
+            size: std::mem::size_of::<WaterParams>() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        }, "water");
+
+        let size = (width.max(1), height.max(1));
+        Self {
+            settings: WaterSettings::default(),
+            color_format,
+            size,
+            reflection: OffscreenTarget::new(device, resources, "water reflection", color_format, size),
+            refraction: OffscreenTarget::new(device, resources, "water refraction", color_format, size),
+            _flat_normal: flat_normal,
+            flat_normal_view,
+            normal_map: None,
+            params_buffer,
+            layout,
+            pipeline,
+        }
+    }
+
+    pub fn resize(&mut self, device: &wgpu::Device, resources: &ResourceRegistry, width: u32, height: u32) {
+        let size = (width.max(1), height.max(1));
+        if size == self.size {
+            return;
+        }
+        self.size = size;
+        self.reflection = OffscreenTarget::new(device, resources, "water reflection", self.color_format, size);
+        self.refraction = OffscreenTarget::new(device, resources, "water refraction", self.color_format, size);
+    }
+
+    // Tangent-space normal map, sampled with repeat addressing so it should tile seamlessly
+    pub fn set_normal_map(&mut self, normal_map: Option<wgpu::TextureView>) {
+        self.normal_map = normal_map;
+    }
+
+    pub fn reflection(&self) -> &OffscreenTarget {
+        &self.reflection
+    }
+
+    pub fn refraction(&self) -> &OffscreenTarget {
+        &self.refraction
+    }
+
+    // `view` mirrored through the water plane; render the reflection with this and the
+    // usual projection
+    pub fn reflection_view(&self, view: Mat4) -> Mat4 {
+        let h = self.settings.height;
+        let mirror = Mat4::from_translation(Vec3::new(0.0, h, 0.0))
+            * Mat4::from_scale(Vec3::new(1.0, -1.0, 1.0))
+            * Mat4::from_translation(Vec3::new(0.0, -h, 0.0));
+        view * mirror
+    }
+
+    // World-space plane (normal, -distance) for clipping geometry below the water out of
+    // the reflection: keep fragments where `dot(plane, vec4(position, 1.0)) >= 0.0`.
+    // Mirroring flips winding, so the reflection pass also needs its cull mode inverted.
+    pub fn clip_plane(&self) -> Vec4 {
+        Vec4::new(0.0, 1.0, 0.0, -self.settings.height)
+    }
+
+    pub fn prepare(
+        &self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        belt: &mut UploadBelt,
+        view_proj: Mat4,
+        camera_position: Vec3,
+        time: f32,
+    ) {
+        let settings = &self.settings;
+        let params = WaterParams {
+            view_proj: view_proj.to_cols_array(),
+            camera_position: camera_position.into(),
+            time,
+            water_color: settings.color.into(),
+            distortion: settings.distortion,
+            height: settings.height,
+            size: settings.size,
+            wave_scale: settings.wave_scale,
+            wave_speed: settings.wave_speed,
+            screen_size: [self.size.0 as f32, self.size.1 as f32],
+            center: settings.center.into(),
+        };
+        belt.write(device, encoder, &self.params_buffer, 0, bytemuck::bytes_of(&params));
+    }
+
+    pub fn draw(
+        &self,
+        device: &wgpu::Device,
+        bind_groups: &mut BindGroupCache,
+        samplers: &mut SamplerCache,
+        render_pass: &mut wgpu::RenderPass<'_>,
+    ) {
+        let sampler = |samplers: &mut SamplerCache, label, address_mode| samplers.get(device, &wgpu::SamplerDescriptor {
+            label: Some(label),
+            address_mode_u: address_mode,
+            address_mode_v: address_mode,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            mipmap_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+        let wrap = sampler(samplers, "water wrap", wgpu::AddressMode::Repeat);
+        let clamp = sampler(samplers, "water clamp", wgpu::AddressMode::ClampToEdge);
+        let normal_map = self.normal_map.as_ref().unwrap_or(&self.flat_normal_view);
+        let bind_group = bind_groups.get_or_create(device, "water", &self.layout, &[
+            (0, BindingKey::buffer(&self.params_buffer)),
+            (1, BindingKey::TextureView(self.reflection.color_view.clone())),
+            (2, BindingKey::TextureView(self.refraction.color_view.clone())),
+            (3, BindingKey::TextureView(normal_map.clone())),
+            (4, BindingKey::Sampler(wrap)),
+            (5, BindingKey::Sampler(clamp)),
+        ]);
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(0, &bind_group, &[]);
+        render_pass.draw(0..6, 0..1);
+    }
+}
@@ -0,0 +1,63 @@
+// Planar water: a quad at the water height that mixes the refracted scene below with a
+// mirrored render from the reflection camera, weighted by Fresnel and distorted by
+// two scrolling normal map samples
+
+struct WaterParams {
+    view_proj: mat4x4<f32>,
+    camera_position: vec3<f32>,
+    time: f32,
+    water_color: vec3<f32>,
+    distortion: f32,
+    height: f32,
+    size: f32,
+    wave_scale: f32,
+    wave_speed: f32,
+    screen_size: vec2<f32>,
+    center: vec2<f32>,
+}
+
+@group(0) @binding(0) var<uniform> params: WaterParams;
+@group(0) @binding(1) var reflection_texture: texture_2d<f32>;
+@group(0) @binding(2) var refraction_texture: texture_2d<f32>;
+@group(0) @binding(3) var normal_map: texture_2d<f32>;
+@group(0) @binding(4) var wrap_sampler: sampler;
+@group(0) @binding(5) var clamp_sampler: sampler;
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) world_position: vec3<f32>,
+}
+
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> VertexOutput {
+    let corners = array<vec2<f32>, 6>(
+        vec2<f32>(-0.5, -0.5), vec2<f32>(-0.5, 0.5), vec2<f32>(0.5, 0.5),
+        vec2<f32>(-0.5, -0.5), vec2<f32>(0.5, 0.5), vec2<f32>(0.5, -0.5),
+    );
+    let xz = params.center + corners[index] * params.size;
+    var out: VertexOutput;
+    out.world_position = vec3<f32>(xz.x, params.height, xz.y);
+    out.position = params.view_proj * vec4<f32>(out.world_position, 1.0);
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let scroll = params.time * params.wave_speed;
+    let uv = in.world_position.xz * params.wave_scale;
+    let a = textureSample(normal_map, wrap_sampler, uv + vec2<f32>(scroll, scroll * 0.5)).xyz;
+    let b = textureSample(normal_map, wrap_sampler, uv * 1.7 - vec2<f32>(scroll * 0.6, scroll)).xyz;
+    // Tangent-space normal maps store xy in red/green; the plane's up axis is the map's z
+    let tangent_normal = normalize((a + b) * 2.0 - 2.0);
+    let normal = normalize(vec3<f32>(tangent_normal.x, tangent_normal.z, tangent_normal.y));
+
+    let screen_uv = in.position.xy / params.screen_size;
+    let offset = normal.xz * params.distortion;
+    let reflection = textureSample(reflection_texture, clamp_sampler, screen_uv + offset).rgb;
+    let refraction = textureSample(refraction_texture, clamp_sampler, screen_uv - offset).rgb * params.water_color;
+
+    let view_direction = normalize(params.camera_position - in.world_position);
+    // Schlick with water's reflectance at normal incidence
+    let fresnel = 0.02 + 0.98 * pow(1.0 - max(dot(view_direction, normal), 0.0), 5.0);
+    return vec4<f32>(mix(refraction, reflection, fresnel), 1.0);
+}
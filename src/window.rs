@@ -2,9 +2,103 @@
 use winit::{
     event::WindowEvent,
     event_loop::{ActiveEventLoop},
-    window::{Window, WindowAttributes, WindowId},
+    monitor::{MonitorHandle, VideoModeHandle},
+    window::{Fullscreen, Window, WindowAttributes, WindowId},
 };
 use std::sync::Arc;
+use glam::Vec2;
+
+// Window size in physical pixels plus the OS scale factor. Render targets use physical
+// pixels; UI layout and text should use logical ones so they keep their size on HiDPI
+// displays instead of rendering tiny.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DisplayMetrics {
+    pub physical_width: u32,
+    pub physical_height: u32,
+    pub scale_factor: f64,
+}
+
+impl DisplayMetrics {
+    pub fn new(physical_width: u32, physical_height: u32, scale_factor: f64) -> Self {
+        Self { physical_width, physical_height, scale_factor: if scale_factor > 0.0 { scale_factor } else { 1.0 } }
+    }
+
+    pub fn from_window(window: &Window) -> Self {
+        let size = window.inner_size();
+        Self::new(size.width, size.height, window.scale_factor())
+    }
+
+    pub fn physical_size(&self) -> (u32, u32) {
+        (self.physical_width, self.physical_height)
+    }
+
+    pub fn logical_size(&self) -> Vec2 {
+        Vec2::new(self.physical_width as f32, self.physical_height as f32) / self.scale_factor as f32
+    }
+
+    pub fn to_physical(&self, logical: Vec2) -> Vec2 {
+        logical * self.scale_factor as f32
+    }
+
+    pub fn to_logical(&self, physical: Vec2) -> Vec2 {
+        physical / self.scale_factor as f32
+    }
+}
+
+impl Default for DisplayMetrics {
+    fn default() -> Self {
+        Self::new(1, 1, 1.0)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct VideoMode {
+    pub width: u32,
+    pub height: u32,
+    pub bit_depth: u16,
+    pub refresh_rate_millihertz: u32,
+}
+
+impl VideoMode {
+    fn from_handle(handle: &VideoModeHandle) -> Self {
+        let size = handle.size();
+        Self {
+            width: size.width,
+            height: size.height,
+            bit_depth: handle.bit_depth(),
+            refresh_rate_millihertz: handle.refresh_rate_millihertz(),
+        }
+    }
+
+    pub fn refresh_rate_hz(&self) -> f32 {
+        self.refresh_rate_millihertz as f32 / 1000.0
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MonitorInfo {
+    // Position in `WindowManager::monitors`, used to pick a monitor for fullscreen
+    pub index: usize,
+    pub name: Option<String>,
+    pub primary: bool,
+    pub width: u32,
+    pub height: u32,
+    // Top-left corner on the virtual desktop, in physical pixels
+    pub position: (i32, i32),
+    pub scale_factor: f64,
+    pub refresh_rate_millihertz: Option<u32>,
+    // Modes usable with `FullscreenMode::Exclusive`, largest and fastest first
+    pub video_modes: Vec<VideoMode>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FullscreenMode {
+    Windowed,
+    // Desktop-resolution fullscreen window; `None` uses the window's current monitor
+    Borderless { monitor: Option<usize> },
+    // Switches the monitor to `mode`, which must come from its `video_modes`
+    Exclusive { monitor: usize, mode: VideoMode },
+}
 
 pub struct WindowManager {
     pub window: Option<Arc<Window>>,
@@ -18,7 +112,8 @@ impl WindowManager {
     pub fn create_window(&mut self, event_loop: &ActiveEventLoop) -> Result<(), winit::error::OsError> {
         let window_attributes = WindowAttributes::default()
             .with_title("VellumEngine")
-            .with_inner_size(winit::dpi::PhysicalSize::new(800, 600));
+            // Logical, so the window opens at a sensible size on HiDPI displays too
+            .with_inner_size(winit::dpi::LogicalSize::new(800, 600));
         let window = Arc::new(event_loop.create_window(window_attributes)?);
         self.window = Some(window);
         Ok(())
@@ -29,18 +124,86 @@ impl WindowManager {
             WindowEvent::CloseRequested => {
                 event_loop.exit();
             }
-            WindowEvent::Resized(size) => {
+            // The new physical size arrives in a `Resized` event right after
+            WindowEvent::Resized(_) | WindowEvent::ScaleFactorChanged { .. } => {
                 if let Some(window) = &self.window {
                     window.request_redraw();
                 }
             }
-            WindowEvent::RedrawRequested => {
-                if let Some(window) = &self.window {
-                    window.request_redraw();
+            // Frames are drawn from `about_to_wait` on the frame pacer's schedule; asking
+            // for another redraw here would keep the event loop from ever sleeping
+            WindowEvent::RedrawRequested => {}
+            _ => {}
+        }
+    }
+
+    pub fn metrics(&self) -> Option<DisplayMetrics> {
+        self.window.as_deref().map(DisplayMetrics::from_window)
+    }
+
+    pub fn monitors(&self, event_loop: &ActiveEventLoop) -> Vec<MonitorInfo> {
+        let primary = event_loop.primary_monitor();
+        event_loop
+            .available_monitors()
+            .enumerate()
+            .map(|(index, monitor)| {
+                let size = monitor.size();
+                let position = monitor.position();
+                let mut video_modes: Vec<VideoMode> = monitor.video_modes().map(|mode| VideoMode::from_handle(&mode)).collect();
+                video_modes.sort_by_key(|mode| {
+                    std::cmp::Reverse((mode.width * mode.height, mode.refresh_rate_millihertz, mode.bit_depth))
+                });
+                video_modes.dedup();
+                MonitorInfo {
+                    index,
+                    name: monitor.name(),
+                    primary: primary.as_ref() == Some(&monitor),
+                    width: size.width,
+                    height: size.height,
+                    position: (position.x, position.y),
+                    scale_factor: monitor.scale_factor(),
+                    refresh_rate_millihertz: monitor.refresh_rate_millihertz(),
+                    video_modes,
                 }
+            })
+            .collect()
+    }
+
+    fn monitor(event_loop: &ActiveEventLoop, index: usize) -> Result<MonitorHandle, String> {
+        event_loop.available_monitors().nth(index).ok_or_else(|| format!("No monitor with index {}", index))
+    }
+
+    pub fn set_fullscreen(&self, event_loop: &ActiveEventLoop, mode: FullscreenMode) -> Result<(), String> {
+        let Some(window) = &self.window else {
+            return Err("Window is not created".to_string());
+        };
+        let fullscreen = match mode {
+            FullscreenMode::Windowed => None,
+            FullscreenMode::Borderless { monitor: None } => Some(Fullscreen::Borderless(None)),
+            FullscreenMode::Borderless { monitor: Some(index) } => {
+                Some(Fullscreen::Borderless(Some(Self::monitor(event_loop, index)?)))
             }
-            _ => {}
+            FullscreenMode::Exclusive { monitor, mode } => {
+                let handle = Self::monitor(event_loop, monitor)?
+                    .video_modes()
+                    .find(|handle| VideoMode::from_handle(handle) == mode)
+                    .ok_or_else(|| format!("Monitor {} has no {}x{} @ {:.2} Hz mode", monitor, mode.width, mode.height, mode.refresh_rate_hz()))?;
+                Some(Fullscreen::Exclusive(handle))
+            }
+        };
+        window.set_fullscreen(fullscreen);
+        Ok(())
+    }
+
+    // Of the monitor the window is on, in Hz; `None` when the platform doesn't report it.
+    // In exclusive fullscreen this is the selected mode's rate.
+    pub fn refresh_rate_hz(&self) -> Option<f32> {
+        let window = self.window.as_ref()?;
+        if let Some(Fullscreen::Exclusive(mode)) = window.fullscreen() {
+            return Some(mode.refresh_rate_millihertz() as f32 / 1000.0);
         }
+        let millihertz = window.current_monitor()?.refresh_rate_millihertz()?;
+        Some(millihertz as f32 / 1000.0)
     }
 
     pub fn request_redraw(&self) {
@@ -48,4 +211,10 @@ impl WindowManager {
             window.request_redraw();
         }
     }
-}
\ No newline at end of file
+}
+
+impl Default for WindowManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
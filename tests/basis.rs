@@ -0,0 +1,49 @@
+// tests/basis.rs
+// Basis Universal decoding against the fixtures `fixtures/basis_fixtures.py` writes, and the
+// limits that keep a tiny hostile file from allocating gigabytes
+use vellum::texture::ktx2::Ktx2Image;
+
+const ETC1S: &[u8] = include_bytes!("fixtures/etc1s.ktx2");
+const ETC1S_RGBA: &[u8] = include_bytes!("fixtures/etc1s.rgba");
+const UASTC: &[u8] = include_bytes!("fixtures/uastc.ktx2");
+const UASTC_RGBA: &[u8] = include_bytes!("fixtures/uastc.rgba");
+
+fn decoded(data: &[u8]) -> Vec<u8> {
+    let image = Ktx2Image::parse(data).unwrap();
+    assert!(image.basis);
+    assert_eq!(image.format, wgpu::TextureFormat::Rgba8Unorm);
+    image.levels.concat()
+}
+
+fn with_u32(data: &[u8], offset: usize, value: u32) -> Vec<u8> {
+    let mut data = data.to_vec();
+    data[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+    data
+}
+
+#[test]
+fn etc1s_matches_fixture() {
+    assert_eq!(decoded(ETC1S), ETC1S_RGBA);
+}
+
+#[test]
+fn uastc_matches_fixture() {
+    assert_eq!(decoded(UASTC), UASTC_RGBA);
+}
+
+#[test]
+fn rejects_more_levels_than_a_full_mip_chain() {
+    // 12x8 has four levels, down to 1x1
+    assert!(Ktx2Image::parse(&with_u32(UASTC, 40, 5)).is_err());
+    assert!(Ktx2Image::parse(&with_u32(UASTC, 40, u32::MAX)).is_err());
+}
+
+#[test]
+fn rejects_huge_basis_images_before_decoding() {
+    let wide = with_u32(&with_u32(UASTC, 20, 1 << 20), 24, 1 << 20);
+    assert!(Ktx2Image::parse(&wide).is_err());
+    let layers = with_u32(UASTC, 32, u32::MAX);
+    assert!(Ktx2Image::parse(&layers).is_err());
+    let faces = with_u32(&with_u32(ETC1S, 32, u32::MAX), 36, 6);
+    assert!(Ktx2Image::parse(&faces).is_err());
+}
@@ -0,0 +1,369 @@
+# tests/fixtures/basis_fixtures.py
+# Writes the Basis Universal fixtures `tests/basis.rs` decodes: etc1s.ktx2 and uastc.ktx2,
+# each with the RGBA8 pixels of every level, level 0 first, in a matching .rgba file.
+# The files are encoded straight from the KTX2 and Basis Universal specifications, and the
+# expected pixels are worked out here without the engine's decoder.
+#
+#   python3 tests/fixtures/basis_fixtures.py
+import os
+import random
+import struct
+
+HERE = os.path.dirname(os.path.abspath(__file__))
+IDENTIFIER = bytes([0xAB, 0x4B, 0x54, 0x58, 0x20, 0x32, 0x30, 0xBB, 0x0D, 0x0A, 0x1A, 0x0A])
+MODEL_ETC1S, MODEL_UASTC = 163, 166
+TRANSFER_LINEAR = 1
+SUPERCOMPRESSION_NONE, SUPERCOMPRESSION_BASIS_LZ = 0, 1
+
+
+class Bits:
+    # LSB-first bit writer; Huffman codes go in most significant bit first
+    def __init__(self):
+        self.bits = []
+
+    def put(self, value, count):
+        for i in range(count):
+            self.bits.append((value >> i) & 1)
+
+    def code(self, code, length):
+        for i in range(length - 1, -1, -1):
+            self.bits.append((code >> i) & 1)
+
+    def vlc(self, value, chunk_bits):
+        while True:
+            chunk = value & ((1 << chunk_bits) - 1)
+            value >>= chunk_bits
+            self.put(chunk | ((1 if value else 0) << chunk_bits), chunk_bits + 1)
+            if not value:
+                break
+
+    def bytes(self):
+        out = bytearray((len(self.bits) + 7) // 8)
+        for i, bit in enumerate(self.bits):
+            out[i // 8] |= bit << (i % 8)
+        return bytes(out)
+
+
+def ktx2(width, height, model, supercompression, levels, global_data=b""):
+    dfd = struct.pack("<I", 28) + struct.pack("<II", 0, 2 | (24 << 16))
+    dfd += bytes([model, 1, TRANSFER_LINEAR, 0]) + bytes(12)
+    index_end = 80 + 24 * len(levels)
+    dfd_offset = index_end
+    global_offset = dfd_offset + len(dfd)
+    level_offset = global_offset + len(global_data)
+    header = IDENTIFIER + struct.pack("<9I", 0, 1, width, height, 0, 0, 1, len(levels), supercompression)
+    header += struct.pack("<IIII", dfd_offset, len(dfd), 0, 0)
+    header += struct.pack("<QQ", global_offset if global_data else 0, len(global_data))
+    index, body = b"", b""
+    for level in levels:
+        uncompressed = len(level) if supercompression == SUPERCOMPRESSION_NONE else 0
+        index += struct.pack("<QQQ", level_offset + len(body), len(level), uncompressed)
+        body += level
+    return header + index + dfd + global_data + body
+
+
+def write(name, data, expected):
+    with open(os.path.join(HERE, name + ".ktx2"), "wb") as f:
+        f.write(data)
+    with open(os.path.join(HERE, name + ".rgba"), "wb") as f:
+        f.write(expected)
+
+
+# ETC1S under BasisLZ: random codebooks, every endpoint prediction and selector history
+# path, an alpha slice on level 0 and levels down to 1x1
+
+ETC1_MODIFIERS = [
+    [-8, -2, 2, 8],
+    [-17, -5, 5, 17],
+    [-29, -9, 9, 29],
+    [-42, -13, 13, 42],
+    [-60, -18, 18, 60],
+    [-80, -24, 24, 80],
+    [-106, -33, 33, 106],
+    [-183, -47, 47, 183],
+]
+CODE_LENGTH_ORDER = [17, 18, 19, 20, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15, 16]
+
+
+class Table:
+    # Huffman table giving every one of `count` symbols a code of the same length
+    def __init__(self, count):
+        self.count = count
+        self.length = max(1, (count - 1).bit_length())
+
+    def write(self, bits):
+        bits.put(self.count, 14)
+        bits.put(21, 5)
+        for symbol in CODE_LENGTH_ORDER:
+            bits.put(5 if symbol <= 16 else 0, 3)
+        for _ in range(self.count):
+            bits.code(self.length, 5)
+
+    def emit(self, bits, symbol):
+        bits.code(symbol, self.length)
+
+
+class History:
+    def __init__(self, size):
+        self.values = [0] * size
+        self.rover = size // 2
+
+    def add(self, value):
+        self.values[self.rover] = value
+        self.rover += 1
+        if self.rover == len(self.values):
+            self.rover = len(self.values) // 2
+
+    def use(self, index):
+        if index:
+            self.values[index // 2], self.values[index] = self.values[index], self.values[index // 2]
+
+
+def etc1s():
+    rng = random.Random(7)
+    endpoint_count, selector_count, history_size = 40, 30, 8
+    endpoints = [([rng.randrange(32) for _ in range(3)], rng.randrange(8)) for _ in range(endpoint_count)]
+    selectors = [[rng.randrange(256) for _ in range(4)] for _ in range(selector_count)]
+
+    endpoint_bits = Bits()
+    deltas = [Table(32), Table(32), Table(32)]
+    intensities = Table(8)
+    for table in deltas + [intensities]:
+        table.write(endpoint_bits)
+    endpoint_bits.put(0, 1)
+    previous, previous_intensity = [16, 16, 16], 0
+    for color, intensity in endpoints:
+        intensities.emit(endpoint_bits, (intensity - previous_intensity) & 7)
+        previous_intensity = intensity
+        for c in range(3):
+            table = deltas[0] if previous[c] <= 9 else deltas[1] if previous[c] <= 21 else deltas[2]
+            table.emit(endpoint_bits, (color[c] - previous[c]) & 31)
+            previous[c] = color[c]
+
+    selector_bits = Bits()
+    selector_bits.put(0, 1)
+    selector_bits.put(0, 1)
+    selector_bits.put(1, 1)
+    for rows in selectors:
+        for row in rows:
+            selector_bits.put(row, 8)
+
+    predictions = Table(257)
+    endpoint_deltas = Table(endpoint_count)
+    selector_symbols = Table(selector_count + history_size + 1)
+    runs = Table(64)
+    table_bits = Bits()
+    for table in (predictions, endpoint_deltas, selector_symbols, runs):
+        table.write(table_bits)
+    table_bits.put(history_size, 13)
+
+    def valid(prediction, x, y):
+        return prediction == 3 or (prediction == 0 and x > 0) or (prediction == 1 and y > 0) or (
+            prediction == 2 and x > 0 and y > 0
+        )
+
+    def slice_bits(width, height, alpha, out):
+        blocks_x, blocks_y = (width + 3) // 4, (height + 3) // 4
+        bits = Bits()
+        groups = []
+        for y in range(0, blocks_y, 2):
+            for x in range(0, blocks_x, 2):
+                corners = [(x, y), (x + 1, y), (x, y + 1), (x + 1, y + 1)]
+                if (
+                    groups
+                    and rng.random() < 0.6
+                    and all(valid((groups[-1] >> (2 * i)) & 3, *corners[i]) for i in range(4))
+                ):
+                    groups.append(groups[-1])
+                    continue
+                packed = 0
+                for i, (cx, cy) in enumerate(corners):
+                    while True:
+                        prediction = rng.randrange(4)
+                        if valid(prediction, cx, cy):
+                            break
+                    packed |= prediction << (2 * i)
+                groups.append(packed)
+
+        # Runs of three or more repeated groups are sent as one repeat symbol
+        plan, i, last = {}, 0, None
+        while i < len(groups):
+            run = 0
+            while i + run < len(groups) and groups[i + run] == last:
+                run += 1
+            if run >= 3:
+                plan[i] = ("repeat", run)
+                for k in range(1, run):
+                    plan[i + k] = ("none",)
+                i += run
+            else:
+                plan[i] = ("literal", groups[i])
+                last = groups[i]
+                i += 1
+
+        group, history, run = 0, History(history_size), 0
+        above, previous_endpoint, lower = [0] * blocks_x, 0, {}
+        for y in range(blocks_y):
+            row, pending = [0] * blocks_x, 0
+            for x in range(blocks_x):
+                if x % 2 == 0:
+                    if y % 2 == 0:
+                        step = plan[group]
+                        if step[0] == "literal":
+                            predictions.emit(bits, step[1])
+                        elif step[0] == "repeat":
+                            predictions.emit(bits, 256)
+                            bits.vlc(step[1] - 3, 4)
+                        pending = groups[group]
+                        lower[x // 2] = pending >> 4
+                        group += 1
+                    else:
+                        pending = lower[x // 2]
+                prediction = pending & 3
+                pending >>= 2
+                if prediction == 0:
+                    endpoint = previous_endpoint
+                elif prediction == 1:
+                    endpoint = above[x]
+                elif prediction == 2:
+                    endpoint = above[x - 1]
+                else:
+                    delta = rng.randrange(endpoint_count)
+                    endpoint_deltas.emit(bits, delta)
+                    endpoint = (previous_endpoint + delta) % endpoint_count
+                previous_endpoint = endpoint
+                row[x] = endpoint
+
+                if run > 0:
+                    run -= 1
+                    selector = history.values[0]
+                else:
+                    roll = rng.random()
+                    if roll < 0.4:
+                        selector = rng.randrange(selector_count)
+                        selector_symbols.emit(bits, selector)
+                        history.add(selector)
+                    elif roll < 0.8:
+                        index = rng.randrange(history_size)
+                        selector_symbols.emit(bits, selector_count + index)
+                        selector = history.values[index]
+                        history.use(index)
+                    else:
+                        count = rng.choice([3, 4, 10, 65, 66, 90])
+                        selector_symbols.emit(bits, selector_count + history_size)
+                        if count - 3 < 63:
+                            runs.emit(bits, count - 3)
+                        else:
+                            runs.emit(bits, 63)
+                            bits.vlc(count - 3, 7)
+                        selector = history.values[0]
+                        run = count - 1
+
+                color, intensity = endpoints[endpoint]
+                rows = selectors[selector]
+                for texel in range(16):
+                    px, py = x * 4 + texel % 4, y * 4 + texel // 4
+                    if px >= width or py >= height:
+                        continue
+                    modifier = ETC1_MODIFIERS[intensity][(rows[texel // 4] >> (2 * (texel % 4))) & 3]
+
+                    def channel(c):
+                        return max(0, min(255, ((c << 3) | (c >> 2)) + modifier))
+
+                    offset = (py * width + px) * 4
+                    if alpha:
+                        out[offset + 3] = channel(color[1])
+                    else:
+                        out[offset : offset + 4] = bytes([channel(color[0]), channel(color[1]), channel(color[2]), 255])
+            above = row
+        return bits.bytes()
+
+    width, height = 24, 16
+    shapes = [(width, height), (12, 8), (6, 4), (3, 2), (1, 1)]
+    levels, descs, expected = [], [], b""
+    for level, (w, h) in enumerate(shapes):
+        out = bytearray(w * h * 4)
+        rgb = slice_bits(w, h, False, out)
+        alpha = slice_bits(w, h, True, out) if level == 0 else b""
+        descs.append((0, 0, len(rgb), len(rgb), len(alpha)))
+        levels.append(rgb + alpha)
+        expected += bytes(out)
+    endpoint_data, selector_data, table_data = endpoint_bits.bytes(), selector_bits.bytes(), table_bits.bytes()
+    global_data = struct.pack(
+        "<HHIIII", endpoint_count, selector_count, len(endpoint_data), len(selector_data), len(table_data), 0
+    )
+    global_data += b"".join(struct.pack("<5I", *desc) for desc in descs)
+    global_data += endpoint_data + selector_data + table_data
+    write("etc1s", ktx2(width, height, MODEL_ETC1S, SUPERCOMPRESSION_BASIS_LZ, levels, global_data), expected)
+
+
+# UASTC: solid colour (mode 8) blocks and single-subset RGB blocks with 8-bit endpoints and
+# 2-bit weights (mode 1), whose interpolation follows ASTC's LDR decode
+
+SOLID_CODE, SOLID_CODE_BITS = 0x17, 5
+RGB_CODE, RGB_CODE_BITS, RGB_HINT_BITS = 0x35, 6, 15
+# ASTC's 2-bit weights unquantised to 0..=64
+WEIGHTS_2_BIT = [0, 21, 43, 64]
+
+
+def solid_block(color):
+    bits = Bits()
+    bits.put(SOLID_CODE, SOLID_CODE_BITS)
+    for channel in color:
+        bits.put(channel, 8)
+    bits.put(0, 128 - len(bits.bits))
+    return bits.bytes(), [list(color)] * 16
+
+
+def rgb_block(rng):
+    low = [rng.randrange(256) for _ in range(3)]
+    high = [rng.randrange(256) for _ in range(3)]
+    # The first weight is stored without its top bit, so it must be 0 or 1
+    weights = [rng.randrange(2)] + [rng.randrange(4) for _ in range(15)]
+    bits = Bits()
+    bits.put(RGB_CODE, RGB_CODE_BITS)
+    bits.put(0, RGB_HINT_BITS)
+    for c in range(3):
+        bits.put(low[c], 8)
+        bits.put(high[c], 8)
+    bits.put(weights[0], 1)
+    for weight in weights[1:]:
+        bits.put(weight, 2)
+    bits.put(0, 128 - len(bits.bits))
+
+    texels = []
+    for weight in weights:
+        w = WEIGHTS_2_BIT[weight]
+        # Endpoints widen to 16 bits, and the top 8 bits of the blend are the result
+        texel = [((low[c] * 257 * (64 - w) + high[c] * 257 * w + 32) >> 6) >> 8 for c in range(3)]
+        texels.append(texel + [255])
+    return bits.bytes(), texels
+
+
+def uastc():
+    rng = random.Random(11)
+    width, height = 12, 8
+    levels, expected = [], b""
+    for level in range(4):
+        w, h = max(1, width >> level), max(1, height >> level)
+        blocks_x, blocks_y = (w + 3) // 4, (h + 3) // 4
+        data, out = b"", bytearray(w * h * 4)
+        for by in range(blocks_y):
+            for bx in range(blocks_x):
+                if (bx + by + level) % 3 == 0:
+                    block, texels = solid_block([rng.randrange(256) for _ in range(4)])
+                else:
+                    block, texels = rgb_block(rng)
+                data += block
+                for texel in range(16):
+                    px, py = bx * 4 + texel % 4, by * 4 + texel // 4
+                    if px < w and py < h:
+                        offset = (py * w + px) * 4
+                        out[offset : offset + 4] = bytes(texels[texel])
+        levels.append(data)
+        expected += bytes(out)
+    write("uastc", ktx2(width, height, MODEL_UASTC, SUPERCOMPRESSION_NONE, levels), expected)
+
+
+etc1s()
+uastc()